@@ -0,0 +1,27 @@
+import Controller from '@ember/controller';
+import { tracked } from '@glimmer/tracking';
+
+import { task } from 'ember-concurrency';
+
+import ajax from '../utils/ajax';
+
+export default class AlertYankController extends Controller {
+  @tracked yanked = false;
+  @tracked errorText = null;
+
+  // The version is only yanked once the owner confirms it, so that email
+  // clients that open the link in advance don't yank it.
+  yankTask = task(async () => {
+    let { crateName, versionNum, token } = this.model;
+    let url = `/api/v1/crates/${crateName}/${versionNum}/alert_yank/${token}`;
+
+    try {
+      await ajax(url, { method: 'PUT', body: '{}' });
+      this.yanked = true;
+      this.errorText = null;
+    } catch (error) {
+      let json = await error.json?.();
+      this.errorText = json?.errors?.[0]?.detail ?? 'The version could not be yanked. Please try again.';
+    }
+  });
+}
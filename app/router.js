@@ -56,6 +56,7 @@ Router.map(function () {
   this.route('data-access');
   this.route('confirm', { path: '/confirm/:email_token' });
   this.route('accept-invite', { path: '/accept-invite/:token' });
+  this.route('alert-yank', { path: '/crates/:crate_id/:version_num/alert-yank/:token' });
 
   this.route('catch-all', { path: '*path' });
 });
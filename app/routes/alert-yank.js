@@ -0,0 +1,12 @@
+import Route from '@ember/routing/route';
+
+export default class AlertYankRoute extends Route {
+  model(params) {
+    return { crateName: params.crate_id, versionNum: params.version_num, token: params.token };
+  }
+
+  resetController(controller) {
+    controller.yanked = false;
+    controller.errorText = null;
+  }
+}
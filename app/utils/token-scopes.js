@@ -4,6 +4,7 @@ const DESCRIPTIONS = {
   'change-owners': 'Invite new crate owners or remove existing ones',
   'publish-new': 'Publish new crates',
   'publish-update': 'Publish new versions of existing crates',
+  read: 'Read crates and the index of a private registry',
   yank: 'Yank and unyank crate versions',
 };
 
@@ -4,12 +4,70 @@ use ammonia::{Builder, UrlRelative, UrlRelativeEvaluate};
 use comrak::nodes::{AstNode, NodeValue};
 use htmlescape::encode_minimal;
 use std::borrow::Cow;
+use std::fmt;
 use std::path::Path;
+use std::sync::Arc;
 use url::Url;
 
+/// Tags whose content is always removed, so they can't be allowed.
+const CLEAN_CONTENT_TAGS: [&str; 2] = ["script", "style"];
+
+/// Attributes that are managed by the renderer itself, so they can't be allowed.
+const MANAGED_ATTRIBUTES: [&str; 2] = ["class", "rel"];
+
+/// Rewrites the absolute URL of an image, e.g. to serve it through a proxy.
+pub type ImageUrlRewriter = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Adjustments to the built-in sanitization of rendered Markdown, e.g. for
+/// private deployments that want stricter or looser rules.
+///
+/// The default policy doesn't change anything.
+#[derive(Clone)]
+pub struct SanitizationPolicy {
+    /// Tags that are allowed in addition to the built-in ones. `script` and
+    /// `style` are always removed.
+    pub extra_tags: Vec<String>,
+    /// Built-in tags that are removed, keeping their content.
+    pub denied_tags: Vec<String>,
+    /// Attributes that are allowed on all tags in addition to the built-in
+    /// ones. `class` and `rel` can't be allowed.
+    pub extra_attributes: Vec<String>,
+    /// Whether raw HTML in Markdown files is rendered at all. Otherwise it is
+    /// omitted from the output.
+    pub raw_html: bool,
+    /// Rewrites the URLs of all `http` and `https` images, after relative
+    /// URLs have been resolved.
+    pub image_url_rewriter: Option<ImageUrlRewriter>,
+}
+
+impl Default for SanitizationPolicy {
+    fn default() -> Self {
+        Self {
+            extra_tags: vec![],
+            denied_tags: vec![],
+            extra_attributes: vec![],
+            raw_html: true,
+            image_url_rewriter: None,
+        }
+    }
+}
+
+impl fmt::Debug for SanitizationPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SanitizationPolicy")
+            .field("extra_tags", &self.extra_tags)
+            .field("denied_tags", &self.denied_tags)
+            .field("extra_attributes", &self.extra_attributes)
+            .field("raw_html", &self.raw_html)
+            .field("image_url_rewriter", &self.image_url_rewriter.is_some())
+            .finish()
+    }
+}
+
 /// Context for markdown to HTML rendering.
 struct MarkdownRenderer<'a> {
     html_sanitizer: Builder<'a>,
+    raw_html: bool,
 }
 
 impl<'a> MarkdownRenderer<'a> {
@@ -17,7 +75,11 @@ impl<'a> MarkdownRenderer<'a> {
     ///
     /// Per `text_to_html`, `base_url` is the base URL prepended to any
     /// relative links in the input document.  See that function for more detail.
-    fn new(base_url: Option<&'a str>, base_dir: &'a str) -> MarkdownRenderer<'a> {
+    fn new(
+        base_url: Option<&'a str>,
+        base_dir: &'a str,
+        policy: &'a SanitizationPolicy,
+    ) -> MarkdownRenderer<'a> {
         let allowed_classes = hashmap(&[(
             "code",
             hashset(&[
@@ -51,7 +113,49 @@ impl<'a> MarkdownRenderer<'a> {
             .allowed_classes(allowed_classes)
             .url_relative(sanitize_url)
             .id_prefix(Some("user-content-"));
-        MarkdownRenderer { html_sanitizer }
+
+        html_sanitizer
+            .add_tags(
+                policy
+                    .extra_tags
+                    .iter()
+                    .filter(|tag| !CLEAN_CONTENT_TAGS.contains(&tag.as_str())),
+            )
+            .rm_tags(&policy.denied_tags)
+            .add_generic_attributes(
+                policy
+                    .extra_attributes
+                    .iter()
+                    .filter(|attribute| !MANAGED_ATTRIBUTES.contains(&attribute.as_str())),
+            );
+
+        // Attributes are filtered before relative URLs are resolved, so images
+        // with relative URLs are resolved here as well before rewriting them
+        if let Some(rewrite) = policy.image_url_rewriter.clone() {
+            let resolver = SanitizeUrl::new(base_url, base_dir);
+            html_sanitizer.attribute_filter(move |element, attribute, value| {
+                if element != "img" || attribute != "src" {
+                    return Some(Cow::Borrowed(value));
+                }
+
+                let url = match Url::parse(value) {
+                    Ok(_) => Cow::Borrowed(value),
+                    Err(_) => resolver.evaluate(value)?,
+                };
+
+                match Url::parse(&url) {
+                    Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {
+                        Some(Cow::Owned(rewrite(&url)))
+                    }
+                    _ => Some(Cow::Borrowed(value)),
+                }
+            });
+        }
+
+        MarkdownRenderer {
+            html_sanitizer,
+            raw_html: policy.raw_html,
+        }
     }
 
     /// Renders the given markdown to HTML using the current settings.
@@ -63,7 +167,7 @@ impl<'a> MarkdownRenderer<'a> {
 
         let options = ComrakOptions {
             render: ComrakRenderOptions {
-                unsafe_: true, // The output will be sanitized with `ammonia`
+                unsafe_: self.raw_html, // The output will be sanitized with `ammonia`
                 ..ComrakRenderOptions::default()
             },
             extension: ComrakExtensionOptions {
@@ -223,8 +327,13 @@ impl UrlRelativeEvaluate for SanitizeUrl {
 
 /// Renders Markdown text to sanitized HTML with a given `base_url`.
 /// See `text_to_html` for the interpretation of `base_url`.
-fn markdown_to_html(text: &str, base_url: Option<&str>, base_dir: &str) -> String {
-    let renderer = MarkdownRenderer::new(base_url, base_dir);
+fn markdown_to_html(
+    text: &str,
+    base_url: Option<&str>,
+    base_dir: &str,
+    policy: &SanitizationPolicy,
+) -> String {
+    let renderer = MarkdownRenderer::new(base_url, base_dir, policy);
     renderer.to_html(text)
 }
 
@@ -258,17 +367,30 @@ pub fn text_to_html(
     readme_path_in_pkg: &str,
     base_url: Option<&str>,
     pkg_path_in_vcs: Option<&str>,
+) -> String {
+    let policy = SanitizationPolicy::default();
+    text_to_html_with_policy(text, readme_path_in_pkg, base_url, pkg_path_in_vcs, &policy)
+}
+
+/// Renders a text file to sanitized HTML, just like [`text_to_html`], but
+/// with adjustments to the sanitization of Markdown files.
+pub fn text_to_html_with_policy(
+    text: &str,
+    readme_path_in_pkg: &str,
+    base_url: Option<&str>,
+    pkg_path_in_vcs: Option<&str>,
+    policy: &SanitizationPolicy,
 ) -> String {
     let path_in_vcs = Path::new(pkg_path_in_vcs.unwrap_or("")).join(readme_path_in_pkg);
     let base_dir = path_in_vcs.parent().and_then(|p| p.to_str()).unwrap_or("");
 
     if path_in_vcs.extension().is_none() {
-        return markdown_to_html(text, base_url, base_dir);
+        return markdown_to_html(text, base_url, base_dir, policy);
     }
 
     if let Some(ext) = path_in_vcs.extension().and_then(|ext| ext.to_str()) {
         if MARKDOWN_EXTENSIONS.contains(&ext.to_lowercase().as_str()) {
-            return markdown_to_html(text, base_url, base_dir);
+            return markdown_to_html(text, base_url, base_dir, policy);
         }
     }
 
@@ -296,6 +418,10 @@ where
 mod tests {
     use super::*;
 
+    fn markdown_to_html(text: &str, base_url: Option<&str>, base_dir: &str) -> String {
+        super::markdown_to_html(text, base_url, base_dir, &SanitizationPolicy::default())
+    }
+
     #[test]
     fn empty_text() {
         let text = "";
@@ -604,4 +730,54 @@ mod tests {
             "<p align=\"center\"><img src=\"https://img.shields.io/crates/v/clap.svg\" alt=\"\"></p>\n"
         );
     }
+
+    #[test]
+    fn policy_with_extra_and_denied_tags() {
+        let policy = SanitizationPolicy {
+            extra_tags: vec!["video".into(), "script".into()],
+            denied_tags: vec!["img".into()],
+            extra_attributes: vec!["title".into(), "class".into()],
+            ..SanitizationPolicy::default()
+        };
+
+        let text = "<video title=\"demo\" class=\"big\"></video>\n\n\
+                    <img src=\"https://example.com/a.png\">\n\n\
+                    <script>alert(1)</script>\n";
+        let result = super::markdown_to_html(text, None, "", &policy);
+        assert!(result.contains("<video title=\"demo\"></video>"));
+        assert!(!result.contains("<img"));
+        assert!(!result.contains("<script"));
+    }
+
+    #[test]
+    fn policy_without_raw_html() {
+        let policy = SanitizationPolicy {
+            raw_html: false,
+            ..SanitizationPolicy::default()
+        };
+
+        let text = "<h1 align=\"center\">foo-bar</h1>\n\n*lobster*\n";
+        let result = super::markdown_to_html(text, None, "", &policy);
+        assert!(!result.contains("<h1"));
+        assert!(!result.contains("foo-bar"));
+        assert!(result.contains("<p><em>lobster</em></p>"));
+    }
+
+    #[test]
+    fn policy_with_image_url_rewriter() {
+        let policy = SanitizationPolicy {
+            image_url_rewriter: Some(Arc::new(|url| format!("https://proxy.example.com/{url}"))),
+            ..SanitizationPolicy::default()
+        };
+
+        let text = "![absolute](https://example.com/a.png) ![relative](b.png) [link](https://example.com/)\n";
+        let result =
+            super::markdown_to_html(text, Some("https://github.com/rust-lang/test"), "", &policy);
+        assert_eq!(
+            result,
+            "<p><img src=\"https://proxy.example.com/https://example.com/a.png\" alt=\"absolute\"> \
+             <img src=\"https://proxy.example.com/https://github.com/rust-lang/test/raw/HEAD/b.png\" alt=\"relative\"> \
+             <a href=\"https://example.com/\" rel=\"nofollow noopener noreferrer\">link</a></p>\n"
+        );
+    }
 }
@@ -116,14 +116,31 @@ impl Bucket {
             headers = "",
             name = self.name,
         );
-        let signature = {
-            let key = self.secret_key.expose_secret().as_bytes();
-            let mut h = Hmac::<Sha1>::new_from_slice(key).expect("HMAC can take key of any size");
-            h.update(string.as_bytes());
-            let res = h.finalize().into_bytes();
-            general_purpose::STANDARD.encode(res)
-        };
-        format!("AWS {}:{}", self.access_key, signature)
+        format!("AWS {}:{}", self.access_key, self.sign(&string))
+    }
+
+    /// Returns a URL that allows downloading the file at `path` without any
+    /// further authentication, until the `expires` Unix timestamp.
+    pub fn presigned_url(&self, path: &str, expires: i64) -> Result<String, Error> {
+        let path = path.strip_prefix('/').unwrap_or(path);
+        let string = format!("GET\n\n\n{expires}\n/{name}/{path}", name = self.name);
+        let signature = self.sign(&string);
+
+        let mut url = self.region.request_url(&self.proto, &self.name, path)?;
+        url.query_pairs_mut()
+            .append_pair("AWSAccessKeyId", &self.access_key)
+            .append_pair("Expires", &expires.to_string())
+            .append_pair("Signature", &signature);
+
+        Ok(url.into())
+    }
+
+    fn sign(&self, string: &str) -> String {
+        let key = self.secret_key.expose_secret().as_bytes();
+        let mut h = Hmac::<Sha1>::new_from_slice(key).expect("HMAC can take key of any size");
+        h.update(string.as_bytes());
+        let res = h.finalize().into_bytes();
+        general_purpose::STANDARD.encode(res)
     }
 
     pub fn url(&self, path: &str) -> Result<String, Error> {
@@ -162,6 +179,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn presigned_url() -> Result<(), Error> {
+        let bucket = Bucket::new(
+            "buckey".into(),
+            region("us-west-2"),
+            "access".into(),
+            "secret".to_string(),
+            "https",
+        );
+
+        assert_eq!(
+            bucket.presigned_url("/foo/bar", 1700000000)?,
+            "https://buckey.s3-us-west-2.amazonaws.com/foo/bar\
+             ?AWSAccessKeyId=access&Expires=1700000000&Signature=7M4vhA9WMy5UQoK423B%2Fn%2Fs0T%2FM%3D"
+        );
+
+        Ok(())
+    }
+
     fn bucket(name: &str, region: Region, proto: &str) -> Bucket {
         Bucket::new(name.into(), region, "".into(), "".to_string(), proto)
     }
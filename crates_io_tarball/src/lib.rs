@@ -5,9 +5,10 @@ extern crate claims;
 #[cfg(any(feature = "builder", test))]
 pub use crate::builder::TarballBuilder;
 use crate::limit_reader::LimitErrorReader;
-pub use crate::manifest::Manifest;
+pub use crate::manifest::{BuildScript, Manifest};
 pub use crate::vcs_info::CargoVcsInfo;
 use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256};
 use std::io::Read;
 use std::path::Path;
 use tracing::instrument;
@@ -18,10 +19,43 @@ mod limit_reader;
 mod manifest;
 mod vcs_info;
 
+/// The number of bytes at the start of a file that are checked for NUL bytes
+/// to decide whether it is a binary file, similar to the heuristic of git.
+const BINARY_DETECTION_LENGTH: u64 = 8000;
+
+/// Files that contain the name or version of the crate, and are thus left out
+/// of the content hash.
+const CONTENT_HASH_IGNORED_FILES: &[&str] = &[
+    "Cargo.toml",
+    "Cargo.toml.orig",
+    "Cargo.lock",
+    ".cargo_vcs_info.json",
+];
+
 #[derive(Debug)]
 pub struct TarballInfo {
     pub manifest: Option<Manifest>,
     pub vcs_info: Option<CargoVcsInfo>,
+    /// Whether the crate has a build script, either the default `build.rs` or
+    /// the one configured in the manifest.
+    pub has_build_script: bool,
+    /// Files that appear to contain binary data instead of text.
+    pub binary_files: Vec<BinaryFile>,
+    /// SHA-256 hash of the paths and contents of all files, except the ones
+    /// that contain the name or version of the crate. `None` if there are no
+    /// other files.
+    ///
+    /// The hash does not depend on the order of the files in the tarball or
+    /// on their metadata like timestamps, so it is the same for identical
+    /// source code that was published under different names.
+    pub content_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryFile {
+    /// Path of the file, relative to the root of the package.
+    pub path: String,
+    pub size: u64,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -56,7 +90,12 @@ pub fn process_tarball(
     let mut vcs_info = None;
 
     let manifest_path = Path::new(&pkg_name).join("Cargo.toml");
-    let mut manifest = None;
+    let mut manifest: Option<Manifest> = None;
+
+    let build_rs_path = Path::new(&pkg_name).join("build.rs");
+    let mut has_build_rs = false;
+    let mut binary_files = Vec::new();
+    let mut file_hashes = Vec::new();
 
     for entry in archive.entries()? {
         let mut entry = entry.map_err(TarballError::Malformed)?;
@@ -93,15 +132,69 @@ pub fn process_tarball(
             let mut contents = String::new();
             entry.read_to_string(&mut contents)?;
             manifest = toml::from_str(&contents).ok();
+        } else if entry_type.is_file() {
+            if entry_path == build_rs_path {
+                has_build_rs = true;
+            }
+
+            let path = entry_path.strip_prefix(pkg_name).unwrap_or(&entry_path);
+            let path = path.display().to_string();
+            let size = entry.header().size()?;
+
+            let mut start = Vec::new();
+            entry
+                .by_ref()
+                .take(BINARY_DETECTION_LENGTH)
+                .read_to_end(&mut start)
+                .map_err(TarballError::Malformed)?;
+
+            let mut hasher = Sha256::new();
+            hasher.update(&start);
+            std::io::copy(&mut entry, &mut hasher).map_err(TarballError::Malformed)?;
+
+            if start.contains(&0) {
+                binary_files.push(BinaryFile {
+                    path: path.clone(),
+                    size,
+                });
+            }
+
+            if !CONTENT_HASH_IGNORED_FILES.contains(&path.as_str()) {
+                file_hashes.push((path, hasher.finalize()));
+            }
         }
     }
 
-    Ok(TarballInfo { manifest, vcs_info })
+    let content_hash = (!file_hashes.is_empty()).then(|| {
+        file_hashes.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut hasher = Sha256::new();
+        for (path, file_hash) in file_hashes {
+            hasher.update(path.as_bytes());
+            hasher.update([0]);
+            hasher.update(file_hash);
+        }
+        hex::encode(hasher.finalize())
+    });
+
+    let has_build_script = match manifest.as_ref().and_then(|m| m.package.build.as_ref()) {
+        Some(BuildScript::Path(_)) | Some(BuildScript::Enabled(true)) => true,
+        Some(BuildScript::Enabled(false)) => false,
+        None => has_build_rs,
+    };
+
+    Ok(TarballInfo {
+        manifest,
+        vcs_info,
+        has_build_script,
+        binary_files,
+        content_hash,
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::process_tarball;
+    use super::{process_tarball, BinaryFile};
     use crate::TarballBuilder;
 
     #[test]
@@ -174,6 +267,95 @@ repository = "https://github.com/foo/bar"
         assert_some_eq!(manifest.package.rust_version, "1.59");
     }
 
+    #[test]
+    fn process_tarball_test_build_script() {
+        let limit = 512 * 1024 * 1024;
+
+        let tarball = TarballBuilder::new("foo", "0.0.1")
+            .add_raw_manifest(b"")
+            .build();
+        let tarball_info = assert_ok!(process_tarball("foo-0.0.1", &tarball, limit));
+        assert!(!tarball_info.has_build_script);
+
+        let tarball = TarballBuilder::new("foo", "0.0.1")
+            .add_raw_manifest(b"[package]")
+            .add_file("foo-0.0.1/build.rs", b"fn main() {}")
+            .build();
+        let tarball_info = assert_ok!(process_tarball("foo-0.0.1", &tarball, limit));
+        assert!(tarball_info.has_build_script);
+
+        let tarball = TarballBuilder::new("foo", "0.0.1")
+            .add_raw_manifest(b"[package]\nbuild = false")
+            .add_file("foo-0.0.1/build.rs", b"fn main() {}")
+            .build();
+        let tarball_info = assert_ok!(process_tarball("foo-0.0.1", &tarball, limit));
+        assert!(!tarball_info.has_build_script);
+
+        let tarball = TarballBuilder::new("foo", "0.0.1")
+            .add_raw_manifest(b"[package]\nbuild = \"src/build.rs\"")
+            .add_file("foo-0.0.1/src/build.rs", b"fn main() {}")
+            .build();
+        let tarball_info = assert_ok!(process_tarball("foo-0.0.1", &tarball, limit));
+        assert!(tarball_info.has_build_script);
+    }
+
+    #[test]
+    fn process_tarball_test_binary_files() {
+        let tarball = TarballBuilder::new("foo", "0.0.1")
+            .add_raw_manifest(b"")
+            .add_file("foo-0.0.1/src/lib.rs", b"pub fn foo() {}")
+            .add_file("foo-0.0.1/blob.bin", &[0x7f, b'E', b'L', b'F', 0, 0, 1])
+            .build();
+
+        let limit = 512 * 1024 * 1024;
+        let tarball_info = assert_ok!(process_tarball("foo-0.0.1", &tarball, limit));
+        assert_eq!(
+            tarball_info.binary_files,
+            vec![BinaryFile {
+                path: "blob.bin".to_string(),
+                size: 7
+            }]
+        );
+    }
+
+    #[test]
+    fn process_tarball_test_content_hash() {
+        let limit = 512 * 1024 * 1024;
+        let content_hash = |tarball: Vec<u8>, pkg_name| {
+            assert_ok!(process_tarball(pkg_name, &tarball, limit)).content_hash
+        };
+
+        let original = TarballBuilder::new("foo", "0.0.1")
+            .add_raw_manifest(b"[package]\nname = \"foo\"")
+            .add_file("foo-0.0.1/src/lib.rs", b"pub fn foo() {}")
+            .add_file("foo-0.0.1/README.md", b"# foo")
+            .build();
+
+        // The same files under a different name and in a different order
+        let renamed = TarballBuilder::new("bar", "1.0.0")
+            .add_raw_manifest(b"[package]\nname = \"bar\"")
+            .add_file("bar-1.0.0/README.md", b"# foo")
+            .add_file("bar-1.0.0/Cargo.toml.orig", b"[package]\nname = \"bar\"")
+            .add_file("bar-1.0.0/src/lib.rs", b"pub fn foo() {}")
+            .build();
+
+        let changed = TarballBuilder::new("foo", "0.0.2")
+            .add_raw_manifest(b"[package]\nname = \"foo\"")
+            .add_file("foo-0.0.2/src/lib.rs", b"pub fn bar() {}")
+            .add_file("foo-0.0.2/README.md", b"# foo")
+            .build();
+
+        let manifest_only = TarballBuilder::new("foo", "0.0.1")
+            .add_raw_manifest(b"[package]\nname = \"foo\"")
+            .build();
+
+        let original = assert_some!(content_hash(original, "foo-0.0.1"));
+        assert_eq!(original.len(), 64);
+        assert_eq!(content_hash(renamed, "bar-1.0.0"), Some(original.clone()));
+        assert_ne!(content_hash(changed, "foo-0.0.2"), Some(original));
+        assert_none!(content_hash(manifest_only, "foo-0.0.1"));
+    }
+
     #[test]
     fn process_tarball_test_manifest_with_project() {
         let tarball = TarballBuilder::new("foo", "0.0.1")
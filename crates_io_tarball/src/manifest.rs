@@ -10,11 +10,21 @@ pub struct Manifest {
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Package {
+    pub build: Option<BuildScript>,
     pub readme: Option<String>,
     pub repository: Option<String>,
     pub rust_version: Option<RustVersion>,
 }
 
+/// The `package.build` field, which is either the path of the build script,
+/// or `false` if the automatic detection of `build.rs` is disabled.
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum BuildScript {
+    Enabled(bool),
+    Path(String),
+}
+
 #[derive(Debug, Deref)]
 pub struct RustVersion(String);
 
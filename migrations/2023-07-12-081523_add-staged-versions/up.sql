@@ -0,0 +1,3 @@
+ALTER TABLE versions ADD COLUMN staged_until TIMESTAMP;
+
+CREATE INDEX versions_staged_until ON versions (staged_until) WHERE staged_until IS NOT NULL;
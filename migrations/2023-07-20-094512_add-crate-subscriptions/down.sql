@@ -0,0 +1,2 @@
+DROP TABLE crate_notifications;
+DROP TABLE crate_subscriptions;
@@ -0,0 +1,17 @@
+CREATE TABLE crate_subscriptions (
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    crate_id INTEGER NOT NULL REFERENCES crates (id) ON DELETE CASCADE,
+    new_versions BOOLEAN NOT NULL DEFAULT TRUE,
+    yanks BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, crate_id)
+);
+
+CREATE INDEX crate_subscriptions_crate_id ON crate_subscriptions (crate_id);
+
+CREATE TABLE crate_notifications (
+    id SERIAL PRIMARY KEY,
+    version_id INTEGER NOT NULL REFERENCES versions (id) ON DELETE CASCADE,
+    action INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now()
+);
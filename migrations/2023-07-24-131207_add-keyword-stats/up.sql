@@ -0,0 +1,7 @@
+CREATE TABLE keyword_stats (
+    keyword_id INTEGER NOT NULL REFERENCES keywords (id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    crates_cnt INTEGER NOT NULL,
+    downloads BIGINT NOT NULL,
+    PRIMARY KEY (keyword_id, date)
+);
@@ -0,0 +1,16 @@
+ALTER TABLE crates ADD COLUMN health_score INTEGER;
+
+CREATE INDEX crates_health_score ON crates (health_score DESC NULLS LAST);
+
+-- Updating the health score should not change `updated_at`, the same way
+-- as updating the download count doesn't
+CREATE OR REPLACE FUNCTION set_updated_at_ignore_downloads() RETURNS trigger AS $$
+BEGIN
+    IF (NEW.updated_at IS NOT DISTINCT FROM OLD.updated_at AND
+        NEW.downloads = OLD.downloads AND
+        NEW.health_score IS NOT DISTINCT FROM OLD.health_score) THEN
+        NEW.updated_at := current_timestamp;
+    END IF;
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,24 @@
+CREATE TABLE namespace_claims
+(
+    id                  SERIAL PRIMARY KEY,
+    prefix              VARCHAR   NOT NULL,
+    github_org_id       INTEGER   NOT NULL,
+    github_org_login    VARCHAR   NOT NULL,
+    verification_method VARCHAR   NOT NULL,
+    domain              VARCHAR,
+    verification_token  VARCHAR   NOT NULL,
+    created_by          INTEGER   NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    created_at          TIMESTAMP NOT NULL DEFAULT now(),
+    verified_at         TIMESTAMP
+);
+
+COMMENT ON TABLE namespace_claims IS 'Crate name prefixes (e.g. `acme-*`) reserved for the members of a GitHub organization.';
+COMMENT ON COLUMN namespace_claims.prefix IS 'Canonical crate name prefix without the trailing separator (e.g. `acme` for `acme-*`).';
+COMMENT ON COLUMN namespace_claims.verification_method IS 'Either `github` (claimed by an organization admin) or `dns` (proven via a TXT record on `domain`).';
+COMMENT ON COLUMN namespace_claims.verified_at IS 'Claims are only enforced once they have been verified.';
+
+-- only a single verified claim may exist per prefix, but there may be
+-- multiple pending claims
+CREATE UNIQUE INDEX namespace_claims_verified_prefix_uindex
+    ON namespace_claims (prefix)
+    WHERE verified_at IS NOT NULL;
@@ -0,0 +1 @@
+DROP TABLE publisher_verifications;
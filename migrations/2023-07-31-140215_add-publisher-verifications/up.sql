@@ -0,0 +1,23 @@
+CREATE TABLE publisher_verifications
+(
+    id                  SERIAL PRIMARY KEY,
+    user_id             INTEGER   NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    method              VARCHAR   NOT NULL,
+    domain              VARCHAR,
+    github_org_id       INTEGER,
+    github_org_login    VARCHAR,
+    verification_token  VARCHAR   NOT NULL,
+    created_at          TIMESTAMP NOT NULL DEFAULT now(),
+    verified_at         TIMESTAMP,
+    CONSTRAINT publisher_verifications_subject_check
+        CHECK ((domain IS NULL) <> (github_org_id IS NULL))
+);
+
+COMMENT ON TABLE publisher_verifications IS 'Domains and GitHub organizations that users have proven control of.';
+COMMENT ON COLUMN publisher_verifications.method IS 'Either `dns` (TXT record), `well_known` (file in the `.well-known` directory of `domain`) or `github` (admin of the GitHub organization).';
+COMMENT ON COLUMN publisher_verifications.verified_at IS 'Verifications are only shown on crates once they have been verified.';
+
+CREATE UNIQUE INDEX publisher_verifications_user_domain_uindex
+    ON publisher_verifications (user_id, domain);
+CREATE UNIQUE INDEX publisher_verifications_user_github_org_uindex
+    ON publisher_verifications (user_id, github_org_id);
@@ -0,0 +1,4 @@
+ALTER TABLE versions
+    ADD COLUMN checksum_zstd CHAR(64);
+
+COMMENT ON COLUMN versions.checksum_zstd IS 'SHA256 checksum of the zstd-compressed copy of the crate file, or NULL if no copy exists (yet).';
@@ -0,0 +1,3 @@
+DROP TABLE legal_hold_actions;
+DROP TABLE legal_holds;
+DROP TABLE takedown_requests;
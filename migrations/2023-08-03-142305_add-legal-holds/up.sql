@@ -0,0 +1,52 @@
+CREATE TABLE takedown_requests
+(
+    id                SERIAL PRIMARY KEY,
+    crate_name        VARCHAR   NOT NULL,
+    version           VARCHAR,
+    complainant_name  VARCHAR   NOT NULL,
+    complainant_email VARCHAR   NOT NULL,
+    description       TEXT      NOT NULL,
+    created_at        TIMESTAMP NOT NULL DEFAULT now(),
+    resolved_at       TIMESTAMP
+);
+
+COMMENT ON TABLE takedown_requests IS 'Takedown requests (e.g. DMCA notices) submitted for crates or versions, waiting to be reviewed by the crates.io team.';
+COMMENT ON COLUMN takedown_requests.crate_name IS 'Name of the crate as submitted. The crate does not necessarily exist.';
+COMMENT ON COLUMN takedown_requests.version IS 'Version number as submitted, or `NULL` if the request concerns the whole crate.';
+COMMENT ON COLUMN takedown_requests.resolved_at IS 'Set once a legal hold has been placed for the request.';
+
+CREATE TABLE legal_holds
+(
+    id                  SERIAL PRIMARY KEY,
+    crate_id            INTEGER   NOT NULL REFERENCES crates (id) ON DELETE CASCADE,
+    version_id          INTEGER REFERENCES versions (id) ON DELETE CASCADE,
+    takedown_request_id INTEGER REFERENCES takedown_requests (id) ON DELETE SET NULL,
+    reason              TEXT      NOT NULL,
+    notice_url          VARCHAR,
+    created_at          TIMESTAMP NOT NULL DEFAULT now(),
+    lifted_at           TIMESTAMP
+);
+
+COMMENT ON TABLE legal_holds IS 'Crates and versions that must not be downloaded for legal reasons. Their data is preserved while the hold is active.';
+COMMENT ON COLUMN legal_holds.version_id IS 'The version under legal hold, or `NULL` if the whole crate is under legal hold.';
+COMMENT ON COLUMN legal_holds.notice_url IS 'Public URL of the legal notice, e.g. in the Lumen database.';
+COMMENT ON COLUMN legal_holds.lifted_at IS 'Holds are only enforced until they have been lifted.';
+
+CREATE INDEX legal_holds_active_crate_id_index
+    ON legal_holds (crate_id)
+    WHERE lifted_at IS NULL;
+
+CREATE TABLE legal_hold_actions
+(
+    id            SERIAL PRIMARY KEY,
+    legal_hold_id INTEGER   NOT NULL REFERENCES legal_holds (id) ON DELETE CASCADE,
+    action        VARCHAR   NOT NULL,
+    note          TEXT,
+    created_at    TIMESTAMP NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE legal_hold_actions IS 'Audit trail of the changes to legal holds.';
+COMMENT ON COLUMN legal_hold_actions.action IS 'Either `place` or `lift`.';
+
+CREATE INDEX legal_hold_actions_legal_hold_id_index
+    ON legal_hold_actions (legal_hold_id);
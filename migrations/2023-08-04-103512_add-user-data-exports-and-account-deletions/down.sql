@@ -0,0 +1,2 @@
+DROP TABLE account_deletions;
+DROP TABLE user_data_exports;
@@ -0,0 +1,28 @@
+CREATE TABLE user_data_exports
+(
+    id           SERIAL PRIMARY KEY,
+    user_id      INTEGER   NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    requested_at TIMESTAMP NOT NULL DEFAULT now(),
+    completed_at TIMESTAMP
+);
+
+COMMENT ON TABLE user_data_exports IS 'Archives with the personal data of a user, which are created by a background job.';
+COMMENT ON COLUMN user_data_exports.completed_at IS 'Set once the archive has been uploaded to the file storage.';
+
+CREATE INDEX user_data_exports_user_id_index
+    ON user_data_exports (user_id);
+
+CREATE TABLE account_deletions
+(
+    user_id            INTEGER PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    confirmation_token VARCHAR   NOT NULL,
+    requested_at       TIMESTAMP NOT NULL DEFAULT now(),
+    confirmed_at       TIMESTAMP,
+    scheduled_for      TIMESTAMP,
+    completed_at       TIMESTAMP
+);
+
+COMMENT ON TABLE account_deletions IS 'Requests to delete (anonymize) user accounts.';
+COMMENT ON COLUMN account_deletions.confirmation_token IS 'Token sent to the verified email address of the user to confirm the deletion.';
+COMMENT ON COLUMN account_deletions.scheduled_for IS 'End of the grace period, during which the confirmed deletion can still be cancelled.';
+COMMENT ON COLUMN account_deletions.completed_at IS 'Set once the account has been anonymized.';
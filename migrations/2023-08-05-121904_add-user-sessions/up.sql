@@ -0,0 +1,23 @@
+CREATE TABLE user_sessions
+(
+    id           SERIAL PRIMARY KEY,
+    user_id      INTEGER   NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    hashed_token BYTEA     NOT NULL,
+    user_agent   VARCHAR   NOT NULL DEFAULT '',
+    ip_address   VARCHAR,
+    created_at   TIMESTAMP NOT NULL DEFAULT now(),
+    last_seen_at TIMESTAMP NOT NULL DEFAULT now(),
+    revoked_at   TIMESTAMP
+);
+
+COMMENT ON TABLE user_sessions IS 'Web sessions of users, which are referenced by the session cookie.';
+COMMENT ON COLUMN user_sessions.hashed_token IS 'SHA256 hash of the session token stored in the session cookie.';
+COMMENT ON COLUMN user_sessions.user_agent IS 'User-Agent header of the request that created the session.';
+COMMENT ON COLUMN user_sessions.ip_address IS 'IP address of the request that created the session.';
+COMMENT ON COLUMN user_sessions.revoked_at IS 'Set once the session has been revoked, either by the user or automatically.';
+
+CREATE UNIQUE INDEX user_sessions_hashed_token_index
+    ON user_sessions (hashed_token);
+
+CREATE INDEX user_sessions_user_id_index
+    ON user_sessions (user_id);
@@ -0,0 +1,26 @@
+CREATE TABLE oauth_identities
+(
+    id               SERIAL PRIMARY KEY,
+    user_id          INTEGER   NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    provider         VARCHAR   NOT NULL,
+    provider_user_id VARCHAR   NOT NULL,
+    login            VARCHAR   NOT NULL,
+    created_at       TIMESTAMP NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE oauth_identities IS 'Accounts at OAuth providers (GitHub, GitLab, Google) that users can sign in with.';
+COMMENT ON COLUMN oauth_identities.provider IS 'Name of the OAuth provider, e.g. `github`.';
+COMMENT ON COLUMN oauth_identities.provider_user_id IS 'ID of the account at the OAuth provider.';
+COMMENT ON COLUMN oauth_identities.login IS 'Username of the account at the OAuth provider, as of the last sign in.';
+
+CREATE UNIQUE INDEX oauth_identities_provider_user_id_index
+    ON oauth_identities (provider, provider_user_id);
+
+-- Every user can link at most one account per provider
+CREATE UNIQUE INDEX oauth_identities_user_id_provider_index
+    ON oauth_identities (user_id, provider);
+
+INSERT INTO oauth_identities (user_id, provider, provider_user_id, login)
+SELECT id, 'github', gh_id::text, gh_login
+FROM users
+WHERE gh_id > 0;
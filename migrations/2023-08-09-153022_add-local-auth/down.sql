@@ -0,0 +1,3 @@
+DROP TABLE password_resets;
+DROP TABLE user_passkeys;
+DROP TABLE user_passwords;
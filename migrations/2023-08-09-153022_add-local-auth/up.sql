@@ -0,0 +1,40 @@
+CREATE TABLE user_passwords
+(
+    user_id       INTEGER PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    password_hash VARCHAR   NOT NULL,
+    created_at    TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at    TIMESTAMP NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE user_passwords IS 'Passwords of users that sign in with local authentication.';
+COMMENT ON COLUMN user_passwords.password_hash IS 'Argon2 hash of the password in the PHC string format.';
+
+CREATE TABLE user_passkeys
+(
+    id            SERIAL PRIMARY KEY,
+    user_id       INTEGER   NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    name          VARCHAR   NOT NULL,
+    credential_id BYTEA     NOT NULL UNIQUE,
+    passkey       JSONB     NOT NULL,
+    created_at    TIMESTAMP NOT NULL DEFAULT now(),
+    last_used_at  TIMESTAMP
+);
+
+COMMENT ON TABLE user_passkeys IS 'WebAuthn passkeys that users can sign in with.';
+COMMENT ON COLUMN user_passkeys.name IS 'User-provided name of the passkey, e.g. the name of the device.';
+COMMENT ON COLUMN user_passkeys.credential_id IS 'ID of the WebAuthn credential.';
+COMMENT ON COLUMN user_passkeys.passkey IS 'The serialized passkey, including the public key and the signature counter.';
+
+CREATE INDEX user_passkeys_user_id_index ON user_passkeys (user_id);
+
+CREATE TABLE password_resets
+(
+    id           SERIAL PRIMARY KEY,
+    user_id      INTEGER   NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    hashed_token BYTEA     NOT NULL UNIQUE,
+    created_at   TIMESTAMP NOT NULL DEFAULT now(),
+    used_at      TIMESTAMP
+);
+
+COMMENT ON TABLE password_resets IS 'Requests to reset the password of a user via their verified email address.';
+COMMENT ON COLUMN password_resets.hashed_token IS 'SHA256 hash of the token that is sent to the user by email.';
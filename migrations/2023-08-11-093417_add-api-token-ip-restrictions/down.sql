@@ -0,0 +1,4 @@
+DROP TABLE audit_log;
+
+ALTER TABLE api_tokens
+    DROP COLUMN allowed_cidrs;
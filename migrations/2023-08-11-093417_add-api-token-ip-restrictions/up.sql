@@ -0,0 +1,24 @@
+ALTER TABLE api_tokens
+    ADD COLUMN allowed_cidrs CIDR[];
+
+COMMENT ON COLUMN api_tokens.allowed_cidrs IS 'NULL or an array of CIDR blocks. Requests using the token from other IP addresses are rejected.';
+
+CREATE TABLE audit_log
+(
+    id           SERIAL PRIMARY KEY,
+    user_id      INTEGER   NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    api_token_id INTEGER REFERENCES api_tokens (id) ON DELETE SET NULL,
+    action       VARCHAR   NOT NULL,
+    ip_address   VARCHAR,
+    details      JSONB     NOT NULL DEFAULT '{}',
+    created_at   TIMESTAMP NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE audit_log IS 'Security relevant events concerning user accounts and their API tokens.';
+COMMENT ON COLUMN audit_log.api_token_id IS 'The API token involved in the event, if any.';
+COMMENT ON COLUMN audit_log.action IS 'The kind of event, e.g. `token_ip_rejected`.';
+COMMENT ON COLUMN audit_log.ip_address IS 'IP address of the request that caused the event.';
+COMMENT ON COLUMN audit_log.details IS 'Additional event specific data.';
+
+CREATE INDEX audit_log_user_id_index
+    ON audit_log (user_id);
@@ -0,0 +1,2 @@
+DROP TABLE publish_alerts;
+DROP TABLE publish_details;
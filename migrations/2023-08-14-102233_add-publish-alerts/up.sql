@@ -0,0 +1,28 @@
+CREATE TABLE publish_details
+(
+    version_id         INTEGER PRIMARY KEY REFERENCES versions (id) ON DELETE CASCADE,
+    api_token_id       INTEGER REFERENCES api_tokens (id) ON DELETE SET NULL,
+    country            VARCHAR,
+    has_build_script   BOOLEAN NOT NULL,
+    large_binary_files TEXT[]  NOT NULL DEFAULT '{}'
+);
+
+COMMENT ON TABLE publish_details IS 'Details about the publish of a version, which are used to detect suspicious publishes.';
+COMMENT ON COLUMN publish_details.api_token_id IS 'The API token that was used to publish the version, if any.';
+COMMENT ON COLUMN publish_details.country IS 'Country code of the publish request, as determined by the CDN.';
+COMMENT ON COLUMN publish_details.has_build_script IS 'Whether the published crate contains a build script.';
+COMMENT ON COLUMN publish_details.large_binary_files IS 'Paths of the large binary files in the published crate.';
+
+CREATE TABLE publish_alerts
+(
+    id         SERIAL PRIMARY KEY,
+    version_id INTEGER   NOT NULL REFERENCES versions (id) ON DELETE CASCADE,
+    reasons    TEXT[]    NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE publish_alerts IS 'Publishes that were flagged as suspicious, and whose owners have been alerted.';
+COMMENT ON COLUMN publish_alerts.reasons IS 'Human readable descriptions of the reasons why the publish was flagged.';
+
+CREATE UNIQUE INDEX publish_alerts_version_id_index
+    ON publish_alerts (version_id);
@@ -0,0 +1,4 @@
+DROP TABLE crate_quarantines;
+
+ALTER TABLE users
+    DROP COLUMN created_at;
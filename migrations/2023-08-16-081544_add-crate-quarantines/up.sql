@@ -0,0 +1,27 @@
+ALTER TABLE users
+    ADD COLUMN created_at TIMESTAMP;
+
+-- Existing accounts keep `NULL`, since their creation time is unknown
+ALTER TABLE users
+    ALTER COLUMN created_at SET DEFAULT now();
+
+COMMENT ON COLUMN users.created_at IS 'Time when the account was created. `NULL` for accounts that were created before this column was added.';
+
+CREATE TABLE crate_quarantines
+(
+    crate_id    INTEGER PRIMARY KEY REFERENCES crates (id) ON DELETE CASCADE,
+    user_id     INTEGER REFERENCES users (id) ON DELETE SET NULL,
+    status      VARCHAR   NOT NULL DEFAULT 'pending',
+    created_at  TIMESTAMP NOT NULL DEFAULT now(),
+    reviewed_at TIMESTAMP,
+    note        VARCHAR
+);
+
+COMMENT ON TABLE crate_quarantines IS 'Crates published by new accounts, which are flagged until they have been reviewed by the crates.io team.';
+COMMENT ON COLUMN crate_quarantines.user_id IS 'The new account that published the crate.';
+COMMENT ON COLUMN crate_quarantines.status IS 'Either `pending`, `approved` or `rejected`.';
+COMMENT ON COLUMN crate_quarantines.reviewed_at IS 'Time when the crate was approved or rejected.';
+COMMENT ON COLUMN crate_quarantines.note IS 'Optional note of the reviewer.';
+
+CREATE INDEX crate_quarantines_status_index
+    ON crate_quarantines (status);
@@ -0,0 +1,12 @@
+CREATE TABLE version_objects
+(
+    version_id  INTEGER PRIMARY KEY REFERENCES versions (id) ON DELETE CASCADE,
+    object_hash VARCHAR   NOT NULL,
+    created_at  TIMESTAMP NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE version_objects IS 'Crate files that are stored in the content-addressed `objects/` layout of the file storage.';
+COMMENT ON COLUMN version_objects.object_hash IS 'Hex-encoded SHA-256 hash of the crate file, which determines its path in the file storage. Multiple versions can share the same object.';
+
+CREATE INDEX version_objects_object_hash_index
+    ON version_objects (object_hash);
@@ -0,0 +1,15 @@
+CREATE TABLE orphaned_file_reports
+(
+    id             SERIAL PRIMARY KEY,
+    dry_run        BOOLEAN   NOT NULL,
+    orphaned_count INTEGER   NOT NULL,
+    deleted_count  INTEGER   NOT NULL,
+    orphaned_files TEXT[]    NOT NULL,
+    created_at     TIMESTAMP NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE orphaned_file_reports IS 'Results of the background job that looks for files in the storage bucket without a corresponding database record.';
+COMMENT ON COLUMN orphaned_file_reports.dry_run IS 'If true, the orphaned files were only reported, but not deleted.';
+COMMENT ON COLUMN orphaned_file_reports.orphaned_count IS 'Total number of orphaned files that were found.';
+COMMENT ON COLUMN orphaned_file_reports.deleted_count IS 'Number of orphaned files that were deleted.';
+COMMENT ON COLUMN orphaned_file_reports.orphaned_files IS 'Paths of the orphaned files. Only the first 1000 paths are recorded.';
@@ -0,0 +1,18 @@
+CREATE TABLE publish_idempotency_keys
+(
+    user_id      INTEGER   NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    key          VARCHAR   NOT NULL,
+    request_hash VARCHAR   NOT NULL,
+    response     JSONB,
+    created_at   TIMESTAMP NOT NULL DEFAULT now(),
+    completed_at TIMESTAMP,
+    PRIMARY KEY (user_id, key)
+);
+
+COMMENT ON TABLE publish_idempotency_keys IS 'Publish requests with an `Idempotency-Key` header, so that retries of a completed publish return the original response.';
+COMMENT ON COLUMN publish_idempotency_keys.key IS 'The value of the `Idempotency-Key` header, which is unique per user.';
+COMMENT ON COLUMN publish_idempotency_keys.request_hash IS 'Hex-encoded SHA-256 hash of the request body, so that a key can not be reused for a different publish.';
+COMMENT ON COLUMN publish_idempotency_keys.response IS 'The response of the completed publish. `NULL` while the publish is still in progress.';
+
+CREATE INDEX publish_idempotency_keys_created_at_index
+    ON publish_idempotency_keys (created_at);
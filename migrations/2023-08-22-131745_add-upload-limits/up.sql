@@ -0,0 +1,19 @@
+CREATE TABLE upload_limits
+(
+    crate_id        INTEGER PRIMARY KEY REFERENCES crates (id) ON DELETE CASCADE,
+    max_upload_size BIGINT    NOT NULL,
+    max_unpack_size BIGINT,
+    note            VARCHAR,
+    created_at      TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMP NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE upload_limits IS 'Per-crate overrides of the maximum crate file size, which are set by the crates.io team.';
+COMMENT ON COLUMN upload_limits.max_upload_size IS 'Maximum size of the compressed crate file in bytes.';
+COMMENT ON COLUMN upload_limits.max_unpack_size IS 'Maximum size of the decompressed crate file in bytes. Falls back to the global limit if `NULL`.';
+COMMENT ON COLUMN upload_limits.note IS 'Optional note explaining why the override was granted.';
+
+INSERT INTO upload_limits (crate_id, max_upload_size, note)
+SELECT id, max_upload_size, 'Migrated from `crates.max_upload_size`'
+FROM crates
+WHERE max_upload_size IS NOT NULL;
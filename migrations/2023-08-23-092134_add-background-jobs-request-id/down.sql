@@ -0,0 +1,2 @@
+ALTER TABLE background_jobs
+    DROP COLUMN request_id;
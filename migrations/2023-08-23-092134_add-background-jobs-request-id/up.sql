@@ -0,0 +1,4 @@
+ALTER TABLE background_jobs
+    ADD COLUMN request_id VARCHAR;
+
+COMMENT ON COLUMN background_jobs.request_id IS 'ID of the HTTP request or background job that enqueued this job, which is included in all log lines of the job.';
@@ -0,0 +1,16 @@
+CREATE TABLE backfill_progress
+(
+    name            VARCHAR   NOT NULL PRIMARY KEY,
+    last_version_id INTEGER   NOT NULL DEFAULT 0,
+    paused          BOOLEAN   NOT NULL DEFAULT FALSE,
+    processed_count INTEGER   NOT NULL DEFAULT 0,
+    skipped_count   INTEGER   NOT NULL DEFAULT 0,
+    updated_at      TIMESTAMP NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE backfill_progress IS 'Progress of resumable backfill background jobs, which process the versions in batches ordered by their id.';
+COMMENT ON COLUMN backfill_progress.name IS 'Name of the backfill, e.g. `artifacts`.';
+COMMENT ON COLUMN backfill_progress.last_version_id IS 'ID of the last version that was processed. The next run continues with the versions after this one.';
+COMMENT ON COLUMN backfill_progress.paused IS 'If true, the backfill stops after the current batch and is not continued until it is resumed.';
+COMMENT ON COLUMN backfill_progress.processed_count IS 'Number of versions whose missing artifacts were backfilled.';
+COMMENT ON COLUMN backfill_progress.skipped_count IS 'Number of versions that could not be backfilled, e.g. because their crate file is missing.';
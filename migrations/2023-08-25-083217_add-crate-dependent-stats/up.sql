@@ -0,0 +1,15 @@
+CREATE TABLE crate_dependent_stats
+(
+    crate_id     INTEGER          NOT NULL REFERENCES crates (id) ON DELETE CASCADE,
+    dependent_id INTEGER          NOT NULL REFERENCES crates (id) ON DELETE CASCADE,
+    downloads    BIGINT           NOT NULL,
+    share        DOUBLE PRECISION NOT NULL,
+    updated_at   TIMESTAMP        NOT NULL DEFAULT now(),
+    PRIMARY KEY (crate_id, dependent_id)
+);
+
+COMMENT ON TABLE crate_dependent_stats IS 'The most downloaded dependents of each crate, computed by the `update_dependent_stats` background job.';
+COMMENT ON COLUMN crate_dependent_stats.crate_id IS 'ID of the crate that is depended on.';
+COMMENT ON COLUMN crate_dependent_stats.dependent_id IS 'ID of the crate whose latest version depends on the crate.';
+COMMENT ON COLUMN crate_dependent_stats.downloads IS 'Total downloads of the dependent crate.';
+COMMENT ON COLUMN crate_dependent_stats.share IS 'Estimated share of the downloads of the crate that are caused by the dependent crate, between 0 and 1.';
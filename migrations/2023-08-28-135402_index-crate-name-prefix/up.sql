@@ -0,0 +1,3 @@
+-- Speeds up the prefix matching of the autocomplete endpoint, which can not
+-- use the trigram index efficiently for short prefixes
+CREATE INDEX index_crates_name_prefix ON crates (canon_crate_name(name) text_pattern_ops);
@@ -0,0 +1,22 @@
+-- Search queries are normalized to NFKC by the application, so the indexed
+-- text is normalized the same way. Existing crates are reindexed on their
+-- next update.
+CREATE OR REPLACE FUNCTION trigger_crates_name_search() RETURNS trigger AS $$
+DECLARE kws TEXT;
+begin
+  SELECT array_to_string(array_agg(keyword), ',') INTO kws
+    FROM keywords INNER JOIN crates_keywords
+    ON keywords.id = crates_keywords.keyword_id
+    WHERE crates_keywords.crate_id = new.id;
+  new.textsearchable_index_col :=
+     setweight(to_tsvector('pg_catalog.english',
+                           normalize(coalesce(new.name, ''), NFKC)), 'A') ||
+     setweight(to_tsvector('pg_catalog.english',
+                           normalize(coalesce(kws, ''), NFKC)), 'B') ||
+     setweight(to_tsvector('pg_catalog.english',
+                           normalize(coalesce(new.description, ''), NFKC)), 'C') ||
+     setweight(to_tsvector('pg_catalog.english',
+                           normalize(coalesce(new.readme, ''), NFKC)), 'D');
+  return new;
+end
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,2 @@
+DROP TABLE index_snapshots;
+DROP TABLE index_changes;
@@ -0,0 +1,23 @@
+CREATE TABLE index_changes
+(
+    id         BIGSERIAL PRIMARY KEY,
+    crate_name VARCHAR   NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE index_changes IS 'Change feed of the sparse index, which mirrors use to catch up after bootstrapping from an index snapshot.';
+COMMENT ON COLUMN index_changes.id IS 'Sequence number of the change.';
+COMMENT ON COLUMN index_changes.crate_name IS 'Name of the crate whose index file was updated or removed.';
+
+CREATE TABLE index_snapshots
+(
+    sequence   BIGINT    PRIMARY KEY,
+    crates     INTEGER   NOT NULL,
+    size       BIGINT    NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE index_snapshots IS 'Compressed snapshots of the sparse index, created by the `create_index_snapshot` background job.';
+COMMENT ON COLUMN index_snapshots.sequence IS 'Sequence number of the last change in `index_changes` that is included in the snapshot.';
+COMMENT ON COLUMN index_snapshots.crates IS 'Number of index files in the snapshot.';
+COMMENT ON COLUMN index_snapshots.size IS 'Size of the snapshot tarball in bytes.';
@@ -0,0 +1,2 @@
+DROP TABLE index_sync_states;
+DROP TABLE crate_index_sequences;
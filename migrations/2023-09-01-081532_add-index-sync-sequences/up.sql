@@ -0,0 +1,22 @@
+CREATE TABLE crate_index_sequences
+(
+    crate_name VARCHAR NOT NULL PRIMARY KEY,
+    sequence   BIGINT  NOT NULL
+);
+
+COMMENT ON TABLE crate_index_sequences IS 'Per-crate sequence numbers of the index sync jobs, which are used to detect duplicate and out-of-order jobs.';
+COMMENT ON COLUMN crate_index_sequences.crate_name IS 'Name of the crate. Rows are kept after the crate is deleted, so that the removal from the index is ordered as well.';
+COMMENT ON COLUMN crate_index_sequences.sequence IS 'Sequence number of the most recently enqueued index sync of the crate.';
+
+CREATE TABLE index_sync_states
+(
+    crate_name      VARCHAR   NOT NULL,
+    target          VARCHAR   NOT NULL,
+    synced_sequence BIGINT    NOT NULL DEFAULT 0,
+    synced_at       TIMESTAMP NOT NULL DEFAULT now(),
+    PRIMARY KEY (crate_name, target)
+);
+
+COMMENT ON TABLE index_sync_states IS 'Progress of the index sync jobs of a crate. The rows are locked while a job syncs the index file, so that the jobs of a crate never interleave.';
+COMMENT ON COLUMN index_sync_states.target IS 'Either `git` or `sparse`.';
+COMMENT ON COLUMN index_sync_states.synced_sequence IS 'Sequence number of the most recent job that synced the index file to the target.';
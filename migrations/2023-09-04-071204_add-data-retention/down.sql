@@ -0,0 +1,6 @@
+DROP TABLE data_retention_stats;
+
+DROP TRIGGER trigger_api_tokens_set_revoked_at ON api_tokens;
+DROP FUNCTION api_tokens_set_revoked_at();
+
+ALTER TABLE api_tokens DROP COLUMN revoked_at;
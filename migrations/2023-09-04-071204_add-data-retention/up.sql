@@ -0,0 +1,32 @@
+ALTER TABLE api_tokens ADD COLUMN revoked_at TIMESTAMP;
+
+COMMENT ON COLUMN api_tokens.revoked_at IS 'Time at which the token was revoked. Tokens that were revoked before this column was added use the time of the migration.';
+
+UPDATE api_tokens SET revoked_at = now() WHERE revoked;
+
+CREATE FUNCTION api_tokens_set_revoked_at() RETURNS trigger AS $$
+  BEGIN
+    IF NEW.revoked AND NOT OLD.revoked THEN
+      NEW.revoked_at := CURRENT_TIMESTAMP;
+    END IF;
+    RETURN NEW;
+  END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_api_tokens_set_revoked_at BEFORE
+UPDATE OF revoked ON api_tokens
+FOR EACH ROW EXECUTE PROCEDURE api_tokens_set_revoked_at();
+
+CREATE TABLE data_retention_stats
+(
+    kind         VARCHAR   NOT NULL PRIMARY KEY,
+    purged_total BIGINT    NOT NULL DEFAULT 0,
+    last_purged  BIGINT    NOT NULL DEFAULT 0,
+    last_run_at  TIMESTAMP NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE data_retention_stats IS 'Number of rows that were purged by the `purge_expired_data` background job, which are exported as service metrics.';
+COMMENT ON COLUMN data_retention_stats.kind IS 'The kind of data, e.g. `ownership_invitations` or `revoked_tokens`.';
+COMMENT ON COLUMN data_retention_stats.purged_total IS 'Number of rows that were purged since the table was created.';
+COMMENT ON COLUMN data_retention_stats.last_purged IS 'Number of rows that were purged by the most recent run of the job.';
+COMMENT ON COLUMN data_retention_stats.last_run_at IS 'Time of the most recent run of the job.';
@@ -0,0 +1,6 @@
+DELETE FROM publish_limit_buckets WHERE action <> 0;
+
+ALTER TABLE publish_limit_buckets DROP CONSTRAINT publish_limit_buckets_pkey;
+ALTER TABLE publish_limit_buckets ADD PRIMARY KEY (user_id);
+
+ALTER TABLE publish_limit_buckets DROP COLUMN action;
@@ -0,0 +1,6 @@
+ALTER TABLE publish_limit_buckets ADD COLUMN action SMALLINT NOT NULL DEFAULT 0;
+
+COMMENT ON COLUMN publish_limit_buckets.action IS 'The rate limited action: 0 for publishing new crates, 1 for rendering README previews.';
+
+ALTER TABLE publish_limit_buckets DROP CONSTRAINT publish_limit_buckets_pkey;
+ALTER TABLE publish_limit_buckets ADD PRIMARY KEY (user_id, action);
@@ -0,0 +1,14 @@
+CREATE TABLE crate_maintenance_status
+(
+    crate_id     INTEGER   NOT NULL PRIMARY KEY REFERENCES crates (id) ON DELETE CASCADE,
+    flagged_at   TIMESTAMP,
+    notified_at  TIMESTAMP,
+    confirmed_at TIMESTAMP,
+    confirmed_by INTEGER REFERENCES users (id) ON DELETE SET NULL
+);
+
+COMMENT ON TABLE crate_maintenance_status IS 'Tracks crates whose owners have all been inactive for a long time, and the confirmations of their owners that the crates are still maintained.';
+COMMENT ON COLUMN crate_maintenance_status.flagged_at IS 'Time at which the crate was flagged as stale by the `flag_stale_crates` background job, or NULL if it is currently not flagged.';
+COMMENT ON COLUMN crate_maintenance_status.notified_at IS 'Time at which the owners were last asked by email to confirm the maintenance status.';
+COMMENT ON COLUMN crate_maintenance_status.confirmed_at IS 'Time at which an owner last confirmed that the crate is still maintained.';
+COMMENT ON COLUMN crate_maintenance_status.confirmed_by IS 'The owner that last confirmed that the crate is still maintained.';
@@ -0,0 +1,5 @@
+ALTER TABLE crate_quarantines DROP COLUMN duplicate_of;
+
+DROP INDEX publish_details_content_hash_index;
+
+ALTER TABLE publish_details DROP COLUMN content_hash;
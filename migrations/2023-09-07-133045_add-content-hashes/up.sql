@@ -0,0 +1,11 @@
+ALTER TABLE publish_details ADD COLUMN content_hash VARCHAR;
+
+COMMENT ON COLUMN publish_details.content_hash IS 'SHA-256 hash of the paths and contents of the files in the crate file, excluding the files that contain the crate name or version. `NULL` for versions published before this column was added.';
+
+CREATE INDEX publish_details_content_hash_index
+    ON publish_details (content_hash);
+
+ALTER TABLE crate_quarantines
+    ADD COLUMN duplicate_of INTEGER REFERENCES versions (id) ON DELETE SET NULL;
+
+COMMENT ON COLUMN crate_quarantines.duplicate_of IS 'A version of another crate with identical content, if the crate was quarantined because it looks like a republish under a different name.';
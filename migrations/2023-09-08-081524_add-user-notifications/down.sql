@@ -0,0 +1,2 @@
+DROP TABLE api_token_origins;
+DROP TABLE user_notifications;
@@ -0,0 +1,35 @@
+CREATE TABLE user_notifications
+(
+    id         SERIAL PRIMARY KEY,
+    user_id    INTEGER   NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    kind       VARCHAR   NOT NULL,
+    severity   VARCHAR   NOT NULL,
+    details    JSONB     NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    emailed_at TIMESTAMP
+);
+
+COMMENT ON TABLE user_notifications IS 'Security relevant events that the user is notified about.';
+COMMENT ON COLUMN user_notifications.kind IS 'The kind of event, e.g. `token_created`.';
+COMMENT ON COLUMN user_notifications.severity IS 'Either `high` for notifications that are emailed immediately, or `low` for notifications that are included in the next digest email.';
+COMMENT ON COLUMN user_notifications.details IS 'Additional event specific data.';
+COMMENT ON COLUMN user_notifications.emailed_at IS 'When the user was emailed about the notification. `NULL` while the email is pending.';
+
+CREATE INDEX user_notifications_user_id_index
+    ON user_notifications (user_id);
+
+CREATE INDEX user_notifications_pending_index
+    ON user_notifications (id)
+    WHERE emailed_at IS NULL;
+
+CREATE TABLE api_token_origins
+(
+    api_token_id INTEGER   NOT NULL REFERENCES api_tokens (id) ON DELETE CASCADE,
+    ip_address   VARCHAR   NOT NULL,
+    country      VARCHAR,
+    created_at   TIMESTAMP NOT NULL DEFAULT now(),
+    PRIMARY KEY (api_token_id, ip_address)
+);
+
+COMMENT ON TABLE api_token_origins IS 'The IP addresses that API tokens have been used from, so that users can be notified about the use of a token from a new IP address.';
+COMMENT ON COLUMN api_token_origins.country IS 'The country code of the first request from the IP address, as reported by the CDN.';
@@ -0,0 +1,10 @@
+CREATE TABLE ip_limit_buckets (
+    ip_address VARCHAR NOT NULL,
+    action SMALLINT NOT NULL,
+    tokens INTEGER NOT NULL,
+    last_refill TIMESTAMP NOT NULL,
+    PRIMARY KEY (ip_address, action)
+);
+
+COMMENT ON TABLE ip_limit_buckets IS 'Token buckets for rate limited actions that can be performed without being logged in, keyed by the IP address of the client.';
+COMMENT ON COLUMN ip_limit_buckets.action IS 'The rate limited action, see `LimitedAction`.';
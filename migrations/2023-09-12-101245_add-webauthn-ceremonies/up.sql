@@ -0,0 +1,15 @@
+CREATE TABLE webauthn_ceremonies
+(
+    id           SERIAL PRIMARY KEY,
+    hashed_token BYTEA     NOT NULL UNIQUE,
+    user_id      INTEGER REFERENCES users (id) ON DELETE CASCADE,
+    kind         VARCHAR   NOT NULL,
+    state        JSONB,
+    created_at   TIMESTAMP NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE webauthn_ceremonies IS 'State of passkey registrations and sign ins between their two requests.';
+COMMENT ON COLUMN webauthn_ceremonies.hashed_token IS 'SHA256 hash of the token that is stored in the session cookie.';
+COMMENT ON COLUMN webauthn_ceremonies.user_id IS 'The user that registers a passkey or signs in, NULL for sign ins of unknown users.';
+COMMENT ON COLUMN webauthn_ceremonies.kind IS 'Either `registration` or `authentication`.';
+COMMENT ON COLUMN webauthn_ceremonies.state IS 'The serialized state of the ceremony, NULL for sign ins of unknown users, which always fail.';
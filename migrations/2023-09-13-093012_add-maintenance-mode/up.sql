@@ -0,0 +1,9 @@
+CREATE TABLE maintenance_mode (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    enabled BOOLEAN NOT NULL,
+    retry_after INTEGER NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMENT ON TABLE maintenance_mode IS 'The maintenance mode as set through the admin API. The table contains at most one row, which is polled by all server instances and the background worker.';
+COMMENT ON COLUMN maintenance_mode.retry_after IS 'Estimated duration of the maintenance in seconds.';
@@ -0,0 +1,7 @@
+ALTER TABLE crates
+    ADD COLUMN max_upload_size INTEGER;
+
+UPDATE crates
+SET max_upload_size = LEAST(upload_limits.max_upload_size, 2147483647)
+FROM upload_limits
+WHERE crates.id = upload_limits.crate_id;
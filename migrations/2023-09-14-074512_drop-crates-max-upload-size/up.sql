@@ -0,0 +1,3 @@
+-- The limits have been migrated to the `upload_limits` table
+ALTER TABLE crates
+    DROP COLUMN max_upload_size;
@@ -0,0 +1,2 @@
+DROP INDEX versions_crate_id_semver_triple_idx;
+DROP FUNCTION to_semver_triple(text);
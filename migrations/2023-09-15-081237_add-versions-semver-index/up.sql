@@ -0,0 +1,10 @@
+-- Unlike `to_semver_no_prerelease()`, this also returns the version triple of
+-- pre-release versions, and `NULL` fields instead of an error for invalid
+-- version numbers.
+CREATE FUNCTION to_semver_triple(text) RETURNS semver_triple IMMUTABLE AS $$
+  SELECT (m[1]::numeric, m[2]::numeric, m[3]::numeric)::semver_triple
+  FROM regexp_match($1, '^(\d+)\.(\d+)\.(\d+)') AS m
+  $$ LANGUAGE SQL
+;
+
+CREATE INDEX versions_crate_id_semver_triple_idx ON versions (crate_id, to_semver_triple(num));
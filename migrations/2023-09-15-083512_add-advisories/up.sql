@@ -0,0 +1,14 @@
+CREATE TABLE advisories
+(
+    id         SERIAL PRIMARY KEY,
+    crate_id   INTEGER   NOT NULL REFERENCES crates (id) ON DELETE CASCADE,
+    identifier VARCHAR   NOT NULL,
+    affected   VARCHAR   NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now(),
+    UNIQUE (crate_id, identifier)
+);
+
+COMMENT ON TABLE advisories IS 'Security advisories of crates, which are recorded by the crates.io team.';
+COMMENT ON COLUMN advisories.identifier IS 'Identifier of the advisory, e.g. `RUSTSEC-2023-0001`.';
+COMMENT ON COLUMN advisories.affected IS 'Version requirement matching the affected versions of the crate.';
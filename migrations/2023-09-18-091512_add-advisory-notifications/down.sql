@@ -0,0 +1,4 @@
+DROP TABLE advisory_notifications;
+
+ALTER TABLE crate_subscriptions
+    DROP COLUMN advisories;
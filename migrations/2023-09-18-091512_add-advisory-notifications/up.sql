@@ -0,0 +1,11 @@
+ALTER TABLE crate_subscriptions
+    ADD COLUMN advisories BOOLEAN NOT NULL DEFAULT TRUE;
+
+CREATE TABLE advisory_notifications
+(
+    id          SERIAL PRIMARY KEY,
+    advisory_id INTEGER   NOT NULL REFERENCES advisories (id) ON DELETE CASCADE,
+    created_at  TIMESTAMP NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE advisory_notifications IS 'Recorded advisories that subscribers of the affected crates will be notified about by the next notification digest.';
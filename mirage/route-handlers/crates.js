@@ -292,6 +292,22 @@ export function register(server) {
     return {};
   });
 
+  server.put('/api/v1/crates/:name/:version/alert_yank/:token', (schema, request) => {
+    const { name, version: versionNum } = request.params;
+    const crate = schema.crates.findBy({ name });
+    if (!crate) {
+      return notFound();
+    }
+
+    const version = schema.versions.findBy({ crateId: crate.id, num: versionNum });
+    if (!version) {
+      return notFound();
+    }
+
+    version.update({ yanked: true });
+    return { ok: true };
+  });
+
   server.get('/api/v1/crates/:name/:version/readme', (schema, request) => {
     const { name, version: versionNum } = request.params;
     const crate = schema.crates.findBy({ name });
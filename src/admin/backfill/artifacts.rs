@@ -0,0 +1,67 @@
+use crate::background_jobs::Job;
+use crate::db;
+use crate::models::BackfillProgress;
+use crate::worker::BACKFILL_ARTIFACTS;
+
+/// Render the missing readmes and compute the missing checksums of
+/// historical versions in the background worker.
+///
+/// The backfill processes the versions in batches and records its progress
+/// in the database, so it can be paused at any time and resumed later on
+/// without processing the same versions again.
+#[derive(clap::Parser, Debug)]
+pub enum ArtifactsCommand {
+    /// Start the backfill, or resume it where it has been paused
+    Start {
+        /// How many versions are processed in a single batch
+        #[arg(long, default_value_t = 100)]
+        batch_size: u32,
+        /// How long the worker waits before processing the next batch
+        #[arg(long, default_value_t = 1000)]
+        batch_delay_ms: u64,
+    },
+    /// Stop the backfill after the current batch
+    Pause,
+    /// Show the progress of the backfill
+    Status,
+    /// Forget the progress, so that the next run starts from the beginning
+    Reset,
+}
+
+pub fn run(command: &ArtifactsCommand) -> anyhow::Result<()> {
+    let conn = &mut db::oneoff_connection()?;
+
+    match *command {
+        ArtifactsCommand::Start {
+            batch_size,
+            batch_delay_ms,
+        } => {
+            let progress = BackfillProgress::set_paused(conn, BACKFILL_ARTIFACTS, false)?;
+            Job::backfill_artifacts(batch_size, batch_delay_ms).enqueue(conn)?;
+            println!(
+                "Enqueued backfill of artifacts after version {}",
+                progress.last_version_id
+            );
+        }
+        ArtifactsCommand::Pause => {
+            BackfillProgress::set_paused(conn, BACKFILL_ARTIFACTS, true)?;
+            println!("Backfill of artifacts will be paused after the current batch");
+        }
+        ArtifactsCommand::Status => match BackfillProgress::find(conn, BACKFILL_ARTIFACTS)? {
+            Some(progress) => {
+                println!("Last processed version: {}", progress.last_version_id);
+                println!("Processed versions:     {}", progress.processed_count);
+                println!("Skipped versions:       {}", progress.skipped_count);
+                println!("Paused:                 {}", progress.paused);
+                println!("Last update:            {}", progress.updated_at);
+            }
+            None => println!("Backfill of artifacts has not been started yet"),
+        },
+        ArtifactsCommand::Reset => {
+            BackfillProgress::reset(conn, BACKFILL_ARTIFACTS)?;
+            println!("Progress of the backfill of artifacts has been reset");
+        }
+    }
+
+    Ok(())
+}
@@ -1,15 +1,20 @@
+mod artifacts;
 mod rust_version;
 
+use self::artifacts::ArtifactsCommand;
 use self::rust_version::RustVersionOptions;
 
 #[derive(clap::Parser, Debug)]
 #[command(about = "Tools to backfill the database from various sources")]
 pub enum Command {
+    #[clap(subcommand)]
+    Artifacts(ArtifactsCommand),
     RustVersion(RustVersionOptions),
 }
 
 pub fn run(command: Command) -> anyhow::Result<()> {
     match command {
+        Command::Artifacts(command) => artifacts::run(&command),
         Command::RustVersion(options) => rust_version::run(&options),
     }
 }
@@ -1,9 +1,10 @@
 use crate::background_jobs::Job;
+use crate::schema::{crates, legal_holds};
 use crate::storage::Storage;
-use crate::{admin::dialoguer, db, schema::crates};
+use crate::{admin::dialoguer, db};
 use anyhow::Context;
 use diesel::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(clap::Parser, Debug)]
 #[command(
@@ -40,16 +41,36 @@ pub fn run(opts: Opts) {
 
     let existing_crates: HashMap<String, i32> = existing_crates.into_iter().collect();
 
+    // The data of crates under legal hold has to be preserved
+    let held_crates: HashSet<i32> = legal_holds::table
+        .select(legal_holds::crate_id)
+        .filter(legal_holds::lifted_at.is_null())
+        .filter(legal_holds::crate_id.eq_any(existing_crates.values()))
+        .load(conn)
+        .context("Failed to look up legal holds from the database")
+        .unwrap()
+        .into_iter()
+        .collect();
+
     println!("Deleting the following crates:");
     println!();
     for name in &crate_names {
         match existing_crates.get(name) {
+            Some(id) if held_crates.contains(id) => {
+                println!(" - {name} (⚠️ crate is under legal hold and will be skipped)")
+            }
             Some(id) => println!(" - {name} (id={id})"),
             None => println!(" - {name} (⚠️ crate not found)"),
         }
     }
     println!();
 
+    crate_names.retain(|name| {
+        existing_crates
+            .get(name)
+            .map_or(true, |id| !held_crates.contains(id))
+    });
+
     if !opts.yes && !dialoguer::confirm("Do you want to permanently delete these crates?") {
         return;
     }
@@ -1,4 +1,5 @@
 use crate::background_jobs::Job;
+use crate::config::RetentionConfig;
 use crate::db;
 use crate::schema::background_jobs::dsl::*;
 use anyhow::Result;
@@ -13,18 +14,48 @@ use secrecy::{ExposeSecret, SecretString};
 )]
 pub enum Command {
     UpdateDownloads,
+    BackfillCrateObjects,
     DumpDb {
         #[arg(env = "READ_ONLY_REPLICA_URL")]
         database_url: SecretString,
         #[arg(default_value = "db-dump.tar.gz")]
         target_name: String,
     },
+    CleanupIdempotencyKeys,
+    CleanupOrphanedFiles {
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+        /// Files that were modified more recently are never considered orphaned
+        #[arg(long = "grace-period-hours", default_value_t = 24)]
+        grace_period_hours: u32,
+    },
+    CreateIndexSnapshot,
     DailyDbMaintenance,
+    FlagStaleCrates {
+        /// Crates are flagged if all of their owners have been inactive for
+        /// this many days
+        #[arg(long = "inactivity-days", default_value_t = 365)]
+        inactivity_days: u32,
+    },
     SquashIndex,
     NormalizeIndex {
         #[arg(long = "dry-run")]
         dry_run: bool,
     },
+    PromoteStagedVersions,
+    PurgeDeletedAccounts,
+    /// Uses the retention periods from the `RETENTION_*_DAYS` environment
+    /// variables
+    PurgeExpiredData,
+    ReconcileStorageReplicas,
+    RecompressCrateFile {
+        version_id: i32,
+    },
+    SendCrateNotificationDigests,
+    SendSecurityNotificationDigests,
+    UpdateDependentStats,
+    UpdateHealthScores,
+    UpdateKeywordStats,
 }
 
 pub fn run(command: Command) -> Result<()> {
@@ -50,8 +81,37 @@ pub fn run(command: Command) -> Result<()> {
             database_url,
             target_name,
         } => Ok(Job::dump_db(database_url.expose_secret().to_string(), target_name).enqueue(conn)?),
+        Command::BackfillCrateObjects => Ok(Job::backfill_crate_objects().enqueue(conn)?),
+        Command::CleanupIdempotencyKeys => Ok(Job::cleanup_idempotency_keys().enqueue(conn)?),
+        Command::CleanupOrphanedFiles {
+            dry_run,
+            grace_period_hours,
+        } => Ok(Job::cleanup_orphaned_files(dry_run, grace_period_hours).enqueue(conn)?),
+        Command::CreateIndexSnapshot => Ok(Job::create_index_snapshot().enqueue(conn)?),
         Command::DailyDbMaintenance => Ok(Job::daily_db_maintenance().enqueue(conn)?),
+        Command::FlagStaleCrates { inactivity_days } => {
+            Ok(Job::flag_stale_crates(inactivity_days).enqueue(conn)?)
+        }
         Command::SquashIndex => Ok(Job::squash_index().enqueue(conn)?),
         Command::NormalizeIndex { dry_run } => Ok(Job::normalize_index(dry_run).enqueue(conn)?),
+        Command::PromoteStagedVersions => Ok(Job::promote_staged_versions().enqueue(conn)?),
+        Command::PurgeDeletedAccounts => Ok(Job::purge_deleted_accounts().enqueue(conn)?),
+        Command::PurgeExpiredData => {
+            let retention = RetentionConfig::from_environment();
+            Ok(Job::purge_expired_data(retention).enqueue(conn)?)
+        }
+        Command::ReconcileStorageReplicas => Ok(Job::reconcile_storage_replicas().enqueue(conn)?),
+        Command::RecompressCrateFile { version_id } => {
+            Ok(Job::recompress_crate_file(version_id).enqueue(conn)?)
+        }
+        Command::SendCrateNotificationDigests => {
+            Ok(Job::send_crate_notification_digests().enqueue(conn)?)
+        }
+        Command::SendSecurityNotificationDigests => {
+            Ok(Job::send_security_notification_digests().enqueue(conn)?)
+        }
+        Command::UpdateDependentStats => Ok(Job::update_dependent_stats().enqueue(conn)?),
+        Command::UpdateHealthScores => Ok(Job::update_health_scores().enqueue(conn)?),
+        Command::UpdateKeywordStats => Ok(Job::update_keyword_stats().enqueue(conn)?),
     }
 }
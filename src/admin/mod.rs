@@ -7,7 +7,11 @@ pub mod git_import;
 pub mod migrate;
 pub mod on_call;
 pub mod populate;
+pub mod rebuild_index;
 pub mod render_readmes;
+pub mod revoke_sessions;
+pub mod revoke_tokens;
+pub mod seed_data;
 pub mod test_pagerduty;
 pub mod transfer_crates;
 pub mod upload_index;
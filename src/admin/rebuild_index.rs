@@ -0,0 +1,27 @@
+use crate::{background_jobs::Job, db, models::Crate};
+
+use diesel::prelude::*;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "rebuild-index",
+    about = "Regenerate the git and sparse index files of crates from the database."
+)]
+pub struct Opts {
+    /// Names of the crates whose index files should be regenerated
+    #[arg(long = "crate", required = true)]
+    crate_names: Vec<String>,
+}
+
+pub fn run(opts: Opts) -> anyhow::Result<()> {
+    let conn = &mut db::oneoff_connection()?;
+
+    for crate_name in opts.crate_names {
+        let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+
+        println!("Enqueueing index sync for {}", krate.name);
+        Job::enqueue_sync_to_index(&krate.name, conn)?;
+    }
+
+    Ok(())
+}
@@ -9,7 +9,7 @@ use std::{io::Read, path::Path, sync::Arc, thread};
 
 use crate::storage::Storage;
 use chrono::{TimeZone, Utc};
-use crates_io_markdown::text_to_html;
+use crates_io_markdown::{text_to_html_with_policy, SanitizationPolicy};
 use crates_io_tarball::Manifest;
 use diesel::prelude::*;
 use flate2::read::GzDecoder;
@@ -34,6 +34,10 @@ pub struct Opts {
     #[arg(long)]
     older_than: Option<String>,
 
+    /// Only rerender readmes of versions that were published after this date.
+    #[arg(long)]
+    since: Option<String>,
+
     /// Only rerender readmes for the specified crate.
     #[arg(long = "crate")]
     crate_name: Option<String>,
@@ -41,6 +45,9 @@ pub struct Opts {
 
 pub fn run(opts: Opts) -> anyhow::Result<()> {
     let base_config = Arc::new(config::Base::from_environment());
+    let policy = Arc::new(
+        config::ReadmeSanitizationConfig::from_environment().policy(&config::domain_name()),
+    );
     let storage = Arc::new(Storage::from_environment());
     let conn = &mut db::oneoff_connection().unwrap();
 
@@ -68,6 +75,16 @@ pub fn run(opts: Opts) -> anyhow::Result<()> {
         .select(versions::id)
         .into_boxed();
 
+    if let Some(ref time) = opts.since {
+        let since = Utc
+            .datetime_from_str(time, "%Y-%m-%d %H:%M:%S")
+            .expect("Could not parse --since argument as a time")
+            .naive_utc();
+
+        println!("Rendering readmes of versions published since: {since}");
+        query = query.filter(versions::created_at.ge(since));
+    }
+
     if let Some(crate_name) = opts.crate_name {
         println!("Rendering readmes for {crate_name}");
         query = query.filter(crates::name.eq(crate_name));
@@ -110,10 +127,17 @@ pub fn run(opts: Opts) -> anyhow::Result<()> {
 
             let client = client.clone();
             let base_config = base_config.clone();
+            let policy = policy.clone();
             let storage = storage.clone();
             let handle = thread::spawn::<_, anyhow::Result<()>>(move || {
                 println!("[{}-{}] Rendering README...", krate_name, version.num);
-                let readme = get_readme(base_config.uploader(), &client, &version, &krate_name)?;
+                let readme = get_readme(
+                    base_config.uploader(),
+                    &client,
+                    &version,
+                    &krate_name,
+                    &policy,
+                )?;
                 if !readme.is_empty() {
                     let rt = tokio::runtime::Builder::new_current_thread()
                         .enable_all()
@@ -147,14 +171,17 @@ fn get_readme(
     client: &Client,
     version: &Version,
     krate_name: &str,
+    policy: &SanitizationPolicy,
 ) -> anyhow::Result<String> {
     let pkg_name = format!("{}-{}", krate_name, version.num);
 
-    let location = uploader.crate_location(krate_name, &version.num.to_string());
+    let location = uploader.crate_location(krate_name, &version.num.to_string(), None);
 
     let location = match uploader {
         Uploader::S3 { .. } => location,
-        Uploader::Local => format!("http://localhost:8888/{location}"),
+        Uploader::Local | Uploader::InMemory { .. } => {
+            format!("http://localhost:8888/{location}")
+        }
     };
 
     let mut extra_headers = header::HeaderMap::new();
@@ -174,10 +201,16 @@ fn get_readme(
 
     let reader = GzDecoder::new(response);
     let archive = Archive::new(reader);
-    render_pkg_readme(archive, &pkg_name)
+    render_pkg_readme(archive, &pkg_name, policy)
 }
 
-fn render_pkg_readme<R: Read>(mut archive: Archive<R>, pkg_name: &str) -> anyhow::Result<String> {
+/// Renders the readme that is referenced by the manifest of an unpacked
+/// crate file.
+pub(crate) fn render_pkg_readme<R: Read>(
+    mut archive: Archive<R>,
+    pkg_name: &str,
+    policy: &SanitizationPolicy,
+) -> anyhow::Result<String> {
     let mut entries = archive.entries().context("Invalid tar archive entries")?;
 
     let manifest: Manifest = {
@@ -202,11 +235,12 @@ fn render_pkg_readme<R: Read>(mut archive: Archive<R>, pkg_name: &str) -> anyhow
         // Would need access to cargo_vcs_info
         let pkg_path_in_vcs = None;
 
-        text_to_html(
+        text_to_html_with_policy(
             &contents,
             &readme_path,
             manifest.package.repository.as_deref(),
             pkg_path_in_vcs,
+            policy,
         )
     };
     Ok(rendered)
@@ -234,6 +268,7 @@ fn find_file_by_path<R: Read>(
 
 #[cfg(test)]
 pub mod tests {
+    use crates_io_markdown::SanitizationPolicy;
     use crates_io_tarball::TarballBuilder;
 
     use super::render_pkg_readme;
@@ -250,8 +285,12 @@ readme = "README.md"
             .add_file("foo-0.0.1/README.md", b"readme")
             .build_unzipped();
 
-        let result =
-            render_pkg_readme(tar::Archive::new(&*serialized_archive), "foo-0.0.1").unwrap();
+        let result = render_pkg_readme(
+            tar::Archive::new(&*serialized_archive),
+            "foo-0.0.1",
+            &SanitizationPolicy::default(),
+        )
+        .unwrap();
         assert!(result.contains("readme"))
     }
 
@@ -267,7 +306,8 @@ readme = "README.md"
 
         assert_err!(render_pkg_readme(
             tar::Archive::new(&*serialized_archive),
-            "foo-0.0.1"
+            "foo-0.0.1",
+            &SanitizationPolicy::default(),
         ));
     }
 
@@ -282,8 +322,12 @@ readme = "README.md"
             .add_file("foo-0.0.1/README.md", b"readme")
             .build_unzipped();
 
-        let result =
-            render_pkg_readme(tar::Archive::new(&*serialized_archive), "foo-0.0.1").unwrap();
+        let result = render_pkg_readme(
+            tar::Archive::new(&*serialized_archive),
+            "foo-0.0.1",
+            &SanitizationPolicy::default(),
+        )
+        .unwrap();
         assert!(result.contains("readme"))
     }
 
@@ -300,8 +344,12 @@ repository = "https://github.com/foo/foo"
             .add_file("foo-0.0.1/README.md", b"readme [link](./Other.md)")
             .build_unzipped();
 
-        let result =
-            render_pkg_readme(tar::Archive::new(&*serialized_archive), "foo-0.0.1").unwrap();
+        let result = render_pkg_readme(
+            tar::Archive::new(&*serialized_archive),
+            "foo-0.0.1",
+            &SanitizationPolicy::default(),
+        )
+        .unwrap();
         assert!(result.contains("\"https://github.com/foo/foo/blob/HEAD/./Other.md\""))
     }
 
@@ -321,8 +369,12 @@ repository = "https://github.com/foo/foo"
             )
             .build_unzipped();
 
-        let result =
-            render_pkg_readme(tar::Archive::new(&*serialized_archive), "foo-0.0.1").unwrap();
+        let result = render_pkg_readme(
+            tar::Archive::new(&*serialized_archive),
+            "foo-0.0.1",
+            &SanitizationPolicy::default(),
+        )
+        .unwrap();
         assert!(result.contains("docs/readme"));
         assert!(result.contains("\"https://github.com/foo/foo/blob/HEAD/docs/./Other.md\""))
     }
@@ -0,0 +1,39 @@
+use crate::{admin::dialoguer, db, models::UserSession, schema::users};
+
+use diesel::prelude::*;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "revoke-sessions",
+    about = "Revoke all web sessions of a user, e.g. if the account is suspected to be compromised."
+)]
+pub struct Opts {
+    /// GitHub login of the user
+    login: String,
+    /// Don't ask for confirmation: yes, we are sure. Best for scripting.
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub fn run(opts: Opts) {
+    let conn = &mut db::oneoff_connection().unwrap();
+
+    let user_id: i32 = users::table
+        .filter(users::gh_login.eq(&opts.login))
+        .select(users::id)
+        .first(conn)
+        .unwrap();
+
+    if !opts.yes {
+        let prompt = format!(
+            "Are you sure you want to revoke all sessions of {} ({user_id})?",
+            opts.login
+        );
+        if !dialoguer::confirm(&prompt) {
+            return;
+        }
+    }
+
+    let revoked = UserSession::revoke_all_for_user(conn, user_id, None).unwrap();
+    println!("Revoked {revoked} sessions of {}", opts.login);
+}
@@ -0,0 +1,40 @@
+use crate::{admin::dialoguer, db, models::ApiToken, schema::users};
+
+use diesel::prelude::*;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "revoke-tokens",
+    about = "Revoke all API tokens of a user, e.g. if the tokens are suspected to be leaked."
+)]
+pub struct Opts {
+    /// GitHub login of the user
+    #[arg(long = "user")]
+    login: String,
+    /// Don't ask for confirmation: yes, we are sure. Best for scripting.
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub fn run(opts: Opts) {
+    let conn = &mut db::oneoff_connection().unwrap();
+
+    let user_id: i32 = users::table
+        .filter(users::gh_login.eq(&opts.login))
+        .select(users::id)
+        .first(conn)
+        .unwrap();
+
+    if !opts.yes {
+        let prompt = format!(
+            "Are you sure you want to revoke all API tokens of {} ({user_id})?",
+            opts.login
+        );
+        if !dialoguer::confirm(&prompt) {
+            return;
+        }
+    }
+
+    let revoked = ApiToken::revoke_all_for_user(conn, user_id).unwrap();
+    println!("Revoked {revoked} API tokens of {}", opts.login);
+}
@@ -0,0 +1,380 @@
+//! Fills a local development database with realistic looking data.
+//!
+//! Crates are published through the regular `PUT /api/v1/crates/new` route,
+//! so that they go through the same validation, storage uploads and
+//! background jobs as crates published by cargo. The index updates and readme
+//! renderings are performed once the `background-worker` is started.
+
+use crate::models::{ApiToken, CrateOwnerInvitation, NewCrateOwnerInvitationOutcome, NewUser};
+use crate::schema::{categories, crate_owners, crates, emails, version_downloads, versions};
+use crate::storage::StorageConfig;
+use crate::{build_handler, config, App, Env};
+use anyhow::{anyhow, Context};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use http::{header, Request, StatusCode};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use secrecy::ExposeSecret;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const NAME_PREFIXES: &[&str] = &[
+    "async", "serde", "tiny", "fast", "rusty", "hyper", "json", "yaml", "log", "http", "crypto",
+    "image", "cli", "parse", "config", "bench", "wasm", "sql", "tokio", "gfx", "audio", "geo",
+    "time", "rand", "text",
+];
+
+const NAME_SUFFIXES: &[&str] = &[
+    "", "-core", "-utils", "-derive", "-macros", "-rs", "-lite", "-sys", "_helpers", "-cli",
+    "-types", "-ext",
+];
+
+const KEYWORDS: &[&str] = &[
+    "async", "parser", "cli", "web", "http", "json", "serialization", "database", "crypto",
+    "graphics", "audio", "embedded", "no-std", "testing", "logging", "macro", "network", "wasm",
+];
+
+const LICENSES: &[&str] = &["MIT", "Apache-2.0", "MIT OR Apache-2.0", "BSD-3-Clause", "MPL-2.0"];
+
+const USER_AGENT: &str = "crates-seed-data";
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "seed-data",
+    about = "Fills the local development database with generated users, crates, versions, \
+        owners, downloads and API tokens.",
+    after_help = "Warning: this is meant for local development only and refuses to run \
+        against S3 storage or in production."
+)]
+pub struct Opts {
+    /// How many users should be created.
+    #[arg(long, default_value = "10")]
+    users: usize,
+
+    /// How many crates should be published.
+    #[arg(long, default_value = "50")]
+    crates: usize,
+
+    /// The maximum number of versions that are published per crate.
+    #[arg(long, default_value = "5")]
+    max_versions: usize,
+
+    /// For how many days download statistics are generated.
+    #[arg(long, default_value = "90")]
+    download_days: i64,
+
+    /// The seed of the random number generator, to generate the same data on
+    /// every run.
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+pub fn run(opts: Opts) -> anyhow::Result<()> {
+    if opts.users == 0 {
+        return Err(anyhow!("at least one user is needed to publish crates"));
+    }
+
+    let mut config = config::Server::default();
+    if config.env() == Env::Production {
+        return Err(anyhow!("seed-data must not be used in production"));
+    }
+    if !matches!(config.storage, StorageConfig::LocalFileSystem { .. }) {
+        return Err(anyhow!("seed-data only supports the local file system storage"));
+    }
+
+    // The generated users publish a lot of crates in a short amount of time.
+    config.publish_rate_limit.burst = i32::MAX;
+    config.new_version_rate_limit = None;
+    config.quarantine_account_age = None;
+
+    let app = Arc::new(App::new(config, None));
+    let router = build_handler(app.clone());
+
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .context("Failed to initialize tokio runtime")?;
+
+    let mut rng = match opts.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let conn = &mut *app.db_write()?;
+
+    let users = create_users(conn, &app, &mut rng, opts.users)?;
+    println!("Created {} users", users.len());
+
+    let categories: Vec<String> = categories::table.select(categories::slug).load(conn)?;
+
+    let mut published: Vec<(String, semver::Version)> = Vec::new();
+    let mut names = HashSet::new();
+    for _ in 0..opts.crates {
+        let name = crate_name(&mut rng, &mut names);
+        let (_, owner_token) = users.choose(&mut rng).unwrap();
+
+        let num_keywords = rng.gen_range(0..=5);
+        let keywords = KEYWORDS
+            .choose_multiple(&mut rng, num_keywords)
+            .collect::<Vec<_>>();
+        let num_categories = rng.gen_range(0..=2);
+        let categories = categories
+            .choose_multiple(&mut rng, num_categories)
+            .collect::<Vec<_>>();
+        let license = LICENSES.choose(&mut rng).unwrap();
+
+        let num_versions = rng.gen_range(1..=opts.max_versions.max(1));
+        let mut version = semver::Version::new(0, rng.gen_range(0..3), 0);
+        for _ in 0..num_versions {
+            // Only crates that were published before this one are candidates,
+            // so that the dependency graph stays acyclic.
+            let candidates = published
+                .iter()
+                .filter(|(dependency, _)| *dependency != name)
+                .collect::<Vec<_>>();
+            let num_dependencies = rng.gen_range(0..=3);
+            let dependencies = candidates
+                .choose_multiple(&mut rng, num_dependencies)
+                .map(|(name, version)| {
+                    json!({
+                        "name": name,
+                        "version_req": format!("^{version}"),
+                        "features": [],
+                        "optional": false,
+                        "default_features": true,
+                        "target": null,
+                        "kind": "normal",
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let metadata = json!({
+                "name": name,
+                "vers": version.to_string(),
+                "deps": dependencies,
+                "features": {},
+                "description": format!("A generated crate for local development: {name}"),
+                "homepage": null,
+                "documentation": format!("https://docs.rs/{name}"),
+                "readme": format!("# {name}\n\nThis crate was generated by `seed-data`.\n"),
+                "readme_file": "README.md",
+                "keywords": keywords,
+                "categories": categories,
+                "license": license,
+                "license_file": null,
+                "repository": format!("https://github.com/seed-data/{name}"),
+                "links": null,
+            });
+
+            let body = publish_body(&metadata, &tarball(&name, &version)?);
+            let request = Request::put("/api/v1/crates/new")
+                .header(header::AUTHORIZATION, owner_token)
+                .header(header::USER_AGENT, USER_AGENT)
+                .header("x-real-ip", "127.0.0.1")
+                .body(hyper::Body::from(body))?;
+
+            let response = rt.block_on(router.clone().oneshot(request))?;
+            if response.status() != StatusCode::OK {
+                let status = response.status();
+                let body = rt.block_on(hyper::body::to_bytes(response.into_body()))?;
+                let body = String::from_utf8_lossy(&body);
+                return Err(anyhow!("Failed to publish {name}@{version}: {status} {body}"));
+            }
+
+            published.push((name.clone(), version.clone()));
+            version = next_version(&mut rng, &version);
+        }
+
+        add_owners(conn, &app, &mut rng, &name, &users)?;
+    }
+    println!("Published {} versions of {} crates", published.len(), names.len());
+
+    let rows = create_downloads(conn, &mut rng, opts.download_days)?;
+    println!("Created {rows} download statistics rows");
+
+    Ok(())
+}
+
+/// Creates users with a verified email address and an API token each, which
+/// is used to publish their crates. Some users get additional tokens.
+fn create_users(
+    conn: &mut PgConnection,
+    app: &App,
+    rng: &mut StdRng,
+    count: usize,
+) -> anyhow::Result<Vec<(i32, String)>> {
+    let mut users = Vec::with_capacity(count);
+    for _ in 0..count {
+        // Negative GitHub IDs can't collide with real GitHub accounts
+        let gh_id = -rng.gen_range(1..i32::MAX);
+        let login = format!("seed-user-{}", -gh_id);
+
+        let user = NewUser {
+            gh_id,
+            gh_login: &login,
+            name: Some(&login),
+            gh_avatar: None,
+            gh_access_token: Cow::Borrowed("seed-data"),
+        }
+        .create_or_update(None, &app.emails, conn)?;
+
+        diesel::insert_into(emails::table)
+            .values((
+                emails::user_id.eq(user.id),
+                emails::email.eq(format!("{login}@example.com")),
+                emails::verified.eq(true),
+            ))
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+
+        for i in 0..rng.gen_range(0..3) {
+            let name = format!("seed-data-{i}");
+            ApiToken::insert(conn, user.id, &name).map_err(|error| anyhow!("{error}"))?;
+        }
+
+        let token = ApiToken::insert(conn, user.id, "seed-data-publish")
+            .map_err(|error| anyhow!("{error}"))?;
+        users.push((user.id, token.plaintext.expose_secret().clone()));
+    }
+
+    Ok(users)
+}
+
+/// Invites and accepts up to two additional owners for the crate.
+fn add_owners(
+    conn: &mut PgConnection,
+    app: &App,
+    rng: &mut StdRng,
+    crate_name: &str,
+    users: &[(i32, String)],
+) -> anyhow::Result<()> {
+    let crate_id: i32 = crates::table
+        .filter(crates::name.eq(crate_name))
+        .select(crates::id)
+        .first(conn)?;
+
+    let invited_by: i32 = crate_owners::table
+        .filter(crate_owners::crate_id.eq(crate_id))
+        .select(crate_owners::owner_id)
+        .first(conn)?;
+
+    let num_owners = rng.gen_range(0..=2);
+    for (user_id, _) in users.choose_multiple(rng, num_owners) {
+        if *user_id == invited_by {
+            continue;
+        }
+
+        let outcome =
+            CrateOwnerInvitation::create(*user_id, invited_by, crate_id, conn, &app.config)
+                .map_err(|error| anyhow!("{error}"))?;
+
+        if let NewCrateOwnerInvitationOutcome::InviteCreated { .. } = outcome {
+            CrateOwnerInvitation::find_by_id(*user_id, crate_id, conn)
+                .and_then(|invitation| invitation.accept(conn, &app.config))
+                .map_err(|error| anyhow!("{error}"))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Generates daily download counts for all versions, with a few popular
+/// versions and a long tail of rarely downloaded ones.
+fn create_downloads(conn: &mut PgConnection, rng: &mut StdRng, days: i64) -> QueryResult<usize> {
+    let version_ids: Vec<i32> = versions::table.select(versions::id).load(conn)?;
+    let today = Utc::now().date_naive();
+
+    let mut rows = 0;
+    for version_id in version_ids {
+        let popularity = rng.gen_range(0f64..1.0).powi(4);
+        let mut downloads = (popularity * 10_000.0) as i32;
+
+        let values = (0..days)
+            .map(|day| {
+                downloads = (downloads + rng.gen_range(-100..=100)).max(0);
+                (
+                    version_downloads::version_id.eq(version_id),
+                    version_downloads::downloads.eq(downloads),
+                    version_downloads::date.eq(today - Duration::days(day)),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        rows += diesel::insert_into(version_downloads::table)
+            .values(&values)
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+    }
+
+    Ok(rows)
+}
+
+fn crate_name(rng: &mut StdRng, names: &mut HashSet<String>) -> String {
+    let prefix = NAME_PREFIXES.choose(rng).unwrap();
+    let suffix = NAME_SUFFIXES.choose(rng).unwrap();
+
+    let mut name = format!("{prefix}{suffix}");
+    while names.contains(&name) {
+        name = format!("{prefix}{suffix}{}", rng.gen_range(2..1000));
+    }
+
+    names.insert(name.clone());
+    name
+}
+
+fn next_version(rng: &mut StdRng, version: &semver::Version) -> semver::Version {
+    match rng.gen_range(0..10) {
+        0 => semver::Version::new(version.major + 1, 0, 0),
+        1..=3 => semver::Version::new(version.major, version.minor + 1, 0),
+        _ => semver::Version::new(version.major, version.minor, version.patch + 1),
+    }
+}
+
+/// Builds a `.crate` file with a manifest, a readme and an empty library.
+fn tarball(name: &str, version: &semver::Version) -> anyhow::Result<Vec<u8>> {
+    let manifest = format!(
+        "[package]\nname = \"{name}\"\nversion = \"{version}\"\nedition = \"2021\"\n\
+         rust-version = \"1.60\"\n"
+    );
+    let readme = format!("# {name}\n");
+
+    let files = [
+        ("Cargo.toml", manifest.as_bytes()),
+        ("README.md", readme.as_bytes()),
+        ("src/lib.rs", b"".as_slice()),
+    ];
+
+    let mut tarball = Vec::new();
+    {
+        let mut archive = tar::Builder::new(GzEncoder::new(&mut tarball, Compression::default()));
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_path(format!("{name}-{version}/{path}"))?;
+            header.set_size(content.len() as u64);
+            header.set_cksum();
+            archive.append(&header, content)?;
+        }
+        archive.into_inner()?.finish()?;
+    }
+
+    Ok(tarball)
+}
+
+/// Encodes the publish request body like cargo does: the length prefixed
+/// JSON metadata, followed by the length prefixed crate file.
+fn publish_body(metadata: &serde_json::Value, tarball: &[u8]) -> Vec<u8> {
+    let json = metadata.to_string();
+
+    let mut body = Vec::with_capacity(8 + json.len() + tarball.len());
+    body.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    body.extend_from_slice(json.as_bytes());
+    body.extend_from_slice(&(tarball.len() as u32).to_le_bytes());
+    body.extend_from_slice(tarball);
+    body
+}
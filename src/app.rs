@@ -1,22 +1,38 @@
 //! Application-wide components in a struct accessible from each request
 
+use crate::auth::Providers;
 use crate::config;
 use crate::db::{ConnectionConfig, DieselPool, DieselPooledConn, PoolError};
 use std::ops::Deref;
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::domains::{DomainClient, RealDomainClient};
 use crate::downloads_counter::DownloadsCounter;
 use crate::email::Emails;
 use crate::github::{GitHubClient, RealGitHubClient};
 use crate::metrics::{InstanceMetrics, ServiceMetrics};
+use crate::models::{ActiveLegalHolds, DependencyGraph, MaintenanceModeSetting};
 use crate::storage::Storage;
+use crate::upstream::{RealUpstreamClient, UpstreamClient};
+use crate::util::circuit_breaker::{CircuitBreaker, CircuitBreakers};
+use crate::util::errors::AppResult;
+use crate::util::signing::Signer;
+use crate::views::EncodableCategoryTreeNode;
 use axum::extract::{FromRef, FromRequestParts, State};
 use diesel::r2d2;
+use diesel::{PgConnection, QueryResult};
 use moka::future::{Cache, CacheBuilder};
-use oauth2::basic::BasicClient;
 use reqwest::blocking::Client;
 use scheduled_thread_pool::ScheduledThreadPool;
+use webauthn_rs::{Webauthn, WebauthnBuilder};
+
+/// The maximum number of resolved dependency graphs kept in memory
+const DEPENDENCY_GRAPH_CACHE_SIZE: u64 = 1000;
+
+/// How long users have to complete a passkey registration or sign in
+pub const PASSKEY_TIMEOUT: Duration = Duration::from_secs(5 * 60);
 
 /// The `App` struct holds the main components of the application like
 /// the database connection pool and configurations
@@ -30,8 +46,18 @@ pub struct App {
     /// GitHub API client
     pub github: Box<dyn GitHubClient>,
 
-    /// The GitHub OAuth2 configuration
-    pub github_oauth: BasicClient,
+    /// Client used to verify the ownership of domains
+    pub domains: Box<dyn DomainClient>,
+
+    /// Client used to fetch crates from the upstream registry, if the
+    /// upstream proxy mode is enabled
+    pub upstream: Option<Box<dyn UpstreamClient>>,
+
+    /// The OAuth providers that users can sign in with
+    pub oauth_providers: Providers,
+
+    /// The WebAuthn configuration for passkeys, if local authentication is enabled
+    pub webauthn: Option<Webauthn>,
 
     /// The server configuration
     pub config: config::Server,
@@ -42,14 +68,27 @@ pub struct App {
     /// `version_id` is only cached under the canonical spelling of the crate name.
     pub(crate) version_id_cacher: Cache<(String, String), i32>,
 
+    /// The active legal holds, which block downloads even if the version ID
+    /// is cached or the database is not available
+    pub legal_holds: ActiveLegalHolds,
+
+    /// Cache of the category tree returned by the `GET /categories/tree` route
+    pub(crate) category_tree_cache: Cache<(), Arc<Vec<EncodableCategoryTreeNode>>>,
+
+    /// Cache of the resolved dependency graphs, keyed by `(version_id, depth)`
+    pub(crate) dependency_graph_cache: Cache<(i32, u32), Arc<DependencyGraph>>,
+
     /// Count downloads and periodically persist them in the database
     pub downloads_counter: DownloadsCounter,
 
     /// Backend used to send emails
-    pub emails: Emails,
+    pub emails: Arc<Emails>,
 
     pub storage: Arc<Storage>,
 
+    /// Circuit breakers of the external dependencies used while handling requests
+    pub circuit_breakers: CircuitBreakers,
+
     /// Metrics related to the service as a whole
     pub service_metrics: ServiceMetrics,
 
@@ -68,6 +107,9 @@ pub struct App {
 
     /// In-flight request counters for the `balance_capacity` middleware.
     pub balance_capacity: BalanceCapacityState,
+
+    /// Runtime state of the maintenance mode, which can be toggled via the admin API.
+    pub maintenance_mode: MaintenanceModeState,
 }
 
 impl App {
@@ -75,25 +117,45 @@ impl App {
     ///
     /// Configures and sets up:
     ///
-    /// - GitHub OAuth
+    /// - OAuth providers
     /// - Database connection pools
     /// - A `git2::Repository` instance from the index repo checkout (that server.rs ensures exists)
     pub fn new(config: config::Server, http_client: Option<Client>) -> App {
-        use oauth2::{AuthUrl, TokenUrl};
-
         let instance_metrics =
             InstanceMetrics::new().expect("could not initialize instance metrics");
 
-        let github = Box::new(RealGitHubClient::new(http_client.clone()));
+        let emails = Arc::new(Emails::from_environment(&config));
+        let storage = Arc::new(Storage::from_config(&config.storage));
 
-        let github_oauth = BasicClient::new(
-            config.gh_client_id.clone(),
-            Some(config.gh_client_secret.clone()),
-            AuthUrl::new(String::from("https://github.com/login/oauth/authorize")).unwrap(),
-            Some(
-                TokenUrl::new(String::from("https://github.com/login/oauth/access_token")).unwrap(),
-            ),
-        );
+        let circuit_breakers = CircuitBreakers {
+            github: Arc::new(CircuitBreaker::new("github")),
+            storage: storage.circuit_breaker(),
+            email: emails.circuit_breaker(),
+        };
+
+        let github = Box::new(RealGitHubClient::new(
+            http_client.clone(),
+            circuit_breakers.github.clone(),
+        ));
+        let domains = Box::new(RealDomainClient::new(http_client.clone()));
+
+        let upstream = config.upstream.as_ref().map(|upstream| {
+            Box::new(RealUpstreamClient::new(http_client.clone(), upstream))
+                as Box<dyn UpstreamClient>
+        });
+
+        let oauth_providers = Providers::from_config(&config);
+
+        let webauthn = config.local_auth.as_ref().map(|local_auth| {
+            WebauthnBuilder::new(&local_auth.webauthn_rp_id, &local_auth.webauthn_origin)
+                .and_then(|builder| {
+                    builder
+                        .rp_name("crates.io")
+                        .timeout(PASSKEY_TIMEOUT)
+                        .build()
+                })
+                .expect("invalid WebAuthn configuration")
+        });
 
         let thread_pool = Arc::new(ScheduledThreadPool::new(config.db.helper_threads));
 
@@ -159,6 +221,14 @@ impl App {
             .time_to_live(config.version_id_cache_ttl)
             .build();
 
+        let category_tree_cache = CacheBuilder::new(1)
+            .time_to_live(config.category_tree_cache_ttl)
+            .build();
+
+        let dependency_graph_cache = CacheBuilder::new(DEPENDENCY_GRAPH_CACHE_SIZE)
+            .time_to_live(config.dependency_graph_cache_ttl)
+            .build();
+
         let fastboot_client = match config.use_fastboot.as_deref() {
             Some("staging-experimental") => Some(reqwest::Client::new()),
             _ => None,
@@ -168,16 +238,27 @@ impl App {
             primary_database,
             read_only_replica_database: replica_database,
             github,
-            github_oauth,
+            domains,
+            upstream,
+            oauth_providers,
+            webauthn,
             version_id_cacher,
+            legal_holds: ActiveLegalHolds::default(),
+            category_tree_cache,
+            dependency_graph_cache,
             downloads_counter: DownloadsCounter::new(),
-            emails: Emails::from_environment(&config),
-            storage: Arc::new(Storage::from_config(&config.storage)),
+            emails,
+            storage,
+            circuit_breakers,
             service_metrics: ServiceMetrics::new().expect("could not initialize service metrics"),
             instance_metrics,
             http_client,
             fastboot_client,
             balance_capacity: Default::default(),
+            maintenance_mode: MaintenanceModeState::new(
+                config.maintenance_mode,
+                config.maintenance_retry_after,
+            ),
             config,
         }
     }
@@ -202,6 +283,12 @@ impl App {
         &self.config.session_key
     }
 
+    /// Signs and verifies links that work without being logged in, e.g. in
+    /// notification emails. The key is derived from the session key.
+    pub fn link_signer(&self) -> Signer {
+        Signer::new(self.session_key().signing())
+    }
+
     /// Obtain a read/write database connection from the primary pool
     #[instrument(skip_all)]
     pub fn db_write(&self) -> Result<DieselPooledConn<'_>, PoolError> {
@@ -267,6 +354,25 @@ impl App {
             (Err(error), _) => Err(error),
         }
     }
+
+    /// Reloads the active legal holds from the database, and drops the cached
+    /// version IDs if they changed, so that the next downloads check the
+    /// database again.
+    pub fn refresh_legal_holds(&self) -> AppResult<()> {
+        let conn = &mut *self.db_read_prefer_primary()?;
+        if self.legal_holds.refresh(conn)? {
+            self.version_id_cacher.invalidate_all();
+        }
+        Ok(())
+    }
+
+    /// Reloads the maintenance mode set through the admin API, which might
+    /// have been changed by another instance.
+    pub fn refresh_maintenance_mode(&self) -> AppResult<()> {
+        let conn = &mut *self.db_read_prefer_primary()?;
+        self.maintenance_mode.refresh(conn)?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Default)]
@@ -275,6 +381,54 @@ pub struct BalanceCapacityState {
     pub in_flight_non_dl_requests: AtomicUsize,
 }
 
+/// While the maintenance mode is enabled all write requests are rejected with
+/// a `503 Service Unavailable` response, while read requests continue to work.
+///
+/// The maintenance mode is either enabled through the `MAINTENANCE_MODE`
+/// environment variable, which can not be disabled at runtime, or through the
+/// admin API, which persists it in the database so that it applies to all
+/// instances, see [`MaintenanceModeSetting`].
+#[derive(Debug)]
+pub struct MaintenanceModeState {
+    forced: bool,
+    enabled: AtomicBool,
+    retry_after_secs: AtomicU64,
+}
+
+impl MaintenanceModeState {
+    pub fn new(forced: bool, retry_after: Duration) -> Self {
+        Self {
+            forced,
+            enabled: AtomicBool::new(false),
+            retry_after_secs: AtomicU64::new(retry_after.as_secs()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.forced || self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// The estimated time until the maintenance is over, which is reported
+    /// to clients in the `Retry-After` header.
+    pub fn retry_after(&self) -> Duration {
+        Duration::from_secs(self.retry_after_secs.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, enabled: bool, retry_after: Duration) {
+        self.retry_after_secs
+            .store(retry_after.as_secs(), Ordering::Relaxed);
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Loads the maintenance mode set through the admin API from the database.
+    pub fn refresh(&self, conn: &mut PgConnection) -> QueryResult<()> {
+        if let Some(setting) = MaintenanceModeSetting::load(conn)? {
+            self.set(setting.enabled, setting.retry_after());
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, FromRequestParts)]
 #[from_request(via(State))]
 pub struct AppState(pub Arc<App>);
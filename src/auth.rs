@@ -1,21 +1,37 @@
 use crate::controllers;
+use crate::controllers::krate::publish::COUNTRY_HEADER;
 use crate::controllers::util::RequestPartsExt;
+use crate::middleware::app::RequestApp;
 use crate::middleware::log_request::RequestLogExt;
 use crate::middleware::session::RequestSession;
 use crate::models::token::{CrateScope, EndpointScope};
-use crate::models::{ApiToken, User};
+use crate::models::{
+    ApiToken, AuditAction, NewAuditLogEntry, NewUserNotification, TokenOrigin, User, UserSession,
+};
 use crate::util::errors::{
     account_locked, forbidden, internal, AppError, AppResult, InsecurelyGeneratedTokenRevoked,
 };
 use chrono::Utc;
-use diesel::PgConnection;
+use diesel::{Connection, PgConnection};
 use http::header;
+use std::net::IpAddr;
+
+mod providers;
+
+pub use self::providers::{
+    GitHubProvider, GitLabProvider, GoogleProvider, Provider, ProviderUser, Providers,
+};
+
+/// The provider name of the identities of users that sign in with a password
+/// instead of an OAuth provider, see `LocalAuthConfig`.
+pub const LOCAL_PROVIDER: &str = "local";
 
 #[derive(Debug, Clone)]
 pub struct AuthCheck {
     allow_token: bool,
     endpoint_scope: Option<EndpointScope>,
     crate_name: Option<String>,
+    allow_any_crate: bool,
 }
 
 impl AuthCheck {
@@ -27,6 +43,7 @@ impl AuthCheck {
             allow_token: true,
             endpoint_scope: None,
             crate_name: None,
+            allow_any_crate: false,
         }
     }
 
@@ -36,6 +53,7 @@ impl AuthCheck {
             allow_token: false,
             endpoint_scope: None,
             crate_name: None,
+            allow_any_crate: false,
         }
     }
 
@@ -44,6 +62,7 @@ impl AuthCheck {
             allow_token: self.allow_token,
             endpoint_scope: Some(endpoint_scope),
             crate_name: self.crate_name.clone(),
+            allow_any_crate: self.allow_any_crate,
         }
     }
 
@@ -52,6 +71,19 @@ impl AuthCheck {
             allow_token: self.allow_token,
             endpoint_scope: self.endpoint_scope,
             crate_name: Some(crate_name.to_string()),
+            allow_any_crate: self.allow_any_crate,
+        }
+    }
+
+    /// Allows tokens with crate scopes for an endpoint that does not deal with
+    /// a single crate, but does not expose any crate data either, e.g. the
+    /// `config.json` file of the index.
+    pub fn for_any_crate(&self) -> Self {
+        Self {
+            allow_token: self.allow_token,
+            endpoint_scope: self.endpoint_scope,
+            crate_name: self.crate_name.clone(),
+            allow_any_crate: true,
         }
     }
 
@@ -105,8 +137,9 @@ impl AuthCheck {
             // The token does not have any crate scopes.
             (Some(token_scopes), _) if token_scopes.is_empty() => true,
 
-            // The token has crate scopes, but the endpoint does not deal with crates.
-            (Some(_), None) => false,
+            // The token has crate scopes, but the endpoint does not deal with a single crate, so
+            // it might expose crates outside of the scopes.
+            (Some(_), None) => self.allow_any_crate,
 
             // The token is NOT a legacy token, and the endpoint allows a certain endpoint scope or a legacy token.
             (Some(token_scopes), Some(crate_name)) => token_scopes
@@ -125,6 +158,7 @@ pub enum Authentication {
 #[derive(Debug)]
 pub struct CookieAuthentication {
     user: User,
+    session: UserSession,
 }
 
 #[derive(Debug)]
@@ -142,6 +176,14 @@ impl Authentication {
         self.api_token().map(|token| token.id)
     }
 
+    /// Returns the ID of the `UserSession` for cookie authentication.
+    pub fn session_id(&self) -> Option<i32> {
+        match self {
+            Authentication::Cookie(cookie) => Some(cookie.session.id),
+            _ => None,
+        }
+    }
+
     pub fn api_token(&self) -> Option<&ApiToken> {
         match self {
             Authentication::Token(token) => Some(&token.token),
@@ -171,14 +213,30 @@ fn authenticate_via_cookie<T: RequestPartsExt>(
         return Ok(None);
     };
 
+    // Cookies from before sessions were tracked in the database are ignored,
+    // which requires these users to log in again.
+    let Some(session_token) = req.session().get("session_token") else {
+        return Ok(None);
+    };
+
     let user = User::find(conn, id)
         .map_err(|err| err.chain(internal("user_id from cookie not found in database")))?;
 
     ensure_not_locked(&user)?;
 
+    let Some(session) = UserSession::find_active(conn, id, &session_token)? else {
+        // Clear the cookie, so that the browser is logged out for good
+        req.session().remove("user_id");
+        req.session().remove("session_token");
+
+        let error_message = "session from cookie was revoked or has expired";
+        return Err(internal(error_message).chain(forbidden()));
+    };
+
     req.request_log().add("uid", id);
+    req.request_log().add("sessionid", session.id);
 
-    Ok(Some(CookieAuthentication { user }))
+    Ok(Some(CookieAuthentication { user, session }))
 }
 
 #[instrument(skip_all)]
@@ -211,9 +269,73 @@ fn authenticate_via_token<T: RequestPartsExt>(
     req.request_log().add("uid", token.user_id);
     req.request_log().add("tokenid", token.id);
 
+    ensure_ip_allowed(req, conn, &token)?;
+    record_token_origin(req, conn, &token);
+
     Ok(Some(TokenAuthentication { user, token }))
 }
 
+/// Rejects requests from IP addresses outside of the allowed CIDR blocks of
+/// the token, and records the violation in the audit log.
+fn ensure_ip_allowed<T: RequestPartsExt>(
+    req: &T,
+    conn: &mut PgConnection,
+    token: &ApiToken,
+) -> AppResult<()> {
+    let ip_header = req.headers().get("x-real-ip").and_then(|h| h.to_str().ok());
+
+    let ip = ip_header.and_then(|ip| ip.parse::<IpAddr>().ok());
+    if token.allows_ip(ip) {
+        return Ok(());
+    }
+
+    let mut entry = NewAuditLogEntry::new(token.user_id, AuditAction::TokenIpRejected);
+    entry.api_token_id = Some(token.id);
+    entry.ip_address = ip_header;
+    entry.details = json!({ "allowed_cidrs": token.allowed_cidrs });
+
+    // The violation is recorded in a new transaction, so that a failure (e.g.
+    // in read only mode) does not affect the rest of the request
+    if let Err(error) = conn.transaction(|conn| entry.insert(conn)) {
+        warn!(
+            ?error,
+            "Failed to record rejected token IP address in the audit log"
+        );
+    }
+
+    let error_message = "IP address not allowed for this token";
+    Err(internal(error_message).chain(forbidden()))
+}
+
+/// Records the IP address that the token is used from, and notifies the user
+/// if the token was used from other IP addresses before.
+fn record_token_origin<T: RequestPartsExt>(req: &T, conn: &mut PgConnection, token: &ApiToken) {
+    let headers = req.headers();
+    let Some(ip_address) = headers.get("x-real-ip").and_then(|h| h.to_str().ok()) else {
+        return;
+    };
+    let country = headers.get(COUNTRY_HEADER).and_then(|h| h.to_str().ok());
+
+    // The origin is recorded in a new transaction, so that a failure (e.g. on
+    // a read-only replica connection) does not affect the rest of the request
+    let result = conn.transaction(|conn| -> AppResult<()> {
+        let new_country = match token.record_origin(conn, ip_address, country)? {
+            TokenOrigin::Known | TokenOrigin::First => return Ok(()),
+            TokenOrigin::NewIpAddress => false,
+            TokenOrigin::NewCountry => true,
+        };
+
+        NewUserNotification::token_used_from_new_ip(token, ip_address, country, new_country)
+            .create(conn, &req.app().emails)?;
+
+        Ok(())
+    });
+
+    if let Err(error) = result {
+        debug!(?error, "Failed to record the IP address of the API token");
+    }
+}
+
 #[instrument(skip_all)]
 fn authenticate<T: RequestPartsExt>(req: &T, conn: &mut PgConnection) -> AppResult<Authentication> {
     controllers::util::verify_origin(req)?;
@@ -234,7 +356,18 @@ fn authenticate<T: RequestPartsExt>(req: &T, conn: &mut PgConnection) -> AppResu
     return Err(internal("no cookie session or auth header found").chain(forbidden()));
 }
 
-fn ensure_not_locked(user: &User) -> AppResult<()> {
+/// Authenticates the request without checking the scopes of the token.
+///
+/// This allows rejecting unauthenticated requests early, e.g. before a large request body is
+/// read. The request still has to be checked with an [`AuthCheck`] once the scopes are known.
+pub fn authenticate_unscoped<T: RequestPartsExt>(
+    req: &T,
+    conn: &mut PgConnection,
+) -> AppResult<Authentication> {
+    authenticate(req, conn)
+}
+
+pub(crate) fn ensure_not_locked(user: &User) -> AppResult<()> {
     if let Some(reason) = &user.account_lock_reason {
         let still_locked = if let Some(until) = user.account_lock_until {
             until > Utc::now().naive_utc()
@@ -347,4 +480,24 @@ mod tests {
         assert!(!auth_check.crate_scope_matches(Some(&vec![cs("anyhow")])));
         assert!(!auth_check.crate_scope_matches(Some(&vec![cs("actix-*")])));
     }
+
+    #[test]
+    fn read_endpoint() {
+        let auth_check = AuthCheck::default().with_endpoint_scope(EndpointScope::Read);
+
+        assert!(auth_check.endpoint_scope_matches(None));
+        assert!(!auth_check.endpoint_scope_matches(Some(&vec![EndpointScope::PublishNew])));
+        assert!(!auth_check.endpoint_scope_matches(Some(&vec![EndpointScope::Yank])));
+        assert!(auth_check.endpoint_scope_matches(Some(&vec![EndpointScope::Read])));
+
+        assert!(auth_check.crate_scope_matches(None));
+        assert!(!auth_check.crate_scope_matches(Some(&vec![cs("tokio-*")])));
+        assert!(auth_check
+            .for_any_crate()
+            .crate_scope_matches(Some(&vec![cs("tokio-*")])));
+
+        let auth_check = auth_check.for_crate("tokio-console");
+        assert!(auth_check.crate_scope_matches(Some(&vec![cs("tokio-*")])));
+        assert!(!auth_check.crate_scope_matches(Some(&vec![cs("anyhow")])));
+    }
 }
@@ -0,0 +1,112 @@
+//! OAuth providers that users can sign in with.
+//!
+//! GitHub is always available, since crates.io relies on GitHub for team
+//! ownership. The other providers are only enabled if they are configured,
+//! see [`OAuthProvidersConfig`](crate::config::OAuthProvidersConfig).
+
+mod github;
+mod gitlab;
+mod google;
+
+pub use self::github::GitHubProvider;
+pub use self::gitlab::GitLabProvider;
+pub use self::google::GoogleProvider;
+
+use crate::app::App;
+use crate::config;
+use crate::util::errors::{server_error, AppError, AppResult};
+use oauth2::basic::BasicClient;
+use oauth2::reqwest::http_client;
+use oauth2::{AccessToken, AuthorizationCode, CsrfToken, Scope, TokenResponse};
+use url::Url;
+
+/// The account information that an OAuth provider returns for an access token.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ProviderUser {
+    /// The stable ID of the account at the provider.
+    pub id: String,
+    pub login: String,
+    pub name: Option<String>,
+    /// A verified email address of the account, if the provider shares one.
+    pub email: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+pub trait Provider: Send + Sync {
+    /// The name of the provider in URLs and in the database, e.g. `github`.
+    fn name(&self) -> &'static str;
+
+    fn oauth_client(&self) -> &BasicClient;
+
+    /// The OAuth scopes that are requested when signing in.
+    fn scopes(&self) -> &'static [&'static str];
+
+    /// Fetches the account information of the user from the provider.
+    fn current_user(&self, app: &App, token: &AccessToken) -> AppResult<ProviderUser>;
+
+    /// Returns the URL that the user has to visit to authorize crates.io,
+    /// together with the random `state` secret of the request.
+    fn authorize_url(&self) -> (Url, CsrfToken) {
+        let scopes = self
+            .scopes()
+            .iter()
+            .map(|scope| Scope::new(scope.to_string()));
+
+        self.oauth_client()
+            .authorize_url(CsrfToken::new_random)
+            .add_scopes(scopes)
+            .url()
+    }
+
+    /// Exchanges the temporary `code` from the OAuth redirect for an access token.
+    fn exchange_code(&self, code: AuthorizationCode) -> AppResult<AccessToken> {
+        let token = self
+            .oauth_client()
+            .exchange_code(code)
+            .request(http_client)
+            .map_err(|err| err.chain(server_error("Error obtaining token")))?;
+
+        Ok(token.access_token().clone())
+    }
+}
+
+/// The OAuth providers that are enabled in this instance.
+pub struct Providers(Vec<Box<dyn Provider>>);
+
+impl Providers {
+    pub fn from_config(config: &config::Server) -> Self {
+        let mut providers: Vec<Box<dyn Provider>> = vec![Box::new(GitHubProvider::new(
+            config.gh_client_id.clone(),
+            config.gh_client_secret.clone(),
+        ))];
+
+        let oauth_providers = &config.oauth_providers;
+        if let Some(gitlab) = &oauth_providers.gitlab {
+            providers.push(Box::new(GitLabProvider::new(gitlab, &config.domain_name)));
+        }
+        if let Some(google) = &oauth_providers.google {
+            providers.push(Box::new(GoogleProvider::new(google, &config.domain_name)));
+        }
+
+        Self(providers)
+    }
+
+    /// Returns the enabled provider with the given name.
+    pub fn get(&self, name: &str) -> Option<&dyn Provider> {
+        self.0
+            .iter()
+            .find(|provider| provider.name() == name)
+            .map(AsRef::as_ref)
+    }
+
+    /// Returns the names of all enabled providers.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.0.iter().map(|provider| provider.name()).collect()
+    }
+}
+
+/// The redirect URL for providers other than GitHub, whose OAuth apps have
+/// the redirect URL configured on their side.
+fn redirect_url(domain_name: &str) -> oauth2::RedirectUrl {
+    oauth2::RedirectUrl::new(format!("https://{domain_name}/oauth-redirect.html")).unwrap()
+}
@@ -0,0 +1,58 @@
+use super::{Provider, ProviderUser};
+use crate::app::App;
+use crate::github::GithubUser;
+use crate::util::errors::AppResult;
+use oauth2::basic::BasicClient;
+use oauth2::{AccessToken, AuthUrl, ClientId, ClientSecret, TokenUrl};
+
+pub struct GitHubProvider {
+    oauth_client: BasicClient,
+}
+
+impl GitHubProvider {
+    pub const NAME: &'static str = "github";
+
+    pub fn new(client_id: ClientId, client_secret: ClientSecret) -> Self {
+        let oauth_client = BasicClient::new(
+            client_id,
+            Some(client_secret),
+            AuthUrl::new(String::from("https://github.com/login/oauth/authorize")).unwrap(),
+            Some(
+                TokenUrl::new(String::from("https://github.com/login/oauth/access_token")).unwrap(),
+            ),
+        );
+
+        Self { oauth_client }
+    }
+}
+
+impl Provider for GitHubProvider {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn oauth_client(&self) -> &BasicClient {
+        &self.oauth_client
+    }
+
+    fn scopes(&self) -> &'static [&'static str] {
+        &["read:org"]
+    }
+
+    /// Uses the `GitHubClient` of the app, so that tests can mock the response.
+    fn current_user(&self, app: &App, token: &AccessToken) -> AppResult<ProviderUser> {
+        app.github.current_user(token).map(Into::into)
+    }
+}
+
+impl From<GithubUser> for ProviderUser {
+    fn from(user: GithubUser) -> Self {
+        Self {
+            id: user.id.to_string(),
+            login: user.login,
+            name: user.name,
+            email: user.email,
+            avatar_url: user.avatar_url,
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use super::{redirect_url, Provider, ProviderUser};
+use crate::app::App;
+use crate::config::GitLabConfig;
+use crate::util::errors::{internal, AppResult};
+use oauth2::basic::BasicClient;
+use oauth2::{AccessToken, AuthUrl, TokenUrl};
+use reqwest::header;
+
+pub struct GitLabProvider {
+    base_url: String,
+    oauth_client: BasicClient,
+}
+
+impl GitLabProvider {
+    pub const NAME: &'static str = "gitlab";
+
+    pub fn new(config: &GitLabConfig, domain_name: &str) -> Self {
+        let base_url = &config.base_url;
+        let oauth_client = BasicClient::new(
+            config.client_id.clone(),
+            Some(config.client_secret.clone()),
+            AuthUrl::new(format!("{base_url}/oauth/authorize")).unwrap(),
+            Some(TokenUrl::new(format!("{base_url}/oauth/token")).unwrap()),
+        )
+        .set_redirect_uri(redirect_url(domain_name));
+
+        Self {
+            base_url: base_url.clone(),
+            oauth_client,
+        }
+    }
+}
+
+impl Provider for GitLabProvider {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn oauth_client(&self) -> &BasicClient {
+        &self.oauth_client
+    }
+
+    fn scopes(&self) -> &'static [&'static str] {
+        &["read_user"]
+    }
+
+    /// see <https://docs.gitlab.com/ee/api/users.html#list-current-user>
+    fn current_user(&self, app: &App, token: &AccessToken) -> AppResult<ProviderUser> {
+        let url = format!("{}/api/v4/user", self.base_url);
+        info!("GITLAB HTTP: {url}");
+
+        let user: GitLabUser = app
+            .http_client()
+            .get(&url)
+            .bearer_auth(token.secret())
+            .header(header::USER_AGENT, "crates.io (https://crates.io)")
+            .send()?
+            .error_for_status()
+            .map_err(|e| internal(format!("didn't get a 200 result from gitlab: {e}")))?
+            .json()?;
+
+        Ok(user.into())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabUser {
+    id: i64,
+    username: String,
+    name: Option<String>,
+    /// The public email address of the user, which GitLab only allows to be
+    /// set to a verified address.
+    public_email: Option<String>,
+    avatar_url: Option<String>,
+}
+
+impl From<GitLabUser> for ProviderUser {
+    fn from(user: GitLabUser) -> Self {
+        Self {
+            id: user.id.to_string(),
+            login: user.username,
+            name: user.name,
+            email: user.public_email.filter(|email| !email.is_empty()),
+            avatar_url: user.avatar_url,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gitlab_user_to_provider_user() {
+        let json = json!({
+            "id": 1234,
+            "username": "jdoe",
+            "name": "Jane Doe",
+            "public_email": "",
+            "avatar_url": "https://gitlab.com/uploads/-/system/user/avatar/1234/avatar.png",
+            "state": "active",
+        });
+
+        let user: GitLabUser = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            ProviderUser::from(user),
+            ProviderUser {
+                id: "1234".to_string(),
+                login: "jdoe".to_string(),
+                name: Some("Jane Doe".to_string()),
+                email: None,
+                avatar_url: Some(
+                    "https://gitlab.com/uploads/-/system/user/avatar/1234/avatar.png".to_string()
+                ),
+            }
+        );
+    }
+}
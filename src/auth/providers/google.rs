@@ -0,0 +1,133 @@
+use super::{redirect_url, Provider, ProviderUser};
+use crate::app::App;
+use crate::config::GoogleConfig;
+use crate::util::errors::{internal, AppResult};
+use oauth2::basic::BasicClient;
+use oauth2::{AccessToken, AuthUrl, TokenUrl};
+use reqwest::header;
+
+const USERINFO_URL: &str = "https://openidconnect.googleapis.com/v1/userinfo";
+
+pub struct GoogleProvider {
+    oauth_client: BasicClient,
+}
+
+impl GoogleProvider {
+    pub const NAME: &'static str = "google";
+
+    pub fn new(config: &GoogleConfig, domain_name: &str) -> Self {
+        let oauth_client = BasicClient::new(
+            config.client_id.clone(),
+            Some(config.client_secret.clone()),
+            AuthUrl::new(String::from("https://accounts.google.com/o/oauth2/v2/auth")).unwrap(),
+            Some(TokenUrl::new(String::from("https://oauth2.googleapis.com/token")).unwrap()),
+        )
+        .set_redirect_uri(redirect_url(domain_name));
+
+        Self { oauth_client }
+    }
+}
+
+impl Provider for GoogleProvider {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn oauth_client(&self) -> &BasicClient {
+        &self.oauth_client
+    }
+
+    fn scopes(&self) -> &'static [&'static str] {
+        &["openid", "email", "profile"]
+    }
+
+    /// see <https://developers.google.com/identity/openid-connect/openid-connect#obtainuserinfo>
+    fn current_user(&self, app: &App, token: &AccessToken) -> AppResult<ProviderUser> {
+        info!("GOOGLE HTTP: {USERINFO_URL}");
+
+        let user: GoogleUser = app
+            .http_client()
+            .get(USERINFO_URL)
+            .bearer_auth(token.secret())
+            .header(header::USER_AGENT, "crates.io (https://crates.io)")
+            .send()?
+            .error_for_status()
+            .map_err(|e| internal(format!("didn't get a 200 result from google: {e}")))?
+            .json()?;
+
+        Ok(user.into())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleUser {
+    sub: String,
+    name: Option<String>,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    picture: Option<String>,
+}
+
+impl From<GoogleUser> for ProviderUser {
+    /// Google accounts have no username, so the local part of the email
+    /// address is used as login, falling back to the account ID.
+    fn from(user: GoogleUser) -> Self {
+        let email = user.email.filter(|_| user.email_verified);
+        let login = email
+            .as_deref()
+            .and_then(|email| email.split_once('@'))
+            .map(|(local_part, _)| local_part.to_string())
+            .unwrap_or_else(|| user.sub.clone());
+
+        Self {
+            id: user.sub,
+            login,
+            name: user.name,
+            email,
+            avatar_url: user.picture,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn google_user_to_provider_user() {
+        let json = json!({
+            "sub": "110169484474386276334",
+            "name": "Jane Doe",
+            "email": "jane.doe@example.com",
+            "email_verified": true,
+            "picture": "https://lh3.googleusercontent.com/a/photo.jpg",
+        });
+
+        let user: GoogleUser = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            ProviderUser::from(user),
+            ProviderUser {
+                id: "110169484474386276334".to_string(),
+                login: "jane.doe".to_string(),
+                name: Some("Jane Doe".to_string()),
+                email: Some("jane.doe@example.com".to_string()),
+                avatar_url: Some("https://lh3.googleusercontent.com/a/photo.jpg".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn unverified_google_email_is_ignored() {
+        let json = json!({
+            "sub": "110169484474386276334",
+            "email": "jane.doe@example.com",
+            "email_verified": false,
+        });
+
+        let user: GoogleUser = serde_json::from_value(json).unwrap();
+        let user = ProviderUser::from(user);
+        assert_eq!(user.login, "110169484474386276334");
+        assert_eq!(user.email, None);
+    }
+}
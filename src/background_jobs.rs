@@ -6,20 +6,47 @@ use std::fmt::Display;
 use std::panic::AssertUnwindSafe;
 use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
+use crate::config::RetentionConfig;
 use crate::db::ConnectionPool;
+use crate::email::Emails;
+use crate::models;
 use crate::storage::Storage;
 use crate::swirl::errors::EnqueueError;
 use crate::swirl::PerformError;
 use crate::uploaders::Uploader;
+use crate::util::request_id::RequestId;
+use crate::util::signing::Signer;
 use crate::worker;
 use crate::worker::cloudfront::CloudFront;
 use crate::worker::fastly::Fastly;
 use crates_io_index::Repository;
+use crates_io_markdown::SanitizationPolicy;
 
 pub const PRIORITY_DEFAULT: i16 = 0;
 pub const PRIORITY_RENDER_README: i16 = 50;
 pub const PRIORITY_SYNC_TO_INDEX: i16 = 100;
 
+/// Job types that modify data in bulk, and are thus not run by the background
+/// worker while the maintenance mode is enabled.
+pub const MAINTENANCE_PAUSED_JOB_TYPES: &[&str] = &[
+    "backfill_artifacts",
+    "backfill_crate_objects",
+    "cleanup_idempotency_keys",
+    "cleanup_orphaned_files",
+    "create_index_snapshot",
+    "daily_db_maintenance",
+    "flag_stale_crates",
+    "normalize_index",
+    "purge_deleted_accounts",
+    "purge_expired_data",
+    "reconcile_storage_replicas",
+    "squash_index",
+    "update_dependent_stats",
+    "update_downloads",
+    "update_health_scores",
+    "update_keyword_stats",
+];
+
 macro_rules! jobs {
     {
         $vis:vis enum $name:ident {
@@ -76,14 +103,33 @@ macro_rules! job_variant_from_value {
 
 jobs! {
     pub enum Job {
+        BackfillArtifacts(BackfillArtifactsJob),
+        BackfillCrateObjects,
+        CheckPublish(CheckPublishJob),
+        CleanupIdempotencyKeys,
+        CleanupOrphanedFiles(CleanupOrphanedFilesJob),
+        CreateIndexSnapshot,
         DailyDbMaintenance,
         DumpDb(DumpDbJob),
+        ExportUserData(ExportUserDataJob),
+        FlagStaleCrates(FlagStaleCratesJob),
         NormalizeIndex(NormalizeIndexJob),
+        PromoteStagedVersions,
+        PurgeDeletedAccounts,
+        PurgeExpiredData(PurgeExpiredDataJob),
+        ReconcileStorageReplicas,
+        RecompressCrateFile(RecompressCrateFileJob),
         RenderAndUploadReadme(RenderAndUploadReadmeJob),
+        SendCrateNotificationDigests,
+        SendSecurityNotificationDigests,
         SquashIndex,
+        SyncLegalHold(SyncLegalHoldJob),
         SyncToGitIndex(SyncToIndexJob),
         SyncToSparseIndex(SyncToIndexJob),
+        UpdateDependentStats,
         UpdateDownloads,
+        UpdateHealthScores,
+        UpdateKeywordStats,
     }
 }
 
@@ -109,18 +155,24 @@ impl Job {
     ) -> Result<(), EnqueueError> {
         use crate::schema::background_jobs::dsl::*;
 
-        let to_git = Self::sync_to_git_index(krate.to_string());
+        let current_request_id = RequestId::current().as_ref().map(ToString::to_string);
+
+        let sequence = models::next_index_sequence(conn, &krate.to_string())?;
+
+        let to_git = Self::sync_to_git_index(krate.to_string(), Some(sequence));
         let to_git = (
             job_type.eq(to_git.as_type_str()),
             data.eq(to_git.to_value()?),
             priority.eq(PRIORITY_SYNC_TO_INDEX),
+            request_id.eq(current_request_id.clone()),
         );
 
-        let to_sparse = Self::sync_to_sparse_index(krate.to_string());
+        let to_sparse = Self::sync_to_sparse_index(krate.to_string(), Some(sequence));
         let to_sparse = (
             job_type.eq(to_sparse.as_type_str()),
             data.eq(to_sparse.to_value()?),
             priority.eq(PRIORITY_SYNC_TO_INDEX),
+            request_id.eq(current_request_id),
         );
 
         diesel::insert_into(background_jobs)
@@ -130,6 +182,36 @@ impl Job {
         Ok(())
     }
 
+    pub fn backfill_artifacts(batch_size: u32, batch_delay_ms: u64) -> Self {
+        Self::BackfillArtifacts(BackfillArtifactsJob {
+            batch_size,
+            batch_delay_ms,
+        })
+    }
+
+    pub fn backfill_crate_objects() -> Self {
+        Self::BackfillCrateObjects
+    }
+
+    pub fn check_publish(version_id: i32) -> Self {
+        Self::CheckPublish(CheckPublishJob { version_id })
+    }
+
+    pub fn cleanup_idempotency_keys() -> Self {
+        Self::CleanupIdempotencyKeys
+    }
+
+    pub fn cleanup_orphaned_files(dry_run: bool, grace_period_hours: u32) -> Self {
+        Self::CleanupOrphanedFiles(CleanupOrphanedFilesJob {
+            dry_run,
+            grace_period_hours,
+        })
+    }
+
+    pub fn create_index_snapshot() -> Self {
+        Self::CreateIndexSnapshot
+    }
+
     pub fn daily_db_maintenance() -> Self {
         Self::DailyDbMaintenance
     }
@@ -141,10 +223,38 @@ impl Job {
         })
     }
 
+    pub fn export_user_data(export_id: i32) -> Self {
+        Self::ExportUserData(ExportUserDataJob { export_id })
+    }
+
+    pub fn flag_stale_crates(inactivity_days: u32) -> Self {
+        Self::FlagStaleCrates(FlagStaleCratesJob { inactivity_days })
+    }
+
     pub fn normalize_index(dry_run: bool) -> Self {
         Self::NormalizeIndex(NormalizeIndexJob { dry_run })
     }
 
+    pub fn promote_staged_versions() -> Self {
+        Self::PromoteStagedVersions
+    }
+
+    pub fn purge_deleted_accounts() -> Self {
+        Self::PurgeDeletedAccounts
+    }
+
+    pub fn purge_expired_data(retention: RetentionConfig) -> Self {
+        Self::PurgeExpiredData(PurgeExpiredDataJob { retention })
+    }
+
+    pub fn reconcile_storage_replicas() -> Self {
+        Self::ReconcileStorageReplicas
+    }
+
+    pub fn recompress_crate_file(version_id: i32) -> Self {
+        Self::RecompressCrateFile(RecompressCrateFileJob { version_id })
+    }
+
     pub fn render_and_upload_readme(
         version_id: i32,
         text: String,
@@ -161,26 +271,52 @@ impl Job {
         })
     }
 
+    pub fn send_crate_notification_digests() -> Self {
+        Self::SendCrateNotificationDigests
+    }
+
+    pub fn send_security_notification_digests() -> Self {
+        Self::SendSecurityNotificationDigests
+    }
+
     pub fn squash_index() -> Self {
         Self::SquashIndex
     }
 
-    pub fn sync_to_git_index<T: ToString>(krate: T) -> Self {
+    pub fn sync_legal_hold(hold_id: i32) -> Self {
+        Self::SyncLegalHold(SyncLegalHoldJob { hold_id })
+    }
+
+    pub fn sync_to_git_index<T: ToString>(krate: T, sequence: Option<i64>) -> Self {
         Self::SyncToGitIndex(SyncToIndexJob {
             krate: krate.to_string(),
+            sequence,
         })
     }
 
-    pub fn sync_to_sparse_index<T: ToString>(krate: T) -> Self {
+    pub fn sync_to_sparse_index<T: ToString>(krate: T, sequence: Option<i64>) -> Self {
         Self::SyncToSparseIndex(SyncToIndexJob {
             krate: krate.to_string(),
+            sequence,
         })
     }
 
+    pub fn update_dependent_stats() -> Self {
+        Self::UpdateDependentStats
+    }
+
     pub fn update_downloads() -> Self {
         Self::UpdateDownloads
     }
 
+    pub fn update_health_scores() -> Self {
+        Self::UpdateHealthScores
+    }
+
+    pub fn update_keyword_stats() -> Self {
+        Self::UpdateKeywordStats
+    }
+
     pub fn enqueue(&self, conn: &mut PgConnection) -> Result<(), EnqueueError> {
         self.enqueue_with_priority(conn, PRIORITY_DEFAULT)
     }
@@ -194,11 +330,13 @@ impl Job {
         use crate::schema::background_jobs::dsl::*;
 
         let job_data = self.to_value()?;
+        let current_request_id = RequestId::current().as_ref().map(ToString::to_string);
         diesel::insert_into(background_jobs)
             .values((
                 job_type.eq(self.as_type_str()),
                 data.eq(job_data),
                 priority.eq(job_priority),
+                request_id.eq(current_request_id),
             ))
             .execute(conn)?;
         Ok(())
@@ -214,12 +352,44 @@ impl Job {
             .as_ref()
             .expect("Application should configure a background runner environment");
         match self {
+            Job::BackfillArtifacts(args) => worker::perform_backfill_artifacts(conn, env, &args),
+            Job::BackfillCrateObjects => worker::perform_backfill_crate_objects(conn, env),
+            Job::CheckPublish(args) => worker::perform_check_publish(conn, env, args.version_id),
+            Job::CleanupIdempotencyKeys => worker::perform_cleanup_idempotency_keys(conn),
+            Job::CleanupOrphanedFiles(args) => worker::perform_cleanup_orphaned_files(
+                conn,
+                env,
+                args.dry_run,
+                args.grace_period_hours,
+            ),
+            Job::CreateIndexSnapshot => worker::perform_create_index_snapshot(env, conn),
             Job::DailyDbMaintenance => {
                 worker::perform_daily_db_maintenance(&mut *fresh_connection(pool)?)
             }
             Job::DumpDb(args) => worker::perform_dump_db(env, args.database_url, args.target_name),
+            Job::ExportUserData(args) => {
+                worker::perform_export_user_data(conn, env, args.export_id)
+            }
+            Job::FlagStaleCrates(args) => {
+                worker::perform_flag_stale_crates(conn, env, args.inactivity_days)
+            }
+            Job::SendCrateNotificationDigests => {
+                worker::perform_send_crate_notification_digests(env, conn)
+            }
+            Job::SendSecurityNotificationDigests => {
+                worker::perform_send_security_notification_digests(env, conn)
+            }
             Job::SquashIndex => worker::perform_index_squash(env),
             Job::NormalizeIndex(args) => worker::perform_normalize_index(env, args),
+            Job::PromoteStagedVersions => worker::perform_promote_staged_versions(env, conn),
+            Job::PurgeDeletedAccounts => worker::perform_purge_deleted_accounts(conn, env),
+            Job::PurgeExpiredData(args) => {
+                worker::perform_purge_expired_data(conn, &args.retention)
+            }
+            Job::ReconcileStorageReplicas => worker::perform_reconcile_storage_replicas(env),
+            Job::RecompressCrateFile(args) => {
+                worker::perform_recompress_crate_file(conn, env, args.version_id)
+            }
             Job::RenderAndUploadReadme(args) => worker::perform_render_and_upload_readme(
                 conn,
                 env,
@@ -229,9 +399,17 @@ impl Job {
                 args.base_url.as_deref(),
                 args.pkg_path_in_vcs.as_deref(),
             ),
-            Job::SyncToGitIndex(args) => worker::sync_to_git_index(env, conn, &args.krate),
-            Job::SyncToSparseIndex(args) => worker::sync_to_sparse_index(env, conn, &args.krate),
+            Job::SyncLegalHold(args) => worker::perform_sync_legal_hold(env, conn, args.hold_id),
+            Job::SyncToGitIndex(args) => {
+                worker::sync_to_git_index(env, conn, &args.krate, args.sequence)
+            }
+            Job::SyncToSparseIndex(args) => {
+                worker::sync_to_sparse_index(env, conn, &args.krate, args.sequence)
+            }
+            Job::UpdateDependentStats => worker::perform_update_dependent_stats(conn),
             Job::UpdateDownloads => worker::perform_update_downloads(&mut *fresh_connection(pool)?),
+            Job::UpdateHealthScores => worker::perform_update_health_scores(conn),
+            Job::UpdateKeywordStats => worker::perform_update_keyword_stats(conn),
         }
     }
 }
@@ -251,6 +429,17 @@ fn fresh_connection(
     Ok(pool.get()?)
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct BackfillArtifactsJob {
+    pub(super) batch_size: u32,
+    pub(super) batch_delay_ms: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CheckPublishJob {
+    pub(super) version_id: i32,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct DumpDbJob {
     pub(super) database_url: String,
@@ -270,6 +459,10 @@ pub struct UpdateCrateIndexJob {
 #[derive(Serialize, Deserialize)]
 pub struct SyncToIndexJob {
     pub(super) krate: String,
+    /// The per-crate sequence number of the job. Jobs that were enqueued
+    /// without one are always performed.
+    #[serde(default)]
+    pub(super) sequence: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -278,11 +471,42 @@ pub struct SyncYankedJob {
     pub(super) version_num: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct ExportUserDataJob {
+    pub(super) export_id: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FlagStaleCratesJob {
+    pub(super) inactivity_days: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CleanupOrphanedFilesJob {
+    pub(super) dry_run: bool,
+    pub(super) grace_period_hours: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PurgeExpiredDataJob {
+    pub(super) retention: RetentionConfig,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct NormalizeIndexJob {
     pub dry_run: bool,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct SyncLegalHoldJob {
+    pub(super) hold_id: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RecompressCrateFileJob {
+    pub(super) version_id: i32,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct RenderAndUploadReadmeJob {
     pub(super) version_id: i32,
@@ -294,14 +518,20 @@ pub struct RenderAndUploadReadmeJob {
 
 pub struct Environment {
     index: Arc<Mutex<Repository>>,
+    pub emails: Arc<Emails>,
     pub uploader: Uploader,
     http_client: AssertUnwindSafe<Client>,
     cloudfront: Option<CloudFront>,
     fastly: Option<Fastly>,
     pub storage: AssertUnwindSafe<Arc<Storage>>,
+    /// Signs the links in notification emails, see `App::link_signer()`.
+    pub link_signer: Signer,
+    /// The policy that READMEs are rendered with.
+    pub readme_policy: AssertUnwindSafe<SanitizationPolicy>,
 }
 
 impl Environment {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         index: Repository,
         uploader: Uploader,
@@ -309,6 +539,9 @@ impl Environment {
         cloudfront: Option<CloudFront>,
         fastly: Option<Fastly>,
         storage: Arc<Storage>,
+        emails: Arc<Emails>,
+        link_signer: Signer,
+        readme_policy: SanitizationPolicy,
     ) -> Self {
         Self::new_shared(
             Arc::new(Mutex::new(index)),
@@ -317,9 +550,13 @@ impl Environment {
             cloudfront,
             fastly,
             storage,
+            emails,
+            link_signer,
+            readme_policy,
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new_shared(
         index: Arc<Mutex<Repository>>,
         uploader: Uploader,
@@ -327,14 +564,20 @@ impl Environment {
         cloudfront: Option<CloudFront>,
         fastly: Option<Fastly>,
         storage: Arc<Storage>,
+        emails: Arc<Emails>,
+        link_signer: Signer,
+        readme_policy: SanitizationPolicy,
     ) -> Self {
         Self {
             index,
+            emails,
             uploader,
             http_client: AssertUnwindSafe(http_client),
             cloudfront,
             fastly,
             storage: AssertUnwindSafe(storage),
+            link_signer,
+            readme_policy: AssertUnwindSafe(readme_policy),
         }
     }
 
@@ -16,6 +16,8 @@
 extern crate tracing;
 
 use crates_io::config;
+use crates_io::email::Emails;
+use crates_io::models::MaintenanceModeSetting;
 use crates_io::storage::Storage;
 use crates_io::worker::cloudfront::CloudFront;
 use crates_io::{background_jobs::*, db, ssh};
@@ -27,8 +29,13 @@ use std::thread::sleep;
 use std::time::{Duration, Instant};
 
 use crates_io::swirl;
+use crates_io::util::signing::Signer;
 use crates_io::worker::fastly::Fastly;
 
+/// How often the maintenance mode set through the admin API is reloaded from
+/// the database.
+const MAINTENANCE_MODE_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
 fn main() {
     let _sentry = crates_io::sentry::init();
 
@@ -77,6 +84,7 @@ fn main() {
     let cloudfront = CloudFront::from_environment();
     let fastly = Fastly::from_environment();
     let storage = Arc::new(Storage::from_config(&config.storage));
+    let emails = Arc::new(Emails::from_environment(&config));
 
     let client = Client::builder()
         .timeout(Duration::from_secs(45))
@@ -90,25 +98,60 @@ fn main() {
         cloudfront,
         fastly,
         storage,
+        emails,
+        Signer::new(config.session_key.signing()),
+        config.readme_sanitization.policy(&config.domain_name),
     );
 
     let environment = Arc::new(Some(environment));
 
-    let build_runner =
-        || swirl::Runner::production_runner(environment.clone(), db_url.clone(), job_start_timeout);
+    let mut maintenance_mode = config.maintenance_mode || load_maintenance_mode(&config);
+    if maintenance_mode {
+        warn!(job_types = ?MAINTENANCE_PAUSED_JOB_TYPES, "Maintenance mode is enabled, pausing jobs");
+    }
+
+    let build_runner = |maintenance_mode: bool| {
+        let runner = swirl::Runner::production_runner(
+            environment.clone(),
+            db_url.clone(),
+            job_start_timeout,
+        );
+
+        pause_jobs(runner, maintenance_mode)
+    };
 
-    let mut runner = build_runner();
+    let mut runner = build_runner(maintenance_mode);
 
     info!("Runner booted, running jobs");
 
     let mut failure_count = 0;
+    let mut maintenance_mode_checked_at = Instant::now();
 
     loop {
+        // The maintenance mode can be toggled at runtime through the admin API
+        if maintenance_mode_checked_at.elapsed() >= MAINTENANCE_MODE_REFRESH_INTERVAL {
+            maintenance_mode_checked_at = Instant::now();
+
+            let enabled = config.maintenance_mode || load_maintenance_mode(&config);
+            if enabled != maintenance_mode {
+                maintenance_mode = enabled;
+                if maintenance_mode {
+                    warn!(
+                        job_types = ?MAINTENANCE_PAUSED_JOB_TYPES,
+                        "Maintenance mode was enabled, pausing jobs"
+                    );
+                } else {
+                    info!("Maintenance mode was disabled, resuming jobs");
+                }
+                runner = pause_jobs(runner, maintenance_mode);
+            }
+        }
+
         if let Err(e) = runner.run_all_pending_jobs() {
             failure_count += 1;
             if failure_count < 5 {
                 warn!(?failure_count, err = ?e, "Error running jobs -- retrying");
-                runner = build_runner();
+                runner = build_runner(maintenance_mode);
             } else {
                 panic!("Failed to begin running jobs 5 times. Restarting the process");
             }
@@ -116,3 +159,28 @@ fn main() {
         sleep(Duration::from_secs(1));
     }
 }
+
+fn pause_jobs(runner: swirl::Runner, maintenance_mode: bool) -> swirl::Runner {
+    if maintenance_mode {
+        runner.pause_job_types(MAINTENANCE_PAUSED_JOB_TYPES)
+    } else {
+        runner.pause_job_types(&[])
+    }
+}
+
+/// Returns whether the maintenance mode has been enabled through the admin
+/// API. If the setting can not be loaded, the maintenance mode is assumed to
+/// be enabled, so that no bulk jobs run while the database is unavailable.
+fn load_maintenance_mode(config: &config::Server) -> bool {
+    let result = db::oneoff_connection_with_config(&config.db)
+        .map_err(anyhow::Error::from)
+        .and_then(|mut conn| Ok(MaintenanceModeSetting::load(&mut conn)?));
+
+    match result {
+        Ok(setting) => setting.map_or(false, |setting| setting.enabled),
+        Err(err) => {
+            warn!(?err, "Failed to load maintenance mode");
+            true
+        }
+    }
+}
@@ -5,7 +5,8 @@ extern crate tracing;
 
 use crates_io::admin::{
     backfill, delete_crate, delete_version, enqueue_job, git_import, migrate, populate,
-    render_readmes, test_pagerduty, transfer_crates, upload_index, verify_token, yank_version,
+    rebuild_index, render_readmes, revoke_sessions, revoke_tokens, test_pagerduty,
+    transfer_crates, upload_index, verify_token, yank_version,
 };
 use tracing_subscriber::filter::LevelFilter;
 
@@ -17,7 +18,10 @@ enum Command {
     DeleteCrate(delete_crate::Opts),
     DeleteVersion(delete_version::Opts),
     Populate(populate::Opts),
+    RebuildIndex(rebuild_index::Opts),
     RenderReadmes(render_readmes::Opts),
+    RevokeSessions(revoke_sessions::Opts),
+    RevokeTokens(revoke_tokens::Opts),
     TestPagerduty(test_pagerduty::Opts),
     TransferCrates(transfer_crates::Opts),
     VerifyToken(verify_token::Opts),
@@ -46,7 +50,10 @@ fn main() -> anyhow::Result<()> {
         Command::DeleteCrate(opts) => delete_crate::run(opts),
         Command::DeleteVersion(opts) => delete_version::run(opts),
         Command::Populate(opts) => populate::run(opts),
+        Command::RebuildIndex(opts) => rebuild_index::run(opts)?,
         Command::RenderReadmes(opts) => render_readmes::run(opts)?,
+        Command::RevokeSessions(opts) => revoke_sessions::run(opts),
+        Command::RevokeTokens(opts) => revoke_tokens::run(opts),
         Command::TestPagerduty(opts) => test_pagerduty::run(opts)?,
         Command::TransferCrates(opts) => transfer_crates::run(opts),
         Command::VerifyToken(opts) => verify_token::run(opts).unwrap(),
@@ -0,0 +1,14 @@
+#![warn(clippy::all, rust_2018_idioms)]
+
+use crates_io::admin::seed_data;
+use tracing_subscriber::filter::LevelFilter;
+
+fn main() -> anyhow::Result<()> {
+    // Initialize logging
+    crates_io::util::tracing::init_with_default_level(LevelFilter::INFO);
+
+    use clap::Parser;
+
+    let opts = seed_data::Opts::parse();
+    seed_data::run(opts)
+}
@@ -18,6 +18,12 @@ use tower::Layer;
 
 const CORE_THREADS: usize = 4;
 
+/// How often the active legal holds are reloaded from the database.
+const LEGAL_HOLDS_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the maintenance mode set through the admin API is reloaded from the database.
+const MAINTENANCE_MODE_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let _sentry = crates_io::sentry::init();
 
@@ -36,6 +42,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Start the background thread periodically logging instance metrics.
     log_instance_metrics_thread(app.clone());
 
+    // Start the background thread periodically reloading the active legal holds.
+    legal_holds_thread(app.clone());
+
+    // Start the background thread periodically reloading the maintenance mode.
+    maintenance_mode_thread(app.clone());
+
     let axum_router = crates_io::build_handler(app.clone());
 
     // Apply the `normalize_path` middleware around the axum router
@@ -109,6 +121,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     info!("Server has gracefully shutdown!");
+    crates_io::util::tracing::shutdown();
+
     Ok(())
 }
 
@@ -120,6 +134,11 @@ fn downloads_counter_thread(app: Arc<App>) {
     std::thread::spawn(move || loop {
         std::thread::sleep(interval);
 
+        // Keep counting downloads in memory until the maintenance is over
+        if app.maintenance_mode.is_enabled() {
+            continue;
+        }
+
         match app.downloads_counter.persist_next_shard(&app) {
             Ok(stats) => stats.log(),
             Err(err) => error!(?err, "downloads_counter error"),
@@ -127,6 +146,24 @@ fn downloads_counter_thread(app: Arc<App>) {
     });
 }
 
+fn legal_holds_thread(app: Arc<App>) {
+    std::thread::spawn(move || loop {
+        if let Err(err) = app.refresh_legal_holds() {
+            error!(?err, "Failed to refresh legal holds");
+        }
+        std::thread::sleep(LEGAL_HOLDS_REFRESH_INTERVAL);
+    });
+}
+
+fn maintenance_mode_thread(app: Arc<App>) {
+    std::thread::spawn(move || loop {
+        if let Err(err) = app.refresh_maintenance_mode() {
+            error!(?err, "Failed to refresh maintenance mode");
+        }
+        std::thread::sleep(MAINTENANCE_MODE_REFRESH_INTERVAL);
+    });
+}
+
 fn log_instance_metrics_thread(app: Arc<App>) {
     // Only run the thread if the configuration is provided
     let interval = if let Some(secs) = app.config.instance_metrics_log_every_seconds {
@@ -1,12 +1,26 @@
 mod balance_capacity;
 mod base;
 mod database_pools;
+mod local_auth;
+mod oauth_providers;
+mod opentelemetry;
+mod readme_sanitization;
+mod retention;
 mod sentry;
 mod server;
+mod signed_downloads;
+mod upstream;
 
 pub use self::balance_capacity::BalanceCapacityConfig;
 pub use self::base::Base;
 pub use self::database_pools::{DatabasePools, DbPoolConfig};
+pub use self::local_auth::LocalAuthConfig;
+pub use self::oauth_providers::{GitLabConfig, GoogleConfig, OAuthProvidersConfig};
+pub use self::opentelemetry::OpenTelemetryConfig;
+pub use self::readme_sanitization::ReadmeSanitizationConfig;
+pub use self::retention::RetentionConfig;
 pub use self::sentry::SentryConfig;
 pub(crate) use self::server::domain_name;
 pub use self::server::Server;
+pub use self::signed_downloads::{SignedDownloadsConfig, SigningKey};
+pub use self::upstream::UpstreamConfig;
@@ -7,8 +7,14 @@
 //! - `AWS_ACCESS_KEY`: The access key to interact with S3.
 //! - `AWS_SECRET_KEY`: The secret key to interact with S3.
 //! - `S3_CDN`: Optional CDN configuration for building public facing URLs.
+//! - `S3_REPLICAS`: Optional comma-separated list of `region=bucket` pairs of buckets in other
+//!    regions that the crate files are replicated to.
+//! - `S3_REPLICA_CDNS`: Optional comma-separated list of `region=host` pairs of the CDNs in front
+//!    of the replica buckets.
 
-use crate::{env, uploaders::Uploader, Env};
+use crate::storage::parse_replicas;
+use crate::uploaders::{Replica, Uploader};
+use crate::{env, Env};
 
 pub struct Base {
     pub env: Env,
@@ -72,6 +78,7 @@ impl Base {
             )),
             index_bucket,
             cdn: dotenvy::var("S3_CDN").ok(),
+            replicas: Self::s3_replicas(env("AWS_ACCESS_KEY"), env("AWS_SECRET_KEY")),
         }
     }
 
@@ -98,6 +105,36 @@ impl Base {
             )),
             index_bucket,
             cdn: dotenvy::var("S3_CDN").ok(),
+            replicas: Self::s3_replicas(
+                dotenvy::var("AWS_ACCESS_KEY").unwrap_or_default(),
+                dotenvy::var("AWS_SECRET_KEY").unwrap_or_default(),
+            ),
         }
     }
+
+    fn s3_replicas(access_key: String, secret_key: String) -> Vec<Replica> {
+        let cdns = dotenvy::var("S3_REPLICA_CDNS")
+            .map(|value| parse_replicas(&value))
+            .unwrap_or_default();
+
+        dotenvy::var("S3_REPLICAS")
+            .map(|value| parse_replicas(&value))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(region, bucket)| Replica {
+                bucket: Box::new(s3::Bucket::new(
+                    bucket,
+                    s3::Region::Region(region.clone()),
+                    access_key.clone(),
+                    secret_key.clone(),
+                    "https",
+                )),
+                cdn: cdns
+                    .iter()
+                    .find(|(cdn_region, _)| *cdn_region == region)
+                    .map(|(_, host)| host.clone()),
+                region,
+            })
+            .collect()
+    }
 }
@@ -0,0 +1,42 @@
+use url::Url;
+
+/// Configuration of the local authentication with passwords and passkeys,
+/// for deployments that can not use an external OAuth provider.
+#[derive(Debug)]
+pub struct LocalAuthConfig {
+    /// The WebAuthn relying party ID that passkeys are bound to, usually the
+    /// domain name of the deployment.
+    pub webauthn_rp_id: String,
+    /// The origin that passkeys are registered and used on.
+    pub webauthn_origin: Url,
+}
+
+impl LocalAuthConfig {
+    /// Load the local authentication configuration from the environment
+    ///
+    /// Returns `None` if local authentication is disabled.
+    ///
+    /// # Optional environment variables
+    ///
+    /// - `LOCAL_AUTH`: If defined (even as empty) then users can sign up and
+    ///   sign in with a password or a passkey.
+    /// - `WEBAUTHN_RP_ID`: The WebAuthn relying party ID. Defaults to the
+    ///   domain name.
+    /// - `WEBAUTHN_ORIGIN`: The origin of the frontend. Defaults to
+    ///   `https://{domain_name}`.
+    pub fn from_environment(domain_name: &str) -> Option<Self> {
+        dotenvy::var("LOCAL_AUTH").ok()?;
+
+        let webauthn_rp_id =
+            dotenvy::var("WEBAUTHN_RP_ID").unwrap_or_else(|_| domain_name.to_string());
+
+        let webauthn_origin =
+            dotenvy::var("WEBAUTHN_ORIGIN").unwrap_or_else(|_| format!("https://{domain_name}"));
+        let webauthn_origin = Url::parse(&webauthn_origin).expect("invalid WEBAUTHN_ORIGIN");
+
+        Some(Self {
+            webauthn_rp_id,
+            webauthn_origin,
+        })
+    }
+}
@@ -0,0 +1,66 @@
+use oauth2::{ClientId, ClientSecret};
+
+const DEFAULT_GITLAB_URL: &str = "https://gitlab.com";
+
+/// Configuration of the OAuth providers that users can sign in with in
+/// addition to GitHub. Providers without credentials are disabled.
+#[derive(Debug, Default)]
+pub struct OAuthProvidersConfig {
+    pub gitlab: Option<GitLabConfig>,
+    pub google: Option<GoogleConfig>,
+}
+
+#[derive(Debug)]
+pub struct GitLabConfig {
+    /// The base URL of the GitLab instance, e.g. `https://gitlab.com`.
+    pub base_url: String,
+    pub client_id: ClientId,
+    pub client_secret: ClientSecret,
+}
+
+#[derive(Debug)]
+pub struct GoogleConfig {
+    pub client_id: ClientId,
+    pub client_secret: ClientSecret,
+}
+
+impl OAuthProvidersConfig {
+    /// Load the OAuth provider configuration from the environment
+    ///
+    /// # Optional environment variables
+    ///
+    /// - `GITLAB_CLIENT_ID`, `GITLAB_CLIENT_SECRET`: Credentials of the GitLab
+    ///   OAuth application. GitLab sign in is disabled if these are not set.
+    /// - `GITLAB_URL`: The GitLab instance to use. Defaults to
+    ///   `https://gitlab.com`.
+    /// - `GOOGLE_CLIENT_ID`, `GOOGLE_CLIENT_SECRET`: Credentials of the Google
+    ///   OAuth client. Google sign in is disabled if these are not set.
+    pub fn from_environment() -> Self {
+        let gitlab = credentials("GITLAB_CLIENT_ID", "GITLAB_CLIENT_SECRET").map(
+            |(client_id, client_secret)| GitLabConfig {
+                base_url: dotenvy::var("GITLAB_URL")
+                    .map(|url| url.trim_end_matches('/').to_string())
+                    .unwrap_or_else(|_| DEFAULT_GITLAB_URL.to_string()),
+                client_id,
+                client_secret,
+            },
+        );
+
+        let google = credentials("GOOGLE_CLIENT_ID", "GOOGLE_CLIENT_SECRET").map(
+            |(client_id, client_secret)| GoogleConfig {
+                client_id,
+                client_secret,
+            },
+        );
+
+        Self { gitlab, google }
+    }
+}
+
+fn credentials(id_var: &str, secret_var: &str) -> Option<(ClientId, ClientSecret)> {
+    let client_id = dotenvy::var(id_var).ok()?;
+    let client_secret = dotenvy::var(secret_var)
+        .unwrap_or_else(|_| panic!("{secret_var} must be set when using {id_var}"));
+
+    Some((ClientId::new(client_id), ClientSecret::new(client_secret)))
+}
@@ -0,0 +1,62 @@
+const DEFAULT_SERVICE_NAME: &str = "crates_io";
+
+pub struct OpenTelemetryConfig {
+    /// The OTLP/HTTP endpoint that spans are exported to, e.g.
+    /// `http://localhost:4318/v1/traces`. If missing, spans are not exported.
+    pub endpoint: Option<String>,
+    pub service_name: String,
+}
+
+impl OpenTelemetryConfig {
+    /// Reads the OpenTelemetry configuration from the environment.
+    ///
+    /// - `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`: The full URL of the OTLP/HTTP traces endpoint.
+    /// - `OTEL_EXPORTER_OTLP_ENDPOINT`: The base URL of the OTLP/HTTP collector. Only used if
+    ///   `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` is not set, in which case `/v1/traces` is appended.
+    /// - `OTEL_SERVICE_NAME`: The `service.name` resource attribute. Defaults to `crates_io`.
+    pub fn from_environment() -> Self {
+        let endpoint = traces_endpoint(
+            dotenvy::var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT").ok(),
+            dotenvy::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok(),
+        );
+
+        let service_name =
+            dotenvy::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| DEFAULT_SERVICE_NAME.into());
+
+        Self {
+            endpoint,
+            service_name,
+        }
+    }
+}
+
+fn traces_endpoint(
+    traces_endpoint: Option<String>,
+    base_endpoint: Option<String>,
+) -> Option<String> {
+    let traces_endpoint = traces_endpoint.filter(|endpoint| !endpoint.is_empty());
+    let base_endpoint = base_endpoint.filter(|endpoint| !endpoint.is_empty());
+
+    traces_endpoint
+        .or_else(|| base_endpoint.map(|base| format!("{}/v1/traces", base.trim_end_matches('/'))))
+}
+
+#[test]
+fn traces_endpoint_resolution() {
+    let s = |s: &str| Some(s.to_string());
+
+    assert_none!(traces_endpoint(None, None));
+    assert_none!(traces_endpoint(s(""), s("")));
+    assert_some_eq!(
+        traces_endpoint(None, s("http://localhost:4318")),
+        "http://localhost:4318/v1/traces"
+    );
+    assert_some_eq!(
+        traces_endpoint(None, s("http://localhost:4318/")),
+        "http://localhost:4318/v1/traces"
+    );
+    assert_some_eq!(
+        traces_endpoint(s("http://collector/traces"), s("http://localhost:4318")),
+        "http://collector/traces"
+    );
+}
@@ -0,0 +1,90 @@
+use crate::image_proxy::ImageProxy;
+use crates_io_markdown::{ImageUrlRewriter, SanitizationPolicy};
+use secrecy::{ExposeSecret, SecretString};
+use std::sync::Arc;
+
+/// Adjustments to the sanitization of rendered READMEs, for deployments that
+/// want stricter or looser rules than crates.io.
+#[derive(Debug)]
+pub struct ReadmeSanitizationConfig {
+    /// Tags that are allowed in addition to the built-in ones.
+    pub allowed_tags: Vec<String>,
+    /// Built-in tags that are removed.
+    pub denied_tags: Vec<String>,
+    /// Attributes that are allowed on all tags in addition to the built-in ones.
+    pub allowed_attributes: Vec<String>,
+    /// Should raw HTML in Markdown READMEs be rendered?
+    pub raw_html: bool,
+    /// The key that image proxy URLs are signed with. External images are
+    /// only served through the image proxy if it is set.
+    pub image_proxy_key: Option<SecretString>,
+}
+
+impl Default for ReadmeSanitizationConfig {
+    fn default() -> Self {
+        Self {
+            allowed_tags: vec![],
+            denied_tags: vec![],
+            allowed_attributes: vec![],
+            raw_html: true,
+            image_proxy_key: None,
+        }
+    }
+}
+
+impl ReadmeSanitizationConfig {
+    /// Load the README sanitization configuration from the environment
+    ///
+    /// # Optional environment variables
+    ///
+    /// - `README_ALLOWED_TAGS`: A comma separated list of HTML tags that are
+    ///   allowed in addition to the built-in ones.
+    /// - `README_DENIED_TAGS`: A comma separated list of built-in HTML tags
+    ///   that are removed, e.g. `img`.
+    /// - `README_ALLOWED_ATTRIBUTES`: A comma separated list of HTML
+    ///   attributes that are allowed on all tags.
+    /// - `README_DISABLE_RAW_HTML`: If defined (even as empty) then raw HTML in
+    ///   Markdown READMEs is omitted.
+    /// - `README_IMAGE_PROXY_KEY`: If defined then external images are served
+    ///   through the image proxy, using this key to sign the proxy URLs.
+    pub fn from_environment() -> Self {
+        Self {
+            allowed_tags: list("README_ALLOWED_TAGS"),
+            denied_tags: list("README_DENIED_TAGS"),
+            allowed_attributes: list("README_ALLOWED_ATTRIBUTES"),
+            raw_html: dotenvy::var("README_DISABLE_RAW_HTML").is_err(),
+            image_proxy_key: dotenvy::var("README_IMAGE_PROXY_KEY").ok().map(Into::into),
+        }
+    }
+
+    /// Returns the image proxy, if it is enabled.
+    pub fn image_proxy(&self, domain_name: &str) -> Option<ImageProxy> {
+        let key = self.image_proxy_key.as_ref()?;
+        Some(ImageProxy::new(domain_name, key.expose_secret().as_bytes()))
+    }
+
+    /// Returns the policy that READMEs are rendered with.
+    pub fn policy(&self, domain_name: &str) -> SanitizationPolicy {
+        let image_url_rewriter = self
+            .image_proxy(domain_name)
+            .map(|proxy| Arc::new(move |url: &str| proxy.rewrite(url)) as ImageUrlRewriter);
+
+        SanitizationPolicy {
+            extra_tags: self.allowed_tags.clone(),
+            denied_tags: self.denied_tags.clone(),
+            extra_attributes: self.allowed_attributes.clone(),
+            raw_html: self.raw_html,
+            image_url_rewriter,
+        }
+    }
+}
+
+fn list(name: &str) -> Vec<String> {
+    dotenvy::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_lowercase())
+        .collect()
+}
@@ -0,0 +1,54 @@
+use crate::env_optional;
+
+/// The number of days that expired or unused data is kept around before it
+/// is purged by the `purge_expired_data` background job.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Counted from the creation of the invitation. Has to be longer than
+    /// the expiration period of ownership invitations, since expired
+    /// invitations are still listed with an "expired" label.
+    pub ownership_invitations_days: u32,
+    /// Counted from the revocation or expiration of the token.
+    pub revoked_tokens_days: u32,
+    /// Counted from the revocation of the session, or from the last time it
+    /// was used.
+    pub stale_sessions_days: u32,
+    /// Counted from the generation of the token of a verified email address.
+    pub consumed_email_tokens_days: u32,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            ownership_invitations_days: 60,
+            revoked_tokens_days: 90,
+            stale_sessions_days: 90,
+            consumed_email_tokens_days: 7,
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// Load the retention configuration from the environment
+    ///
+    /// # Optional environment variables
+    ///
+    /// - `RETENTION_OWNERSHIP_INVITATIONS_DAYS`: Defaults to 60.
+    /// - `RETENTION_REVOKED_TOKENS_DAYS`: Defaults to 90.
+    /// - `RETENTION_STALE_SESSIONS_DAYS`: Defaults to 90.
+    /// - `RETENTION_CONSUMED_EMAIL_TOKENS_DAYS`: Defaults to 7.
+    pub fn from_environment() -> Self {
+        let default = Self::default();
+
+        Self {
+            ownership_invitations_days: env_optional("RETENTION_OWNERSHIP_INVITATIONS_DAYS")
+                .unwrap_or(default.ownership_invitations_days),
+            revoked_tokens_days: env_optional("RETENTION_REVOKED_TOKENS_DAYS")
+                .unwrap_or(default.revoked_tokens_days),
+            stale_sessions_days: env_optional("RETENTION_STALE_SESSIONS_DAYS")
+                .unwrap_or(default.stale_sessions_days),
+            consumed_email_tokens_days: env_optional("RETENTION_CONSUMED_EMAIL_TOKENS_DAYS")
+                .unwrap_or(default.consumed_email_tokens_days),
+        }
+    }
+}
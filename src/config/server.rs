@@ -8,6 +8,7 @@ use crate::{env, env_optional, uploaders::Uploader, Env};
 use super::base::Base;
 use super::database_pools::DatabasePools;
 use crate::config::balance_capacity::BalanceCapacityConfig;
+use crate::config::{LocalAuthConfig, OAuthProvidersConfig, SignedDownloadsConfig, UpstreamConfig};
 use crate::storage::StorageConfig;
 use http::HeaderValue;
 use std::collections::HashSet;
@@ -16,6 +17,12 @@ use std::time::Duration;
 
 const DEFAULT_VERSION_ID_CACHE_SIZE: u64 = 10_000;
 const DEFAULT_VERSION_ID_CACHE_TTL: u64 = 5 * 60; // 5 minutes
+const DEFAULT_READINESS_MAX_JOB_LAG: u64 = 15 * 60; // 15 minutes
+const DEFAULT_MAINTENANCE_RETRY_AFTER: u64 = 5 * 60; // 5 minutes
+const DEFAULT_STAGED_RELEASE_SOAK_PERIOD: u64 = 24 * 60 * 60; // 1 day
+const DEFAULT_ACCOUNT_DELETION_GRACE_PERIOD: u64 = 14 * 24 * 60 * 60; // 14 days
+const DEFAULT_CATEGORY_TREE_CACHE_TTL: u64 = 5 * 60; // 5 minutes
+const DEFAULT_DEPENDENCY_GRAPH_CACHE_TTL: u64 = 60 * 60; // 1 hour
 
 pub struct Server {
     pub base: Base,
@@ -28,10 +35,36 @@ pub struct Server {
     pub session_key: cookie::Key,
     pub gh_client_id: ClientId,
     pub gh_client_secret: ClientSecret,
+
+    /// The OAuth providers that can be used to sign in, in addition to GitHub.
+    pub oauth_providers: OAuthProvidersConfig,
+
+    /// Local authentication with passwords and passkeys, if enabled.
+    pub local_auth: Option<LocalAuthConfig>,
+
     pub max_upload_size: u64,
     pub max_unpack_size: u64,
+
+    /// The `max_upload_size` of crates that have regularly published crate
+    /// files close to the global limit. Per-crate overrides can be set
+    /// through the admin API.
+    pub large_crate_max_upload_size: u64,
+
     pub publish_rate_limit: PublishRateLimit,
     pub new_version_rate_limit: Option<u32>,
+
+    /// The rate limit of the README preview endpoint, which renders
+    /// arbitrary Markdown for the authenticated user.
+    pub readme_preview_rate_limit: PublishRateLimit,
+
+    /// The rate limit of the takedown request intake endpoint, per IP
+    /// address, since it does not require authentication.
+    pub takedown_request_rate_limit: PublishRateLimit,
+
+    /// The rate limit of password logins, which applies both per IP address
+    /// and per account, to slow down the guessing of passwords.
+    pub login_rate_limit: PublishRateLimit,
+
     pub blocked_traffic: Vec<(String, Vec<String>)>,
     pub max_allowed_page_offset: u32,
     pub page_offset_ua_blocklist: Vec<String>,
@@ -42,6 +75,7 @@ pub struct Server {
     pub downloads_persist_interval_ms: usize,
     pub ownership_invitations_expiration_days: u64,
     pub metrics_authorization_token: Option<String>,
+    pub admin_authorization_token: Option<String>,
     pub use_test_database_pool: bool,
     pub instance_metrics_log_every_seconds: Option<u64>,
     pub force_unconditional_redirects: bool,
@@ -51,6 +85,75 @@ pub struct Server {
     pub cdn_user_agent: String,
     pub balance_capacity: BalanceCapacityConfig,
 
+    /// The `/readyz` endpoint reports the instance as not ready if the oldest
+    /// background job has been queued for longer than this.
+    pub readiness_max_job_lag: Duration,
+
+    /// Should the server stay in maintenance mode, rejecting all write requests, regardless of the
+    /// maintenance mode set through the admin API?
+    pub maintenance_mode: bool,
+
+    /// The default `Retry-After` value reported while in maintenance mode.
+    pub maintenance_retry_after: Duration,
+
+    /// How long staged versions are only visible to their owners before they
+    /// are published automatically.
+    pub staged_release_soak_period: Duration,
+
+    /// How long confirmed account deletions can still be cancelled before the
+    /// account is anonymized.
+    pub account_deletion_grace_period: Duration,
+
+    /// Crates published by accounts younger than this are quarantined until
+    /// they have been reviewed. Quarantining is disabled if `None`.
+    pub quarantine_account_age: Option<Duration>,
+
+    /// Should newly published crate files also be recompressed with zstd
+    /// by a background job?
+    pub zstd_recompression: bool,
+
+    /// Should newly published crate files be stored under the SHA-256 hash
+    /// of their content, instead of their crate name and version?
+    pub content_addressed_storage: bool,
+
+    /// Should the first publish of a crate be rejected if its content is
+    /// identical to another crate, instead of only flagging it for review?
+    pub reject_duplicate_content: bool,
+
+    /// How long the category tree is cached before it is computed again.
+    pub category_tree_cache_ttl: Duration,
+
+    /// How long resolved dependency graphs are cached before they are resolved again.
+    pub dependency_graph_cache_ttl: Duration,
+
+    /// Should search queries also be matched in their ASCII transliteration,
+    /// e.g. `naïve` as `naive`?
+    pub search_transliteration: bool,
+
+    /// Should keywords with non-ASCII letters be accepted? This is meant for
+    /// private deployments, since `cargo` only accepts ASCII keywords on
+    /// crates.io.
+    pub allow_non_ascii_keywords: bool,
+
+    /// Should all read endpoints, the sparse index and the crate downloads
+    /// require an authenticated user or an API token with the `read` scope?
+    pub private_registry: bool,
+
+    /// The keys that the download URLs of a private registry are signed
+    /// with, if the crate files should not be served directly.
+    pub signed_downloads: Option<SignedDownloadsConfig>,
+
+    /// The upstream registry that crates are fetched from if they do not
+    /// exist locally, if enabled.
+    pub upstream: Option<UpstreamConfig>,
+
+    /// The request header that the CDN or GeoDNS sets to the region of the
+    /// client, which selects the replica that downloads are redirected to.
+    pub region_hint_header: Option<String>,
+
+    /// Adjustments to the sanitization of rendered READMEs.
+    pub readme_sanitization: ReadmeSanitizationConfig,
+
     /// Should the server serve the frontend assets in the `dist` directory?
     pub serve_dist: bool,
 
@@ -79,6 +182,8 @@ impl Default for Server {
     /// - `DOWNLOADS_PERSIST_INTERVAL_MS`: how frequent to persist download counts (in ms).
     /// - `METRICS_AUTHORIZATION_TOKEN`: authorization token needed to query metrics. If missing,
     ///   querying metrics will be completely disabled.
+    /// - `ADMIN_AUTHORIZATION_TOKEN`: authorization token needed to use the admin API. If missing,
+    ///   the admin API will be completely disabled.
     /// - `WEB_MAX_ALLOWED_PAGE_OFFSET`: Page offsets larger than this value are rejected. Defaults
     ///   to 200.
     /// - `WEB_PAGE_OFFSET_UA_BLOCKLIST`: A comma separated list of user-agent substrings that will
@@ -94,6 +199,47 @@ impl Default for Server {
     ///   endpoint even with a healthy database pool.
     /// - `BLOCKED_ROUTES`: A comma separated list of HTTP route patterns that are manually blocked
     ///   by an operator (e.g. `/crates/:crate_id/:version/download`).
+    /// - `READINESS_MAX_JOB_LAG_SECONDS`: How long the oldest background job may be queued before
+    ///   the `/readyz` endpoint reports the instance as not ready. Defaults to 15 minutes.
+    /// - `MAINTENANCE_MODE`: If defined (even as empty) then all write requests are rejected and
+    ///   the background worker pauses jobs that modify data in bulk. In contrast to the maintenance
+    ///   mode set through the admin API this does not require database access, and can not be
+    ///   disabled at runtime.
+    /// - `MAINTENANCE_RETRY_AFTER_SECONDS`: The default `Retry-After` value reported while in
+    ///   maintenance mode. Defaults to 5 minutes.
+    /// - `STAGED_RELEASE_SOAK_PERIOD_SECONDS`: How long staged versions are only visible to their
+    ///   owners before they are published automatically. Defaults to 1 day.
+    /// - `ACCOUNT_DELETION_GRACE_PERIOD_SECONDS`: How long confirmed account deletions can still be
+    ///   cancelled before the account is anonymized. Defaults to 14 days.
+    /// - `QUARANTINE_ACCOUNT_AGE_DAYS`: Crates published by accounts younger than this number of
+    ///   days are quarantined until they have been reviewed through the admin API. If missing,
+    ///   crates are never quarantined.
+    /// - `ZSTD_RECOMPRESSION`: If defined (even as empty) then a zstd-compressed copy of every
+    ///   newly published crate file is created by a background job.
+    /// - `CONTENT_ADDRESSED_STORAGE`: If defined (even as empty) then newly published crate files
+    ///   are stored as `objects/ab/cd/<sha256>`, so that identical files are only stored once.
+    /// - `LARGE_CRATE_MAX_UPLOAD_SIZE`: The max upload size in bytes of crates that have regularly
+    ///   published crate files close to the global limit. Defaults to 20MiB.
+    /// - `REJECT_DUPLICATE_CONTENT`: If defined (even as empty) then the first publish of a crate
+    ///   is rejected if its content is identical to another crate. Otherwise such crates are only
+    ///   quarantined for review.
+    /// - `CATEGORY_TREE_CACHE_TTL_SECONDS`: How long the category tree is cached before it is
+    ///   computed again. Defaults to 5 minutes.
+    /// - `DEPENDENCY_GRAPH_CACHE_TTL_SECONDS`: How long resolved dependency graphs are cached
+    ///   before they are resolved again. Defaults to 1 hour.
+    /// - `SEARCH_TRANSLITERATION`: If defined (even as empty) then search queries are also matched
+    ///   in their ASCII transliteration, e.g. `наивный` as `naivnyi`.
+    /// - `ALLOW_NON_ASCII_KEYWORDS`: If defined (even as empty) then keywords may contain non-ASCII
+    ///   letters and digits.
+    /// - `PRIVATE_REGISTRY`: If defined (even as empty) then all read requests, including the sparse
+    ///   index and crate downloads, require authentication. API tokens need the `read` scope.
+    ///   Crate downloads redirect to signed URLs if `SIGNED_DOWNLOADS_KEYS` is set.
+    /// - `REGION_HINT_HEADER`: The request header that contains the region of the client, e.g. as
+    ///   set by the CDN. Downloads are redirected to the `S3_REPLICAS` bucket of that region.
+    /// - `WEB_README_PREVIEW_RATE_LIMIT_RATE_SECONDS`: How often a user regains a README preview.
+    ///   Defaults to 10 seconds.
+    /// - `WEB_README_PREVIEW_RATE_LIMIT_BURST`: How many README previews a user can render in a
+    ///   burst. Defaults to 30.
     ///
     /// # Panics
     ///
@@ -151,10 +297,28 @@ impl Default for Server {
             session_key: cookie::Key::derive_from(env("SESSION_KEY").as_bytes()),
             gh_client_id: ClientId::new(env("GH_CLIENT_ID")),
             gh_client_secret: ClientSecret::new(env("GH_CLIENT_SECRET")),
+            oauth_providers: OAuthProvidersConfig::from_environment(),
+            local_auth: LocalAuthConfig::from_environment(&domain_name()),
             max_upload_size: 10 * 1024 * 1024, // 10 MB default file upload size limit
             max_unpack_size: 512 * 1024 * 1024, // 512 MB max when decompressed
+            large_crate_max_upload_size: env_optional("LARGE_CRATE_MAX_UPLOAD_SIZE")
+                .unwrap_or(20 * 1024 * 1024),
             publish_rate_limit: Default::default(),
             new_version_rate_limit: env_optional("MAX_NEW_VERSIONS_DAILY"),
+            readme_preview_rate_limit: PublishRateLimit {
+                rate: Duration::from_secs(
+                    env_optional("WEB_README_PREVIEW_RATE_LIMIT_RATE_SECONDS").unwrap_or(10),
+                ),
+                burst: env_optional("WEB_README_PREVIEW_RATE_LIMIT_BURST").unwrap_or(30),
+            },
+            takedown_request_rate_limit: PublishRateLimit {
+                rate: Duration::from_secs(60 * 60),
+                burst: 5,
+            },
+            login_rate_limit: PublishRateLimit {
+                rate: Duration::from_secs(60),
+                burst: 10,
+            },
             blocked_traffic: blocked_traffic(),
             max_allowed_page_offset: env_optional("WEB_MAX_ALLOWED_PAGE_OFFSET").unwrap_or(200),
             page_offset_ua_blocklist,
@@ -171,6 +335,7 @@ impl Default for Server {
                 .unwrap_or(60_000), // 1 minute
             ownership_invitations_expiration_days: 30,
             metrics_authorization_token: dotenvy::var("METRICS_AUTHORIZATION_TOKEN").ok(),
+            admin_authorization_token: dotenvy::var("ADMIN_AUTHORIZATION_TOKEN").ok(),
             use_test_database_pool: false,
             instance_metrics_log_every_seconds: env_optional("INSTANCE_METRICS_LOG_EVERY_SECONDS"),
             force_unconditional_redirects: dotenvy::var("FORCE_UNCONDITIONAL_REDIRECTS").is_ok(),
@@ -185,6 +350,43 @@ impl Default for Server {
             cdn_user_agent: dotenvy::var("WEB_CDN_USER_AGENT")
                 .unwrap_or_else(|_| "Amazon CloudFront".into()),
             balance_capacity: BalanceCapacityConfig::from_environment(),
+            readiness_max_job_lag: Duration::from_secs(
+                env_optional("READINESS_MAX_JOB_LAG_SECONDS")
+                    .unwrap_or(DEFAULT_READINESS_MAX_JOB_LAG),
+            ),
+            maintenance_mode: dotenvy::var("MAINTENANCE_MODE").is_ok(),
+            maintenance_retry_after: Duration::from_secs(
+                env_optional("MAINTENANCE_RETRY_AFTER_SECONDS")
+                    .unwrap_or(DEFAULT_MAINTENANCE_RETRY_AFTER),
+            ),
+            staged_release_soak_period: Duration::from_secs(
+                env_optional("STAGED_RELEASE_SOAK_PERIOD_SECONDS")
+                    .unwrap_or(DEFAULT_STAGED_RELEASE_SOAK_PERIOD),
+            ),
+            account_deletion_grace_period: Duration::from_secs(
+                env_optional("ACCOUNT_DELETION_GRACE_PERIOD_SECONDS")
+                    .unwrap_or(DEFAULT_ACCOUNT_DELETION_GRACE_PERIOD),
+            ),
+            quarantine_account_age: env_optional::<u64>("QUARANTINE_ACCOUNT_AGE_DAYS")
+                .map(|days| Duration::from_secs(days * 24 * 60 * 60)),
+            zstd_recompression: dotenvy::var("ZSTD_RECOMPRESSION").is_ok(),
+            content_addressed_storage: dotenvy::var("CONTENT_ADDRESSED_STORAGE").is_ok(),
+            reject_duplicate_content: dotenvy::var("REJECT_DUPLICATE_CONTENT").is_ok(),
+            category_tree_cache_ttl: Duration::from_secs(
+                env_optional("CATEGORY_TREE_CACHE_TTL_SECONDS")
+                    .unwrap_or(DEFAULT_CATEGORY_TREE_CACHE_TTL),
+            ),
+            dependency_graph_cache_ttl: Duration::from_secs(
+                env_optional("DEPENDENCY_GRAPH_CACHE_TTL_SECONDS")
+                    .unwrap_or(DEFAULT_DEPENDENCY_GRAPH_CACHE_TTL),
+            ),
+            search_transliteration: dotenvy::var("SEARCH_TRANSLITERATION").is_ok(),
+            allow_non_ascii_keywords: dotenvy::var("ALLOW_NON_ASCII_KEYWORDS").is_ok(),
+            private_registry: dotenvy::var("PRIVATE_REGISTRY").is_ok(),
+            signed_downloads: SignedDownloadsConfig::from_environment(),
+            upstream: UpstreamConfig::from_environment(),
+            region_hint_header: dotenvy::var("REGION_HINT_HEADER").ok(),
+            readme_sanitization: ReadmeSanitizationConfig::from_environment(),
             serve_dist: true,
             serve_html: true,
             use_fastboot: dotenvy::var("USE_FASTBOOT").ok(),
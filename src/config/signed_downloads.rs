@@ -0,0 +1,170 @@
+use crate::env_optional;
+use crate::util::signing::Signer;
+use std::time::Duration;
+
+const DEFAULT_TTL_SECONDS: u64 = 5 * 60;
+
+/// Configuration of the short-lived signed URLs that the download endpoint
+/// redirects to in private registry mode, instead of serving the crate files
+/// directly.
+#[derive(Debug)]
+pub struct SignedDownloadsConfig {
+    /// The keys that signatures are accepted for. The first key is used to
+    /// sign new URLs, the other ones are only kept around while rotating keys,
+    /// so that URLs signed with them stay valid until they expire.
+    pub keys: Vec<SigningKey>,
+    /// How long a signed URL is valid after it was handed out.
+    pub ttl: Duration,
+}
+
+#[derive(Debug)]
+pub struct SigningKey {
+    /// Identifies the key in the signed URLs, so that it can be found again
+    /// while verifying them.
+    pub id: String,
+    signer: Signer,
+}
+
+impl SigningKey {
+    pub fn new(id: &str, secret: &[u8]) -> Self {
+        Self {
+            id: id.to_string(),
+            signer: Signer::new(secret),
+        }
+    }
+}
+
+impl SignedDownloadsConfig {
+    /// Load the signed downloads configuration from the environment
+    ///
+    /// Returns `None` if no signing keys are configured, in which case the
+    /// crate files of a private registry are served directly.
+    ///
+    /// # Optional environment variables
+    ///
+    /// - `SIGNED_DOWNLOADS_KEYS`: A comma separated list of `id:secret` pairs.
+    ///   The first key is used for signing, while the other ones are still
+    ///   accepted while verifying, which allows rotating keys without
+    ///   invalidating URLs that were already handed out.
+    /// - `SIGNED_DOWNLOADS_TTL_SECONDS`: How long signed URLs are valid.
+    ///   Defaults to 5 minutes.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if one of the keys has no `id:` prefix.
+    pub fn from_environment() -> Option<Self> {
+        let keys = dotenvy::var("SIGNED_DOWNLOADS_KEYS").ok()?;
+        let keys = keys
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(|key| {
+                let (id, secret) = key
+                    .split_once(':')
+                    .expect("SIGNED_DOWNLOADS_KEYS entries must be `id:secret` pairs");
+                SigningKey::new(id, secret.as_bytes())
+            })
+            .collect::<Vec<_>>();
+
+        if keys.is_empty() {
+            return None;
+        }
+
+        let ttl = env_optional("SIGNED_DOWNLOADS_TTL_SECONDS").unwrap_or(DEFAULT_TTL_SECONDS);
+
+        Some(Self {
+            keys,
+            ttl: Duration::from_secs(ttl),
+        })
+    }
+
+    /// Returns the Unix timestamp until which newly signed URLs are valid.
+    pub fn expires_at(&self) -> i64 {
+        chrono::Utc::now().timestamp() + self.ttl.as_secs() as i64
+    }
+
+    /// Returns the query string that allows downloading the file at `path`
+    /// until the `expires` Unix timestamp, signed with the current key.
+    pub fn signed_query(&self, path: &str, expires: i64) -> String {
+        let key = &self.keys[0];
+        let signature = key.signer.sign(&signed_message(path, expires));
+        format!("expires={expires}&key={}&signature={signature}", key.id)
+    }
+
+    /// Checks the signature of a download URL that was created by
+    /// [`Self::signed_query`], with any of the configured keys.
+    ///
+    /// This does not check whether the URL has expired.
+    pub fn verify(&self, path: &str, expires: i64, key_id: &str, signature: &str) -> bool {
+        self.keys
+            .iter()
+            .find(|key| key.id == key_id)
+            .map_or(false, |key| {
+                key.signer.verify(&signed_message(path, expires), signature)
+            })
+    }
+}
+
+fn signed_message(path: &str, expires: i64) -> String {
+    format!("{path}\n{expires}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(keys: &[(&str, &str)]) -> SignedDownloadsConfig {
+        SignedDownloadsConfig {
+            keys: keys
+                .iter()
+                .map(|(id, secret)| SigningKey::new(id, secret.as_bytes()))
+                .collect(),
+            ttl: Duration::from_secs(60),
+        }
+    }
+
+    fn parse(query: &str) -> (i64, String, String) {
+        let params = url::form_urlencoded::parse(query.as_bytes()).collect::<Vec<_>>();
+        let get = |name: &str| {
+            params
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.to_string())
+                .unwrap()
+        };
+        (
+            get("expires").parse().unwrap(),
+            get("key"),
+            get("signature"),
+        )
+    }
+
+    #[test]
+    fn sign_and_verify() {
+        let config = config(&[("new", "new-secret"), ("old", "old-secret")]);
+        let path = "crates/foo/foo-1.0.0.crate";
+
+        let (expires, key, signature) = parse(&config.signed_query(path, 1000));
+        assert_eq!(expires, 1000);
+        assert_eq!(key, "new");
+        assert!(config.verify(path, expires, &key, &signature));
+
+        assert!(!config.verify("crates/bar/bar-1.0.0.crate", expires, &key, &signature));
+        assert!(!config.verify(path, 2000, &key, &signature));
+        assert!(!config.verify(path, expires, "old", &signature));
+        assert!(!config.verify(path, expires, "unknown", &signature));
+    }
+
+    #[test]
+    fn rotated_keys_are_still_accepted() {
+        let old_config = config(&[("old", "old-secret")]);
+        let rotated_config = config(&[("new", "new-secret"), ("old", "old-secret")]);
+        let path = "crates/foo/foo-1.0.0.crate";
+
+        let (expires, key, signature) = parse(&old_config.signed_query(path, 1000));
+        assert!(rotated_config.verify(path, expires, &key, &signature));
+
+        let removed_config = config(&[("new", "new-secret")]);
+        assert!(!removed_config.verify(path, expires, &key, &signature));
+    }
+}
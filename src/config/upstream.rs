@@ -0,0 +1,39 @@
+/// Configuration of the upstream registry that crates are fetched from if
+/// they do not exist locally, turning a private deployment into a combined
+/// private registry and mirror.
+#[derive(Debug)]
+pub struct UpstreamConfig {
+    /// The base URL of the sparse index of the upstream registry.
+    pub index_url: String,
+    /// The base URL that the crate files of the upstream registry are
+    /// downloaded from.
+    pub dl_url: String,
+}
+
+impl UpstreamConfig {
+    /// Load the upstream registry configuration from the environment
+    ///
+    /// Returns `None` if the upstream proxy mode is disabled.
+    ///
+    /// # Optional environment variables
+    ///
+    /// - `UPSTREAM_PROXY`: If defined (even as empty) then crates that do not
+    ///   exist locally are fetched from the upstream registry.
+    /// - `UPSTREAM_INDEX_URL`: The base URL of the upstream sparse index.
+    ///   Defaults to `https://index.crates.io`.
+    /// - `UPSTREAM_DL_URL`: The base URL of the upstream crate files. Defaults
+    ///   to `https://static.crates.io/crates`.
+    pub fn from_environment() -> Option<Self> {
+        dotenvy::var("UPSTREAM_PROXY").ok()?;
+
+        let index_url = dotenvy::var("UPSTREAM_INDEX_URL")
+            .unwrap_or_else(|_| "https://index.crates.io".to_string());
+        let dl_url = dotenvy::var("UPSTREAM_DL_URL")
+            .unwrap_or_else(|_| "https://static.crates.io/crates".to_string());
+
+        Some(Self {
+            index_url: index_url.trim_end_matches('/').to_string(),
+            dl_url: dl_url.trim_end_matches('/').to_string(),
+        })
+    }
+}
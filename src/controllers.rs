@@ -64,15 +64,22 @@ mod prelude {
 pub mod helpers;
 pub mod util;
 
+pub mod admin;
 pub mod category;
-mod conduit_axum;
+pub(crate) mod conduit_axum;
 pub mod crate_owner_invitation;
 pub mod git;
 pub mod github;
+pub mod health;
+pub mod image_proxy;
+pub mod index_snapshot;
 pub mod keyword;
 pub mod krate;
 pub mod metrics;
+pub mod namespace_claim;
 pub mod site_metadata;
+pub mod sparse_index;
+pub mod takedown_request;
 pub mod team;
 pub mod token;
 pub mod user;
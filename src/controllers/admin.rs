@@ -0,0 +1,102 @@
+//! Endpoints for crates.io operators
+//!
+//! All of these endpoints require the `ADMIN_AUTHORIZATION_TOKEN` to be sent as a bearer token
+//! in the `Authorization` header, and are disabled if the token is not configured.
+
+use super::frontend_prelude::*;
+use crate::models::MaintenanceModeSetting;
+use crate::util::errors::{forbidden, not_found};
+use std::time::Duration;
+
+pub mod advisories;
+pub mod index_replays;
+pub mod legal_holds;
+pub mod orphaned_files;
+pub mod quarantines;
+pub mod upload_limits;
+
+/// Makes sure that the request contains the configured admin authorization token.
+fn verify_admin_token(app: &AppState, req: &Parts) -> AppResult<()> {
+    let Some(expected_token) = &app.config.admin_authorization_token else {
+        // To avoid accidentally exposing the admin API if the environment variable is not set,
+        // prevent access to all admin endpoints if the authorization token is not configured.
+        return Err(not_found());
+    };
+
+    let provided_token = req
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    if provided_token != Some(expected_token.as_str()) {
+        return Err(forbidden());
+    }
+
+    Ok(())
+}
+
+fn maintenance_mode_json(app: &AppState) -> Json<Value> {
+    let maintenance_mode = &app.maintenance_mode;
+    Json(json!({
+        "maintenance": {
+            "enabled": maintenance_mode.is_enabled(),
+            "retry_after": maintenance_mode.retry_after().as_secs(),
+        },
+    }))
+}
+
+/// Handles the `GET /api/private/admin/maintenance` route.
+pub async fn maintenance_mode(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        verify_admin_token(&app, &req)?;
+        app.refresh_maintenance_mode()?;
+        Ok(maintenance_mode_json(&app))
+    })
+    .await
+}
+
+/// Handles the `PUT /api/private/admin/maintenance` route.
+///
+/// Enables or disables the maintenance mode. While the maintenance mode is enabled, all write
+/// requests are rejected with `503 Service Unavailable`, and the background worker pauses jobs
+/// that modify data in bulk.
+///
+/// The setting is persisted in the database, and picked up by the other instances and the
+/// background worker when they poll it next. A maintenance mode enabled through the
+/// `MAINTENANCE_MODE` environment variable can not be disabled through this endpoint.
+pub async fn update_maintenance_mode(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
+    #[derive(Deserialize)]
+    struct MaintenanceModeUpdate {
+        enabled: bool,
+        /// Estimated duration of the maintenance in seconds
+        retry_after: Option<u64>,
+    }
+
+    conduit_compat(move || {
+        let (req, body) = req.0.into_parts();
+        verify_admin_token(&app, &req)?;
+
+        let update: MaintenanceModeUpdate = serde_json::from_slice(&body)
+            .map_err(|e| bad_request(&format!("invalid maintenance mode update: {e}")))?;
+
+        let retry_after = update
+            .retry_after
+            .map(Duration::from_secs)
+            .unwrap_or(app.config.maintenance_retry_after);
+
+        warn!(
+            enabled = update.enabled,
+            ?retry_after,
+            "Updating maintenance mode"
+        );
+
+        let conn = &mut *app.db_write()?;
+        let setting = MaintenanceModeSetting::save(conn, update.enabled, retry_after)?;
+        app.maintenance_mode
+            .set(setting.enabled, setting.retry_after());
+
+        Ok(maintenance_mode_json(&app))
+    })
+    .await
+}
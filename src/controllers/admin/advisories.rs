@@ -0,0 +1,117 @@
+//! Endpoints for managing the security advisories of crates
+//!
+//! The advisories are returned by the `POST /api/v1/resolve` endpoint for the
+//! affected versions.
+
+use super::verify_admin_token;
+use crate::controllers::frontend_prelude::*;
+use crate::models::{insert_advisory_notification, Advisory, Crate, NewAdvisory};
+use crate::views::EncodableAdvisory;
+
+/// Handles the `GET /api/private/admin/advisories` route.
+pub async fn list(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        verify_admin_token(&app, &req)?;
+
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let advisories = Advisory::all(conn)?
+            .into_iter()
+            .map(|(advisory, crate_name)| EncodableAdvisory::from(advisory, crate_name))
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({ "advisories": advisories })))
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct AdvisoryUpdate {
+    affected: String,
+}
+
+/// Handles the `PUT /api/private/admin/advisories/:crate_id/:advisory_id` route.
+///
+/// Records an advisory of a crate, or replaces the affected versions of an
+/// existing advisory. The subscribers of the crate are notified about new
+/// advisories and about changes of the affected versions.
+pub async fn update(
+    app: AppState,
+    Path((crate_name, identifier)): Path<(String, String)>,
+    req: BytesRequest,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let (req, body) = req.0.into_parts();
+        verify_admin_token(&app, &req)?;
+
+        let update: AdvisoryUpdate = serde_json::from_slice(&body)
+            .map_err(|e| bad_request(&format!("invalid advisory: {e}")))?;
+
+        if let Err(e) = semver::VersionReq::parse(&update.affected) {
+            let detail = format!("invalid version requirement `{}`: {e}", update.affected);
+            return Err(bad_request(&detail));
+        }
+
+        let conn = &mut *app.db_write()?;
+        let krate = find_crate(conn, &crate_name)?;
+
+        let advisory = conn.transaction(|conn| -> AppResult<_> {
+            let previous = Advisory::find(conn, krate.id, &identifier)?;
+
+            let advisory = NewAdvisory {
+                crate_id: krate.id,
+                identifier: &identifier,
+                affected: &update.affected,
+            }
+            .upsert(conn)?;
+
+            if previous.map_or(true, |previous| previous.affected != advisory.affected) {
+                insert_advisory_notification(conn, advisory.id)?;
+            }
+
+            Ok(advisory)
+        })?;
+
+        warn!(
+            krate = %krate.name,
+            advisory = %advisory.identifier,
+            affected = %advisory.affected,
+            "Recorded advisory"
+        );
+
+        let advisory = EncodableAdvisory::from(advisory, krate.name);
+        Ok(Json(json!({ "advisory": advisory })))
+    })
+    .await
+}
+
+/// Handles the `DELETE /api/private/admin/advisories/:crate_id/:advisory_id` route.
+pub async fn delete(
+    app: AppState,
+    Path((crate_name, identifier)): Path<(String, String)>,
+    req: Parts,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        verify_admin_token(&app, &req)?;
+
+        let conn = &mut *app.db_write()?;
+        let krate = find_crate(conn, &crate_name)?;
+
+        if !Advisory::delete(conn, krate.id, &identifier)? {
+            return Err(bad_request(&format_args!(
+                "crate `{crate_name}` has no advisory `{identifier}`"
+            )));
+        }
+
+        warn!(krate = %krate.name, advisory = %identifier, "Removed advisory");
+
+        Ok(Json(json!({ "ok": true })))
+    })
+    .await
+}
+
+fn find_crate(conn: &mut PgConnection, crate_name: &str) -> AppResult<Crate> {
+    Crate::by_name(crate_name)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| bad_request(&format_args!("crate `{crate_name}` does not exist")))
+}
@@ -0,0 +1,62 @@
+//! Endpoint for replaying the index sync of a single crate
+//!
+//! The index files are always regenerated from the database, so replaying the sync fixes index
+//! files that got out of sync, e.g. after a failed job was discarded.
+
+use super::verify_admin_token;
+use crate::background_jobs::Job;
+use crate::controllers::frontend_prelude::*;
+use crate::models::{latest_index_sequence, Crate, IndexSyncState};
+use crate::views::EncodableIndexSyncState;
+
+/// Handles the `PUT /api/private/admin/index_replays/:crate_id` route.
+///
+/// Enqueues the index sync jobs of the crate with a new sequence number, so
+/// that all pending jobs of the crate are skipped in favor of them. The sync
+/// states before the replay are returned.
+///
+/// Crates that were deleted can be replayed as long as their index files were
+/// synced before, which removes their index files.
+pub async fn replay(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: Parts,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        verify_admin_token(&app, &req)?;
+
+        let conn = &mut *app.db_write()?;
+
+        let krate: Option<Crate> = Crate::by_name(&crate_name).first(conn).optional()?;
+        let exists = krate.is_some();
+        let crate_name = krate.map_or(crate_name, |krate| krate.name);
+
+        let sync_states = IndexSyncState::for_crate(conn, &crate_name)?;
+        if !exists && sync_states.is_empty() {
+            return Err(bad_request(&format_args!(
+                "crate `{crate_name}` does not exist"
+            )));
+        }
+
+        let sequence = conn.transaction(|conn| {
+            Job::enqueue_sync_to_index(&crate_name, conn)?;
+            Ok::<_, BoxedAppError>(latest_index_sequence(conn, &crate_name)?)
+        })?;
+
+        warn!(krate = %crate_name, %sequence, "Replaying index sync");
+
+        let sync_states = sync_states
+            .into_iter()
+            .map(EncodableIndexSyncState::from)
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({
+            "replay": {
+                "crate": crate_name,
+                "sequence": sequence,
+                "sync_states": sync_states,
+            },
+        })))
+    })
+    .await
+}
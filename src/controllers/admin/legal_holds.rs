@@ -0,0 +1,212 @@
+//! Endpoints for placing crates and versions under legal hold
+//!
+//! Crates and versions under legal hold can not be downloaded, and crates
+//! under legal hold are hidden from search, but their data is preserved.
+
+use super::verify_admin_token;
+use crate::background_jobs::Job;
+use crate::controllers::frontend_prelude::*;
+use crate::models::{Crate, LegalHold, NewLegalHold, TakedownRequest};
+use crate::schema::{crates, legal_holds, takedown_requests, versions};
+use crate::views::{EncodableLegalHold, EncodableTakedownRequest};
+use url::Url;
+
+/// Loads the crate name, version number and audit trail of a legal hold.
+fn encode_hold(conn: &mut PgConnection, hold: LegalHold) -> QueryResult<EncodableLegalHold> {
+    let crate_name = crates::table
+        .find(hold.crate_id)
+        .select(crates::name)
+        .first(conn)?;
+
+    let version = hold
+        .version_id
+        .map(|version_id| {
+            versions::table
+                .find(version_id)
+                .select(versions::num)
+                .first(conn)
+        })
+        .transpose()?;
+
+    let actions = hold.actions(conn)?;
+    Ok(EncodableLegalHold::from(hold, crate_name, version, actions))
+}
+
+/// Handles the `GET /api/private/admin/takedown_requests` route.
+///
+/// Lists all takedown requests that have not been resolved yet.
+pub async fn list_takedown_requests(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        verify_admin_token(&app, &req)?;
+
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let requests = TakedownRequest::unresolved(conn)?
+            .into_iter()
+            .map(EncodableTakedownRequest::from)
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({ "takedown_requests": requests })))
+    })
+    .await
+}
+
+/// Handles the `GET /api/private/admin/legal_holds` route.
+///
+/// Lists all legal holds, including the lifted ones, with their audit trail.
+pub async fn list(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        verify_admin_token(&app, &req)?;
+
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let holds: Vec<LegalHold> = legal_holds::table
+            .order(legal_holds::id.desc())
+            .load(conn)?;
+
+        let holds = holds
+            .into_iter()
+            .map(|hold| encode_hold(conn, hold))
+            .collect::<QueryResult<Vec<_>>>()?;
+
+        Ok(Json(json!({ "legal_holds": holds })))
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct PlaceLegalHold {
+    #[serde(rename = "crate")]
+    krate: String,
+    version: Option<String>,
+    reason: String,
+    notice_url: Option<String>,
+    takedown_request_id: Option<i32>,
+    note: Option<String>,
+}
+
+/// Handles the `POST /api/private/admin/legal_holds` route.
+///
+/// Places a crate, or a single version if `version` is set, under legal hold.
+pub async fn place(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let (req, body) = req.0.into_parts();
+        verify_admin_token(&app, &req)?;
+
+        let params: PlaceLegalHold = serde_json::from_slice(&body)
+            .map_err(|e| bad_request(&format!("invalid legal hold: {e}")))?;
+
+        if params.reason.trim().is_empty() {
+            return Err(bad_request("the reason of a legal hold must not be empty"));
+        }
+
+        if let Some(notice_url) = &params.notice_url {
+            let is_valid = Url::parse(notice_url)
+                .map_or(false, |url| matches!(url.scheme(), "http" | "https"));
+            if !is_valid {
+                return Err(bad_request(&format!("invalid notice URL: {notice_url}")));
+            }
+        }
+
+        let conn = &mut *app.db_write()?;
+
+        let krate: Crate = Crate::by_name(&params.krate)
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| bad_request(&format_args!("crate `{}` does not exist", params.krate)))?;
+
+        let version_id = match &params.version {
+            Some(version) => Some(
+                versions::table
+                    .filter(versions::crate_id.eq(krate.id))
+                    .filter(versions::num.eq(version))
+                    .select(versions::id)
+                    .first::<i32>(conn)
+                    .optional()?
+                    .ok_or_else(|| {
+                        bad_request(&format_args!(
+                            "crate `{}` does not have a version `{version}`",
+                            krate.name
+                        ))
+                    })?,
+            ),
+            None => None,
+        };
+
+        if let Some(takedown_request_id) = params.takedown_request_id {
+            takedown_requests::table
+                .find(takedown_request_id)
+                .select(takedown_requests::id)
+                .first::<i32>(conn)
+                .optional()?
+                .ok_or_else(|| {
+                    bad_request(&format_args!(
+                        "takedown request {takedown_request_id} does not exist"
+                    ))
+                })?;
+        }
+
+        let hold = NewLegalHold {
+            crate_id: krate.id,
+            version_id,
+            takedown_request_id: params.takedown_request_id,
+            reason: params.reason.trim(),
+            notice_url: params.notice_url.as_deref(),
+        }
+        .place(conn, params.note.as_deref())?;
+
+        warn!(
+            krate = %krate.name,
+            version = ?params.version,
+            hold_id = hold.id,
+            "Placed legal hold"
+        );
+
+        // Other instances pick up the hold with their next periodic refresh,
+        // see `App::refresh_legal_holds()`
+        app.legal_holds.refresh(conn)?;
+        app.version_id_cacher.invalidate_all();
+
+        Job::sync_legal_hold(hold.id).enqueue(conn)?;
+
+        Ok(Json(json!({ "legal_hold": encode_hold(conn, hold)? })))
+    })
+    .await
+}
+
+#[derive(Deserialize, Default)]
+struct LiftLegalHold {
+    note: Option<String>,
+}
+
+/// Handles the `PUT /api/private/admin/legal_holds/:id/lift` route.
+pub async fn lift(app: AppState, Path(id): Path<i32>, req: BytesRequest) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let (req, body) = req.0.into_parts();
+        verify_admin_token(&app, &req)?;
+
+        let params: LiftLegalHold = if body.is_empty() {
+            LiftLegalHold::default()
+        } else {
+            serde_json::from_slice(&body)
+                .map_err(|e| bad_request(&format!("invalid legal hold update: {e}")))?
+        };
+
+        let conn = &mut *app.db_write()?;
+
+        let hold: LegalHold = legal_holds::table.find(id).first(conn)?;
+
+        if !hold.is_active() {
+            return Err(bad_request(&format!("legal hold {id} was already lifted")));
+        }
+
+        let hold = hold.lift(conn, params.note.as_deref())?;
+        warn!(hold_id = hold.id, "Lifted legal hold");
+
+        app.legal_holds.refresh(conn)?;
+        app.version_id_cacher.invalidate_all();
+
+        Job::sync_legal_hold(hold.id).enqueue(conn)?;
+
+        Ok(Json(json!({ "legal_hold": encode_hold(conn, hold)? })))
+    })
+    .await
+}
@@ -0,0 +1,30 @@
+//! Endpoints for the cleanup of orphaned files in the storage bucket
+//!
+//! The cleanup itself is run by the `cleanup_orphaned_files` background job,
+//! which can be enqueued with `crates-admin enqueue-job`.
+
+use super::verify_admin_token;
+use crate::controllers::frontend_prelude::*;
+use crate::models::OrphanedFileReport;
+use crate::views::EncodableOrphanedFileReport;
+
+/// The number of reports that are returned by the report endpoint.
+const MAX_REPORTS: i64 = 10;
+
+/// Handles the `GET /api/private/admin/orphaned_files` route.
+///
+/// Lists the reports of the most recent cleanup runs, newest first.
+pub async fn reports(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        verify_admin_token(&app, &req)?;
+
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let reports = OrphanedFileReport::recent(conn, MAX_REPORTS)?
+            .into_iter()
+            .map(EncodableOrphanedFileReport::from)
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({ "reports": reports })))
+    })
+    .await
+}
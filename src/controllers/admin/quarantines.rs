@@ -0,0 +1,142 @@
+//! Endpoints for reviewing quarantined crates
+//!
+//! Crates published by accounts younger than the configured
+//! `quarantine_account_age`, and new crates whose content is identical to
+//! another crate, are flagged in the API until they have been approved.
+//! Rejecting a crate yanks all of its versions.
+
+use super::verify_admin_token;
+use crate::background_jobs::Job;
+use crate::controllers::frontend_prelude::*;
+use crate::models::{Crate, CrateQuarantine, QuarantineStatus};
+use crate::schema::{crates, users, versions};
+use crate::views::EncodableCrateQuarantine;
+
+/// Loads the crate name, publisher login and duplicated version of a
+/// quarantine.
+fn encode_quarantine(
+    conn: &mut PgConnection,
+    quarantine: CrateQuarantine,
+) -> QueryResult<EncodableCrateQuarantine> {
+    let crate_name = crates::table
+        .find(quarantine.crate_id)
+        .select(crates::name)
+        .first(conn)?;
+
+    let user_login = quarantine
+        .user_id
+        .map(|user_id| {
+            users::table
+                .find(user_id)
+                .select(users::gh_login)
+                .first(conn)
+        })
+        .transpose()?;
+
+    let duplicate_of = quarantine
+        .duplicate_of
+        .map(|version_id| {
+            versions::table
+                .find(version_id)
+                .inner_join(crates::table)
+                .select((crates::name, versions::num))
+                .first(conn)
+        })
+        .transpose()?;
+
+    Ok(EncodableCrateQuarantine::from(
+        quarantine,
+        crate_name,
+        user_login,
+        duplicate_of,
+    ))
+}
+
+/// Handles the `GET /api/private/admin/quarantines` route.
+///
+/// Lists all quarantined crates that are waiting for review.
+pub async fn list(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        verify_admin_token(&app, &req)?;
+
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let quarantines = CrateQuarantine::pending(conn)?
+            .into_iter()
+            .map(|quarantine| encode_quarantine(conn, quarantine))
+            .collect::<QueryResult<Vec<_>>>()?;
+
+        Ok(Json(json!({ "quarantines": quarantines })))
+    })
+    .await
+}
+
+#[derive(Deserialize, Default)]
+struct ReviewQuarantine {
+    note: Option<String>,
+}
+
+/// Handles the `PUT /api/private/admin/quarantines/:crate_id/approve` route.
+pub async fn approve(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: BytesRequest,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || review(&app, &crate_name, req, QuarantineStatus::Approved)).await
+}
+
+/// Handles the `PUT /api/private/admin/quarantines/:crate_id/reject` route.
+pub async fn reject(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: BytesRequest,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || review(&app, &crate_name, req, QuarantineStatus::Rejected)).await
+}
+
+fn review(
+    app: &AppState,
+    crate_name: &str,
+    req: BytesRequest,
+    status: QuarantineStatus,
+) -> AppResult<Json<Value>> {
+    let (req, body) = req.0.into_parts();
+    verify_admin_token(app, &req)?;
+
+    let params: ReviewQuarantine = if body.is_empty() {
+        ReviewQuarantine::default()
+    } else {
+        serde_json::from_slice(&body)
+            .map_err(|e| bad_request(&format!("invalid quarantine review: {e}")))?
+    };
+
+    let conn = &mut *app.db_write()?;
+
+    let krate: Crate = Crate::by_name(crate_name)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| bad_request(&format_args!("crate `{crate_name}` does not exist")))?;
+
+    let quarantine = CrateQuarantine::find(conn, krate.id)?
+        .ok_or_else(|| bad_request(&format_args!("crate `{crate_name}` is not quarantined")))?;
+
+    if !quarantine.is_pending() {
+        return Err(bad_request(&format_args!(
+            "crate `{crate_name}` was already {}",
+            quarantine.status
+        )));
+    }
+
+    let quarantine = conn.transaction(|conn| {
+        let quarantine = quarantine.review(conn, status, params.note.as_deref())?;
+        if status == QuarantineStatus::Rejected {
+            Job::enqueue_sync_to_index(&krate.name, conn)?;
+        }
+        AppResult::Ok(quarantine)
+    })?;
+
+    warn!(krate = %krate.name, status = status.as_str(), "Reviewed quarantined crate");
+
+    Ok(Json(
+        json!({ "quarantine": encode_quarantine(conn, quarantine)? }),
+    ))
+}
@@ -0,0 +1,111 @@
+//! Endpoints for managing per-crate upload limit overrides
+//!
+//! Overrides take precedence over the global `max_upload_size` and the
+//! automatic limit for crates with large crate files, see the
+//! `upload_limits` module.
+
+use super::verify_admin_token;
+use crate::controllers::frontend_prelude::*;
+use crate::models::{Crate, NewUploadLimit, UploadLimit};
+use crate::views::EncodableUploadLimit;
+
+/// Handles the `GET /api/private/admin/upload_limits` route.
+pub async fn list(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        verify_admin_token(&app, &req)?;
+
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let upload_limits = UploadLimit::all(conn)?
+            .into_iter()
+            .map(|(limit, crate_name)| EncodableUploadLimit::from(limit, crate_name))
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({ "upload_limits": upload_limits })))
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct UploadLimitUpdate {
+    max_upload_size: i64,
+    max_unpack_size: Option<i64>,
+    note: Option<String>,
+}
+
+/// Handles the `PUT /api/private/admin/upload_limits/:crate_id` route.
+///
+/// Creates or replaces the upload limit override of a crate.
+pub async fn update(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: BytesRequest,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let (req, body) = req.0.into_parts();
+        verify_admin_token(&app, &req)?;
+
+        let update: UploadLimitUpdate = serde_json::from_slice(&body)
+            .map_err(|e| bad_request(&format!("invalid upload limit: {e}")))?;
+
+        if update.max_upload_size <= 0 || update.max_unpack_size.map_or(false, |size| size <= 0) {
+            return Err(bad_request("upload limits must be positive"));
+        }
+
+        let conn = &mut *app.db_write()?;
+        let krate = find_crate(conn, &crate_name)?;
+
+        let limit = NewUploadLimit {
+            crate_id: krate.id,
+            max_upload_size: update.max_upload_size,
+            max_unpack_size: update.max_unpack_size,
+            note: update.note.as_deref(),
+        }
+        .upsert(conn)?;
+
+        warn!(
+            krate = %krate.name,
+            max_upload_size = limit.max_upload_size,
+            max_unpack_size = limit.max_unpack_size,
+            "Updated upload limit override"
+        );
+
+        let limit = EncodableUploadLimit::from(limit, krate.name);
+        Ok(Json(json!({ "upload_limit": limit })))
+    })
+    .await
+}
+
+/// Handles the `DELETE /api/private/admin/upload_limits/:crate_id` route.
+///
+/// Removes the upload limit override of a crate, so that the default policy
+/// applies again.
+pub async fn delete(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: Parts,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        verify_admin_token(&app, &req)?;
+
+        let conn = &mut *app.db_write()?;
+        let krate = find_crate(conn, &crate_name)?;
+
+        if !UploadLimit::delete(conn, krate.id)? {
+            return Err(bad_request(&format_args!(
+                "crate `{crate_name}` has no upload limit override"
+            )));
+        }
+
+        warn!(krate = %krate.name, "Removed upload limit override");
+
+        Ok(Json(json!({ "ok": true })))
+    })
+    .await
+}
+
+fn find_crate(conn: &mut PgConnection, crate_name: &str) -> AppResult<Crate> {
+    Crate::by_name(crate_name)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| bad_request(&format_args!("crate `{crate_name}` does not exist")))
+}
@@ -1,9 +1,15 @@
 use super::helpers::pagination::*;
 use super::prelude::*;
 
-use crate::models::Category;
-use crate::schema::categories;
-use crate::views::{EncodableCategory, EncodableCategoryWithSubcategories};
+use crate::models::krate::ALL_COLUMNS;
+use crate::models::{Category, Crate, CrateVersions, TopVersions, Version};
+use crate::schema::{categories, crates, crates_categories, recent_crate_downloads};
+use crate::util::errors::bad_request;
+use crate::views::{
+    EncodableCategory, EncodableCategoryTreeNode, EncodableCategoryWithSubcategories,
+    EncodableCrate,
+};
+use std::sync::Arc;
 
 /// Handles the `GET /categories` route.
 pub async fn index(app: AppState, req: Parts) -> AppResult<Json<Value>> {
@@ -67,6 +73,131 @@ pub async fn show(state: AppState, Path(slug): Path<String>) -> AppResult<Json<V
     .await
 }
 
+/// Handles the `GET /categories/tree` route.
+///
+/// Returns all categories as a tree, with the crate counts of each category including and
+/// excluding its subcategories. The tree is cached for `category_tree_cache_ttl`.
+pub async fn tree(state: AppState) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let tree = match state.category_tree_cache.get(&()) {
+            Some(tree) => tree,
+            None => {
+                let conn = &mut *state.db_read()?;
+                let tree = Arc::new(EncodableCategoryTreeNode::from_rows(Category::tree(conn)?));
+                state
+                    .category_tree_cache
+                    .blocking()
+                    .insert((), Arc::clone(&tree));
+                tree
+            }
+        };
+
+        Ok(Json(json!({ "categories": *tree })))
+    })
+    .await
+}
+
+/// The maximum number of categories returned by the `GET /categories/suggest` route.
+const MAX_SUGGESTIONS: i64 = 10;
+
+/// Handles the `GET /categories/suggest` route.
+///
+/// Returns the categories best matching the `q` query parameter, for autocompletion.
+pub async fn suggest(state: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let query = req.query();
+        let q = query
+            .get("q")
+            .map(|q| q.trim())
+            .filter(|q| !q.is_empty())
+            .ok_or_else(|| bad_request("missing query parameter `q`"))?;
+
+        let conn = &mut *state.db_read()?;
+        let categories = Category::suggest(conn, q, MAX_SUGGESTIONS)?
+            .into_iter()
+            .map(Category::into)
+            .collect::<Vec<EncodableCategory>>();
+
+        Ok(Json(json!({ "categories": categories })))
+    })
+    .await
+}
+
+/// Handles the `GET /categories/:category_id/crates` route.
+///
+/// Returns the crates in the category, sorted by name. If the `include_children=true` query
+/// parameter is set, crates in any of its subcategories are included as well.
+pub async fn crates(
+    state: AppState,
+    Path(slug): Path<String>,
+    req: Parts,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let include_children = req
+            .query()
+            .get("include_children")
+            .map_or(false, |value| value == "true");
+        let pagination = PaginationOptions::builder().gather(&req)?;
+
+        let conn = &mut *state.db_read()?;
+        let category: Category = Category::by_slug(&slug).first(conn)?;
+        let category_ids = if include_children {
+            category
+                .with_descendants(conn)?
+                .into_iter()
+                .map(|category| category.id)
+                .collect()
+        } else {
+            vec![category.id]
+        };
+
+        let crate_ids = crates_categories::table
+            .select(crates_categories::crate_id)
+            .filter(crates_categories::category_id.eq_any(category_ids));
+
+        let data: Paginated<(Crate, Option<i64>)> = crates::table
+            .left_join(recent_crate_downloads::table)
+            .filter(crates::id.eq_any(crate_ids))
+            .select((ALL_COLUMNS, recent_crate_downloads::downloads.nullable()))
+            .order(crates::name.asc())
+            .pages_pagination(pagination)
+            .load(conn)?;
+
+        let total = data.total();
+        let next_page = data.next_page_params().map(|p| req.query_with_params(p));
+        let prev_page = data.prev_page_params().map(|p| req.query_with_params(p));
+
+        let (crates, recent_downloads): (Vec<_>, Vec<_>) = data.into_iter().unzip();
+        let versions: Vec<Version> = crates.versions().load(conn)?;
+        let crates = versions
+            .grouped_by(&crates)
+            .into_iter()
+            .map(TopVersions::from_versions)
+            .zip(crates)
+            .zip(recent_downloads)
+            .map(|((top_versions, krate), recent_downloads)| {
+                EncodableCrate::from_minimal(
+                    krate,
+                    Some(&top_versions),
+                    Some(vec![]),
+                    false,
+                    Some(recent_downloads.unwrap_or(0)),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({
+            "crates": crates,
+            "meta": {
+                "total": total,
+                "next_page": next_page,
+                "prev_page": prev_page,
+            },
+        })))
+    })
+    .await
+}
+
 /// Handles the `GET /category_slugs` route.
 pub async fn slugs(state: AppState) -> AppResult<Json<Value>> {
     conduit_compat(move || {
@@ -1,17 +1,24 @@
 use crate::util::errors::AppResult;
+use crate::util::request_id::RequestId;
 use sentry::Hub;
 use std::convert::identity;
 use tokio::task::JoinHandle;
+use tracing::Span;
 
 /// Just like [tokio::task::spawn_blocking], but automatically runs the passed
-/// in function in the context of the current Sentry hub.
+/// in function in the context of the current Sentry hub, `tracing` span and
+/// request ID.
 fn spawn_blocking<F, R>(f: F) -> JoinHandle<R>
 where
     F: FnOnce() -> R + Send + 'static,
     R: Send + 'static,
 {
     let hub = Hub::current();
-    tokio::task::spawn_blocking(move || Hub::run(hub, f))
+    let span = Span::current();
+    let request_id = RequestId::current();
+    tokio::task::spawn_blocking(move || {
+        RequestId::in_scope(request_id, || span.in_scope(|| Hub::run(hub, f)))
+    })
 }
 
 /// This runs the passed-in function in a synchronous [spawn_blocking] context
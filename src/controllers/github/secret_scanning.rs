@@ -1,6 +1,6 @@
 use crate::app::AppState;
 use crate::controllers::frontend_prelude::*;
-use crate::models::{ApiToken, User};
+use crate::models::{ApiToken, User, UserSession};
 use crate::schema::api_tokens;
 use crate::util::token::HashedToken;
 use anyhow::{anyhow, Context};
@@ -190,6 +190,9 @@ fn alert_revoke_token(
         "Active API token received and revoked (true positive)",
     );
 
+    // The account may have been compromised, so the web sessions are revoked too
+    UserSession::revoke_all_for_user(conn, token.user_id, None)?;
+
     if let Err(error) = send_notification_email(&token, alert, state, conn) {
         warn!(
             token_id = %token.id, user_id = %token.user_id, ?error,
@@ -0,0 +1,132 @@
+//! Liveness and readiness probes for container orchestrators like Kubernetes.
+
+use crate::controllers::frontend_prelude::*;
+use diesel::dsl::{min, now};
+use std::time::Duration;
+
+/// Requests to the file storage that take longer than this are considered failed.
+const STORAGE_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The result of a readiness check.
+///
+/// The probe is not authenticated, so the reason of a failure is only logged,
+/// since error messages can contain internal host or bucket names.
+#[derive(Serialize)]
+struct ReadinessCheck {
+    ok: bool,
+}
+
+impl ReadinessCheck {
+    fn ok() -> Self {
+        Self { ok: true }
+    }
+
+    fn failed(check: &str, detail: impl std::fmt::Display) -> Self {
+        warn!(check, %detail, "Readiness check failed");
+        Self { ok: false }
+    }
+}
+
+/// Handles the `GET /healthz` route.
+///
+/// This only confirms that the server process is able to respond to requests,
+/// without checking any of its external dependencies.
+pub async fn liveness() -> AppResult<Response> {
+    ok_true()
+}
+
+/// Handles the `GET /readyz` route.
+///
+/// Responds with `503 Service Unavailable` if the database or the file storage
+/// can't be reached, or if the oldest background job has been waiting in the
+/// queue for longer than the configured threshold.
+pub async fn readiness(app: AppState) -> AppResult<Response> {
+    let storage = check_storage(&app).await;
+
+    let (database, background_jobs) = conduit_compat(move || Ok(check_database(&app))).await?;
+
+    let ok = database.ok && storage.ok && background_jobs.ok;
+    let status = if ok {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    let json = json!({
+        "ok": ok,
+        "checks": {
+            "database": database,
+            "storage": storage,
+            "background_jobs": background_jobs,
+        },
+    });
+
+    Ok((status, Json(json)).into_response())
+}
+
+/// Handles the `GET /api/private/circuit_breakers` route.
+///
+/// Reports the state of the circuit breakers that guard the calls to external
+/// dependencies like the GitHub API. An open breaker does not make the
+/// instance unready, since most requests don't depend on these services.
+pub async fn circuit_breakers(app: AppState) -> Json<Value> {
+    Json(json!({ "circuit_breakers": app.circuit_breakers.statuses() }))
+}
+
+async fn check_storage(app: &AppState) -> ReadinessCheck {
+    match tokio::time::timeout(STORAGE_CHECK_TIMEOUT, app.storage.check_availability()).await {
+        Ok(Ok(())) => ReadinessCheck::ok(),
+        Ok(Err(error)) => ReadinessCheck::failed("storage", error),
+        Err(_) => ReadinessCheck::failed("storage", "timed out"),
+    }
+}
+
+/// Checks the database connectivity and the background job queue lag.
+fn check_database(app: &AppState) -> (ReadinessCheck, ReadinessCheck) {
+    let conn = &mut *match app.db_read_prefer_primary() {
+        Ok(conn) => conn,
+        Err(error) => {
+            let database = ReadinessCheck::failed("database", error);
+            let background_jobs = ReadinessCheck::failed("background_jobs", "database unavailable");
+            return (database, background_jobs);
+        }
+    };
+
+    match oldest_job_lag(conn) {
+        Ok(lag) => {
+            let max_lag = app.config.readiness_max_job_lag;
+            let background_jobs = match lag {
+                Some(lag) if lag > max_lag => ReadinessCheck::failed(
+                    "background_jobs",
+                    format_args!(
+                        "oldest background job has been queued for {} seconds",
+                        lag.as_secs()
+                    ),
+                ),
+                _ => ReadinessCheck::ok(),
+            };
+
+            (ReadinessCheck::ok(), background_jobs)
+        }
+        Err(error) => {
+            let database = ReadinessCheck::failed("database", error);
+            let background_jobs = ReadinessCheck::failed("background_jobs", "database unavailable");
+            (database, background_jobs)
+        }
+    }
+}
+
+/// Returns how long the oldest background job has been waiting in the queue,
+/// or `None` if the queue is empty.
+///
+/// Jobs with a negative priority are ignored, just like in the `monitor` binary.
+fn oldest_job_lag(conn: &mut PgConnection) -> QueryResult<Option<Duration>> {
+    use crate::schema::background_jobs::dsl::*;
+
+    let (oldest, current): (Option<chrono::NaiveDateTime>, chrono::NaiveDateTime) = background_jobs
+        .select((min(created_at), now))
+        .filter(priority.ge(0))
+        .get_result(conn)?;
+
+    Ok(oldest.map(|oldest| (current - oldest).to_std().unwrap_or_default()))
+}
@@ -2,8 +2,10 @@ use crate::controllers::cargo_prelude::{AppResult, Response};
 use axum::response::IntoResponse;
 use axum::Json;
 
+pub(crate) mod download_series;
 pub(crate) mod pagination;
 
+pub(crate) use self::download_series::DownloadSeries;
 pub(crate) use self::pagination::Paginate;
 
 pub fn ok_true() -> AppResult<Response> {
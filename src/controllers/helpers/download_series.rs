@@ -0,0 +1,218 @@
+//! Download counts as dense time series, for the `from`, `to` and
+//! `resolution` query parameters of the download count endpoints.
+
+use crate::controllers::prelude::RequestUtils;
+use crate::controllers::util::RequestPartsExt;
+use crate::util::errors::{bad_request, AppResult};
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+
+/// Longer ranges have to be requested in multiple parts, or with a coarser
+/// resolution.
+const MAX_BUCKETS: i64 = 366;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Resolution {
+    Day,
+    /// Weeks start on Monday, like in PostgreSQL's `date_trunc('week', ...)`
+    Week,
+}
+
+impl Resolution {
+    fn as_str(self) -> &'static str {
+        match self {
+            Resolution::Day => "day",
+            Resolution::Week => "week",
+        }
+    }
+
+    fn bucket_days(self) -> i64 {
+        match self {
+            Resolution::Day => 1,
+            Resolution::Week => 7,
+        }
+    }
+
+    /// Returns the first day of the bucket that contains the given date.
+    fn bucket_start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Resolution::Day => date,
+            Resolution::Week => date - Duration::days(date.weekday().num_days_from_monday().into()),
+        }
+    }
+}
+
+/// The buckets of a download count time series.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DownloadSeries {
+    /// The first day of the first bucket
+    pub(crate) from: NaiveDate,
+    /// The last day that is included
+    pub(crate) to: NaiveDate,
+    pub(crate) resolution: Resolution,
+}
+
+impl DownloadSeries {
+    /// Parses the `from`, `to` and `resolution` query parameters.
+    ///
+    /// Returns `None` if none of them is present, in which case the endpoints
+    /// fall back to their previous response format.
+    pub(crate) fn from_query<T: RequestPartsExt>(req: &T) -> AppResult<Option<Self>> {
+        let query = req.query();
+
+        let from = query.get("from");
+        let to = query.get("to");
+        let resolution = query.get("resolution");
+        if from.is_none() && to.is_none() && resolution.is_none() {
+            return Ok(None);
+        }
+
+        let resolution = match resolution.map(String::as_str) {
+            None | Some("day") => Resolution::Day,
+            Some("week") => Resolution::Week,
+            Some(_) => return Err(bad_request("`resolution` must be either `day` or `week`")),
+        };
+
+        let parse_date = |name: &str, value: &str| {
+            NaiveDate::parse_from_str(value, "%F")
+                .map_err(|_| bad_request(&format!("`{name}` must be a date like `2023-08-31`")))
+        };
+
+        let to = match to {
+            Some(to) => parse_date("to", to)?,
+            None => Utc::now().date_naive(),
+        };
+
+        let from = match from {
+            Some(from) => parse_date("from", from)?,
+            None => match resolution {
+                Resolution::Day => to - Duration::days(89),
+                Resolution::Week => to - Duration::weeks(51),
+            },
+        };
+
+        Self::new(from, to, resolution).map(Some)
+    }
+
+    pub(crate) fn new(from: NaiveDate, to: NaiveDate, resolution: Resolution) -> AppResult<Self> {
+        if from > to {
+            return Err(bad_request("`from` must not be after `to`"));
+        }
+
+        let series = Self {
+            from: resolution.bucket_start(from),
+            to,
+            resolution,
+        };
+
+        if series.len() > MAX_BUCKETS as usize {
+            let message = format!(
+                "The requested range is longer than {MAX_BUCKETS} {}s",
+                resolution.as_str()
+            );
+            return Err(bad_request(&message));
+        }
+
+        Ok(series)
+    }
+
+    /// The number of buckets in the series.
+    pub(crate) fn len(&self) -> usize {
+        let days = (self.to - self.from).num_days();
+        (days / self.resolution.bucket_days() + 1) as usize
+    }
+
+    /// The first day of every bucket.
+    pub(crate) fn dates(&self) -> Vec<String> {
+        let step = Duration::days(self.resolution.bucket_days());
+        (0..self.len() as i32)
+            .map(|i| (self.from + step * i).format("%F").to_string())
+            .collect()
+    }
+
+    /// Sums up the daily download counts into the buckets of the series.
+    /// Days outside of the range are ignored.
+    pub(crate) fn collect(
+        &self,
+        downloads: impl IntoIterator<Item = (NaiveDate, i32)>,
+    ) -> Vec<i64> {
+        let mut series = vec![0; self.len()];
+        for (date, count) in downloads {
+            if date < self.from || date > self.to {
+                continue;
+            }
+
+            let index = (date - self.from).num_days() / self.resolution.bucket_days();
+            series[index as usize] += i64::from(count);
+        }
+        series
+    }
+
+    /// The fields that describe the series in the JSON responses.
+    pub(crate) fn meta(&self) -> serde_json::Value {
+        json!({
+            "resolution": self.resolution.as_str(),
+            "from": self.from.format("%F").to_string(),
+            "to": self.to.format("%F").to_string(),
+            "dates": self.dates(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%F").unwrap()
+    }
+
+    #[test]
+    fn daily_series() {
+        let series =
+            DownloadSeries::new(date("2023-08-30"), date("2023-09-01"), Resolution::Day).unwrap();
+
+        assert_eq!(series.len(), 3);
+        assert_eq!(
+            series.dates(),
+            vec!["2023-08-30", "2023-08-31", "2023-09-01"]
+        );
+
+        let downloads = vec![
+            (date("2023-08-29"), 100),
+            (date("2023-08-30"), 1),
+            (date("2023-09-01"), 2),
+        ];
+        assert_eq!(series.collect(downloads), vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn weekly_series() {
+        // 2023-08-30 is a Wednesday
+        let series =
+            DownloadSeries::new(date("2023-08-30"), date("2023-09-12"), Resolution::Week).unwrap();
+
+        assert_eq!(series.from, date("2023-08-28"));
+        assert_eq!(
+            series.dates(),
+            vec!["2023-08-28", "2023-09-04", "2023-09-11"]
+        );
+
+        let downloads = vec![
+            (date("2023-08-28"), 1),
+            (date("2023-09-03"), 2),
+            (date("2023-09-04"), 3),
+            (date("2023-09-13"), 100),
+        ];
+        assert_eq!(series.collect(downloads), vec![3, 3, 0]);
+    }
+
+    #[test]
+    fn invalid_ranges() {
+        let from = date("2023-09-01");
+        assert!(DownloadSeries::new(from, date("2023-08-31"), Resolution::Day).is_err());
+
+        let to = from + Duration::days(MAX_BUCKETS);
+        assert!(DownloadSeries::new(from, to, Resolution::Day).is_err());
+        assert!(DownloadSeries::new(from, to, Resolution::Week).is_ok());
+    }
+}
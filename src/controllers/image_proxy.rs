@@ -0,0 +1,92 @@
+//! Serves the external images of rendered READMEs, see [`crate::image_proxy`].
+
+use crate::controllers::frontend_prelude::*;
+
+use crate::util::errors::{forbidden, internal, not_found};
+use crate::util::network::get_public_url;
+use hyper::body::Bytes;
+use std::io::Read;
+
+/// Images larger than this are not proxied.
+const MAX_IMAGE_SIZE: u64 = 5 * 1024 * 1024;
+
+const MAX_REDIRECTS: usize = 3;
+
+const CACHE_CONTROL_IMAGE: &str = "public,max-age=86400";
+
+/// Handles the `GET /api/v1/image_proxy/:digest/:url` route.
+///
+/// The `url` is the hex encoded URL of the original image, which is only
+/// fetched if the `digest` matches, and only from public hosts. Responses
+/// that are not images are rejected, so that the proxy can't be used to serve
+/// arbitrary content.
+pub async fn show(
+    app: AppState,
+    Path((digest, encoded_url)): Path<(String, String)>,
+) -> AppResult<Response> {
+    let proxy = app
+        .config
+        .readme_sanitization
+        .image_proxy(&app.config.domain_name)
+        .ok_or_else(not_found)?;
+
+    let url = proxy
+        .original_url(&digest, &encoded_url)
+        .ok_or_else(forbidden)?;
+
+    let (content_type, bytes) = conduit_compat(move || fetch_image(&url)).await?;
+
+    let headers = [
+        (header::CONTENT_TYPE, content_type),
+        (
+            header::CACHE_CONTROL,
+            header::HeaderValue::from_static(CACHE_CONTROL_IMAGE),
+        ),
+        (
+            header::X_CONTENT_TYPE_OPTIONS,
+            header::HeaderValue::from_static("nosniff"),
+        ),
+        (
+            header::CONTENT_SECURITY_POLICY,
+            header::HeaderValue::from_static("default-src 'none'; style-src 'unsafe-inline'"),
+        ),
+    ];
+
+    Ok((headers, bytes).into_response())
+}
+
+fn fetch_image(url: &str) -> AppResult<(header::HeaderValue, Bytes)> {
+    // Internal hosts are rejected here as well as on every redirect
+    let response = get_public_url(url, MAX_REDIRECTS).map_err(|error| {
+        debug!(%url, ?error, "Failed to fetch proxied image");
+        forbidden()
+    })?;
+
+    if !response.status().is_success() {
+        return Err(not_found());
+    }
+
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .filter(|value| value.as_bytes().starts_with(b"image/"))
+        .cloned()
+        .ok_or_else(not_found)?;
+
+    if response.content_length().unwrap_or(0) > MAX_IMAGE_SIZE {
+        return Err(not_found());
+    }
+
+    // The content length is optional, so the download is limited as well
+    let mut bytes = Vec::new();
+    response
+        .take(MAX_IMAGE_SIZE + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| internal(format!("failed to fetch image: {e}")))?;
+
+    if bytes.len() as u64 > MAX_IMAGE_SIZE {
+        return Err(not_found());
+    }
+
+    Ok((content_type, Bytes::from(bytes)))
+}
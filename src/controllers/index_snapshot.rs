@@ -0,0 +1,87 @@
+//! Endpoints for bootstrapping mirrors of the sparse index
+//!
+//! New mirrors download the latest snapshot of the index and then follow the change feed from
+//! the sequence number of the snapshot, instead of crawling the index file of every crate.
+
+use crate::controllers::frontend_prelude::*;
+
+use crate::models::{IndexChange, IndexSnapshot};
+use crate::util::errors::{internal, not_found, IndexChangesExpired};
+use crate::views::{EncodableIndexChange, EncodableIndexSnapshot};
+
+/// The maximum number of changes that are returned per request.
+const MAX_CHANGES: i64 = 1000;
+
+/// Handles the `GET /index/snapshot` route.
+pub async fn latest(app: AppState) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_read()?;
+
+        let snapshot = IndexSnapshot::latest(conn)?.ok_or_else(not_found)?;
+
+        let snapshot = EncodableIndexSnapshot::from(snapshot);
+        Ok(Json(json!({ "snapshot": snapshot })))
+    })
+    .await
+}
+
+/// Handles the `GET /index/snapshots/:sequence/download` route.
+pub async fn download(app: AppState, Path(sequence): Path<i64>) -> AppResult<Response> {
+    let bytes = app
+        .storage
+        .download_index_snapshot(sequence)
+        .await
+        .map_err(|e| match e {
+            object_store::Error::NotFound { .. } => not_found(),
+            e => internal(format!("failed to download index snapshot: {e}")),
+        })?;
+
+    let content_disposition = format!("attachment; filename=\"index-{sequence}.tar.gz\"");
+    let headers = [
+        (header::CONTENT_TYPE, "application/gzip".to_string()),
+        (header::CONTENT_DISPOSITION, content_disposition),
+    ];
+
+    Ok((headers, bytes).into_response())
+}
+
+/// Handles the `GET /index/changes` route.
+///
+/// Returns the crates whose index files changed after the `since` sequence
+/// number, oldest first. A crate might appear multiple times. Mirrors should
+/// continue with the `next_since` sequence number until no more changes are
+/// returned.
+///
+/// If the changes after `since` were already pruned, the mirror has to start
+/// over from the latest snapshot, which is signalled with `410 Gone`.
+pub async fn changes(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let since = match req.query().get("since") {
+            Some(since) => since
+                .parse::<i64>()
+                .map_err(|_| bad_request("invalid `since` parameter"))?,
+            None => 0,
+        };
+
+        let conn = &mut *app.db_read()?;
+
+        let oldest = IndexSnapshot::oldest(conn)?;
+        if oldest.map_or(false, |snapshot| since < snapshot.sequence) {
+            return Err(Box::new(IndexChangesExpired { since }));
+        }
+
+        let changes = IndexChange::since(conn, since, MAX_CHANGES)?;
+        let next_since = changes.last().map_or(since, |change| change.id);
+
+        let changes = changes
+            .into_iter()
+            .map(EncodableIndexChange::from)
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({
+            "changes": changes,
+            "meta": { "next_since": next_since },
+        })))
+    })
+    .await
+}
@@ -53,3 +53,51 @@ pub async fn show(Path(name): Path<String>, state: AppState) -> AppResult<Json<V
     })
     .await
 }
+
+/// The number of days of data returned by the `GET /keywords/:keyword_id/trends` route.
+const TRENDS_DAYS: i64 = 90;
+
+/// Handles the `GET /keywords/:keyword_id/trends` route.
+///
+/// Returns the daily number of crates with the keyword, the number of crates added since the
+/// previous day and the downloads of these crates over the last 90 days.
+pub async fn trends(Path(name): Path<String>, state: AppState) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        use crate::schema::keyword_stats;
+        use chrono::{NaiveDate, Utc};
+
+        let conn = &mut *state.db_read()?;
+
+        let kw = Keyword::find_by_keyword(conn, &name)?;
+
+        let since = Utc::now().date_naive() - chrono::Duration::days(TRENDS_DAYS);
+        let stats: Vec<(NaiveDate, i32, i64)> = keyword_stats::table
+            .filter(keyword_stats::keyword_id.eq(kw.id))
+            .filter(keyword_stats::date.gt(since))
+            .select((
+                keyword_stats::date,
+                keyword_stats::crates_cnt,
+                keyword_stats::downloads,
+            ))
+            .order(keyword_stats::date.asc())
+            .load(conn)?;
+
+        let mut previous_crates_cnt = None;
+        let trends = stats
+            .into_iter()
+            .map(|(date, crates_cnt, downloads)| {
+                let crates_added = previous_crates_cnt.map(|previous| crates_cnt - previous);
+                previous_crates_cnt = Some(crates_cnt);
+                json!({
+                    "date": date,
+                    "crates_cnt": crates_cnt,
+                    "crates_added": crates_added,
+                    "downloads": downloads,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({ "trends": trends })))
+    })
+    .await
+}
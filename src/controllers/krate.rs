@@ -1,6 +1,15 @@
+pub mod badges;
+pub mod batch;
+pub mod compare;
+pub mod dependents;
 pub mod downloads;
 pub mod follow;
+pub mod health;
+pub mod maintenance;
 pub mod metadata;
 pub mod owners;
 pub mod publish;
+pub mod render_readme;
+pub mod resolve;
 pub mod search;
+pub mod subscription;
@@ -0,0 +1,167 @@
+//! Endpoints serving SVG badges for crates
+//!
+//! The badges are rendered in the "flat" style of shields.io, so they can be
+//! used in READMEs without depending on a third-party badge proxy.
+
+use crate::controllers::frontend_prelude::*;
+
+use crate::models::{Crate, CrateVersions};
+use crate::schema::versions;
+use crate::util::errors::not_found;
+
+/// Badges are cached for an hour by browsers and CDNs.
+const CACHE_CONTROL_BADGE: &str = "public,max-age=3600";
+
+const COLOR_BLUE: &str = "#007ec6";
+const COLOR_GREEN: &str = "#4c1";
+const COLOR_GREY: &str = "#9f9f9f";
+const COLOR_ORANGE: &str = "#fe7d37";
+const COLOR_RED: &str = "#e05d44";
+
+/// Handles the `GET /crates/:crate_id/badges/:badge` route.
+///
+/// Supported badges are `version.svg`, `downloads.svg`, `msrv.svg` and `license.svg`.
+pub async fn badge(
+    state: AppState,
+    Path((crate_name, badge)): Path<(String, String)>,
+) -> AppResult<Response> {
+    conduit_compat(move || {
+        let conn = &mut *state.db_read()?;
+
+        let Some(krate) = Crate::by_name(&crate_name).first::<Crate>(conn).optional()? else {
+            let svg = render("crates.io", "not found", COLOR_GREY);
+            return Ok(svg_response(StatusCode::NOT_FOUND, svg));
+        };
+
+        let svg = match badge.as_str() {
+            "downloads.svg" => render(
+                "downloads",
+                &humanize_count(krate.downloads as i64),
+                COLOR_GREEN,
+            ),
+            "version.svg" | "msrv.svg" | "license.svg" => {
+                let latest = latest_version(&krate, conn)?;
+                match (badge.as_str(), latest) {
+                    (_, None) => render("crates.io", "yanked", COLOR_RED),
+                    ("version.svg", Some(version)) => {
+                        let color = match version.num.pre.is_empty() {
+                            true => COLOR_BLUE,
+                            false => COLOR_ORANGE,
+                        };
+                        render("crates.io", &format!("v{}", version.num), color)
+                    }
+                    ("msrv.svg", Some(version)) => match version.rust_version {
+                        Some(rust_version) => render("msrv", &rust_version, COLOR_BLUE),
+                        None => render("msrv", "unknown", COLOR_GREY),
+                    },
+                    (_, Some(version)) => match version.license {
+                        Some(license) => render("license", &license, COLOR_BLUE),
+                        None => render("license", "unknown", COLOR_GREY),
+                    },
+                }
+            }
+            _ => return Err(not_found()),
+        };
+
+        Ok(svg_response(StatusCode::OK, svg))
+    })
+    .await
+}
+
+struct LatestVersion {
+    num: semver::Version,
+    license: Option<String>,
+    rust_version: Option<String>,
+}
+
+/// Returns the highest version of the crate that has not been yanked,
+/// preferring stable versions over pre-releases.
+fn latest_version(krate: &Crate, conn: &mut PgConnection) -> QueryResult<Option<LatestVersion>> {
+    let rows: Vec<(String, Option<String>, Option<String>)> = krate
+        .all_versions()
+        .filter(versions::yanked.eq(false))
+        .select((versions::num, versions::license, versions::rust_version))
+        .load(conn)?;
+
+    let latest = rows
+        .into_iter()
+        .filter_map(|(num, license, rust_version)| {
+            let num = semver::Version::parse(&num).ok()?;
+            Some(LatestVersion {
+                num,
+                license,
+                rust_version,
+            })
+        })
+        .max_by(|a, b| {
+            let a_key = (a.num.pre.is_empty(), &a.num);
+            let b_key = (b.num.pre.is_empty(), &b.num);
+            a_key.cmp(&b_key)
+        });
+
+    Ok(latest)
+}
+
+fn svg_response(status: StatusCode, svg: String) -> Response {
+    let headers = [
+        (header::CONTENT_TYPE, "image/svg+xml;charset=utf-8"),
+        (header::CACHE_CONTROL, CACHE_CONTROL_BADGE),
+    ];
+    (status, headers, svg).into_response()
+}
+
+/// Formats large numbers like shields.io does, e.g. `12.3k` or `4.5M`.
+fn humanize_count(count: i64) -> String {
+    const UNITS: [(f64, &str); 3] = [(1e9, "G"), (1e6, "M"), (1e3, "k")];
+
+    let value = count as f64;
+    for (factor, unit) in UNITS {
+        if value >= factor {
+            let scaled = value / factor;
+            return match scaled < 10.0 {
+                true => format!("{:.1}{unit}", (scaled * 10.0).floor() / 10.0),
+                false => format!("{}{unit}", scaled.floor()),
+            };
+        }
+    }
+
+    count.to_string()
+}
+
+/// Approximates the rendered width of the text in 11px Verdana.
+fn text_width(text: &str) -> u32 {
+    text.chars()
+        .map(|c| match c {
+            'i' | 'l' | 'j' | '.' | ',' | ':' | '\'' | '|' | ' ' => 4,
+            'f' | 't' | 'r' | '(' | ')' | '-' => 5,
+            'm' | 'w' | 'M' | 'W' => 10,
+            c if c.is_ascii_uppercase() => 8,
+            _ => 7,
+        })
+        .sum()
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Renders a badge in the "flat" style of shields.io.
+fn render(label: &str, message: &str, color: &str) -> String {
+    const PADDING: u32 = 10;
+
+    let label_width = text_width(label) + PADDING;
+    let message_width = text_width(message) + PADDING;
+    let width = label_width + message_width;
+    let label_x = label_width / 2;
+    let message_x = label_width + message_width / 2;
+    let title = escape_xml(&format!("{label}: {message}"));
+    let label = escape_xml(label);
+    let message = escape_xml(message);
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{title}"><title>{title}</title><linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient><clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath><g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="#555"/><rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/><rect width="{width}" height="20" fill="url(#s)"/></g><g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11"><text x="{label_x}" y="15" fill="#010101" fill-opacity=".3">{label}</text><text x="{label_x}" y="14">{label}</text><text x="{message_x}" y="15" fill="#010101" fill-opacity=".3">{message}</text><text x="{message_x}" y="14">{message}</text></g></svg>"##
+    )
+}
@@ -0,0 +1,120 @@
+//! Endpoint for fetching the metadata of multiple crates at once
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDateTime;
+
+use crate::controllers::frontend_prelude::*;
+use crate::models::TopVersions;
+use crate::schema::{crates, recent_crate_downloads, versions};
+
+/// The maximum number of crate names that can be requested at once.
+pub const MAX_BATCH_SIZE: usize = 100;
+
+#[derive(Deserialize)]
+struct BatchRequest {
+    names: Vec<String>,
+}
+
+#[derive(Queryable)]
+struct BatchRow {
+    name: String,
+    description: Option<String>,
+    downloads: i32,
+    recent_downloads: Option<i64>,
+    created_at: NaiveDateTime,
+    num: String,
+    yanked: bool,
+}
+
+#[derive(Default)]
+struct BatchEntry {
+    description: Option<String>,
+    downloads: i32,
+    recent_downloads: Option<i64>,
+    versions: Vec<(NaiveDateTime, String, bool)>,
+}
+
+/// Handles the `POST /crates_batch` route.
+///
+/// Returns the metadata of up to [`MAX_BATCH_SIZE`] crates. The version fields only consider
+/// versions that have not been yanked, unless all versions of a crate have been yanked, in which
+/// case the crate is marked as `yanked` and all of its versions are considered. Names that do
+/// not match any crate are returned in the `missing` list.
+pub async fn batch(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let request: BatchRequest = serde_json::from_slice(req.body())
+            .map_err(|e| bad_request(&format!("invalid batch request: {e}")))?;
+
+        let mut names = request.names;
+        names.sort();
+        names.dedup();
+
+        if names.len() > MAX_BATCH_SIZE {
+            let detail = format!("too many crates requested, the maximum is {MAX_BATCH_SIZE}");
+            return Err(bad_request(&detail));
+        }
+
+        let conn = &mut *app.db_read()?;
+
+        let rows: Vec<BatchRow> = crates::table
+            .inner_join(versions::table)
+            .left_join(recent_crate_downloads::table)
+            .filter(crates::name.eq_any(&names))
+            .filter(versions::staged_until.is_null())
+            .select((
+                crates::name,
+                crates::description,
+                crates::downloads,
+                recent_crate_downloads::downloads.nullable(),
+                versions::created_at,
+                versions::num,
+                versions::yanked,
+            ))
+            .load(conn)?;
+
+        let mut entries: BTreeMap<String, BatchEntry> = BTreeMap::new();
+        for row in rows {
+            let entry = entries.entry(row.name).or_insert_with(|| BatchEntry {
+                description: row.description,
+                downloads: row.downloads,
+                recent_downloads: row.recent_downloads,
+                ..Default::default()
+            });
+            entry.versions.push((row.created_at, row.num, row.yanked));
+        }
+
+        let missing = names
+            .iter()
+            .filter(|name| !entries.contains_key(*name))
+            .collect::<Vec<_>>();
+
+        let crates = entries
+            .into_iter()
+            .map(|(name, entry)| {
+                let yanked = entry.versions.iter().all(|(_, _, yanked)| *yanked);
+                let top_versions = TopVersions::from_date_version_pairs(
+                    entry
+                        .versions
+                        .into_iter()
+                        .filter(|(_, _, version_yanked)| yanked || !version_yanked)
+                        .map(|(created_at, num, _)| (created_at, num)),
+                );
+
+                json!({
+                    "name": name,
+                    "description": entry.description,
+                    "downloads": entry.downloads,
+                    "recent_downloads": entry.recent_downloads,
+                    "max_version": top_versions.highest.map(|v| v.to_string()),
+                    "max_stable_version": top_versions.highest_stable.map(|v| v.to_string()),
+                    "newest_version": top_versions.newest.map(|v| v.to_string()),
+                    "yanked": yanked,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({ "crates": crates, "missing": missing })))
+    })
+    .await
+}
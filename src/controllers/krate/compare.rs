@@ -0,0 +1,222 @@
+//! Endpoint for comparing the metadata of multiple crates side by side
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+
+use crate::controllers::frontend_prelude::*;
+use crate::controllers::helpers::download_series::Resolution;
+use crate::controllers::helpers::DownloadSeries;
+use crate::models::{DependencyKind, TopVersions};
+use crate::schema::{crates, dependencies, recent_crate_downloads, version_downloads, versions};
+use crate::util::rfc3339;
+
+/// The maximum number of crates that can be compared at once.
+pub const MAX_COMPARED_CRATES: usize = 10;
+
+/// The number of weeks of the downloads trend.
+const TREND_WEEKS: i64 = 12;
+
+#[derive(Queryable)]
+struct CrateRow {
+    id: i32,
+    name: String,
+    downloads: i32,
+    recent_downloads: Option<i64>,
+}
+
+#[derive(Queryable)]
+struct VersionRow {
+    id: i32,
+    crate_id: i32,
+    num: String,
+    yanked: bool,
+    created_at: NaiveDateTime,
+    license: Option<String>,
+    rust_version: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ComparedCrate {
+    name: String,
+    latest_version: Option<String>,
+    /// The minimum supported Rust version of the latest version
+    rust_version: Option<String>,
+    license: Option<String>,
+    /// The number of normal dependencies of the latest version
+    dependencies: Option<i64>,
+    #[serde(with = "rfc3339::option")]
+    last_release_at: Option<NaiveDateTime>,
+    downloads: i32,
+    recent_downloads: Option<i64>,
+    /// The weekly download counts of the last weeks, see `meta.downloads_trend`
+    downloads_trend: Vec<i64>,
+}
+
+/// Handles the `GET /crate_comparisons` route.
+///
+/// Returns the metadata of up to [`MAX_COMPARED_CRATES`] crates from the
+/// comma separated `names` query parameter, in the order of the parameter.
+/// The version fields refer to the highest stable version that has not been
+/// yanked, like the `max_stable_version` of the batch endpoint. Names that do
+/// not match any crate are returned in the `missing` list.
+pub async fn compare(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let mut names: Vec<String> = Vec::new();
+        let query = req.query();
+        let requested = query.get("names").map(String::as_str).unwrap_or_default();
+        for name in requested.split(',').map(str::trim) {
+            if !name.is_empty() && !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+
+        if names.is_empty() {
+            return Err(bad_request("missing `names` query parameter"));
+        }
+        if names.len() > MAX_COMPARED_CRATES {
+            let detail = format!("too many crates requested, the maximum is {MAX_COMPARED_CRATES}");
+            return Err(bad_request(&detail));
+        }
+
+        let today = Utc::now().date_naive();
+        let from = today - Duration::weeks(TREND_WEEKS - 1);
+        let series = DownloadSeries::new(from, today, Resolution::Week)?;
+
+        let conn = &mut *app.db_read()?;
+
+        let crate_rows: Vec<CrateRow> = crates::table
+            .left_join(recent_crate_downloads::table)
+            .filter(crates::name.eq_any(&names))
+            .select((
+                crates::id,
+                crates::name,
+                crates::downloads,
+                recent_crate_downloads::downloads.nullable(),
+            ))
+            .load(conn)?;
+
+        let crate_ids = crate_rows.iter().map(|row| row.id).collect::<Vec<_>>();
+
+        let version_rows: Vec<VersionRow> = versions::table
+            .filter(versions::crate_id.eq_any(&crate_ids))
+            .filter(versions::staged_until.is_null())
+            .select((
+                versions::id,
+                versions::crate_id,
+                versions::num,
+                versions::yanked,
+                versions::created_at,
+                versions::license,
+                versions::rust_version,
+            ))
+            .load(conn)?;
+
+        let mut versions_by_crate: HashMap<i32, Vec<VersionRow>> = HashMap::new();
+        for row in version_rows {
+            versions_by_crate.entry(row.crate_id).or_default().push(row);
+        }
+
+        // The latest version and the last release date of every crate
+        let mut latest: HashMap<i32, (VersionRow, NaiveDateTime)> = HashMap::new();
+        for (crate_id, versions) in versions_by_crate {
+            let all_yanked = versions.iter().all(|version| version.yanked);
+            let candidates = versions
+                .into_iter()
+                .filter(|version| all_yanked || !version.yanked)
+                .collect::<Vec<_>>();
+
+            let top_versions = TopVersions::from_date_version_pairs(
+                candidates
+                    .iter()
+                    .map(|version| (version.created_at, version.num.clone())),
+            );
+            let last_release = candidates.iter().map(|version| version.created_at).max();
+
+            let Some(num) = top_versions.highest_stable.or(top_versions.highest) else {
+                continue;
+            };
+            let version = candidates
+                .into_iter()
+                .find(|version| semver::Version::parse(&version.num).ok().as_ref() == Some(&num));
+            if let (Some(version), Some(last_release)) = (version, last_release) {
+                latest.insert(crate_id, (version, last_release));
+            }
+        }
+
+        let latest_ids = latest
+            .values()
+            .map(|(version, _)| version.id)
+            .collect::<Vec<_>>();
+
+        let dependency_counts: HashMap<i32, i64> = dependencies::table
+            .filter(dependencies::version_id.eq_any(&latest_ids))
+            .filter(dependencies::kind.eq(DependencyKind::Normal as i32))
+            .group_by(dependencies::version_id)
+            .select((dependencies::version_id, diesel::dsl::count_star()))
+            .load::<(i32, i64)>(conn)?
+            .into_iter()
+            .collect();
+
+        let downloads: Vec<(i32, NaiveDate, i32)> = version_downloads::table
+            .inner_join(versions::table)
+            .filter(versions::crate_id.eq_any(&crate_ids))
+            .filter(version_downloads::date.between(series.from, series.to))
+            .select((
+                versions::crate_id,
+                version_downloads::date,
+                version_downloads::downloads,
+            ))
+            .load(conn)?;
+
+        let mut downloads_by_crate: HashMap<i32, Vec<(NaiveDate, i32)>> = HashMap::new();
+        for (crate_id, date, count) in downloads {
+            downloads_by_crate
+                .entry(crate_id)
+                .or_default()
+                .push((date, count));
+        }
+
+        let mut crate_rows = crate_rows
+            .into_iter()
+            .map(|row| (row.name.clone(), row))
+            .collect::<HashMap<_, _>>();
+
+        let mut crates = Vec::with_capacity(names.len());
+        let mut missing = Vec::new();
+        for name in names {
+            let Some(row) = crate_rows.remove(&name) else {
+                missing.push(name);
+                continue;
+            };
+
+            let (version, last_release_at) = latest.remove(&row.id).unzip();
+            let dependencies = version.as_ref().map(|version| {
+                dependency_counts
+                    .get(&version.id)
+                    .copied()
+                    .unwrap_or_default()
+            });
+            let trend = downloads_by_crate.remove(&row.id).unwrap_or_default();
+
+            crates.push(ComparedCrate {
+                name: row.name,
+                latest_version: version.as_ref().map(|version| version.num.clone()),
+                rust_version: version.as_ref().and_then(|v| v.rust_version.clone()),
+                license: version.and_then(|version| version.license),
+                dependencies,
+                last_release_at,
+                downloads: row.downloads,
+                recent_downloads: row.recent_downloads,
+                downloads_trend: series.collect(trend),
+            });
+        }
+
+        Ok(Json(json!({
+            "crates": crates,
+            "missing": missing,
+            "meta": { "downloads_trend": series.meta() },
+        })))
+    })
+    .await
+}
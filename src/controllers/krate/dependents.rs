@@ -0,0 +1,61 @@
+//! Endpoint for exposing the download share of the most popular dependents
+
+use crate::controllers::frontend_prelude::*;
+
+use crate::models::Crate;
+use crate::schema::{crate_dependent_stats, crates};
+use chrono::NaiveDateTime;
+
+/// Handles the `GET /crates/:crate_id/dependents_stats` route.
+///
+/// Returns the most downloaded crates that depend on the latest version of
+/// this crate, together with the estimated share of this crate's downloads
+/// that is caused by them. The stats are updated periodically by the
+/// `update_dependent_stats` job.
+pub async fn dependents_stats(
+    state: AppState,
+    Path(crate_name): Path<String>,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *state.db_read()?;
+        let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+
+        let stats: Vec<(String, i64, f64, NaiveDateTime)> = crate_dependent_stats::table
+            .inner_join(crates::table.on(crates::id.eq(crate_dependent_stats::dependent_id)))
+            .filter(crate_dependent_stats::crate_id.eq(krate.id))
+            .select((
+                crates::name,
+                crate_dependent_stats::downloads,
+                crate_dependent_stats::share,
+                crate_dependent_stats::updated_at,
+            ))
+            .order((crate_dependent_stats::downloads.desc(), crates::name.asc()))
+            .load(conn)?;
+
+        let updated_at = stats.iter().map(|(.., updated_at)| *updated_at).max();
+
+        // The shares of the dependents overlap if they depend on each other,
+        // so their sum is only an upper bound
+        let dependents_share = stats.iter().map(|(_, _, share, _)| share).sum::<f64>();
+
+        let top_dependents = stats
+            .into_iter()
+            .map(|(name, downloads, share, _)| {
+                json!({
+                    "crate": name,
+                    "downloads": downloads,
+                    "share": share,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({
+            "crate": krate.name,
+            "downloads": krate.downloads,
+            "dependents_share": dependents_share.min(1.0),
+            "top_dependents": top_dependents,
+            "updated_at": updated_at,
+        })))
+    })
+    .await
+}
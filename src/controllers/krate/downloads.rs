@@ -4,26 +4,44 @@
 //! download counts are located in `version::downloads`.
 
 use std::cmp;
+use std::collections::HashMap;
 
 use crate::controllers::frontend_prelude::*;
 
+use crate::controllers::helpers::DownloadSeries;
 use crate::models::{Crate, CrateVersions, Version, VersionDownload};
 use crate::schema::version_downloads;
 use crate::sql::to_char;
 use crate::views::EncodableVersionDownload;
+use chrono::NaiveDate;
 
 /// Handles the `GET /crates/:crate_id/downloads` route.
-pub async fn downloads(state: AppState, Path(crate_name): Path<String>) -> AppResult<Json<Value>> {
+///
+/// Without query parameters, the daily download counts of the last 90 days are
+/// returned for the latest five versions, and summed up for all other versions.
+/// With the `from`, `to` or `resolution` query parameters, the download counts
+/// of the requested range are returned as a dense series for every version.
+pub async fn downloads(
+    state: AppState,
+    Path(crate_name): Path<String>,
+    req: Parts,
+) -> AppResult<Json<Value>> {
     conduit_compat(move || {
         use diesel::dsl::*;
         use diesel::sql_types::BigInt;
 
+        let series = DownloadSeries::from_query(&req)?;
+
         let conn = &mut *state.db_read()?;
         let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
 
         let mut versions: Vec<Version> = krate.all_versions().load(conn)?;
         versions
             .sort_by_cached_key(|version| cmp::Reverse(semver::Version::parse(&version.num).ok()));
+
+        if let Some(series) = series {
+            return Ok(Json(versions_series(conn, &versions, &series)?));
+        }
         let (latest_five, rest) = versions.split_at(cmp::min(5, versions.len()));
 
         let downloads = VersionDownload::belonging_to(latest_five)
@@ -60,3 +78,44 @@ pub async fn downloads(state: AppState, Path(crate_name): Path<String>) -> AppRe
     })
     .await
 }
+
+/// Returns the download counts of all versions as dense series, in the same
+/// order as the versions.
+fn versions_series(
+    conn: &mut PgConnection,
+    versions: &[Version],
+    series: &DownloadSeries,
+) -> QueryResult<Value> {
+    let downloads: Vec<(i32, NaiveDate, i32)> = VersionDownload::belonging_to(versions)
+        .filter(version_downloads::date.between(series.from, series.to))
+        .select((
+            version_downloads::version_id,
+            version_downloads::date,
+            version_downloads::downloads,
+        ))
+        .load(conn)?;
+
+    let mut downloads_by_version: HashMap<i32, Vec<(NaiveDate, i32)>> = HashMap::new();
+    for (version_id, date, count) in downloads {
+        downloads_by_version
+            .entry(version_id)
+            .or_default()
+            .push((date, count));
+    }
+
+    let versions = versions
+        .iter()
+        .map(|version| {
+            let downloads = downloads_by_version.remove(&version.id).unwrap_or_default();
+            json!({
+                "id": version.id,
+                "num": version.num,
+                "downloads": series.collect(downloads),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut json = series.meta();
+    json["versions"] = versions.into();
+    Ok(json)
+}
@@ -0,0 +1,32 @@
+//! Endpoint for exposing the breakdown of the crate health score
+
+use crate::controllers::frontend_prelude::*;
+
+use crate::models::{health_checks, Crate, CrateReleaseStats};
+
+/// Handles the `GET /crates/:crate_id/health` route.
+///
+/// The breakdown is computed from the current release history of the crate,
+/// so the total `score` may differ from the `health_score` field of the crate
+/// until the `update_health_scores` job has run again.
+pub async fn health(state: AppState, Path(crate_name): Path<String>) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *state.db_read()?;
+        let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+
+        let now = chrono::Utc::now().naive_utc();
+        let checks = CrateReleaseStats::load(conn, Some(krate.id))?
+            .first()
+            .map(|stats| health_checks(stats, now))
+            .unwrap_or_default();
+
+        let score: i32 = checks.iter().map(|check| check.score).sum();
+
+        Ok(Json(json!({
+            "crate": krate.name,
+            "score": score,
+            "checks": checks,
+        })))
+    })
+    .await
+}
@@ -0,0 +1,52 @@
+//! Endpoints for the maintenance status of crates whose owners have all been
+//! inactive for a long time
+
+use crate::auth::AuthCheck;
+
+use crate::controllers::frontend_prelude::*;
+use crate::models::{Crate, CrateMaintenanceStatus, Rights};
+use crate::util::errors::forbidden;
+use crate::views::EncodableMaintenanceStatus;
+
+/// Handles the `GET /crates/:crate_id/maintenance` route.
+pub async fn show(state: AppState, Path(crate_name): Path<String>) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *state.db_read()?;
+        let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+        let status = CrateMaintenanceStatus::find(conn, krate.id)?;
+
+        Ok(Json(json!({
+            "maintenance": EncodableMaintenanceStatus::from(status),
+        })))
+    })
+    .await
+}
+
+/// Handles the `PUT /crates/:crate_id/maintenance` route.
+///
+/// Records that the crate is still maintained, which removes the stale flag
+/// and resets the inactivity timer of the `flag_stale_crates` job.
+pub async fn confirm(
+    state: AppState,
+    Path(crate_name): Path<String>,
+    req: Parts,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *state.db_write()?;
+        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let user = auth.user();
+
+        let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+        let owners = krate.owners(conn)?;
+        if user.rights(&state, &owners)? < Rights::Publish {
+            return Err(forbidden());
+        }
+
+        let status = CrateMaintenanceStatus::confirm(conn, krate.id, user.id)?;
+
+        Ok(Json(json!({
+            "maintenance": EncodableMaintenanceStatus::from(Some(status)),
+        })))
+    })
+    .await
+}
@@ -11,12 +11,14 @@ use crate::controllers::frontend_prelude::*;
 use crate::controllers::helpers::pagination::PaginationOptions;
 
 use crate::models::{
-    Category, Crate, CrateCategory, CrateKeyword, CrateVersions, Keyword, RecentCrateDownloads,
-    TopVersions, User, Version, VersionOwnerAction,
+    Category, Crate, CrateCategory, CrateKeyword, CrateMaintenanceStatus, CrateQuarantine,
+    CrateVersions, Keyword, PublisherVerification, RecentCrateDownloads, TopVersions, User,
+    Version, VersionOwnerAction,
 };
 use crate::schema::*;
 use crate::views::{
-    EncodableCategory, EncodableCrate, EncodableDependency, EncodableKeyword, EncodableVersion,
+    EncodableCategory, EncodableCrate, EncodableDependency, EncodableKeyword,
+    EncodableVerifiedPublisher, EncodableVersion,
 };
 
 /// Handles the `GET /summary` route.
@@ -209,7 +211,14 @@ pub async fn show(app: AppState, Path(name): Path<String>, req: Parts) -> AppRes
             None
         };
 
-        let encodable_crate = EncodableCrate::from(
+        let verifications = PublisherVerification::verified_for_crates(conn, &[krate.id])?;
+        let quarantine_status = CrateQuarantine::flagged_for_crates(conn, &[krate.id])?
+            .pop()
+            .map(|(_, status)| status);
+        let stale_since =
+            CrateMaintenanceStatus::find(conn, krate.id)?.and_then(|status| status.flagged_at);
+
+        let mut encodable_crate = EncodableCrate::from(
             krate.clone(),
             top_versions.as_ref(),
             ids,
@@ -219,6 +228,14 @@ pub async fn show(app: AppState, Path(name): Path<String>, req: Parts) -> AppRes
             false,
             recent_downloads,
         );
+        encodable_crate.verified_publisher = EncodableVerifiedPublisher::from_verifications(
+            verifications
+                .into_iter()
+                .map(|(_, verification)| verification),
+        );
+        encodable_crate.quarantine_status = quarantine_status;
+        encodable_crate.stale_since = stale_since;
+
         let encodable_versions = versions_publishers_and_audit_actions.map(|vpa| {
             vpa.into_iter()
                 .map(|(v, pb, aas)| EncodableVersion::from(v, &krate.name, pb, aas))
@@ -372,6 +389,7 @@ pub async fn reverse_dependencies(
 
         let versions_and_publishers: Vec<(Version, String, Option<User>)> = versions::table
             .filter(versions::id.eq_any(version_ids))
+            .filter(versions::staged_until.is_null())
             .inner_join(crates::table)
             .left_outer_join(users::table)
             .select((
@@ -6,6 +6,7 @@ use crate::models::token::EndpointScope;
 use crate::models::{Crate, Owner, Rights, Team, User};
 use crate::views::EncodableOwner;
 use axum::body::Bytes;
+use diesel::result::Error as DieselError;
 use http::Request;
 
 /// Handles the `GET /crates/:crate_id/owners` route.
@@ -156,3 +157,164 @@ fn modify_owners(
         Ok(Json(json!({ "ok": true, "msg": comma_sep_msg })))
     })
 }
+
+#[derive(Deserialize)]
+struct BulkOwnersRequest {
+    /// The complete set of desired owners, e.g. `["username", "github:org:team"]`
+    owners: Vec<String>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// An owner that could not be added to the crate.
+#[derive(Serialize)]
+struct BulkOwnerError {
+    owner: String,
+    detail: String,
+}
+
+/// Handles the `PUT /crates/:crate_id/owners/bulk` route.
+///
+/// Takes the complete set of desired owners of the crate, and adds and removes
+/// owners so that the crate ends up with exactly these owners. Users are
+/// invited as usual, while teams are added immediately. All changes are
+/// applied in a single transaction.
+///
+/// With `dry_run` the planned additions and removals are returned without
+/// changing anything, together with the owners that could not be added.
+/// Otherwise the first error aborts the whole update.
+pub async fn bulk_update_owners(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: BytesRequest,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || bulk_update(&app, &crate_name, &req)).await
+}
+
+fn bulk_update(app: &AppState, crate_name: &str, req: &Request<Bytes>) -> AppResult<Json<Value>> {
+    let request: BulkOwnersRequest = serde_json::from_slice(req.body())
+        .map_err(|e| cargo_err(&format!("invalid json request: {e}")))?;
+
+    let conn = &mut *app.db_write()?;
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::ChangeOwners)
+        .for_crate(crate_name)
+        .check(req, conn)?;
+
+    let user = auth.user();
+    let dry_run = request.dry_run;
+
+    let update = |conn: &mut PgConnection| -> AppResult<Json<Value>> {
+        let krate: Crate = Crate::by_name(crate_name).first(conn)?;
+        let owners = krate.owners(conn)?;
+
+        match user.rights(app, &owners)? {
+            Rights::Full => {}
+            Rights::Publish => {
+                return Err(cargo_err(
+                    "team members don't have permission to modify owners",
+                ));
+            }
+            Rights::None => {
+                return Err(cargo_err("only owners have permission to modify owners"));
+            }
+        }
+
+        let mut desired: Vec<&str> = Vec::with_capacity(request.owners.len());
+        for login in &request.owners {
+            if !desired
+                .iter()
+                .any(|other| other.eq_ignore_ascii_case(login))
+            {
+                desired.push(login);
+            }
+        }
+
+        let is_desired = |owner: &Owner| {
+            desired
+                .iter()
+                .any(|login| login.eq_ignore_ascii_case(owner.login()))
+        };
+
+        let removed = owners
+            .iter()
+            .filter(|owner| !is_desired(owner))
+            .map(|owner| owner.login().to_string())
+            .collect::<Vec<_>>();
+
+        if !owners
+            .iter()
+            .any(|owner| matches!(owner, Owner::User(_)) && is_desired(owner))
+        {
+            return Err(cargo_err(
+                "cannot remove all individual owners of a crate. \
+                     Team member don't have permission to modify owners, so \
+                     at least one individual owner is required.",
+            ));
+        }
+
+        let added = desired
+            .iter()
+            .filter(|login| {
+                !owners
+                    .iter()
+                    .any(|owner| owner.login().eq_ignore_ascii_case(login))
+            })
+            .map(|login| login.to_string())
+            .collect::<Vec<_>>();
+
+        let mut msgs = Vec::with_capacity(added.len());
+        let mut errors = Vec::new();
+        for login in &added {
+            // Dry runs only check that the owner can be added, so that no
+            // invitations are sent
+            let result = if dry_run {
+                Owner::find_or_create_by_login(app, conn, user, login).map(|_| None)
+            } else {
+                krate.owner_add(app, conn, user, login).map(Some)
+            };
+
+            match result {
+                Ok(msg) => msgs.extend(msg),
+                Err(error) if dry_run => errors.push(BulkOwnerError {
+                    owner: login.clone(),
+                    detail: error.to_string(),
+                }),
+                Err(error) => return Err(error),
+            }
+        }
+
+        for login in &removed {
+            krate.owner_remove(app, conn, user, login)?;
+        }
+
+        if !removed.is_empty() {
+            msgs.push("owners successfully removed".to_owned());
+        }
+
+        Ok(Json(json!({
+            "ok": true,
+            "dry_run": dry_run,
+            "added": added,
+            "removed": removed,
+            "errors": errors,
+            "msg": msgs.join(","),
+        })))
+    };
+
+    if !dry_run {
+        return conn.transaction(update);
+    }
+
+    let mut result = None;
+    conn.transaction(|conn| {
+        result = Some(update(conn));
+        Err(DieselError::RollbackTransaction)
+    })
+    .or_else(|error| match error {
+        DieselError::RollbackTransaction => Ok(()),
+        error => Err(error),
+    })?;
+
+    result.expect("the transaction closure is always called")
+}
@@ -1,30 +1,37 @@
 //! Functionality related to publishing a new crate or version of a crate.
 
-use crate::auth::AuthCheck;
+use crate::auth::{authenticate_unscoped, AuthCheck, Authentication};
 use crate::background_jobs::{Job, PRIORITY_RENDER_README};
 use axum::body::Bytes;
-use crates_io_tarball::{process_tarball, TarballError};
+use axum::extract::FromRequest;
+use chrono::{NaiveDateTime, Utc};
+use crates_io_tarball::{process_tarball, TarballError, TarballInfo};
 use hex::ToHex;
 use hyper::body::Buf;
+use hyper::Body;
 use sha2::{Digest, Sha256};
 use std::ops::Deref;
 use tokio::runtime::Handle;
+use tracing::Span;
 
 use crate::controllers::cargo_prelude::*;
 use crate::controllers::util::RequestPartsExt;
 use crate::models::{
-    insert_version_owner_action, Category, Crate, Keyword, NewCrate, NewVersion, Rights,
-    VersionAction,
+    insert_crate_notification, insert_version_owner_action, AuditAction, Category, Crate,
+    CrateMetadata, CrateQuarantine, IdempotentPublish, Keyword, NamespaceClaim, NewAuditLogEntry,
+    NewCrate, NewVersion, PublishDetails, PublishIdempotencyKey, Rights, User, Version,
+    VersionAction, VersionObject,
 };
 
 use crate::middleware::log_request::RequestLogExt;
 use crate::models::token::EndpointScope;
 use crate::schema::*;
-use crate::util::errors::{cargo_err, internal, AppResult};
-use crate::util::Maximums;
+use crate::upload_limits::UploadLimits;
+use crate::util::errors::{cargo_err, internal, is_cargo_err, unknown_categories, AppResult};
 use crate::views::{
     EncodableCrate, EncodableCrateDependency, EncodableCrateUpload, GoodCrate, PublishWarnings,
 };
+use diesel::result::Error as DieselError;
 
 pub const MISSING_RIGHTS_ERROR_MESSAGE: &str =
     "this crate exists but you don't seem to be an owner. \
@@ -37,50 +44,282 @@ pub const WILDCARD_ERROR_MESSAGE: &str = "wildcard (`*`) dependency constraints
      libraries-use--as-a-version-for-their-dependencies for more \
      information";
 
+/// The maximum size of a `PUT /crates/new` request body.
+pub const MAX_PUBLISH_CONTENT_LENGTH: usize = 128 * 1024 * 1024; // 128 MB
+
+/// The maximum number of crates that can be published together.
+pub const MAX_BATCH_PUBLISH_SIZE: usize = 20;
+
+/// The maximum size of a `PUT /crates/new/batch` request body. Each crate in the batch is still
+/// limited to `MAX_PUBLISH_CONTENT_LENGTH`, but the whole body is kept small, since it is
+/// buffered in memory. Batches of larger crates have to be split up by the client.
+pub const MAX_BATCH_PUBLISH_CONTENT_LENGTH: usize = 2 * MAX_PUBLISH_CONTENT_LENGTH;
+
+/// Binary files of at least this size are considered suspicious if they are
+/// added to a crate, see `Job::check_publish()`.
+pub const LARGE_BINARY_FILE_SIZE: u64 = 1024 * 1024;
+
+/// The header that contains the country code of the client, which is set by
+/// the CDN in front of the API.
+pub(crate) const COUNTRY_HEADER: &str = "cloudfront-viewer-country";
+
+/// The header that allows clients to safely retry a publish, see `publish()`.
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// The maximum length of the `Idempotency-Key` header.
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
 /// Handles the `PUT /crates/new` route.
 /// Used by `cargo publish` to publish a new crate or to publish a new version of an
 /// existing crate.
 ///
+/// If the `dry_run=true` query parameter is set, all checks are run inside of a database
+/// transaction that is rolled back afterwards, and nothing is uploaded. Instead of aborting on
+/// the first failed check, all errors are collected and returned together.
+///
+/// If the `staged=true` query parameter is set, the new version is staged. Staged versions can
+/// only be downloaded by the owners of the crate, and are added to the index once the configured
+/// soak period is over, or once they are promoted explicitly.
+///
+/// If an `Idempotency-Key` header is set, the request can safely be retried, e.g. after a
+/// network failure. Retries with the same key and request body get the response of the first
+/// successful attempt instead of a "crate version already exists" error. Keys are scoped to the
+/// user and expire after a day.
+///
 /// Currently blocks the HTTP thread, perhaps some function calls can spawn new
 /// threads and return completion or error through other methods  a `cargo publish
 /// --status` command, via crates.io's front end, or email.
-pub async fn publish(app: AppState, req: BytesRequest) -> AppResult<Json<GoodCrate>> {
-    let (req, bytes) = req.0.into_parts();
-    let (json_bytes, tarball_bytes) = split_body(bytes, &req)?;
-
-    let new_crate: EncodableCrateUpload = serde_json::from_slice(&json_bytes)
-        .map_err(|e| cargo_err(&format_args!("invalid upload request: {e}")))?;
+#[instrument(skip_all, fields(krate.name, krate.version, dry_run))]
+pub async fn publish(app: AppState, req: Request<Body>) -> AppResult<Response> {
+    let req = match read_authenticated_body(&app, req).await {
+        Ok(req) => req,
+        Err(response) => return Ok(response),
+    };
+
+    let (req, mut bytes) = req.0.into_parts();
+    let dry_run = query_flag(&req, "dry_run");
+    let idempotency_key = IdempotencyKey::from_request(&req, &bytes)?;
+    let upload = CrateUpload::from_body(&mut bytes, &req)?;
 
     let request_log = req.request_log();
-    request_log.add("crate_name", new_crate.name.to_string());
-    request_log.add("crate_version", new_crate.vers.to_string());
+    request_log.add("crate_name", upload.new_crate.name.to_string());
+    request_log.add("crate_version", upload.new_crate.vers.to_string());
+
+    let span = Span::current();
+    span.record(
+        "krate.name",
+        tracing::field::display(&*upload.new_crate.name),
+    );
+    span.record(
+        "krate.version",
+        tracing::field::display(&*upload.new_crate.vers),
+    );
+    span.record("dry_run", dry_run);
+
+    let mut validation = Validation::new(dry_run);
+    validation.check(validate_metadata(&upload.new_crate))?;
 
-    // Make sure required fields are provided
-    fn empty(s: Option<&String>) -> bool {
-        s.map_or(true, String::is_empty)
-    }
+    conduit_compat(move || {
+        let conn = &mut *app.primary_database.get()?;
 
-    // It can have up to three elements per below conditions.
-    let mut missing = Vec::with_capacity(3);
+        let uploads = vec![upload];
+        let mut published =
+            publish_uploads(&app, &req, conn, uploads, &mut validation, idempotency_key)?;
+        match published.pop() {
+            Some(good_crate) if validation.errors.is_empty() => {
+                Ok(Json(good_crate).into_response())
+            }
+            _ => Ok(validation.into_response()),
+        }
+    })
+    .await
+}
 
-    if empty(new_crate.description.as_ref()) {
-        missing.push("description");
+/// Handles the `PUT /crates/new/batch` route.
+///
+/// Publishes several crates at once, e.g. all members of a workspace. The request body consists
+/// of multiple `PUT /crates/new` request bodies concatenated together. The crates are published
+/// in the order in which they are sent, so dependencies between them have to be sent first.
+///
+/// Either all of the new versions are published or none of them. The index is only updated
+/// once all versions have been saved and uploaded successfully.
+///
+/// The `dry_run=true` and `staged=true` query parameters and the `Idempotency-Key` header are
+/// supported the same way as for `PUT /crates/new`.
+#[instrument(skip_all, fields(krate.names, dry_run))]
+pub async fn publish_batch(app: AppState, req: Request<Body>) -> AppResult<Response> {
+    let req = match read_authenticated_body(&app, req).await {
+        Ok(req) => req,
+        Err(response) => return Ok(response),
+    };
+
+    let (req, mut bytes) = req.0.into_parts();
+    let dry_run = query_flag(&req, "dry_run");
+    let idempotency_key = IdempotencyKey::from_request(&req, &bytes)?;
+
+    let mut uploads = Vec::new();
+    while bytes.has_remaining() {
+        if uploads.len() == MAX_BATCH_PUBLISH_SIZE {
+            return Err(cargo_err(&format_args!(
+                "too many crates in batch, the maximum is {MAX_BATCH_PUBLISH_SIZE}"
+            )));
+        }
+
+        let remaining = bytes.len();
+        let upload = CrateUpload::from_body(&mut bytes, &req)?;
+        if remaining - bytes.len() > MAX_PUBLISH_CONTENT_LENGTH {
+            return Err(cargo_err(&format_args!(
+                "crate `{}` is too large, each crate in a batch can be at most \
+                 {MAX_PUBLISH_CONTENT_LENGTH} bytes",
+                &*upload.new_crate.name
+            )));
+        }
+
+        uploads.push(upload);
     }
-    if empty(new_crate.license.as_ref()) && empty(new_crate.license_file.as_ref()) {
-        missing.push("license");
+
+    if uploads.is_empty() {
+        return Err(cargo_err("no crates found in batch"));
     }
-    if !missing.is_empty() {
-        let message = missing_metadata_error_message(&missing);
-        return Err(cargo_err(&message));
+
+    let names = uploads
+        .iter()
+        .map(|upload| format!("{}@{}", &*upload.new_crate.name, &*upload.new_crate.vers))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    req.request_log().add("crate_names", &names);
+
+    let span = Span::current();
+    span.record("krate.names", &names);
+    span.record("dry_run", dry_run);
+
+    let mut validation = Validation::new(dry_run);
+    for upload in &uploads {
+        validation.check(validate_metadata(&upload.new_crate))?;
     }
 
     conduit_compat(move || {
         let conn = &mut *app.primary_database.get()?;
 
+        let published =
+            publish_uploads(&app, &req, conn, uploads, &mut validation, idempotency_key)?;
+        if !validation.errors.is_empty() {
+            return Ok(validation.into_response());
+        }
+
+        Ok(Json(json!({ "crates": published })).into_response())
+    })
+    .await
+}
+
+/// Reads the request body, once the request has been authenticated.
+///
+/// Publish request bodies are large, so unauthenticated clients must not be able to make the
+/// server buffer them. The scopes of the token can only be checked once the crate names are
+/// known, see `publish_uploads()`.
+async fn read_authenticated_body(
+    app: &AppState,
+    req: Request<Body>,
+) -> Result<BytesRequest, Response> {
+    let (parts, body) = req.into_parts();
+
+    let app = app.clone();
+    let parts = conduit_compat(move || {
+        let conn = &mut *app.db_write()?;
+        authenticate_unscoped(&parts, conn)?;
+        Ok(parts)
+    })
+    .await
+    .map_err(IntoResponse::into_response)?;
+
+    BytesRequest::from_request(Request::from_parts(parts, body), &()).await
+}
+
+fn query_flag(req: &Parts, name: &str) -> bool {
+    req.query().get(name).map_or(false, |value| value == "true")
+}
+
+/// The key of a publish request that can safely be retried, see `publish()`.
+struct IdempotencyKey {
+    key: String,
+    /// The SHA-256 hash of the request body, to detect keys that are reused for different
+    /// requests
+    request_hash: String,
+}
+
+impl IdempotencyKey {
+    fn from_request(req: &Parts, body: &[u8]) -> AppResult<Option<Self>> {
+        let Some(value) = req.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+            return Ok(None);
+        };
+
+        let key = value
+            .to_str()
+            .ok()
+            .filter(|key| !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LENGTH)
+            .filter(|key| key.bytes().all(|b| b.is_ascii_graphic()))
+            .ok_or_else(|| {
+                cargo_err(&format_args!(
+                    "invalid `{IDEMPOTENCY_KEY_HEADER}` header, expected at most \
+                     {MAX_IDEMPOTENCY_KEY_LENGTH} visible ASCII characters"
+                ))
+            })?;
+
+        Ok(Some(Self {
+            key: key.to_string(),
+            request_hash: Sha256::digest(body).encode_hex(),
+        }))
+    }
+}
+
+/// A single crate, as uploaded by `cargo publish`.
+struct CrateUpload {
+    new_crate: EncodableCrateUpload,
+    tarball_bytes: Bytes,
+}
+
+impl CrateUpload {
+    /// Reads the next crate upload from the request body.
+    fn from_body<R: RequestPartsExt>(bytes: &mut Bytes, req: &R) -> AppResult<Self> {
+        let (json_bytes, tarball_bytes) = split_body(bytes, req)?;
+
+        let new_crate: EncodableCrateUpload = serde_json::from_slice(&json_bytes)
+            .map_err(|e| cargo_err(&format_args!("invalid upload request: {e}")))?;
+
+        Ok(Self {
+            new_crate,
+            tarball_bytes,
+        })
+    }
+}
+
+/// A crate version that has been saved to the database.
+struct PublishedVersion {
+    krate_name: String,
+    staged: bool,
+    good_crate: GoodCrate,
+}
+
+/// Publishes all uploaded crates inside of a single database transaction.
+///
+/// The tarballs are checked and uploaded before the transaction is opened, and the index sync is
+/// only enqueued after all versions have been saved successfully. During dry runs nothing is
+/// uploaded, and the transaction is rolled back instead.
+fn publish_uploads(
+    app: &AppState,
+    req: &Parts,
+    conn: &mut PgConnection,
+    uploads: Vec<CrateUpload>,
+    validation: &mut Validation,
+    idempotency_key: Option<IdempotencyKey>,
+) -> AppResult<Vec<GoodCrate>> {
+    let mut auth = None;
+    for upload in &uploads {
         // this query should only be used for the endpoint scope calculation
         // since a race condition there would only cause `publish-new` instead of
         // `publish-update` to be used.
-        let existing_crate = Crate::by_name(&new_crate.name)
+        let existing_crate = Crate::by_name(&upload.new_crate.name)
             .first::<Crate>(conn)
             .optional()?;
 
@@ -89,194 +328,821 @@ pub async fn publish(app: AppState, req: BytesRequest) -> AppResult<Json<GoodCra
             None => EndpointScope::PublishNew,
         };
 
-        let auth = AuthCheck::default()
-            .with_endpoint_scope(endpoint_scope)
-            .for_crate(&new_crate.name)
-            .check(&req, conn)?;
+        auth = Some(
+            AuthCheck::default()
+                .with_endpoint_scope(endpoint_scope)
+                .for_crate(&upload.new_crate.name)
+                .check(req, conn)?,
+        );
+    }
+
+    let Some(auth) = auth else {
+        return Ok(Vec::new());
+    };
+
+    // Dry runs do not persist anything, so there is nothing to deduplicate
+    let idempotency_key = idempotency_key.filter(|_| !validation.dry_run);
+    if let Some(key) = &idempotency_key {
+        match PublishIdempotencyKey::begin(conn, auth.user_id(), &key.key, &key.request_hash)? {
+            IdempotentPublish::Started => {}
+            IdempotentPublish::Completed(response) => {
+                info!(key = %key.key, "Replaying response of completed publish");
+                return serde_json::from_value(response)
+                    .map_err(|e| internal(format!("invalid saved publish response: {e}")));
+            }
+            IdempotentPublish::InProgress => {
+                return Err(cargo_err(
+                    "a publish with this idempotency key is still in progress, \
+                     please try again later",
+                ));
+            }
+            IdempotentPublish::Mismatch => {
+                return Err(cargo_err(
+                    "this idempotency key was already used for a different publish request",
+                ));
+            }
+        }
+    }
+
+    let result = publish_authenticated(
+        app,
+        req,
+        conn,
+        uploads,
+        validation,
+        &auth,
+        idempotency_key.as_ref(),
+    );
+
+    if result.is_err() {
+        if let Some(key) = &idempotency_key {
+            // Allow the client to retry right away instead of waiting for the key to go stale
+            let user_id = auth.user_id();
+            if let Err(error) = PublishIdempotencyKey::abandon(conn, user_id, &key.key) {
+                warn!(key = %key.key, ?error, "Failed to abandon idempotency key");
+            }
+        }
+    }
 
-        let api_token_id = auth.api_token_id();
-        let user = auth.user();
+    result
+}
 
-        let verified_email_address = user.verified_email(conn)?;
-        let verified_email_address = verified_email_address.ok_or_else(|| {
+/// Publishes the uploaded crates after the user has been authenticated.
+///
+/// If an idempotency key is given, the response is saved in the publish transaction, so that
+/// retries of the request can be answered with it.
+fn publish_authenticated(
+    app: &AppState,
+    req: &Parts,
+    conn: &mut PgConnection,
+    uploads: Vec<CrateUpload>,
+    validation: &mut Validation,
+    auth: &Authentication,
+    idempotency_key: Option<&IdempotencyKey>,
+) -> AppResult<Vec<GoodCrate>> {
+    let api_token_id = auth.api_token_id();
+    let user = auth.user();
+
+    let verified_email_address = user.verified_email(conn)?;
+    let verified_email_address = validation
+        .check(verified_email_address.ok_or_else(|| {
             cargo_err(&format!(
                 "A verified email address is required to publish crates to crates.io. \
              Visit https://{}/settings/profile to set and verify your email address.",
                 app.config.domain_name,
             ))
-        })?;
+        }))?
+        // Dry runs continue without an email address, since nothing will be persisted
+        .unwrap_or_default();
+
+    let staged_until = if query_flag(req, "staged") {
+        let soak_period = chrono::Duration::from_std(app.config.staged_release_soak_period)
+            .map_err(|_| internal("invalid staged release soak period"))?;
+        Some(Utc::now().naive_utc() + soak_period)
+    } else {
+        None
+    };
+
+    let country = req
+        .headers()
+        .get(COUNTRY_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    let mut checked_uploads = Vec::with_capacity(uploads.len());
+    for upload in uploads {
+        let tarball_info = check_crate_file(app, conn, &upload, user, validation)?;
+        checked_uploads.push((upload, tarball_info));
+    }
+
+    // The crate files are uploaded before the transaction is opened, so that the transaction is
+    // kept short. The uploaded files are deleted again if an upload fails or if the transaction
+    // can not be committed, so that no orphaned files are left behind.
+    let dry_run = validation.dry_run;
+    if !dry_run {
+        let mut uploaded_files = Vec::new();
+        for (upload, _) in &checked_uploads {
+            match upload_crate_file(app, upload, staged_until.is_some()) {
+                Ok(Some(file)) => uploaded_files.push(file),
+                Ok(None) => {}
+                Err(error) => {
+                    delete_uploaded_files(app, conn, uploaded_files);
+                    return Err(error);
+                }
+            }
+        }
 
         // Create a transaction on the database, if there are no errors,
         // commit the transactions to record a new or updated crate.
-        conn.transaction(|conn| {
-            let _ = &new_crate;
-            let name = new_crate.name;
-            let vers = &*new_crate.vers;
-            let links = new_crate.links;
-            let repo = new_crate.repository;
-            let features = new_crate
-                .features
-                .into_iter()
-                .map(|(k, v)| (k.0, v.into_iter().map(|v| v.0).collect()))
-                .collect();
-            let keywords = new_crate
-                .keywords
-                .iter()
-                .map(|s| s.as_str())
-                .collect::<Vec<_>>();
-            let categories = new_crate
-                .categories
-                .iter()
-                .map(|s| s.as_str())
-                .collect::<Vec<_>>();
-
-            // Persist the new crate, if it doesn't already exist
-            let persist = NewCrate {
-                name: &name,
-                description: new_crate.description.as_deref(),
-                homepage: new_crate.homepage.as_deref(),
-                documentation: new_crate.documentation.as_deref(),
-                readme: new_crate.readme.as_deref(),
-                repository: repo.as_deref(),
-                max_upload_size: None,
-            };
-
-            let license_file = new_crate.license_file.as_deref();
-            let krate =
-                persist.create_or_update(conn, user.id, Some(&app.config.publish_rate_limit))?;
-
-            let owners = krate.owners(conn)?;
-            if user.rights(&app, &owners)? < Rights::Publish {
-                return Err(cargo_err(MISSING_RIGHTS_ERROR_MESSAGE));
-            }
+        let _span = info_span!("publish.transaction").entered();
+        let result = conn.transaction(|conn| {
+            publish(
+                app,
+                conn,
+                checked_uploads,
+                user,
+                api_token_id,
+                country,
+                &verified_email_address,
+                staged_until,
+                validation,
+                auth,
+                idempotency_key,
+            )
+        });
+        if result.is_err() {
+            delete_uploaded_files(app, conn, uploaded_files);
+        }
+        return result;
+    }
+
+    let _span = info_span!("publish.transaction").entered();
+    let mut result = None;
+    conn.transaction(|conn| {
+        result = Some(publish(
+            app,
+            conn,
+            checked_uploads,
+            user,
+            api_token_id,
+            country,
+            &verified_email_address,
+            staged_until,
+            validation,
+            auth,
+            idempotency_key,
+        ));
+        Err(DieselError::RollbackTransaction)
+    })
+    .or_else(|error| match error {
+        DieselError::RollbackTransaction => Ok(()),
+        error => Err(error),
+    })?;
+
+    result.expect("the transaction closure is always called")
+}
+
+/// Saves the new versions to the database, inside of the publish transaction.
+#[allow(clippy::too_many_arguments)]
+fn publish(
+    app: &AppState,
+    conn: &mut PgConnection,
+    uploads: Vec<(CrateUpload, Option<TarballInfo>)>,
+    user: &User,
+    api_token_id: Option<i32>,
+    country: Option<&str>,
+    verified_email_address: &str,
+    staged_until: Option<NaiveDateTime>,
+    validation: &mut Validation,
+    auth: &Authentication,
+    idempotency_key: Option<&IdempotencyKey>,
+) -> AppResult<Vec<GoodCrate>> {
+    let mut published = Vec::with_capacity(uploads.len());
+    for (upload, tarball_info) in uploads {
+        let version = publish_version(
+            app,
+            conn,
+            upload,
+            tarball_info,
+            user,
+            api_token_id,
+            country,
+            verified_email_address,
+            staged_until,
+            validation,
+        )?;
+
+        published.extend(version);
+    }
+
+    if !validation.dry_run {
+        // Staged versions are added to the index once they are promoted
+        for version in published.iter().filter(|version| !version.staged) {
+            info_span!("publish.enqueue_sync_to_index")
+                .in_scope(|| Job::enqueue_sync_to_index(&version.krate_name, conn))?;
+        }
+    }
+
+    let published = published
+        .into_iter()
+        .map(|v| v.good_crate)
+        .collect::<Vec<_>>();
+
+    if let Some(key) = idempotency_key {
+        let response = serde_json::to_value(&published)
+            .map_err(|e| internal(format!("failed to serialize publish response: {e}")))?;
+        PublishIdempotencyKey::complete(conn, auth.user_id(), &key.key, &response)?;
+    }
+
+    Ok(published)
+}
+
+/// Checks a crate file before it is uploaded, see `publish_authenticated()`.
+///
+/// Returns `None` if a dry run could not continue because of a failed check.
+fn check_crate_file(
+    app: &AppState,
+    conn: &mut PgConnection,
+    upload: &CrateUpload,
+    user: &User,
+    validation: &mut Validation,
+) -> AppResult<Option<TarballInfo>> {
+    let name = &*upload.new_crate.name;
+    let vers = &*upload.new_crate.vers;
+
+    let existing_crate = Crate::by_name(name).first::<Crate>(conn).optional()?;
+
+    // Nothing is uploaded during dry runs, and these checks are repeated by `publish_version()`
+    if let (Some(krate), false) = (&existing_crate, validation.dry_run) {
+        let owners = krate.owners(conn)?;
+        if user.rights(app, &owners)? < Rights::Publish {
+            return Err(cargo_err(MISSING_RIGHTS_ERROR_MESSAGE));
+        }
+
+        // The crate file of an existing version must not be overwritten
+        Version::ensure_not_uploaded(conn, krate.id, vers)?;
+    }
 
-            if krate.name != *name {
-                return Err(cargo_err(&format_args!(
-                    "crate was previously named `{}`",
-                    krate.name
-                )));
+    let limits = match &existing_crate {
+        Some(krate) => UploadLimits::for_crate(conn, &app.config, krate.id)?,
+        None => UploadLimits::for_new_crate(&app.config),
+    };
+
+    validation.check(limits.check_upload_size(upload.tarball_bytes.len() as u64))?;
+
+    let pkg_name = format!("{name}-{vers}");
+    validation.check(
+        info_span!("publish.process_tarball")
+            .in_scope(|| process_tarball(&pkg_name, &upload.tarball_bytes, limits.max_unpack_size))
+            .map_err(tarball_to_app_error),
+    )
+}
+
+/// Uploads the crate file of a checked crate upload.
+///
+/// Returns `None` if the crate file did not have to be uploaded, because a crate file with the
+/// same content is already stored in the content-addressed layout.
+fn upload_crate_file(
+    app: &AppState,
+    upload: &CrateUpload,
+    staged: bool,
+) -> AppResult<Option<(String, String, UploadedFile)>> {
+    let name = &*upload.new_crate.name;
+    let vers = &*upload.new_crate.vers;
+    let bytes = upload.tarball_bytes.clone();
+
+    // Staged crate files are moved to the legacy layout once they are promoted
+    let object_hash = (app.config.content_addressed_storage && !staged)
+        .then(|| Sha256::digest(&bytes).encode_hex::<String>());
+
+    let future = async {
+        if staged {
+            app.storage
+                .upload_staged_crate_file(name, vers, bytes)
+                .await?;
+            Ok::<_, object_store::Error>(Some(UploadedFile::Staged))
+        } else if let Some(hash) = object_hash {
+            let uploaded = app.storage.upload_crate_object(&hash, bytes).await?;
+            if !uploaded {
+                info!(%name, %vers, %hash, "Crate file is already stored");
+                return Ok(None);
             }
+            Ok(Some(UploadedFile::Object(hash)))
+        } else {
+            app.storage.upload_crate_file(name, vers, bytes).await?;
+            Ok(Some(UploadedFile::CrateFile))
+        }
+    };
+
+    let uploaded = info_span!("publish.upload")
+        .in_scope(|| Handle::current().block_on(future))
+        .map_err(|e| internal(format!("failed to upload crate: {e}")))?;
+
+    Ok(uploaded.map(|file| (name.to_string(), vers.to_string(), file)))
+}
+
+/// A crate file that was uploaded while publishing a version, see `publish_authenticated()`.
+enum UploadedFile {
+    Staged,
+    CrateFile,
+    /// A file in the content-addressed layout, with its SHA-256 hash
+    Object(String),
+}
 
-            if let Some(daily_version_limit) = app.config.new_version_rate_limit {
-                let published_today = count_versions_published_today(krate.id, conn)?;
-                if published_today >= daily_version_limit as i64 {
-                    return Err(cargo_err(
-                        "You have published too many versions of this crate in the last 24 hours",
-                    ));
+/// Deletes the crate files of a publish that has been rolled back.
+///
+/// Files are kept if they belong to another version, which might have been published with the
+/// same name or the same content in the meantime.
+fn delete_uploaded_files(
+    app: &AppState,
+    conn: &mut PgConnection,
+    files: Vec<(String, String, UploadedFile)>,
+) {
+    for (name, vers, file) in files {
+        let result: anyhow::Result<()> = match file {
+            UploadedFile::Staged | UploadedFile::CrateFile => {
+                let published = diesel::select(diesel::dsl::exists(
+                    versions::table
+                        .inner_join(crates::table)
+                        .filter(crates::name.eq(&name))
+                        .filter(versions::num.eq(&vers)),
+                ))
+                .get_result::<bool>(conn);
+
+                match (published, file) {
+                    (Ok(true), _) => Ok(()),
+                    (Ok(false), UploadedFile::Staged) => Handle::current()
+                        .block_on(app.storage.delete_staged_crate_file(&name, &vers))
+                        .map_err(Into::into),
+                    (Ok(false), _) => Handle::current()
+                        .block_on(app.storage.delete_crate_file(&name, &vers))
+                        .map_err(Into::into),
+                    (Err(error), _) => Err(error.into()),
+                }
+            }
+            UploadedFile::Object(hash) => {
+                let referenced = diesel::select(diesel::dsl::exists(
+                    version_objects::table.filter(version_objects::object_hash.eq(&hash)),
+                ))
+                .get_result::<bool>(conn);
+
+                match referenced {
+                    Ok(true) => Ok(()),
+                    Ok(false) => Handle::current()
+                        .block_on(app.storage.delete_crate_object(&hash))
+                        .map_err(Into::into),
+                    Err(error) => Err(error.into()),
                 }
             }
+        };
+
+        if let Err(error) = result {
+            warn!(%name, %vers, ?error, "Failed to delete crate file of failed publish");
+        }
+    }
+}
 
-            let content_length = tarball_bytes.len() as u64;
+/// Saves a single new crate version to the database.
+///
+/// Returns `None` if a dry run could not continue because of a failed check.
+#[allow(clippy::too_many_arguments)]
+fn publish_version(
+    app: &AppState,
+    conn: &mut PgConnection,
+    upload: CrateUpload,
+    tarball_info: Option<TarballInfo>,
+    user: &User,
+    api_token_id: Option<i32>,
+    country: Option<&str>,
+    verified_email_address: &str,
+    staged_until: Option<NaiveDateTime>,
+    validation: &mut Validation,
+) -> AppResult<Option<PublishedVersion>> {
+    let CrateUpload {
+        new_crate,
+        tarball_bytes,
+    } = upload;
+
+    let name = new_crate.name;
+    let vers = &*new_crate.vers;
+    let links = new_crate.links;
+    let repo = new_crate.repository;
+    let features = new_crate
+        .features
+        .into_iter()
+        .map(|(k, v)| (k.0, v.into_iter().map(|v| v.0).collect()))
+        .collect();
+    let keywords = new_crate
+        .keywords
+        .iter()
+        .map(|s| s.as_str())
+        .collect::<Vec<_>>();
+    if !app.config.allow_non_ascii_keywords {
+        if let Some(keyword) = keywords.iter().find(|keyword| !keyword.is_ascii()) {
+            validation.report(cargo_err(&format_args!(
+                "invalid upload request: invalid value: string \"{keyword}\", \
+                 expected a valid keyword specifier"
+            )))?;
+        }
+    }
+    let categories = new_crate
+        .categories
+        .iter()
+        .map(|s| s.as_str())
+        .collect::<Vec<_>>();
+
+    // Persist the new crate, if it doesn't already exist
+    let persist = NewCrate {
+        name: &name,
+        description: new_crate.description.as_deref(),
+        homepage: new_crate.homepage.as_deref(),
+        documentation: new_crate.documentation.as_deref(),
+        readme: new_crate.readme.as_deref(),
+        repository: repo.as_deref(),
+    };
+
+    validation.check(validate_namespace(app, conn, &name, user))?;
+
+    // The metadata of existing crates is compared with the metadata after the
+    // publish, so that suspicious changes end up in the audit log
+    let previous_metadata = match Crate::by_name(&name)
+        .select(crates::id)
+        .first::<i32>(conn)
+        .optional()?
+    {
+        Some(crate_id) => Some(CrateMetadata::load(conn, crate_id)?),
+        None => None,
+    };
+
+    let license_file = new_crate.license_file.as_deref();
+    let Some(krate) = validation.check(persist.create_or_update(
+        conn,
+        user.id,
+        Some(&app.config.publish_rate_limit),
+    ))?
+    else {
+        return Ok(None);
+    };
+
+    let owners = krate.owners(conn)?;
+    if user.rights(app, &owners)? < Rights::Publish {
+        validation.report(cargo_err(MISSING_RIGHTS_ERROR_MESSAGE))?;
+    }
+
+    if krate.name != *name {
+        validation.report(cargo_err(&format_args!(
+            "crate was previously named `{}`",
+            krate.name
+        )))?;
+    }
+
+    let quarantine = CrateQuarantine::find(conn, krate.id)?;
+    if quarantine
+        .as_ref()
+        .map_or(false, CrateQuarantine::is_rejected)
+    {
+        validation.report(cargo_err(&format_args!(
+            "crate `{}` was rejected during review and can not be published anymore",
+            krate.name
+        )))?;
+    }
+
+    if let Some(daily_version_limit) = app.config.new_version_rate_limit {
+        let published_today = count_versions_published_today(krate.id, conn)?;
+        if published_today >= daily_version_limit as i64 {
+            validation.report(cargo_err(
+                "You have published too many versions of this crate in the last 24 hours",
+            ))?;
+        }
+    }
 
-            let maximums = Maximums::new(
-                krate.max_upload_size,
-                app.config.max_upload_size,
-                app.config.max_unpack_size,
-            );
+    validation.check(validate_categories(conn, &categories))?;
+
+    // The upload limits have already been checked by `check_crate_file()`
+    let content_length = tarball_bytes.len() as u64;
+
+    // This is only redundant for now. Eventually the duplication will be removed.
+    let license = new_crate.license.clone();
+
+    // Read tarball from request
+    let hex_cksum: String =
+        info_span!("publish.checksum").in_scope(|| Sha256::digest(&tarball_bytes).encode_hex());
+
+    // Staged crate files are moved to the legacy layout once they are promoted
+    let object_hash =
+        (app.config.content_addressed_storage && staged_until.is_none()).then(|| hex_cksum.clone());
+
+    let has_build_script = tarball_info
+        .as_ref()
+        .map_or(false, |info| info.has_build_script);
+
+    let large_binary_files = tarball_info
+        .as_ref()
+        .map(|info| {
+            info.binary_files
+                .iter()
+                .filter(|file| file.size >= LARGE_BINARY_FILE_SIZE)
+                .map(|file| file.path.clone())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let content_hash = tarball_info
+        .as_ref()
+        .and_then(|info| info.content_hash.clone());
+
+    // The identical content of another crate under a new name is likely
+    // squatting or a republish, so it is flagged for review
+    let duplicate_of = match &content_hash {
+        Some(content_hash) if is_new_crate(conn, krate.id)? => {
+            PublishDetails::find_duplicate(conn, content_hash, krate.id)?
+        }
+        _ => None,
+    };
+
+    let mut other_warnings = Vec::new();
+    if let Some((_, duplicate_name, duplicate_num)) = &duplicate_of {
+        if app.config.reject_duplicate_content {
+            validation.report(cargo_err(&format_args!(
+                "the content of this crate is identical to `{duplicate_name}` {duplicate_num}, \
+                 republishing existing crates under a new name is not allowed"
+            )))?;
+        } else {
+            other_warnings.push(format!(
+                "the content of this crate is identical to `{duplicate_name}` {duplicate_num}, \
+                 so it has been flagged for review"
+            ));
+        }
+    }
+
+    let (manifest, vcs_info) = tarball_info
+        .map(|info| (info.manifest, info.vcs_info))
+        .unwrap_or_default();
+
+    let rust_version = manifest
+        .and_then(|m| m.package.rust_version)
+        .map(|rv| rv.deref().to_string());
+
+    // Persist the new version of this crate
+    let version = validation.check(
+        NewVersion::new(
+            krate.id,
+            vers,
+            &features,
+            license,
+            license_file,
+            // Downcast is okay because the file length must be less than the max upload size
+            // to get here, and max upload sizes are way less than i32 max
+            content_length as i32,
+            user.id,
+            hex_cksum,
+            links,
+            rust_version,
+            staged_until,
+        )
+        .and_then(|version| version.save(conn, verified_email_address)),
+    )?;
+
+    let dependencies = validate_dependencies(conn, &new_crate.deps, validation)?;
+
+    if let Some(version) = &version {
+        insert_version_owner_action(
+            conn,
+            version.id,
+            user.id,
+            api_token_id,
+            VersionAction::Publish,
+        )?;
+
+        // Subscribers of staged versions are notified once the version is promoted
+        if staged_until.is_none() {
+            insert_crate_notification(conn, version.id, VersionAction::Publish)?;
+        }
+
+        // Link this new version to all dependencies
+        add_dependencies(conn, &dependencies, version.id)?;
+
+        // Staged versions can be recompressed with `enqueue-job` once promoted
+        if !validation.dry_run && staged_until.is_none() && app.config.zstd_recompression {
+            Job::recompress_crate_file(version.id).enqueue(conn)?;
+        }
 
-            if content_length > maximums.max_upload_size {
-                return Err(cargo_err(&format_args!(
-                    "max upload size is: {}",
-                    maximums.max_upload_size
-                )));
+        PublishDetails {
+            version_id: version.id,
+            api_token_id,
+            country: country.map(str::to_string),
+            has_build_script,
+            large_binary_files,
+            content_hash,
+        }
+        .insert(conn)?;
+
+        if let Some((duplicate_version_id, _, _)) = duplicate_of {
+            CrateQuarantine::create_for_duplicate(conn, krate.id, user.id, duplicate_version_id)?;
+        }
+
+        if let Some(object_hash) = &object_hash {
+            VersionObject::insert(conn, version.id, object_hash)?;
+        }
+
+        if !validation.dry_run {
+            Job::check_publish(version.id).enqueue(conn)?;
+        }
+
+        if let Some(max_account_age) = app.config.quarantine_account_age {
+            let max_account_age = chrono::Duration::from_std(max_account_age)
+                .map_err(|_| internal("invalid quarantine account age"))?;
+
+            if quarantine.is_none() && user.is_younger_than(max_account_age) {
+                CrateQuarantine::create(conn, krate.id, user.id)?;
             }
+        }
+    }
 
-            // This is only redundant for now. Eventually the duplication will be removed.
-            let license = new_crate.license.clone();
-
-            // Read tarball from request
-            let hex_cksum: String = Sha256::digest(&tarball_bytes).encode_hex();
-
-            let pkg_name = format!("{}-{}", krate.name, vers);
-            let tarball_info = process_tarball(&pkg_name, &tarball_bytes, maximums.max_unpack_size)
-                .map_err(tarball_to_app_error)?;
-
-            let rust_version = tarball_info
-                .manifest
-                .and_then(|m| m.package.rust_version)
-                .map(|rv| rv.deref().to_string());
-
-            // Persist the new version of this crate
-            let version = NewVersion::new(
-                krate.id,
-                vers,
-                &features,
-                license,
-                license_file,
-                // Downcast is okay because the file length must be less than the max upload size
-                // to get here, and max upload sizes are way less than i32 max
-                content_length as i32,
-                user.id,
-                hex_cksum,
-                links,
-                rust_version,
-            )?
-            .save(conn, &verified_email_address)?;
-
-            insert_version_owner_action(
-                conn,
+    // Update all keywords for this crate
+    Keyword::update_crate(conn, &krate, &keywords)?;
+
+    // Update all categories for this crate. Unknown categories have already
+    // been rejected by `validate_categories()`.
+    Category::update_crate(conn, &krate, &categories)?;
+
+    if let (Some(version), Some(previous_metadata)) = (&version, &previous_metadata) {
+        let changes = CrateMetadata::load(conn, krate.id)?.diff(previous_metadata);
+        if !changes.is_empty() {
+            let mut entry = NewAuditLogEntry::new(user.id, AuditAction::CrateMetadataChanged);
+            entry.api_token_id = api_token_id;
+            entry.details = json!({
+                "crate": krate.name,
+                "version": version.num,
+                "changes": changes,
+            });
+            entry.insert(conn)?;
+        }
+    }
+
+    let top_versions = krate.top_versions(conn)?;
+
+    let pkg_path_in_vcs = vcs_info.map(|info| info.path_in_vcs);
+
+    if let (Some(version), Some(readme)) = (&version, new_crate.readme) {
+        if !validation.dry_run && !readme.is_empty() {
+            Job::render_and_upload_readme(
                 version.id,
-                user.id,
-                api_token_id,
-                VersionAction::Publish,
-            )?;
-
-            // Link this new version to all dependencies
-            add_dependencies(conn, &new_crate.deps, version.id)?;
-
-            // Update all keywords for this crate
-            Keyword::update_crate(conn, &krate, &keywords)?;
-
-            // Update all categories for this crate, collecting any invalid categories
-            // in order to be able to warn about them
-            let ignored_invalid_categories = Category::update_crate(conn, &krate, &categories)?;
-
-            let top_versions = krate.top_versions(conn)?;
-
-            let pkg_path_in_vcs = tarball_info.vcs_info.map(|info| info.path_in_vcs);
-
-            if let Some(readme) = new_crate.readme {
-                if !readme.is_empty() {
-                    Job::render_and_upload_readme(
-                        version.id,
-                        readme,
-                        new_crate
-                            .readme_file
-                            .unwrap_or_else(|| String::from("README.md")),
-                        repo,
-                        pkg_path_in_vcs,
-                    )
-                    .enqueue_with_priority(conn, PRIORITY_RENDER_README)?;
-                }
+                readme,
+                new_crate
+                    .readme_file
+                    .unwrap_or_else(|| String::from("README.md")),
+                repo,
+                pkg_path_in_vcs,
+            )
+            .enqueue_with_priority(conn, PRIORITY_RENDER_README)?;
+        }
+    }
+
+    // The `other` field on `PublishWarnings` is currently only used for crates whose content
+    // duplicates another crate.
+    let warnings = PublishWarnings {
+        invalid_categories: vec![],
+        invalid_badges: vec![],
+        other: other_warnings,
+    };
+
+    Ok(Some(PublishedVersion {
+        krate_name: krate.name.clone(),
+        staged: staged_until.is_some(),
+        good_crate: GoodCrate {
+            krate: EncodableCrate::from_minimal(krate, Some(&top_versions), None, false, None),
+            warnings,
+        },
+    }))
+}
+
+/// Keeps track of failed checks while publishing a crate.
+///
+/// Outside of dry runs, the first error is returned immediately. During dry runs, errors created
+/// via [`cargo_err`] are collected instead, so that all of them can be reported at once.
+struct Validation {
+    dry_run: bool,
+    errors: Vec<String>,
+}
+
+impl Validation {
+    fn new(dry_run: bool) -> Self {
+        Self {
+            dry_run,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Returns `Ok(None)` if the result is an error that was collected.
+    fn check<T>(&mut self, result: AppResult<T>) -> AppResult<Option<T>> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(error) if self.dry_run && is_cargo_err(&error) => {
+                self.errors.push(error.to_string());
+                Ok(None)
             }
+            Err(error) => Err(error),
+        }
+    }
 
-            // Upload crate tarball
-            Handle::current()
-                .block_on(app.storage.upload_crate_file(
-                    &krate.name,
-                    &vers.to_string(),
-                    tarball_bytes,
-                ))
-                .map_err(|e| internal(format!("failed to upload crate: {e}")))?;
-
-            Job::enqueue_sync_to_index(&krate.name, conn)?;
-
-            // The `other` field on `PublishWarnings` was introduced to handle a temporary warning
-            // that is no longer needed. As such, crates.io currently does not return any `other`
-            // warnings at this time, but if we need to, the field is available.
-            let warnings = PublishWarnings {
-                invalid_categories: ignored_invalid_categories,
-                invalid_badges: vec![],
-                other: vec![],
-            };
-
-            Ok(Json(GoodCrate {
-                krate: EncodableCrate::from_minimal(krate, Some(&top_versions), None, false, None),
-                warnings,
-            }))
+    fn report(&mut self, error: BoxedAppError) -> AppResult<()> {
+        self.check(Err::<(), _>(error)).map(|_| ())
+    }
+
+    fn into_response(self) -> Response {
+        let errors = self
+            .errors
+            .into_iter()
+            .map(|detail| json!({ "detail": detail }))
+            .collect::<Vec<_>>();
+
+        Json(json!({ "errors": errors })).into_response()
+    }
+}
+
+/// Makes sure that all metadata fields required for publishing are provided.
+#[instrument(skip_all)]
+fn validate_metadata(new_crate: &EncodableCrateUpload) -> AppResult<()> {
+    fn empty(s: Option<&String>) -> bool {
+        s.map_or(true, String::is_empty)
+    }
+
+    // It can have up to three elements per below conditions.
+    let mut missing = Vec::with_capacity(3);
+
+    if empty(new_crate.description.as_ref()) {
+        missing.push("description");
+    }
+    if empty(new_crate.license.as_ref()) && empty(new_crate.license_file.as_ref()) {
+        missing.push("license");
+    }
+    if !missing.is_empty() {
+        let message = missing_metadata_error_message(&missing);
+        return Err(cargo_err(&message));
+    }
+
+    Ok(())
+}
+
+/// The maximum number of valid categories suggested for each unknown category.
+const MAX_CATEGORY_SUGGESTIONS: i64 = 3;
+
+/// Makes sure that all categories exist, suggesting the closest valid
+/// categories for the ones that don't.
+#[instrument(skip_all)]
+fn validate_categories(conn: &mut PgConnection, slugs: &[&str]) -> AppResult<()> {
+    let known: Vec<String> = categories::table
+        .filter(categories::slug.eq_any(slugs))
+        .select(categories::slug)
+        .load(conn)?;
+
+    let unknown = slugs
+        .iter()
+        .filter(|slug| !known.iter().any(|known| known == *slug))
+        .map(|slug| {
+            let suggestions = Category::suggest(conn, slug, MAX_CATEGORY_SUGGESTIONS)?
+                .into_iter()
+                .map(|category| category.slug)
+                .collect();
+            Ok((slug.to_string(), suggestions))
         })
-    })
-    .await
+        .collect::<QueryResult<Vec<_>>>()?;
+
+    if !unknown.is_empty() {
+        return Err(unknown_categories(unknown));
+    }
+
+    Ok(())
+}
+
+/// Makes sure that new crates within a namespace claimed by a GitHub
+/// organization can only be published by members of the organization.
+///
+/// Crates that already exist are not affected, since their owners are checked
+/// separately.
+#[instrument(skip_all)]
+fn validate_namespace(
+    app: &AppState,
+    conn: &mut PgConnection,
+    name: &str,
+    user: &User,
+) -> AppResult<()> {
+    let Some(claim) = NamespaceClaim::find_for_crate(conn, name)? else {
+        return Ok(());
+    };
+
+    let exists: bool =
+        diesel::select(diesel::dsl::exists(Crate::by_name(name))).get_result(conn)?;
+    if exists || claim.contains_user(app, user)? {
+        return Ok(());
+    }
+
+    Err(cargo_err(&format_args!(
+        "the crate name `{name}` is within the `{}` namespace, which is reserved for \
+         members of the `{}` GitHub organization",
+        claim.pattern(),
+        claim.github_org_login
+    )))
 }
 
 /// Counts the number of versions for `krate_id` that were published within
@@ -292,8 +1158,18 @@ fn count_versions_published_today(krate_id: i32, conn: &mut PgConnection) -> Que
         .get_result(conn)
 }
 
+/// Returns `true` if no version of the crate has been published yet.
+fn is_new_crate(conn: &mut PgConnection, krate_id: i32) -> QueryResult<bool> {
+    let has_versions: bool = diesel::select(diesel::dsl::exists(
+        versions::table.filter(versions::crate_id.eq(krate_id)),
+    ))
+    .get_result(conn)?;
+
+    Ok(!has_versions)
+}
+
 #[instrument(skip_all)]
-fn split_body<R: RequestPartsExt>(mut bytes: Bytes, req: &R) -> AppResult<(Bytes, Bytes)> {
+fn split_body<R: RequestPartsExt>(bytes: &mut Bytes, req: &R) -> AppResult<(Bytes, Bytes)> {
     // The format of the req.body() of a publish request is as follows:
     //
     // metadata length
@@ -343,10 +1219,49 @@ pub fn missing_metadata_error_message(missing: &[&str]) -> String {
     )
 }
 
+/// Looks up the crates of all dependencies and makes sure that they are allowed on crates.io.
+#[instrument(skip_all)]
+fn validate_dependencies<'a>(
+    conn: &mut PgConnection,
+    deps: &'a [EncodableCrateDependency],
+    validation: &mut Validation,
+) -> AppResult<Vec<(&'a EncodableCrateDependency, i32)>> {
+    let mut dependencies = Vec::with_capacity(deps.len());
+    for dep in deps {
+        if let Some(crate_id) = validation.check(validate_dependency(conn, dep))? {
+            dependencies.push((dep, crate_id));
+        }
+    }
+
+    Ok(dependencies)
+}
+
+/// Returns the ID of the crate that the dependency refers to.
+fn validate_dependency(conn: &mut PgConnection, dep: &EncodableCrateDependency) -> AppResult<i32> {
+    if let Some(registry) = &dep.registry {
+        if !registry.is_empty() {
+            return Err(cargo_err(&format_args!("Dependency `{}` is hosted on another registry. Cross-registry dependencies are not permitted on crates.io.", &*dep.name)));
+        }
+    }
+
+    // Match only identical names to ensure the index always references the original crate name
+    let krate: Crate = Crate::by_exact_name(&dep.name)
+        .first(conn)
+        .map_err(|_| cargo_err(&format_args!("no known crate named `{}`", &*dep.name)))?;
+
+    if let Ok(version_req) = semver::VersionReq::parse(&dep.version_req.0) {
+        if version_req == semver::VersionReq::STAR {
+            return Err(cargo_err(WILDCARD_ERROR_MESSAGE));
+        }
+    }
+
+    Ok(krate.id)
+}
+
 #[instrument(skip_all)]
 pub fn add_dependencies(
     conn: &mut PgConnection,
-    deps: &[EncodableCrateDependency],
+    deps: &[(&EncodableCrateDependency, i32)],
     target_version_id: i32,
 ) -> AppResult<()> {
     use self::dependencies::dsl::*;
@@ -354,37 +1269,20 @@ pub fn add_dependencies(
 
     let new_dependencies = deps
         .iter()
-        .map(|dep| {
-            if let Some(registry) = &dep.registry {
-                if !registry.is_empty() {
-                    return Err(cargo_err(&format_args!("Dependency `{}` is hosted on another registry. Cross-registry dependencies are not permitted on crates.io.", &*dep.name)));
-                }
-            }
-
-            // Match only identical names to ensure the index always references the original crate name
-            let krate:Crate = Crate::by_exact_name(&dep.name)
-                .first(conn)
-                .map_err(|_| cargo_err(&format_args!("no known crate named `{}`", &*dep.name)))?;
-
-            if let Ok(version_req) = semver::VersionReq::parse(&dep.version_req.0) {
-                if version_req == semver::VersionReq::STAR {
-                    return Err(cargo_err(WILDCARD_ERROR_MESSAGE));
-                }
-            }
-
-            Ok((
+        .map(|(dep, dep_crate_id)| {
+            (
                 version_id.eq(target_version_id),
-                crate_id.eq(krate.id),
+                crate_id.eq(dep_crate_id),
                 req.eq(dep.version_req.to_string()),
                 dep.kind.map(|k| kind.eq(k as i32)),
                 optional.eq(dep.optional),
                 default_features.eq(dep.default_features),
                 features.eq(&dep.features),
                 target.eq(dep.target.as_deref()),
-                explicit_name.eq(dep.explicit_name_in_toml.as_deref())
-            ))
+                explicit_name.eq(dep.explicit_name_in_toml.as_deref()),
+            )
         })
-        .collect::<Result<Vec<_>, _>>()?;
+        .collect::<Vec<_>>();
 
     insert_into(dependencies)
         .values(&new_dependencies)
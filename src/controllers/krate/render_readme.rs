@@ -0,0 +1,84 @@
+//! Endpoint for previewing how a README is rendered before publishing it
+
+use crate::auth::AuthCheck;
+use crate::controllers::frontend_prelude::*;
+use crate::models::Crate;
+use crate::publish_rate_limit::LimitedAction;
+use crate::util::errors::not_found;
+use crates_io_markdown::text_to_html_with_policy;
+
+/// The maximum size of the request body. READMEs that are larger than this
+/// can still be published, but not previewed.
+pub const MAX_README_PREVIEW_SIZE: usize = 512 * 1024;
+
+#[derive(Deserialize)]
+struct RenderReadmeRequest {
+    text: String,
+    /// The path of the README file in the package, which determines whether
+    /// it is rendered as Markdown or as plain text
+    readme_file: Option<String>,
+    /// The repository URL that relative links and images are resolved against
+    repository: Option<String>,
+    /// The path of the package in the repository, like `path_in_vcs` in
+    /// `.cargo_vcs_info.json`
+    path_in_vcs: Option<String>,
+    /// An existing crate whose repository URL is used if `repository` is
+    /// missing
+    #[serde(rename = "crate")]
+    krate: Option<String>,
+}
+
+/// Handles the `POST /render_readme` route.
+///
+/// Returns the sanitized HTML that the registry would render for the README
+/// when it is published, so that maintainers can preview it.
+pub async fn render_readme(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        if req.body().len() > MAX_README_PREVIEW_SIZE {
+            let detail =
+                format!("the request must not be larger than {MAX_README_PREVIEW_SIZE} bytes");
+            return Err(bad_request(&detail));
+        }
+
+        let request: RenderReadmeRequest = serde_json::from_slice(req.body())
+            .map_err(|e| bad_request(&format!("invalid render request: {e}")))?;
+
+        let conn = &mut *app.db_write()?;
+
+        let auth = AuthCheck::default().check(&req, conn)?;
+        app.config.readme_preview_rate_limit.check_rate_limit(
+            auth.user_id(),
+            LimitedAction::RenderReadme,
+            conn,
+        )?;
+
+        let repository = match (request.repository, request.krate) {
+            (Some(repository), _) => Some(repository),
+            (None, Some(name)) => {
+                let krate: Crate = Crate::by_name(&name)
+                    .first(conn)
+                    .optional()?
+                    .ok_or_else(not_found)?;
+
+                krate.repository
+            }
+            (None, None) => None,
+        };
+
+        let readme_file = request.readme_file.as_deref().unwrap_or("README.md");
+        let policy = app
+            .config
+            .readme_sanitization
+            .policy(&app.config.domain_name);
+        let html = text_to_html_with_policy(
+            &request.text,
+            readme_file,
+            repository.as_deref(),
+            request.path_in_vcs.as_deref(),
+            &policy,
+        );
+
+        Ok(Json(json!({ "html": html })))
+    })
+    .await
+}
@@ -0,0 +1,270 @@
+//! Endpoint for resolving version requirements to concrete versions
+
+use std::collections::HashMap;
+
+use diesel::dsl::sql;
+use diesel::sql_types::Bool;
+use semver::{Comparator, Op, VersionReq};
+
+use crate::controllers::frontend_prelude::*;
+use crate::models::Advisory;
+use crate::schema::{crates, versions};
+
+/// The maximum number of dependencies that can be resolved at once.
+pub const MAX_DEPENDENCIES: usize = 100;
+
+#[derive(Deserialize)]
+struct ResolveRequest {
+    dependencies: Vec<DependencyRequest>,
+}
+
+#[derive(Deserialize)]
+struct DependencyRequest {
+    name: String,
+    req: String,
+}
+
+/// Handles the `POST /resolve` route.
+///
+/// Resolves each `(name, req)` pair to the highest version of the crate matching the version
+/// requirement. Like `cargo`, yanked versions are only selected if no other version matches,
+/// in which case the result is flagged as `yanked`. If no version matches at all, or the crate
+/// does not exist, `version` is `null`.
+///
+/// The `advisories` of a result contain the identifiers of all security advisories that affect
+/// the resolved version, see the `admin::advisories` module.
+pub async fn resolve(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let request: ResolveRequest = serde_json::from_slice(req.body())
+            .map_err(|e| bad_request(&format!("invalid resolve request: {e}")))?;
+
+        let dependencies = request.dependencies;
+        if dependencies.len() > MAX_DEPENDENCIES {
+            let detail =
+                format!("too many dependencies requested, the maximum is {MAX_DEPENDENCIES}");
+            return Err(bad_request(&detail));
+        }
+
+        let requirements = dependencies
+            .iter()
+            .map(|dep| {
+                VersionReq::parse(&dep.req).map_err(|e| {
+                    bad_request(&format!("invalid version requirement `{}`: {e}", dep.req))
+                })
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        let names = dependencies
+            .iter()
+            .map(|dep| dep.name.as_str())
+            .collect::<Vec<_>>();
+
+        let conn = &mut *app.db_read()?;
+
+        let mut advisories_by_crate: HashMap<String, Vec<Advisory>> = HashMap::new();
+        for (advisory, name) in Advisory::for_crates(conn, &names)? {
+            advisories_by_crate.entry(name).or_default().push(advisory);
+        }
+
+        let resolved = dependencies
+            .iter()
+            .zip(requirements)
+            .map(|(dep, requirement)| -> AppResult<Value> {
+                // Only the versions within the bounds of the requirement are
+                // loaded, using the `to_semver_triple()` index
+                let rows: Vec<(String, bool)> = versions::table
+                    .inner_join(crates::table)
+                    .filter(crates::name.eq(&dep.name))
+                    .filter(versions::staged_until.is_null())
+                    .filter(sql::<Bool>(&semver_triple_filter(&requirement)))
+                    .select((versions::num, versions::yanked))
+                    .load(conn)?;
+
+                let matching = rows
+                    .into_iter()
+                    // skip versions that we can't parse
+                    .filter_map(|(num, yanked)| Some((semver::Version::parse(&num).ok()?, yanked)))
+                    .filter(|(version, _)| requirement.matches(version));
+
+                // Prefer non-yanked versions, then the highest version
+                let best = matching.max_by_key(|(version, yanked)| (!yanked, version.clone()));
+
+                let advisories = best
+                    .as_ref()
+                    .map(|(version, _)| {
+                        advisories_by_crate
+                            .get(&dep.name)
+                            .into_iter()
+                            .flatten()
+                            .filter(|advisory| advisory.affects(version))
+                            .map(|advisory| advisory.identifier.as_str())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+
+                Ok(json!({
+                    "name": dep.name,
+                    "req": dep.req,
+                    "version": best.as_ref().map(|(version, _)| version.to_string()),
+                    "yanked": best.as_ref().map_or(false, |(_, yanked)| *yanked),
+                    "advisories": advisories,
+                }))
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(Json(json!({ "dependencies": resolved })))
+    })
+    .await
+}
+
+/// A `(major, minor, patch)` version triple, ignoring pre-release identifiers.
+type Triple = (u64, u64, u64);
+
+/// Returns an SQL condition that limits the versions to the range of version
+/// triples that can match the requirement.
+///
+/// The range might contain versions that don't match the requirement, e.g.
+/// pre-releases, so the versions still have to be matched afterwards.
+fn semver_triple_filter(requirement: &VersionReq) -> String {
+    let (lower, upper) = triple_bounds(requirement);
+
+    let row =
+        |(major, minor, patch): Triple| format!("ROW({major}, {minor}, {patch})::semver_triple");
+    let conditions = lower
+        .map(|lower| format!("to_semver_triple(versions.num) >= {}", row(lower)))
+        .into_iter()
+        .chain(upper.map(|upper| format!("to_semver_triple(versions.num) < {}", row(upper))))
+        .collect::<Vec<_>>();
+
+    if conditions.is_empty() {
+        "TRUE".to_string()
+    } else {
+        conditions.join(" AND ")
+    }
+}
+
+/// Returns the inclusive lower and the exclusive upper bound of the version
+/// triples that can match the requirement.
+fn triple_bounds(requirement: &VersionReq) -> (Option<Triple>, Option<Triple>) {
+    requirement
+        .comparators
+        .iter()
+        .map(comparator_bounds)
+        .fold((None, None), |(lower, upper), (c_lower, c_upper)| {
+            (lower.max(c_lower), min_bound(upper, c_upper))
+        })
+}
+
+fn min_bound(a: Option<Triple>, b: Option<Triple>) -> Option<Triple> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn comparator_bounds(comparator: &Comparator) -> (Option<Triple>, Option<Triple>) {
+    let next = |value: u64| value.saturating_add(1);
+
+    let major = comparator.major;
+    let lower = (
+        major,
+        comparator.minor.unwrap_or(0),
+        comparator.patch.unwrap_or(0),
+    );
+
+    match (comparator.op, comparator.minor, comparator.patch) {
+        (Op::Exact | Op::Wildcard, None, _) => (Some(lower), Some((next(major), 0, 0))),
+        (Op::Exact | Op::Wildcard, Some(minor), None) => {
+            (Some(lower), Some((major, next(minor), 0)))
+        }
+        (Op::Exact | Op::Wildcard, Some(minor), Some(patch)) => {
+            (Some(lower), Some((major, minor, next(patch))))
+        }
+        (Op::Greater, None, _) => (Some((next(major), 0, 0)), None),
+        (Op::Greater, Some(minor), None) => (Some((major, next(minor), 0)), None),
+        (Op::Greater, Some(_), Some(_)) | (Op::GreaterEq, _, _) => (Some(lower), None),
+        // Pre-releases of the version itself only match if the comparator
+        // has a pre-release as well
+        (Op::Less, _, Some(patch)) if !comparator.pre.is_empty() => {
+            (None, Some((major, lower.1, next(patch))))
+        }
+        (Op::Less, _, _) => (None, Some(lower)),
+        (Op::LessEq, None, _) => (None, Some((next(major), 0, 0))),
+        (Op::LessEq, Some(minor), None) => (None, Some((major, next(minor), 0))),
+        (Op::LessEq, Some(minor), Some(patch)) => (None, Some((major, minor, next(patch)))),
+        (Op::Tilde, None, _) => (Some(lower), Some((next(major), 0, 0))),
+        (Op::Tilde, Some(minor), _) => (Some(lower), Some((major, next(minor), 0))),
+        (Op::Caret, Some(0), Some(patch)) if major == 0 => (Some(lower), Some((0, 0, next(patch)))),
+        (Op::Caret, Some(minor), _) if major == 0 => (Some(lower), Some((0, next(minor), 0))),
+        (Op::Caret, _, _) => (Some(lower), Some((next(major), 0, 0))),
+        _ => (None, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(requirement: &str) -> (Option<Triple>, Option<Triple>) {
+        triple_bounds(&VersionReq::parse(requirement).unwrap())
+    }
+
+    #[test]
+    fn requirement_bounds() {
+        assert_eq!(bounds("*"), (None, None));
+        assert_eq!(bounds("1.2.3"), (Some((1, 2, 3)), Some((2, 0, 0))));
+        assert_eq!(bounds("^0.2.3"), (Some((0, 2, 3)), Some((0, 3, 0))));
+        assert_eq!(bounds("^0.0.3"), (Some((0, 0, 3)), Some((0, 0, 4))));
+        assert_eq!(bounds("~1.2"), (Some((1, 2, 0)), Some((1, 3, 0))));
+        assert_eq!(bounds("=1.2.3"), (Some((1, 2, 3)), Some((1, 2, 4))));
+        assert_eq!(bounds("1.*"), (Some((1, 0, 0)), Some((2, 0, 0))));
+        assert_eq!(bounds(">1.2"), (Some((1, 3, 0)), None));
+        assert_eq!(bounds("<=1.2"), (None, Some((1, 3, 0))));
+        assert_eq!(bounds("<2.0.0-beta"), (None, Some((2, 0, 1))));
+        assert_eq!(bounds(">=1.2, <1.5"), (Some((1, 2, 0)), Some((1, 5, 0))));
+    }
+
+    #[test]
+    fn bounds_contain_all_matches() {
+        let versions = [
+            "0.0.3",
+            "0.2.9",
+            "1.0.0",
+            "1.2.3",
+            "1.4.0-beta.1",
+            "2.0.0-beta.2",
+        ];
+        let requirements = [
+            "*",
+            "^0.0.3",
+            "^0.2",
+            "^1.2",
+            "~1",
+            "=1.2.3",
+            ">1.2",
+            ">=1.4.0-beta",
+            "<=1.2",
+            "<2.0.0-beta.3",
+            ">=1.2, <1.5",
+        ];
+
+        for requirement in requirements {
+            let (lower, upper) = bounds(requirement);
+            let requirement = VersionReq::parse(requirement).unwrap();
+            for version in versions {
+                let version = semver::Version::parse(version).unwrap();
+                if requirement.matches(&version) {
+                    let triple = (version.major, version.minor, version.patch);
+                    assert!(
+                        lower.map_or(true, |lower| triple >= lower),
+                        "{requirement} {version}"
+                    );
+                    assert!(
+                        upper.map_or(true, |upper| triple < upper),
+                        "{requirement} {version}"
+                    );
+                }
+            }
+        }
+    }
+}
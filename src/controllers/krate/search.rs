@@ -5,13 +5,18 @@ use diesel::dsl::*;
 use diesel::sql_types::Array;
 use diesel_full_text_search::*;
 use indexmap::IndexMap;
+use std::collections::HashMap;
 
 use crate::controllers::cargo_prelude::*;
 use crate::controllers::helpers::Paginate;
-use crate::models::{Crate, CrateOwner, CrateVersions, OwnerKind, TopVersions, Version};
+use crate::models::{
+    Crate, CrateOwner, CrateQuarantine, CrateVersions, OwnerKind, PublisherVerification,
+    TopVersions, Version,
+};
 use crate::schema::*;
 use crate::util::errors::bad_request;
-use crate::views::EncodableCrate;
+use crate::util::unicode;
+use crate::views::{EncodableCrate, EncodableVerifiedPublisher};
 
 use crate::controllers::helpers::pagination::{Page, Paginated, PaginationOptions};
 use crate::models::krate::ALL_COLUMNS;
@@ -52,7 +57,14 @@ pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
         // Remove 0x00 characters from the query string because Postgres can not
         // handle them and will return an error, which would cause us to throw
         // an Internal Server Error ourselves.
-        let q_string = params.get("q").map(|q| q.replace('\u{0}', ""));
+        let q_string = params
+            .get("q")
+            .map(|q| unicode::fold(&q.replace('\u{0}', "")));
+
+        let transliterated_q = q_string
+            .as_deref()
+            .filter(|_| app.config.search_transliteration)
+            .and_then(unicode::transliterate);
 
         let selection = (
             ALL_COLUMNS,
@@ -64,6 +76,15 @@ pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
             .select(selection)
             .into_boxed();
 
+        // Crates under legal hold are hidden from search, but stay available
+        // under their own URLs
+        query = query.filter(not(exists(
+            legal_holds::table
+                .filter(legal_holds::crate_id.eq(crates::id))
+                .filter(legal_holds::version_id.is_null())
+                .filter(legal_holds::lifted_at.is_null()),
+        )));
+
         let mut supports_seek = true;
 
         if let Some(q_string) = &q_string {
@@ -74,13 +95,23 @@ pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
             if !q_string.is_empty() {
                 let sort = params.get("sort").map(|s| &**s).unwrap_or("relevance");
 
+                // The transliteration is matched in addition to the original
+                // query, since descriptions are not transliterated. Without a
+                // transliteration the second `tsquery` is empty, which leaves
+                // the first one unchanged.
                 let q = sql::<TsQuery>("plainto_tsquery('english', ")
                     .bind::<Text, _>(q_string)
+                    .sql(") || plainto_tsquery('english', ")
+                    .bind::<Text, _>(transliterated_q.as_deref().unwrap_or_default())
                     .sql(")");
+
+                // Crate names are always ASCII, so only the transliteration
+                // can match them
+                let name_q = transliterated_q.as_deref().unwrap_or(q_string);
                 query = query.filter(
                     q.clone()
                         .matches(crates::textsearchable_index_col)
-                        .or(Crate::loosly_matches_name(q_string)),
+                        .or(Crate::loosly_matches_name(name_q)),
                 );
 
                 query = query.select((
@@ -219,7 +250,8 @@ pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
             query = query.filter(exists(
                 versions::table
                     .filter(versions::crate_id.eq(crates::id))
-                    .filter(versions::yanked.eq(false)),
+                    .filter(versions::yanked.eq(false))
+                    .filter(versions::staged_until.is_null()),
             ));
         }
 
@@ -244,6 +276,11 @@ pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
             supports_seek = false;
 
             query = query.order(crates::created_at.desc());
+        } else if sort == Some("health") {
+            // Custom sorting is not supported yet with seek.
+            supports_seek = false;
+
+            query = query.order(crates::health_score.desc().nulls_last());
         } else {
             query = query.then_order_by(crates::name.asc())
         }
@@ -327,19 +364,42 @@ pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
             .into_iter()
             .map(TopVersions::from_versions);
 
+        let crate_ids = crates.iter().map(|krate| krate.id).collect::<Vec<_>>();
+        let mut verifications: HashMap<i32, Vec<PublisherVerification>> = HashMap::new();
+        for (crate_id, verification) in
+            PublisherVerification::verified_for_crates(conn, &crate_ids)?
+        {
+            verifications
+                .entry(crate_id)
+                .or_default()
+                .push(verification);
+        }
+        let mut quarantine_statuses: HashMap<i32, String> =
+            CrateQuarantine::flagged_for_crates(conn, &crate_ids)?
+                .into_iter()
+                .collect();
+
         let crates = versions
             .zip(crates)
             .zip(perfect_matches)
             .zip(recent_downloads)
             .map(
                 |(((max_version, krate), perfect_match), recent_downloads)| {
-                    EncodableCrate::from_minimal(
+                    let verified_publisher = verifications
+                        .remove(&krate.id)
+                        .and_then(EncodableVerifiedPublisher::from_verifications);
+                    let quarantine_status = quarantine_statuses.remove(&krate.id);
+
+                    let mut encodable_crate = EncodableCrate::from_minimal(
                         krate,
                         Some(&max_version),
                         Some(vec![]),
                         perfect_match,
                         Some(recent_downloads),
-                    )
+                    );
+                    encodable_crate.verified_publisher = verified_publisher;
+                    encodable_crate.quarantine_status = quarantine_status;
+                    encodable_crate
                 },
             )
             .collect::<Vec<_>>();
@@ -357,3 +417,61 @@ pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
 }
 
 diesel::infix_operator!(Contains, "@>");
+
+/// The maximum number of crates that are returned by the `suggest` endpoint.
+const MAX_SUGGESTIONS: i64 = 10;
+
+/// Handles the `GET /crate_suggestions` route.
+///
+/// Returns the names of the crates that start with the `q` query parameter,
+/// for autocompletion in search fields. In contrast to the `search` endpoint
+/// this only uses the prefix index on the crate names, so it stays fast
+/// enough to be called on every keystroke. An exact match is always ranked
+/// first, followed by the most downloaded crates.
+pub async fn suggest(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let q = req
+            .query()
+            .get("q")
+            .map(|q| unicode::fold(q.replace('\u{0}', "").trim()))
+            .unwrap_or_default();
+
+        if q.is_empty() {
+            return Ok(Json(json!({ "crates": [] })));
+        }
+
+        // `-` and `_` are equivalent in crate names, and the LIKE wildcards
+        // have to be escaped to match them literally
+        let prefix = q
+            .replace('-', "_")
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+
+        let conn = &mut *app.db_read()?;
+        let crates: Vec<(String, i32)> = crates::table
+            .filter(canon_crate_name(crates::name).like(format!("{prefix}%")))
+            .filter(not(exists(
+                legal_holds::table
+                    .filter(legal_holds::crate_id.eq(crates::id))
+                    .filter(legal_holds::version_id.is_null())
+                    .filter(legal_holds::lifted_at.is_null()),
+            )))
+            .select((crates::name, crates::downloads))
+            .order((
+                Crate::with_name(&q).desc(),
+                crates::downloads.desc(),
+                crates::name.asc(),
+            ))
+            .limit(MAX_SUGGESTIONS)
+            .load(conn)?;
+
+        let crates = crates
+            .into_iter()
+            .map(|(name, downloads)| json!({ "name": name, "downloads": downloads }))
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({ "crates": crates })))
+    })
+    .await
+}
@@ -0,0 +1,117 @@
+//! Endpoints for subscribing to email notifications about a crate
+
+use crate::auth::AuthCheck;
+
+use crate::controllers::frontend_prelude::*;
+use crate::models::{Crate, CrateSubscription, NewCrateSubscription};
+use crate::schema::*;
+
+#[derive(Deserialize, Default)]
+struct SubscriptionPreferences {
+    new_versions: Option<bool>,
+    yanks: Option<bool>,
+    advisories: Option<bool>,
+}
+
+fn encode_subscription(subscription: Option<CrateSubscription>) -> Json<Value> {
+    let subscription = subscription.map(|subscription| {
+        json!({
+            "new_versions": subscription.new_versions,
+            "yanks": subscription.yanks,
+            "advisories": subscription.advisories,
+            "created_at": subscription.created_at,
+        })
+    });
+
+    Json(json!({ "subscription": subscription }))
+}
+
+/// Handles the `PUT /crates/:crate_id/subscription` route.
+///
+/// Subscribes the current user to notifications about the crate. The request body may contain
+/// the `new_versions`, `yanks` and `advisories` flags to choose which notifications are sent.
+/// Flags that are not given keep their current value, or default to `true` for new
+/// subscriptions.
+pub async fn subscribe(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: BytesRequest,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let preferences: SubscriptionPreferences = if req.body().is_empty() {
+            Default::default()
+        } else {
+            serde_json::from_slice(req.body())
+                .map_err(|e| bad_request(&format!("invalid subscription preferences: {e}")))?
+        };
+
+        let conn = &mut *app.db_write()?;
+        let user_id = AuthCheck::default().check(&req, conn)?.user_id();
+        let crate_id = Crate::by_name(&crate_name).select(crates::id).first(conn)?;
+
+        let existing = crate_subscriptions::table
+            .find((user_id, crate_id))
+            .first::<CrateSubscription>(conn)
+            .optional()?;
+
+        let subscription = NewCrateSubscription {
+            user_id,
+            crate_id,
+            new_versions: preferences
+                .new_versions
+                .or(existing.map(|s| s.new_versions))
+                .unwrap_or(true),
+            yanks: preferences
+                .yanks
+                .or(existing.map(|s| s.yanks))
+                .unwrap_or(true),
+            advisories: preferences
+                .advisories
+                .or(existing.map(|s| s.advisories))
+                .unwrap_or(true),
+        }
+        .upsert(conn)?;
+
+        Ok(encode_subscription(Some(subscription)))
+    })
+    .await
+}
+
+/// Handles the `DELETE /crates/:crate_id/subscription` route.
+pub async fn unsubscribe(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: Parts,
+) -> AppResult<Response> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_write()?;
+        let user_id = AuthCheck::default().check(&req, conn)?.user_id();
+        let crate_id: i32 = Crate::by_name(&crate_name).select(crates::id).first(conn)?;
+
+        diesel::delete(crate_subscriptions::table.find((user_id, crate_id))).execute(conn)?;
+
+        ok_true()
+    })
+    .await
+}
+
+/// Handles the `GET /crates/:crate_id/subscription` route.
+pub async fn subscription(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: Parts,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+        let crate_id: i32 = Crate::by_name(&crate_name).select(crates::id).first(conn)?;
+
+        let subscription = crate_subscriptions::table
+            .find((user_id, crate_id))
+            .first(conn)
+            .optional()?;
+
+        Ok(encode_subscription(subscription))
+    })
+    .await
+}
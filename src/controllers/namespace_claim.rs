@@ -0,0 +1,251 @@
+//! Endpoints for reserving crate name prefixes for GitHub organizations
+//!
+//! A verified claim on a prefix like `acme-*` ensures that only members of
+//! the owning organization can publish new crates matching the prefix, which
+//! protects against dependency confusion attacks.
+//!
+//! A prefix can only be claimed if all existing crates matching it are owned
+//! by members or teams of the organization, and if it does not overlap with
+//! the prefix of another verified claim.
+
+use super::frontend_prelude::*;
+
+use crate::app::App;
+use crate::auth::AuthCheck;
+use crate::domains::validate_domain;
+use crate::models::namespace_claim::{canonical_prefix, crates_with_prefix, prefix_pattern};
+use crate::models::{
+    is_gh_org_owner, Crate, NamespaceClaim, NewNamespaceClaim, Owner, VerificationMethod,
+};
+use crate::schema::namespace_claims;
+use crate::util::errors::{forbidden, NotFound};
+use crate::views::EncodableNamespaceClaim;
+use oauth2::AccessToken;
+use std::collections::HashMap;
+
+/// The maximum number of existing crates that a new claim can cover. Claims on
+/// more widely used prefixes have to be approved by the crates.io team.
+const MAX_EXISTING_CRATES: usize = 50;
+
+#[derive(Deserialize)]
+struct NewClaimRequest {
+    prefix: String,
+    github_org: String,
+    verification_method: VerificationMethod,
+    domain: Option<String>,
+}
+
+fn encode_claim(claim: NamespaceClaim) -> Json<Value> {
+    // The DNS record is only needed until the claim has been verified
+    let dns_record = match claim.dns_record_name() {
+        Some(name) if !claim.is_verified() => Some(json!({
+            "type": "TXT",
+            "name": name,
+            "value": claim.dns_record_value(),
+        })),
+        _ => None,
+    };
+
+    Json(json!({
+        "namespace_claim": EncodableNamespaceClaim::from(claim),
+        "dns_record": dns_record,
+    }))
+}
+
+fn already_claimed(claim: &NamespaceClaim) -> BoxedAppError {
+    cargo_err(&format_args!(
+        "the namespace `{}` has already been claimed by the `{}` GitHub organization",
+        claim.pattern(),
+        claim.github_org_login
+    ))
+}
+
+/// Handles the `GET /namespace_claims` route.
+///
+/// Returns all verified claims.
+pub async fn list(app: AppState) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_read()?;
+
+        let claims = namespace_claims::table
+            .filter(namespace_claims::verified_at.is_not_null())
+            .order(namespace_claims::prefix)
+            .load::<NamespaceClaim>(conn)?
+            .into_iter()
+            .map(EncodableNamespaceClaim::from)
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({ "namespace_claims": claims })))
+    })
+    .await
+}
+
+/// Handles the `POST /namespace_claims` route.
+///
+/// Only admins of the GitHub organization can claim a namespace for it. Claims using the
+/// `github` verification method are verified immediately, while claims using the `dns` method
+/// return a `TXT` record that has to be added to the domain before calling the
+/// `PUT /namespace_claims/:id/verify` route.
+pub async fn create(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let request: NewClaimRequest = serde_json::from_slice(req.body())
+            .map_err(|e| bad_request(&format!("invalid namespace claim: {e}")))?;
+
+        let prefix = canonical_prefix(&request.prefix)?;
+
+        let domain = match (request.verification_method, request.domain.as_deref()) {
+            (VerificationMethod::Dns, Some(domain)) => {
+                validate_domain(domain)?;
+                Some(domain.to_lowercase())
+            }
+            (VerificationMethod::Dns, None) => {
+                return Err(cargo_err("a domain is required for DNS verification"));
+            }
+            (VerificationMethod::GitHub, _) => None,
+        };
+
+        let conn = &mut *app.db_write()?;
+        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let user = auth.user();
+
+        if let Some(claim) = NamespaceClaim::find_overlapping(conn, &prefix)? {
+            return Err(already_claimed(&claim));
+        }
+
+        let token = AccessToken::new(user.gh_access_token.clone());
+        let org = app
+            .github
+            .org_by_name(&request.github_org, &token)
+            .map_err(|e| match e.is::<NotFound>() {
+                true => cargo_err(&format_args!(
+                    "could not find the GitHub organization `{}`",
+                    request.github_org
+                )),
+                false => e,
+            })?;
+
+        if !is_gh_org_owner(&app, org.id, user)? {
+            return Err(cargo_err(
+                "only admins of the GitHub organization can claim a namespace for it",
+            ));
+        }
+
+        let existing_crates = crates_with_prefix(conn, &prefix)?;
+        if existing_crates.len() > MAX_EXISTING_CRATES {
+            return Err(cargo_err(&format_args!(
+                "the namespace `{}` covers more than {MAX_EXISTING_CRATES} existing crates, \
+                 please contact help@crates.io to claim it",
+                prefix_pattern(&prefix)
+            )));
+        }
+
+        let mut members = HashMap::new();
+        for krate in &existing_crates {
+            if !is_owned_by_org(&app, conn, krate, org.id, &token, &mut members)? {
+                return Err(cargo_err(&format_args!(
+                    "the namespace `{}` can not be claimed, because the existing crate `{}` \
+                     is not owned by members or teams of the `{}` GitHub organization",
+                    prefix_pattern(&prefix),
+                    krate.name,
+                    request.github_org
+                )));
+            }
+        }
+
+        let github_org = request.github_org.to_lowercase();
+        let claim = NewNamespaceClaim::new(
+            &prefix,
+            org.id,
+            &github_org,
+            request.verification_method,
+            domain.as_deref(),
+            user.id,
+        )
+        .insert(conn)?;
+
+        Ok(encode_claim(claim))
+    })
+    .await
+}
+
+/// Returns `true` if one of the owners of the crate is a team of the GitHub organization, or an
+/// active member of it.
+///
+/// The memberships are looked up with the token of the admin creating the claim, and are cached
+/// in `members`, since the same users often own many of the crates.
+fn is_owned_by_org(
+    app: &App,
+    conn: &mut PgConnection,
+    krate: &Crate,
+    org_id: i32,
+    token: &AccessToken,
+    members: &mut HashMap<i32, bool>,
+) -> AppResult<bool> {
+    for owner in krate.owners(conn)? {
+        let owned = match owner {
+            Owner::Team(team) => team.org_id == Some(org_id),
+            Owner::User(user) => match members.get(&user.id) {
+                Some(is_member) => *is_member,
+                None => {
+                    let is_member = is_org_member(app, org_id, &user.gh_login, token)?;
+                    members.insert(user.id, is_member);
+                    is_member
+                }
+            },
+        };
+
+        if owned {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+fn is_org_member(app: &App, org_id: i32, login: &str, token: &AccessToken) -> AppResult<bool> {
+    match app.github.org_membership(org_id, login, token) {
+        Ok(membership) => Ok(membership.state == "active"),
+        Err(e) if e.is::<NotFound>() => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Handles the `PUT /namespace_claims/:id/verify` route.
+///
+/// Looks up the `TXT` record of a pending DNS claim, and marks the claim as verified if the
+/// record contains the verification token.
+pub async fn verify(app: AppState, Path(id): Path<i32>, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_write()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+        let claim: NamespaceClaim = namespace_claims::table.find(id).first(conn)?;
+        if claim.created_by != user_id {
+            return Err(forbidden());
+        }
+
+        if claim.is_verified() {
+            return Ok(encode_claim(claim));
+        }
+
+        if let Some(existing) = NamespaceClaim::find_overlapping(conn, &claim.prefix)? {
+            return Err(already_claimed(&existing));
+        }
+
+        let Some(record_name) = claim.dns_record_name() else {
+            return Err(cargo_err("only DNS claims need to be verified"));
+        };
+
+        let records = app.domains.txt_records(&record_name)?;
+        if !records.contains(&claim.dns_record_value()) {
+            return Err(cargo_err(&format_args!(
+                "could not find a TXT record on `{record_name}` with the value `{}`",
+                claim.dns_record_value()
+            )));
+        }
+
+        let claim = claim.verify(conn)?;
+        Ok(encode_claim(claim))
+    })
+    .await
+}
@@ -6,8 +6,11 @@ use axum::Json;
 ///
 /// The sha is contained within the `HEROKU_SLUG_COMMIT` environment variable.
 /// If `HEROKU_SLUG_COMMIT` is not set, returns `"unknown"`.
+///
+/// `read_only` is `true` if all database pools are read-only or the
+/// maintenance mode is enabled.
 pub async fn show_deployed_sha(state: AppState) -> impl IntoResponse {
-    let read_only = state.config.db.are_all_read_only();
+    let read_only = state.config.db.are_all_read_only() || state.maintenance_mode.is_enabled();
 
     let deployed_sha =
         dotenvy::var("HEROKU_SLUG_COMMIT").unwrap_or_else(|_| String::from("unknown"));
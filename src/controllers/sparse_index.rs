@@ -0,0 +1,87 @@
+//! Serve the sparse index of a private registry or an upstream proxy
+//!
+//! Public registries serve their index through a CDN, but the index of a private registry must
+//! only be available to authenticated clients, which is enforced by the `private_registry`
+//! middleware. In the upstream proxy mode, the index files of crates that do not exist locally
+//! are fetched from the upstream registry instead.
+
+use crate::controllers::frontend_prelude::*;
+use crate::util::errors::{internal, not_found};
+use hyper::body::Bytes;
+
+/// Handles the `GET /index/config.json` route.
+///
+/// Tells `cargo` where to download crates from and whether it needs to send
+/// its token for all requests to this registry.
+pub async fn config_json(state: AppState) -> Json<Value> {
+    let domain_name = &state.config.domain_name;
+
+    Json(json!({
+        "dl": format!("https://{domain_name}/api/v1/crates"),
+        "api": format!("https://{domain_name}"),
+        "auth-required": state.config.private_registry,
+    }))
+}
+
+/// Handles the `GET /index/*path` route.
+///
+/// Local crates always take precedence over crates of the same name in the
+/// upstream registry.
+pub async fn index_file(state: AppState, Path(path): Path<String>) -> AppResult<Response> {
+    let path = path.trim_start_matches('/');
+
+    // Only the canonical path of a crate is served, e.g. `se/rd/serde`
+    let name = path.rsplit('/').next().unwrap_or_default();
+    if name.is_empty() || crates_io_index::Repository::relative_index_file_for_url(name) != path {
+        return Err(not_found());
+    }
+
+    let bytes = match state.storage.download_index_file(name).await {
+        Ok(bytes) => bytes,
+        Err(object_store::Error::NotFound { .. }) if state.upstream.is_some() => {
+            upstream_index_file(&state, name).await?
+        }
+        Err(object_store::Error::NotFound { .. }) => return Err(not_found()),
+        Err(e) => return Err(internal(format!("failed to download index file: {e}"))),
+    };
+
+    let headers = [
+        (header::CONTENT_TYPE, "text/plain"),
+        (header::CACHE_CONTROL, "private, no-cache"),
+    ];
+    Ok((headers, bytes).into_response())
+}
+
+/// Fetches the index file of a crate from the upstream registry and caches
+/// it. If the upstream registry is unreachable, the cached copy is used.
+async fn upstream_index_file(state: &AppState, name: &str) -> AppResult<Bytes> {
+    let app = state.clone();
+    let crate_name = name.to_string();
+    let result = conduit_compat(move || match &app.upstream {
+        Some(upstream) => upstream.index_file(&crate_name),
+        None => Ok(None),
+    })
+    .await;
+
+    match result {
+        Ok(Some(content)) => {
+            if let Err(error) = state
+                .storage
+                .upload_upstream_index_file(name, content.clone())
+                .await
+            {
+                warn!(?error, "Failed to cache upstream index file of {name}");
+            }
+            Ok(content.into())
+        }
+        Ok(None) => Err(not_found()),
+        Err(error) => {
+            warn!(?error, "Failed to fetch upstream index file of {name}");
+            state
+                .storage
+                .download_upstream_index_file(name)
+                .await
+                .map_err(|_| error)
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Intake endpoint for takedown requests, e.g. DMCA notices
+//!
+//! Submitted requests are reviewed by the crates.io team, which can then
+//! place the affected crate or version under legal hold.
+
+use super::frontend_prelude::*;
+
+use crate::models::{Crate, NewTakedownRequest};
+use crate::publish_rate_limit::LimitedAction;
+use crate::util::HeaderMapExt;
+use crate::views::EncodableTakedownRequest;
+use lettre::Address;
+
+const MAX_NAME_LENGTH: usize = 200;
+const MAX_EMAIL_LENGTH: usize = 254;
+const MAX_DESCRIPTION_LENGTH: usize = 10_000;
+
+#[derive(Deserialize)]
+struct NewTakedownRequestParams {
+    #[serde(rename = "crate")]
+    krate: String,
+    version: Option<String>,
+    name: String,
+    email: String,
+    description: String,
+}
+
+/// Handles the `POST /api/v1/takedown_requests` route.
+///
+/// This endpoint does not require authentication, since takedown requests
+/// are usually submitted by people without a crates.io account. It is rate
+/// limited per IP address instead.
+pub async fn create(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let params: NewTakedownRequestParams = serde_json::from_slice(req.body())
+            .map_err(|e| bad_request(&format!("invalid takedown request: {e}")))?;
+
+        validate(&params)?;
+
+        let conn = &mut *app.db_write()?;
+
+        let ip_address = req.headers().get_str_or_default("x-real-ip");
+        app.config.takedown_request_rate_limit.check_ip_rate_limit(
+            ip_address,
+            LimitedAction::TakedownRequest,
+            conn,
+        )?;
+
+        let request = NewTakedownRequest {
+            crate_name: params.krate.trim(),
+            version: params.version.as_deref().map(str::trim),
+            complainant_name: params.name.trim(),
+            complainant_email: params.email.trim(),
+            description: params.description.trim(),
+        }
+        .insert(conn)?;
+
+        info!(
+            krate = %request.crate_name,
+            id = request.id,
+            "Received takedown request"
+        );
+
+        Ok(Json(
+            json!({ "takedown_request": EncodableTakedownRequest::from(request) }),
+        ))
+    })
+    .await
+}
+
+fn validate(params: &NewTakedownRequestParams) -> AppResult<()> {
+    let required_fields = [
+        ("crate", &params.krate),
+        ("name", &params.name),
+        ("email", &params.email),
+        ("description", &params.description),
+    ];
+    for (field, value) in required_fields {
+        if value.trim().is_empty() {
+            return Err(bad_request(&format!(
+                "the `{field}` field must not be empty"
+            )));
+        }
+    }
+
+    let krate = params.krate.trim();
+    if !Crate::valid_name(krate) {
+        return Err(bad_request(&format!("invalid crate name: `{krate}`")));
+    }
+
+    if let Some(version) = params.version.as_deref().map(str::trim) {
+        if semver::Version::parse(version).is_err() {
+            return Err(bad_request(&format!("invalid version: `{version}`")));
+        }
+    }
+
+    let email = params.email.trim();
+    if email.len() > MAX_EMAIL_LENGTH || email.parse::<Address>().is_err() {
+        return Err(bad_request("invalid email address"));
+    }
+
+    let lengths = [
+        ("name", &params.name, MAX_NAME_LENGTH),
+        ("description", &params.description, MAX_DESCRIPTION_LENGTH),
+    ];
+    for (field, value, max_length) in lengths {
+        if value.trim().chars().count() > max_length {
+            return Err(bad_request(&format!(
+                "the `{field}` field must not be longer than {max_length} characters"
+            )));
+        }
+    }
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use super::frontend_prelude::*;
 
-use crate::models::ApiToken;
+use crate::models::{ApiToken, NewUserNotification};
 use crate::schema::api_tokens;
 use crate::util::rfc3339;
 use crate::views::EncodableApiTokenWithToken;
@@ -13,8 +13,12 @@ use chrono::NaiveDateTime;
 use diesel::data_types::PgInterval;
 use diesel::dsl::{now, sql, IntervalDsl};
 use diesel::sql_types::{Interval, Timestamp};
+use ipnetwork::IpNetwork;
 use serde_json as json;
 
+/// The maximum number of CIDR blocks that a token can be restricted to.
+const MAX_ALLOWED_CIDRS: usize = 50;
+
 #[derive(Deserialize)]
 pub struct GetParams {
     expired_days: Option<i32>,
@@ -75,6 +79,7 @@ pub async fn new(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
             endpoint_scopes: Option<Vec<String>>,
             #[serde(default, with = "rfc3339::option")]
             expired_at: Option<NaiveDateTime>,
+            allowed_cidrs: Option<Vec<String>>,
         }
 
         /// The incoming serialization format for the `ApiToken` model.
@@ -134,6 +139,13 @@ pub async fn new(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
             .transpose()
             .map_err(|_err| bad_request("invalid endpoint scope"))?;
 
+        let allowed_cidrs = new
+            .api_token
+            .allowed_cidrs
+            .map(|cidrs| parse_allowed_cidrs(&cidrs))
+            .transpose()?
+            .filter(|cidrs| !cidrs.is_empty());
+
         let api_token = ApiToken::insert_with_scopes(
             conn,
             user.id,
@@ -141,7 +153,11 @@ pub async fn new(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
             crate_scopes,
             endpoint_scopes,
             new.api_token.expired_at,
+            allowed_cidrs,
         )?;
+
+        NewUserNotification::token_created(user.id, name).create(conn, &app.emails)?;
+
         let api_token = EncodableApiTokenWithToken::from(api_token);
 
         Ok(Json(json!({ "api_token": api_token })))
@@ -181,3 +197,27 @@ pub async fn revoke_current(app: AppState, req: Parts) -> AppResult<Response> {
     })
     .await
 }
+
+/// Parses the CIDR blocks that a new token is restricted to.
+///
+/// Host bits are cleared, since the database only accepts network addresses
+/// (e.g. `192.0.2.0/24` instead of `192.0.2.1/24`).
+fn parse_allowed_cidrs(cidrs: &[String]) -> AppResult<Vec<IpNetwork>> {
+    if cidrs.len() > MAX_ALLOWED_CIDRS {
+        return Err(bad_request(&format!(
+            "maximum allowed CIDR blocks per token is: {MAX_ALLOWED_CIDRS}"
+        )));
+    }
+
+    cidrs
+        .iter()
+        .map(|cidr| {
+            let network = cidr
+                .parse::<IpNetwork>()
+                .map_err(|_err| bad_request(&format!("invalid CIDR block: {cidr}")))?;
+
+            IpNetwork::new(network.network(), network.prefix())
+                .map_err(|_err| bad_request(&format!("invalid CIDR block: {cidr}")))
+        })
+        .collect()
+}
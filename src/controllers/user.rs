@@ -1,3 +1,9 @@
+pub mod data;
+pub mod identities;
 pub mod me;
+pub mod notifications;
 pub mod other;
+pub mod passkeys;
+pub mod password;
+pub mod publisher_verification;
 pub mod session;
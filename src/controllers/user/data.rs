@@ -0,0 +1,179 @@
+//! Endpoints for exporting the personal data of a user, and for deleting
+//! their account.
+
+use crate::controllers::frontend_prelude::*;
+
+use crate::auth::AuthCheck;
+use crate::background_jobs::Job;
+use crate::models::{AccountDeletion, UserDataExport};
+use crate::util::errors::{internal, not_found};
+use crate::views::{EncodableAccountDeletion, EncodableUserDataExport};
+use chrono::Utc;
+
+/// Completed exports are handed out again for this many days, after which a new
+/// export is created on request.
+const EXPORT_REUSE_DAYS: i64 = 7;
+
+/// Handles the `GET /me/export` route.
+///
+/// Returns the most recent export of the user if it is still pending or was
+/// completed recently. Otherwise a new export is requested, and the archive is
+/// created by a background job. Pending exports are answered with
+/// `202 Accepted`, and can be polled via this route until they are ready.
+pub async fn export(app: AppState, req: Parts) -> AppResult<Response> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_write()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+        let reuse_after = Utc::now().naive_utc() - chrono::Duration::days(EXPORT_REUSE_DAYS);
+        let latest = UserDataExport::latest_for_user(conn, user_id)?
+            .filter(|export| export.completed_at.map_or(true, |time| time > reuse_after));
+
+        let export = match latest {
+            Some(export) => export,
+            None => conn.transaction(|conn| {
+                let export = UserDataExport::request(conn, user_id)?;
+                Job::export_user_data(export.id).enqueue(conn)?;
+                Ok::<_, BoxedAppError>(export)
+            })?,
+        };
+
+        let status = if export.is_completed() {
+            StatusCode::OK
+        } else {
+            StatusCode::ACCEPTED
+        };
+
+        let export = EncodableUserDataExport::from(export);
+        Ok((status, Json(json!({ "export": export }))).into_response())
+    })
+    .await
+}
+
+/// Handles the `GET /me/export/download` route.
+pub async fn download_export(app: AppState, req: Parts) -> AppResult<Response> {
+    let export = conduit_compat({
+        let app = app.clone();
+        move || {
+            let conn = &mut *app.db_read_prefer_primary()?;
+            let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+            UserDataExport::latest_for_user(conn, user_id)?
+                .filter(UserDataExport::is_completed)
+                .ok_or_else(not_found)
+        }
+    })
+    .await?;
+
+    let bytes = app
+        .storage
+        .download_user_export(export.user_id, export.id)
+        .await
+        .map_err(|e| internal(format!("failed to download user data export: {e}")))?;
+
+    let date = export.requested_at.format("%Y-%m-%d");
+    let content_disposition = format!("attachment; filename=\"crates-io-export-{date}.tar.gz\"");
+    let headers = [
+        (header::CONTENT_TYPE, "application/gzip".to_string()),
+        (header::CONTENT_DISPOSITION, content_disposition),
+    ];
+
+    Ok((headers, bytes).into_response())
+}
+
+/// Handles the `DELETE /me` route.
+///
+/// The account is not deleted right away. Instead, a confirmation link is sent
+/// to the verified email address of the user.
+pub async fn request_deletion(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_write()?;
+        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let user = auth.user();
+
+        let email = user.verified_email(conn)?.ok_or_else(|| {
+            bad_request("A verified email address is required to delete the account.")
+        })?;
+
+        if let Some(deletion) = AccountDeletion::find(conn, user.id)? {
+            if deletion.confirmed_at.is_some() {
+                return Err(bad_request(
+                    "The deletion of the account is already scheduled.",
+                ));
+            }
+        }
+
+        // The email is only sent once the request has been stored. If sending
+        // fails, the user can request the deletion again, which replaces the
+        // confirmation token.
+        let deletion = AccountDeletion::request(conn, user.id)?;
+        app.emails.send_account_deletion_confirmation(
+            &email,
+            &user.gh_login,
+            &deletion.confirmation_token,
+        )?;
+
+        let deletion = EncodableAccountDeletion::from(deletion);
+        Ok(Json(json!({ "deletion": deletion })))
+    })
+    .await
+}
+
+/// Handles the `GET /me/deletion` route.
+pub async fn deletion_status(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+        let deletion = AccountDeletion::find(conn, user_id)?.map(EncodableAccountDeletion::from);
+        Ok(Json(json!({ "deletion": deletion })))
+    })
+    .await
+}
+
+/// Handles the `PUT /me/deletion/:token` route.
+///
+/// Schedules the deletion of the account for the end of the grace period.
+pub async fn confirm_deletion(
+    app: AppState,
+    Path(token): Path<String>,
+    req: Parts,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_write()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+        let deletion = AccountDeletion::find(conn, user_id)?
+            .filter(|deletion| deletion.confirmation_token == token)
+            .ok_or_else(|| bad_request("Account deletion belonging to token not found."))?;
+
+        if deletion.confirmed_at.is_some() {
+            return Err(bad_request(
+                "The deletion of the account is already scheduled.",
+            ));
+        }
+
+        let grace_period = chrono::Duration::from_std(app.config.account_deletion_grace_period)
+            .map_err(|e| internal(format!("invalid account deletion grace period: {e}")))?;
+        let deletion = deletion.confirm(conn, grace_period)?;
+
+        let deletion = EncodableAccountDeletion::from(deletion);
+        Ok(Json(json!({ "deletion": deletion })))
+    })
+    .await
+}
+
+/// Handles the `DELETE /me/deletion` route.
+pub async fn cancel_deletion(app: AppState, req: Parts) -> AppResult<Response> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_write()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+        if !AccountDeletion::cancel(conn, user_id)? {
+            return Err(not_found());
+        }
+
+        ok_true()
+    })
+    .await
+}
@@ -0,0 +1,95 @@
+use crate::controllers::frontend_prelude::*;
+
+use crate::auth::{AuthCheck, GitHubProvider, LOCAL_PROVIDER};
+use crate::models::{OAuthIdentity, UserSession};
+use crate::schema::{user_passwords, users};
+use crate::util::errors::not_found;
+use crate::views::EncodableOAuthIdentity;
+
+/// Handles the `GET /me/identities` route.
+///
+/// Returns the OAuth accounts that are linked to the account of the user,
+/// together with the names of all providers that can be linked.
+pub async fn list(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+        let identities = OAuthIdentity::for_user(conn, user_id)?
+            .into_iter()
+            .map(EncodableOAuthIdentity::from)
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({
+            "identities": identities,
+            "providers": app.oauth_providers.names(),
+        })))
+    })
+    .await
+}
+
+/// Handles the `DELETE /me/identities/:provider` route.
+///
+/// The last linked account can not be unlinked, since the user would no
+/// longer be able to log in. The other sessions of the user are revoked.
+pub async fn unlink(
+    app: AppState,
+    Path(provider): Path<String>,
+    req: Parts,
+) -> AppResult<Response> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_write()?;
+        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let user_id = auth.user_id();
+
+        conn.transaction(|conn| {
+            let identities = OAuthIdentity::for_user(conn, user_id)?;
+            let (unlinked, remaining): (Vec<_>, Vec<_>) = identities
+                .into_iter()
+                .partition(|identity| identity.provider == provider);
+
+            if unlinked.is_empty() {
+                return Err(not_found());
+            }
+
+            let Some(remaining) = remaining.first() else {
+                return Err(bad_request(
+                    "the only linked account can not be unlinked, \
+                     since you would no longer be able to log in",
+                ));
+            };
+
+            OAuthIdentity::unlink(conn, user_id, &provider)?;
+
+            if provider == LOCAL_PROVIDER {
+                diesel::delete(user_passwords::table.find(user_id)).execute(conn)?;
+            }
+
+            // Without a GitHub account, the user gets the login of another
+            // provider, so that the GitHub login can be used by a new account
+            if provider == GitHubProvider::NAME {
+                let login = OAuthIdentity::crates_io_login(
+                    conn,
+                    &remaining.provider,
+                    &remaining.provider_user_id,
+                    &remaining.login,
+                )?;
+
+                diesel::update(users::table.find(user_id))
+                    .set((
+                        users::gh_id.eq(-1),
+                        users::gh_login.eq(login),
+                        users::gh_access_token.eq(""),
+                    ))
+                    .execute(conn)?;
+            }
+
+            UserSession::revoke_all_for_user(conn, user_id, auth.session_id())?;
+
+            Ok(())
+        })?;
+
+        ok_true()
+    })
+    .await
+}
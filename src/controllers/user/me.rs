@@ -1,4 +1,6 @@
 use crate::auth::AuthCheck;
+use chrono::{NaiveDateTime, Utc};
+use diesel::sql_types::{BigInt, Integer, Text};
 use std::collections::HashMap;
 
 use crate::controllers::frontend_prelude::*;
@@ -7,9 +9,13 @@ use crate::controllers::helpers::*;
 
 use crate::controllers::helpers::pagination::{Paginated, PaginationOptions};
 use crate::models::{
-    CrateOwner, Email, Follow, NewEmail, OwnerKind, User, Version, VersionOwnerAction,
+    CrateOwner, Email, Follow, NewEmail, NewUserNotification, OwnerKind, User, Version,
+    VersionAction, VersionOwnerAction,
+};
+use crate::schema::{
+    crate_owner_invitations, crate_owners, crates, emails, follows, users, version_owner_actions,
+    versions,
 };
-use crate::schema::{crate_owners, crates, emails, follows, users, versions};
 use crate::views::{EncodableMe, EncodablePrivateUser, EncodableVersion, OwnedCrate};
 
 /// Handles the `GET /me` route.
@@ -66,6 +72,7 @@ pub async fn updates(app: AppState, req: Parts) -> AppResult<Json<Value>> {
             .inner_join(crates::table)
             .left_outer_join(users::table)
             .filter(crates::id.eq_any(followed_crates))
+            .filter(versions::staged_until.is_null())
             .order(versions::created_at.desc())
             .select((
                 versions::all_columns,
@@ -96,6 +103,128 @@ pub async fn updates(app: AppState, req: Parts) -> AppResult<Json<Value>> {
     .await
 }
 
+/// The maximum number of events returned by the `GET /me/stats` route.
+const MAX_STATS_EVENTS: i64 = 20;
+
+#[derive(QueryableByName)]
+struct OwnedCrateStats {
+    #[diesel(sql_type = Integer)]
+    id: i32,
+    #[diesel(sql_type = Text)]
+    name: String,
+    #[diesel(sql_type = Integer)]
+    downloads: i32,
+    #[diesel(sql_type = BigInt)]
+    downloads_7_days: i64,
+    #[diesel(sql_type = BigInt)]
+    downloads_30_days: i64,
+    #[diesel(sql_type = BigInt)]
+    downloads_90_days: i64,
+    #[diesel(sql_type = BigInt)]
+    dependents: i64,
+}
+
+/// Handles the `GET /me/stats` route.
+///
+/// Returns the download trends and dependents counts of all crates owned by the current user,
+/// their open ownership invitations and the most recent publish and yank events of their crates.
+pub async fn stats(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        use diesel::sql_query;
+
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+        let crates: Vec<OwnedCrateStats> = sql_query(include_str!("me_stats.sql"))
+            .bind::<Integer, _>(user_id)
+            .load(conn)?;
+
+        let crate_ids = crates.iter().map(|krate| krate.id).collect::<Vec<_>>();
+        let crates = crates
+            .into_iter()
+            .map(|krate| {
+                json!({
+                    "id": krate.id,
+                    "name": krate.name,
+                    "downloads": krate.downloads,
+                    "recent_downloads": {
+                        "7_days": krate.downloads_7_days,
+                        "30_days": krate.downloads_30_days,
+                        "90_days": krate.downloads_90_days,
+                    },
+                    "dependents": krate.dependents,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let expire_cutoff =
+            chrono::Duration::days(app.config.ownership_invitations_expiration_days as i64);
+        let invitations: Vec<(i32, String, String, NaiveDateTime)> = crate_owner_invitations::table
+            .inner_join(crates::table)
+            .inner_join(users::table.on(users::id.eq(crate_owner_invitations::invited_by_user_id)))
+            .filter(crate_owner_invitations::invited_user_id.eq(user_id))
+            .filter(
+                crate_owner_invitations::created_at.gt((Utc::now() - expire_cutoff).naive_utc()),
+            )
+            .select((
+                crates::id,
+                crates::name,
+                users::gh_login,
+                crate_owner_invitations::created_at,
+            ))
+            .order(crate_owner_invitations::created_at.desc())
+            .load(conn)?;
+
+        let invitations = invitations
+            .into_iter()
+            .map(|(crate_id, crate_name, invited_by, created_at)| {
+                json!({
+                    "crate_id": crate_id,
+                    "crate_name": crate_name,
+                    "invited_by_username": invited_by,
+                    "created_at": created_at,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let events: Vec<(String, String, VersionAction, String, NaiveDateTime)> =
+            version_owner_actions::table
+                .inner_join(versions::table.inner_join(crates::table))
+                .inner_join(users::table)
+                .filter(versions::crate_id.eq_any(&crate_ids))
+                .select((
+                    crates::name,
+                    versions::num,
+                    version_owner_actions::action,
+                    users::gh_login,
+                    version_owner_actions::time,
+                ))
+                .order(version_owner_actions::time.desc())
+                .limit(MAX_STATS_EVENTS)
+                .load(conn)?;
+
+        let events = events
+            .into_iter()
+            .map(|(crate_name, num, action, user, time)| {
+                json!({
+                    "crate": crate_name,
+                    "version": num,
+                    "action": String::from(action),
+                    "user": user,
+                    "time": time,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({
+            "crates": crates,
+            "crate_owner_invitations": invitations,
+            "events": events,
+        })))
+    })
+    .await
+}
+
 /// Handles the `PUT /users/:user_id` route.
 pub async fn update_user(
     state: AppState,
@@ -139,6 +268,11 @@ pub async fn update_user(
         }
 
         conn.transaction::<_, BoxedAppError, _>(|conn| {
+            let previous_email: Option<(String, bool)> = Email::belonging_to(user)
+                .select((emails::email, emails::verified))
+                .first(conn)
+                .optional()?;
+
             let new_email = NewEmail {
                 user_id: user.id,
                 email: user_email,
@@ -161,6 +295,19 @@ pub async fn update_user(
                 .emails
                 .send_user_confirm(user_email, &user.gh_login, &token);
 
+            let changed = previous_email
+                .as_ref()
+                .map_or(true, |(email, _)| email != user_email);
+            if changed {
+                let verified_email = previous_email
+                    .as_ref()
+                    .filter(|(_, verified)| *verified)
+                    .map(|(email, _)| email.as_str());
+
+                NewUserNotification::email_changed(user.id, verified_email, user_email)
+                    .create(conn, &state.emails)?;
+            }
+
             Ok(())
         })?;
 
@@ -0,0 +1,29 @@
+SELECT
+    crates.id,
+    crates.name,
+    crates.downloads,
+    COALESCE(SUM(version_downloads.downloads) FILTER (WHERE version_downloads.date > CURRENT_DATE - 7), 0) AS downloads_7_days,
+    COALESCE(SUM(version_downloads.downloads) FILTER (WHERE version_downloads.date > CURRENT_DATE - 30), 0) AS downloads_30_days,
+    COALESCE(SUM(version_downloads.downloads), 0) AS downloads_90_days,
+    (
+        -- Crates with at least one non-yanked version depending on this crate
+        SELECT COUNT(DISTINCT dependents.crate_id)
+        FROM dependencies
+        INNER JOIN versions dependents
+          ON dependents.id = dependencies.version_id
+        WHERE dependencies.crate_id = crates.id
+          AND NOT dependents.yanked
+    ) AS dependents
+FROM crates
+INNER JOIN crate_owners
+  ON crate_owners.crate_id = crates.id
+LEFT JOIN versions
+  ON versions.crate_id = crates.id
+LEFT JOIN version_downloads
+  ON version_downloads.version_id = versions.id
+ AND version_downloads.date > CURRENT_DATE - 90
+WHERE crate_owners.owner_id = $1
+  AND crate_owners.owner_kind = 0
+  AND NOT crate_owners.deleted
+GROUP BY crates.id
+ORDER BY crates.name
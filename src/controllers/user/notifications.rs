@@ -0,0 +1,37 @@
+use crate::controllers::frontend_prelude::*;
+
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::pagination::{Paginated, PaginationOptions};
+use crate::controllers::helpers::Paginate;
+use crate::models::UserNotification;
+use crate::schema::user_notifications;
+use crate::views::EncodableUserNotification;
+
+/// Handles the `GET /me/notifications` route.
+///
+/// Returns the security relevant events concerning the account of the user,
+/// newest first.
+pub async fn list(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+        let query = user_notifications::table
+            .filter(user_notifications::user_id.eq(user_id))
+            .order(user_notifications::id.desc())
+            .pages_pagination(PaginationOptions::builder().gather(&req)?);
+        let data: Paginated<UserNotification> = query.load(conn)?;
+        let more = data.next_page_params().is_some();
+
+        let notifications = data
+            .into_iter()
+            .map(EncodableUserNotification::from)
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({
+            "notifications": notifications,
+            "meta": { "more": more },
+        })))
+    })
+    .await
+}
@@ -0,0 +1,327 @@
+//! Endpoints for registering WebAuthn passkeys and signing in with them
+//!
+//! These are only available if local authentication is enabled. The state of
+//! the registration and authentication ceremonies is kept in the database
+//! between the two requests of each ceremony, see [`WebauthnCeremony`]. The
+//! session cookie only contains the token of the ceremony.
+
+use crate::controllers::frontend_prelude::*;
+
+use super::session::log_in;
+use crate::app::PASSKEY_TIMEOUT;
+use crate::auth::{AuthCheck, LOCAL_PROVIDER};
+use crate::middleware::session::SessionExtension;
+use crate::models::{OAuthIdentity, User, UserPasskey, WebauthnCeremony, WebauthnCeremonyKind};
+use crate::schema::{user_passkeys, users};
+use crate::sql::lower;
+use crate::util::errors::{internal, not_found};
+use crate::views::{EncodableMe, EncodableUserPasskey};
+use base64::{engine::general_purpose, Engine};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use secrecy::ExposeSecret;
+use webauthn_rs::prelude::{
+    PasskeyAuthentication, PasskeyRegistration, PublicKeyCredential, RegisterPublicKeyCredential,
+    Uuid,
+};
+use webauthn_rs::Webauthn;
+
+const REGISTRATION_STATE_KEY: &str = "passkey_registration";
+const AUTHENTICATION_STATE_KEY: &str = "passkey_authentication";
+const MAX_NAME_LENGTH: usize = 64;
+
+#[derive(Deserialize)]
+struct NewPasskeyRequest {
+    name: String,
+    credential: RegisterPublicKeyCredential,
+}
+
+#[derive(Deserialize)]
+struct BeginLoginRequest {
+    username: String,
+}
+
+#[derive(Deserialize)]
+struct FinishLoginRequest {
+    credential: PublicKeyCredential,
+}
+
+fn webauthn(app: &AppState) -> AppResult<&Webauthn> {
+    app.webauthn.as_ref().ok_or_else(not_found)
+}
+
+fn parse_body<T: serde::de::DeserializeOwned>(req: &BytesRequest) -> AppResult<T> {
+    serde_json::from_slice(req.body()).map_err(|e| bad_request(&format!("invalid request: {e}")))
+}
+
+/// The WebAuthn user handle of the user, which has to be stable so that
+/// authenticators can tell apart the passkeys of different users.
+fn user_handle(user: &User) -> Uuid {
+    Uuid::from_u64_pair(0, user.id as u64)
+}
+
+/// Returns options for `navigator.credentials.get()` that look like the
+/// options for a user with a single passkey. These are returned for unknown
+/// users and users without passkeys, so that the response does not tell
+/// whether an account exists. Signing in with them always fails.
+fn decoy_challenge(app: &AppState, username: &str) -> AppResult<Value> {
+    let rp_id = app
+        .config
+        .local_auth
+        .as_ref()
+        .map(|local_auth| local_auth.webauthn_rp_id.as_str())
+        .ok_or_else(not_found)?;
+
+    let mut challenge = [0; 32];
+    OsRng.fill_bytes(&mut challenge);
+
+    // The credential ID is the same for every request of the username, like
+    // the IDs of real passkeys
+    let signature = app.link_signer().sign(&format!("passkey-decoy:{username}"));
+    let credential_id = hex::decode(&signature[..32]).map_err(internal)?;
+
+    Ok(json!({
+        "publicKey": {
+            "challenge": general_purpose::URL_SAFE_NO_PAD.encode(challenge),
+            "timeout": PASSKEY_TIMEOUT.as_millis(),
+            "rpId": rp_id,
+            "allowCredentials": [{
+                "type": "public-key",
+                "id": general_purpose::URL_SAFE_NO_PAD.encode(credential_id),
+            }],
+            "userVerification": "required",
+        }
+    }))
+}
+
+/// Handles the `GET /me/passkeys` route.
+pub async fn list(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        webauthn(&app)?;
+
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+        let passkeys = UserPasskey::for_user(conn, user_id)?
+            .into_iter()
+            .map(EncodableUserPasskey::from)
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({ "passkeys": passkeys })))
+    })
+    .await
+}
+
+/// Handles the `POST /me/passkeys/challenge` route.
+///
+/// Starts the registration of a new passkey. The response contains the
+/// options for `navigator.credentials.create()`.
+pub async fn challenge(
+    app: AppState,
+    session: SessionExtension,
+    req: Parts,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let webauthn = webauthn(&app)?;
+
+        let conn = &mut *app.db_write()?;
+        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let user = auth.user();
+
+        let existing = UserPasskey::for_user(conn, user.id)?
+            .into_iter()
+            .map(|passkey| passkey.credential_id.into())
+            .collect::<Vec<_>>();
+
+        let display_name = user.name.as_deref().unwrap_or(&user.gh_login);
+        let (options, state) = webauthn
+            .start_passkey_registration(
+                user_handle(user),
+                &user.gh_login,
+                display_name,
+                Some(existing),
+            )
+            .map_err(|e| server_error(&format_args!("failed to start registration: {e}")))?;
+
+        let state = serde_json::to_value(&state)?;
+        let kind = WebauthnCeremonyKind::Registration;
+        let token = WebauthnCeremony::create(conn, kind, Some(user.id), Some(state))?;
+        session.insert(
+            REGISTRATION_STATE_KEY.to_string(),
+            token.expose_secret().to_string(),
+        );
+
+        Ok(Json(json!(options)))
+    })
+    .await
+}
+
+/// Handles the `POST /me/passkeys` route.
+///
+/// Finishes the registration of a passkey that was started with the
+/// `POST /me/passkeys/challenge` route.
+pub async fn create(
+    app: AppState,
+    session: SessionExtension,
+    req: BytesRequest,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let webauthn = webauthn(&app)?;
+
+        let request: NewPasskeyRequest = parse_body(&req)?;
+        let name = request.name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+            return Err(bad_request(&format_args!(
+                "passkey names must be between 1 and {MAX_NAME_LENGTH} characters long"
+            )));
+        }
+
+        let conn = &mut *app.db_write()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+        let ceremony = match session.remove(REGISTRATION_STATE_KEY) {
+            Some(token) => {
+                WebauthnCeremony::take(conn, WebauthnCeremonyKind::Registration, &token)?
+            }
+            None => None,
+        };
+
+        let state = ceremony
+            .filter(|ceremony| ceremony.user_id == Some(user_id))
+            .and_then(|ceremony| ceremony.state)
+            .and_then(|state| serde_json::from_value::<PasskeyRegistration>(state).ok())
+            .ok_or_else(|| bad_request("no passkey registration in progress"))?;
+
+        let passkey = webauthn
+            .finish_passkey_registration(&request.credential, &state)
+            .map_err(|e| bad_request(&format_args!("invalid passkey: {e}")))?;
+
+        let passkey = UserPasskey::create(conn, user_id, name, &passkey)?;
+        Ok(Json(
+            json!({ "passkey": EncodableUserPasskey::from(passkey) }),
+        ))
+    })
+    .await
+}
+
+/// Handles the `DELETE /me/passkeys/:id` route.
+pub async fn delete(app: AppState, Path(id): Path<i32>, req: Parts) -> AppResult<Response> {
+    conduit_compat(move || {
+        webauthn(&app)?;
+
+        let conn = &mut *app.db_write()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+        if !UserPasskey::delete(conn, user_id, id)? {
+            return Err(not_found());
+        }
+
+        ok_true()
+    })
+    .await
+}
+
+/// Handles the `POST /api/private/session/passkey/begin` route.
+///
+/// Starts signing in with a passkey. The `username` is either the username
+/// of a local account, or the crates.io login of the user. The response
+/// contains the options for `navigator.credentials.get()`, which look the
+/// same for users without passkeys and for unknown users.
+pub async fn begin_login(
+    app: AppState,
+    session: SessionExtension,
+    req: BytesRequest,
+) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let webauthn = webauthn(&app)?;
+
+        let request: BeginLoginRequest = parse_body(&req)?;
+        let username = request.username.to_lowercase();
+
+        let conn = &mut *app.db_write()?;
+        let user_id = match OAuthIdentity::find(conn, LOCAL_PROVIDER, &username)? {
+            Some(identity) => identity.user_id,
+            None => users::table
+                .inner_join(user_passkeys::table)
+                .filter(lower(users::gh_login).eq(&username))
+                .select(users::id)
+                .first(conn)
+                .optional()?
+                .unwrap_or_default(),
+        };
+
+        let passkeys = UserPasskey::for_user(conn, user_id)?
+            .iter()
+            .map(UserPasskey::passkey)
+            .collect::<AppResult<Vec<_>>>()?;
+
+        let kind = WebauthnCeremonyKind::Authentication;
+        let (options, token) = if passkeys.is_empty() {
+            let options = decoy_challenge(&app, &username)?;
+            let token = WebauthnCeremony::create(conn, kind, None, None)?;
+            (options, token)
+        } else {
+            let (options, state) = webauthn
+                .start_passkey_authentication(&passkeys)
+                .map_err(|e| server_error(&format_args!("failed to start authentication: {e}")))?;
+
+            let state = serde_json::to_value(&state)?;
+            let token = WebauthnCeremony::create(conn, kind, Some(user_id), Some(state))?;
+            (json!(options), token)
+        };
+
+        session.insert(
+            AUTHENTICATION_STATE_KEY.to_string(),
+            token.expose_secret().to_string(),
+        );
+
+        Ok(Json(options))
+    })
+    .await
+}
+
+/// Handles the `POST /api/private/session/passkey/finish` route.
+pub async fn finish_login(
+    app: AppState,
+    session: SessionExtension,
+    req: BytesRequest,
+) -> AppResult<Json<EncodableMe>> {
+    let app_clone = app.clone();
+
+    let req = conduit_compat(move || {
+        let webauthn = webauthn(&app)?;
+
+        let request: FinishLoginRequest = parse_body(&req)?;
+
+        let conn = &mut *app.db_write()?;
+
+        let token = session
+            .remove(AUTHENTICATION_STATE_KEY)
+            .ok_or_else(|| bad_request("no passkey sign in in progress"))?;
+
+        let ceremony = WebauthnCeremony::take(conn, WebauthnCeremonyKind::Authentication, &token)?
+            .ok_or_else(|| bad_request("no passkey sign in in progress"))?;
+
+        // Sign ins of unknown users have no state, and fail like a wrong passkey
+        let state = ceremony
+            .state
+            .and_then(|state| serde_json::from_value::<PasskeyAuthentication>(state).ok())
+            .ok_or_else(|| bad_request("invalid passkey"))?;
+
+        let result = webauthn
+            .finish_passkey_authentication(&request.credential, &state)
+            .map_err(|error| {
+                debug!(?error, "Invalid passkey");
+                bad_request("invalid passkey")
+            })?;
+
+        let user_id = UserPasskey::record_use(conn, &result)?;
+        let user = User::find(conn, user_id)?;
+        log_in(conn, &session, req.headers(), &user)?;
+
+        Ok(req.0.into_parts().0)
+    })
+    .await?;
+
+    super::me::me(app_clone, req).await
+}
@@ -0,0 +1,330 @@
+//! Endpoints for local authentication with passwords
+//!
+//! These are only available if local authentication is enabled, for
+//! deployments that can not use an external OAuth provider. Local accounts
+//! have an `OAuthIdentity` of the `local` provider, whose provider user ID is
+//! the lowercase username.
+
+use crate::controllers::frontend_prelude::*;
+
+use super::session::log_in;
+use crate::auth::{AuthCheck, LOCAL_PROVIDER};
+use crate::middleware::session::SessionExtension;
+use crate::models::{NewUser, OAuthIdentity, PasswordReset, User, UserPassword, UserSession};
+use crate::publish_rate_limit::LimitedAction;
+use crate::schema::{emails, oauth_identities};
+use crate::util::errors::not_found;
+use crate::util::HeaderMapExt;
+use crate::views::EncodableMe;
+use secrecy::ExposeSecret;
+
+const MIN_PASSWORD_LENGTH: usize = 12;
+/// Hashing is deliberately expensive, so the length of passwords is limited.
+const MAX_PASSWORD_LENGTH: usize = 256;
+const MAX_USERNAME_LENGTH: usize = 39;
+
+#[derive(Deserialize)]
+struct RegisterRequest {
+    username: String,
+    email: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+struct ChangePasswordRequest {
+    /// Required if the user already has a password.
+    current_password: Option<String>,
+    new_password: String,
+    /// Required to add a password to an account without a local identity.
+    username: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RecoverRequest {
+    email: String,
+}
+
+#[derive(Deserialize)]
+struct ResetPasswordRequest {
+    password: String,
+}
+
+fn ensure_enabled(app: &AppState) -> AppResult<()> {
+    match app.config.local_auth {
+        Some(_) => Ok(()),
+        None => Err(not_found()),
+    }
+}
+
+fn parse_body<T: serde::de::DeserializeOwned>(req: &BytesRequest) -> AppResult<T> {
+    serde_json::from_slice(req.body()).map_err(|e| bad_request(&format!("invalid request: {e}")))
+}
+
+fn validate_username(username: &str) -> AppResult<()> {
+    let valid_chars = username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if username.is_empty()
+        || username.len() > MAX_USERNAME_LENGTH
+        || !valid_chars
+        || username.starts_with('-')
+    {
+        return Err(bad_request(&format_args!(
+            "invalid username `{username}`, usernames may only contain up to \
+             {MAX_USERNAME_LENGTH} alphanumeric characters, `-` and `_`"
+        )));
+    }
+
+    Ok(())
+}
+
+fn validate_password(password: &str) -> AppResult<()> {
+    let length = password.chars().count();
+    if length < MIN_PASSWORD_LENGTH {
+        return Err(bad_request(&format_args!(
+            "passwords must be at least {MIN_PASSWORD_LENGTH} characters long"
+        )));
+    }
+    if length > MAX_PASSWORD_LENGTH {
+        return Err(bad_request(&format_args!(
+            "passwords must be at most {MAX_PASSWORD_LENGTH} characters long"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Links a local identity with the given username to the user.
+fn link_local_identity(conn: &mut PgConnection, user_id: i32, username: &str) -> AppResult<()> {
+    validate_username(username)?;
+
+    let provider_user_id = username.to_lowercase();
+    if OAuthIdentity::find(conn, LOCAL_PROVIDER, &provider_user_id)?.is_some() {
+        return Err(bad_request(&format_args!(
+            "the username `{username}` is already taken"
+        )));
+    }
+
+    OAuthIdentity::link(conn, user_id, LOCAL_PROVIDER, &provider_user_id, username)?;
+    Ok(())
+}
+
+/// Handles the `POST /api/private/users` route.
+///
+/// Creates a new local account and logs the user in. The email address has
+/// to be verified before it can be used for account recovery.
+pub async fn register(
+    app: AppState,
+    session: SessionExtension,
+    req: BytesRequest,
+) -> AppResult<Json<EncodableMe>> {
+    let app_clone = app.clone();
+
+    let req = conduit_compat(move || {
+        ensure_enabled(&app)?;
+
+        let request: RegisterRequest = parse_body(&req)?;
+        validate_username(&request.username)?;
+        validate_password(&request.password)?;
+
+        let email = request.email.trim();
+        if email.is_empty() {
+            return Err(bad_request("empty email rejected"));
+        }
+
+        let conn = &mut *app.db_write()?;
+        let user = conn.transaction(|conn| {
+            let username = &request.username;
+            let provider_user_id = username.to_lowercase();
+            let login =
+                OAuthIdentity::crates_io_login(conn, LOCAL_PROVIDER, &provider_user_id, username)?;
+
+            // Local users have no GitHub account, like users of other providers
+            let user = NewUser::new(-1, &login, None, None, "").create_or_update(
+                Some(email),
+                &app.emails,
+                conn,
+            )?;
+
+            link_local_identity(conn, user.id, username)?;
+            UserPassword::set(conn, user.id, &request.password)?;
+
+            Ok::<_, BoxedAppError>(user)
+        })?;
+
+        log_in(conn, &session, req.headers(), &user)?;
+
+        Ok(req.0.into_parts().0)
+    })
+    .await?;
+
+    super::me::me(app_clone, req).await
+}
+
+/// Handles the `POST /api/private/session/password` route.
+///
+/// Login attempts are rate limited per IP address and per account, see
+/// [`crate::config::Server::login_rate_limit`].
+pub async fn login(
+    app: AppState,
+    session: SessionExtension,
+    req: BytesRequest,
+) -> AppResult<Json<EncodableMe>> {
+    let app_clone = app.clone();
+
+    let req = conduit_compat(move || {
+        ensure_enabled(&app)?;
+
+        let request: LoginRequest = parse_body(&req)?;
+
+        let conn = &mut *app.db_write()?;
+
+        let rate_limit = &app.config.login_rate_limit;
+        let ip_address = req.headers().get_str_or_default("x-real-ip");
+        rate_limit.check_ip_rate_limit(ip_address, LimitedAction::Login, conn)?;
+
+        let provider_user_id = request.username.to_lowercase();
+        let Some(identity) = OAuthIdentity::find(conn, LOCAL_PROVIDER, &provider_user_id)? else {
+            // Unknown usernames take as long as wrong passwords
+            UserPassword::verify_dummy(&request.password);
+            return Err(bad_request("invalid username or password"));
+        };
+
+        rate_limit.check_rate_limit(identity.user_id, LimitedAction::Login, conn)?;
+
+        if !UserPassword::verify(conn, identity.user_id, &request.password)? {
+            return Err(bad_request("invalid username or password"));
+        }
+        let user_id = identity.user_id;
+
+        let user = User::find(conn, user_id)?;
+        log_in(conn, &session, req.headers(), &user)?;
+
+        Ok(req.0.into_parts().0)
+    })
+    .await?;
+
+    super::me::me(app_clone, req).await
+}
+
+/// Handles the `PUT /me/password` route.
+///
+/// Changes the password of the user, or adds a password to an account that
+/// was created with an OAuth provider. All other sessions of the user are
+/// revoked.
+pub async fn change(app: AppState, req: BytesRequest) -> AppResult<Response> {
+    conduit_compat(move || {
+        ensure_enabled(&app)?;
+
+        let request: ChangePasswordRequest = parse_body(&req)?;
+        validate_password(&request.new_password)?;
+
+        let conn = &mut *app.db_write()?;
+        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let user_id = auth.user_id();
+
+        conn.transaction(|conn| {
+            let has_identity = OAuthIdentity::for_user(conn, user_id)?
+                .iter()
+                .any(|identity| identity.provider == LOCAL_PROVIDER);
+
+            if !has_identity {
+                let Some(username) = &request.username else {
+                    return Err(bad_request("a username is required to add a password"));
+                };
+                link_local_identity(conn, user_id, username)?;
+            } else if UserPassword::exists(conn, user_id)? {
+                let current_password = request.current_password.as_deref().unwrap_or_default();
+                if !UserPassword::verify(conn, user_id, current_password)? {
+                    return Err(bad_request("the current password is incorrect"));
+                }
+            }
+
+            UserPassword::set(conn, user_id, &request.new_password)?;
+            UserSession::revoke_all_for_user(conn, user_id, auth.session_id())?;
+
+            Ok(())
+        })?;
+
+        ok_true()
+    })
+    .await
+}
+
+/// Handles the `POST /api/private/password_resets` route.
+///
+/// Sends a password reset link to the email address, if it is the verified
+/// email address of a local account. The response is the same either way, so
+/// that it can not be used to find out which email addresses are in use.
+pub async fn recover(app: AppState, req: BytesRequest) -> AppResult<Response> {
+    conduit_compat(move || {
+        ensure_enabled(&app)?;
+
+        let request: RecoverRequest = parse_body(&req)?;
+        let email = request.email.trim();
+
+        let conn = &mut *app.db_write()?;
+        let account: Option<(i32, String, String)> = emails::table
+            .inner_join(oauth_identities::table.on(oauth_identities::user_id.eq(emails::user_id)))
+            .filter(emails::email.eq(email))
+            .filter(emails::verified.eq(true))
+            .filter(oauth_identities::provider.eq(LOCAL_PROVIDER))
+            .select((emails::user_id, emails::email, oauth_identities::login))
+            .first(conn)
+            .optional()?;
+
+        if let Some((user_id, email, username)) = account {
+            let token = PasswordReset::create(conn, user_id)?;
+            if let Err(error) =
+                app.emails
+                    .send_password_reset(&email, &username, token.expose_secret())
+            {
+                warn!(%user_id, ?error, "Failed to send password reset email");
+            }
+        }
+
+        ok_true()
+    })
+    .await
+}
+
+/// Handles the `PUT /api/private/password_resets/:token` route.
+///
+/// Sets a new password and revokes all sessions of the user.
+pub async fn reset(
+    app: AppState,
+    Path(token): Path<String>,
+    req: BytesRequest,
+) -> AppResult<Response> {
+    conduit_compat(move || {
+        ensure_enabled(&app)?;
+
+        let request: ResetPasswordRequest = parse_body(&req)?;
+
+        let conn = &mut *app.db_write()?;
+        let Some(password_reset) = PasswordReset::find_valid(conn, &token)? else {
+            return Err(bad_request("invalid or expired password reset link"));
+        };
+
+        validate_password(&request.password)?;
+
+        let user_id = password_reset.user_id;
+        conn.transaction(|conn| {
+            UserPassword::set(conn, user_id, &request.password)?;
+            password_reset.mark_used(conn)?;
+            UserSession::revoke_all_for_user(conn, user_id, None)?;
+
+            Ok::<_, BoxedAppError>(())
+        })?;
+
+        ok_true()
+    })
+    .await
+}
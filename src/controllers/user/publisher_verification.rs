@@ -0,0 +1,212 @@
+//! Endpoints for verifying control of domains and GitHub organizations
+//!
+//! Verified domains and organizations of the crate owners are shown in the
+//! `verified_publisher` block of the crate API, which makes it harder to
+//! impersonate well-known publishers.
+
+use crate::controllers::frontend_prelude::*;
+
+use crate::auth::AuthCheck;
+use crate::domains::{self, validate_domain, VERIFICATION_WELL_KNOWN_FILE};
+use crate::models::{
+    is_gh_org_owner, NewPublisherVerification, PublisherVerification, PublisherVerificationMethod,
+};
+use crate::schema::publisher_verifications;
+use crate::util::errors::{not_found, NotFound};
+use crate::views::EncodablePublisherVerification;
+use oauth2::AccessToken;
+
+#[derive(Deserialize)]
+struct NewVerificationRequest {
+    method: PublisherVerificationMethod,
+    domain: Option<String>,
+    github_org: Option<String>,
+}
+
+/// Returns the instructions for proving control of the domain of a pending
+/// verification.
+fn instructions(verification: &PublisherVerification) -> Option<Value> {
+    let domain = verification.domain.as_deref()?;
+    if verification.is_verified() {
+        return None;
+    }
+
+    let value = verification.verification_value();
+    match verification.method.as_str() {
+        "dns" => Some(json!({
+            "type": "TXT",
+            "name": domains::verification_record_name(domain),
+            "value": value,
+        })),
+        "well_known" => Some(json!({
+            "url": format!("https://{domain}/.well-known/{VERIFICATION_WELL_KNOWN_FILE}"),
+            "value": value,
+        })),
+        _ => None,
+    }
+}
+
+fn encode_verification(verification: PublisherVerification) -> Value {
+    let instructions = instructions(&verification);
+    let mut json = json!(EncodablePublisherVerification::from(verification));
+    json["instructions"] = json!(instructions);
+    json
+}
+
+/// Handles the `GET /me/publisher_verifications` route.
+pub async fn list(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+        let verifications = publisher_verifications::table
+            .filter(publisher_verifications::user_id.eq(user_id))
+            .order(publisher_verifications::id)
+            .load::<PublisherVerification>(conn)?
+            .into_iter()
+            .map(encode_verification)
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({ "publisher_verifications": verifications })))
+    })
+    .await
+}
+
+/// Handles the `POST /me/publisher_verifications` route.
+///
+/// GitHub organizations are verified immediately if the user is an admin of the organization.
+/// Domains have to be verified via the `PUT /me/publisher_verifications/:id/verify` route once
+/// the returned `instructions` have been followed.
+pub async fn create(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let request: NewVerificationRequest = serde_json::from_slice(req.body())
+            .map_err(|e| bad_request(&format!("invalid publisher verification: {e}")))?;
+
+        let conn = &mut *app.db_write()?;
+        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let user = auth.user();
+
+        let verification = match request.method {
+            PublisherVerificationMethod::GitHub => {
+                let Some(org_name) = request.github_org else {
+                    return Err(cargo_err("a GitHub organization is required"));
+                };
+
+                let token = AccessToken::new(user.gh_access_token.clone());
+                let org = app
+                    .github
+                    .org_by_name(&org_name, &token)
+                    .map_err(|e| match e.is::<NotFound>() {
+                        true => cargo_err(&format_args!(
+                            "could not find the GitHub organization `{org_name}`"
+                        )),
+                        false => e,
+                    })?;
+
+                if !is_gh_org_owner(&app, org.id, user)? {
+                    return Err(cargo_err(
+                        "only admins of the GitHub organization can verify it",
+                    ));
+                }
+
+                let org_login = org_name.to_lowercase();
+                NewPublisherVerification::github_org(user.id, org.id, &org_login).insert(conn)?
+            }
+            method => {
+                let Some(domain) = request.domain else {
+                    return Err(cargo_err("a domain is required"));
+                };
+                validate_domain(&domain)?;
+
+                let domain = domain.to_lowercase();
+                NewPublisherVerification::domain(user.id, method, &domain).insert(conn)?
+            }
+        };
+
+        let verification = verification.ok_or_else(|| {
+            cargo_err("a verification for this domain or GitHub organization already exists")
+        })?;
+
+        Ok(Json(
+            json!({ "publisher_verification": encode_verification(verification) }),
+        ))
+    })
+    .await
+}
+
+/// Handles the `PUT /me/publisher_verifications/:id/verify` route.
+///
+/// Checks the `TXT` record or `.well-known` file of a pending domain verification, and marks
+/// the verification as verified if it contains the verification value.
+pub async fn verify(app: AppState, Path(id): Path<i32>, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_write()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+        let verification: PublisherVerification = publisher_verifications::table
+            .find(id)
+            .filter(publisher_verifications::user_id.eq(user_id))
+            .first(conn)
+            .optional()?
+            .ok_or_else(not_found)?;
+
+        if verification.is_verified() {
+            return Ok(Json(
+                json!({ "publisher_verification": encode_verification(verification) }),
+            ));
+        }
+
+        // Only domain verifications can be pending
+        let Some(domain) = verification.domain.as_deref() else {
+            return Err(not_found());
+        };
+
+        let value = verification.verification_value();
+        let found = match verification.method.as_str() {
+            "dns" => {
+                let name = domains::verification_record_name(domain);
+                app.domains.txt_records(&name)?.contains(&value)
+            }
+            _ => app
+                .domains
+                .well_known_file(domain, VERIFICATION_WELL_KNOWN_FILE)?
+                .map_or(false, |content| {
+                    content.lines().any(|line| line.trim() == value)
+                }),
+        };
+
+        if !found {
+            return Err(cargo_err(&format_args!(
+                "could not find the verification value `{value}` for `{domain}`"
+            )));
+        }
+
+        let verification = verification.verify(conn)?;
+        Ok(Json(
+            json!({ "publisher_verification": encode_verification(verification) }),
+        ))
+    })
+    .await
+}
+
+/// Handles the `DELETE /me/publisher_verifications/:id` route.
+pub async fn delete(app: AppState, Path(id): Path<i32>, req: Parts) -> AppResult<Response> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_write()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+        let deleted = diesel::delete(
+            publisher_verifications::table
+                .find(id)
+                .filter(publisher_verifications::user_id.eq(user_id)),
+        )
+        .execute(conn)?;
+
+        if deleted == 0 {
+            return Err(not_found());
+        }
+
+        ok_true()
+    })
+    .await
+}
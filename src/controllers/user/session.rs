@@ -1,23 +1,31 @@
 use crate::controllers::frontend_prelude::*;
 
-use oauth2::reqwest::http_client;
-use oauth2::{AuthorizationCode, Scope, TokenResponse};
+use http::HeaderMap;
+use oauth2::{AccessToken, AuthorizationCode};
 
+use crate::auth::{ensure_not_locked, AuthCheck, GitHubProvider, ProviderUser};
 use crate::email::Emails;
-use crate::github::GithubUser;
 use crate::middleware::session::SessionExtension;
-use crate::models::{NewUser, User};
+use crate::models::{NewUser, OAuthIdentity, User, UserSession};
 use crate::schema::users;
-use crate::util::errors::ReadOnlyMode;
-use crate::views::EncodableMe;
+use crate::util::errors::{not_found, ReadOnlyMode};
+use crate::util::HeaderMapExt;
+use crate::views::{EncodableMe, EncodableUserSession};
+use secrecy::ExposeSecret;
 
 /// Handles the `GET /api/private/session/begin` route.
 ///
-/// This route will return an authorization URL for the GitHub OAuth flow including the crates.io
-/// `client_id` and a randomly generated `state` secret.
+/// This route will return an authorization URL for the OAuth flow of the requested provider
+/// including the crates.io `client_id` and a randomly generated `state` secret.
 ///
 /// see <https://developer.github.com/v3/oauth/#redirect-users-to-request-github-access>
 ///
+/// ## Query Parameters
+///
+/// - `provider` – the OAuth provider to sign in with, e.g. `gitlab`. Defaults to `github`.
+/// - `link` – if `true`, the account at the provider is linked to the account of the user
+///   that is currently logged in, instead of logging in with it.
+///
 /// ## Response Body Example
 ///
 /// ```json
@@ -26,32 +34,53 @@ use crate::views::EncodableMe;
 ///     "url": "https://github.com/login/oauth/authorize?client_id=...&state=...&scope=read%3Aorg"
 /// }
 /// ```
-pub async fn begin(app: AppState, session: SessionExtension) -> Json<Value> {
-    let (url, state) = app
-        .github_oauth
-        .authorize_url(oauth2::CsrfToken::new_random)
-        .add_scope(Scope::new("read:org".to_string()))
-        .url();
+pub async fn begin(app: AppState, session: SessionExtension, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let query = req.query();
+        let provider_name = query
+            .get("provider")
+            .map(String::as_str)
+            .unwrap_or(GitHubProvider::NAME);
 
-    let state = state.secret().to_string();
-    session.insert("github_oauth_state".to_string(), state.clone());
+        let provider = app.oauth_providers.get(provider_name).ok_or_else(|| {
+            bad_request(&format_args!("unknown OAuth provider `{provider_name}`"))
+        })?;
+
+        if query.get("link").map(String::as_str) == Some("true") {
+            let conn = &mut *app.db_read_prefer_primary()?;
+            let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+            session.insert("oauth_link_user_id".to_string(), user_id.to_string());
+        } else {
+            session.remove("oauth_link_user_id");
+        }
 
-    Json(json!({ "url": url.to_string(), "state": state }))
+        let (url, state) = provider.authorize_url();
+
+        let state = state.secret().to_string();
+        session.insert("oauth_state".to_string(), state.clone());
+        session.insert("oauth_provider".to_string(), provider.name().to_string());
+
+        Ok(Json(json!({ "url": url.to_string(), "state": state })))
+    })
+    .await
 }
 
 /// Handles the `GET /api/private/session/authorize` route.
 ///
-/// This route is called from the GitHub API OAuth flow after the user accepted or rejected
-/// the data access permissions. It will check the `state` parameter and then call the GitHub API
-/// to exchange the temporary `code` for an API token. The API token is returned together with
-/// the corresponding user information.
+/// This route is called from the OAuth flow after the user accepted or rejected
+/// the data access permissions. It will check the `state` parameter and then call the API
+/// of the provider to exchange the temporary `code` for an API token. The API token is
+/// returned together with the corresponding user information.
+///
+/// If the OAuth flow was started in link mode, the account at the provider is linked to the
+/// account of the current user instead of logging in with it.
 ///
 /// see <https://developer.github.com/v3/oauth/#github-redirects-back-to-your-site>
 ///
 /// ## Query Parameters
 ///
-/// - `code` – temporary code received from the GitHub API  **(Required)**
-/// - `state` – state parameter received from the GitHub API  **(Required)**
+/// - `code` – temporary code received from the OAuth provider  **(Required)**
+/// - `state` – state parameter received from the OAuth provider  **(Required)**
 ///
 /// ## Response Body Example
 ///
@@ -83,29 +112,49 @@ pub async fn authorize(
         // Make sure that the state we just got matches the session state that we
         // should have issued earlier.
         {
-            let session_state = session.remove("github_oauth_state");
+            let session_state = session.remove("oauth_state");
             let session_state = session_state.as_deref();
             if Some(&state[..]) != session_state {
                 return Err(bad_request("invalid state parameter"));
             }
         }
 
-        // Fetch the access token from GitHub using the code we just got
-        let code = AuthorizationCode::new(code);
-        let token = app
-            .github_oauth
-            .exchange_code(code)
-            .request(http_client)
-            .map_err(|err| err.chain(server_error("Error obtaining token")))?;
-        let token = token.access_token();
-
-        // Fetch the user info from GitHub using the access token we just got and create a user record
-        let ghuser = app.github.current_user(token)?;
-        let user =
-            save_user_to_database(&ghuser, token.secret(), &app.emails, &mut *app.db_write()?)?;
+        let provider_name = session.remove("oauth_provider");
+        let provider_name = provider_name.as_deref().unwrap_or(GitHubProvider::NAME);
+        let provider = app
+            .oauth_providers
+            .get(provider_name)
+            .ok_or_else(|| bad_request("invalid state parameter"))?;
 
-        // Log in by setting a cookie and the middleware authentication
-        session.insert("user_id".to_string(), user.id.to_string());
+        let link_user_id = session.remove("oauth_link_user_id");
+
+        // Fetch the access token from the provider using the code we just got
+        let token = provider.exchange_code(AuthorizationCode::new(code))?;
+
+        // Fetch the user info from the provider using the access token we just got
+        let provider_user = provider.current_user(&app, &token)?;
+        let conn = &mut *app.db_write()?;
+
+        if let Some(link_user_id) = link_user_id {
+            let auth = AuthCheck::only_cookie().check(&req, conn)?;
+            if auth.user_id().to_string() != link_user_id {
+                return Err(bad_request("invalid state parameter"));
+            }
+
+            let current_session_id = auth.session_id();
+            link_identity(
+                auth.user(),
+                current_session_id,
+                provider.name(),
+                &provider_user,
+                &token,
+                conn,
+            )?;
+            return Ok(req);
+        }
+
+        let user = find_or_create_user(provider.name(), &provider_user, &token, &app.emails, conn)?;
+        log_in(conn, &session, &req.headers, &user)?;
 
         Ok(req)
     })
@@ -114,14 +163,178 @@ pub async fn authorize(
     super::me::me(app_clone, req).await
 }
 
+/// Logs the user in by creating a `UserSession` and storing its token in the
+/// session cookie.
+pub(super) fn log_in(
+    conn: &mut PgConnection,
+    session: &SessionExtension,
+    headers: &HeaderMap,
+    user: &User,
+) -> AppResult<()> {
+    ensure_not_locked(user)?;
+
+    let user_agent = headers.get_str_or_default(header::USER_AGENT);
+    let ip_address = headers.get("x-real-ip").and_then(|ip| ip.to_str().ok());
+    let user_session = UserSession::create(conn, user.id, user_agent, ip_address)?;
+
+    session.insert("user_id".to_string(), user.id.to_string());
+    session.insert(
+        "session_token".to_string(),
+        user_session.plaintext.expose_secret().to_string(),
+    );
+
+    Ok(())
+}
+
+/// Finds the user that the account at the provider is linked to, or creates
+/// a new user for it.
+fn find_or_create_user(
+    provider: &str,
+    provider_user: &ProviderUser,
+    access_token: &AccessToken,
+    emails: &Emails,
+    conn: &mut PgConnection,
+) -> AppResult<User> {
+    // GitHub users are still identified by their `gh_id`, and their details
+    // are updated on every login
+    if provider == GitHubProvider::NAME {
+        let gh_id = parse_github_id(provider_user)?;
+        let user =
+            save_user_to_database(gh_id, provider_user, access_token.secret(), emails, conn)?;
+
+        OAuthIdentity::upsert(
+            conn,
+            user.id,
+            provider,
+            &provider_user.id,
+            &provider_user.login,
+        )
+        .map_err(Into::into)
+        .or_else(|e: BoxedAppError| {
+            // If we're in read only mode, the identity was linked on an earlier login
+            if e.is::<ReadOnlyMode>() {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        })?;
+
+        return Ok(user);
+    }
+
+    if let Some(identity) = OAuthIdentity::find(conn, provider, &provider_user.id)? {
+        identity.update_login(conn, &provider_user.login)?;
+        return Ok(User::find(conn, identity.user_id)?);
+    }
+
+    let user = conn.transaction(|conn| {
+        let login = OAuthIdentity::crates_io_login(
+            conn,
+            provider,
+            &provider_user.id,
+            &provider_user.login,
+        )?;
+
+        // Users without a GitHub account have a `gh_id` of `-1`, which is
+        // excluded from the unique index
+        let user = NewUser::new(
+            -1,
+            &login,
+            provider_user.name.as_deref(),
+            provider_user.avatar_url.as_deref(),
+            "",
+        )
+        .create_or_update(provider_user.email.as_deref(), emails, conn)?;
+
+        OAuthIdentity::link(
+            conn,
+            user.id,
+            provider,
+            &provider_user.id,
+            &provider_user.login,
+        )?;
+
+        Ok::<_, BoxedAppError>(user)
+    })?;
+
+    Ok(user)
+}
+
+/// Links the account at the provider to the account of the user, and revokes
+/// the other sessions of the user, since the new account can be used to log in.
+fn link_identity(
+    user: &User,
+    current_session_id: Option<i32>,
+    provider: &str,
+    provider_user: &ProviderUser,
+    access_token: &AccessToken,
+    conn: &mut PgConnection,
+) -> AppResult<()> {
+    conn.transaction(|conn| {
+        if let Some(identity) = OAuthIdentity::find(conn, provider, &provider_user.id)? {
+            if identity.user_id != user.id {
+                let detail = format!(
+                    "this {provider} account is already linked to another crates.io account"
+                );
+                return Err(bad_request(&detail));
+            }
+
+            identity.update_login(conn, &provider_user.login)?;
+            return Ok(());
+        }
+
+        let identities = OAuthIdentity::for_user(conn, user.id)?;
+        if identities
+            .iter()
+            .any(|identity| identity.provider == provider)
+        {
+            let detail =
+                format!("your crates.io account is already linked to a {provider} account");
+            return Err(bad_request(&detail));
+        }
+
+        OAuthIdentity::link(
+            conn,
+            user.id,
+            provider,
+            &provider_user.id,
+            &provider_user.login,
+        )?;
+
+        // Linking a GitHub account makes the user a regular GitHub user, which
+        // is required for team ownership and takes over the GitHub login
+        if provider == GitHubProvider::NAME {
+            diesel::update(users::table.find(user.id))
+                .set((
+                    users::gh_id.eq(parse_github_id(provider_user)?),
+                    users::gh_login.eq(&provider_user.login),
+                    users::gh_access_token.eq(access_token.secret()),
+                ))
+                .execute(conn)?;
+        }
+
+        UserSession::revoke_all_for_user(conn, user.id, current_session_id)?;
+
+        Ok(())
+    })
+}
+
+fn parse_github_id(provider_user: &ProviderUser) -> AppResult<i32> {
+    provider_user
+        .id
+        .parse()
+        .map_err(|_| server_error("invalid GitHub user ID"))
+}
+
 fn save_user_to_database(
-    user: &GithubUser,
+    gh_id: i32,
+    user: &ProviderUser,
     access_token: &str,
     emails: &Emails,
     conn: &mut PgConnection,
 ) -> AppResult<User> {
     NewUser::new(
-        user.id,
+        gh_id,
         &user.login,
         user.name.as_deref(),
         user.avatar_url.as_deref(),
@@ -134,7 +347,7 @@ fn save_user_to_database(
         // just look for an existing user
         if e.is::<ReadOnlyMode>() {
             users::table
-                .filter(users::gh_id.eq(user.id))
+                .filter(users::gh_id.eq(gh_id))
                 .first(conn)
                 .optional()?
                 .ok_or(e)
@@ -145,9 +358,73 @@ fn save_user_to_database(
 }
 
 /// Handles the `DELETE /api/private/session` route.
-pub async fn logout(session: SessionExtension) -> Json<bool> {
-    session.remove("user_id");
-    Json(true)
+pub async fn logout(app: AppState, session: SessionExtension) -> AppResult<Json<bool>> {
+    let user_id = session.remove("user_id");
+    let session_token = session.remove("session_token");
+
+    if let (Some(user_id), Some(session_token)) = (user_id, session_token) {
+        conduit_compat(move || {
+            let Ok(user_id) = user_id.parse() else {
+                return Ok(());
+            };
+
+            let conn = &mut *app.db_write()?;
+            if let Some(user_session) = UserSession::find_active(conn, user_id, &session_token)? {
+                UserSession::revoke(conn, user_id, user_session.id)?;
+            }
+
+            Ok::<_, BoxedAppError>(())
+        })
+        .await?;
+    }
+
+    Ok(Json(true))
+}
+
+/// Handles the `GET /me/sessions` route.
+pub async fn list(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let current_session_id = auth.session_id();
+
+        let sessions = UserSession::active_for_user(conn, auth.user_id())?
+            .into_iter()
+            .map(|session| EncodableUserSession::from(session, current_session_id))
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({ "sessions": sessions })))
+    })
+    .await
+}
+
+/// Handles the `DELETE /me/sessions` route.
+///
+/// Revokes all sessions of the user, except for the one making the request.
+pub async fn revoke_others(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_write()?;
+        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+
+        let revoked = UserSession::revoke_all_for_user(conn, auth.user_id(), auth.session_id())?;
+        Ok(Json(json!({ "revoked": revoked })))
+    })
+    .await
+}
+
+/// Handles the `DELETE /me/sessions/:id` route.
+pub async fn revoke(app: AppState, Path(id): Path<i32>, req: Parts) -> AppResult<Response> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_write()?;
+        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+
+        if !UserSession::revoke(conn, user_id, id)? {
+            return Err(not_found());
+        }
+
+        ok_true()
+    })
+    .await
 }
 
 #[cfg(test)]
@@ -159,14 +436,14 @@ mod tests {
     fn gh_user_with_invalid_email_doesnt_fail() {
         let emails = Emails::new_in_memory();
         let conn = &mut pg_connection_no_transaction();
-        let gh_user = GithubUser {
+        let gh_user = ProviderUser {
             email: Some("String.Format(\"{0}.{1}@live.com\", FirstName, LastName)".into()),
             name: Some("My Name".into()),
             login: "github_user".into(),
-            id: -1,
+            id: "-1".into(),
             avatar_url: None,
         };
-        let result = save_user_to_database(&gh_user, "arbitrary_token", &emails, conn);
+        let result = save_user_to_database(-1, &gh_user, "arbitrary_token", &emails, conn);
 
         assert!(
             result.is_ok(),
@@ -1,6 +1,8 @@
+pub mod dependency_graph;
 pub mod deprecated;
 pub mod downloads;
 pub mod metadata;
+pub mod promote;
 pub mod yank;
 
 use super::prelude::*;
@@ -0,0 +1,87 @@
+//! Endpoint exporting the transitive dependency graph of a version
+
+use crate::controllers::frontend_prelude::*;
+
+use crate::models::{DependencyGraph, DependencyGraphNode};
+use std::sync::Arc;
+
+use super::version_and_crate;
+
+/// The depth used if no `depth` query parameter is given.
+const DEFAULT_DEPTH: u32 = 3;
+
+/// The maximum supported `depth` query parameter.
+const MAX_DEPTH: u32 = 10;
+
+/// The maximum number of versions in a dependency graph.
+const MAX_NODES: usize = 500;
+
+/// Handles the `GET /crates/:crate_id/:version/dependency_graph` route.
+///
+/// Resolves the normal and build dependencies of the version up to `depth`
+/// levels deep, using the highest non-yanked version matching each
+/// requirement. The graph is returned as JSON, or in the DOT language of
+/// Graphviz if `format=dot` is requested. Resolved graphs are cached for
+/// `dependency_graph_cache_ttl`.
+pub async fn dependency_graph(
+    state: AppState,
+    Path((crate_name, version)): Path<(String, String)>,
+    req: Parts,
+) -> AppResult<Response> {
+    conduit_compat(move || {
+        if semver::Version::parse(&version).is_err() {
+            return Err(cargo_err(&format_args!("invalid semver: {version}")));
+        }
+
+        let query = req.query();
+        let depth = match query.get("depth") {
+            Some(depth) => depth
+                .parse::<u32>()
+                .ok()
+                .filter(|depth| (1..=MAX_DEPTH).contains(depth))
+                .ok_or_else(|| {
+                    bad_request(&format!("depth must be a number between 1 and {MAX_DEPTH}"))
+                })?,
+            None => DEFAULT_DEPTH,
+        };
+
+        let dot = match query.get("format").map(String::as_str) {
+            None | Some("json") => false,
+            Some("dot") => true,
+            Some(format) => return Err(bad_request(&format!("unsupported format: {format}"))),
+        };
+
+        let conn = &mut *state.db_read()?;
+        let (version, krate) = version_and_crate(conn, &crate_name, &version)?;
+
+        let key = (version.id, depth);
+        let graph = match state.dependency_graph_cache.get(&key) {
+            Some(graph) => graph,
+            None => {
+                let root = DependencyGraphNode {
+                    id: version.id,
+                    crate_name: krate.name,
+                    version: version.num,
+                };
+                let graph = Arc::new(DependencyGraph::resolve(conn, root, depth, MAX_NODES)?);
+                state
+                    .dependency_graph_cache
+                    .blocking()
+                    .insert(key, Arc::clone(&graph));
+                graph
+            }
+        };
+
+        if dot {
+            let headers = [(header::CONTENT_TYPE, "text/vnd.graphviz; charset=utf-8")];
+            return Ok((headers, graph.to_dot()).into_response());
+        }
+
+        Ok(Json(json!({
+            "dependency_graph": *graph,
+            "meta": { "depth": depth },
+        }))
+        .into_response())
+    })
+    .await
+}
@@ -31,6 +31,7 @@ pub async fn index(app: AppState, req: Parts) -> AppResult<Json<Value>> {
                 users::all_columns.nullable(),
             ))
             .filter(versions::id.eq_any(ids))
+            .filter(versions::staged_until.is_null())
             .load(conn)?;
         let versions = versions_and_publishers
             .iter()
@@ -58,6 +59,7 @@ pub async fn show_by_id(state: AppState, Path(id): Path<i32>) -> AppResult<Json<
         let conn = &mut *state.db_read()?;
         let (version, krate, published_by): (Version, Crate, Option<User>) = versions::table
             .find(id)
+            .filter(versions::staged_until.is_null())
             .inner_join(crates::table)
             .left_outer_join(users::table)
             .select((
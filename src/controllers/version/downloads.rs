@@ -3,36 +3,65 @@
 //! Crate level functionality is located in `krate::downloads`.
 
 use super::version_and_crate;
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::DownloadSeries;
 use crate::controllers::prelude::*;
 use crate::db::PoolError;
 use crate::middleware::log_request::RequestLogExt;
-use crate::models::{Crate, VersionDownload};
+use crate::models::token::EndpointScope;
+use crate::models::{Crate, LegalHold, Rights, VersionDownload, VersionObject};
 use crate::schema::*;
+use crate::uploaders::Uploader;
+use crate::upstream::{index_checksum, is_valid_crate_file};
+use crate::util::errors::{forbidden, internal, not_found};
+use crate::util::range_requests::serve_bytes;
+use crate::util::rfc3339;
 use crate::views::EncodableVersionDownload;
-use chrono::{Duration, NaiveDate, Utc};
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use diesel::dsl::exists;
+use hex::ToHex;
+use hyper::body::Bytes;
+use sha2::{Digest, Sha256};
 
 /// Handles the `GET /crates/:crate_id/:version/download` route.
 /// This returns a URL to the location where the crate is stored.
+///
+/// Staged versions are not publicly available yet, so their crate files are
+/// served directly, and only to the owners of the crate. These responses
+/// support `Range` requests, so that interrupted downloads can be resumed.
+/// The same applies to all crate files in private registry mode.
+///
+/// Clients can ask for the zstd-compressed copy of the crate file with the
+/// `format=zstd` query parameter or a `zstd` entry in the `Accept-Encoding`
+/// header. The regular gzip-compressed file is used if no such copy exists.
 pub async fn download(
     app: AppState,
     Path((crate_name, version)): Path<(String, String)>,
     req: Parts,
 ) -> AppResult<Response> {
     let wants_json = req.wants_json();
+    let wants_zstd = wants_zstd(&req);
+    let request_headers = req.headers.clone();
+
+    // The in-memory copy of the legal holds is checked first, so that holds
+    // also apply to cached version IDs and to unconditional redirects
+    if let Some(hold) = app.legal_holds.get(&crate_name, &version) {
+        return Ok(legal_hold_response(&crate_name, &version, hold));
+    }
 
     let cache_key = (crate_name.to_string(), version.to_string());
 
     let cache_result =
         info_span!("cache.read", ?cache_key).in_scope(|| app.version_id_cacher.get(&cache_key));
 
-    let (crate_name, version) = if let Some(version_id) = cache_result {
+    let download = if let Some(version_id) = cache_result {
         app.instance_metrics.version_id_cache_hits.inc();
 
         // The increment does not happen instantly, but it's deferred to be executed in a batch
         // along with other downloads. See crate::downloads_counter for the implementation.
         app.downloads_counter.increment(version_id);
 
-        (crate_name, version)
+        Download::Public(crate_name, version)
     } else {
         app.instance_metrics.version_id_cache_misses.inc();
 
@@ -57,7 +86,7 @@ pub async fn download(
 
                 // Returns the crate name as stored in the database, or an error if we could
                 // not load the version ID from the database.
-                let (version_id, canonical_crate_name) = app
+                let result = app
                     .instance_metrics
                     .downloads_select_query_execution_time
                     .observe_closure_duration(|| {
@@ -65,13 +94,51 @@ pub async fn download(
                             || {
                                 versions
                                     .inner_join(crates::table)
-                                    .select((id, crates::name))
+                                    .select((
+                                        id,
+                                        crates::id,
+                                        crates::name,
+                                        staged_until.is_not_null(),
+                                    ))
                                     .filter(Crate::with_name(&crate_name))
                                     .filter(num.eq(&version))
-                                    .first::<(i32, String)>(&mut *conn)
+                                    .first::<(i32, i32, String, bool)>(&mut *conn)
                             },
                         )
-                    })?;
+                    });
+
+                // Versions of crates that do not exist locally are fetched from the upstream
+                // registry, if the upstream proxy mode is enabled. Local crates always take
+                // precedence, so that their names can not be hijacked upstream.
+                let (version_id, krate_id, canonical_crate_name, staged) = match result {
+                    Err(diesel::result::Error::NotFound)
+                        if app.upstream.is_some()
+                            && !diesel::select(exists(
+                                crates::table.filter(Crate::with_name(&crate_name)),
+                            ))
+                            .get_result::<bool>(&mut *conn)? =>
+                    {
+                        return Ok(Download::Upstream(crate_name, version));
+                    }
+                    result => result?,
+                };
+
+                // Versions under legal hold are neither counted nor cached, so
+                // that the hold is checked again for every download request.
+                if let Some(hold) = LegalHold::active_for_version(&mut conn, krate_id, version_id)?
+                {
+                    return Ok(Download::LegalHold(canonical_crate_name, version, hold));
+                }
+
+                if staged {
+                    // Staged versions are only available to the owners of the crate, and
+                    // their downloads are neither counted nor cached. Authentication might
+                    // need to write to the database, so the read connection is swapped out.
+                    drop(conn);
+                    let conn = &mut *app.db_write()?;
+                    verify_staged_version_access(&app, &req, conn, &canonical_crate_name)?;
+                    return Ok(Download::Staged(canonical_crate_name, version));
+                }
 
                 // The increment does not happen instantly, but it's deferred to be executed in a batch
                 // along with other downloads. See crate::downloads_counter for the implementation.
@@ -83,7 +150,7 @@ pub async fn download(
                         .inc();
                     req.request_log().add("bot", "dl");
 
-                    Ok((canonical_crate_name, version))
+                    Ok(Download::Public(canonical_crate_name, version))
                 } else {
                     // The version_id is only cached if the provided crate name was canonical.
                     // Non-canonical requests fallback to the "slow" path with a DB query, but
@@ -94,7 +161,7 @@ pub async fn download(
                             .insert(cache_key, version_id)
                     });
 
-                    Ok((crate_name, version))
+                    Ok(Download::Public(crate_name, version))
                 }
             } else {
                 // The download endpoint is the most critical route in the whole crates.io application,
@@ -119,13 +186,84 @@ pub async fn download(
 
                 req.request_log().add("unconditional_redirect", "true");
 
-                Ok((crate_name, version))
+                Ok(Download::Public(crate_name, version))
             }
         })
         .await?
     };
 
-    let redirect_url = app.config.uploader().crate_location(&crate_name, &version);
+    let (crate_name, version) = match download {
+        Download::Public(crate_name, version) => (crate_name, version),
+        Download::LegalHold(crate_name, version, hold) => {
+            return Ok(legal_hold_response(&crate_name, &version, hold));
+        }
+        Download::Upstream(crate_name, version) => {
+            let bytes = upstream_crate_file(&app, &crate_name, &version).await?;
+            let content_type = header::HeaderValue::from_static("application/gzip");
+            return Ok(serve_bytes(&request_headers, content_type, bytes));
+        }
+        Download::Staged(crate_name, version) => {
+            let bytes = app
+                .storage
+                .download_staged_crate_file(&crate_name, &version)
+                .await
+                .map_err(|e| internal(format!("failed to download staged crate: {e}")))?;
+
+            let content_type = header::HeaderValue::from_static("application/gzip");
+            return Ok(serve_bytes(&request_headers, content_type, bytes));
+        }
+    };
+
+    let signed_downloads = app
+        .config
+        .signed_downloads
+        .as_ref()
+        .filter(|_| app.config.private_registry);
+
+    // The crate files of a private registry must not be reachable without
+    // authentication, so they are served directly instead of redirecting,
+    // unless they can be redirected to a short-lived signed URL.
+    if app.config.private_registry && signed_downloads.is_none() {
+        let bytes = match crate_object_hash(&app, &crate_name, &version).await {
+            Some(hash) => app.storage.download_crate_object(&hash).await,
+            None => app.storage.download_crate_file(&crate_name, &version).await,
+        }
+        .map_err(|e| match e {
+            object_store::Error::NotFound { .. } => not_found(),
+            e => internal(format!("failed to download crate: {e}")),
+        })?;
+
+        let content_type = header::HeaderValue::from_static("application/gzip");
+        return Ok(serve_bytes(&request_headers, content_type, bytes));
+    }
+
+    // Clients are redirected to the replica of the crate files in their
+    // region, if the CDN or GeoDNS tells us about it.
+    let region = app
+        .config
+        .region_hint_header
+        .as_ref()
+        .and_then(|name| request_headers.get(name))
+        .and_then(|value| value.to_str().ok());
+
+    let uploader = app.config.uploader();
+    let redirect_url = if let Some(signing) = signed_downloads {
+        let path = if wants_zstd && has_zstd_crate_file(&app, &crate_name, &version).await {
+            Uploader::zstd_crate_path(&crate_name, &version)
+        } else if let Some(hash) = crate_object_hash(&app, &crate_name, &version).await {
+            Uploader::crate_object_path(&hash)
+        } else {
+            Uploader::crate_path(&crate_name, &version)
+        };
+        uploader.signed_location(&path, signing, region)
+    } else if wants_zstd && has_zstd_crate_file(&app, &crate_name, &version).await {
+        uploader.zstd_crate_location(&crate_name, &version, region)
+    } else if let Some(hash) = crate_object_hash(&app, &crate_name, &version).await {
+        uploader.crate_location_by_hash(&hash, region)
+    } else {
+        uploader.crate_location(&crate_name, &version, region)
+    };
+
     if wants_json {
         Ok(Json(json!({ "url": redirect_url })).into_response())
     } else {
@@ -133,7 +271,282 @@ pub async fn download(
     }
 }
 
+/// Handles the `GET /api/private/signed_downloads/*path` route.
+///
+/// This serves the crate files of a private registry to anyone with a signed
+/// URL from the download endpoint, as long as it has not expired. It is only
+/// used with the local uploaders, since S3 checks presigned URLs itself.
+pub async fn signed_download(
+    app: AppState,
+    Path(path): Path<String>,
+    req: Parts,
+) -> AppResult<Response> {
+    let signing = app
+        .config
+        .signed_downloads
+        .as_ref()
+        .filter(|_| app.config.private_registry)
+        .ok_or_else(not_found)?;
+
+    let path = path.trim_start_matches('/');
+    let query = req.query();
+    let expires = query
+        .get("expires")
+        .and_then(|expires| expires.parse::<i64>().ok());
+    let (Some(expires), Some(key), Some(signature)) =
+        (expires, query.get("key"), query.get("signature"))
+    else {
+        return Err(forbidden());
+    };
+
+    if !signing.verify(path, expires, key, signature) || expires < Utc::now().timestamp() {
+        return Err(forbidden());
+    }
+
+    let bytes = app.storage.download_file(path).await.map_err(|e| match e {
+        object_store::Error::NotFound { .. } => not_found(),
+        e => internal(format!("failed to download crate: {e}")),
+    })?;
+
+    let content_type = if path.ends_with(".tar.zst") {
+        header::HeaderValue::from_static("application/zstd")
+    } else {
+        header::HeaderValue::from_static("application/gzip")
+    };
+    Ok(serve_bytes(&req.headers, content_type, bytes))
+}
+
+enum Download {
+    /// The crate file is publicly available and the client is redirected to it.
+    Public(String, String),
+    /// The crate file of a staged version is served directly to an owner of the crate.
+    Staged(String, String),
+    /// The crate file is not available because of a legal hold.
+    LegalHold(String, String, LegalHold),
+    /// The crate does not exist locally, so its crate file is served from the
+    /// upstream registry.
+    Upstream(String, String),
+}
+
+/// Returns the crate file of a version in the upstream registry, which is
+/// cached after it was fetched for the first time.
+///
+/// The crate file is only cached if its checksum matches the one in the
+/// upstream index.
+async fn upstream_crate_file(app: &AppState, crate_name: &str, version: &str) -> AppResult<Bytes> {
+    // The name and version are used in the paths of the cache
+    if !is_valid_crate_file(crate_name, version) {
+        return Err(not_found());
+    }
+
+    match app
+        .storage
+        .download_upstream_crate_file(crate_name, version)
+        .await
+    {
+        Ok(bytes) => return Ok(bytes),
+        Err(object_store::Error::NotFound { .. }) => {}
+        Err(e) => return Err(internal(format!("failed to download crate: {e}"))),
+    }
+
+    let bytes = {
+        let app = app.clone();
+        let crate_name = crate_name.to_string();
+        let version = version.to_string();
+        conduit_compat(move || {
+            let Some(upstream) = &app.upstream else {
+                return Err(not_found());
+            };
+
+            let checksum = upstream
+                .index_file(&crate_name)?
+                .and_then(|index_file| index_checksum(&index_file, &version))
+                .ok_or_else(not_found)?;
+
+            let bytes = upstream
+                .crate_file(&crate_name, &version)?
+                .ok_or_else(not_found)?;
+
+            let actual_checksum: String = Sha256::digest(&bytes).encode_hex();
+            if actual_checksum != checksum {
+                let message =
+                    format!("checksum mismatch of upstream crate file of {crate_name}@{version}");
+                return Err(internal(message));
+            }
+
+            Ok(bytes)
+        })
+        .await?
+    };
+
+    if let Err(error) = app
+        .storage
+        .upload_upstream_crate_file(crate_name, version, bytes.clone())
+        .await
+    {
+        warn!(
+            ?error,
+            "Failed to cache upstream crate file of {crate_name}@{version}"
+        );
+    }
+
+    Ok(bytes)
+}
+
+/// Returns a `451 Unavailable For Legal Reasons` response with a
+/// machine-readable notice about the legal hold.
+///
+/// If the legal notice is public, it is also linked with the `blocked-by`
+/// relation, as described in RFC 7725.
+fn legal_hold_response(crate_name: &str, version: &str, hold: LegalHold) -> Response {
+    #[derive(Serialize)]
+    struct LegalHoldNotice<'a> {
+        #[serde(rename = "crate")]
+        krate: &'a str,
+        version: &'a str,
+        /// Either `crate` or `version`, depending on what is under legal hold
+        scope: &'static str,
+        reason: &'a str,
+        notice_url: Option<&'a str>,
+        #[serde(with = "rfc3339")]
+        created_at: NaiveDateTime,
+    }
+
+    let notice = LegalHoldNotice {
+        krate: crate_name,
+        version,
+        scope: if hold.version_id.is_some() {
+            "version"
+        } else {
+            "crate"
+        },
+        reason: &hold.reason,
+        notice_url: hold.notice_url.as_deref(),
+        created_at: hold.created_at,
+    };
+
+    let detail = format!("{crate_name}@{version} is unavailable for legal reasons");
+    let body = json!({ "errors": [{ "detail": detail }], "legal_hold": notice });
+
+    let mut response = (StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS, Json(body)).into_response();
+    if let Some(link) = hold
+        .notice_url
+        .as_deref()
+        .and_then(|url| header::HeaderValue::try_from(format!("<{url}>; rel=\"blocked-by\"")).ok())
+    {
+        response.headers_mut().insert(header::LINK, link);
+    }
+    response
+}
+
+/// Returns whether the client asked for the zstd-compressed crate file, either
+/// explicitly via `format=zstd`, or via the `Accept-Encoding` header.
+fn wants_zstd(req: &Parts) -> bool {
+    if let Some(format) = req.query().get("format") {
+        return format == "zstd";
+    }
+
+    req.headers
+        .get_all(header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|encoding| {
+            let mut parts = encoding.split(';').map(str::trim);
+            let name = parts.next().unwrap_or_default();
+            let rejected = parts.any(|param| param.replace(' ', "") == "q=0");
+            name.eq_ignore_ascii_case("zstd") && !rejected
+        })
+}
+
+/// Checks whether a zstd-compressed copy of the crate file has been created.
+///
+/// Errors are only logged, since the gzip-compressed crate file can always
+/// be used as a fallback.
+async fn has_zstd_crate_file(app: &AppState, crate_name: &str, version: &str) -> bool {
+    let app = app.clone();
+    let crate_name = crate_name.to_string();
+    let version = version.to_string();
+
+    let result = conduit_compat(move || {
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let checksum = versions::table
+            .inner_join(crates::table)
+            .filter(crates::name.eq(&crate_name))
+            .filter(versions::num.eq(&version))
+            .select(versions::checksum_zstd)
+            .first::<Option<String>>(conn)
+            .optional()?;
+
+        Ok::<_, BoxedAppError>(checksum.flatten().is_some())
+    })
+    .await;
+
+    result.unwrap_or_else(|error| {
+        warn!(%error, "Failed to look up zstd crate file");
+        false
+    })
+}
+
+/// Looks up the hash of the crate file, if it is stored in the
+/// content-addressed layout and that layout is enabled.
+///
+/// Errors are only logged, and the legacy location is used instead. The
+/// backfill job does not delete the crate files in the legacy layout, so
+/// only crate files published after enabling the layout are missing there.
+async fn crate_object_hash(app: &AppState, crate_name: &str, version: &str) -> Option<String> {
+    if !app.config.content_addressed_storage {
+        return None;
+    }
+
+    let app = app.clone();
+    let crate_name = crate_name.to_string();
+    let version = version.to_string();
+
+    let result = conduit_compat(move || {
+        let conn = &mut *app.db_read_prefer_primary()?;
+        Ok::<_, BoxedAppError>(VersionObject::hash_for_version(
+            conn,
+            &crate_name,
+            &version,
+        )?)
+    })
+    .await;
+
+    result.unwrap_or_else(|error| {
+        warn!(%error, "Failed to look up crate object");
+        None
+    })
+}
+
+/// Makes sure that the request was sent by an owner of the crate, which may
+/// access the staged versions of the crate.
+fn verify_staged_version_access(
+    app: &AppState,
+    req: &Parts,
+    conn: &mut PgConnection,
+    crate_name: &str,
+) -> AppResult<()> {
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::PublishUpdate)
+        .for_crate(crate_name)
+        .check(req, conn)?;
+
+    let krate: Crate = Crate::by_exact_name(crate_name).first(conn)?;
+    let owners = krate.owners(conn)?;
+    if auth.user().rights(app, &owners)? < Rights::Publish {
+        return Err(not_found());
+    }
+
+    Ok(())
+}
+
 /// Handles the `GET /crates/:crate_id/:version/downloads` route.
+///
+/// Without query parameters, the daily download counts of the 90 days before
+/// the `before_date` are returned. With the `from`, `to` or `resolution`
+/// query parameters, the download counts of the requested range are returned
+/// as a dense series, in which the n-th count belongs to the n-th date.
 pub async fn downloads(
     app: AppState,
     Path((crate_name, version)): Path<(String, String)>,
@@ -144,9 +557,22 @@ pub async fn downloads(
             return Err(cargo_err(&format_args!("invalid semver: {version}")));
         }
 
+        let series = DownloadSeries::from_query(&req)?;
+
         let conn = &mut *app.db_read()?;
         let (version, _) = version_and_crate(conn, &crate_name, &version)?;
 
+        if let Some(series) = series {
+            let downloads = VersionDownload::belonging_to(&version)
+                .filter(version_downloads::date.between(series.from, series.to))
+                .select((version_downloads::date, version_downloads::downloads))
+                .load::<(NaiveDate, i32)>(conn)?;
+
+            let mut json = series.meta();
+            json["downloads"] = series.collect(downloads).into();
+            return Ok(Json(json));
+        }
+
         let cutoff_end_date = req
             .query()
             .get("before_date")
@@ -0,0 +1,62 @@
+//! Endpoint for publishing staged versions before their soak period is over
+
+use crate::auth::AuthCheck;
+use crate::background_jobs::Job;
+
+use crate::controllers::cargo_prelude::*;
+use crate::models::token::EndpointScope;
+use crate::models::{Crate, Rights, Version};
+use crate::schema::versions;
+use diesel::dsl::now;
+
+/// Handles the `PUT /crates/:crate_id/:version/promote` route.
+///
+/// Ends the soak period of a staged version. The version is published by a
+/// background job, which adds it to the index.
+pub async fn promote(
+    app: AppState,
+    Path((crate_name, version)): Path<(String, String)>,
+    req: Parts,
+) -> AppResult<Response> {
+    conduit_compat(move || {
+        if semver::Version::parse(&version).is_err() {
+            return Err(cargo_err(&format_args!("invalid semver: {version}")));
+        }
+
+        let conn = &mut *app.db_write()?;
+
+        let auth = AuthCheck::default()
+            .with_endpoint_scope(EndpointScope::PublishUpdate)
+            .for_crate(&crate_name)
+            .check(&req, conn)?;
+
+        let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+        let owners = krate.owners(conn)?;
+        if auth.user().rights(&app, &owners)? < Rights::Publish {
+            return Err(cargo_err(
+                "must already be an owner to promote a staged version",
+            ));
+        }
+
+        let staged_version: Version = Version::belonging_to(&krate)
+            .filter(versions::num.eq(&version))
+            .filter(versions::staged_until.is_not_null())
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| {
+                cargo_err(&format_args!(
+                    "crate `{}` does not have a staged version `{version}`",
+                    krate.name
+                ))
+            })?;
+
+        diesel::update(&staged_version)
+            .set(versions::staged_until.eq(now))
+            .execute(conn)?;
+
+        Job::promote_staged_versions().enqueue(conn)?;
+
+        ok_true()
+    })
+    .await
+}
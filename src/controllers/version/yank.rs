@@ -6,9 +6,13 @@ use crate::background_jobs::Job;
 use super::version_and_crate;
 use crate::controllers::cargo_prelude::*;
 use crate::models::token::EndpointScope;
-use crate::models::Rights;
-use crate::models::{insert_version_owner_action, VersionAction};
+use crate::models::{
+    insert_crate_notification, insert_version_owner_action, Crate, PublishAlert, Rights, User,
+    Version, VersionAction,
+};
 use crate::schema::versions;
+use crate::util::errors::bad_request;
+use chrono::Utc;
 
 /// Handles the `DELETE /crates/:crate_id/:version/yank` route.
 /// This does not delete a crate version, it makes the crate
@@ -67,12 +71,56 @@ fn modify_yank(
         return Err(cargo_err("must already be an owner to yank or unyank"));
     }
 
+    set_yanked(conn, &krate, &version, user.id, api_token_id, yanked)?;
+
+    ok_true()
+}
+
+/// Handles the `PUT /crates/:crate_id/:version/alert_yank/:token` route.
+///
+/// Yanks the version through the signed link in a publish alert email, which
+/// works without being logged in, so that owners can react quickly to a
+/// publish with a compromised account or API token.
+pub async fn alert_yank(
+    app: AppState,
+    Path((crate_name, version, token)): Path<(String, String, String)>,
+) -> AppResult<Response> {
+    conduit_compat(move || {
+        let conn = &mut *app.db_write()?;
+
+        let (version, krate) = version_and_crate(conn, &crate_name, &version)?;
+
+        let now = Utc::now().naive_utc();
+        let user_id = PublishAlert::verify_yank_token(&app.link_signer(), version.id, &token, now)
+            .ok_or_else(|| bad_request("The yank link is invalid or has expired."))?;
+
+        let user = User::find(conn, user_id)?;
+        let owners = krate.owners(conn)?;
+        if user.rights(&app, &owners)? < Rights::Publish {
+            return Err(bad_request("must already be an owner to yank"));
+        }
+
+        set_yanked(conn, &krate, &version, user.id, None, true)?;
+
+        ok_true()
+    })
+    .await
+}
+
+fn set_yanked(
+    conn: &mut PgConnection,
+    krate: &Crate,
+    version: &Version,
+    user_id: i32,
+    api_token_id: Option<i32>,
+    yanked: bool,
+) -> AppResult<()> {
     if version.yanked == yanked {
         // The crate is already in the state requested, nothing to do
-        return ok_true();
+        return Ok(());
     }
 
-    diesel::update(&version)
+    diesel::update(version)
         .set(versions::yanked.eq(yanked))
         .execute(conn)?;
 
@@ -82,9 +130,13 @@ fn modify_yank(
         VersionAction::Unyank
     };
 
-    insert_version_owner_action(conn, version.id, user.id, api_token_id, action)?;
+    insert_version_owner_action(conn, version.id, user_id, api_token_id, action)?;
+
+    if yanked {
+        insert_crate_notification(conn, version.id, action)?;
+    }
 
     Job::enqueue_sync_to_index(&krate.name, conn)?;
 
-    ok_true()
+    Ok(())
 }
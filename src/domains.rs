@@ -0,0 +1,186 @@
+//! This module implements lookups that are used to verify the ownership of
+//! domains, either via DNS `TXT` records or via files in the `.well-known`
+//! directory of the website.
+
+use rand::distributions::{Alphanumeric, DistString};
+use reqwest::blocking::Client;
+use reqwest::{header, StatusCode, Url};
+use std::io::Read;
+
+use crate::util::errors::{cargo_err, internal, AppResult};
+use crate::util::network::{get_public_url, has_public_host};
+
+/// The DNS-over-HTTPS endpoint used by [`RealDomainClient`].
+const DOH_URL: &str = "https://cloudflare-dns.com/dns-query";
+
+/// The numeric DNS record type of `TXT` records.
+const TXT_RECORD_TYPE: u16 = 16;
+
+/// The maximum size of `.well-known` files that are read.
+const MAX_WELL_KNOWN_FILE_SIZE: u64 = 64 * 1024;
+
+/// The maximum number of redirects that are followed when reading
+/// `.well-known` files.
+const MAX_REDIRECTS: usize = 3;
+
+/// The DNS label below a domain that has to contain the verification `TXT`
+/// record.
+const VERIFICATION_DNS_LABEL: &str = "_crates-io-verification";
+
+/// The name of the `.well-known` file that has to contain the verification
+/// value.
+pub const VERIFICATION_WELL_KNOWN_FILE: &str = "crates-io-verification.txt";
+
+/// Generates a random token that has to be published on a domain to prove its
+/// ownership.
+pub fn generate_verification_token() -> String {
+    Alphanumeric.sample_string(&mut rand::thread_rng(), 32)
+}
+
+/// The name of the DNS record that has to contain the [`verification_value`].
+pub fn verification_record_name(domain: &str) -> String {
+    format!("{VERIFICATION_DNS_LABEL}.{domain}")
+}
+
+/// The value of the `TXT` record or `.well-known` file that proves the
+/// ownership of a domain.
+pub fn verification_value(token: &str) -> String {
+    format!("crates-io-verification={token}")
+}
+
+/// Validates that the domain looks like a fully qualified domain name, and
+/// is neither an IP address nor a local name.
+pub fn validate_domain(domain: &str) -> AppResult<()> {
+    let valid = domain.contains('.')
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+        && Url::parse(&format!("https://{domain}/")).map_or(false, |url| has_public_host(&url));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(cargo_err(&format_args!("invalid domain `{domain}`")))
+    }
+}
+
+pub trait DomainClient: Send + Sync {
+    /// Returns the values of all `TXT` records of the given domain name.
+    fn txt_records(&self, name: &str) -> AppResult<Vec<String>>;
+
+    /// Returns the content of `https://{domain}/.well-known/{file}`, or `None`
+    /// if the file does not exist.
+    fn well_known_file(&self, domain: &str, file: &str) -> AppResult<Option<String>>;
+}
+
+/// A [`DomainClient`] that resolves names via DNS-over-HTTPS.
+#[derive(Debug)]
+pub struct RealDomainClient {
+    client: Option<Client>,
+}
+
+impl RealDomainClient {
+    pub fn new(client: Option<Client>) -> Self {
+        Self { client }
+    }
+
+    fn client(&self) -> &Client {
+        self.client
+            .as_ref()
+            .expect("No HTTP client is configured.  In tests, use `TestApp::with_proxy()`.")
+    }
+}
+
+impl DomainClient for RealDomainClient {
+    fn txt_records(&self, name: &str) -> AppResult<Vec<String>> {
+        info!("DNS TXT lookup: {name}");
+
+        let response: DohResponse = self
+            .client()
+            .get(DOH_URL)
+            .query(&[("name", name), ("type", "TXT")])
+            .header(header::ACCEPT, "application/dns-json")
+            .header(header::USER_AGENT, "crates.io (https://crates.io)")
+            .send()?
+            .error_for_status()
+            .map_err(|e| internal(format!("DNS lookup failed: {e}")))?
+            .json()?;
+
+        let records = response
+            .answer
+            .into_iter()
+            .filter(|answer| answer.record_type == TXT_RECORD_TYPE)
+            .map(|answer| answer.data.trim_matches('"').to_string())
+            .collect();
+
+        Ok(records)
+    }
+
+    fn well_known_file(&self, domain: &str, file: &str) -> AppResult<Option<String>> {
+        let url = format!("https://{domain}/.well-known/{file}");
+        info!("Well-known file lookup: {url}");
+
+        // Internal hosts are rejected here as well as on every redirect
+        let response = get_public_url(&url, MAX_REDIRECTS)?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let response = response
+            .error_for_status()
+            .map_err(|e| internal(format!("well-known file lookup failed: {e}")))?;
+
+        if response.content_length().unwrap_or(0) > MAX_WELL_KNOWN_FILE_SIZE {
+            return Err(internal(format!("well-known file `{url}` is too large")));
+        }
+
+        // The content length is optional, so the download is limited as well
+        let mut content = String::new();
+        response
+            .take(MAX_WELL_KNOWN_FILE_SIZE + 1)
+            .read_to_string(&mut content)
+            .map_err(|e| internal(format!("failed to read well-known file `{url}`: {e}")))?;
+
+        if content.len() as u64 > MAX_WELL_KNOWN_FILE_SIZE {
+            return Err(internal(format!("well-known file `{url}` is too large")));
+        }
+
+        Ok(Some(content))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DohAnswer {
+    #[serde(rename = "type")]
+    record_type: u16,
+    data: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn domain_validation() {
+        assert_ok!(validate_domain("acme.example"));
+        assert_ok!(validate_domain("sub.acme-corp.example"));
+        assert_err!(validate_domain("localhost"));
+        assert_err!(validate_domain("acme..example"));
+        assert_err!(validate_domain("-acme.example"));
+        assert_err!(validate_domain("acme.example/path"));
+        assert_err!(validate_domain("127.0.0.1"));
+        assert_err!(validate_domain("169.254.169.254"));
+        assert_err!(validate_domain("0x7f.1"));
+        assert_err!(validate_domain("acme.localhost"));
+    }
+}
@@ -461,6 +461,7 @@ mod tests {
                 "0000000000000000000000000000000000000000000000000000000000000000".to_string(),
                 None,
                 None,
+                None,
             )
             .expect("failed to create version")
             .save(conn, "ghost@example.com")
@@ -1,7 +1,9 @@
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use crate::util::errors::{server_error, AppResult};
+use crate::util::circuit_breaker::CircuitBreaker;
+use crate::util::errors::{server_error, service_unavailable, AppResult};
 
 use crate::config;
 use crate::Env;
@@ -12,9 +14,13 @@ use lettre::transport::smtp::SmtpTransport;
 use lettre::{Message, Transport};
 use rand::distributions::{Alphanumeric, DistString};
 
+/// Connections to the SMTP server that take longer than this are considered failed.
+const SMTP_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[derive(Debug)]
 pub struct Emails {
     backend: EmailBackend,
+    circuit_breaker: Arc<CircuitBreaker>,
 }
 
 impl Emails {
@@ -40,7 +46,10 @@ impl Emails {
             panic!("only the smtp backend is allowed in production");
         }
 
-        Self { backend }
+        Self {
+            backend,
+            circuit_breaker: Arc::new(CircuitBreaker::new("email")),
+        }
     }
 
     /// Create a new test backend that stores all the outgoing emails in memory, allowing for tests
@@ -50,9 +59,15 @@ impl Emails {
             backend: EmailBackend::Memory {
                 mails: Mutex::new(Vec::new()),
             },
+            circuit_breaker: Arc::new(CircuitBreaker::new("email")),
         }
     }
 
+    /// The circuit breaker that guards the connections to the SMTP server.
+    pub fn circuit_breaker(&self) -> Arc<CircuitBreaker> {
+        self.circuit_breaker.clone()
+    }
+
     /// Attempts to send a confirmation email.
     pub fn send_user_confirm(&self, email: &str, user_name: &str, token: &str) -> AppResult<()> {
         // Create a URL with token string as path to send to user
@@ -118,6 +133,156 @@ Source type: {source}\n",
         self.send(email, subject, &body)
     }
 
+    /// Attempts to send a digest of the new versions and yanks of the crates a
+    /// user is subscribed to.
+    pub fn send_crate_notification_digest(&self, email: &str, updates: &[String]) -> AppResult<()> {
+        let subject = "Updates to the crates you are subscribed to";
+        let mut body = String::from(
+            "The following crates you are subscribed to on crates.io have changed:\n\n",
+        );
+        for update in updates {
+            body.push_str(&format!("- {update}\n"));
+        }
+        body.push_str(&format!(
+            "\nYou can manage your subscriptions on the crate pages at https://{}.\n",
+            crate::config::domain_name()
+        ));
+
+        self.send(email, subject, &body)
+    }
+
+    /// Attempts to notify a user about a high severity security event
+    /// concerning their account.
+    pub fn send_security_notification(&self, email: &str, message: &str) -> AppResult<()> {
+        let subject = "Security notification for your crates.io account";
+        let body = format!(
+            "{message}.\n
+If this was you, you can ignore this email. Otherwise your account or one of
+your API tokens may have been compromised. Please review your account at
+https://{domain}/settings/tokens right away.",
+            domain = crate::config::domain_name()
+        );
+
+        self.send(email, subject, &body)
+    }
+
+    /// Attempts to send a digest of the low severity security events
+    /// concerning the account of a user.
+    pub fn send_security_notification_digest(
+        &self,
+        email: &str,
+        messages: &[String],
+    ) -> AppResult<()> {
+        let subject = "Recent activity on your crates.io account";
+        let mut body = String::from(
+            "The following security relevant events happened on your crates.io account:\n\n",
+        );
+        for message in messages {
+            body.push_str(&format!("- {message}\n"));
+        }
+        body.push_str(&format!(
+            "\nIf you don't recognize any of them, please review your account at
+https://{}/settings/tokens.\n",
+            crate::config::domain_name()
+        ));
+
+        self.send(email, subject, &body)
+    }
+
+    /// Attempts to send the confirmation email for a requested account
+    /// deletion.
+    pub fn send_account_deletion_confirmation(
+        &self,
+        email: &str,
+        user_name: &str,
+        token: &str,
+    ) -> AppResult<()> {
+        let subject = "Please confirm the deletion of your account";
+        let body = format!(
+            "Hello {user_name}! We have received a request to delete your crates.io account.\n
+Visit https://{domain}/me/delete/{token} to confirm the deletion. Your account
+will be deleted after a grace period, during which you can still cancel the
+deletion in your account settings.\n
+Your crates will not be deleted, and your name will be kept in their ownership
+and publishing history in anonymized form. If you did not request the deletion
+of your account, you can ignore this email.",
+            domain = crate::config::domain_name()
+        );
+
+        self.send(email, subject, &body)
+    }
+
+    /// Attempts to send a password reset link to a user that signs in with
+    /// local authentication.
+    pub fn send_password_reset(&self, email: &str, user_name: &str, token: &str) -> AppResult<()> {
+        let subject = "Reset your crates.io password";
+        let body = format!(
+            "Hello {user_name}! We have received a request to reset the password of your
+crates.io account.\n
+Visit https://{domain}/reset-password/{token} to choose a new password. The link
+is valid for one hour. If you did not request a password reset, you can ignore
+this email.",
+            domain = crate::config::domain_name()
+        );
+
+        self.send(email, subject, &body)
+    }
+
+    /// Attempts to alert an owner of a crate about a suspicious publish, with a
+    /// link to yank the published version.
+    pub fn send_publish_alert(
+        &self,
+        email: &str,
+        user_name: &str,
+        crate_name: &str,
+        version: &str,
+        reasons: &[String],
+        yank_token: &str,
+    ) -> AppResult<()> {
+        let subject = format!("Unusual publish of {crate_name} {version}");
+        let mut body = format!(
+            "Hello {user_name}! Version {version} of the crate {crate_name} has just been
+published on crates.io, and the publish looks different from the previous ones:\n\n"
+        );
+        for reason in reasons {
+            body.push_str(&format!("- {reason}\n"));
+        }
+        body.push_str(&format!(
+            "\nIf this publish was expected, you can ignore this email. Otherwise your
+account or one of your API tokens may have been compromised.\n
+Visit https://{domain}/crates/{crate_name}/{version}/alert-yank/{yank_token}
+to yank the version right away. The link is valid for seven days. Please also
+review your API tokens at https://{domain}/settings/tokens.",
+            domain = crate::config::domain_name()
+        ));
+
+        self.send(email, &subject, &body)
+    }
+
+    /// Asks an owner of a crate that was flagged as stale to confirm that the
+    /// crate is still maintained.
+    pub fn send_maintenance_confirmation_request(
+        &self,
+        email: &str,
+        user_name: &str,
+        crate_name: &str,
+        inactivity_days: u32,
+    ) -> AppResult<()> {
+        let subject = format!("Is {crate_name} still maintained?");
+        let body = format!(
+            "Hello {user_name}! None of the owners of the crate {crate_name} have been active on
+crates.io within the last {inactivity_days} days, so the crate is now flagged as
+possibly unmaintained.\n
+If you are still maintaining the crate, please visit
+https://{domain}/crates/{crate_name}/maintenance to confirm it and remove the
+flag. Signing in, using an API token or publishing a new version also removes
+the flag the next time it is checked.",
+            domain = crate::config::domain_name()
+        );
+
+        self.send(email, &subject, &body)
+    }
+
     /// This is supposed to be used only during tests, to retrieve the messages stored in the
     /// "memory" backend. It's not cfg'd away because our integration tests need to access this.
     pub fn mails_in_memory(&self) -> Option<Vec<StoredEmail>> {
@@ -156,18 +321,34 @@ Source type: {source}\n",
                 login,
                 password,
             } => {
-                SmtpTransport::relay(server)
-                    .and_then(|transport| {
-                        transport
-                            .credentials(Credentials::new(login.clone(), password.clone()))
-                            .authentication(vec![Mechanism::Plain])
-                            .build()
-                            .send(&email)
-                    })
-                    .map_err(|error| {
+                self.circuit_breaker
+                    .try_acquire()
+                    .map_err(|error| service_unavailable(&error))?;
+
+                let result = SmtpTransport::relay(server).and_then(|transport| {
+                    transport
+                        .credentials(Credentials::new(login.clone(), password.clone()))
+                        .authentication(vec![Mechanism::Plain])
+                        .timeout(Some(SMTP_TIMEOUT))
+                        .build()
+                        .send(&email)
+                });
+
+                match result {
+                    Ok(_) => self.circuit_breaker.record_success(),
+                    // Permanent errors are caused by the message, e.g. an
+                    // unknown recipient, and not by an unavailable server.
+                    Err(error) if error.is_permanent() => {
+                        self.circuit_breaker.record_success();
+                        error!(?error, "Failed to send email");
+                        return Err(server_error("Failed to send the email"));
+                    }
+                    Err(error) => {
+                        self.circuit_breaker.record_failure();
                         error!(?error, "Failed to send email");
-                        server_error("Failed to send the email")
-                    })?;
+                        return Err(server_error("Failed to send the email"));
+                    }
+                }
 
                 info!(?message_id, ?subject, "Email sent");
             }
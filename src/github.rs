@@ -6,10 +6,26 @@ use reqwest::{self, header};
 use serde::de::DeserializeOwned;
 
 use std::str;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 use crate::controllers::github::secret_scanning::{GitHubPublicKey, GitHubPublicKeyList};
-use crate::util::errors::{cargo_err, internal, not_found, AppResult, BoxedAppError};
-use reqwest::blocking::Client;
+use crate::util::circuit_breaker::CircuitBreaker;
+use crate::util::errors::{
+    cargo_err, internal, not_found, service_unavailable, AppResult, BoxedAppError,
+};
+use reqwest::blocking::{Client, Response};
+
+/// How often a GET request to GitHub is retried after a timeout, a connection
+/// error or a `5xx` response.
+const MAX_RETRIES: u32 = 2;
+
+/// The delay before the first retry, which is doubled for every further retry.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Requests to GitHub that take longer than this are considered failed.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub trait GitHubClient: Send + Sync {
     fn current_user(&self, auth: &AccessToken) -> AppResult<GithubUser>;
@@ -39,11 +55,15 @@ pub trait GitHubClient: Send + Sync {
 #[derive(Debug)]
 pub struct RealGitHubClient {
     client: Option<Client>,
+    circuit_breaker: Arc<CircuitBreaker>,
 }
 
 impl RealGitHubClient {
-    pub fn new(client: Option<Client>) -> Self {
-        Self { client }
+    pub fn new(client: Option<Client>, circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        Self {
+            client,
+            circuit_breaker,
+        }
     }
 
     /// Does all the nonsense for sending a GET to Github.
@@ -54,18 +74,67 @@ impl RealGitHubClient {
         let url = format!("https://api.github.com{url}");
         info!("GITHUB HTTP: {url}");
 
-        self.client()
-            .get(&url)
-            .header(header::ACCEPT, "application/vnd.github.v3+json")
-            .header(header::AUTHORIZATION, auth)
-            .header(header::USER_AGENT, "crates.io (https://crates.io)")
-            .send()?
+        // Fail fast while GitHub is known to be unavailable, instead of
+        // letting every request wait for the timeout.
+        self.circuit_breaker
+            .try_acquire()
+            .map_err(|error| service_unavailable(&error))?;
+
+        let result = self.send_with_retries(&url, auth);
+        match &result {
+            Err(error) if is_transient(error) => self.circuit_breaker.record_failure(),
+            _ => self.circuit_breaker.record_success(),
+        }
+
+        let response = result.map_err(|error| match error.status() {
+            Some(_) => handle_error_response(&error),
+            None => error.into(),
+        })?;
+
+        response
             .error_for_status()
             .map_err(|e| handle_error_response(&e))?
             .json()
             .map_err(Into::into)
     }
 
+    /// Sends the GET request, retrying it with an exponential backoff if the
+    /// failure looks transient.
+    ///
+    /// `5xx` responses are turned into errors, so that they are retried and
+    /// counted by the circuit breaker.
+    fn send_with_retries(&self, url: &str, auth: &str) -> reqwest::Result<Response> {
+        let mut delay = INITIAL_RETRY_DELAY;
+        let mut retries = 0;
+        loop {
+            let result = self
+                .client()
+                .get(url)
+                .header(header::ACCEPT, "application/vnd.github.v3+json")
+                .header(header::AUTHORIZATION, auth)
+                .header(header::USER_AGENT, "crates.io (https://crates.io)")
+                .timeout(REQUEST_TIMEOUT)
+                .send()
+                .and_then(|response| {
+                    if response.status().is_server_error() {
+                        response.error_for_status()
+                    } else {
+                        Ok(response)
+                    }
+                });
+
+            match result {
+                Err(error) if is_transient(&error) && retries < MAX_RETRIES => {
+                    warn!(%error, retries, "GitHub request failed, retrying");
+                    thread::sleep(delay);
+                    delay *= 2;
+                    retries += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
     /// Sends a GET to GitHub using OAuth access token authentication
     pub fn request<T>(&self, url: &str, auth: &AccessToken) -> AppResult<T>
     where
@@ -151,6 +220,14 @@ impl GitHubClient for RealGitHubClient {
     }
 }
 
+/// Returns `true` for errors that indicate that GitHub itself is having
+/// problems, as opposed to e.g. permission or "not found" errors.
+fn is_transient(error: &reqwest::Error) -> bool {
+    error.is_timeout()
+        || error.is_connect()
+        || error.status().map_or(false, |status| status.is_server_error())
+}
+
 fn handle_error_response(error: &reqwest::Error) -> BoxedAppError {
     use reqwest::StatusCode as Status;
 
@@ -0,0 +1,97 @@
+//! A camo-style proxy for the external images in rendered READMEs, so that
+//! readers don't reveal their IP addresses to arbitrary image hosts.
+//!
+//! Image URLs are rewritten to `/api/v1/image_proxy/<digest>/<hex encoded url>`,
+//! where the digest is an HMAC signature of the original URL. Only URLs with a
+//! valid signature are fetched, so that the proxy can't be used for arbitrary
+//! requests. URLs of internal hosts are never signed, and are checked again
+//! when the image is fetched, see [`crate::util::network`].
+
+use crate::util::network::has_public_host;
+use crate::util::signing::Signer;
+use url::Url;
+
+#[derive(Clone, Debug)]
+pub struct ImageProxy {
+    base_url: String,
+    signer: Signer,
+}
+
+impl ImageProxy {
+    pub fn new(domain_name: &str, key: &[u8]) -> Self {
+        Self {
+            base_url: format!("https://{domain_name}/api/v1/image_proxy"),
+            signer: Signer::new(key),
+        }
+    }
+
+    /// Returns the URL that the image at `url` is served from by the proxy.
+    pub fn proxy_url(&self, url: &str) -> String {
+        let digest = self.signer.sign(url);
+        format!("{}/{digest}/{}", self.base_url, hex::encode(url))
+    }
+
+    /// Returns the proxy URL of the image, or the unchanged URL if its host
+    /// is an IP address or otherwise can't be fetched by the proxy.
+    pub fn rewrite(&self, url: &str) -> String {
+        match Url::parse(url) {
+            Ok(parsed) if has_public_host(&parsed) => self.proxy_url(url),
+            _ => url.to_string(),
+        }
+    }
+
+    /// Returns the original URL of a proxied image, or `None` if the digest
+    /// doesn't match.
+    pub fn original_url(&self, digest: &str, encoded_url: &str) -> Option<String> {
+        let url = String::from_utf8(hex::decode(encoded_url).ok()?).ok()?;
+        self.signer.verify(&url, digest).then_some(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImageProxy;
+
+    #[test]
+    fn proxy_urls() {
+        let proxy = ImageProxy::new("crates.io", b"secret");
+        let image_url = "https://example.com/logo.png";
+
+        let proxy_url = proxy.proxy_url(image_url);
+        let path = proxy_url
+            .strip_prefix("https://crates.io/api/v1/image_proxy/")
+            .unwrap();
+        let (digest, encoded_url) = path.split_once('/').unwrap();
+        assert_eq!(encoded_url, hex::encode(image_url));
+
+        assert_eq!(
+            proxy.original_url(digest, encoded_url).as_deref(),
+            Some(image_url)
+        );
+        assert_eq!(
+            proxy.original_url(digest, &hex::encode("https://example.com/other.png")),
+            None
+        );
+        assert_eq!(proxy.original_url(digest, "not hex"), None);
+
+        assert_eq!(proxy.rewrite(image_url), proxy_url);
+        assert_eq!(
+            ImageProxy::new("crates.io", b"other secret").original_url(digest, encoded_url),
+            None
+        );
+    }
+
+    #[test]
+    fn internal_urls_are_not_proxied() {
+        let proxy = ImageProxy::new("crates.io", b"secret");
+
+        for url in [
+            "http://169.254.169.254/latest/meta-data",
+            "http://localhost:8888/logo.png",
+            "http://10.0.0.1/logo.png",
+            "http://[fd00::1]/logo.png",
+        ] {
+            assert_eq!(proxy.rewrite(url), url);
+        }
+    }
+}
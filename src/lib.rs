@@ -40,10 +40,12 @@ pub mod background_jobs;
 pub mod boot;
 pub mod config;
 pub mod db;
+pub mod domains;
 mod downloads_counter;
 pub mod email;
 pub mod github;
 pub mod headers;
+pub mod image_proxy;
 pub mod metrics;
 pub mod middleware;
 mod publish_rate_limit;
@@ -52,7 +54,9 @@ pub mod sql;
 pub mod ssh;
 pub mod swirl;
 mod test_util;
+mod upload_limits;
 pub mod uploaders;
+pub mod upstream;
 pub mod util;
 pub mod worker;
 
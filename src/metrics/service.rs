@@ -10,10 +10,11 @@
 //! As a rule of thumb, if the metric is not straight up fetched from the database it's probably an
 //! instance-level metric, and you should add it to `src/metrics/instance.rs`.
 
+use crate::models::RetentionStats;
 use crate::schema::{background_jobs, crates, versions};
 use crate::util::errors::AppResult;
 use diesel::{dsl::count_star, prelude::*, PgConnection};
-use prometheus::{proto::MetricFamily, IntGauge};
+use prometheus::{proto::MetricFamily, IntGauge, IntGaugeVec};
 
 metrics! {
     pub struct ServiceMetrics {
@@ -23,6 +24,12 @@ metrics! {
         versions_total: IntGauge,
         /// Number of queued up background jobs
         background_jobs: IntGauge,
+        /// Number of rows purged by the retention job since the stats were created
+        retention_purged_total: IntGaugeVec["kind"],
+        /// Number of rows purged by the most recent run of the retention job
+        retention_last_purged: IntGaugeVec["kind"],
+        /// Unix timestamp of the most recent run of the retention job
+        retention_last_run_timestamp: IntGaugeVec["kind"],
     }
 
     // All service metrics will be prefixed with this namespace.
@@ -38,6 +45,19 @@ impl ServiceMetrics {
         self.background_jobs
             .set(background_jobs::table.select(count_star()).first(conn)?);
 
+        for stats in RetentionStats::all(conn)? {
+            let kind = [stats.kind.as_str()];
+            self.retention_purged_total
+                .with_label_values(&kind)
+                .set(stats.purged_total);
+            self.retention_last_purged
+                .with_label_values(&kind)
+                .set(stats.last_purged);
+            self.retention_last_run_timestamp
+                .with_label_values(&kind)
+                .set(stats.last_run_at.timestamp());
+        }
+
         Ok(self.registry.gather())
     }
 }
@@ -5,7 +5,9 @@ mod debug;
 mod ember_html;
 mod head;
 pub mod log_request;
+mod maintenance_mode;
 pub mod normalize_path;
+mod private_registry;
 mod require_user_agent;
 pub mod session;
 mod static_or_continue;
@@ -64,6 +66,13 @@ pub fn apply_axum_middleware(state: AppState, router: Router) -> Router {
             state.clone(),
             block_traffic::block_routes,
         ))
+        .layer(conditional_layer(config.private_registry, || {
+            from_fn_with_state(state.clone(), private_registry::require_read_access)
+        }))
+        .layer(from_fn_with_state(
+            state.clone(),
+            maintenance_mode::reject_writes,
+        ))
         .layer(from_fn(head::support_head_requests))
         .layer(conditional_layer(env == Env::Development, || {
             from_fn(static_or_continue::serve_local_uploads)
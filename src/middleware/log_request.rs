@@ -4,16 +4,18 @@
 use crate::controllers::util::RequestPartsExt;
 use crate::headers::{XRealIp, XRequestId};
 use crate::middleware::normalize_path::OriginalPath;
+use crate::util::request_id::RequestId;
 use axum::headers::UserAgent;
 use axum::middleware::Next;
 use axum::response::IntoResponse;
 use axum::{Extension, TypedHeader};
-use http::{Method, Request, StatusCode, Uri};
+use http::{HeaderValue, Method, Request, StatusCode, Uri};
 use parking_lot::Mutex;
 use std::fmt::{self, Display, Formatter};
 use std::ops::Deref;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tracing::Instrument;
 
 const SLOW_REQUEST_THRESHOLD_MS: u128 = 1000;
 
@@ -35,6 +37,7 @@ pub struct RequestMetadata {
 
 pub struct Metadata<'a> {
     request: RequestMetadata,
+    request_id: RequestId,
     status: StatusCode,
     cause: Option<&'a CauseField>,
     error: Option<&'a ErrorField>,
@@ -65,10 +68,7 @@ impl Display for Metadata<'_> {
         }
 
         if !is_download_redirect {
-            match &self.request.request_id {
-                Some(header) => line.add_field("request_id", header.as_str())?,
-                None => line.add_field("request_id", "")?,
-            };
+            line.add_field("request_id", &self.request_id)?;
         }
 
         match &self.request.real_ip {
@@ -122,10 +122,27 @@ pub async fn log_requests<B>(
     let custom_metadata = RequestLog::default();
     req.extensions_mut().insert(custom_metadata.clone());
 
-    let response = next.run(req).await;
+    let request_id = request_metadata.request_id.as_ref();
+    let request_id = RequestId::from_header(request_id.map(|header| header.as_str()));
+
+    let span = info_span!(
+        "http.request",
+        method = %request_metadata.method,
+        path = %request_metadata.uri.path(),
+        request_id = %request_id,
+    );
+
+    let future = request_id.clone().scope(next.run(req));
+    let mut response = future.instrument(span).await;
+
+    // Allows users to reference the request when reporting problems
+    if let Ok(value) = HeaderValue::from_str(request_id.as_str()) {
+        response.headers_mut().insert("x-request-id", value);
+    }
 
     let metadata = Metadata {
         request: request_metadata,
+        request_id,
         status: response.status(),
         cause: response.extensions().get(),
         error: response.extensions().get(),
@@ -0,0 +1,43 @@
+//! Reject all write requests while the maintenance mode is enabled
+//!
+//! Read requests (`GET`, `HEAD` and `OPTIONS`) continue to work as usual. The admin endpoint
+//! controlling the maintenance mode is exempt, so that it can be disabled again, and so are
+//! `POST` endpoints that only read data.
+
+use crate::app::AppState;
+use crate::util::errors::{AppError, MaintenanceMode};
+use axum::extract::MatchedPath;
+use axum::middleware::Next;
+use axum::response::Response;
+use http::{Method, Request};
+
+const EXEMPT_ROUTES: &[&str] = &[
+    "/api/private/admin/maintenance",
+    "/api/v1/crates_batch",
+    "/api/v1/resolve",
+];
+
+pub async fn reject_writes<B>(
+    matched_path: Option<MatchedPath>,
+    state: AppState,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let maintenance_mode = &state.maintenance_mode;
+    if maintenance_mode.is_enabled() && !is_read(req.method()) && !is_exempt(matched_path) {
+        let error = MaintenanceMode {
+            retry_after: maintenance_mode.retry_after(),
+        };
+        return error.response();
+    }
+
+    next.run(req).await
+}
+
+fn is_read(method: &Method) -> bool {
+    method == Method::GET || method == Method::HEAD || method == Method::OPTIONS
+}
+
+fn is_exempt(matched_path: Option<MatchedPath>) -> bool {
+    matched_path.map_or(false, |path| EXEMPT_ROUTES.contains(&path.as_str()))
+}
@@ -0,0 +1,171 @@
+//! Require authentication for all read requests in private registry mode
+//!
+//! If `PRIVATE_REGISTRY` is set, the API, the sparse index and the crate downloads are only
+//! available to logged in users and to API tokens with the `read` scope. Write requests are not
+//! affected, since they are authenticated by their endpoints anyway.
+//!
+//! Tokens with crate scopes can only read the crates that match their scopes, so they are
+//! rejected by endpoints that deal with multiple crates, e.g. the search or the batch lookup.
+//!
+//! The login flow, the site metadata and the endpoints that use their own bearer tokens (admin
+//! API and metrics) or signatures (signed downloads) are exempt, as well as the health checks,
+//! which are not under `/api`.
+
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::conduit_axum::conduit_compat;
+use crate::models::token::EndpointScope;
+use crate::models::ApiToken;
+use crate::util::errors::AppResult;
+use axum::extract::MatchedPath;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use http::{Method, Request};
+
+const GUARDED_PREFIXES: &[&str] = &["/api/", "/index/"];
+
+const EXEMPT_PREFIXES: &[&str] = &[
+    "/api/private/admin",
+    "/api/private/metrics",
+    "/api/private/session",
+    "/api/private/signed_downloads",
+    "/api/v1/site_metadata",
+];
+
+/// `POST` endpoints that only read data.
+const READ_POST_ROUTES: &[&str] = &["/api/v1/crates_batch", "/api/v1/resolve"];
+
+/// Endpoints that do not expose any crate data, and are available to tokens
+/// with crate scopes.
+const CRATE_INDEPENDENT_ROUTES: &[&str] = &["/index/config.json"];
+
+pub async fn require_read_access<B>(
+    matched_path: Option<MatchedPath>,
+    state: AppState,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let matched_path = matched_path.as_ref().map(MatchedPath::as_str);
+    if !is_guarded(req.method(), req.uri().path(), matched_path) {
+        return next.run(req).await;
+    }
+
+    let crate_name = crate_name(req.uri().path(), matched_path);
+    let crate_independent =
+        matched_path.map_or(false, |path| CRATE_INDEPENDENT_ROUTES.contains(&path));
+
+    let (parts, body) = req.into_parts();
+    let result = conduit_compat(move || {
+        // Writes during the authentication (e.g. the audit log entries of
+        // rejected token IP addresses) are best-effort, so the read pool is
+        // sufficient here.
+        let conn = &mut *state.db_read()?;
+
+        let mut auth_check = AuthCheck::default().with_endpoint_scope(EndpointScope::Read);
+        if let Some(crate_name) = &crate_name {
+            auth_check = auth_check.for_crate(crate_name);
+        } else if crate_independent {
+            auth_check = auth_check.for_any_crate();
+        }
+        let auth = auth_check.check(&parts, conn)?;
+
+        // The token lookup can't record the use of the token on the replica
+        if let Some(token) = auth.api_token() {
+            if state.read_only_replica_database.is_some() {
+                if let Err(error) = record_token_use(&state, token) {
+                    debug!(?error, "Failed to record the use of the API token");
+                }
+            }
+        }
+
+        Ok(parts)
+    })
+    .await;
+
+    match result {
+        Ok(parts) => next.run(Request::from_parts(parts, body)).await,
+        Err(error) => error.into_response(),
+    }
+}
+
+fn record_token_use(state: &AppState, token: &ApiToken) -> AppResult<()> {
+    let conn = &mut *state.db_write()?;
+    token.record_use(conn)?;
+    Ok(())
+}
+
+fn is_guarded(method: &Method, path: &str, matched_path: Option<&str>) -> bool {
+    let is_read = method == Method::GET
+        || method == Method::HEAD
+        || matched_path.map_or(false, |path| READ_POST_ROUTES.contains(&path));
+
+    is_read
+        && GUARDED_PREFIXES
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+        && !EXEMPT_PREFIXES
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+}
+
+/// Returns the name of the crate that the request is about, so that the crate
+/// scopes of the token can be checked.
+fn crate_name(path: &str, matched_path: Option<&str>) -> Option<String> {
+    let matched_path = matched_path?;
+
+    if matched_path.starts_with("/api/v1/crates/:crate_id") {
+        return path.split('/').nth(4).map(ToString::to_string);
+    }
+
+    if matched_path == "/index/*path" {
+        return path.rsplit('/').next().map(ToString::to_string);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guarded_requests() {
+        assert!(is_guarded(&Method::GET, "/api/v1/crates", None));
+        assert!(is_guarded(&Method::HEAD, "/index/se/rd/serde", None));
+        assert!(is_guarded(
+            &Method::POST,
+            "/api/v1/crates_batch",
+            Some("/api/v1/crates_batch")
+        ));
+
+        assert!(!is_guarded(&Method::PUT, "/api/v1/crates/new", None));
+        assert!(!is_guarded(&Method::GET, "/api/v1/site_metadata", None));
+        assert!(!is_guarded(
+            &Method::GET,
+            "/api/private/session/begin",
+            None
+        ));
+        assert!(!is_guarded(&Method::GET, "/healthz", None));
+    }
+
+    #[test]
+    fn crate_names() {
+        assert_eq!(
+            crate_name(
+                "/api/v1/crates/serde/1.0.0/download",
+                Some("/api/v1/crates/:crate_id/:version/download")
+            ),
+            Some("serde".to_string())
+        );
+        assert_eq!(
+            crate_name("/index/se/rd/serde", Some("/index/*path")),
+            Some("serde".to_string())
+        );
+        assert_eq!(
+            crate_name("/index/config.json", Some("/index/config.json")),
+            None
+        );
+        assert_eq!(crate_name("/api/v1/crates", Some("/api/v1/crates")), None);
+        assert_eq!(crate_name("/api/v1/crates/serde", None), None);
+    }
+}
@@ -13,7 +13,9 @@ use std::ops::Deref;
 use std::sync::Arc;
 
 static COOKIE_NAME: &str = "cargo_session";
-static MAX_AGE_DAYS: i64 = 90;
+/// The session cookie expires this many days after the login, and so does
+/// the matching `UserSession`.
+pub static MAX_AGE_DAYS: i32 = 90;
 
 #[derive(Clone, FromRequestParts)]
 #[from_request(via(Extension))]
@@ -75,7 +77,7 @@ pub async fn attach_session<B>(
             .http_only(true)
             .secure(true)
             .same_site(SameSite::Strict)
-            .max_age(Duration::days(MAX_AGE_DAYS))
+            .max_age(Duration::days(MAX_AGE_DAYS.into()))
             .path("/")
             .finish();
 
@@ -1,15 +1,53 @@
 //! This module implements middleware to serve static files from the
 //! specified directory.
 
+use crate::util::range_requests::serve_bytes;
 use axum::middleware::Next;
-use axum::response::Response;
-use http::{Method, Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use http::{header, HeaderValue, Method, Request, StatusCode};
 use std::path::Path;
 use tower::ServiceExt;
 use tower_http::services::ServeDir;
 
+/// Serves the files of the local uploader.
+///
+/// In contrast to [`serve_dist`] the files are served with strong `ETag`
+/// headers, and `Range` requests are resolved against them, so that crate
+/// downloads behave the same way as when they are served by the backend
+/// directly.
 pub async fn serve_local_uploads<B>(request: Request<B>, next: Next<B>) -> Response {
-    serve("local_uploads", request, next).await
+    let method = request.method().clone();
+    if method != Method::GET && method != Method::HEAD {
+        return next.run(request).await;
+    }
+
+    let mut static_req = Request::new(());
+    *static_req.uri_mut() = request.uri().clone();
+
+    let Ok(response) = ServeDir::new("local_uploads").oneshot(static_req).await else {
+        return next.run(request).await;
+    };
+
+    if response.status() != StatusCode::OK {
+        return next.run(request).await;
+    }
+
+    let (parts, body) = response.into_parts();
+    let Ok(bytes) = hyper::body::to_bytes(body).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .cloned()
+        .unwrap_or_else(|| HeaderValue::from_static("application/octet-stream"));
+
+    let mut response = serve_bytes(request.headers(), content_type, bytes);
+    if method == Method::HEAD {
+        *response.body_mut() = Default::default();
+    }
+    response
 }
 
 pub async fn serve_dist<B>(request: Request<B>, next: Next<B>) -> Response {
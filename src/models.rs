@@ -1,33 +1,109 @@
+pub use self::account_deletion::AccountDeletion;
 pub use self::action::{insert_version_owner_action, VersionAction, VersionOwnerAction};
-pub use self::category::{Category, CrateCategory, NewCategory};
+pub use self::advisory::{Advisory, NewAdvisory};
+pub use self::audit_log::{AuditAction, AuditLogEntry, NewAuditLogEntry};
+pub use self::backfill_progress::BackfillProgress;
+pub use self::category::{Category, CategoryTreeRow, CrateCategory, NewCategory};
+pub use self::crate_metadata::{CrateMetadata, MetadataDiff};
 pub use self::crate_owner_invitation::{CrateOwnerInvitation, NewCrateOwnerInvitationOutcome};
 pub use self::dependency::{Dependency, DependencyKind, ReverseDependency};
+pub use self::dependency_graph::{DependencyGraph, DependencyGraphNode};
 pub use self::download::VersionDownload;
 pub use self::email::{Email, NewEmail};
 pub use self::follow::Follow;
+pub use self::health_score::{health_checks, health_score, CrateReleaseStats, HealthCheck};
+pub use self::index_snapshot::{IndexChange, IndexSnapshot};
+pub use self::index_sync::{
+    latest_index_sequence, next_index_sequence, IndexSyncState, IndexTarget,
+};
 pub use self::keyword::{CrateKeyword, Keyword};
 pub use self::krate::{Crate, CrateVersions, NewCrate, RecentCrateDownloads};
+pub use self::legal_hold::{
+    ActiveLegalHolds, LegalHold, LegalHoldAction, LegalHoldActionKind, NewLegalHold,
+    NewTakedownRequest, TakedownRequest,
+};
+pub use self::maintenance_mode::MaintenanceModeSetting;
+pub use self::maintenance_status::CrateMaintenanceStatus;
+pub use self::namespace_claim::{NamespaceClaim, NewNamespaceClaim, VerificationMethod};
+pub use self::oauth_identity::OAuthIdentity;
+pub use self::orphaned_file_report::{NewOrphanedFileReport, OrphanedFileReport};
 pub use self::owner::{CrateOwner, Owner, OwnerKind};
+pub use self::password_reset::PasswordReset;
+pub use self::publish_alert::{PublishAlert, PublishDetails};
+pub use self::publish_idempotency_key::{
+    IdempotentPublish, PublishIdempotencyKey, IDEMPOTENCY_KEY_RETENTION_HOURS,
+};
+pub use self::publisher_verification::{
+    NewPublisherVerification, PublisherVerification, PublisherVerificationMethod,
+};
+pub use self::quarantine::{CrateQuarantine, QuarantineStatus};
+pub use self::retention_stats::RetentionStats;
 pub use self::rights::Rights;
+pub use self::subscription::{
+    insert_advisory_notification, insert_crate_notification, CrateSubscription,
+    NewCrateSubscription,
+};
+pub(crate) use self::team::is_gh_org_owner;
 pub use self::team::{NewTeam, Team};
-pub use self::token::{ApiToken, CreatedApiToken};
+pub use self::token::{ApiToken, CreatedApiToken, TokenOrigin};
+pub use self::upload_limit::{NewUploadLimit, UploadLimit};
 pub use self::user::{NewUser, User};
+pub use self::user_data_export::UserDataExport;
+pub use self::user_notification::{
+    notify_owner_added, NewUserNotification, NotificationKind, NotificationSeverity,
+    UserNotification,
+};
+pub use self::user_passkey::UserPasskey;
+pub use self::user_password::UserPassword;
+pub use self::user_session::{CreatedUserSession, UserSession};
 pub use self::version::{NewVersion, TopVersions, Version};
+pub use self::version_object::VersionObject;
+pub use self::webauthn_ceremony::{WebauthnCeremony, WebauthnCeremonyKind};
 
 pub mod helpers;
 
+mod account_deletion;
 mod action;
+mod advisory;
+mod audit_log;
+mod backfill_progress;
 pub mod category;
+mod crate_metadata;
 mod crate_owner_invitation;
 pub mod dependency;
+mod dependency_graph;
 mod download;
 mod email;
 mod follow;
+mod health_score;
+mod index_snapshot;
+mod index_sync;
 mod keyword;
 pub mod krate;
+mod legal_hold;
+mod maintenance_mode;
+mod maintenance_status;
+pub mod namespace_claim;
+mod oauth_identity;
+mod orphaned_file_report;
 mod owner;
+mod password_reset;
+mod publish_alert;
+mod publish_idempotency_key;
+mod publisher_verification;
+mod quarantine;
+mod retention_stats;
 mod rights;
+mod subscription;
 mod team;
 pub mod token;
+mod upload_limit;
 pub mod user;
+mod user_data_export;
+mod user_notification;
+mod user_passkey;
+mod user_password;
+mod user_session;
 mod version;
+mod version_object;
+mod webauthn_ceremony;
@@ -0,0 +1,102 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use rand::distributions::{Alphanumeric, DistString};
+
+use crate::schema::account_deletions;
+
+/// A request to delete the account of a user.
+///
+/// The deletion has to be confirmed through a link sent to the verified email
+/// address of the user. Confirmed deletions are scheduled after a grace
+/// period, during which they can still be cancelled, and are then performed
+/// by the `purge_deleted_accounts` background job.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable)]
+#[diesel(primary_key(user_id))]
+pub struct AccountDeletion {
+    pub user_id: i32,
+    pub confirmation_token: String,
+    pub requested_at: NaiveDateTime,
+    pub confirmed_at: Option<NaiveDateTime>,
+    pub scheduled_for: Option<NaiveDateTime>,
+    pub completed_at: Option<NaiveDateTime>,
+}
+
+impl AccountDeletion {
+    pub fn status(&self) -> &'static str {
+        if self.completed_at.is_some() {
+            "completed"
+        } else if self.confirmed_at.is_some() {
+            "scheduled"
+        } else {
+            "unconfirmed"
+        }
+    }
+
+    pub fn find(conn: &mut PgConnection, user_id: i32) -> QueryResult<Option<Self>> {
+        account_deletions::table
+            .find(user_id)
+            .first(conn)
+            .optional()
+    }
+
+    /// Requests the deletion of the account, replacing any previous request
+    /// and its confirmation token.
+    pub fn request(conn: &mut PgConnection, user_id: i32) -> QueryResult<Self> {
+        let token = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+
+        diesel::insert_into(account_deletions::table)
+            .values((
+                account_deletions::user_id.eq(user_id),
+                account_deletions::confirmation_token.eq(&token),
+            ))
+            .on_conflict(account_deletions::user_id)
+            .do_update()
+            .set((
+                account_deletions::confirmation_token.eq(&token),
+                account_deletions::requested_at.eq(now),
+                account_deletions::confirmed_at.eq(None::<NaiveDateTime>),
+                account_deletions::scheduled_for.eq(None::<NaiveDateTime>),
+            ))
+            .get_result(conn)
+    }
+
+    /// Confirms the deletion and schedules it for the end of the grace period.
+    pub fn confirm(
+        &self,
+        conn: &mut PgConnection,
+        grace_period: chrono::Duration,
+    ) -> QueryResult<Self> {
+        let confirmed_at = chrono::Utc::now().naive_utc();
+        diesel::update(self)
+            .set((
+                account_deletions::confirmed_at.eq(confirmed_at),
+                account_deletions::scheduled_for.eq(confirmed_at + grace_period),
+            ))
+            .get_result(conn)
+    }
+
+    /// Cancels a pending deletion. Returns `false` if there was nothing to
+    /// cancel.
+    pub fn cancel(conn: &mut PgConnection, user_id: i32) -> QueryResult<bool> {
+        let deleted = diesel::delete(account_deletions::table.find(user_id))
+            .filter(account_deletions::completed_at.is_null())
+            .execute(conn)?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Returns all confirmed deletions whose grace period is over.
+    pub fn due(conn: &mut PgConnection) -> QueryResult<Vec<Self>> {
+        account_deletions::table
+            .filter(account_deletions::completed_at.is_null())
+            .filter(account_deletions::scheduled_for.le(now))
+            .load(conn)
+    }
+
+    pub fn mark_completed(&self, conn: &mut PgConnection) -> QueryResult<Self> {
+        diesel::update(self)
+            .set(account_deletions::completed_at.eq(now))
+            .get_result(conn)
+    }
+}
@@ -0,0 +1,95 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::prelude::*;
+
+use crate::schema::{advisories, crates};
+
+/// A security advisory of a crate, which can be recorded by the crates.io
+/// team through the admin API.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable, Selectable)]
+#[diesel(table_name = advisories)]
+pub struct Advisory {
+    pub id: i32,
+    pub crate_id: i32,
+    /// Identifier of the advisory, e.g. `RUSTSEC-2023-0001`
+    pub identifier: String,
+    /// Version requirement matching the affected versions of the crate
+    pub affected: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl Advisory {
+    /// Returns all advisories together with the name of their crate, ordered
+    /// by crate name and identifier.
+    pub fn all(conn: &mut PgConnection) -> QueryResult<Vec<(Self, String)>> {
+        advisories::table
+            .inner_join(crates::table)
+            .order((crates::name, advisories::identifier))
+            .select((Advisory::as_select(), crates::name))
+            .load(conn)
+    }
+
+    /// Returns the advisories of the given crates together with the name of
+    /// their crate.
+    pub fn for_crates(conn: &mut PgConnection, names: &[&str]) -> QueryResult<Vec<(Self, String)>> {
+        advisories::table
+            .inner_join(crates::table)
+            .filter(crates::name.eq_any(names))
+            .order(advisories::identifier)
+            .select((Advisory::as_select(), crates::name))
+            .load(conn)
+    }
+
+    pub fn find(
+        conn: &mut PgConnection,
+        crate_id: i32,
+        identifier: &str,
+    ) -> QueryResult<Option<Self>> {
+        advisories::table
+            .filter(advisories::crate_id.eq(crate_id))
+            .filter(advisories::identifier.eq(identifier))
+            .select(Advisory::as_select())
+            .first(conn)
+            .optional()
+    }
+
+    /// Returns whether the given version is affected by the advisory.
+    pub fn affects(&self, version: &semver::Version) -> bool {
+        semver::VersionReq::parse(&self.affected).map_or(false, |req| req.matches(version))
+    }
+
+    pub fn delete(conn: &mut PgConnection, crate_id: i32, identifier: &str) -> QueryResult<bool> {
+        let advisory = advisories::table
+            .filter(advisories::crate_id.eq(crate_id))
+            .filter(advisories::identifier.eq(identifier));
+
+        let deleted = diesel::delete(advisory).execute(conn)?;
+        Ok(deleted > 0)
+    }
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = advisories, check_for_backend(diesel::pg::Pg))]
+pub struct NewAdvisory<'a> {
+    pub crate_id: i32,
+    pub identifier: &'a str,
+    pub affected: &'a str,
+}
+
+impl NewAdvisory<'_> {
+    /// Records the advisory, or replaces the affected versions of an existing
+    /// advisory with the same identifier.
+    pub fn upsert(&self, conn: &mut PgConnection) -> QueryResult<Advisory> {
+        diesel::insert_into(advisories::table)
+            .values(self)
+            .on_conflict((advisories::crate_id, advisories::identifier))
+            .do_update()
+            .set((
+                advisories::affected.eq(self.affected),
+                advisories::updated_at.eq(now),
+            ))
+            .returning(Advisory::as_returning())
+            .get_result(conn)
+    }
+}
@@ -0,0 +1,80 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde_json::Value;
+
+use crate::models::User;
+use crate::schema::audit_log;
+
+/// The kind of event recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    /// An API token was used from an IP address outside of its allowed CIDR
+    /// blocks.
+    TokenIpRejected,
+    /// A publish changed the crate-level metadata, e.g. the repository URL.
+    /// The details contain the values before and after the publish.
+    CrateMetadataChanged,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::TokenIpRejected => "token_ip_rejected",
+            AuditAction::CrateMetadataChanged => "crate_metadata_changed",
+        }
+    }
+}
+
+/// A security relevant event concerning a user account, one of its API
+/// tokens or one of its crates.
+#[derive(Clone, Debug, PartialEq, Identifiable, Queryable, Selectable, Associations)]
+#[diesel(table_name = audit_log, belongs_to(User))]
+pub struct AuditLogEntry {
+    pub id: i32,
+    pub user_id: i32,
+    pub api_token_id: Option<i32>,
+    pub action: String,
+    pub ip_address: Option<String>,
+    pub details: Value,
+    pub created_at: NaiveDateTime,
+}
+
+impl AuditLogEntry {
+    /// Returns the audit log of the user, oldest entries first.
+    pub fn for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<Vec<Self>> {
+        audit_log::table
+            .filter(audit_log::user_id.eq(user_id))
+            .order(audit_log::id)
+            .select(AuditLogEntry::as_select())
+            .load(conn)
+    }
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = audit_log, check_for_backend(diesel::pg::Pg))]
+pub struct NewAuditLogEntry<'a> {
+    pub user_id: i32,
+    pub api_token_id: Option<i32>,
+    pub action: &'static str,
+    pub ip_address: Option<&'a str>,
+    pub details: Value,
+}
+
+impl<'a> NewAuditLogEntry<'a> {
+    pub fn new(user_id: i32, action: AuditAction) -> Self {
+        Self {
+            user_id,
+            api_token_id: None,
+            action: action.as_str(),
+            ip_address: None,
+            details: json!({}),
+        }
+    }
+
+    pub fn insert(&self, conn: &mut PgConnection) -> QueryResult<AuditLogEntry> {
+        diesel::insert_into(audit_log::table)
+            .values(self)
+            .returning(AuditLogEntry::as_returning())
+            .get_result(conn)
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::prelude::*;
+
+use crate::schema::backfill_progress;
+
+/// The progress of a resumable backfill background job.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable, Selectable)]
+#[diesel(table_name = backfill_progress, primary_key(name))]
+pub struct BackfillProgress {
+    pub name: String,
+    /// ID of the last version that was processed
+    pub last_version_id: i32,
+    /// `true` if the backfill should stop after the current batch
+    pub paused: bool,
+    pub processed_count: i32,
+    pub skipped_count: i32,
+    pub updated_at: NaiveDateTime,
+}
+
+impl BackfillProgress {
+    pub fn find(conn: &mut PgConnection, name: &str) -> QueryResult<Option<Self>> {
+        backfill_progress::table
+            .find(name)
+            .select(BackfillProgress::as_select())
+            .first(conn)
+            .optional()
+    }
+
+    /// Returns the progress of the backfill, starting a new one if it has
+    /// not been run before.
+    pub fn find_or_create(conn: &mut PgConnection, name: &str) -> QueryResult<Self> {
+        diesel::insert_into(backfill_progress::table)
+            .values(backfill_progress::name.eq(name))
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+
+        backfill_progress::table
+            .find(name)
+            .select(BackfillProgress::as_select())
+            .first(conn)
+    }
+
+    pub fn set_paused(conn: &mut PgConnection, name: &str, paused: bool) -> QueryResult<Self> {
+        Self::find_or_create(conn, name)?;
+
+        diesel::update(backfill_progress::table.find(name))
+            .set((
+                backfill_progress::paused.eq(paused),
+                backfill_progress::updated_at.eq(now),
+            ))
+            .returning(BackfillProgress::as_returning())
+            .get_result(conn)
+    }
+
+    /// Records that a batch of versions up to `last_version_id` has been
+    /// processed.
+    pub fn record_batch(
+        conn: &mut PgConnection,
+        name: &str,
+        last_version_id: i32,
+        processed: i32,
+        skipped: i32,
+    ) -> QueryResult<Self> {
+        diesel::update(backfill_progress::table.find(name))
+            .set((
+                backfill_progress::last_version_id.eq(last_version_id),
+                backfill_progress::processed_count
+                    .eq(backfill_progress::processed_count + processed),
+                backfill_progress::skipped_count.eq(backfill_progress::skipped_count + skipped),
+                backfill_progress::updated_at.eq(now),
+            ))
+            .returning(BackfillProgress::as_returning())
+            .get_result(conn)
+    }
+
+    /// Deletes the progress, so that the next run starts from the beginning.
+    pub fn reset(conn: &mut PgConnection, name: &str) -> QueryResult<usize> {
+        diesel::delete(backfill_progress::table.find(name)).execute(conn)
+    }
+}
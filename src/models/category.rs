@@ -15,6 +15,9 @@ pub struct Category {
     pub created_at: NaiveDateTime,
 }
 
+/// The minimum trigram similarity for a category to be suggested.
+const SIMILARITY_THRESHOLD: f32 = 0.2;
+
 type WithSlug<'a> = diesel::dsl::Eq<categories::slug, crate::sql::lower::HelperType<&'a str>>;
 type BySlug<'a> = diesel::dsl::Filter<categories::table, WithSlug<'a>>;
 
@@ -71,6 +74,35 @@ impl Category {
         })
     }
 
+    /// Returns up to `limit` categories matching the query, for example to
+    /// suggest valid categories for a misspelled slug. Categories whose slug or
+    /// name starts with the query come first, followed by categories ordered by
+    /// their trigram similarity to the query.
+    pub fn suggest(conn: &mut PgConnection, query: &str, limit: i64) -> QueryResult<Vec<Category>> {
+        use crate::sql::{greatest, lower, similarity};
+
+        let query = query.to_lowercase();
+        let escaped = query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let pattern = format!("{escaped}%");
+
+        let is_prefix = categories::slug
+            .like(pattern.clone())
+            .or(lower(categories::category).like(pattern));
+        let score = greatest(
+            similarity(categories::slug, query.clone()),
+            similarity(lower(categories::category), query),
+        );
+
+        categories::table
+            .filter(is_prefix.clone().or(score.clone().gt(SIMILARITY_THRESHOLD)))
+            .order((is_prefix.desc(), score.desc(), categories::slug))
+            .limit(limit)
+            .load(conn)
+    }
+
     pub fn count_toplevel(conn: &mut PgConnection) -> QueryResult<i64> {
         use self::categories::dsl::*;
 
@@ -101,6 +133,13 @@ impl Category {
             .load(conn)
     }
 
+    /// Returns all categories that are reachable from a top-level category,
+    /// ordered so that every category directly follows its parent or
+    /// preceding siblings.
+    pub fn tree(conn: &mut PgConnection) -> QueryResult<Vec<CategoryTreeRow>> {
+        sql_query(include_str!("category_tree.sql")).load(conn)
+    }
+
     pub fn subcategories(&self, conn: &mut PgConnection) -> QueryResult<Vec<Category>> {
         use diesel::sql_types::Text;
 
@@ -109,6 +148,15 @@ impl Category {
             .load(conn)
     }
 
+    /// Returns this category followed by all of its direct and indirect subcategories.
+    pub fn with_descendants(&self, conn: &mut PgConnection) -> QueryResult<Vec<Category>> {
+        use diesel::sql_types::Integer;
+
+        sql_query(include_str!("category_descendants.sql"))
+            .bind::<Integer, _>(self.id)
+            .load(conn)
+    }
+
     /// Gathers the parent categories from the top-level Category to the direct parent of this Category.
     /// Returns categories as a Vector in order of traversal, not including this Category.
     /// The intention is to be able to have slugs or parent categories arrayed in order, to
@@ -122,6 +170,20 @@ impl Category {
     }
 }
 
+/// A category together with its position in the category tree, as returned
+/// by [`Category::tree`].
+#[derive(QueryableByName, Debug)]
+pub struct CategoryTreeRow {
+    #[diesel(embed)]
+    pub category: Category,
+    /// The `id` of the parent category, or `None` for top-level categories
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Integer>)]
+    pub parent_id: Option<i32>,
+    /// The sum of the `crates_cnt` of this category and all its subcategories
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub total_crates_cnt: i32,
+}
+
 /// Struct for inserting categories; only used in tests. Actual categories are inserted
 /// in src/boot/categories.rs.
 #[derive(Insertable, AsChangeset, Default, Debug)]
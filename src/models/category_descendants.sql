@@ -0,0 +1,12 @@
+WITH RECURSIVE descendants AS (
+    SELECT c.id, c.category, c.slug, c.description, c.crates_cnt, c.created_at, c.path
+    FROM categories c
+    WHERE c.id = $1
+  UNION ALL
+    SELECT c.id, c.category, c.slug, c.description, c.crates_cnt, c.created_at, c.path
+    FROM categories c
+    INNER JOIN descendants d ON subpath(c.path, 0, -1) = d.path
+)
+SELECT id, category, slug, description, crates_cnt, created_at
+FROM descendants
+ORDER BY path
@@ -0,0 +1,19 @@
+WITH RECURSIVE tree AS (
+    SELECT c.id, c.category, c.slug, c.description, c.crates_cnt, c.created_at, c.path,
+      NULL::int AS parent_id
+    FROM categories c
+    WHERE nlevel(c.path) = 2
+  UNION ALL
+    SELECT c.id, c.category, c.slug, c.description, c.crates_cnt, c.created_at, c.path,
+      tree.id AS parent_id
+    FROM categories c
+    INNER JOIN tree ON subpath(c.path, 0, -1) = tree.path
+)
+SELECT tree.id, tree.category, tree.slug, tree.description, tree.crates_cnt, tree.created_at,
+  tree.parent_id,
+  COALESCE((
+    SELECT sum(c2.crates_cnt)::int FROM categories c2
+    WHERE c2.path <@ tree.path
+  ), 0) AS total_crates_cnt
+FROM tree
+ORDER BY tree.path
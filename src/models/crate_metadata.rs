@@ -0,0 +1,148 @@
+use diesel::prelude::*;
+
+use crate::schema::{categories, crates, crates_categories, crates_keywords, keywords, versions};
+
+/// The crate-level metadata that is compared between publishes, so that
+/// suspicious changes like a swapped repository URL can be detected.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CrateMetadata {
+    pub description: Option<String>,
+    pub repository: Option<String>,
+    /// The license of the most recently published version, since the
+    /// license is stored per version.
+    pub license: Option<String>,
+    /// Sorted alphabetically, so that the order in the manifest doesn't matter.
+    pub keywords: Vec<String>,
+    /// Category slugs, sorted alphabetically.
+    pub categories: Vec<String>,
+}
+
+impl CrateMetadata {
+    pub fn load(conn: &mut PgConnection, crate_id: i32) -> QueryResult<Self> {
+        let (description, repository) = crates::table
+            .find(crate_id)
+            .select((crates::description, crates::repository))
+            .first(conn)?;
+
+        let license = versions::table
+            .filter(versions::crate_id.eq(crate_id))
+            .order(versions::id.desc())
+            .select(versions::license)
+            .first::<Option<String>>(conn)
+            .optional()?
+            .flatten();
+
+        let keywords = crates_keywords::table
+            .inner_join(keywords::table)
+            .filter(crates_keywords::crate_id.eq(crate_id))
+            .select(keywords::keyword)
+            .order(keywords::keyword)
+            .load(conn)?;
+
+        let categories = crates_categories::table
+            .inner_join(categories::table)
+            .filter(crates_categories::crate_id.eq(crate_id))
+            .select(categories::slug)
+            .order(categories::slug)
+            .load(conn)?;
+
+        Ok(Self {
+            description,
+            repository,
+            license,
+            keywords,
+            categories,
+        })
+    }
+
+    /// Returns the fields that differ from the `previous` metadata.
+    pub fn diff(&self, previous: &Self) -> MetadataDiff {
+        MetadataDiff {
+            description: Change::between(&previous.description, &self.description),
+            repository: Change::between(&previous.repository, &self.repository),
+            license: Change::between(&previous.license, &self.license),
+            keywords: Change::between(&previous.keywords, &self.keywords),
+            categories: Change::between(&previous.categories, &self.categories),
+        }
+    }
+}
+
+/// The changed crate-level metadata of a publish. Unchanged fields are
+/// omitted when serialized.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct MetadataDiff {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<Change<Option<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<Change<Option<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<Change<Option<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Change<Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Change<Vec<String>>>,
+}
+
+impl MetadataDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Change<T> {
+    pub before: T,
+    pub after: T,
+}
+
+impl<T: Clone + PartialEq> Change<T> {
+    fn between(before: &T, after: &T) -> Option<Self> {
+        (before != after).then(|| Self {
+            before: before.clone(),
+            after: after.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(repository: &str, keywords: &[&str]) -> CrateMetadata {
+        CrateMetadata {
+            description: Some("description".into()),
+            repository: Some(repository.into()),
+            license: Some("MIT".into()),
+            keywords: keywords.iter().map(|keyword| keyword.to_string()).collect(),
+            categories: vec![],
+        }
+    }
+
+    #[test]
+    fn unchanged_metadata() {
+        let previous = metadata("https://github.com/foo/foo", &["foo"]);
+        assert!(previous.clone().diff(&previous).is_empty());
+    }
+
+    #[test]
+    fn changed_metadata() {
+        let previous = metadata("https://github.com/foo/foo", &["foo"]);
+        let current = metadata("https://github.com/evil/foo", &["bar", "foo"]);
+
+        let diff = current.diff(&previous);
+        assert!(!diff.is_empty());
+        assert_eq!(
+            serde_json::to_value(diff).unwrap(),
+            json!({
+                "repository": {
+                    "before": "https://github.com/foo/foo",
+                    "after": "https://github.com/evil/foo",
+                },
+                "keywords": {
+                    "before": ["foo"],
+                    "after": ["bar", "foo"],
+                },
+            })
+        );
+    }
+}
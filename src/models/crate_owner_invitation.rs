@@ -2,8 +2,8 @@ use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
 
 use crate::config;
-use crate::models::{CrateOwner, OwnerKind};
-use crate::schema::{crate_owner_invitations, crate_owners, crates};
+use crate::models::{notify_owner_added, CrateOwner, OwnerKind};
+use crate::schema::{crate_owner_invitations, crate_owners, crates, users};
 use crate::util::errors::{AppResult, OwnershipInvitationExpired};
 
 #[derive(Debug)]
@@ -118,6 +118,22 @@ impl CrateOwnerInvitation {
 
             diesel::delete(&self).execute(conn)?;
 
+            let crate_name: String = crates::table
+                .find(self.crate_id)
+                .select(crates::name)
+                .first(conn)?;
+            let login: String = users::table
+                .find(self.invited_user_id)
+                .select(users::gh_login)
+                .first(conn)?;
+            notify_owner_added(
+                conn,
+                self.crate_id,
+                &crate_name,
+                &login,
+                Some(self.invited_user_id),
+            )?;
+
             Ok(())
         })
     }
@@ -131,6 +147,17 @@ impl CrateOwnerInvitation {
         Ok(())
     }
 
+    /// Deletes the invitations that were created before the given time.
+    /// Returns the number of deleted invitations.
+    pub fn purge_created_before(
+        conn: &mut PgConnection,
+        cutoff: NaiveDateTime,
+    ) -> QueryResult<usize> {
+        diesel::delete(crate_owner_invitations::table)
+            .filter(crate_owner_invitations::created_at.lt(cutoff))
+            .execute(conn)
+    }
+
     pub fn is_expired(&self, config: &config::Server) -> bool {
         self.expires_at(config) <= Utc::now().naive_utc()
     }
@@ -0,0 +1,9 @@
+SELECT crate_id,
+  MAX(created_at) FILTER (WHERE NOT yanked) AS last_release,
+  COUNT(*) AS num_versions,
+  COUNT(*) FILTER (WHERE yanked) AS num_yanked,
+  COUNT(*) FILTER (WHERE created_at > now() - interval '1 year') AS num_recent_releases
+FROM versions
+WHERE staged_until IS NULL
+  AND ($1::int IS NULL OR crate_id = $1)
+GROUP BY crate_id
@@ -0,0 +1,280 @@
+//! Server-side resolution of the transitive dependency tree of a version.
+//!
+//! Every dependency is resolved to the highest published version matching
+//! its version requirement that has not been yanked, similar to a fresh
+//! `cargo generate-lockfile`. Features and targets are not taken into
+//! account, so the graph is a superset of what any specific build uses.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use diesel::prelude::*;
+
+use crate::models::DependencyKind;
+use crate::schema::{crates, dependencies, versions};
+
+/// A version in the dependency graph.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DependencyGraphNode {
+    /// The ID of the version
+    pub id: i32,
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    pub version: String,
+}
+
+/// A dependency between two versions in the graph.
+#[derive(Clone, Debug, Serialize)]
+pub struct DependencyGraphEdge {
+    pub from: i32,
+    pub to: i32,
+    pub req: String,
+    pub kind: DependencyKind,
+}
+
+/// A dependency without any published version that matches its requirement.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct UnresolvedDependency {
+    pub from: i32,
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    pub req: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct DependencyGraph {
+    /// All versions in the graph, starting with the root version
+    pub nodes: Vec<DependencyGraphNode>,
+    pub edges: Vec<DependencyGraphEdge>,
+    pub unresolved: Vec<UnresolvedDependency>,
+    /// `true` if some dependencies were left out because of the node limit
+    pub truncated: bool,
+}
+
+#[derive(Queryable)]
+struct DependencyRow {
+    version_id: i32,
+    crate_id: i32,
+    crate_name: String,
+    req: String,
+    kind: DependencyKind,
+}
+
+impl DependencyGraph {
+    /// Resolves the dependencies of the given version, up to `depth` levels
+    /// deep and with at most `max_nodes` versions.
+    ///
+    /// Dev-dependencies and optional dependencies are left out. Cycles are
+    /// represented as edges back to versions that are already in the graph.
+    pub fn resolve(
+        conn: &mut PgConnection,
+        root: DependencyGraphNode,
+        depth: u32,
+        max_nodes: usize,
+    ) -> QueryResult<Self> {
+        let mut graph = DependencyGraph::default();
+        let mut visited = HashSet::from([root.id]);
+        let mut level = vec![root.id];
+        graph.nodes.push(root);
+
+        // The published versions of every crate that was encountered so far
+        let mut crate_versions: HashMap<i32, Vec<(i32, semver::Version)>> = HashMap::new();
+
+        for _ in 0..depth {
+            if level.is_empty() {
+                break;
+            }
+
+            let rows: Vec<DependencyRow> = dependencies::table
+                .inner_join(crates::table)
+                .filter(dependencies::version_id.eq_any(&level))
+                .filter(dependencies::kind.ne(DependencyKind::Dev as i32))
+                .filter(dependencies::optional.eq(false))
+                .order((dependencies::version_id, crates::name))
+                .select((
+                    dependencies::version_id,
+                    dependencies::crate_id,
+                    crates::name,
+                    dependencies::req,
+                    dependencies::kind,
+                ))
+                .load(conn)?;
+
+            let new_crate_ids = rows
+                .iter()
+                .map(|row| row.crate_id)
+                .filter(|crate_id| !crate_versions.contains_key(crate_id))
+                .collect::<HashSet<_>>();
+            load_crate_versions(conn, new_crate_ids, &mut crate_versions)?;
+
+            let mut next_level = Vec::new();
+            for row in rows {
+                let Some((version_id, version)) = semver::VersionReq::parse(&row.req)
+                    .ok()
+                    .and_then(|req| resolve_req(&req, &crate_versions[&row.crate_id]))
+                else {
+                    graph.unresolved.push(UnresolvedDependency {
+                        from: row.version_id,
+                        crate_name: row.crate_name,
+                        req: row.req,
+                    });
+                    continue;
+                };
+
+                if !visited.contains(&version_id) {
+                    if graph.nodes.len() >= max_nodes {
+                        graph.truncated = true;
+                        continue;
+                    }
+
+                    visited.insert(version_id);
+                    next_level.push(version_id);
+                    graph.nodes.push(DependencyGraphNode {
+                        id: version_id,
+                        crate_name: row.crate_name,
+                        version: version.to_string(),
+                    });
+                }
+
+                graph.edges.push(DependencyGraphEdge {
+                    from: row.version_id,
+                    to: version_id,
+                    req: row.req,
+                    kind: row.kind,
+                });
+            }
+
+            level = next_level;
+        }
+
+        Ok(graph)
+    }
+
+    /// Renders the graph in the DOT language of Graphviz.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph dependencies {\n");
+
+        for node in &self.nodes {
+            let label = format!("{} {}", node.crate_name, node.version);
+            let _ = writeln!(dot, "    v{} [label={label:?}];", node.id);
+        }
+
+        for edge in &self.edges {
+            let label = &edge.req;
+            let _ = match edge.kind {
+                DependencyKind::Build => writeln!(
+                    dot,
+                    "    v{} -> v{} [label={label:?}, style=dashed];",
+                    edge.from, edge.to
+                ),
+                _ => writeln!(dot, "    v{} -> v{} [label={label:?}];", edge.from, edge.to),
+            };
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn load_crate_versions(
+    conn: &mut PgConnection,
+    crate_ids: HashSet<i32>,
+    crate_versions: &mut HashMap<i32, Vec<(i32, semver::Version)>>,
+) -> QueryResult<()> {
+    let crate_ids = crate_ids.into_iter().collect::<Vec<_>>();
+    for crate_id in &crate_ids {
+        crate_versions.insert(*crate_id, Vec::new());
+    }
+
+    let rows: Vec<(i32, i32, String)> = versions::table
+        .filter(versions::crate_id.eq_any(&crate_ids))
+        .filter(versions::yanked.eq(false))
+        .filter(versions::staged_until.is_null())
+        .select((versions::id, versions::crate_id, versions::num))
+        .load(conn)?;
+
+    for (id, crate_id, num) in rows {
+        if let Ok(num) = semver::Version::parse(&num) {
+            if let Some(versions) = crate_versions.get_mut(&crate_id) {
+                versions.push((id, num));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the highest version that matches the requirement.
+fn resolve_req(
+    req: &semver::VersionReq,
+    versions: &[(i32, semver::Version)],
+) -> Option<(i32, semver::Version)> {
+    versions
+        .iter()
+        .filter(|(_, version)| req.matches(version))
+        .max_by(|(_, a), (_, b)| a.cmp(b))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(num: &str) -> semver::Version {
+        semver::Version::parse(num).unwrap()
+    }
+
+    #[test]
+    fn resolve_highest_matching_version() {
+        let versions = vec![
+            (1, version("1.0.0")),
+            (2, version("1.2.0")),
+            (3, version("2.0.0")),
+            (4, version("1.3.0-beta.1")),
+        ];
+
+        let req = semver::VersionReq::parse("^1").unwrap();
+        assert_eq!(resolve_req(&req, &versions), Some((2, version("1.2.0"))));
+
+        let req = semver::VersionReq::parse("^3").unwrap();
+        assert_eq!(resolve_req(&req, &versions), None);
+    }
+
+    #[test]
+    fn dot_output() {
+        let node = |id, crate_name: &str| DependencyGraphNode {
+            id,
+            crate_name: crate_name.to_string(),
+            version: "1.0.0".to_string(),
+        };
+
+        let graph = DependencyGraph {
+            nodes: vec![node(1, "foo"), node(2, "bar")],
+            edges: vec![
+                DependencyGraphEdge {
+                    from: 1,
+                    to: 2,
+                    req: "^1".to_string(),
+                    kind: DependencyKind::Normal,
+                },
+                DependencyGraphEdge {
+                    from: 2,
+                    to: 1,
+                    req: "^1".to_string(),
+                    kind: DependencyKind::Build,
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            graph.to_dot(),
+            "digraph dependencies {\n    \
+                v1 [label=\"foo 1.0.0\"];\n    \
+                v2 [label=\"bar 1.0.0\"];\n    \
+                v1 -> v2 [label=\"^1\"];\n    \
+                v2 -> v1 [label=\"^1\", style=dashed];\n\
+            }\n"
+        );
+    }
+}
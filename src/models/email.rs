@@ -1,4 +1,5 @@
 use chrono::NaiveDateTime;
+use diesel::prelude::*;
 
 use crate::models::User;
 use crate::schema::emails;
@@ -14,6 +15,23 @@ pub struct Email {
     pub token_generated_at: Option<NaiveDateTime>,
 }
 
+impl Email {
+    /// Clears the confirmation tokens of verified email addresses that were
+    /// generated before the given time, so that the links in old
+    /// confirmation emails stop working. Returns the number of cleared tokens.
+    pub fn clear_consumed_tokens_before(
+        conn: &mut PgConnection,
+        cutoff: NaiveDateTime,
+    ) -> QueryResult<usize> {
+        diesel::update(emails::table)
+            .filter(emails::verified.eq(true))
+            .filter(emails::token.ne(""))
+            .filter(emails::token_generated_at.lt(cutoff))
+            .set(emails::token.eq(""))
+            .execute(conn)
+    }
+}
+
 #[derive(Debug, Insertable, AsChangeset)]
 #[diesel(table_name = emails, check_for_backend(diesel::pg::Pg))]
 pub struct NewEmail<'a> {
@@ -0,0 +1,139 @@
+//! The crate "health score", a transparent maintenance indicator computed from
+//! the release history of a crate.
+//!
+//! The score is the sum of the scores of all [`HealthCheck`]s and ranges from 0
+//! to 100.
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Integer, Nullable, Timestamp};
+
+/// Releases older than this many days don't contribute to the recency score.
+const RELEASE_RECENCY_DAYS: i64 = 2 * 365;
+
+/// The number of releases per year that results in the full frequency score.
+const RELEASE_FREQUENCY_TARGET: i64 = 4;
+
+/// Aggregated information about the versions of a crate.
+#[derive(Debug, Clone, QueryableByName)]
+pub struct CrateReleaseStats {
+    #[diesel(sql_type = Integer)]
+    pub crate_id: i32,
+    /// The creation date of the newest version that has not been yanked
+    #[diesel(sql_type = Nullable<Timestamp>)]
+    pub last_release: Option<NaiveDateTime>,
+    #[diesel(sql_type = BigInt)]
+    pub num_versions: i64,
+    #[diesel(sql_type = BigInt)]
+    pub num_yanked: i64,
+    /// The number of versions released within the last year
+    #[diesel(sql_type = BigInt)]
+    pub num_recent_releases: i64,
+}
+
+impl CrateReleaseStats {
+    /// Loads the release stats of a single crate, or of all crates if
+    /// `crate_id` is `None`.
+    pub fn load(conn: &mut PgConnection, crate_id: Option<i32>) -> QueryResult<Vec<Self>> {
+        diesel::sql_query(include_str!("crate_release_stats.sql"))
+            .bind::<Nullable<Integer>, _>(crate_id)
+            .load(conn)
+    }
+}
+
+/// A single component of the health score.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthCheck {
+    pub name: &'static str,
+    pub score: i32,
+    pub max_score: i32,
+    pub description: &'static str,
+}
+
+/// Computes the individual checks of the health score of a crate.
+pub fn health_checks(stats: &CrateReleaseStats, now: NaiveDateTime) -> Vec<HealthCheck> {
+    let release_recency = stats.last_release.map_or(0, |last_release| {
+        let days = (now - last_release)
+            .num_days()
+            .clamp(0, RELEASE_RECENCY_DAYS);
+        50 * (RELEASE_RECENCY_DAYS - days) / RELEASE_RECENCY_DAYS
+    });
+
+    let yanked_ratio = if stats.num_versions > 0 {
+        30 * (stats.num_versions - stats.num_yanked) / stats.num_versions
+    } else {
+        0
+    };
+
+    let release_frequency =
+        20 * stats.num_recent_releases.min(RELEASE_FREQUENCY_TARGET) / RELEASE_FREQUENCY_TARGET;
+
+    vec![
+        HealthCheck {
+            name: "release_recency",
+            score: release_recency as i32,
+            max_score: 50,
+            description: "Time since the latest release that has not been yanked",
+        },
+        HealthCheck {
+            name: "yanked_ratio",
+            score: yanked_ratio as i32,
+            max_score: 30,
+            description: "Share of versions that have not been yanked",
+        },
+        HealthCheck {
+            name: "release_frequency",
+            score: release_frequency as i32,
+            max_score: 20,
+            description: "Number of releases within the last year",
+        },
+    ]
+}
+
+/// Computes the health score of a crate, the sum of all [`health_checks`].
+pub fn health_score(stats: &CrateReleaseStats, now: NaiveDateTime) -> i32 {
+    health_checks(stats, now)
+        .iter()
+        .map(|check| check.score)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate};
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2023, 7, 26)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    fn stats(last_release_days_ago: Option<i64>, versions: i64, yanked: i64) -> CrateReleaseStats {
+        CrateReleaseStats {
+            crate_id: 1,
+            last_release: last_release_days_ago.map(|days| now() - Duration::days(days)),
+            num_versions: versions,
+            num_yanked: yanked,
+            num_recent_releases: versions,
+        }
+    }
+
+    #[test]
+    fn actively_maintained_crate() {
+        assert_eq!(health_score(&stats(Some(0), 4, 0), now()), 100);
+    }
+
+    #[test]
+    fn abandoned_crate() {
+        let mut stats = stats(Some(3 * 365), 2, 1);
+        stats.num_recent_releases = 0;
+        assert_eq!(health_score(&stats, now()), 15);
+    }
+
+    #[test]
+    fn fully_yanked_crate() {
+        assert_eq!(health_score(&stats(None, 2, 2), now()), 10);
+    }
+}
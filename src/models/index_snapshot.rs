@@ -0,0 +1,113 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::{max, now, IntervalDsl};
+use diesel::prelude::*;
+
+use crate::schema::{index_changes, index_snapshots};
+
+/// Sequence numbers are assigned when a change is recorded, but the change only
+/// becomes visible when its transaction is committed. Changes are only handed
+/// out once they are older than this margin, so that a change is never skipped
+/// because a change with a higher sequence number was committed first.
+const SETTLE_MARGIN_SECONDS: i32 = 60;
+
+/// An update or removal of the sparse index file of a crate.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable, Selectable)]
+pub struct IndexChange {
+    /// The sequence number of the change
+    pub id: i64,
+    pub crate_name: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl IndexChange {
+    pub fn record(conn: &mut PgConnection, crate_name: &str) -> QueryResult<()> {
+        diesel::insert_into(index_changes::table)
+            .values(index_changes::crate_name.eq(crate_name))
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Returns the settled changes after the given sequence number, oldest
+    /// first.
+    pub fn since(conn: &mut PgConnection, sequence: i64, limit: i64) -> QueryResult<Vec<Self>> {
+        index_changes::table
+            .filter(index_changes::id.gt(sequence))
+            .filter(index_changes::created_at.lt(now - SETTLE_MARGIN_SECONDS.seconds()))
+            .order(index_changes::id)
+            .limit(limit)
+            .select(IndexChange::as_select())
+            .load(conn)
+    }
+
+    /// Returns the sequence number of the last settled change, or 0 if there
+    /// is none.
+    pub fn settled_sequence(conn: &mut PgConnection) -> QueryResult<i64> {
+        let sequence = index_changes::table
+            .filter(index_changes::created_at.lt(now - SETTLE_MARGIN_SECONDS.seconds()))
+            .select(max(index_changes::id))
+            .get_result::<Option<i64>>(conn)?;
+
+        Ok(sequence.unwrap_or_default())
+    }
+
+    /// Deletes the changes up to the given sequence number, which are all
+    /// included in a snapshot.
+    pub fn prune(conn: &mut PgConnection, sequence: i64) -> QueryResult<usize> {
+        diesel::delete(index_changes::table.filter(index_changes::id.le(sequence))).execute(conn)
+    }
+}
+
+/// A compressed snapshot of all files of the sparse index.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable, Selectable, Insertable)]
+#[diesel(primary_key(sequence))]
+pub struct IndexSnapshot {
+    /// The sequence number of the last change that is included
+    pub sequence: i64,
+    /// The number of index files in the snapshot
+    pub crates: i32,
+    /// The size of the snapshot tarball in bytes
+    pub size: i64,
+    pub created_at: NaiveDateTime,
+}
+
+impl IndexSnapshot {
+    /// Returns the snapshots, newest first.
+    pub fn all(conn: &mut PgConnection) -> QueryResult<Vec<Self>> {
+        index_snapshots::table
+            .order(index_snapshots::sequence.desc())
+            .select(IndexSnapshot::as_select())
+            .load(conn)
+    }
+
+    /// Returns the oldest snapshot. The change feed is retained back to its
+    /// sequence number.
+    pub fn oldest(conn: &mut PgConnection) -> QueryResult<Option<Self>> {
+        index_snapshots::table
+            .order(index_snapshots::sequence.asc())
+            .select(IndexSnapshot::as_select())
+            .first(conn)
+            .optional()
+    }
+
+    pub fn latest(conn: &mut PgConnection) -> QueryResult<Option<Self>> {
+        index_snapshots::table
+            .order(index_snapshots::sequence.desc())
+            .select(IndexSnapshot::as_select())
+            .first(conn)
+            .optional()
+    }
+
+    pub fn insert(&self, conn: &mut PgConnection) -> QueryResult<()> {
+        diesel::insert_into(index_snapshots::table)
+            .values(self)
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    pub fn delete(&self, conn: &mut PgConnection) -> QueryResult<usize> {
+        diesel::delete(index_snapshots::table.find(self.sequence)).execute(conn)
+    }
+}
@@ -0,0 +1,103 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::schema::{crate_index_sequences, index_sync_states};
+
+/// The places that the index files of the crates are synced to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndexTarget {
+    Git,
+    Sparse,
+}
+
+impl IndexTarget {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IndexTarget::Git => "git",
+            IndexTarget::Sparse => "sparse",
+        }
+    }
+}
+
+/// Assigns the next sequence number for the index sync jobs of a crate.
+///
+/// The row of the crate stays locked until the transaction is committed, so
+/// the jobs of concurrent transactions are committed in the order of their
+/// sequence numbers.
+pub fn next_index_sequence(conn: &mut PgConnection, crate_name: &str) -> QueryResult<i64> {
+    diesel::insert_into(crate_index_sequences::table)
+        .values((
+            crate_index_sequences::crate_name.eq(crate_name),
+            crate_index_sequences::sequence.eq(1),
+        ))
+        .on_conflict(crate_index_sequences::crate_name)
+        .do_update()
+        .set(crate_index_sequences::sequence.eq(crate_index_sequences::sequence + 1))
+        .returning(crate_index_sequences::sequence)
+        .get_result(conn)
+}
+
+/// Returns the sequence number of the most recently enqueued index sync job
+/// of a crate, or 0 if there is none.
+pub fn latest_index_sequence(conn: &mut PgConnection, crate_name: &str) -> QueryResult<i64> {
+    let sequence = crate_index_sequences::table
+        .find(crate_name)
+        .select(crate_index_sequences::sequence)
+        .first(conn)
+        .optional()?;
+
+    Ok(sequence.unwrap_or_default())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Queryable, Selectable)]
+pub struct IndexSyncState {
+    pub crate_name: String,
+    pub target: String,
+    /// The sequence number of the most recent job that synced the index file
+    pub synced_sequence: i64,
+    pub synced_at: NaiveDateTime,
+}
+
+impl IndexSyncState {
+    /// Locks the sync state of a crate for the given target until the end of
+    /// the transaction, so that the index sync jobs of a crate never run
+    /// concurrently.
+    pub fn lock(
+        conn: &mut PgConnection,
+        crate_name: &str,
+        target: IndexTarget,
+    ) -> QueryResult<Self> {
+        diesel::insert_into(index_sync_states::table)
+            .values((
+                index_sync_states::crate_name.eq(crate_name),
+                index_sync_states::target.eq(target.as_str()),
+            ))
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+
+        index_sync_states::table
+            .find((crate_name, target.as_str()))
+            .select(IndexSyncState::as_select())
+            .for_update()
+            .first(conn)
+    }
+
+    pub fn mark_synced(&self, conn: &mut PgConnection, sequence: i64) -> QueryResult<()> {
+        diesel::update(index_sync_states::table.find((&self.crate_name, &self.target)))
+            .set((
+                index_sync_states::synced_sequence.eq(sequence),
+                index_sync_states::synced_at.eq(diesel::dsl::now),
+            ))
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    pub fn for_crate(conn: &mut PgConnection, crate_name: &str) -> QueryResult<Vec<Self>> {
+        index_sync_states::table
+            .filter(index_sync_states::crate_name.eq(crate_name))
+            .order(index_sync_states::target)
+            .select(IndexSyncState::as_select())
+            .load(conn)
+    }
+}
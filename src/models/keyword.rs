@@ -4,6 +4,7 @@ use diesel::prelude::*;
 use crate::models::Crate;
 use crate::schema::*;
 use crate::sql::lower;
+use crate::util::unicode;
 
 #[derive(Clone, Identifiable, Queryable, Debug)]
 pub struct Keyword {
@@ -29,7 +30,7 @@ pub struct CrateKeyword {
 impl Keyword {
     pub fn find_by_keyword(conn: &mut PgConnection, name: &str) -> QueryResult<Keyword> {
         keywords::table
-            .filter(keywords::keyword.eq(lower(name)))
+            .filter(keywords::keyword.eq(lower(unicode::fold(name))))
             .first(conn)
     }
 
@@ -37,7 +38,7 @@ impl Keyword {
         conn: &mut PgConnection,
         names: &[&str],
     ) -> QueryResult<Vec<Keyword>> {
-        let lowercase_names: Vec<_> = names.iter().map(|s| s.to_lowercase()).collect();
+        let lowercase_names: Vec<_> = names.iter().map(|s| unicode::fold(s)).collect();
 
         let new_keywords: Vec<_> = lowercase_names
             .iter()
@@ -54,13 +55,20 @@ impl Keyword {
     }
 
     pub fn valid_name(name: &str) -> bool {
+        name.is_ascii() && Self::valid_unicode_name(name)
+    }
+
+    /// Like [`Self::valid_name`], but also accepts non-ASCII letters and
+    /// digits, which are only allowed if `allow_non_ascii_keywords` is
+    /// enabled in the config.
+    pub fn valid_unicode_name(name: &str) -> bool {
         let mut chars = name.chars();
         let first = match chars.next() {
             None => return false,
             Some(c) => c,
         };
-        first.is_ascii_alphanumeric()
-            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '+')
+        first.is_alphanumeric()
+            && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '+')
     }
 
     pub fn update_crate(
@@ -11,13 +11,13 @@ use crate::app::App;
 use crate::controllers::helpers::pagination::*;
 use crate::models::version::TopVersions;
 use crate::models::{
-    CrateOwner, CrateOwnerInvitation, Dependency, NewCrateOwnerInvitationOutcome, Owner, OwnerKind,
-    ReverseDependency, User, Version,
+    notify_owner_added, CrateOwner, CrateOwnerInvitation, Dependency,
+    NewCrateOwnerInvitationOutcome, Owner, OwnerKind, ReverseDependency, User, Version,
 };
 use crate::util::errors::{cargo_err, AppResult};
 
 use crate::models::helpers::with_count::*;
-use crate::publish_rate_limit::PublishRateLimit;
+use crate::publish_rate_limit::{LimitedAction, PublishRateLimit};
 use crate::schema::*;
 use crate::sql::canon_crate_name;
 
@@ -45,7 +45,7 @@ pub struct Crate {
     pub homepage: Option<String>,
     pub documentation: Option<String>,
     pub repository: Option<String>,
-    pub max_upload_size: Option<i32>,
+    pub health_score: Option<i32>,
 }
 
 /// We literally never want to select `textsearchable_index_col`
@@ -60,7 +60,7 @@ type AllColumns = (
     crates::homepage,
     crates::documentation,
     crates::repository,
-    crates::max_upload_size,
+    crates::health_score,
 );
 
 pub const ALL_COLUMNS: AllColumns = (
@@ -73,7 +73,7 @@ pub const ALL_COLUMNS: AllColumns = (
     crates::homepage,
     crates::documentation,
     crates::repository,
-    crates::max_upload_size,
+    crates::health_score,
 );
 
 pub const MAX_NAME_LENGTH: usize = 64;
@@ -88,8 +88,8 @@ type ByExactName<'a> = diesel::dsl::Filter<All, diesel::dsl::Eq<crates::name, &'
 #[diesel(
     table_name = crates,
     check_for_backend(diesel::pg::Pg),
-    // This is actually just to skip updating them
-    primary_key(name, max_upload_size),
+    // This is actually just to skip updating it
+    primary_key(name),
     treat_none_as_null = true,
 )]
 pub struct NewCrate<'a> {
@@ -99,7 +99,6 @@ pub struct NewCrate<'a> {
     pub documentation: Option<&'a str>,
     pub readme: Option<&'a str>,
     pub repository: Option<&'a str>,
-    pub max_upload_size: Option<i32>,
 }
 
 impl<'a> NewCrate<'a> {
@@ -119,7 +118,7 @@ impl<'a> NewCrate<'a> {
             // first so we know whether to add an owner
             if let Some(krate) = self.save_new_crate(conn, uploader)? {
                 if let Some(rate_limit) = rate_limit {
-                    rate_limit.check_rate_limit(uploader, conn)?;
+                    rate_limit.check_rate_limit(uploader, LimitedAction::PublishNew, conn)?;
                 }
                 return Ok(krate);
             }
@@ -397,6 +396,8 @@ impl Crate {
                     .set(crate_owners::deleted.eq(false))
                     .execute(conn)?;
 
+                notify_owner_added(conn, self.id, &self.name, owner.login(), None)?;
+
                 Ok(format!(
                     "team {} has been added as an owner of crate {}",
                     owner.login(),
@@ -448,7 +449,18 @@ impl Crate {
         &self,
         conn: &mut PgConnection,
     ) -> QueryResult<Vec<crates_io_index::Crate>> {
-        let mut versions: Vec<Version> = self.all_versions().load(conn)?;
+        // Versions under legal hold are omitted from the index
+        let held_versions: Vec<i32> = legal_holds::table
+            .filter(legal_holds::crate_id.eq(self.id))
+            .filter(legal_holds::lifted_at.is_null())
+            .filter(legal_holds::version_id.is_not_null())
+            .select(legal_holds::version_id.assume_not_null())
+            .load(conn)?;
+
+        let mut versions: Vec<Version> = self
+            .all_versions()
+            .filter(versions::id.ne_all(held_versions))
+            .load(conn)?;
 
         // We sort by `created_at` by default, but since tests run within a
         // single database transaction the versions will all have the same
@@ -541,7 +553,6 @@ mod tests {
             documentation: None,
             readme: None,
             repository: None,
-            max_upload_size: None,
         };
         assert_err!(krate.validate());
     }
@@ -598,12 +609,16 @@ pub trait CrateVersions {
         self.all_versions().filter(versions::yanked.eq(false))
     }
 
+    /// Returns all versions including yanked ones, but excluding staged
+    /// versions that have not been published yet.
     fn all_versions(&self) -> versions::BoxedQuery<'_, Pg>;
 }
 
 impl CrateVersions for Crate {
     fn all_versions(&self) -> versions::BoxedQuery<'_, Pg> {
-        Version::belonging_to(self).into_boxed()
+        Version::belonging_to(self)
+            .filter(versions::staged_until.is_null())
+            .into_boxed()
     }
 }
 
@@ -615,6 +630,8 @@ impl CrateVersions for Vec<Crate> {
 
 impl CrateVersions for [Crate] {
     fn all_versions(&self) -> versions::BoxedQuery<'_, Pg> {
-        Version::belonging_to(self).into_boxed()
+        Version::belonging_to(self)
+            .filter(versions::staged_until.is_null())
+            .into_boxed()
     }
 }
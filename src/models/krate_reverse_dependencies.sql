@@ -16,6 +16,7 @@ SELECT *, COUNT(*) OVER () as total FROM (
         ) rn
         FROM versions
         WHERE NOT yanked
+        AND staged_until IS NULL
         -- This is completely redundant, but it's faster to filter the versions
         -- early even if this subselect is done via an index scan.
         AND crate_id = ANY(
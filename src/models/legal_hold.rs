@@ -0,0 +1,271 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::schema::{crates, legal_hold_actions, legal_holds, takedown_requests, versions};
+
+/// A change to a legal hold, as recorded in the audit trail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegalHoldActionKind {
+    Place,
+    Lift,
+}
+
+impl LegalHoldActionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LegalHoldActionKind::Place => "place",
+            LegalHoldActionKind::Lift => "lift",
+        }
+    }
+}
+
+/// A crate or a single version that must not be downloaded for legal reasons,
+/// e.g. because of a DMCA takedown notice.
+///
+/// The data of the crate is preserved while the hold is active, so that it can
+/// be restored once the hold has been lifted.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable)]
+pub struct LegalHold {
+    pub id: i32,
+    pub crate_id: i32,
+    /// `None` if the whole crate is under legal hold
+    pub version_id: Option<i32>,
+    pub takedown_request_id: Option<i32>,
+    pub reason: String,
+    pub notice_url: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub lifted_at: Option<NaiveDateTime>,
+}
+
+impl LegalHold {
+    pub fn is_active(&self) -> bool {
+        self.lifted_at.is_none()
+    }
+
+    /// Returns the active legal hold that blocks downloads of the given
+    /// version, either because of a hold on the version itself, or on the
+    /// whole crate.
+    pub fn active_for_version(
+        conn: &mut PgConnection,
+        crate_id: i32,
+        version_id: i32,
+    ) -> QueryResult<Option<Self>> {
+        legal_holds::table
+            .filter(legal_holds::crate_id.eq(crate_id))
+            .filter(legal_holds::lifted_at.is_null())
+            .filter(
+                legal_holds::version_id
+                    .is_null()
+                    .or(legal_holds::version_id.eq(version_id)),
+            )
+            .order(legal_holds::version_id.is_not_null())
+            .first(conn)
+            .optional()
+    }
+
+    /// Returns `true` if the crate has an active legal hold on any of its
+    /// versions, or on the crate itself.
+    pub fn any_active_for_crate(conn: &mut PgConnection, crate_id: i32) -> QueryResult<bool> {
+        diesel::select(diesel::dsl::exists(
+            legal_holds::table
+                .filter(legal_holds::crate_id.eq(crate_id))
+                .filter(legal_holds::lifted_at.is_null()),
+        ))
+        .get_result(conn)
+    }
+
+    /// Returns `true` if the whole crate, as opposed to single versions of it,
+    /// is under an active legal hold.
+    pub fn crate_held(conn: &mut PgConnection, crate_id: i32) -> QueryResult<bool> {
+        diesel::select(diesel::dsl::exists(
+            legal_holds::table
+                .filter(legal_holds::crate_id.eq(crate_id))
+                .filter(legal_holds::version_id.is_null())
+                .filter(legal_holds::lifted_at.is_null()),
+        ))
+        .get_result(conn)
+    }
+
+    /// Lifts the legal hold and records the change in the audit trail.
+    pub fn lift(&self, conn: &mut PgConnection, note: Option<&str>) -> QueryResult<Self> {
+        conn.transaction(|conn| {
+            let hold = diesel::update(self)
+                .set(legal_holds::lifted_at.eq(now))
+                .get_result(conn)?;
+
+            LegalHoldAction::record(conn, self.id, LegalHoldActionKind::Lift, note)?;
+            Ok(hold)
+        })
+    }
+
+    pub fn actions(&self, conn: &mut PgConnection) -> QueryResult<Vec<LegalHoldAction>> {
+        legal_hold_actions::table
+            .filter(legal_hold_actions::legal_hold_id.eq(self.id))
+            .order(legal_hold_actions::id)
+            .load(conn)
+    }
+}
+
+/// The crate name and version number (`None` for holds of the whole crate)
+/// that a legal hold applies to. Crate names are normalized like the
+/// `canon_crate_name` SQL function does.
+type HoldKey = (String, Option<String>);
+
+/// A copy of all active legal holds, which every instance keeps in memory.
+///
+/// This is used by the download endpoint to check for legal holds without a
+/// database query, i.e. for cached version IDs and when the database is not
+/// available. The copy is refreshed periodically by a background thread of
+/// the server, so holds placed by other instances are picked up as well.
+#[derive(Debug, Default)]
+pub struct ActiveLegalHolds {
+    holds: RwLock<HashMap<HoldKey, LegalHold>>,
+}
+
+impl ActiveLegalHolds {
+    /// Loads the active legal holds from the database, and returns `true` if
+    /// they changed since the last refresh.
+    pub fn refresh(&self, conn: &mut PgConnection) -> QueryResult<bool> {
+        let rows: Vec<(LegalHold, String, Option<String>)> = legal_holds::table
+            .inner_join(crates::table)
+            .left_join(versions::table)
+            .filter(legal_holds::lifted_at.is_null())
+            .select((
+                legal_holds::all_columns,
+                crates::name,
+                versions::num.nullable(),
+            ))
+            .load(conn)?;
+
+        let holds = rows
+            .into_iter()
+            .map(|(hold, crate_name, version)| ((canonical_name(&crate_name), version), hold))
+            .collect::<HashMap<_, _>>();
+
+        let mut current = self.holds.write().unwrap_or_else(|e| e.into_inner());
+        let changed = *current != holds;
+        *current = holds;
+        Ok(changed)
+    }
+
+    /// Returns the active legal hold of the version, or of the whole crate.
+    pub fn get(&self, crate_name: &str, version: &str) -> Option<LegalHold> {
+        let holds = self.holds.read().unwrap_or_else(|e| e.into_inner());
+        let crate_name = canonical_name(crate_name);
+
+        holds
+            .get(&(crate_name.clone(), None))
+            .or_else(|| holds.get(&(crate_name, Some(version.to_string()))))
+            .cloned()
+    }
+}
+
+fn canonical_name(crate_name: &str) -> String {
+    crate_name.replace('-', "_").to_lowercase()
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = legal_holds, check_for_backend(diesel::pg::Pg))]
+pub struct NewLegalHold<'a> {
+    pub crate_id: i32,
+    pub version_id: Option<i32>,
+    pub takedown_request_id: Option<i32>,
+    pub reason: &'a str,
+    pub notice_url: Option<&'a str>,
+}
+
+impl NewLegalHold<'_> {
+    /// Places the legal hold, records it in the audit trail, and resolves the
+    /// takedown request that led to it.
+    pub fn place(&self, conn: &mut PgConnection, note: Option<&str>) -> QueryResult<LegalHold> {
+        conn.transaction(|conn| {
+            let hold: LegalHold = diesel::insert_into(legal_holds::table)
+                .values(self)
+                .get_result(conn)?;
+
+            LegalHoldAction::record(conn, hold.id, LegalHoldActionKind::Place, note)?;
+
+            if let Some(takedown_request_id) = self.takedown_request_id {
+                diesel::update(takedown_requests::table.find(takedown_request_id))
+                    .filter(takedown_requests::resolved_at.is_null())
+                    .set(takedown_requests::resolved_at.eq(now))
+                    .execute(conn)?;
+            }
+
+            Ok(hold)
+        })
+    }
+}
+
+/// An entry in the audit trail of a legal hold.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable)]
+pub struct LegalHoldAction {
+    pub id: i32,
+    pub legal_hold_id: i32,
+    pub action: String,
+    pub note: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl LegalHoldAction {
+    fn record(
+        conn: &mut PgConnection,
+        legal_hold_id: i32,
+        action: LegalHoldActionKind,
+        note: Option<&str>,
+    ) -> QueryResult<()> {
+        diesel::insert_into(legal_hold_actions::table)
+            .values((
+                legal_hold_actions::legal_hold_id.eq(legal_hold_id),
+                legal_hold_actions::action.eq(action.as_str()),
+                legal_hold_actions::note.eq(note),
+            ))
+            .execute(conn)?;
+
+        Ok(())
+    }
+}
+
+/// A request to take down a crate or version, e.g. a DMCA notice, that has
+/// been submitted through the intake endpoint.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable)]
+pub struct TakedownRequest {
+    pub id: i32,
+    pub crate_name: String,
+    pub version: Option<String>,
+    pub complainant_name: String,
+    pub complainant_email: String,
+    pub description: String,
+    pub created_at: NaiveDateTime,
+    pub resolved_at: Option<NaiveDateTime>,
+}
+
+impl TakedownRequest {
+    pub fn unresolved(conn: &mut PgConnection) -> QueryResult<Vec<Self>> {
+        takedown_requests::table
+            .filter(takedown_requests::resolved_at.is_null())
+            .order(takedown_requests::id)
+            .load(conn)
+    }
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = takedown_requests, check_for_backend(diesel::pg::Pg))]
+pub struct NewTakedownRequest<'a> {
+    pub crate_name: &'a str,
+    pub version: Option<&'a str>,
+    pub complainant_name: &'a str,
+    pub complainant_email: &'a str,
+    pub description: &'a str,
+}
+
+impl NewTakedownRequest<'_> {
+    pub fn insert(&self, conn: &mut PgConnection) -> QueryResult<TakedownRequest> {
+        diesel::insert_into(takedown_requests::table)
+            .values(self)
+            .get_result(conn)
+    }
+}
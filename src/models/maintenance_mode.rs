@@ -0,0 +1,55 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use std::time::Duration;
+
+use crate::schema::maintenance_mode;
+
+/// The maintenance mode as set through the admin API.
+///
+/// In contrast to the `MAINTENANCE_MODE` environment variable this is shared
+/// by all server instances and the background worker, which poll it
+/// periodically.
+#[derive(Clone, Copy, Debug, Queryable)]
+#[diesel(table_name = maintenance_mode)]
+pub struct MaintenanceModeSetting {
+    pub id: bool,
+    pub enabled: bool,
+    pub retry_after: i32,
+    pub updated_at: NaiveDateTime,
+}
+
+impl MaintenanceModeSetting {
+    /// Returns `None` if the maintenance mode has never been set through the
+    /// admin API.
+    pub fn load(conn: &mut PgConnection) -> QueryResult<Option<Self>> {
+        maintenance_mode::table.first(conn).optional()
+    }
+
+    pub fn save(
+        conn: &mut PgConnection,
+        enabled: bool,
+        retry_after: Duration,
+    ) -> QueryResult<Self> {
+        let retry_after = i32::try_from(retry_after.as_secs()).unwrap_or(i32::MAX);
+
+        diesel::insert_into(maintenance_mode::table)
+            .values((
+                maintenance_mode::enabled.eq(enabled),
+                maintenance_mode::retry_after.eq(retry_after),
+            ))
+            .on_conflict(maintenance_mode::id)
+            .do_update()
+            .set((
+                maintenance_mode::enabled.eq(excluded(maintenance_mode::enabled)),
+                maintenance_mode::retry_after.eq(excluded(maintenance_mode::retry_after)),
+                maintenance_mode::updated_at.eq(now),
+            ))
+            .get_result(conn)
+    }
+
+    pub fn retry_after(&self) -> Duration {
+        Duration::from_secs(self.retry_after.max(0) as u64)
+    }
+}
@@ -0,0 +1,143 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel::sql_types::{Integer, Timestamp};
+use diesel::upsert::excluded;
+
+use crate::schema::crate_maintenance_status;
+
+/// Whether the owners of a crate are still around to maintain it.
+///
+/// Crates are flagged as stale by the `flag_stale_crates` job if all of their
+/// owners have been inactive for a long time. Owners can confirm that a crate
+/// is still maintained, which removes the flag and resets the timer.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable, Selectable)]
+#[diesel(table_name = crate_maintenance_status, primary_key(crate_id))]
+pub struct CrateMaintenanceStatus {
+    pub crate_id: i32,
+    /// When the crate was flagged as stale, `None` if it is not flagged
+    pub flagged_at: Option<NaiveDateTime>,
+    /// When the owners were last asked to confirm the maintenance status
+    pub notified_at: Option<NaiveDateTime>,
+    pub confirmed_at: Option<NaiveDateTime>,
+    pub confirmed_by: Option<i32>,
+}
+
+#[derive(QueryableByName)]
+struct StaleCrate {
+    #[diesel(sql_type = Integer)]
+    crate_id: i32,
+}
+
+impl CrateMaintenanceStatus {
+    pub fn find(conn: &mut PgConnection, crate_id: i32) -> QueryResult<Option<Self>> {
+        crate_maintenance_status::table
+            .find(crate_id)
+            .select(CrateMaintenanceStatus::as_select())
+            .first(conn)
+            .optional()
+    }
+
+    /// Records that the user confirmed that the crate is still maintained.
+    pub fn confirm(conn: &mut PgConnection, crate_id: i32, user_id: i32) -> QueryResult<Self> {
+        diesel::insert_into(crate_maintenance_status::table)
+            .values((
+                crate_maintenance_status::crate_id.eq(crate_id),
+                crate_maintenance_status::confirmed_at.eq(now),
+                crate_maintenance_status::confirmed_by.eq(user_id),
+            ))
+            .on_conflict(crate_maintenance_status::crate_id)
+            .do_update()
+            .set((
+                crate_maintenance_status::flagged_at.eq(None::<NaiveDateTime>),
+                crate_maintenance_status::confirmed_at.eq(now),
+                crate_maintenance_status::confirmed_by.eq(user_id),
+            ))
+            .returning(CrateMaintenanceStatus::as_returning())
+            .get_result(conn)
+    }
+
+    /// Returns the IDs of all crates whose owners have all been inactive
+    /// since `cutoff`, see `stale_crates.sql`.
+    pub fn find_stale_crates(
+        conn: &mut PgConnection,
+        cutoff: NaiveDateTime,
+    ) -> QueryResult<Vec<i32>> {
+        let rows: Vec<StaleCrate> = diesel::sql_query(include_str!("stale_crates.sql"))
+            .bind::<Timestamp, _>(cutoff)
+            .load(conn)?;
+
+        Ok(rows.into_iter().map(|row| row.crate_id).collect())
+    }
+
+    /// Flags the crates as stale. Returns the IDs of the crates that were
+    /// not flagged before.
+    pub fn flag(
+        conn: &mut PgConnection,
+        crate_ids: &[i32],
+        flagged_at: NaiveDateTime,
+    ) -> QueryResult<Vec<i32>> {
+        let already_flagged: Vec<i32> = crate_maintenance_status::table
+            .filter(crate_maintenance_status::crate_id.eq_any(crate_ids))
+            .filter(crate_maintenance_status::flagged_at.is_not_null())
+            .select(crate_maintenance_status::crate_id)
+            .load(conn)?;
+
+        let newly_flagged = crate_ids
+            .iter()
+            .filter(|crate_id| !already_flagged.contains(*crate_id))
+            .copied()
+            .collect::<Vec<_>>();
+
+        if newly_flagged.is_empty() {
+            return Ok(newly_flagged);
+        }
+
+        let rows = newly_flagged
+            .iter()
+            .map(|crate_id| {
+                (
+                    crate_maintenance_status::crate_id.eq(crate_id),
+                    crate_maintenance_status::flagged_at.eq(flagged_at),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(crate_maintenance_status::table)
+            .values(rows)
+            .on_conflict(crate_maintenance_status::crate_id)
+            .do_update()
+            .set(
+                crate_maintenance_status::flagged_at
+                    .eq(excluded(crate_maintenance_status::flagged_at)),
+            )
+            .execute(conn)?;
+
+        Ok(newly_flagged)
+    }
+
+    /// Removes the flag from all crates that are not in `stale_crate_ids`
+    /// anymore, e.g. because one of their owners has been active again.
+    pub fn unflag_all_except(
+        conn: &mut PgConnection,
+        stale_crate_ids: &[i32],
+    ) -> QueryResult<usize> {
+        diesel::update(crate_maintenance_status::table)
+            .filter(crate_maintenance_status::flagged_at.is_not_null())
+            .filter(crate_maintenance_status::crate_id.ne_all(stale_crate_ids))
+            .set(crate_maintenance_status::flagged_at.eq(None::<NaiveDateTime>))
+            .execute(conn)
+    }
+
+    pub fn mark_notified(
+        conn: &mut PgConnection,
+        crate_id: i32,
+        notified_at: NaiveDateTime,
+    ) -> QueryResult<()> {
+        diesel::update(crate_maintenance_status::table.find(crate_id))
+            .set(crate_maintenance_status::notified_at.eq(notified_at))
+            .execute(conn)?;
+
+        Ok(())
+    }
+}
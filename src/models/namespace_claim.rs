@@ -0,0 +1,282 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use oauth2::AccessToken;
+
+use crate::app::App;
+use crate::domains;
+use crate::models::{Crate, User};
+use crate::schema::{crates, namespace_claims};
+use crate::sql::{canon_crate_name, length, starts_with};
+use crate::util::errors::{cargo_err, AppResult, NotFound};
+
+/// The minimum length of a namespace prefix, to avoid claims on overly
+/// generic prefixes like `rs-*`.
+const MIN_PREFIX_LENGTH: usize = 3;
+
+/// The way the ownership of a namespace claim is proven.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerificationMethod {
+    /// The claim was created by an admin of the GitHub organization and is
+    /// verified immediately.
+    GitHub,
+    /// The claim is verified once a `TXT` record with the verification token
+    /// has been added to the domain.
+    Dns,
+}
+
+impl VerificationMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerificationMethod::GitHub => "github",
+            VerificationMethod::Dns => "dns",
+        }
+    }
+}
+
+/// A crate name prefix (e.g. `acme-*`) reserved for the members of a GitHub
+/// organization.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable)]
+pub struct NamespaceClaim {
+    pub id: i32,
+    /// The canonical prefix, without the trailing separator
+    pub prefix: String,
+    pub github_org_id: i32,
+    pub github_org_login: String,
+    pub verification_method: String,
+    pub domain: Option<String>,
+    pub verification_token: String,
+    pub created_by: i32,
+    pub created_at: NaiveDateTime,
+    pub verified_at: Option<NaiveDateTime>,
+}
+
+impl NamespaceClaim {
+    pub fn is_verified(&self) -> bool {
+        self.verified_at.is_some()
+    }
+
+    /// The glob pattern of the crate names covered by this claim.
+    pub fn pattern(&self) -> String {
+        prefix_pattern(&self.prefix)
+    }
+
+    /// Returns `true` if the crate name is covered by this claim.
+    ///
+    /// Like crate names, the prefix is matched case-insensitively and without
+    /// distinguishing between `-` and `_`.
+    pub fn matches(&self, crate_name: &str) -> bool {
+        canonicalize(crate_name)
+            .strip_prefix(&self.prefix)
+            .and_then(|rest| rest.strip_prefix('_'))
+            .map_or(false, |rest| !rest.is_empty())
+    }
+
+    /// The name of the DNS record that has to contain the
+    /// [`dns_record_value`](Self::dns_record_value).
+    pub fn dns_record_name(&self) -> Option<String> {
+        self.domain
+            .as_deref()
+            .map(domains::verification_record_name)
+    }
+
+    pub fn dns_record_value(&self) -> String {
+        domains::verification_value(&self.verification_token)
+    }
+
+    /// Returns the verified claim covering the crate name, if there is one.
+    ///
+    /// The same rules as in [`matches`](Self::matches) are applied in SQL. If
+    /// multiple claims cover the crate name, the one with the longest prefix
+    /// is returned.
+    pub fn find_for_crate(conn: &mut PgConnection, crate_name: &str) -> QueryResult<Option<Self>> {
+        namespace_claims::table
+            .filter(namespace_claims::verified_at.is_not_null())
+            .filter(starts_with(
+                canon_crate_name(crate_name),
+                namespace_claims::prefix.concat("_"),
+            ))
+            .filter(canon_crate_name(crate_name).ne(namespace_claims::prefix.concat("_")))
+            .order(length(namespace_claims::prefix).desc())
+            .first(conn)
+            .optional()
+    }
+
+    /// Returns a verified claim that overlaps with the canonical prefix, if
+    /// there is one.
+    ///
+    /// Claims overlap if they have the same prefix, or if one of them covers
+    /// the prefix of the other one, e.g. `acme-*` and `acme-tools-*`.
+    pub fn find_overlapping(conn: &mut PgConnection, prefix: &str) -> QueryResult<Option<Self>> {
+        namespace_claims::table
+            .filter(namespace_claims::verified_at.is_not_null())
+            .filter(
+                namespace_claims::prefix
+                    .eq(prefix)
+                    .or(starts_with(prefix, namespace_claims::prefix.concat("_")))
+                    .or(starts_with(namespace_claims::prefix, format!("{prefix}_"))),
+            )
+            .order(length(namespace_claims::prefix).desc())
+            .first(conn)
+            .optional()
+    }
+
+    /// Marks the claim as verified.
+    pub fn verify(&self, conn: &mut PgConnection) -> QueryResult<Self> {
+        diesel::update(self)
+            .set(namespace_claims::verified_at.eq(diesel::dsl::now))
+            .get_result(conn)
+    }
+
+    /// Phones home to GitHub to ask if the user is an active member of the
+    /// organization owning this claim.
+    pub fn contains_user(&self, app: &App, user: &User) -> AppResult<bool> {
+        let token = AccessToken::new(user.gh_access_token.clone());
+        match app
+            .github
+            .org_membership(self.github_org_id, &user.gh_login, &token)
+        {
+            Ok(membership) => Ok(membership.state == "active"),
+            Err(e) if e.is::<NotFound>() => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = namespace_claims, check_for_backend(diesel::pg::Pg))]
+pub struct NewNamespaceClaim<'a> {
+    prefix: &'a str,
+    github_org_id: i32,
+    github_org_login: &'a str,
+    verification_method: &'static str,
+    domain: Option<&'a str>,
+    verification_token: String,
+    created_by: i32,
+    verified_at: Option<NaiveDateTime>,
+}
+
+impl<'a> NewNamespaceClaim<'a> {
+    pub fn new(
+        prefix: &'a str,
+        github_org_id: i32,
+        github_org_login: &'a str,
+        method: VerificationMethod,
+        domain: Option<&'a str>,
+        created_by: i32,
+    ) -> Self {
+        let verified_at = match method {
+            VerificationMethod::GitHub => Some(chrono::Utc::now().naive_utc()),
+            VerificationMethod::Dns => None,
+        };
+
+        Self {
+            prefix,
+            github_org_id,
+            github_org_login,
+            verification_method: method.as_str(),
+            domain,
+            verification_token: domains::generate_verification_token(),
+            created_by,
+            verified_at,
+        }
+    }
+
+    pub fn insert(&self, conn: &mut PgConnection) -> QueryResult<NamespaceClaim> {
+        diesel::insert_into(namespace_claims::table)
+            .values(self)
+            .get_result(conn)
+    }
+}
+
+/// Returns the existing crates that would be covered by a claim on the
+/// canonical prefix, ordered by name.
+pub fn crates_with_prefix(conn: &mut PgConnection, prefix: &str) -> QueryResult<Vec<Crate>> {
+    let pattern = format!("{prefix}_");
+
+    Crate::all()
+        .filter(starts_with(
+            canon_crate_name(crates::name),
+            pattern.as_str(),
+        ))
+        .filter(canon_crate_name(crates::name).ne(pattern.as_str()))
+        .order(crates::name)
+        .load(conn)
+}
+
+/// Validates a namespace prefix like `acme` or `acme-*` and returns its
+/// canonical form.
+pub fn canonical_prefix(prefix: &str) -> AppResult<String> {
+    let prefix = prefix.trim_end_matches('*').trim_end_matches(['-', '_']);
+
+    let mut chars = prefix.chars();
+    let valid = chars.next().map_or(false, |c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(cargo_err(&format_args!(
+            "invalid namespace prefix `{prefix}`, prefixes must start with a letter \
+             and only contain alphanumeric characters, `-` or `_`"
+        )));
+    }
+
+    if prefix.len() < MIN_PREFIX_LENGTH {
+        return Err(cargo_err(&format_args!(
+            "namespace prefixes must be at least {MIN_PREFIX_LENGTH} characters long"
+        )));
+    }
+
+    Ok(canonicalize(prefix))
+}
+
+/// The glob pattern of the crate names covered by the canonical prefix.
+pub fn prefix_pattern(prefix: &str) -> String {
+    format!("{}-*", prefix.replace('_', "-"))
+}
+
+/// Mirrors the `canon_crate_name` SQL function.
+fn canonicalize(name: &str) -> String {
+    name.to_lowercase().replace('-', "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim(prefix: &str) -> NamespaceClaim {
+        NamespaceClaim {
+            id: 1,
+            prefix: prefix.into(),
+            github_org_id: 1,
+            github_org_login: "acme".into(),
+            verification_method: "github".into(),
+            domain: None,
+            verification_token: "token".into(),
+            created_by: 1,
+            created_at: NaiveDateTime::default(),
+            verified_at: None,
+        }
+    }
+
+    #[test]
+    fn matches() {
+        let claim = claim("acme");
+        assert!(claim.matches("acme-foo"));
+        assert!(claim.matches("acme_foo"));
+        assert!(claim.matches("ACME-Foo"));
+        assert!(!claim.matches("acme"));
+        assert!(!claim.matches("acme-"));
+        assert!(!claim.matches("acmefoo"));
+        assert!(!claim.matches("foo-acme-bar"));
+    }
+
+    #[test]
+    fn prefix_validation() {
+        assert_eq!(canonical_prefix("acme").unwrap(), "acme");
+        assert_eq!(canonical_prefix("Acme-*").unwrap(), "acme");
+        assert_eq!(canonical_prefix("big-corp_").unwrap(), "big_corp");
+        assert_err!(canonical_prefix("ab"));
+        assert_err!(canonical_prefix("1acme"));
+        assert_err!(canonical_prefix("ac*me"));
+        assert_err!(canonical_prefix(""));
+    }
+}
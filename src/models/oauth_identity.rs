@@ -0,0 +1,140 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::models::User;
+use crate::schema::{oauth_identities, users};
+use crate::sql::lower;
+
+/// An account at an OAuth provider that a user can sign in with.
+///
+/// Every user can link at most one account per provider, and every account
+/// can only be linked to a single user.
+#[derive(Debug, Identifiable, Queryable, Selectable, Associations)]
+#[diesel(table_name = oauth_identities, belongs_to(User))]
+pub struct OAuthIdentity {
+    pub id: i32,
+    pub user_id: i32,
+    pub provider: String,
+    pub provider_user_id: String,
+    pub login: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl OAuthIdentity {
+    /// Finds the identity of an account at the given provider.
+    pub fn find(
+        conn: &mut PgConnection,
+        provider: &str,
+        provider_user_id: &str,
+    ) -> QueryResult<Option<Self>> {
+        oauth_identities::table
+            .filter(oauth_identities::provider.eq(provider))
+            .filter(oauth_identities::provider_user_id.eq(provider_user_id))
+            .select(OAuthIdentity::as_select())
+            .first(conn)
+            .optional()
+    }
+
+    /// Returns all identities of the user, ordered by provider.
+    pub fn for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<Vec<Self>> {
+        oauth_identities::table
+            .filter(oauth_identities::user_id.eq(user_id))
+            .order(oauth_identities::provider)
+            .select(OAuthIdentity::as_select())
+            .load(conn)
+    }
+
+    /// Links an account at the given provider to the user.
+    pub fn link(
+        conn: &mut PgConnection,
+        user_id: i32,
+        provider: &str,
+        provider_user_id: &str,
+        login: &str,
+    ) -> QueryResult<Self> {
+        diesel::insert_into(oauth_identities::table)
+            .values((
+                oauth_identities::user_id.eq(user_id),
+                oauth_identities::provider.eq(provider),
+                oauth_identities::provider_user_id.eq(provider_user_id),
+                oauth_identities::login.eq(login),
+            ))
+            .returning(OAuthIdentity::as_returning())
+            .get_result(conn)
+    }
+
+    /// Links an account at the given provider to the user, or updates the
+    /// login of an existing identity of that account.
+    pub fn upsert(
+        conn: &mut PgConnection,
+        user_id: i32,
+        provider: &str,
+        provider_user_id: &str,
+        login: &str,
+    ) -> QueryResult<()> {
+        diesel::insert_into(oauth_identities::table)
+            .values((
+                oauth_identities::user_id.eq(user_id),
+                oauth_identities::provider.eq(provider),
+                oauth_identities::provider_user_id.eq(provider_user_id),
+                oauth_identities::login.eq(login),
+            ))
+            .on_conflict((
+                oauth_identities::provider,
+                oauth_identities::provider_user_id,
+            ))
+            .do_update()
+            .set(oauth_identities::login.eq(login))
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Returns the crates.io login for a user that signed in with an account
+    /// at the given provider, which is used if no GitHub account is linked.
+    ///
+    /// The login is suffixed with the provider, e.g. `jdoe@gitlab`, so that
+    /// it can not collide with GitHub logins. If the login is already taken,
+    /// the ID of the account at the provider is used instead.
+    pub fn crates_io_login(
+        conn: &mut PgConnection,
+        provider: &str,
+        provider_user_id: &str,
+        login: &str,
+    ) -> QueryResult<String> {
+        let login = format!("{login}@{provider}");
+
+        let taken = diesel::select(diesel::dsl::exists(
+            users::table.filter(lower(users::gh_login).eq(login.to_lowercase())),
+        ))
+        .get_result(conn)?;
+
+        if taken {
+            Ok(format!("{provider_user_id}@{provider}"))
+        } else {
+            Ok(login)
+        }
+    }
+
+    /// Updates the login of the identity, which can change at the provider.
+    pub fn update_login(&self, conn: &mut PgConnection, login: &str) -> QueryResult<()> {
+        if self.login != login {
+            diesel::update(self)
+                .set(oauth_identities::login.eq(login))
+                .execute(conn)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the identity of the user at the given provider. Returns `false`
+    /// if the user had no linked account at that provider.
+    pub fn unlink(conn: &mut PgConnection, user_id: i32, provider: &str) -> QueryResult<bool> {
+        let deleted = diesel::delete(oauth_identities::table)
+            .filter(oauth_identities::user_id.eq(user_id))
+            .filter(oauth_identities::provider.eq(provider))
+            .execute(conn)?;
+
+        Ok(deleted > 0)
+    }
+}
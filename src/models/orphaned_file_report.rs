@@ -0,0 +1,46 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::schema::orphaned_file_reports;
+
+/// The result of a run of the `cleanup_orphaned_files` background job.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable, Selectable)]
+pub struct OrphanedFileReport {
+    pub id: i32,
+    /// `true` if the orphaned files were only reported, but not deleted
+    pub dry_run: bool,
+    pub orphaned_count: i32,
+    pub deleted_count: i32,
+    /// Paths of the orphaned files, which is truncated for large reports
+    pub orphaned_files: Vec<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl OrphanedFileReport {
+    /// Returns the most recent reports, newest first.
+    pub fn recent(conn: &mut PgConnection, limit: i64) -> QueryResult<Vec<Self>> {
+        orphaned_file_reports::table
+            .order(orphaned_file_reports::id.desc())
+            .limit(limit)
+            .select(OrphanedFileReport::as_select())
+            .load(conn)
+    }
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = orphaned_file_reports, check_for_backend(diesel::pg::Pg))]
+pub struct NewOrphanedFileReport<'a> {
+    pub dry_run: bool,
+    pub orphaned_count: i32,
+    pub deleted_count: i32,
+    pub orphaned_files: &'a [String],
+}
+
+impl NewOrphanedFileReport<'_> {
+    pub fn insert(&self, conn: &mut PgConnection) -> QueryResult<OrphanedFileReport> {
+        diesel::insert_into(orphaned_file_reports::table)
+            .values(self)
+            .returning(OrphanedFileReport::as_returning())
+            .get_result(conn)
+    }
+}
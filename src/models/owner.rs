@@ -72,9 +72,18 @@ impl Owner {
                 app, conn, name, req_user,
             )?))
         } else {
-            users::table
+            // Users of other OAuth providers have no GitHub ID, but can be
+            // told apart from users whose GitHub ID is unknown by their login
+            let query = users::table
                 .filter(lower(users::gh_login).eq(name.to_lowercase()))
-                .filter(users::gh_id.ne(-1))
+                .into_boxed();
+            let query = if User::is_github_login(name) {
+                query.filter(users::gh_id.ne(-1))
+            } else {
+                query
+            };
+
+            query
                 .order(users::gh_id.desc())
                 .first(conn)
                 .map(Owner::User)
@@ -0,0 +1,63 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::{now, IntervalDsl};
+use diesel::prelude::*;
+
+use crate::schema::password_resets;
+use crate::util::token::{HashedToken, PlainToken};
+
+/// Password reset links are only valid for this many hours.
+const MAX_AGE_HOURS: i32 = 1;
+
+/// A request to reset the password of a user, which is confirmed by a link
+/// that is sent to the verified email address of the user.
+#[derive(Debug, Identifiable, Queryable, Selectable)]
+pub struct PasswordReset {
+    pub id: i32,
+    pub user_id: i32,
+    pub hashed_token: HashedToken,
+    pub created_at: NaiveDateTime,
+    pub used_at: Option<NaiveDateTime>,
+}
+
+impl PasswordReset {
+    /// Creates a new password reset, returning the plaintext token that has
+    /// to be sent to the user.
+    pub fn create(conn: &mut PgConnection, user_id: i32) -> QueryResult<PlainToken> {
+        let token = PlainToken::generate();
+
+        diesel::insert_into(password_resets::table)
+            .values((
+                password_resets::user_id.eq(user_id),
+                password_resets::hashed_token.eq(token.hashed()),
+            ))
+            .execute(conn)?;
+
+        Ok(token)
+    }
+
+    /// Finds the unused and unexpired password reset with the given token.
+    pub fn find_valid(conn: &mut PgConnection, token: &str) -> QueryResult<Option<Self>> {
+        let Some(token) = HashedToken::parse(token) else {
+            return Ok(None);
+        };
+
+        password_resets::table
+            .filter(password_resets::hashed_token.eq(token))
+            .filter(password_resets::used_at.is_null())
+            .filter(password_resets::created_at.gt(now - MAX_AGE_HOURS.hours()))
+            .select(PasswordReset::as_select())
+            .first(conn)
+            .optional()
+    }
+
+    /// Marks this and all other pending password resets of the user as used.
+    pub fn mark_used(&self, conn: &mut PgConnection) -> QueryResult<()> {
+        diesel::update(password_resets::table)
+            .filter(password_resets::user_id.eq(self.user_id))
+            .filter(password_resets::used_at.is_null())
+            .set(password_resets::used_at.eq(now))
+            .execute(conn)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use chrono::{Duration, NaiveDateTime};
+use diesel::prelude::*;
+
+use crate::schema::{crates, publish_alerts, publish_details, versions};
+use crate::util::signing::Signer;
+
+/// How long the yank links in publish alert emails can be used.
+const YANK_LINK_VALIDITY_DAYS: i64 = 7;
+
+/// Details about the publish of a version, which are compared with the
+/// previous publishes of the crate to detect suspicious publishes.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable, Selectable, Insertable)]
+#[diesel(table_name = publish_details, primary_key(version_id))]
+pub struct PublishDetails {
+    pub version_id: i32,
+    pub api_token_id: Option<i32>,
+    /// Country code of the publish request, if known
+    pub country: Option<String>,
+    pub has_build_script: bool,
+    /// Paths of the binary files that exceed `LARGE_BINARY_FILE_SIZE`
+    pub large_binary_files: Vec<String>,
+    /// Hash of the files in the crate file, which does not depend on the
+    /// crate name or version
+    pub content_hash: Option<String>,
+}
+
+impl PublishDetails {
+    pub fn insert(&self, conn: &mut PgConnection) -> QueryResult<()> {
+        diesel::insert_into(publish_details::table)
+            .values(self)
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    pub fn find(conn: &mut PgConnection, version_id: i32) -> QueryResult<Option<Self>> {
+        publish_details::table
+            .find(version_id)
+            .select(PublishDetails::as_select())
+            .first(conn)
+            .optional()
+    }
+
+    /// Returns the oldest version of another crate with the same content
+    /// hash, e.g. because the crate is republished under a different name.
+    pub fn find_duplicate(
+        conn: &mut PgConnection,
+        content_hash: &str,
+        crate_id: i32,
+    ) -> QueryResult<Option<(i32, String, String)>> {
+        publish_details::table
+            .inner_join(versions::table.inner_join(crates::table))
+            .filter(publish_details::content_hash.eq(content_hash))
+            .filter(versions::crate_id.ne(crate_id))
+            .order(versions::id)
+            .select((versions::id, crates::name, versions::num))
+            .first(conn)
+            .optional()
+    }
+}
+
+/// A publish that was flagged as suspicious.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable, Selectable)]
+pub struct PublishAlert {
+    pub id: i32,
+    pub version_id: i32,
+    pub reasons: Vec<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl PublishAlert {
+    /// Flags the version as suspicious. Returns `None` if the version was
+    /// already flagged before, so that owners are not alerted twice.
+    pub fn create(
+        conn: &mut PgConnection,
+        version_id: i32,
+        reasons: &[String],
+    ) -> QueryResult<Option<Self>> {
+        diesel::insert_into(publish_alerts::table)
+            .values((
+                publish_alerts::version_id.eq(version_id),
+                publish_alerts::reasons.eq(reasons),
+            ))
+            .on_conflict_do_nothing()
+            .returning(PublishAlert::as_returning())
+            .get_result(conn)
+            .optional()
+    }
+
+    /// Creates the token of the one-click yank link for an owner of the
+    /// flagged version.
+    ///
+    /// The token is signed instead of stored, and contains the owner and the
+    /// expiry time of the link, e.g. `42.1692000000.<signature>`.
+    pub fn yank_token(
+        signer: &Signer,
+        version_id: i32,
+        user_id: i32,
+        now: NaiveDateTime,
+    ) -> String {
+        let expires = (now + Duration::days(YANK_LINK_VALIDITY_DAYS)).timestamp();
+        let signature = signer.sign(&yank_message(version_id, user_id, expires));
+        format!("{user_id}.{expires}.{signature}")
+    }
+
+    /// Returns the ID of the owner that the yank link was created for, or
+    /// `None` if the token is invalid or has expired.
+    pub fn verify_yank_token(
+        signer: &Signer,
+        version_id: i32,
+        token: &str,
+        now: NaiveDateTime,
+    ) -> Option<i32> {
+        let mut parts = token.splitn(3, '.');
+        let user_id = parts.next()?.parse::<i32>().ok()?;
+        let expires = parts.next()?.parse::<i64>().ok()?;
+        let signature = parts.next()?;
+
+        if expires < now.timestamp() {
+            return None;
+        }
+
+        signer
+            .verify(&yank_message(version_id, user_id, expires), signature)
+            .then_some(user_id)
+    }
+}
+
+fn yank_message(version_id: i32, user_id: i32, expires: i64) -> String {
+    format!("alert-yank:{version_id}:{user_id}:{expires}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn yank_token() {
+        let signer = Signer::new(b"secret");
+        let now = NaiveDate::from_ymd_opt(2023, 8, 14)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+
+        let token = PublishAlert::yank_token(&signer, 1, 42, now);
+        assert!(token.starts_with("42.1692612000."));
+
+        let verify = |version_id, token: &str, now| {
+            PublishAlert::verify_yank_token(&signer, version_id, token, now)
+        };
+
+        assert_eq!(verify(1, &token, now), Some(42));
+        assert_eq!(verify(1, &token, now + Duration::days(7)), Some(42));
+        assert_eq!(verify(1, &token, now + Duration::days(8)), None);
+        assert_eq!(verify(2, &token, now), None);
+        assert_eq!(verify(1, &token.replacen("42", "43", 1), now), None);
+        assert_eq!(verify(1, "42.1692612000", now), None);
+        assert_eq!(verify(1, "", now), None);
+    }
+}
@@ -0,0 +1,132 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::dsl::now;
+use diesel::prelude::*;
+use serde_json::Value;
+
+use crate::schema::publish_idempotency_keys;
+
+/// How long a publish can be in progress before it is considered abandoned,
+/// e.g. because the server was restarted, and can be retried.
+const STALE_AFTER_MINUTES: i64 = 10;
+
+/// How long idempotency keys are kept before they are deleted by the
+/// `cleanup_idempotency_keys` background job.
+pub const IDEMPOTENCY_KEY_RETENTION_HOURS: i64 = 24;
+
+/// The state of a publish with an idempotency key, as returned by
+/// [`PublishIdempotencyKey::begin()`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdempotentPublish {
+    /// The publish should proceed, since it has not been attempted before, or
+    /// the previous attempt has been abandoned
+    Started,
+    /// The publish was completed before, with the given response
+    Completed(Value),
+    /// Another request with the same key is still in progress
+    InProgress,
+    /// The key was used before for a different request body
+    Mismatch,
+}
+
+/// A publish request with an `Idempotency-Key` header.
+#[derive(Clone, Debug, PartialEq, Queryable, Selectable)]
+pub struct PublishIdempotencyKey {
+    pub user_id: i32,
+    pub key: String,
+    pub request_hash: String,
+    /// `None` while the publish is in progress
+    pub response: Option<Value>,
+    pub created_at: NaiveDateTime,
+    pub completed_at: Option<NaiveDateTime>,
+}
+
+impl PublishIdempotencyKey {
+    /// Records that a publish with the key is in progress, unless a publish
+    /// with the same key has been started before.
+    ///
+    /// This needs to be committed before the publish itself is started, so
+    /// that concurrent retries can see it.
+    pub fn begin(
+        conn: &mut PgConnection,
+        user_id: i32,
+        key: &str,
+        request_hash: &str,
+    ) -> QueryResult<IdempotentPublish> {
+        conn.transaction(|conn| {
+            let inserted = diesel::insert_into(publish_idempotency_keys::table)
+                .values((
+                    publish_idempotency_keys::user_id.eq(user_id),
+                    publish_idempotency_keys::key.eq(key),
+                    publish_idempotency_keys::request_hash.eq(request_hash),
+                ))
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+
+            if inserted > 0 {
+                return Ok(IdempotentPublish::Started);
+            }
+
+            let existing: Self = publish_idempotency_keys::table
+                .find((user_id, key))
+                .select(PublishIdempotencyKey::as_select())
+                .for_update()
+                .first(conn)?;
+
+            if existing.request_hash != request_hash {
+                return Ok(IdempotentPublish::Mismatch);
+            }
+
+            if let Some(response) = existing.response {
+                return Ok(IdempotentPublish::Completed(response));
+            }
+
+            let stale_before = Utc::now().naive_utc() - Duration::minutes(STALE_AFTER_MINUTES);
+            if existing.created_at > stale_before {
+                return Ok(IdempotentPublish::InProgress);
+            }
+
+            diesel::update(publish_idempotency_keys::table.find((user_id, key)))
+                .set(publish_idempotency_keys::created_at.eq(now))
+                .execute(conn)?;
+
+            Ok(IdempotentPublish::Started)
+        })
+    }
+
+    /// Saves the response of the publish. This should happen in the same
+    /// transaction as the publish itself.
+    pub fn complete(
+        conn: &mut PgConnection,
+        user_id: i32,
+        key: &str,
+        response: &Value,
+    ) -> QueryResult<()> {
+        diesel::update(publish_idempotency_keys::table.find((user_id, key)))
+            .set((
+                publish_idempotency_keys::response.eq(response),
+                publish_idempotency_keys::completed_at.eq(now),
+            ))
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Forgets a failed publish, so that it can be retried right away.
+    pub fn abandon(conn: &mut PgConnection, user_id: i32, key: &str) -> QueryResult<()> {
+        diesel::delete(publish_idempotency_keys::table.find((user_id, key)))
+            .filter(publish_idempotency_keys::response.is_null())
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Deletes all keys that are older than the retention period.
+    pub fn delete_expired(conn: &mut PgConnection) -> QueryResult<usize> {
+        let expired_before =
+            Utc::now().naive_utc() - Duration::hours(IDEMPOTENCY_KEY_RETENTION_HOURS);
+
+        diesel::delete(publish_idempotency_keys::table)
+            .filter(publish_idempotency_keys::created_at.lt(expired_before))
+            .execute(conn)
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::domains;
+use crate::models::OwnerKind;
+use crate::schema::{crate_owners, publisher_verifications};
+
+/// The way a user proves control of a domain or GitHub organization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum PublisherVerificationMethod {
+    /// A `TXT` record with the verification token on the domain
+    #[serde(rename = "dns")]
+    Dns,
+    /// A file with the verification token in the `.well-known` directory of
+    /// the domain
+    #[serde(rename = "well_known")]
+    WellKnown,
+    /// Admin membership in the GitHub organization, which is verified
+    /// immediately
+    #[serde(rename = "github")]
+    GitHub,
+}
+
+impl PublisherVerificationMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PublisherVerificationMethod::Dns => "dns",
+            PublisherVerificationMethod::WellKnown => "well_known",
+            PublisherVerificationMethod::GitHub => "github",
+        }
+    }
+}
+
+/// A domain or GitHub organization that a user has proven (or is in the
+/// process of proving) control of.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable)]
+pub struct PublisherVerification {
+    pub id: i32,
+    pub user_id: i32,
+    pub method: String,
+    pub domain: Option<String>,
+    pub github_org_id: Option<i32>,
+    pub github_org_login: Option<String>,
+    pub verification_token: String,
+    pub created_at: NaiveDateTime,
+    pub verified_at: Option<NaiveDateTime>,
+}
+
+impl PublisherVerification {
+    pub fn is_verified(&self) -> bool {
+        self.verified_at.is_some()
+    }
+
+    pub fn verification_value(&self) -> String {
+        domains::verification_value(&self.verification_token)
+    }
+
+    /// Marks the verification as verified.
+    pub fn verify(&self, conn: &mut PgConnection) -> QueryResult<Self> {
+        diesel::update(self)
+            .set(publisher_verifications::verified_at.eq(diesel::dsl::now))
+            .get_result(conn)
+    }
+
+    /// Returns the verified verifications of the user owners of the given
+    /// crates, as `(crate_id, verification)` pairs.
+    pub fn verified_for_crates(
+        conn: &mut PgConnection,
+        crate_ids: &[i32],
+    ) -> QueryResult<Vec<(i32, Self)>> {
+        crate_owners::table
+            .inner_join(
+                publisher_verifications::table
+                    .on(publisher_verifications::user_id.eq(crate_owners::owner_id)),
+            )
+            .filter(crate_owners::crate_id.eq_any(crate_ids))
+            .filter(crate_owners::owner_kind.eq(OwnerKind::User as i32))
+            .filter(crate_owners::deleted.eq(false))
+            .filter(publisher_verifications::verified_at.is_not_null())
+            .select((crate_owners::crate_id, publisher_verifications::all_columns))
+            .load(conn)
+    }
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = publisher_verifications, check_for_backend(diesel::pg::Pg))]
+pub struct NewPublisherVerification<'a> {
+    user_id: i32,
+    method: &'static str,
+    domain: Option<&'a str>,
+    github_org_id: Option<i32>,
+    github_org_login: Option<&'a str>,
+    verification_token: String,
+    verified_at: Option<NaiveDateTime>,
+}
+
+impl<'a> NewPublisherVerification<'a> {
+    /// A pending verification of a domain via DNS or a `.well-known` file.
+    pub fn domain(user_id: i32, method: PublisherVerificationMethod, domain: &'a str) -> Self {
+        Self {
+            user_id,
+            method: method.as_str(),
+            domain: Some(domain),
+            github_org_id: None,
+            github_org_login: None,
+            verification_token: domains::generate_verification_token(),
+            verified_at: None,
+        }
+    }
+
+    /// A verification of a GitHub organization, whose admin membership has
+    /// already been checked.
+    pub fn github_org(user_id: i32, org_id: i32, org_login: &'a str) -> Self {
+        Self {
+            user_id,
+            method: PublisherVerificationMethod::GitHub.as_str(),
+            domain: None,
+            github_org_id: Some(org_id),
+            github_org_login: Some(org_login),
+            verification_token: domains::generate_verification_token(),
+            verified_at: Some(chrono::Utc::now().naive_utc()),
+        }
+    }
+
+    /// Inserts the verification, or returns `None` if the user already has a
+    /// verification for the same domain or GitHub organization.
+    pub fn insert(&self, conn: &mut PgConnection) -> QueryResult<Option<PublisherVerification>> {
+        diesel::insert_into(publisher_verifications::table)
+            .values(self)
+            .on_conflict_do_nothing()
+            .get_result(conn)
+            .optional()
+    }
+}
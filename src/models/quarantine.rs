@@ -0,0 +1,149 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::prelude::*;
+
+use crate::schema::{crate_quarantines, versions};
+
+/// The review state of a quarantined crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuarantineStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl QuarantineStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuarantineStatus::Pending => "pending",
+            QuarantineStatus::Approved => "approved",
+            QuarantineStatus::Rejected => "rejected",
+        }
+    }
+}
+
+/// A crate that was published by a new account, or whose content is identical
+/// to another crate, and is flagged in the API until it has been reviewed.
+///
+/// Quarantined crates can still be downloaded. Rejecting a crate yanks all of
+/// its versions and prevents further publishes.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable, Selectable)]
+#[diesel(primary_key(crate_id))]
+pub struct CrateQuarantine {
+    pub crate_id: i32,
+    /// The account that published the crate, `None` if it has been deleted
+    pub user_id: Option<i32>,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub reviewed_at: Option<NaiveDateTime>,
+    pub note: Option<String>,
+    /// A version of another crate with identical content
+    pub duplicate_of: Option<i32>,
+}
+
+impl CrateQuarantine {
+    /// Quarantines the crate. Returns `None` if the crate has been
+    /// quarantined before, so that reviewed crates are not flagged again.
+    pub fn create(
+        conn: &mut PgConnection,
+        crate_id: i32,
+        user_id: i32,
+    ) -> QueryResult<Option<Self>> {
+        diesel::insert_into(crate_quarantines::table)
+            .values((
+                crate_quarantines::crate_id.eq(crate_id),
+                crate_quarantines::user_id.eq(user_id),
+            ))
+            .on_conflict_do_nothing()
+            .returning(CrateQuarantine::as_returning())
+            .get_result(conn)
+            .optional()
+    }
+
+    /// Quarantines the crate because its content is identical to the given
+    /// version of another crate. A crate that is already quarantined keeps its
+    /// status, but the duplicate is recorded for the review.
+    pub fn create_for_duplicate(
+        conn: &mut PgConnection,
+        crate_id: i32,
+        user_id: i32,
+        duplicate_of: i32,
+    ) -> QueryResult<Self> {
+        diesel::insert_into(crate_quarantines::table)
+            .values((
+                crate_quarantines::crate_id.eq(crate_id),
+                crate_quarantines::user_id.eq(user_id),
+                crate_quarantines::duplicate_of.eq(duplicate_of),
+            ))
+            .on_conflict(crate_quarantines::crate_id)
+            .do_update()
+            .set(crate_quarantines::duplicate_of.eq(duplicate_of))
+            .returning(CrateQuarantine::as_returning())
+            .get_result(conn)
+    }
+
+    pub fn find(conn: &mut PgConnection, crate_id: i32) -> QueryResult<Option<Self>> {
+        crate_quarantines::table
+            .find(crate_id)
+            .select(CrateQuarantine::as_select())
+            .first(conn)
+            .optional()
+    }
+
+    /// Returns the crates waiting for review, oldest first.
+    pub fn pending(conn: &mut PgConnection) -> QueryResult<Vec<Self>> {
+        crate_quarantines::table
+            .filter(crate_quarantines::status.eq(QuarantineStatus::Pending.as_str()))
+            .order(crate_quarantines::created_at)
+            .select(CrateQuarantine::as_select())
+            .load(conn)
+    }
+
+    /// Returns the quarantine status of all given crates that are pending
+    /// review or have been rejected. Approved crates are not flagged anymore.
+    pub fn flagged_for_crates(
+        conn: &mut PgConnection,
+        crate_ids: &[i32],
+    ) -> QueryResult<Vec<(i32, String)>> {
+        crate_quarantines::table
+            .filter(crate_quarantines::crate_id.eq_any(crate_ids))
+            .filter(crate_quarantines::status.ne(QuarantineStatus::Approved.as_str()))
+            .select((crate_quarantines::crate_id, crate_quarantines::status))
+            .load(conn)
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.status == QuarantineStatus::Pending.as_str()
+    }
+
+    pub fn is_rejected(&self) -> bool {
+        self.status == QuarantineStatus::Rejected.as_str()
+    }
+
+    /// Approves or rejects the crate. Rejecting it also yanks all of its
+    /// versions, so the caller needs to sync the index afterwards.
+    pub fn review(
+        &self,
+        conn: &mut PgConnection,
+        status: QuarantineStatus,
+        note: Option<&str>,
+    ) -> QueryResult<Self> {
+        conn.transaction(|conn| {
+            if status == QuarantineStatus::Rejected {
+                diesel::update(versions::table)
+                    .filter(versions::crate_id.eq(self.crate_id))
+                    .set(versions::yanked.eq(true))
+                    .execute(conn)?;
+            }
+
+            diesel::update(self)
+                .set((
+                    crate_quarantines::status.eq(status.as_str()),
+                    crate_quarantines::reviewed_at.eq(now),
+                    crate_quarantines::note.eq(note),
+                ))
+                .returning(CrateQuarantine::as_returning())
+                .get_result(conn)
+        })
+    }
+}
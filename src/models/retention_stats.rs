@@ -0,0 +1,47 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::prelude::*;
+
+use crate::schema::data_retention_stats;
+
+/// The number of rows that were purged by the `purge_expired_data` background
+/// job for one kind of data.
+#[derive(Clone, Debug, PartialEq, Eq, Queryable, Selectable)]
+#[diesel(table_name = data_retention_stats)]
+pub struct RetentionStats {
+    pub kind: String,
+    pub purged_total: i64,
+    pub last_purged: i64,
+    pub last_run_at: NaiveDateTime,
+}
+
+impl RetentionStats {
+    pub fn all(conn: &mut PgConnection) -> QueryResult<Vec<Self>> {
+        data_retention_stats::table
+            .order(data_retention_stats::kind)
+            .select(RetentionStats::as_select())
+            .load(conn)
+    }
+
+    /// Records the number of rows that were purged by a run of the job.
+    pub fn record(conn: &mut PgConnection, kind: &str, purged: usize) -> QueryResult<()> {
+        let purged = purged as i64;
+
+        diesel::insert_into(data_retention_stats::table)
+            .values((
+                data_retention_stats::kind.eq(kind),
+                data_retention_stats::purged_total.eq(purged),
+                data_retention_stats::last_purged.eq(purged),
+            ))
+            .on_conflict(data_retention_stats::kind)
+            .do_update()
+            .set((
+                data_retention_stats::purged_total.eq(data_retention_stats::purged_total + purged),
+                data_retention_stats::last_purged.eq(purged),
+                data_retention_stats::last_run_at.eq(now),
+            ))
+            .execute(conn)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+-- Crates that were created before the cutoff, whose maintenance status has not
+-- been confirmed since, and whose user owners have all been inactive since.
+-- Signing in, using an API token and publishing a version count as activity.
+SELECT crates.id AS crate_id
+FROM crates
+LEFT JOIN crate_maintenance_status ON crate_maintenance_status.crate_id = crates.id
+WHERE crates.created_at < $1
+  AND (crate_maintenance_status.confirmed_at IS NULL OR crate_maintenance_status.confirmed_at < $1)
+  AND EXISTS (
+    SELECT 1 FROM crate_owners
+    WHERE crate_owners.crate_id = crates.id
+      AND crate_owners.owner_kind = 0
+      AND NOT crate_owners.deleted
+  )
+  AND NOT EXISTS (
+    SELECT 1 FROM crate_owners
+    WHERE crate_owners.crate_id = crates.id
+      AND NOT crate_owners.deleted
+      AND (
+        -- The members of teams are only known to GitHub, so crates with team
+        -- owners are never flagged
+        crate_owners.owner_kind <> 0
+        OR EXISTS (
+          SELECT 1 FROM users
+          WHERE users.id = crate_owners.owner_id AND users.created_at >= $1
+        )
+        OR EXISTS (
+          SELECT 1 FROM user_sessions
+          WHERE user_sessions.user_id = crate_owners.owner_id AND user_sessions.last_seen_at >= $1
+        )
+        OR EXISTS (
+          SELECT 1 FROM api_tokens
+          WHERE api_tokens.user_id = crate_owners.owner_id AND api_tokens.last_used_at >= $1
+        )
+        OR EXISTS (
+          SELECT 1 FROM versions
+          WHERE versions.published_by = crate_owners.owner_id AND versions.created_at >= $1
+        )
+      )
+  )
@@ -0,0 +1,76 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::models::{User, VersionAction};
+use crate::schema::{advisory_notifications, crate_notifications, crate_subscriptions};
+
+#[derive(Debug, Clone, Copy, Queryable, Identifiable, Associations)]
+#[diesel(
+    table_name = crate_subscriptions,
+    check_for_backend(diesel::pg::Pg),
+    primary_key(user_id, crate_id),
+    belongs_to(User),
+)]
+pub struct CrateSubscription {
+    pub user_id: i32,
+    pub crate_id: i32,
+    pub new_versions: bool,
+    pub yanks: bool,
+    pub created_at: NaiveDateTime,
+    pub advisories: bool,
+}
+
+#[derive(Debug, Clone, Copy, Insertable)]
+#[diesel(table_name = crate_subscriptions, check_for_backend(diesel::pg::Pg))]
+pub struct NewCrateSubscription {
+    pub user_id: i32,
+    pub crate_id: i32,
+    pub new_versions: bool,
+    pub yanks: bool,
+    pub advisories: bool,
+}
+
+impl NewCrateSubscription {
+    /// Inserts the subscription, or updates the notification preferences of an
+    /// existing subscription.
+    pub fn upsert(&self, conn: &mut PgConnection) -> QueryResult<CrateSubscription> {
+        use diesel::upsert::excluded;
+
+        diesel::insert_into(crate_subscriptions::table)
+            .values(self)
+            .on_conflict((crate_subscriptions::user_id, crate_subscriptions::crate_id))
+            .do_update()
+            .set((
+                crate_subscriptions::new_versions.eq(excluded(crate_subscriptions::new_versions)),
+                crate_subscriptions::yanks.eq(excluded(crate_subscriptions::yanks)),
+                crate_subscriptions::advisories.eq(excluded(crate_subscriptions::advisories)),
+            ))
+            .get_result(conn)
+    }
+}
+
+/// Records a new version or yank event that subscribers of the crate will be
+/// notified about by the next notification digest.
+pub fn insert_crate_notification(
+    conn: &mut PgConnection,
+    version_id: i32,
+    action: VersionAction,
+) -> QueryResult<usize> {
+    diesel::insert_into(crate_notifications::table)
+        .values((
+            crate_notifications::version_id.eq(version_id),
+            crate_notifications::action.eq(action),
+        ))
+        .execute(conn)
+}
+
+/// Records a new or updated advisory that subscribers of the affected crate
+/// will be notified about by the next notification digest.
+pub fn insert_advisory_notification(
+    conn: &mut PgConnection,
+    advisory_id: i32,
+) -> QueryResult<usize> {
+    diesel::insert_into(advisory_notifications::table)
+        .values(advisory_notifications::advisory_id.eq(advisory_id))
+        .execute(conn)
+}
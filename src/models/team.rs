@@ -202,7 +202,8 @@ fn can_add_team(app: &App, org_id: i32, team_id: i32, user: &User) -> AppResult<
         || is_gh_org_owner(app, org_id, user)?)
 }
 
-fn is_gh_org_owner(app: &App, org_id: i32, user: &User) -> AppResult<bool> {
+/// Phones home to GitHub to ask if the user is an admin of the organization.
+pub(crate) fn is_gh_org_owner(app: &App, org_id: i32, user: &User) -> AppResult<bool> {
     let token = AccessToken::new(user.gh_access_token.clone());
     match app.github.org_membership(org_id, &user.gh_login, &token) {
         Ok(membership) => Ok(membership.state == "active" && membership.role == "admin"),
@@ -2,10 +2,12 @@ mod scopes;
 
 use chrono::NaiveDateTime;
 use diesel::prelude::*;
+use ipnetwork::IpNetwork;
+use std::net::IpAddr;
 
 pub use self::scopes::{CrateScope, EndpointScope};
 use crate::models::User;
-use crate::schema::api_tokens;
+use crate::schema::{api_token_origins, api_tokens};
 use crate::util::errors::{AppResult, InsecurelyGeneratedTokenRevoked};
 use crate::util::rfc3339;
 use crate::util::token::{HashedToken, PlainToken};
@@ -30,12 +32,14 @@ pub struct ApiToken {
     pub endpoint_scopes: Option<Vec<EndpointScope>>,
     #[serde(with = "rfc3339::option")]
     pub expired_at: Option<NaiveDateTime>,
+    /// `None` or a list of CIDR blocks that requests using the token must originate from
+    pub allowed_cidrs: Option<Vec<IpNetwork>>,
 }
 
 impl ApiToken {
     /// Generates a new named API token for a user
     pub fn insert(conn: &mut PgConnection, user_id: i32, name: &str) -> AppResult<CreatedApiToken> {
-        Self::insert_with_scopes(conn, user_id, name, None, None, None)
+        Self::insert_with_scopes(conn, user_id, name, None, None, None, None)
     }
 
     pub fn insert_with_scopes(
@@ -45,6 +49,7 @@ impl ApiToken {
         crate_scopes: Option<Vec<CrateScope>>,
         endpoint_scopes: Option<Vec<EndpointScope>>,
         expired_at: Option<NaiveDateTime>,
+        allowed_cidrs: Option<Vec<IpNetwork>>,
     ) -> AppResult<CreatedApiToken> {
         let token = PlainToken::generate();
 
@@ -56,6 +61,7 @@ impl ApiToken {
                 api_tokens::crate_scopes.eq(crate_scopes),
                 api_tokens::endpoint_scopes.eq(endpoint_scopes),
                 api_tokens::expired_at.eq(expired_at),
+                api_tokens::allowed_cidrs.eq(allowed_cidrs),
             ))
             .returning(ApiToken::as_returning())
             .get_result(conn)?;
@@ -66,6 +72,43 @@ impl ApiToken {
         })
     }
 
+    /// Revokes all tokens of the user. Returns the number of revoked tokens.
+    ///
+    /// The tokens are only revoked and not deleted, since the audit trail of
+    /// published versions refers to them.
+    pub fn revoke_all_for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<usize> {
+        diesel::update(api_tokens::table)
+            .filter(api_tokens::user_id.eq(user_id))
+            .filter(api_tokens::revoked.eq(false))
+            .set(api_tokens::revoked.eq(true))
+            .execute(conn)
+    }
+
+    /// Deletes the tokens that were revoked or that expired before the given
+    /// time. Returns the number of deleted tokens.
+    ///
+    /// Tokens that published versions are kept, since the audit trail of the
+    /// versions refers to them.
+    pub fn purge_revoked_before(
+        conn: &mut PgConnection,
+        cutoff: NaiveDateTime,
+    ) -> QueryResult<usize> {
+        use crate::schema::version_owner_actions;
+        use diesel::dsl::{exists, not};
+
+        let referenced = version_owner_actions::table
+            .filter(version_owner_actions::api_token_id.eq(api_tokens::id.nullable()));
+
+        diesel::delete(api_tokens::table)
+            .filter(
+                api_tokens::revoked_at
+                    .lt(cutoff)
+                    .or(api_tokens::expired_at.lt(cutoff)),
+            )
+            .filter(not(exists(referenced)))
+            .execute(conn)
+    }
+
     pub fn find_by_api_token(conn: &mut PgConnection, token_: &str) -> AppResult<ApiToken> {
         use crate::schema::api_tokens::dsl::*;
         use diesel::{dsl::now, update};
@@ -89,6 +132,94 @@ impl ApiToken {
         .or_else(|_| tokens.select(ApiToken::as_select()).first(conn))
         .map_err(Into::into)
     }
+
+    /// Records that the token has been used, for tokens that have been looked
+    /// up on a read-only replica by [`ApiToken::find_by_api_token`].
+    pub fn record_use(&self, conn: &mut PgConnection) -> QueryResult<()> {
+        use diesel::dsl::now;
+
+        diesel::update(api_tokens::table.find(self.id))
+            .set(api_tokens::last_used_at.eq(now.nullable()))
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Returns whether the token may be used from the given IP address.
+    ///
+    /// Tokens without CIDR restrictions may be used from anywhere, while
+    /// restricted tokens can't be used if the address of the client is unknown.
+    pub fn allows_ip(&self, ip: Option<IpAddr>) -> bool {
+        match (&self.allowed_cidrs, ip) {
+            (None, _) => true,
+            (Some(cidrs), _) if cidrs.is_empty() => true,
+            (Some(_), None) => false,
+            (Some(cidrs), Some(ip)) => cidrs.iter().any(|cidr| cidr.contains(ip)),
+        }
+    }
+
+    /// Records that the token was used from the IP address, and returns
+    /// whether the address or its country are new for the token.
+    pub fn record_origin(
+        &self,
+        conn: &mut PgConnection,
+        ip_address: &str,
+        country: Option<&str>,
+    ) -> QueryResult<TokenOrigin> {
+        use diesel::dsl::exists;
+        use diesel::select;
+
+        let inserted = diesel::insert_into(api_token_origins::table)
+            .values((
+                api_token_origins::api_token_id.eq(self.id),
+                api_token_origins::ip_address.eq(ip_address),
+                api_token_origins::country.eq(country),
+            ))
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+
+        if inserted == 0 {
+            return Ok(TokenOrigin::Known);
+        }
+
+        let previous_origins = || {
+            api_token_origins::table
+                .filter(api_token_origins::api_token_id.eq(self.id))
+                .filter(api_token_origins::ip_address.ne(ip_address))
+        };
+
+        let used_before: bool = select(exists(previous_origins())).get_result(conn)?;
+        if !used_before {
+            return Ok(TokenOrigin::First);
+        }
+
+        let Some(country) = country else {
+            return Ok(TokenOrigin::NewIpAddress);
+        };
+
+        let same_country = previous_origins().filter(api_token_origins::country.eq(country));
+        let known_country: bool = select(exists(same_country)).get_result(conn)?;
+        if known_country {
+            Ok(TokenOrigin::NewIpAddress)
+        } else {
+            Ok(TokenOrigin::NewCountry)
+        }
+    }
+}
+
+/// How the IP address of a request relates to the previous uses of a token,
+/// see [`ApiToken::record_origin()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenOrigin {
+    /// The token was used from the IP address before.
+    Known,
+    /// The first recorded use of the token.
+    First,
+    /// A new IP address in a country that the token was used from before, or
+    /// in an unknown country.
+    NewIpAddress,
+    /// A new IP address in a country that the token was never used from.
+    NewCountry,
 }
 
 #[derive(Debug)]
@@ -119,6 +250,7 @@ mod tests {
             crate_scopes: None,
             endpoint_scopes: None,
             expired_at: None,
+            allowed_cidrs: None,
         };
         let json = serde_json::to_string(&tok).unwrap();
         assert_some!(json
@@ -128,4 +260,40 @@ mod tests {
             .as_str()
             .find(r#""last_used_at":"2017-01-06T14:23:12+00:00""#));
     }
+
+    #[test]
+    fn allows_ip() {
+        let mut token = ApiToken {
+            id: 12345,
+            user_id: 23456,
+            revoked: false,
+            name: "".to_string(),
+            created_at: NaiveDate::from_ymd_opt(2017, 1, 6)
+                .unwrap()
+                .and_hms_opt(14, 23, 11)
+                .unwrap(),
+            last_used_at: None,
+            crate_scopes: None,
+            endpoint_scopes: None,
+            expired_at: None,
+            allowed_cidrs: None,
+        };
+
+        let ip = |ip: &str| Some(ip.parse::<IpAddr>().unwrap());
+
+        assert!(token.allows_ip(None));
+        assert!(token.allows_ip(ip("192.0.2.1")));
+
+        token.allowed_cidrs = Some(vec![
+            "192.0.2.0/24".parse().unwrap(),
+            "2001:db8::/32".parse().unwrap(),
+        ]);
+
+        assert!(!token.allows_ip(None));
+        assert!(token.allows_ip(ip("192.0.2.1")));
+        assert!(token.allows_ip(ip("192.0.2.255")));
+        assert!(!token.allows_ip(ip("192.0.3.1")));
+        assert!(token.allows_ip(ip("2001:db8::1")));
+        assert!(!token.allows_ip(ip("2001:db9::1")));
+    }
 }
@@ -0,0 +1,72 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::prelude::*;
+
+use crate::schema::{crates, upload_limits};
+
+/// An override of the maximum crate file size of a crate, which can be set by
+/// the crates.io team through the admin API.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable, Selectable)]
+#[diesel(primary_key(crate_id))]
+pub struct UploadLimit {
+    pub crate_id: i32,
+    /// Maximum size of the compressed crate file in bytes
+    pub max_upload_size: i64,
+    /// Maximum size of the decompressed crate file in bytes, falls back to
+    /// the global limit if `None`
+    pub max_unpack_size: Option<i64>,
+    pub note: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl UploadLimit {
+    pub fn find(conn: &mut PgConnection, crate_id: i32) -> QueryResult<Option<Self>> {
+        upload_limits::table
+            .find(crate_id)
+            .select(UploadLimit::as_select())
+            .first(conn)
+            .optional()
+    }
+
+    /// Returns all overrides together with the name of their crate, ordered
+    /// by crate name.
+    pub fn all(conn: &mut PgConnection) -> QueryResult<Vec<(Self, String)>> {
+        upload_limits::table
+            .inner_join(crates::table)
+            .order(crates::name)
+            .select((UploadLimit::as_select(), crates::name))
+            .load(conn)
+    }
+
+    pub fn delete(conn: &mut PgConnection, crate_id: i32) -> QueryResult<bool> {
+        let deleted = diesel::delete(upload_limits::table.find(crate_id)).execute(conn)?;
+        Ok(deleted > 0)
+    }
+}
+
+#[derive(Insertable, AsChangeset, Debug, Clone)]
+#[diesel(
+    table_name = upload_limits,
+    check_for_backend(diesel::pg::Pg),
+    treat_none_as_null = true
+)]
+pub struct NewUploadLimit<'a> {
+    pub crate_id: i32,
+    pub max_upload_size: i64,
+    pub max_unpack_size: Option<i64>,
+    pub note: Option<&'a str>,
+}
+
+impl NewUploadLimit<'_> {
+    /// Creates the override, or replaces the existing override of the crate.
+    pub fn upsert(&self, conn: &mut PgConnection) -> QueryResult<UploadLimit> {
+        diesel::insert_into(upload_limits::table)
+            .values(self)
+            .on_conflict(upload_limits::crate_id)
+            .do_update()
+            .set((self, upload_limits::updated_at.eq(now)))
+            .returning(UploadLimit::as_returning())
+            .get_result(conn)
+    }
+}
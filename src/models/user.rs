@@ -1,4 +1,4 @@
-use chrono::NaiveDateTime;
+use chrono::{Duration, NaiveDateTime, Utc};
 use diesel::prelude::*;
 use std::borrow::Cow;
 
@@ -20,6 +20,9 @@ pub struct User {
     pub gh_id: i32,
     pub account_lock_reason: Option<String>,
     pub account_lock_until: Option<NaiveDateTime>,
+    /// `None` for accounts that were created before the creation time was
+    /// recorded
+    pub created_at: Option<NaiveDateTime>,
 }
 
 /// Represents a new user record insertable to the `users` table
@@ -110,6 +113,27 @@ impl<'a> NewUser<'a> {
 }
 
 impl User {
+    /// Users that signed up with an OAuth provider other than GitHub get a
+    /// login with a provider suffix, e.g. `jdoe@gitlab`. These logins can not
+    /// collide with GitHub logins, which never contain an `@`.
+    pub fn is_github_login(login: &str) -> bool {
+        !login.contains('@')
+    }
+
+    /// Returns the URL of the GitHub profile for the given login.
+    pub fn github_url(login: &str) -> Option<String> {
+        Self::is_github_login(login).then(|| format!("https://github.com/{login}"))
+    }
+
+    /// Returns `true` if the account was created less than `age` ago.
+    ///
+    /// Accounts with an unknown creation time are never considered new.
+    pub fn is_younger_than(&self, age: Duration) -> bool {
+        self.created_at.map_or(false, |created_at| {
+            created_at > Utc::now().naive_utc() - age
+        })
+    }
+
     pub fn find(conn: &mut PgConnection, id: i32) -> QueryResult<User> {
         users::table.find(id).first(conn)
     }
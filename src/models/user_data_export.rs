@@ -0,0 +1,44 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::prelude::*;
+
+use crate::schema::user_data_exports;
+
+/// An archive with the personal data of a user, which is created by the
+/// `export_user_data` background job.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable)]
+pub struct UserDataExport {
+    pub id: i32,
+    pub user_id: i32,
+    pub requested_at: NaiveDateTime,
+    pub completed_at: Option<NaiveDateTime>,
+}
+
+impl UserDataExport {
+    pub fn is_completed(&self) -> bool {
+        self.completed_at.is_some()
+    }
+
+    /// Returns the most recently requested export of the user.
+    pub fn latest_for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<Option<Self>> {
+        user_data_exports::table
+            .filter(user_data_exports::user_id.eq(user_id))
+            .order(user_data_exports::id.desc())
+            .first(conn)
+            .optional()
+    }
+
+    /// Records a new export request. The archive itself has to be created by
+    /// enqueueing the `export_user_data` background job.
+    pub fn request(conn: &mut PgConnection, user_id: i32) -> QueryResult<Self> {
+        diesel::insert_into(user_data_exports::table)
+            .values(user_data_exports::user_id.eq(user_id))
+            .get_result(conn)
+    }
+
+    pub fn mark_completed(&self, conn: &mut PgConnection) -> QueryResult<Self> {
+        diesel::update(self)
+            .set(user_data_exports::completed_at.eq(now))
+            .get_result(conn)
+    }
+}
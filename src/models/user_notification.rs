@@ -0,0 +1,324 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use serde_json::Value;
+
+use crate::email::Emails;
+use crate::models::{ApiToken, CrateOwner, OwnerKind, User};
+use crate::schema::{crate_owners, emails, user_notifications};
+
+/// The kind of security relevant event that a user is notified about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    /// A new API token was created for the account.
+    TokenCreated,
+    /// An API token was used from an IP address that it wasn't used from
+    /// before.
+    TokenUsedFromNewIp,
+    /// A user or team was added as an owner of one of the user's crates.
+    OwnerAdded,
+    /// The email address of the account was changed.
+    EmailChanged,
+}
+
+impl NotificationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::TokenCreated => "token_created",
+            NotificationKind::TokenUsedFromNewIp => "token_used_from_new_ip",
+            NotificationKind::OwnerAdded => "owner_added",
+            NotificationKind::EmailChanged => "email_changed",
+        }
+    }
+}
+
+/// How urgently the user is emailed about a notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationSeverity {
+    /// Included in the next digest email.
+    Low,
+    /// Emailed immediately, or included in the next digest email if that
+    /// fails.
+    High,
+}
+
+impl NotificationSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationSeverity::Low => "low",
+            NotificationSeverity::High => "high",
+        }
+    }
+}
+
+/// A security relevant event concerning a user account, which the user is
+/// notified about by email.
+#[derive(Clone, Debug, PartialEq, Identifiable, Queryable, Selectable, Associations)]
+#[diesel(table_name = user_notifications, belongs_to(User))]
+pub struct UserNotification {
+    pub id: i32,
+    pub user_id: i32,
+    pub kind: String,
+    pub severity: String,
+    pub details: Value,
+    pub created_at: NaiveDateTime,
+    pub emailed_at: Option<NaiveDateTime>,
+}
+
+impl UserNotification {
+    pub fn is_high_severity(&self) -> bool {
+        self.severity == NotificationSeverity::High.as_str()
+    }
+
+    /// Returns a human readable description of the event, which is used in
+    /// the notification emails.
+    pub fn message(&self) -> String {
+        let detail = |key: &str| self.details[key].as_str().unwrap_or("unknown");
+
+        match self.kind.as_str() {
+            "token_created" => format!("The API token \"{}\" was created", detail("token")),
+            "token_used_from_new_ip" => {
+                let mut message = format!(
+                    "The API token \"{}\" was used from the new IP address {}",
+                    detail("token"),
+                    detail("ip_address")
+                );
+                if let Some(country) = self.details["country"].as_str() {
+                    message.push_str(&format!(" ({country})"));
+                }
+                message
+            }
+            "owner_added" => format!(
+                "{} was added as an owner of the crate {}",
+                detail("owner"),
+                detail("crate")
+            ),
+            "email_changed" => format!(
+                "The email address of the account was changed to {}",
+                detail("email")
+            ),
+            kind => format!("Security event: {kind}"),
+        }
+    }
+
+    /// Returns the address that the notification is emailed to.
+    ///
+    /// Notifications about a changed email address are sent to the previous
+    /// address, since the new one might be controlled by an attacker.
+    pub fn recipient(&self, conn: &mut PgConnection) -> QueryResult<Option<String>> {
+        if let Some(previous_email) = self.details["previous_email"].as_str() {
+            return Ok(Some(previous_email.to_string()));
+        }
+
+        emails::table
+            .filter(emails::user_id.eq(self.user_id))
+            .filter(emails::verified)
+            .select(emails::email)
+            .first(conn)
+            .optional()
+    }
+
+    fn send_email(&self, conn: &mut PgConnection, emails: &Emails) -> QueryResult<()> {
+        let Some(email) = self.recipient(conn)? else {
+            return Ok(());
+        };
+
+        // A failure is not propagated, since the notification is still
+        // pending and will be included in the next digest email
+        if let Err(error) = emails.send_security_notification(&email, &self.message()) {
+            warn!(
+                notification.id = self.id,
+                ?error,
+                "Failed to send security notification"
+            );
+            return Ok(());
+        }
+
+        Self::mark_emailed(conn, &[self.id])?;
+        Ok(())
+    }
+
+    pub fn mark_emailed(conn: &mut PgConnection, ids: &[i32]) -> QueryResult<usize> {
+        diesel::update(user_notifications::table.filter(user_notifications::id.eq_any(ids)))
+            .set(user_notifications::emailed_at.eq(now.nullable()))
+            .execute(conn)
+    }
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = user_notifications, check_for_backend(diesel::pg::Pg))]
+pub struct NewUserNotification {
+    pub user_id: i32,
+    pub kind: &'static str,
+    pub severity: &'static str,
+    pub details: Value,
+}
+
+impl NewUserNotification {
+    pub fn new(
+        user_id: i32,
+        kind: NotificationKind,
+        severity: NotificationSeverity,
+        details: Value,
+    ) -> Self {
+        Self {
+            user_id,
+            kind: kind.as_str(),
+            severity: severity.as_str(),
+            details,
+        }
+    }
+
+    pub fn token_created(user_id: i32, token_name: &str) -> Self {
+        let details = json!({ "token": token_name });
+        Self::new(
+            user_id,
+            NotificationKind::TokenCreated,
+            NotificationSeverity::High,
+            details,
+        )
+    }
+
+    /// The use of a token from a new IP address is only urgent if the
+    /// token has never been used from the country of the address before.
+    pub fn token_used_from_new_ip(
+        token: &ApiToken,
+        ip_address: &str,
+        country: Option<&str>,
+        new_country: bool,
+    ) -> Self {
+        let severity = if new_country {
+            NotificationSeverity::High
+        } else {
+            NotificationSeverity::Low
+        };
+        let details = json!({
+            "token": token.name,
+            "api_token_id": token.id,
+            "ip_address": ip_address,
+            "country": country,
+        });
+        Self::new(
+            token.user_id,
+            NotificationKind::TokenUsedFromNewIp,
+            severity,
+            details,
+        )
+    }
+
+    pub fn owner_added(user_id: i32, crate_name: &str, owner_login: &str) -> Self {
+        let details = json!({ "crate": crate_name, "owner": owner_login });
+        Self::new(
+            user_id,
+            NotificationKind::OwnerAdded,
+            NotificationSeverity::Low,
+            details,
+        )
+    }
+
+    /// The `previous_email` is only set if it was verified, in which case
+    /// the notification is sent to it instead of the new, unverified
+    /// address.
+    pub fn email_changed(user_id: i32, previous_email: Option<&str>, email: &str) -> Self {
+        let details = json!({ "email": email, "previous_email": previous_email });
+        Self::new(
+            user_id,
+            NotificationKind::EmailChanged,
+            NotificationSeverity::High,
+            details,
+        )
+    }
+
+    pub fn insert(&self, conn: &mut PgConnection) -> QueryResult<UserNotification> {
+        diesel::insert_into(user_notifications::table)
+            .values(self)
+            .returning(UserNotification::as_returning())
+            .get_result(conn)
+    }
+
+    /// Inserts the notification, and emails the user right away if it is of
+    /// high severity.
+    pub fn create(
+        &self,
+        conn: &mut PgConnection,
+        emails: &Emails,
+    ) -> QueryResult<UserNotification> {
+        let notification = self.insert(conn)?;
+        if notification.is_high_severity() {
+            notification.send_email(conn, emails)?;
+        }
+        Ok(notification)
+    }
+}
+
+/// Notifies the user owners of a crate that a new owner was added, except for
+/// the new owner itself.
+pub fn notify_owner_added(
+    conn: &mut PgConnection,
+    crate_id: i32,
+    crate_name: &str,
+    owner_login: &str,
+    new_owner_id: Option<i32>,
+) -> QueryResult<()> {
+    let owner_ids: Vec<i32> = CrateOwner::by_owner_kind(OwnerKind::User)
+        .filter(crate_owners::crate_id.eq(crate_id))
+        .select(crate_owners::owner_id)
+        .load(conn)?;
+
+    let notifications = owner_ids
+        .into_iter()
+        .filter(|owner_id| Some(*owner_id) != new_owner_id)
+        .map(|owner_id| NewUserNotification::owner_added(owner_id, crate_name, owner_login))
+        .collect::<Vec<_>>();
+
+    if notifications.is_empty() {
+        return Ok(());
+    }
+
+    diesel::insert_into(user_notifications::table)
+        .values(&notifications)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(kind: NotificationKind, details: Value) -> UserNotification {
+        UserNotification {
+            id: 1,
+            user_id: 1,
+            kind: kind.as_str().to_string(),
+            severity: NotificationSeverity::Low.as_str().to_string(),
+            details,
+            created_at: NaiveDateTime::default(),
+            emailed_at: None,
+        }
+    }
+
+    #[test]
+    fn messages() {
+        let details = json!({ "token": "ci", "ip_address": "192.0.2.1", "country": "DE" });
+        let token_used = notification(NotificationKind::TokenUsedFromNewIp, details);
+        assert_eq!(
+            token_used.message(),
+            "The API token \"ci\" was used from the new IP address 192.0.2.1 (DE)"
+        );
+
+        let details = json!({ "token": "ci", "ip_address": "192.0.2.1", "country": null });
+        let token_used = notification(NotificationKind::TokenUsedFromNewIp, details);
+        assert_eq!(
+            token_used.message(),
+            "The API token \"ci\" was used from the new IP address 192.0.2.1"
+        );
+
+        let details = json!({ "crate": "foo", "owner": "github:rust-lang:core" });
+        let owner_added = notification(NotificationKind::OwnerAdded, details);
+        assert_eq!(
+            owner_added.message(),
+            "github:rust-lang:core was added as an owner of the crate foo"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use serde_json::Value;
+use webauthn_rs::prelude::{AuthenticationResult, Passkey};
+
+use crate::models::User;
+use crate::schema::user_passkeys;
+use crate::util::errors::{internal, AppResult};
+
+/// A WebAuthn passkey that a user can sign in with.
+#[derive(Debug, Identifiable, Queryable, Selectable, Associations)]
+#[diesel(belongs_to(User))]
+pub struct UserPasskey {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub credential_id: Vec<u8>,
+    pub passkey: Value,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+impl UserPasskey {
+    pub fn create(
+        conn: &mut PgConnection,
+        user_id: i32,
+        name: &str,
+        passkey: &Passkey,
+    ) -> AppResult<Self> {
+        let value = serde_json::to_value(passkey)
+            .map_err(|e| internal(format!("failed to serialize passkey: {e}")))?;
+
+        let passkey = diesel::insert_into(user_passkeys::table)
+            .values((
+                user_passkeys::user_id.eq(user_id),
+                user_passkeys::name.eq(name),
+                user_passkeys::credential_id.eq(&passkey.cred_id().0),
+                user_passkeys::passkey.eq(value),
+            ))
+            .returning(UserPasskey::as_returning())
+            .get_result(conn)?;
+
+        Ok(passkey)
+    }
+
+    /// Returns all passkeys of the user, oldest first.
+    pub fn for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<Vec<Self>> {
+        user_passkeys::table
+            .filter(user_passkeys::user_id.eq(user_id))
+            .order(user_passkeys::id)
+            .select(UserPasskey::as_select())
+            .load(conn)
+    }
+
+    /// Deserializes the stored passkey.
+    pub fn passkey(&self) -> AppResult<Passkey> {
+        serde_json::from_value(self.passkey.clone())
+            .map_err(|e| internal(format!("failed to deserialize passkey: {e}")))
+    }
+
+    /// Records the successful use of a passkey, and updates its signature
+    /// counter. Returns the ID of the user that the passkey belongs to.
+    pub fn record_use(conn: &mut PgConnection, result: &AuthenticationResult) -> AppResult<i32> {
+        let model: UserPasskey = user_passkeys::table
+            .filter(user_passkeys::credential_id.eq(&result.cred_id().0))
+            .select(UserPasskey::as_select())
+            .first(conn)?;
+
+        let mut passkey = model.passkey()?;
+        let value = match passkey.update_credential(result) {
+            Some(true) => serde_json::to_value(&passkey)
+                .map_err(|e| internal(format!("failed to serialize passkey: {e}")))?,
+            _ => model.passkey,
+        };
+
+        diesel::update(user_passkeys::table.find(model.id))
+            .set((
+                user_passkeys::passkey.eq(value),
+                user_passkeys::last_used_at.eq(now),
+            ))
+            .execute(conn)?;
+
+        Ok(model.user_id)
+    }
+
+    /// Deletes a passkey of the user. Returns `false` if the user had no
+    /// passkey with the given ID.
+    pub fn delete(conn: &mut PgConnection, user_id: i32, id: i32) -> QueryResult<bool> {
+        let deleted = diesel::delete(user_passkeys::table.find(id))
+            .filter(user_passkeys::user_id.eq(user_id))
+            .execute(conn)?;
+
+        Ok(deleted > 0)
+    }
+}
@@ -0,0 +1,97 @@
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use once_cell::sync::Lazy;
+use rand::rngs::OsRng;
+
+use crate::schema::user_passwords;
+use crate::util::errors::{internal, AppResult};
+
+/// The hash of a random password, which is verified instead of the hash of
+/// the user if the user does not exist or has no password. This way, the
+/// response time does not tell whether an account exists.
+static DUMMY_HASH: Lazy<String> = Lazy::new(|| {
+    let salt = SaltString::generate(&mut OsRng);
+    let password = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_str().as_bytes(), &salt)
+        .expect("failed to hash dummy password")
+        .to_string()
+});
+
+/// The password of a user that signs in with local authentication.
+#[derive(Debug, Identifiable, Queryable, Selectable)]
+#[diesel(primary_key(user_id))]
+pub struct UserPassword {
+    pub user_id: i32,
+    pub password_hash: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl UserPassword {
+    /// Sets the password of the user, replacing any existing password.
+    pub fn set(conn: &mut PgConnection, user_id: i32, password: &str) -> AppResult<()> {
+        let salt = SaltString::generate(&mut OsRng);
+        let password_hash = Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| internal(format!("failed to hash password: {e}")))?
+            .to_string();
+
+        diesel::insert_into(user_passwords::table)
+            .values((
+                user_passwords::user_id.eq(user_id),
+                user_passwords::password_hash.eq(&password_hash),
+            ))
+            .on_conflict(user_passwords::user_id)
+            .do_update()
+            .set((
+                user_passwords::password_hash.eq(&password_hash),
+                user_passwords::updated_at.eq(now),
+            ))
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Checks the password of the user. Returns `false` if the password does
+    /// not match, or if the user has no password.
+    pub fn verify(conn: &mut PgConnection, user_id: i32, password: &str) -> QueryResult<bool> {
+        let password_hash: Option<String> = user_passwords::table
+            .find(user_id)
+            .select(user_passwords::password_hash)
+            .first(conn)
+            .optional()?;
+
+        let Some(password_hash) = password_hash else {
+            Self::verify_dummy(password);
+            return Ok(false);
+        };
+
+        // An unparseable hash can never match, so it is treated like a wrong password
+        let Ok(password_hash) = PasswordHash::new(&password_hash) else {
+            warn!(%user_id, "Invalid password hash in the database");
+            return Ok(false);
+        };
+
+        Ok(Argon2::default()
+            .verify_password(password.as_bytes(), &password_hash)
+            .is_ok())
+    }
+
+    /// Does the same amount of work as [`Self::verify`], for users that do
+    /// not exist.
+    pub fn verify_dummy(password: &str) {
+        let password_hash =
+            PasswordHash::new(&DUMMY_HASH).expect("the dummy password hash is valid");
+
+        // The dummy password is random, so the result does not matter
+        let _ = Argon2::default().verify_password(password.as_bytes(), &password_hash);
+    }
+
+    pub fn exists(conn: &mut PgConnection, user_id: i32) -> QueryResult<bool> {
+        diesel::select(diesel::dsl::exists(user_passwords::table.find(user_id))).get_result(conn)
+    }
+}
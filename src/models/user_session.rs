@@ -0,0 +1,153 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::{now, IntervalDsl};
+use diesel::prelude::*;
+
+use crate::middleware::session::MAX_AGE_DAYS;
+use crate::models::User;
+use crate::schema::user_sessions;
+use crate::util::token::{HashedToken, PlainToken};
+
+/// The maximum length of the stored `User-Agent` header.
+const MAX_USER_AGENT_LENGTH: usize = 256;
+
+/// A web session of a user.
+///
+/// The session cookie contains the plaintext token of the session, so that
+/// revoking the session in the database immediately logs out the browser
+/// that is using it.
+#[derive(Debug, Identifiable, Queryable, Selectable, Associations)]
+#[diesel(belongs_to(User))]
+pub struct UserSession {
+    pub id: i32,
+    pub user_id: i32,
+    pub hashed_token: HashedToken,
+    pub user_agent: String,
+    pub ip_address: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_seen_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+impl UserSession {
+    /// Creates a new session for the user, returning the plaintext token that
+    /// has to be stored in the session cookie.
+    pub fn create(
+        conn: &mut PgConnection,
+        user_id: i32,
+        user_agent: &str,
+        ip_address: Option<&str>,
+    ) -> QueryResult<CreatedUserSession> {
+        let token = PlainToken::generate();
+        let user_agent = truncate(user_agent, MAX_USER_AGENT_LENGTH);
+
+        let model = diesel::insert_into(user_sessions::table)
+            .values((
+                user_sessions::user_id.eq(user_id),
+                user_sessions::hashed_token.eq(token.hashed()),
+                user_sessions::user_agent.eq(user_agent),
+                user_sessions::ip_address.eq(ip_address),
+            ))
+            .returning(UserSession::as_returning())
+            .get_result(conn)?;
+
+        Ok(CreatedUserSession {
+            model,
+            plaintext: token,
+        })
+    }
+
+    /// Finds the active session of the user with the given token, and
+    /// updates its `last_seen_at` timestamp.
+    pub fn find_active(
+        conn: &mut PgConnection,
+        user_id: i32,
+        token: &str,
+    ) -> QueryResult<Option<UserSession>> {
+        let Some(token) = HashedToken::parse(token) else {
+            return Ok(None);
+        };
+
+        let sessions = user_sessions::table
+            .filter(user_sessions::user_id.eq(user_id))
+            .filter(user_sessions::hashed_token.eq(&token))
+            .filter(user_sessions::revoked_at.is_null())
+            .filter(user_sessions::created_at.gt(now - MAX_AGE_DAYS.days()));
+
+        // If the database is in read only mode, we can't update last_seen_at.
+        // Try updating in a new transaction, if that fails, fall back to reading
+        conn.transaction(|conn| {
+            diesel::update(sessions)
+                .set(user_sessions::last_seen_at.eq(now))
+                .returning(UserSession::as_returning())
+                .get_result(conn)
+        })
+        .or_else(|_| sessions.select(UserSession::as_select()).first(conn))
+        .optional()
+    }
+
+    /// Returns all active sessions of the user, most recently seen first.
+    pub fn active_for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<Vec<Self>> {
+        user_sessions::table
+            .filter(user_sessions::user_id.eq(user_id))
+            .filter(user_sessions::revoked_at.is_null())
+            .filter(user_sessions::created_at.gt(now - MAX_AGE_DAYS.days()))
+            .order(user_sessions::last_seen_at.desc())
+            .select(UserSession::as_select())
+            .load(conn)
+    }
+
+    /// Revokes a session of the user. Returns `false` if there was no active
+    /// session with the given ID.
+    pub fn revoke(conn: &mut PgConnection, user_id: i32, id: i32) -> QueryResult<bool> {
+        let revoked = diesel::update(user_sessions::table.find(id))
+            .filter(user_sessions::user_id.eq(user_id))
+            .filter(user_sessions::revoked_at.is_null())
+            .set(user_sessions::revoked_at.eq(now))
+            .execute(conn)?;
+
+        Ok(revoked > 0)
+    }
+
+    /// Revokes all sessions of the user, except for the given one. Returns the
+    /// number of revoked sessions.
+    pub fn revoke_all_for_user(
+        conn: &mut PgConnection,
+        user_id: i32,
+        except_id: Option<i32>,
+    ) -> QueryResult<usize> {
+        diesel::update(user_sessions::table)
+            .filter(user_sessions::user_id.eq(user_id))
+            .filter(user_sessions::id.ne(except_id.unwrap_or(0)))
+            .filter(user_sessions::revoked_at.is_null())
+            .set(user_sessions::revoked_at.eq(now))
+            .execute(conn)
+    }
+
+    /// Deletes the sessions that were revoked, or that were last used before
+    /// the given time. Returns the number of deleted sessions.
+    pub fn purge_stale_before(
+        conn: &mut PgConnection,
+        cutoff: NaiveDateTime,
+    ) -> QueryResult<usize> {
+        diesel::delete(user_sessions::table)
+            .filter(
+                user_sessions::revoked_at
+                    .lt(cutoff)
+                    .or(user_sessions::last_seen_at.lt(cutoff)),
+            )
+            .execute(conn)
+    }
+}
+
+fn truncate(value: &str, max_length: usize) -> &str {
+    match value.char_indices().nth(max_length) {
+        Some((index, _)) => &value[..index],
+        None => value,
+    }
+}
+
+#[derive(Debug)]
+pub struct CreatedUserSession {
+    pub model: UserSession,
+    pub plaintext: PlainToken,
+}
@@ -27,6 +27,8 @@ pub struct Version {
     pub checksum: String,
     pub links: Option<String>,
     pub rust_version: Option<String>,
+    pub staged_until: Option<NaiveDateTime>,
+    pub checksum_zstd: Option<String>,
 }
 
 #[derive(Insertable, Debug)]
@@ -41,6 +43,7 @@ pub struct NewVersion {
     checksum: String,
     links: Option<String>,
     rust_version: Option<String>,
+    staged_until: Option<NaiveDateTime>,
 }
 
 /// The highest version (semver order) and the most recently updated version.
@@ -120,6 +123,25 @@ impl Version {
             .execute(conn)
     }
 
+    /// Returns an error if a version with the same number, ignoring the build
+    /// metadata, has already been uploaded for the crate.
+    pub fn ensure_not_uploaded(conn: &mut PgConnection, crate_id: i32, num: &str) -> AppResult<()> {
+        use diesel::dsl::exists;
+
+        let num_no_build = strip_build_metadata(num);
+        let already_uploaded = versions::table
+            .filter(versions::crate_id.eq(crate_id))
+            .filter(split_part(versions::num, "+", 1).eq(num_no_build));
+
+        if diesel::select(exists(already_uploaded)).get_result(conn)? {
+            return Err(cargo_err(&format_args!(
+                "crate version `{num_no_build}` is already uploaded"
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Gets the User who ran `cargo publish` for this version, if recorded.
     /// Not for use when you have a group of versions you need the publishers for.
     pub fn published_by(&self, conn: &mut PgConnection) -> Option<User> {
@@ -143,6 +165,7 @@ impl NewVersion {
         checksum: String,
         links: Option<String>,
         rust_version: Option<String>,
+        staged_until: Option<NaiveDateTime>,
     ) -> AppResult<Self> {
         let features = serde_json::to_value(features)?;
 
@@ -156,6 +179,7 @@ impl NewVersion {
             checksum,
             links,
             rust_version,
+            staged_until,
         };
 
         new_version.validate_license(license_file)?;
@@ -165,22 +189,10 @@ impl NewVersion {
 
     pub fn save(&self, conn: &mut PgConnection, published_by_email: &str) -> AppResult<Version> {
         use crate::schema::versions::dsl::*;
-        use diesel::dsl::exists;
-        use diesel::{insert_into, select};
+        use diesel::insert_into;
 
         conn.transaction(|conn| {
-            let num_no_build = strip_build_metadata(&self.num);
-
-            let already_uploaded = versions
-                .filter(crate_id.eq(self.crate_id))
-                .filter(split_part(num, "+", 1).eq(num_no_build));
-
-            if select(exists(already_uploaded)).get_result(conn)? {
-                return Err(cargo_err(&format_args!(
-                    "crate version `{}` is already uploaded",
-                    num_no_build
-                )));
-            }
+            Version::ensure_not_uploaded(conn, self.crate_id, &self.num)?;
 
             let version: Version = insert_into(versions).values(self).get_result(conn)?;
 
@@ -0,0 +1,47 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::schema::{crates, version_objects, versions};
+
+/// A crate file that is stored in the content-addressed `objects/` layout,
+/// i.e. under the SHA-256 hash of its content.
+///
+/// Crate files with the same content are only stored once, so multiple
+/// versions can share the same object.
+#[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable, Selectable)]
+#[diesel(primary_key(version_id))]
+pub struct VersionObject {
+    pub version_id: i32,
+    pub object_hash: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl VersionObject {
+    pub fn insert(conn: &mut PgConnection, version_id: i32, object_hash: &str) -> QueryResult<()> {
+        diesel::insert_into(version_objects::table)
+            .values((
+                version_objects::version_id.eq(version_id),
+                version_objects::object_hash.eq(object_hash),
+            ))
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Returns the object hash of the crate file of the given version, or
+    /// `None` if it is only stored in the legacy layout.
+    pub fn hash_for_version(
+        conn: &mut PgConnection,
+        crate_name: &str,
+        version: &str,
+    ) -> QueryResult<Option<String>> {
+        version_objects::table
+            .inner_join(versions::table.inner_join(crates::table))
+            .filter(crates::name.eq(crate_name))
+            .filter(versions::num.eq(version))
+            .select(version_objects::object_hash)
+            .first(conn)
+            .optional()
+    }
+}
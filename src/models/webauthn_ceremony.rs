@@ -0,0 +1,98 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::{now, IntervalDsl};
+use diesel::prelude::*;
+use serde_json::Value;
+
+use crate::schema::webauthn_ceremonies;
+use crate::util::token::{HashedToken, PlainToken};
+
+/// Ceremonies have to be finished within this many minutes.
+const MAX_AGE_MINUTES: i32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebauthnCeremonyKind {
+    Registration,
+    Authentication,
+}
+
+impl WebauthnCeremonyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebauthnCeremonyKind::Registration => "registration",
+            WebauthnCeremonyKind::Authentication => "authentication",
+        }
+    }
+}
+
+/// The state of a passkey registration or sign in between its two requests.
+///
+/// The state is kept in the database instead of the session cookie, which is
+/// signed but not encrypted. The session cookie only contains the token of
+/// the ceremony.
+#[derive(Debug, Identifiable, Queryable, Selectable)]
+#[diesel(table_name = webauthn_ceremonies)]
+pub struct WebauthnCeremony {
+    pub id: i32,
+    pub hashed_token: HashedToken,
+    /// `None` for sign ins of unknown users
+    pub user_id: Option<i32>,
+    pub kind: String,
+    /// `None` for sign ins of unknown users, which can never be finished
+    pub state: Option<Value>,
+    pub created_at: NaiveDateTime,
+}
+
+impl WebauthnCeremony {
+    /// Stores the state of a new ceremony, returning the plaintext token that
+    /// identifies it.
+    pub fn create(
+        conn: &mut PgConnection,
+        kind: WebauthnCeremonyKind,
+        user_id: Option<i32>,
+        state: Option<Value>,
+    ) -> QueryResult<PlainToken> {
+        let token = PlainToken::generate();
+
+        diesel::insert_into(webauthn_ceremonies::table)
+            .values((
+                webauthn_ceremonies::hashed_token.eq(token.hashed()),
+                webauthn_ceremonies::user_id.eq(user_id),
+                webauthn_ceremonies::kind.eq(kind.as_str()),
+                webauthn_ceremonies::state.eq(state),
+            ))
+            .execute(conn)?;
+
+        Ok(token)
+    }
+
+    /// Removes the ceremony with the given token and returns it, unless it
+    /// has expired. Each ceremony can only be finished once.
+    pub fn take(
+        conn: &mut PgConnection,
+        kind: WebauthnCeremonyKind,
+        token: &str,
+    ) -> QueryResult<Option<Self>> {
+        let Some(token) = HashedToken::parse(token) else {
+            return Ok(None);
+        };
+
+        diesel::delete(webauthn_ceremonies::table)
+            .filter(webauthn_ceremonies::hashed_token.eq(token))
+            .filter(webauthn_ceremonies::kind.eq(kind.as_str()))
+            .filter(webauthn_ceremonies::created_at.gt(now - MAX_AGE_MINUTES.minutes()))
+            .returning(WebauthnCeremony::as_returning())
+            .get_result(conn)
+            .optional()
+    }
+
+    /// Deletes the ceremonies that were started before the cutoff and never
+    /// finished.
+    pub fn purge_created_before(
+        conn: &mut PgConnection,
+        cutoff: NaiveDateTime,
+    ) -> QueryResult<usize> {
+        diesel::delete(webauthn_ceremonies::table)
+            .filter(webauthn_ceremonies::created_at.lt(cutoff))
+            .execute(conn)
+    }
+}
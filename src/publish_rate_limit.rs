@@ -4,10 +4,40 @@ use diesel::prelude::*;
 use diesel::sql_types::Interval;
 use std::time::Duration;
 
-use crate::schema::{publish_limit_buckets, publish_rate_overrides};
+use crate::schema::{ip_limit_buckets, publish_limit_buckets, publish_rate_overrides};
 use crate::sql::{date_part, floor, greatest, interval_part, least};
 use crate::util::errors::{AppResult, TooManyRequests};
 
+/// The actions that are rate limited with a token bucket per user, or per
+/// IP address for actions that don't require authentication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i16)]
+pub enum LimitedAction {
+    PublishNew = 0,
+    RenderReadme = 1,
+    TakedownRequest = 2,
+    Login = 3,
+}
+
+impl LimitedAction {
+    pub fn error_message(&self) -> &'static str {
+        match self {
+            LimitedAction::PublishNew => {
+                "You have published too many crates in a short period of time."
+            }
+            LimitedAction::RenderReadme => {
+                "You have rendered too many README previews in a short period of time."
+            }
+            LimitedAction::TakedownRequest => {
+                "You have submitted too many takedown requests in a short period of time."
+            }
+            LimitedAction::Login => {
+                "You have tried to log in too many times in a short period of time."
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct PublishRateLimit {
     pub rate: Duration,
@@ -40,15 +70,50 @@ struct Bucket {
     user_id: i32,
     tokens: i32,
     last_refill: NaiveDateTime,
+    action: i16,
+}
+
+#[derive(Queryable, Debug, PartialEq, Clone)]
+#[allow(dead_code)] // Most fields only read in tests
+struct IpBucket {
+    ip_address: String,
+    action: i16,
+    tokens: i32,
+    last_refill: NaiveDateTime,
 }
 
 impl PublishRateLimit {
-    pub fn check_rate_limit(&self, uploader: i32, conn: &mut PgConnection) -> AppResult<()> {
-        let bucket = self.take_token(uploader, Utc::now().naive_utc(), conn)?;
+    pub fn check_rate_limit(
+        &self,
+        uploader: i32,
+        action: LimitedAction,
+        conn: &mut PgConnection,
+    ) -> AppResult<()> {
+        let bucket = self.take_token(uploader, action, Utc::now().naive_utc(), conn)?;
+        if bucket.tokens >= 1 {
+            Ok(())
+        } else {
+            Err(Box::new(TooManyRequests {
+                action,
+                retry_after: bucket.last_refill + chrono::Duration::from_std(self.rate).unwrap(),
+            }))
+        }
+    }
+
+    /// Like [`Self::check_rate_limit`], but for actions that can be performed
+    /// without being logged in, which are limited per IP address instead.
+    pub fn check_ip_rate_limit(
+        &self,
+        ip_address: &str,
+        action: LimitedAction,
+        conn: &mut PgConnection,
+    ) -> AppResult<()> {
+        let bucket = self.take_ip_token(ip_address, action, Utc::now().naive_utc(), conn)?;
         if bucket.tokens >= 1 {
             Ok(())
         } else {
             Err(Box::new(TooManyRequests {
+                action,
                 retry_after: bucket.last_refill + chrono::Duration::from_std(self.rate).unwrap(),
             }))
         }
@@ -62,25 +127,33 @@ impl PublishRateLimit {
     /// have a token to take. Technically a "full" bucket would have
     /// `self.burst + 1` tokens in it, but that value would never be returned
     /// since we only refill buckets when trying to take a token from it.
+    ///
+    /// The overrides of the burst only apply to publishing new crates.
     fn take_token(
         &self,
         uploader: i32,
+        limited_action: LimitedAction,
         now: NaiveDateTime,
         conn: &mut PgConnection,
     ) -> QueryResult<Bucket> {
         use self::publish_limit_buckets::dsl::*;
 
-        let burst: i32 = publish_rate_overrides::table
-            .find(uploader)
-            .filter(
-                publish_rate_overrides::expires_at
-                    .is_null()
-                    .or(publish_rate_overrides::expires_at.gt(now)),
-            )
-            .select(publish_rate_overrides::burst)
-            .first(conn)
-            .optional()?
-            .unwrap_or(self.burst);
+        let burst: i32 = match limited_action {
+            LimitedAction::PublishNew => publish_rate_overrides::table
+                .find(uploader)
+                .filter(
+                    publish_rate_overrides::expires_at
+                        .is_null()
+                        .or(publish_rate_overrides::expires_at.gt(now)),
+                )
+                .select(publish_rate_overrides::burst)
+                .first(conn)
+                .optional()?
+                .unwrap_or(self.burst),
+            LimitedAction::RenderReadme | LimitedAction::TakedownRequest | LimitedAction::Login => {
+                self.burst
+            }
+        };
 
         // Interval division is poorly defined in general (what is 1 month / 30 days?)
         // However, for the intervals we're dealing with, it is always well
@@ -91,8 +164,13 @@ impl PublishRateLimit {
         );
 
         diesel::insert_into(publish_limit_buckets)
-            .values((user_id.eq(uploader), tokens.eq(burst), last_refill.eq(now)))
-            .on_conflict(user_id)
+            .values((
+                user_id.eq(uploader),
+                action.eq(limited_action as i16),
+                tokens.eq(burst),
+                last_refill.eq(now),
+            ))
+            .on_conflict((user_id, action))
             .do_update()
             .set((
                 tokens.eq(least(burst, greatest(0, tokens - 1) + tokens_to_add)),
@@ -102,12 +180,55 @@ impl PublishRateLimit {
             .get_result(conn)
     }
 
+    /// The same as [`Self::take_token`], for the buckets of IP addresses.
+    fn take_ip_token(
+        &self,
+        ip: &str,
+        limited_action: LimitedAction,
+        now: NaiveDateTime,
+        conn: &mut PgConnection,
+    ) -> QueryResult<IpBucket> {
+        use self::ip_limit_buckets::dsl::*;
+
+        let tokens_to_add = floor(
+            (date_part("epoch", now) - date_part("epoch", last_refill))
+                / interval_part("epoch", self.refill_rate()),
+        );
+
+        diesel::insert_into(ip_limit_buckets)
+            .values((
+                ip_address.eq(ip),
+                action.eq(limited_action as i16),
+                tokens.eq(self.burst),
+                last_refill.eq(now),
+            ))
+            .on_conflict((ip_address, action))
+            .do_update()
+            .set((
+                tokens.eq(least(self.burst, greatest(0, tokens - 1) + tokens_to_add)),
+                last_refill
+                    .eq(last_refill + self.refill_rate().into_sql::<Interval>() * tokens_to_add),
+            ))
+            .get_result(conn)
+    }
+
     fn refill_rate(&self) -> PgInterval {
         use diesel::dsl::*;
         (self.rate.as_millis() as i64).milliseconds()
     }
 }
 
+/// Deletes the buckets of IP addresses that have not been used since the
+/// cutoff. These are refilled completely by now, so deleting them does not
+/// change the rate limits.
+pub fn purge_ip_buckets_before(
+    conn: &mut PgConnection,
+    cutoff: NaiveDateTime,
+) -> QueryResult<usize> {
+    diesel::delete(ip_limit_buckets::table.filter(ip_limit_buckets::last_refill.lt(cutoff)))
+        .execute(conn)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -123,11 +244,17 @@ mod tests {
             rate: Duration::from_secs(1),
             burst: 10,
         };
-        let bucket = rate.take_token(new_user(conn, "user1")?, now, conn)?;
+        let bucket = rate.take_token(
+            new_user(conn, "user1")?,
+            LimitedAction::PublishNew,
+            now,
+            conn,
+        )?;
         let expected = Bucket {
             user_id: bucket.user_id,
             tokens: 10,
             last_refill: now,
+            action: LimitedAction::PublishNew as i16,
         };
         assert_eq!(expected, bucket);
 
@@ -135,11 +262,17 @@ mod tests {
             rate: Duration::from_millis(50),
             burst: 20,
         };
-        let bucket = rate.take_token(new_user(conn, "user2")?, now, conn)?;
+        let bucket = rate.take_token(
+            new_user(conn, "user2")?,
+            LimitedAction::PublishNew,
+            now,
+            conn,
+        )?;
         let expected = Bucket {
             user_id: bucket.user_id,
             tokens: 20,
             last_refill: now,
+            action: LimitedAction::PublishNew as i16,
         };
         assert_eq!(expected, bucket);
         Ok(())
@@ -155,11 +288,12 @@ mod tests {
             burst: 10,
         };
         let user_id = new_user_bucket(conn, 5, now)?.user_id;
-        let bucket = rate.take_token(user_id, now, conn)?;
+        let bucket = rate.take_token(user_id, LimitedAction::PublishNew, now, conn)?;
         let expected = Bucket {
             user_id,
             tokens: 4,
             last_refill: now,
+            action: LimitedAction::PublishNew as i16,
         };
         assert_eq!(expected, bucket);
         Ok(())
@@ -176,11 +310,12 @@ mod tests {
         };
         let user_id = new_user_bucket(conn, 5, now)?.user_id;
         let refill_time = now + chrono::Duration::seconds(2);
-        let bucket = rate.take_token(user_id, refill_time, conn)?;
+        let bucket = rate.take_token(user_id, LimitedAction::PublishNew, refill_time, conn)?;
         let expected = Bucket {
             user_id,
             tokens: 6,
             last_refill: refill_time,
+            action: LimitedAction::PublishNew as i16,
         };
         assert_eq!(expected, bucket);
         Ok(())
@@ -201,11 +336,12 @@ mod tests {
         };
         let user_id = new_user_bucket(conn, 5, now)?.user_id;
         let refill_time = now + chrono::Duration::milliseconds(300);
-        let bucket = rate.take_token(user_id, refill_time, conn)?;
+        let bucket = rate.take_token(user_id, LimitedAction::PublishNew, refill_time, conn)?;
         let expected = Bucket {
             user_id,
             tokens: 7,
             last_refill: refill_time,
+            action: LimitedAction::PublishNew as i16,
         };
         assert_eq!(expected, bucket);
         Ok(())
@@ -221,12 +357,18 @@ mod tests {
             burst: 10,
         };
         let user_id = new_user_bucket(conn, 5, now)?.user_id;
-        let bucket = rate.take_token(user_id, now + chrono::Duration::milliseconds(250), conn)?;
+        let bucket = rate.take_token(
+            user_id,
+            LimitedAction::PublishNew,
+            now + chrono::Duration::milliseconds(250),
+            conn,
+        )?;
         let expected_refill_time = now + chrono::Duration::milliseconds(200);
         let expected = Bucket {
             user_id,
             tokens: 6,
             last_refill: expected_refill_time,
+            action: LimitedAction::PublishNew as i16,
         };
         assert_eq!(expected, bucket);
         Ok(())
@@ -242,15 +384,16 @@ mod tests {
             burst: 10,
         };
         let user_id = new_user_bucket(conn, 1, now)?.user_id;
-        let bucket = rate.take_token(user_id, now, conn)?;
+        let bucket = rate.take_token(user_id, LimitedAction::PublishNew, now, conn)?;
         let expected = Bucket {
             user_id,
             tokens: 0,
             last_refill: now,
+            action: LimitedAction::PublishNew as i16,
         };
         assert_eq!(expected, bucket);
 
-        let bucket = rate.take_token(user_id, now, conn)?;
+        let bucket = rate.take_token(user_id, LimitedAction::PublishNew, now, conn)?;
         assert_eq!(expected, bucket);
         Ok(())
     }
@@ -266,11 +409,12 @@ mod tests {
         };
         let user_id = new_user_bucket(conn, 0, now)?.user_id;
         let refill_time = now + chrono::Duration::seconds(1);
-        let bucket = rate.take_token(user_id, refill_time, conn)?;
+        let bucket = rate.take_token(user_id, LimitedAction::PublishNew, refill_time, conn)?;
         let expected = Bucket {
             user_id,
             tokens: 1,
             last_refill: refill_time,
+            action: LimitedAction::PublishNew as i16,
         };
         assert_eq!(expected, bucket);
 
@@ -288,11 +432,12 @@ mod tests {
         };
         let user_id = new_user_bucket(conn, 8, now)?.user_id;
         let refill_time = now + chrono::Duration::seconds(4);
-        let bucket = rate.take_token(user_id, refill_time, conn)?;
+        let bucket = rate.take_token(user_id, LimitedAction::PublishNew, refill_time, conn)?;
         let expected = Bucket {
             user_id,
             tokens: 10,
             last_refill: refill_time,
+            action: LimitedAction::PublishNew as i16,
         };
         assert_eq!(expected, bucket);
 
@@ -318,8 +463,8 @@ mod tests {
             ))
             .execute(conn)?;
 
-        let bucket = rate.take_token(user_id, now, conn)?;
-        let other_bucket = rate.take_token(other_user_id, now, conn)?;
+        let bucket = rate.take_token(user_id, LimitedAction::PublishNew, now, conn)?;
+        let other_bucket = rate.take_token(other_user_id, LimitedAction::PublishNew, now, conn)?;
 
         assert_eq!(20, bucket.tokens);
         assert_eq!(10, other_bucket.tokens);
@@ -346,8 +491,8 @@ mod tests {
             ))
             .execute(conn)?;
 
-        let bucket = rate.take_token(user_id, now, conn)?;
-        let other_bucket = rate.take_token(other_user_id, now, conn)?;
+        let bucket = rate.take_token(user_id, LimitedAction::PublishNew, now, conn)?;
+        let other_bucket = rate.take_token(other_user_id, LimitedAction::PublishNew, now, conn)?;
 
         assert_eq!(20, bucket.tokens);
         assert_eq!(10, other_bucket.tokens);
@@ -358,8 +503,8 @@ mod tests {
             .filter(publish_rate_overrides::user_id.eq(user_id))
             .execute(conn)?;
 
-        let bucket = rate.take_token(user_id, now, conn)?;
-        let other_bucket = rate.take_token(other_user_id, now, conn)?;
+        let bucket = rate.take_token(user_id, LimitedAction::PublishNew, now, conn)?;
+        let other_bucket = rate.take_token(other_user_id, LimitedAction::PublishNew, now, conn)?;
 
         // The number of tokens of user_id is 10 and not 9 because when the new burst limit is
         // lower than the amount of available tokens, the number of available tokens is reset to
@@ -370,6 +515,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn actions_have_separate_buckets() -> QueryResult<()> {
+        let conn = &mut pg_connection();
+        let now = now();
+
+        let rate = PublishRateLimit {
+            rate: Duration::from_secs(1),
+            burst: 10,
+        };
+        let user_id = new_user_bucket(conn, 5, now)?.user_id;
+
+        diesel::insert_into(publish_rate_overrides::table)
+            .values((
+                publish_rate_overrides::user_id.eq(user_id),
+                publish_rate_overrides::burst.eq(20),
+            ))
+            .execute(conn)?;
+
+        let bucket = rate.take_token(user_id, LimitedAction::RenderReadme, now, conn)?;
+        let expected = Bucket {
+            user_id,
+            tokens: 10,
+            last_refill: now,
+            action: LimitedAction::RenderReadme as i16,
+        };
+        assert_eq!(expected, bucket);
+
+        let bucket = rate.take_token(user_id, LimitedAction::PublishNew, now, conn)?;
+        assert_eq!(4, bucket.tokens);
+        Ok(())
+    }
+
     fn new_user(conn: &mut PgConnection, gh_login: &str) -> QueryResult<i32> {
         use crate::models::NewUser;
 
@@ -381,6 +558,36 @@ mod tests {
         Ok(user.id)
     }
 
+    #[test]
+    fn ip_buckets_are_separate_per_address() -> QueryResult<()> {
+        let conn = &mut pg_connection();
+        let now = now();
+
+        let rate = PublishRateLimit {
+            rate: Duration::from_secs(1),
+            burst: 2,
+        };
+        let action = LimitedAction::TakedownRequest;
+        let bucket = rate.take_ip_token("192.0.2.1", action, now, conn)?;
+        assert_eq!(bucket.tokens, 2);
+        let bucket = rate.take_ip_token("192.0.2.1", action, now, conn)?;
+        assert_eq!(bucket.tokens, 1);
+        let bucket = rate.take_ip_token("192.0.2.1", action, now, conn)?;
+        assert_eq!(bucket.tokens, 0);
+
+        let bucket = rate.take_ip_token("192.0.2.2", action, now, conn)?;
+        assert_eq!(bucket.tokens, 2);
+
+        let refill_time = now + chrono::Duration::seconds(1);
+        let bucket = rate.take_ip_token("192.0.2.1", action, refill_time, conn)?;
+        assert_eq!(bucket.tokens, 1);
+        assert_eq!(bucket.last_refill, refill_time);
+
+        let purged = purge_ip_buckets_before(conn, refill_time)?;
+        assert_eq!(purged, 1);
+        Ok(())
+    }
+
     fn new_user_bucket(
         conn: &mut PgConnection,
         tokens: i32,
@@ -391,6 +598,7 @@ mod tests {
                 user_id: new_user(conn, "new_user")?,
                 tokens,
                 last_refill: now,
+                action: LimitedAction::PublishNew as i16,
             })
             .get_result(conn)
     }
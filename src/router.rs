@@ -4,27 +4,38 @@ use axum::routing::{delete, get, post, put};
 use axum::Router;
 
 use crate::app::AppState;
+use crate::controllers::krate::publish::{
+    MAX_BATCH_PUBLISH_CONTENT_LENGTH, MAX_PUBLISH_CONTENT_LENGTH,
+};
 use crate::controllers::*;
 use crate::util::errors::not_found;
 use crate::Env;
 
-const MAX_PUBLISH_CONTENT_LENGTH: usize = 128 * 1024 * 1024; // 128 MB
-
 pub fn build_axum_router(state: AppState) -> Router {
     let mut router = Router::new()
         // Route used by both `cargo search` and the frontend
         .route("/api/v1/crates", get(krate::search::search))
+        .route("/api/v1/crate_suggestions", get(krate::search::suggest))
         // Routes used by `cargo`
         .route(
             "/api/v1/crates/new",
             put(krate::publish::publish).layer(DefaultBodyLimit::max(MAX_PUBLISH_CONTENT_LENGTH)),
         )
+        .route(
+            "/api/v1/crates/new/batch",
+            put(krate::publish::publish_batch)
+                .layer(DefaultBodyLimit::max(MAX_BATCH_PUBLISH_CONTENT_LENGTH)),
+        )
         .route(
             "/api/v1/crates/:crate_id/owners",
             get(krate::owners::owners)
                 .put(krate::owners::add_owners)
                 .delete(krate::owners::remove_owners),
         )
+        .route(
+            "/api/v1/crates/:crate_id/owners/bulk",
+            put(krate::owners::bulk_update_owners),
+        )
         .route(
             "/api/v1/crates/:crate_id/:version/yank",
             delete(version::yank::yank),
@@ -33,6 +44,14 @@ pub fn build_axum_router(state: AppState) -> Router {
             "/api/v1/crates/:crate_id/:version/unyank",
             put(version::yank::unyank),
         )
+        .route(
+            "/api/v1/crates/:crate_id/:version/alert_yank/:token",
+            put(version::yank::alert_yank),
+        )
+        .route(
+            "/api/v1/crates/:crate_id/:version/promote",
+            put(version::promote::promote),
+        )
         .route(
             "/api/v1/crates/:crate_id/:version/download",
             get(version::downloads::download),
@@ -44,6 +63,14 @@ pub fn build_axum_router(state: AppState) -> Router {
             get(version::deprecated::show_by_id),
         )
         // Routes used by the frontend
+        .route("/api/v1/crates_batch", post(krate::batch::batch))
+        .route("/api/v1/crate_comparisons", get(krate::compare::compare))
+        .route("/api/v1/resolve", post(krate::resolve::resolve))
+        .route(
+            "/api/v1/render_readme",
+            post(krate::render_readme::render_readme),
+        )
+        .route("/api/v1/image_proxy/:digest/:url", get(image_proxy::show))
         .route("/api/v1/crates/:crate_id", get(krate::metadata::show))
         .route(
             "/api/v1/crates/:crate_id/:version",
@@ -57,6 +84,10 @@ pub fn build_axum_router(state: AppState) -> Router {
             "/api/v1/crates/:crate_id/:version/dependencies",
             get(version::metadata::dependencies),
         )
+        .route(
+            "/api/v1/crates/:crate_id/:version/dependency_graph",
+            get(version::dependency_graph::dependency_graph),
+        )
         .route(
             "/api/v1/crates/:crate_id/:version/downloads",
             get(version::downloads::downloads),
@@ -65,6 +96,10 @@ pub fn build_axum_router(state: AppState) -> Router {
             "/api/v1/crates/:crate_id/:version/authors",
             get(version::metadata::authors),
         )
+        .route(
+            "/api/v1/crates/:crate_id/badges/:badge",
+            get(krate::badges::badge),
+        )
         .route(
             "/api/v1/crates/:crate_id/downloads",
             get(krate::downloads::downloads),
@@ -81,6 +116,24 @@ pub fn build_axum_router(state: AppState) -> Router {
             "/api/v1/crates/:crate_id/following",
             get(krate::follow::following),
         )
+        .route(
+            "/api/v1/crates/:crate_id/subscription",
+            get(krate::subscription::subscription)
+                .put(krate::subscription::subscribe)
+                .delete(krate::subscription::unsubscribe),
+        )
+        .route(
+            "/api/v1/crates/:crate_id/dependents_stats",
+            get(krate::dependents::dependents_stats),
+        )
+        .route(
+            "/api/v1/crates/:crate_id/health",
+            get(krate::health::health),
+        )
+        .route(
+            "/api/v1/crates/:crate_id/maintenance",
+            get(krate::maintenance::show).put(krate::maintenance::confirm),
+        )
         .route(
             "/api/v1/crates/:crate_id/owner_team",
             get(krate::owners::owner_team),
@@ -93,19 +146,92 @@ pub fn build_axum_router(state: AppState) -> Router {
             "/api/v1/crates/:crate_id/reverse_dependencies",
             get(krate::metadata::reverse_dependencies),
         )
+        // Routes used by mirrors of the sparse index
+        .route("/api/v1/index/snapshot", get(index_snapshot::latest))
+        .route(
+            "/api/v1/index/snapshots/:sequence/download",
+            get(index_snapshot::download),
+        )
+        .route("/api/v1/index/changes", get(index_snapshot::changes))
         .route("/api/v1/keywords", get(keyword::index))
         .route("/api/v1/keywords/:keyword_id", get(keyword::show))
+        .route("/api/v1/keywords/:keyword_id/trends", get(keyword::trends))
         .route("/api/v1/categories", get(category::index))
+        .route("/api/v1/categories/suggest", get(category::suggest))
+        .route("/api/v1/categories/tree", get(category::tree))
         .route("/api/v1/categories/:category_id", get(category::show))
+        .route(
+            "/api/v1/categories/:category_id/crates",
+            get(category::crates),
+        )
         .route("/api/v1/category_slugs", get(category::slugs))
         .route(
             "/api/v1/users/:user_id",
             get(user::other::show).put(user::me::update_user),
         )
         .route("/api/v1/users/:user_id/stats", get(user::other::stats))
+        .route(
+            "/api/v1/namespace_claims",
+            get(namespace_claim::list).post(namespace_claim::create),
+        )
+        .route(
+            "/api/v1/namespace_claims/:id/verify",
+            put(namespace_claim::verify),
+        )
+        .route("/api/v1/takedown_requests", post(takedown_request::create))
         .route("/api/v1/teams/:team_id", get(team::show_team))
-        .route("/api/v1/me", get(user::me::me))
+        .route(
+            "/api/v1/me",
+            get(user::me::me).delete(user::data::request_deletion),
+        )
+        .route("/api/v1/me/export", get(user::data::export))
+        .route(
+            "/api/v1/me/export/download",
+            get(user::data::download_export),
+        )
+        .route(
+            "/api/v1/me/deletion",
+            get(user::data::deletion_status).delete(user::data::cancel_deletion),
+        )
+        .route(
+            "/api/v1/me/deletion/:token",
+            put(user::data::confirm_deletion),
+        )
         .route("/api/v1/me/updates", get(user::me::updates))
+        .route("/api/v1/me/notifications", get(user::notifications::list))
+        .route("/api/v1/me/stats", get(user::me::stats))
+        .route(
+            "/api/v1/me/publisher_verifications",
+            get(user::publisher_verification::list).post(user::publisher_verification::create),
+        )
+        .route(
+            "/api/v1/me/publisher_verifications/:id",
+            delete(user::publisher_verification::delete),
+        )
+        .route(
+            "/api/v1/me/publisher_verifications/:id/verify",
+            put(user::publisher_verification::verify),
+        )
+        .route(
+            "/api/v1/me/sessions",
+            get(user::session::list).delete(user::session::revoke_others),
+        )
+        .route("/api/v1/me/sessions/:id", delete(user::session::revoke))
+        .route("/api/v1/me/identities", get(user::identities::list))
+        .route("/api/v1/me/password", put(user::password::change))
+        .route(
+            "/api/v1/me/passkeys",
+            get(user::passkeys::list).post(user::passkeys::create),
+        )
+        .route(
+            "/api/v1/me/passkeys/challenge",
+            post(user::passkeys::challenge),
+        )
+        .route("/api/v1/me/passkeys/:id", delete(user::passkeys::delete))
+        .route(
+            "/api/v1/me/identities/:provider",
+            delete(user::identities::unlink),
+        )
         .route("/api/v1/me/tokens", get(token::list).put(token::new))
         .route("/api/v1/me/tokens/:id", delete(token::revoke))
         .route("/api/v1/tokens/current", delete(token::revoke_current))
@@ -145,8 +271,91 @@ pub fn build_axum_router(state: AppState) -> Router {
             get(user::session::authorize),
         )
         .route("/api/private/session", delete(user::session::logout))
+        .route("/api/private/session/password", post(user::password::login))
+        .route(
+            "/api/private/session/passkey/begin",
+            post(user::passkeys::begin_login),
+        )
+        .route(
+            "/api/private/session/passkey/finish",
+            post(user::passkeys::finish_login),
+        )
+        .route("/api/private/users", post(user::password::register))
+        .route(
+            "/api/private/password_resets",
+            post(user::password::recover),
+        )
+        .route(
+            "/api/private/password_resets/:token",
+            put(user::password::reset),
+        )
+        // Signed crate file downloads of private registries
+        .route(
+            "/api/private/signed_downloads/*path",
+            get(version::downloads::signed_download),
+        )
         // Metrics
         .route("/api/private/metrics/:kind", get(metrics::prometheus))
+        // Operator endpoints
+        .route(
+            "/api/private/admin/maintenance",
+            get(admin::maintenance_mode).put(admin::update_maintenance_mode),
+        )
+        .route(
+            "/api/private/admin/advisories",
+            get(admin::advisories::list),
+        )
+        .route(
+            "/api/private/admin/advisories/:crate_id/:advisory_id",
+            put(admin::advisories::update).delete(admin::advisories::delete),
+        )
+        .route(
+            "/api/private/admin/index_replays/:crate_id",
+            put(admin::index_replays::replay),
+        )
+        .route(
+            "/api/private/admin/takedown_requests",
+            get(admin::legal_holds::list_takedown_requests),
+        )
+        .route(
+            "/api/private/admin/legal_holds",
+            get(admin::legal_holds::list).post(admin::legal_holds::place),
+        )
+        .route(
+            "/api/private/admin/legal_holds/:id/lift",
+            put(admin::legal_holds::lift),
+        )
+        .route(
+            "/api/private/admin/orphaned_files",
+            get(admin::orphaned_files::reports),
+        )
+        .route(
+            "/api/private/admin/quarantines",
+            get(admin::quarantines::list),
+        )
+        .route(
+            "/api/private/admin/quarantines/:crate_id/approve",
+            put(admin::quarantines::approve),
+        )
+        .route(
+            "/api/private/admin/quarantines/:crate_id/reject",
+            put(admin::quarantines::reject),
+        )
+        .route(
+            "/api/private/admin/upload_limits",
+            get(admin::upload_limits::list),
+        )
+        .route(
+            "/api/private/admin/upload_limits/:crate_id",
+            put(admin::upload_limits::update).delete(admin::upload_limits::delete),
+        )
+        // Health checks
+        .route("/healthz", get(health::liveness))
+        .route("/readyz", get(health::readiness))
+        .route(
+            "/api/private/circuit_breakers",
+            get(health::circuit_breakers),
+        )
         // Crate ownership invitations management in the frontend
         .route(
             "/api/private/crate_owner_invitations",
@@ -168,6 +377,15 @@ pub fn build_axum_router(state: AppState) -> Router {
         );
     }
 
+    // Private registries serve their sparse index themselves, so that it is
+    // only available to authenticated clients. Upstream proxies serve it to
+    // merge the local and the upstream index files.
+    if state.config.private_registry || state.config.upstream.is_some() {
+        router = router
+            .route("/index/config.json", get(sparse_index::config_json))
+            .route("/index/*path", get(sparse_index::index_file));
+    }
+
     router
         .fallback(|| async { not_found().into_response() })
         .with_state(state)
@@ -17,6 +17,152 @@ pub mod sql_types {
     pub use diesel_full_text_search::Tsvector;
 }
 
+diesel::table! {
+    /// Representation of the `account_deletions` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    account_deletions (user_id) {
+        /// The `user_id` column of the `account_deletions` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Int4,
+        /// The `confirmation_token` column of the `account_deletions` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        confirmation_token -> Varchar,
+        /// The `requested_at` column of the `account_deletions` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        requested_at -> Timestamp,
+        /// The `confirmed_at` column of the `account_deletions` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        confirmed_at -> Nullable<Timestamp>,
+        /// The `scheduled_for` column of the `account_deletions` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        scheduled_for -> Nullable<Timestamp>,
+        /// The `completed_at` column of the `account_deletions` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        completed_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `advisories` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    advisories (id) {
+        /// The `id` column of the `advisories` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `crate_id` column of the `advisories` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crate_id -> Int4,
+        /// The `identifier` column of the `advisories` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        identifier -> Varchar,
+        /// The `affected` column of the `advisories` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        affected -> Varchar,
+        /// The `created_at` column of the `advisories` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `updated_at` column of the `advisories` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `advisory_notifications` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    advisory_notifications (id) {
+        /// The `id` column of the `advisory_notifications` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `advisory_id` column of the `advisory_notifications` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        advisory_id -> Int4,
+        /// The `created_at` column of the `advisory_notifications` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `api_token_origins` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    api_token_origins (api_token_id, ip_address) {
+        /// The `api_token_id` column of the `api_token_origins` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        api_token_id -> Int4,
+        /// The `ip_address` column of the `api_token_origins` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        ip_address -> Varchar,
+        /// The `country` column of the `api_token_origins` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        country -> Nullable<Varchar>,
+        /// The `created_at` column of the `api_token_origins` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     /// Representation of the `api_tokens` table.
     ///
@@ -74,6 +220,108 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         expired_at -> Nullable<Timestamp>,
+        /// NULL or an array of CIDR blocks. Requests using the token from other IP addresses are rejected.
+        allowed_cidrs -> Nullable<Array<Cidr>>,
+        /// The `revoked_at` column of the `api_tokens` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        revoked_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `audit_log` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    audit_log (id) {
+        /// The `id` column of the `audit_log` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `user_id` column of the `audit_log` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Int4,
+        /// The `api_token_id` column of the `audit_log` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        api_token_id -> Nullable<Int4>,
+        /// The `action` column of the `audit_log` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        action -> Varchar,
+        /// The `ip_address` column of the `audit_log` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        ip_address -> Nullable<Varchar>,
+        /// The `details` column of the `audit_log` table.
+        ///
+        /// Its SQL type is `Jsonb`.
+        ///
+        /// (Automatically generated by Diesel.)
+        details -> Jsonb,
+        /// The `created_at` column of the `audit_log` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `backfill_progress` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    backfill_progress (name) {
+        /// The `name` column of the `backfill_progress` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        name -> Varchar,
+        /// The `last_version_id` column of the `backfill_progress` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        last_version_id -> Int4,
+        /// The `paused` column of the `backfill_progress` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        paused -> Bool,
+        /// The `processed_count` column of the `backfill_progress` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        processed_count -> Int4,
+        /// The `skipped_count` column of the `backfill_progress` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        skipped_count -> Int4,
+        /// The `updated_at` column of the `backfill_progress` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        updated_at -> Timestamp,
     }
 }
 
@@ -124,6 +372,12 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         priority -> Int2,
+        /// The `request_id` column of the `background_jobs` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        request_id -> Nullable<Varchar>,
     }
 }
 
@@ -301,107 +555,329 @@ diesel::table! {
 }
 
 diesel::table! {
-    use diesel::sql_types::*;
-    use super::sql_types::Tsvector;
-
-    /// Representation of the `crates` table.
+    /// Representation of the `crate_dependent_stats` table.
     ///
     /// (Automatically generated by Diesel.)
-    crates (id) {
-        /// The `id` column of the `crates` table.
+    crate_dependent_stats (crate_id, dependent_id) {
+        /// The `crate_id` column of the `crate_dependent_stats` table.
         ///
         /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
-        id -> Int4,
-        /// The `name` column of the `crates` table.
+        crate_id -> Int4,
+        /// The `dependent_id` column of the `crate_dependent_stats` table.
         ///
-        /// Its SQL type is `Varchar`.
+        /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
-        name -> Varchar,
-        /// The `updated_at` column of the `crates` table.
+        dependent_id -> Int4,
+        /// The `downloads` column of the `crate_dependent_stats` table.
         ///
-        /// Its SQL type is `Timestamp`.
+        /// Its SQL type is `Int8`.
         ///
         /// (Automatically generated by Diesel.)
-        updated_at -> Timestamp,
-        /// The `created_at` column of the `crates` table.
+        downloads -> Int8,
+        /// The `share` column of the `crate_dependent_stats` table.
         ///
-        /// Its SQL type is `Timestamp`.
+        /// Its SQL type is `Float8`.
         ///
         /// (Automatically generated by Diesel.)
-        created_at -> Timestamp,
-        /// The `downloads` column of the `crates` table.
+        share -> Float8,
+        /// The `updated_at` column of the `crate_dependent_stats` table.
         ///
-        /// Its SQL type is `Int4`.
+        /// Its SQL type is `Timestamp`.
         ///
         /// (Automatically generated by Diesel.)
-        downloads -> Int4,
-        /// The `description` column of the `crates` table.
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `crate_index_sequences` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    crate_index_sequences (crate_name) {
+        /// The `crate_name` column of the `crate_index_sequences` table.
         ///
-        /// Its SQL type is `Nullable<Varchar>`.
+        /// Its SQL type is `Varchar`.
         ///
         /// (Automatically generated by Diesel.)
-        description -> Nullable<Varchar>,
-        /// The `homepage` column of the `crates` table.
+        crate_name -> Varchar,
+        /// The `sequence` column of the `crate_index_sequences` table.
         ///
-        /// Its SQL type is `Nullable<Varchar>`.
+        /// Its SQL type is `Int8`.
         ///
         /// (Automatically generated by Diesel.)
-        homepage -> Nullable<Varchar>,
-        /// The `documentation` column of the `crates` table.
+        sequence -> Int8,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `crate_maintenance_status` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    crate_maintenance_status (crate_id) {
+        /// The `crate_id` column of the `crate_maintenance_status` table.
         ///
-        /// Its SQL type is `Nullable<Varchar>`.
+        /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
-        documentation -> Nullable<Varchar>,
-        /// The `readme` column of the `crates` table.
+        crate_id -> Int4,
+        /// The `flagged_at` column of the `crate_maintenance_status` table.
         ///
-        /// Its SQL type is `Nullable<Varchar>`.
+        /// Its SQL type is `Nullable<Timestamp>`.
         ///
         /// (Automatically generated by Diesel.)
-        readme -> Nullable<Varchar>,
-        /// The `textsearchable_index_col` column of the `crates` table.
+        flagged_at -> Nullable<Timestamp>,
+        /// The `notified_at` column of the `crate_maintenance_status` table.
         ///
-        /// Its SQL type is `Tsvector`.
+        /// Its SQL type is `Nullable<Timestamp>`.
         ///
         /// (Automatically generated by Diesel.)
-        textsearchable_index_col -> Tsvector,
-        /// The `repository` column of the `crates` table.
+        notified_at -> Nullable<Timestamp>,
+        /// The `confirmed_at` column of the `crate_maintenance_status` table.
         ///
-        /// Its SQL type is `Nullable<Varchar>`.
+        /// Its SQL type is `Nullable<Timestamp>`.
         ///
         /// (Automatically generated by Diesel.)
-        repository -> Nullable<Varchar>,
-        /// The `max_upload_size` column of the `crates` table.
+        confirmed_at -> Nullable<Timestamp>,
+        /// The `confirmed_by` column of the `crate_maintenance_status` table.
         ///
         /// Its SQL type is `Nullable<Int4>`.
         ///
         /// (Automatically generated by Diesel.)
-        max_upload_size -> Nullable<Int4>,
+        confirmed_by -> Nullable<Int4>,
     }
 }
 
 diesel::table! {
-    /// Representation of the `crates_categories` table.
+    /// Representation of the `crate_notifications` table.
     ///
     /// (Automatically generated by Diesel.)
-    crates_categories (crate_id, category_id) {
-        /// The `crate_id` column of the `crates_categories` table.
+    crate_notifications (id) {
+        /// The `id` column of the `crate_notifications` table.
         ///
         /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
-        crate_id -> Int4,
-        /// The `category_id` column of the `crates_categories` table.
+        id -> Int4,
+        /// The `version_id` column of the `crate_notifications` table.
         ///
         /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
-        category_id -> Int4,
-    }
-}
+        version_id -> Int4,
+        /// The `action` column of the `crate_notifications` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        action -> Int4,
+        /// The `created_at` column of the `crate_notifications` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `crate_quarantines` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    crate_quarantines (crate_id) {
+        /// The `crate_id` column of the `crate_quarantines` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crate_id -> Int4,
+        /// The `user_id` column of the `crate_quarantines` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Nullable<Int4>,
+        /// The `status` column of the `crate_quarantines` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        status -> Varchar,
+        /// The `created_at` column of the `crate_quarantines` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `reviewed_at` column of the `crate_quarantines` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        reviewed_at -> Nullable<Timestamp>,
+        /// The `note` column of the `crate_quarantines` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        note -> Nullable<Varchar>,
+        /// The `duplicate_of` column of the `crate_quarantines` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        duplicate_of -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `crate_subscriptions` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    crate_subscriptions (user_id, crate_id) {
+        /// The `user_id` column of the `crate_subscriptions` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Int4,
+        /// The `crate_id` column of the `crate_subscriptions` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crate_id -> Int4,
+        /// The `new_versions` column of the `crate_subscriptions` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        new_versions -> Bool,
+        /// The `yanks` column of the `crate_subscriptions` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        yanks -> Bool,
+        /// The `created_at` column of the `crate_subscriptions` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `advisories` column of the `crate_subscriptions` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        advisories -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::Tsvector;
+
+    /// Representation of the `crates` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    crates (id) {
+        /// The `id` column of the `crates` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `name` column of the `crates` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        name -> Varchar,
+        /// The `updated_at` column of the `crates` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        updated_at -> Timestamp,
+        /// The `created_at` column of the `crates` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `downloads` column of the `crates` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        downloads -> Int4,
+        /// The `description` column of the `crates` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        description -> Nullable<Varchar>,
+        /// The `homepage` column of the `crates` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        homepage -> Nullable<Varchar>,
+        /// The `documentation` column of the `crates` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        documentation -> Nullable<Varchar>,
+        /// The `readme` column of the `crates` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        readme -> Nullable<Varchar>,
+        /// The `textsearchable_index_col` column of the `crates` table.
+        ///
+        /// Its SQL type is `Tsvector`.
+        ///
+        /// (Automatically generated by Diesel.)
+        textsearchable_index_col -> Tsvector,
+        /// The `repository` column of the `crates` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        repository -> Nullable<Varchar>,
+        /// The `health_score` column of the `crates` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        health_score -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `crates_categories` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    crates_categories (crate_id, category_id) {
+        /// The `crate_id` column of the `crates_categories` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crate_id -> Int4,
+        /// The `category_id` column of the `crates_categories` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        category_id -> Int4,
+    }
+}
 
 diesel::table! {
     /// Representation of the `crates_keywords` table.
@@ -423,6 +899,38 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Representation of the `data_retention_stats` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    data_retention_stats (kind) {
+        /// The `kind` column of the `data_retention_stats` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        kind -> Varchar,
+        /// The `purged_total` column of the `data_retention_stats` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        purged_total -> Int8,
+        /// The `last_purged` column of the `data_retention_stats` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        last_purged -> Int8,
+        /// The `last_run_at` column of the `data_retention_stats` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        last_run_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     /// Representation of the `dependencies` table.
     ///
@@ -454,298 +962,1280 @@ diesel::table! {
         req -> Varchar,
         /// The `optional` column of the `dependencies` table.
         ///
-        /// Its SQL type is `Bool`.
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        optional -> Bool,
+        /// The `default_features` column of the `dependencies` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        default_features -> Bool,
+        /// The `features` column of the `dependencies` table.
+        ///
+        /// Its SQL type is `Array<Nullable<Text>>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        features -> Array<Text>,
+        /// The `target` column of the `dependencies` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        target -> Nullable<Varchar>,
+        /// The `kind` column of the `dependencies` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        kind -> Int4,
+        /// The `explicit_name` column of the `dependencies` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        explicit_name -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `emails` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    emails (id) {
+        /// The `id` column of the `emails` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `user_id` column of the `emails` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Int4,
+        /// The `email` column of the `emails` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        email -> Varchar,
+        /// The `verified` column of the `emails` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        verified -> Bool,
+        /// The `token` column of the `emails` table.
+        ///
+        /// Its SQL type is `Text`.
+        ///
+        /// (Automatically generated by Diesel.)
+        token -> Text,
+        /// The `token_generated_at` column of the `emails` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        token_generated_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `follows` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    follows (user_id, crate_id) {
+        /// The `user_id` column of the `follows` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Int4,
+        /// The `crate_id` column of the `follows` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crate_id -> Int4,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `index_changes` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    index_changes (id) {
+        /// The `id` column of the `index_changes` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int8,
+        /// The `crate_name` column of the `index_changes` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crate_name -> Varchar,
+        /// The `created_at` column of the `index_changes` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `index_snapshots` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    index_snapshots (sequence) {
+        /// The `sequence` column of the `index_snapshots` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        sequence -> Int8,
+        /// The `crates` column of the `index_snapshots` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crates -> Int4,
+        /// The `size` column of the `index_snapshots` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        size -> Int8,
+        /// The `created_at` column of the `index_snapshots` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `index_sync_states` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    index_sync_states (crate_name, target) {
+        /// The `crate_name` column of the `index_sync_states` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crate_name -> Varchar,
+        /// The `target` column of the `index_sync_states` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        target -> Varchar,
+        /// The `synced_sequence` column of the `index_sync_states` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        synced_sequence -> Int8,
+        /// The `synced_at` column of the `index_sync_states` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        synced_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `ip_limit_buckets` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    ip_limit_buckets (ip_address, action) {
+        /// The `ip_address` column of the `ip_limit_buckets` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        ip_address -> Varchar,
+        /// The `action` column of the `ip_limit_buckets` table.
+        ///
+        /// Its SQL type is `Int2`.
+        ///
+        /// (Automatically generated by Diesel.)
+        action -> Int2,
+        /// The `tokens` column of the `ip_limit_buckets` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        tokens -> Int4,
+        /// The `last_refill` column of the `ip_limit_buckets` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        last_refill -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `keyword_stats` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    keyword_stats (keyword_id, date) {
+        /// The `keyword_id` column of the `keyword_stats` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        keyword_id -> Int4,
+        /// The `date` column of the `keyword_stats` table.
+        ///
+        /// Its SQL type is `Date`.
+        ///
+        /// (Automatically generated by Diesel.)
+        date -> Date,
+        /// The `crates_cnt` column of the `keyword_stats` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crates_cnt -> Int4,
+        /// The `downloads` column of the `keyword_stats` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        downloads -> Int8,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `keywords` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    keywords (id) {
+        /// The `id` column of the `keywords` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `keyword` column of the `keywords` table.
+        ///
+        /// Its SQL type is `Text`.
+        ///
+        /// (Automatically generated by Diesel.)
+        keyword -> Text,
+        /// The `crates_cnt` column of the `keywords` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crates_cnt -> Int4,
+        /// The `created_at` column of the `keywords` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `legal_hold_actions` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    legal_hold_actions (id) {
+        /// The `id` column of the `legal_hold_actions` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `legal_hold_id` column of the `legal_hold_actions` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        legal_hold_id -> Int4,
+        /// The `action` column of the `legal_hold_actions` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        action -> Varchar,
+        /// The `note` column of the `legal_hold_actions` table.
+        ///
+        /// Its SQL type is `Nullable<Text>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        note -> Nullable<Text>,
+        /// The `created_at` column of the `legal_hold_actions` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `legal_holds` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    legal_holds (id) {
+        /// The `id` column of the `legal_holds` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `crate_id` column of the `legal_holds` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crate_id -> Int4,
+        /// The `version_id` column of the `legal_holds` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        version_id -> Nullable<Int4>,
+        /// The `takedown_request_id` column of the `legal_holds` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        takedown_request_id -> Nullable<Int4>,
+        /// The `reason` column of the `legal_holds` table.
+        ///
+        /// Its SQL type is `Text`.
+        ///
+        /// (Automatically generated by Diesel.)
+        reason -> Text,
+        /// The `notice_url` column of the `legal_holds` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        notice_url -> Nullable<Varchar>,
+        /// The `created_at` column of the `legal_holds` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `lifted_at` column of the `legal_holds` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        lifted_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `maintenance_mode` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    maintenance_mode (id) {
+        /// The `id` column of the `maintenance_mode` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Bool,
+        /// The `enabled` column of the `maintenance_mode` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        enabled -> Bool,
+        /// The `retry_after` column of the `maintenance_mode` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        retry_after -> Int4,
+        /// The `updated_at` column of the `maintenance_mode` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `metadata` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    metadata (total_downloads) {
+        /// The `total_downloads` column of the `metadata` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        total_downloads -> Int8,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `namespace_claims` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    namespace_claims (id) {
+        /// The `id` column of the `namespace_claims` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `prefix` column of the `namespace_claims` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        prefix -> Varchar,
+        /// The `github_org_id` column of the `namespace_claims` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        github_org_id -> Int4,
+        /// The `github_org_login` column of the `namespace_claims` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        github_org_login -> Varchar,
+        /// The `verification_method` column of the `namespace_claims` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        verification_method -> Varchar,
+        /// The `domain` column of the `namespace_claims` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        domain -> Nullable<Varchar>,
+        /// The `verification_token` column of the `namespace_claims` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        verification_token -> Varchar,
+        /// The `created_by` column of the `namespace_claims` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_by -> Int4,
+        /// The `created_at` column of the `namespace_claims` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `verified_at` column of the `namespace_claims` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        verified_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `oauth_identities` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    oauth_identities (id) {
+        /// The `id` column of the `oauth_identities` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `user_id` column of the `oauth_identities` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Int4,
+        /// The `provider` column of the `oauth_identities` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        provider -> Varchar,
+        /// The `provider_user_id` column of the `oauth_identities` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        provider_user_id -> Varchar,
+        /// The `login` column of the `oauth_identities` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        login -> Varchar,
+        /// The `created_at` column of the `oauth_identities` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `orphaned_file_reports` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    orphaned_file_reports (id) {
+        /// The `id` column of the `orphaned_file_reports` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `dry_run` column of the `orphaned_file_reports` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        dry_run -> Bool,
+        /// The `orphaned_count` column of the `orphaned_file_reports` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        orphaned_count -> Int4,
+        /// The `deleted_count` column of the `orphaned_file_reports` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        deleted_count -> Int4,
+        /// The `orphaned_files` column of the `orphaned_file_reports` table.
+        ///
+        /// Its SQL type is `Array<Text>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        orphaned_files -> Array<Text>,
+        /// The `created_at` column of the `orphaned_file_reports` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `password_resets` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    password_resets (id) {
+        /// The `id` column of the `password_resets` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `user_id` column of the `password_resets` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Int4,
+        /// The `hashed_token` column of the `password_resets` table.
+        ///
+        /// Its SQL type is `Bytea`.
+        ///
+        /// (Automatically generated by Diesel.)
+        hashed_token -> Bytea,
+        /// The `created_at` column of the `password_resets` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `used_at` column of the `password_resets` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        used_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `publish_alerts` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    publish_alerts (id) {
+        /// The `id` column of the `publish_alerts` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `version_id` column of the `publish_alerts` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        version_id -> Int4,
+        /// The `reasons` column of the `publish_alerts` table.
+        ///
+        /// Its SQL type is `Array<Text>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        reasons -> Array<Text>,
+        /// The `created_at` column of the `publish_alerts` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `publish_details` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    publish_details (version_id) {
+        /// The `version_id` column of the `publish_details` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        version_id -> Int4,
+        /// The `api_token_id` column of the `publish_details` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        api_token_id -> Nullable<Int4>,
+        /// The `country` column of the `publish_details` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        country -> Nullable<Varchar>,
+        /// The `has_build_script` column of the `publish_details` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        has_build_script -> Bool,
+        /// The `large_binary_files` column of the `publish_details` table.
+        ///
+        /// Its SQL type is `Array<Text>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        large_binary_files -> Array<Text>,
+        /// The `content_hash` column of the `publish_details` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        content_hash -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `publish_idempotency_keys` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    publish_idempotency_keys (user_id, key) {
+        /// The `user_id` column of the `publish_idempotency_keys` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Int4,
+        /// The `key` column of the `publish_idempotency_keys` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        key -> Varchar,
+        /// The `request_hash` column of the `publish_idempotency_keys` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        request_hash -> Varchar,
+        /// The `response` column of the `publish_idempotency_keys` table.
+        ///
+        /// Its SQL type is `Nullable<Jsonb>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        response -> Nullable<Jsonb>,
+        /// The `created_at` column of the `publish_idempotency_keys` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `completed_at` column of the `publish_idempotency_keys` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        completed_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `publish_limit_buckets` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    publish_limit_buckets (user_id, action) {
+        /// The `user_id` column of the `publish_limit_buckets` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Int4,
+        /// The `tokens` column of the `publish_limit_buckets` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        tokens -> Int4,
+        /// The `last_refill` column of the `publish_limit_buckets` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        last_refill -> Timestamp,
+        /// The `action` column of the `publish_limit_buckets` table.
+        ///
+        /// Its SQL type is `Int2`.
+        ///
+        /// (Automatically generated by Diesel.)
+        action -> Int2,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `publish_rate_overrides` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    publish_rate_overrides (user_id) {
+        /// The `user_id` column of the `publish_rate_overrides` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Int4,
+        /// The `burst` column of the `publish_rate_overrides` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        burst -> Int4,
+        /// The `expires_at` column of the `publish_rate_overrides` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        expires_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `publisher_verifications` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    publisher_verifications (id) {
+        /// The `id` column of the `publisher_verifications` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `user_id` column of the `publisher_verifications` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Int4,
+        /// The `method` column of the `publisher_verifications` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        method -> Varchar,
+        /// The `domain` column of the `publisher_verifications` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        domain -> Nullable<Varchar>,
+        /// The `github_org_id` column of the `publisher_verifications` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        github_org_id -> Nullable<Int4>,
+        /// The `github_org_login` column of the `publisher_verifications` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        github_org_login -> Nullable<Varchar>,
+        /// The `verification_token` column of the `publisher_verifications` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        verification_token -> Varchar,
+        /// The `created_at` column of the `publisher_verifications` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `verified_at` column of the `publisher_verifications` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        verified_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `readme_renderings` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    readme_renderings (version_id) {
+        /// The `version_id` column of the `readme_renderings` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        version_id -> Int4,
+        /// The `rendered_at` column of the `readme_renderings` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        rendered_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `recent_crate_downloads` view.
+    ///
+    /// This data represents the downloads in the last 90 days.
+    /// This view does not contain realtime data.
+    /// It is refreshed by the `update-downloads` script.
+    recent_crate_downloads (crate_id) {
+        /// The `crate_id` column of the `recent_crate_downloads` view.
+        ///
+        /// Its SQL type is `Integer`.
+        crate_id -> Integer,
+        /// The `downloads` column of the `recent_crate_downloads` table.
+        ///
+        /// Its SQL type is `BigInt`.
+        downloads -> BigInt,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `reserved_crate_names` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    reserved_crate_names (name) {
+        /// The `name` column of the `reserved_crate_names` table.
+        ///
+        /// Its SQL type is `Text`.
+        ///
+        /// (Automatically generated by Diesel.)
+        name -> Text,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `takedown_requests` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    takedown_requests (id) {
+        /// The `id` column of the `takedown_requests` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `crate_name` column of the `takedown_requests` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crate_name -> Varchar,
+        /// The `version` column of the `takedown_requests` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        version -> Nullable<Varchar>,
+        /// The `complainant_name` column of the `takedown_requests` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        complainant_name -> Varchar,
+        /// The `complainant_email` column of the `takedown_requests` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        complainant_email -> Varchar,
+        /// The `description` column of the `takedown_requests` table.
+        ///
+        /// Its SQL type is `Text`.
+        ///
+        /// (Automatically generated by Diesel.)
+        description -> Text,
+        /// The `created_at` column of the `takedown_requests` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `resolved_at` column of the `takedown_requests` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        resolved_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `teams` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    teams (id) {
+        /// The `id` column of the `teams` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `login` column of the `teams` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        login -> Varchar,
+        /// The `github_id` column of the `teams` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        github_id -> Int4,
+        /// The `name` column of the `teams` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        name -> Nullable<Varchar>,
+        /// The `avatar` column of the `teams` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        avatar -> Nullable<Varchar>,
+        /// The `org_id` column of the `teams` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        org_id -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `upload_limits` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    upload_limits (crate_id) {
+        /// The `crate_id` column of the `upload_limits` table.
+        ///
+        /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
-        optional -> Bool,
-        /// The `default_features` column of the `dependencies` table.
+        crate_id -> Int4,
+        /// The `max_upload_size` column of the `upload_limits` table.
         ///
-        /// Its SQL type is `Bool`.
+        /// Its SQL type is `Int8`.
         ///
         /// (Automatically generated by Diesel.)
-        default_features -> Bool,
-        /// The `features` column of the `dependencies` table.
+        max_upload_size -> Int8,
+        /// The `max_unpack_size` column of the `upload_limits` table.
         ///
-        /// Its SQL type is `Array<Nullable<Text>>`.
+        /// Its SQL type is `Nullable<Int8>`.
         ///
         /// (Automatically generated by Diesel.)
-        features -> Array<Text>,
-        /// The `target` column of the `dependencies` table.
+        max_unpack_size -> Nullable<Int8>,
+        /// The `note` column of the `upload_limits` table.
         ///
         /// Its SQL type is `Nullable<Varchar>`.
         ///
         /// (Automatically generated by Diesel.)
-        target -> Nullable<Varchar>,
-        /// The `kind` column of the `dependencies` table.
+        note -> Nullable<Varchar>,
+        /// The `created_at` column of the `upload_limits` table.
         ///
-        /// Its SQL type is `Int4`.
+        /// Its SQL type is `Timestamp`.
         ///
         /// (Automatically generated by Diesel.)
-        kind -> Int4,
-        /// The `explicit_name` column of the `dependencies` table.
+        created_at -> Timestamp,
+        /// The `updated_at` column of the `upload_limits` table.
         ///
-        /// Its SQL type is `Nullable<Varchar>`.
+        /// Its SQL type is `Timestamp`.
         ///
         /// (Automatically generated by Diesel.)
-        explicit_name -> Nullable<Varchar>,
+        updated_at -> Timestamp,
     }
 }
 
 diesel::table! {
-    /// Representation of the `emails` table.
+    /// Representation of the `user_data_exports` table.
     ///
     /// (Automatically generated by Diesel.)
-    emails (id) {
-        /// The `id` column of the `emails` table.
+    user_data_exports (id) {
+        /// The `id` column of the `user_data_exports` table.
         ///
         /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
         id -> Int4,
-        /// The `user_id` column of the `emails` table.
+        /// The `user_id` column of the `user_data_exports` table.
         ///
         /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
         user_id -> Int4,
-        /// The `email` column of the `emails` table.
-        ///
-        /// Its SQL type is `Varchar`.
-        ///
-        /// (Automatically generated by Diesel.)
-        email -> Varchar,
-        /// The `verified` column of the `emails` table.
-        ///
-        /// Its SQL type is `Bool`.
-        ///
-        /// (Automatically generated by Diesel.)
-        verified -> Bool,
-        /// The `token` column of the `emails` table.
+        /// The `requested_at` column of the `user_data_exports` table.
         ///
-        /// Its SQL type is `Text`.
+        /// Its SQL type is `Timestamp`.
         ///
         /// (Automatically generated by Diesel.)
-        token -> Text,
-        /// The `token_generated_at` column of the `emails` table.
+        requested_at -> Timestamp,
+        /// The `completed_at` column of the `user_data_exports` table.
         ///
         /// Its SQL type is `Nullable<Timestamp>`.
         ///
         /// (Automatically generated by Diesel.)
-        token_generated_at -> Nullable<Timestamp>,
+        completed_at -> Nullable<Timestamp>,
     }
 }
 
 diesel::table! {
-    /// Representation of the `follows` table.
+    /// Representation of the `user_notifications` table.
     ///
     /// (Automatically generated by Diesel.)
-    follows (user_id, crate_id) {
-        /// The `user_id` column of the `follows` table.
+    user_notifications (id) {
+        /// The `id` column of the `user_notifications` table.
         ///
         /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
-        user_id -> Int4,
-        /// The `crate_id` column of the `follows` table.
+        id -> Int4,
+        /// The `user_id` column of the `user_notifications` table.
         ///
         /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
-        crate_id -> Int4,
-    }
-}
-
-diesel::table! {
-    /// Representation of the `keywords` table.
-    ///
-    /// (Automatically generated by Diesel.)
-    keywords (id) {
-        /// The `id` column of the `keywords` table.
+        user_id -> Int4,
+        /// The `kind` column of the `user_notifications` table.
         ///
-        /// Its SQL type is `Int4`.
+        /// Its SQL type is `Varchar`.
         ///
         /// (Automatically generated by Diesel.)
-        id -> Int4,
-        /// The `keyword` column of the `keywords` table.
+        kind -> Varchar,
+        /// The `severity` column of the `user_notifications` table.
         ///
-        /// Its SQL type is `Text`.
+        /// Its SQL type is `Varchar`.
         ///
         /// (Automatically generated by Diesel.)
-        keyword -> Text,
-        /// The `crates_cnt` column of the `keywords` table.
+        severity -> Varchar,
+        /// The `details` column of the `user_notifications` table.
         ///
-        /// Its SQL type is `Int4`.
+        /// Its SQL type is `Jsonb`.
         ///
         /// (Automatically generated by Diesel.)
-        crates_cnt -> Int4,
-        /// The `created_at` column of the `keywords` table.
+        details -> Jsonb,
+        /// The `created_at` column of the `user_notifications` table.
         ///
         /// Its SQL type is `Timestamp`.
         ///
         /// (Automatically generated by Diesel.)
         created_at -> Timestamp,
-    }
-}
-
-diesel::table! {
-    /// Representation of the `metadata` table.
-    ///
-    /// (Automatically generated by Diesel.)
-    metadata (total_downloads) {
-        /// The `total_downloads` column of the `metadata` table.
+        /// The `emailed_at` column of the `user_notifications` table.
         ///
-        /// Its SQL type is `Int8`.
+        /// Its SQL type is `Nullable<Timestamp>`.
         ///
         /// (Automatically generated by Diesel.)
-        total_downloads -> Int8,
+        emailed_at -> Nullable<Timestamp>,
     }
 }
 
 diesel::table! {
-    /// Representation of the `publish_limit_buckets` table.
+    /// Representation of the `user_passkeys` table.
     ///
     /// (Automatically generated by Diesel.)
-    publish_limit_buckets (user_id) {
-        /// The `user_id` column of the `publish_limit_buckets` table.
+    user_passkeys (id) {
+        /// The `id` column of the `user_passkeys` table.
         ///
         /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
-        user_id -> Int4,
-        /// The `tokens` column of the `publish_limit_buckets` table.
+        id -> Int4,
+        /// The `user_id` column of the `user_passkeys` table.
         ///
         /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
-        tokens -> Int4,
-        /// The `last_refill` column of the `publish_limit_buckets` table.
+        user_id -> Int4,
+        /// The `name` column of the `user_passkeys` table.
         ///
-        /// Its SQL type is `Timestamp`.
+        /// Its SQL type is `Varchar`.
         ///
         /// (Automatically generated by Diesel.)
-        last_refill -> Timestamp,
-    }
-}
-
-diesel::table! {
-    /// Representation of the `publish_rate_overrides` table.
-    ///
-    /// (Automatically generated by Diesel.)
-    publish_rate_overrides (user_id) {
-        /// The `user_id` column of the `publish_rate_overrides` table.
+        name -> Varchar,
+        /// The `credential_id` column of the `user_passkeys` table.
         ///
-        /// Its SQL type is `Int4`.
+        /// Its SQL type is `Bytea`.
         ///
         /// (Automatically generated by Diesel.)
-        user_id -> Int4,
-        /// The `burst` column of the `publish_rate_overrides` table.
+        credential_id -> Bytea,
+        /// The `passkey` column of the `user_passkeys` table.
         ///
-        /// Its SQL type is `Int4`.
+        /// Its SQL type is `Jsonb`.
         ///
         /// (Automatically generated by Diesel.)
-        burst -> Int4,
-        /// The `expires_at` column of the `publish_rate_overrides` table.
+        passkey -> Jsonb,
+        /// The `created_at` column of the `user_passkeys` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `last_used_at` column of the `user_passkeys` table.
         ///
         /// Its SQL type is `Nullable<Timestamp>`.
         ///
         /// (Automatically generated by Diesel.)
-        expires_at -> Nullable<Timestamp>,
+        last_used_at -> Nullable<Timestamp>,
     }
 }
 
 diesel::table! {
-    /// Representation of the `readme_renderings` table.
+    /// Representation of the `user_passwords` table.
     ///
     /// (Automatically generated by Diesel.)
-    readme_renderings (version_id) {
-        /// The `version_id` column of the `readme_renderings` table.
+    user_passwords (user_id) {
+        /// The `user_id` column of the `user_passwords` table.
         ///
         /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
-        version_id -> Int4,
-        /// The `rendered_at` column of the `readme_renderings` table.
+        user_id -> Int4,
+        /// The `password_hash` column of the `user_passwords` table.
         ///
-        /// Its SQL type is `Timestamp`.
+        /// Its SQL type is `Varchar`.
         ///
         /// (Automatically generated by Diesel.)
-        rendered_at -> Timestamp,
-    }
-}
-
-diesel::table! {
-    /// Representation of the `recent_crate_downloads` view.
-    ///
-    /// This data represents the downloads in the last 90 days.
-    /// This view does not contain realtime data.
-    /// It is refreshed by the `update-downloads` script.
-    recent_crate_downloads (crate_id) {
-        /// The `crate_id` column of the `recent_crate_downloads` view.
+        password_hash -> Varchar,
+        /// The `created_at` column of the `user_passwords` table.
         ///
-        /// Its SQL type is `Integer`.
-        crate_id -> Integer,
-        /// The `downloads` column of the `recent_crate_downloads` table.
+        /// Its SQL type is `Timestamp`.
         ///
-        /// Its SQL type is `BigInt`.
-        downloads -> BigInt,
-    }
-}
-
-diesel::table! {
-    /// Representation of the `reserved_crate_names` table.
-    ///
-    /// (Automatically generated by Diesel.)
-    reserved_crate_names (name) {
-        /// The `name` column of the `reserved_crate_names` table.
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `updated_at` column of the `user_passwords` table.
         ///
-        /// Its SQL type is `Text`.
+        /// Its SQL type is `Timestamp`.
         ///
         /// (Automatically generated by Diesel.)
-        name -> Text,
+        updated_at -> Timestamp,
     }
 }
 
 diesel::table! {
-    /// Representation of the `teams` table.
+    /// Representation of the `user_sessions` table.
     ///
     /// (Automatically generated by Diesel.)
-    teams (id) {
-        /// The `id` column of the `teams` table.
+    user_sessions (id) {
+        /// The `id` column of the `user_sessions` table.
         ///
         /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
         id -> Int4,
-        /// The `login` column of the `teams` table.
+        /// The `user_id` column of the `user_sessions` table.
         ///
-        /// Its SQL type is `Varchar`.
+        /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
-        login -> Varchar,
-        /// The `github_id` column of the `teams` table.
+        user_id -> Int4,
+        /// The `hashed_token` column of the `user_sessions` table.
         ///
-        /// Its SQL type is `Int4`.
+        /// Its SQL type is `Bytea`.
         ///
         /// (Automatically generated by Diesel.)
-        github_id -> Int4,
-        /// The `name` column of the `teams` table.
+        hashed_token -> Bytea,
+        /// The `user_agent` column of the `user_sessions` table.
         ///
-        /// Its SQL type is `Nullable<Varchar>`.
+        /// Its SQL type is `Varchar`.
         ///
         /// (Automatically generated by Diesel.)
-        name -> Nullable<Varchar>,
-        /// The `avatar` column of the `teams` table.
+        user_agent -> Varchar,
+        /// The `ip_address` column of the `user_sessions` table.
         ///
         /// Its SQL type is `Nullable<Varchar>`.
         ///
         /// (Automatically generated by Diesel.)
-        avatar -> Nullable<Varchar>,
-        /// The `org_id` column of the `teams` table.
+        ip_address -> Nullable<Varchar>,
+        /// The `created_at` column of the `user_sessions` table.
         ///
-        /// Its SQL type is `Nullable<Int4>`.
+        /// Its SQL type is `Timestamp`.
         ///
         /// (Automatically generated by Diesel.)
-        org_id -> Nullable<Int4>,
+        created_at -> Timestamp,
+        /// The `last_seen_at` column of the `user_sessions` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        last_seen_at -> Timestamp,
+        /// The `revoked_at` column of the `user_sessions` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        revoked_at -> Nullable<Timestamp>,
     }
 }
 
@@ -802,6 +2292,12 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         account_lock_until -> Nullable<Timestamp>,
+        /// The `created_at` column of the `users` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Nullable<Timestamp>,
     }
 }
 
@@ -843,6 +2339,32 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Representation of the `version_objects` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    version_objects (version_id) {
+        /// The `version_id` column of the `version_objects` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        version_id -> Int4,
+        /// The `object_hash` column of the `version_objects` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        object_hash -> Varchar,
+        /// The `created_at` column of the `version_objects` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     /// Representation of the `version_owner_actions` table.
     ///
@@ -977,6 +2499,18 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         rust_version -> Nullable<Varchar>,
+        /// The `staged_until` column of the `versions` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        staged_until -> Nullable<Timestamp>,
+        /// The `checksum_zstd` column of the `versions` table.
+        ///
+        /// Its SQL type is `Nullable<Bpchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        checksum_zstd -> Nullable<Bpchar>,
     }
 }
 
@@ -1000,12 +2534,70 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Representation of the `webauthn_ceremonies` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    webauthn_ceremonies (id) {
+        /// The `id` column of the `webauthn_ceremonies` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `hashed_token` column of the `webauthn_ceremonies` table.
+        ///
+        /// Its SQL type is `Bytea`.
+        ///
+        /// (Automatically generated by Diesel.)
+        hashed_token -> Bytea,
+        /// The `user_id` column of the `webauthn_ceremonies` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Nullable<Int4>,
+        /// The `kind` column of the `webauthn_ceremonies` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        kind -> Varchar,
+        /// The `state` column of the `webauthn_ceremonies` table.
+        ///
+        /// Its SQL type is `Nullable<Jsonb>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        state -> Nullable<Jsonb>,
+        /// The `created_at` column of the `webauthn_ceremonies` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+diesel::joinable!(account_deletions -> users (user_id));
+diesel::joinable!(advisories -> crates (crate_id));
+diesel::joinable!(advisory_notifications -> advisories (advisory_id));
+diesel::joinable!(api_token_origins -> api_tokens (api_token_id));
 diesel::joinable!(api_tokens -> users (user_id));
+diesel::joinable!(audit_log -> api_tokens (api_token_id));
+diesel::joinable!(audit_log -> users (user_id));
 diesel::joinable!(badges -> crates (crate_id));
+diesel::joinable!(crate_maintenance_status -> crates (crate_id));
+diesel::joinable!(crate_maintenance_status -> users (confirmed_by));
+diesel::joinable!(crate_notifications -> versions (version_id));
 diesel::joinable!(crate_owner_invitations -> crates (crate_id));
 diesel::joinable!(crate_owners -> crates (crate_id));
 diesel::joinable!(crate_owners -> teams (owner_id));
 diesel::joinable!(crate_owners -> users (owner_id));
+diesel::joinable!(crate_quarantines -> crates (crate_id));
+diesel::joinable!(crate_quarantines -> users (user_id));
+diesel::joinable!(crate_quarantines -> versions (duplicate_of));
+diesel::joinable!(crate_subscriptions -> crates (crate_id));
+diesel::joinable!(crate_subscriptions -> users (user_id));
 diesel::joinable!(crates_categories -> categories (category_id));
 diesel::joinable!(crates_categories -> crates (crate_id));
 diesel::joinable!(crates_keywords -> crates (crate_id));
@@ -1015,42 +2607,101 @@ diesel::joinable!(dependencies -> versions (version_id));
 diesel::joinable!(emails -> users (user_id));
 diesel::joinable!(follows -> crates (crate_id));
 diesel::joinable!(follows -> users (user_id));
+diesel::joinable!(keyword_stats -> keywords (keyword_id));
+diesel::joinable!(legal_hold_actions -> legal_holds (legal_hold_id));
+diesel::joinable!(legal_holds -> crates (crate_id));
+diesel::joinable!(legal_holds -> takedown_requests (takedown_request_id));
+diesel::joinable!(legal_holds -> versions (version_id));
+diesel::joinable!(namespace_claims -> users (created_by));
+diesel::joinable!(oauth_identities -> users (user_id));
+diesel::joinable!(password_resets -> users (user_id));
+diesel::joinable!(publish_alerts -> versions (version_id));
+diesel::joinable!(publish_details -> api_tokens (api_token_id));
+diesel::joinable!(publish_details -> versions (version_id));
+diesel::joinable!(publish_idempotency_keys -> users (user_id));
 diesel::joinable!(publish_limit_buckets -> users (user_id));
 diesel::joinable!(publish_rate_overrides -> users (user_id));
+diesel::joinable!(publisher_verifications -> users (user_id));
 diesel::joinable!(readme_renderings -> versions (version_id));
 diesel::joinable!(recent_crate_downloads -> crates (crate_id));
+diesel::joinable!(upload_limits -> crates (crate_id));
+diesel::joinable!(user_data_exports -> users (user_id));
+diesel::joinable!(user_notifications -> users (user_id));
+diesel::joinable!(user_passkeys -> users (user_id));
+diesel::joinable!(user_passwords -> users (user_id));
+diesel::joinable!(user_sessions -> users (user_id));
 diesel::joinable!(version_downloads -> versions (version_id));
+diesel::joinable!(version_objects -> versions (version_id));
 diesel::joinable!(version_owner_actions -> api_tokens (api_token_id));
 diesel::joinable!(version_owner_actions -> users (user_id));
 diesel::joinable!(version_owner_actions -> versions (version_id));
 diesel::joinable!(versions -> crates (crate_id));
 diesel::joinable!(versions -> users (published_by));
 diesel::joinable!(versions_published_by -> versions (version_id));
+diesel::joinable!(webauthn_ceremonies -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    account_deletions,
+    advisories,
+    advisory_notifications,
+    api_token_origins,
     api_tokens,
+    audit_log,
+    backfill_progress,
     background_jobs,
     badges,
     categories,
+    crate_dependent_stats,
+    crate_index_sequences,
+    crate_maintenance_status,
+    crate_notifications,
     crate_owner_invitations,
     crate_owners,
+    crate_quarantines,
+    crate_subscriptions,
     crates,
     crates_categories,
     crates_keywords,
+    data_retention_stats,
     dependencies,
     emails,
     follows,
+    index_changes,
+    index_snapshots,
+    index_sync_states,
+    ip_limit_buckets,
+    keyword_stats,
     keywords,
+    legal_hold_actions,
+    legal_holds,
+    maintenance_mode,
     metadata,
+    namespace_claims,
+    oauth_identities,
+    orphaned_file_reports,
+    password_resets,
+    publish_alerts,
+    publish_details,
+    publish_idempotency_keys,
     publish_limit_buckets,
     publish_rate_overrides,
+    publisher_verifications,
     readme_renderings,
     recent_crate_downloads,
     reserved_crate_names,
+    takedown_requests,
     teams,
+    upload_limits,
+    user_data_exports,
+    user_notifications,
+    user_passkeys,
+    user_passwords,
+    user_sessions,
     users,
     version_downloads,
+    version_objects,
     version_owner_actions,
     versions,
     versions_published_by,
+    webauthn_ceremonies,
 );
@@ -13,3 +13,6 @@ sql_function!(fn floor(x: Double) -> Integer);
 sql_function!(fn greatest<T: SingleValue>(x: T, y: T) -> T);
 sql_function!(fn least<T: SingleValue>(x: T, y: T) -> T);
 sql_function!(fn split_part(string: Text, delimiter: Text, n: Integer) -> Text);
+sql_function!(fn similarity(x: Text, y: Text) -> Float);
+sql_function!(fn starts_with(string: Text, prefix: Text) -> Bool);
+sql_function!(fn length(x: Text) -> Integer);
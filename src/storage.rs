@@ -2,7 +2,9 @@ mod arc_store;
 
 use crate::env;
 use crate::storage::arc_store::ArcStore;
+use crate::util::circuit_breaker::CircuitBreaker;
 use anyhow::Context;
+use futures_util::future::{join, join_all};
 use futures_util::{StreamExt, TryStreamExt};
 use http::header::CACHE_CONTROL;
 use http::{HeaderMap, HeaderValue};
@@ -12,25 +14,48 @@ use object_store::local::LocalFileSystem;
 use object_store::memory::InMemory;
 use object_store::path::Path;
 use object_store::prefix::PrefixStore;
-use object_store::{ClientOptions, ObjectStore, Result};
+use object_store::{ClientOptions, ObjectMeta, ObjectStore, Result, RetryConfig};
 use secrecy::{ExposeSecret, SecretString};
+use std::collections::HashSet;
 use std::fs;
+use std::future::Future;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
 const PREFIX_CRATES: &str = "crates";
+const PREFIX_INDEX_SNAPSHOTS: &str = "index-snapshots";
+const PREFIX_LEGAL_HOLDS: &str = "legal-holds";
+const PREFIX_OBJECTS: &str = "objects";
 const PREFIX_READMES: &str = "readmes";
+const PREFIX_STAGED_CRATES: &str = "staged-crates";
+const PREFIX_UPSTREAM: &str = "upstream";
+const PREFIX_USER_EXPORTS: &str = "user-exports";
+const HEALTH_CHECK_PATH: &str = "healthcheck";
 const DEFAULT_REGION: &str = "us-west-1";
 const CONTENT_TYPE_CRATE: &str = "application/gzip";
+const CONTENT_TYPE_CRATE_ZSTD: &str = "application/zstd";
 const CONTENT_TYPE_INDEX: &str = "text/plain";
 const CONTENT_TYPE_README: &str = "text/html";
 const CACHE_CONTROL_IMMUTABLE: &str = "public,max-age=31536000,immutable";
 const CACHE_CONTROL_INDEX: &str = "public,max-age=600";
 const CACHE_CONTROL_README: &str = "public,max-age=604800";
+const S3_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const S3_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const S3_MAX_RETRIES: usize = 3;
+const S3_RETRY_TIMEOUT: Duration = Duration::from_secs(60);
 
 #[derive(Debug)]
 pub enum StorageConfig {
-    S3 { default: S3Config, index: S3Config },
-    LocalFileSystem { path: PathBuf },
+    S3 {
+        default: S3Config,
+        index: S3Config,
+        /// Buckets in other regions that the crate files are replicated to
+        replicas: Vec<S3Config>,
+    },
+    LocalFileSystem {
+        path: PathBuf,
+    },
     InMemory,
 }
 
@@ -60,6 +85,18 @@ impl StorageConfig {
                 secret_key: secret_key.clone(),
             };
 
+            let replicas = dotenvy::var("S3_REPLICAS")
+                .map(|value| parse_replicas(&value))
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(region, bucket)| S3Config {
+                    bucket,
+                    region: Some(region),
+                    access_key: access_key.clone(),
+                    secret_key: secret_key.clone(),
+                })
+                .collect();
+
             let index = S3Config {
                 bucket: index_bucket,
                 region: index_region,
@@ -67,7 +104,11 @@ impl StorageConfig {
                 secret_key,
             };
 
-            return Self::S3 { default, index };
+            return Self::S3 {
+                default,
+                index,
+                replicas,
+            };
         }
 
         let current_dir = std::env::current_dir()
@@ -83,10 +124,17 @@ impl StorageConfig {
 pub struct Storage {
     store: Box<dyn ObjectStore>,
     crate_upload_store: Box<dyn ObjectStore>,
+    zstd_crate_upload_store: Box<dyn ObjectStore>,
     readme_upload_store: Box<dyn ObjectStore>,
 
     index_store: Box<dyn ObjectStore>,
     index_upload_store: Box<dyn ObjectStore>,
+
+    /// Stores in other regions that the crate files are replicated to.
+    replicas: Vec<Replica>,
+
+    /// Guards the uploads and downloads that happen while handling requests.
+    circuit_breaker: Arc<CircuitBreaker>,
 }
 
 impl Storage {
@@ -96,13 +144,20 @@ impl Storage {
 
     pub fn from_config(config: &StorageConfig) -> Self {
         match config {
-            StorageConfig::S3 { default, index } => {
+            StorageConfig::S3 {
+                default,
+                index,
+                replicas,
+            } => {
                 let options = ClientOptions::default();
                 let store = build_s3(default, options);
 
                 let options = client_options(CONTENT_TYPE_CRATE, CACHE_CONTROL_IMMUTABLE);
                 let crate_upload_store = build_s3(default, options);
 
+                let options = client_options(CONTENT_TYPE_CRATE_ZSTD, CACHE_CONTROL_IMMUTABLE);
+                let zstd_crate_upload_store = build_s3(default, options);
+
                 let options = client_options(CONTENT_TYPE_README, CACHE_CONTROL_README);
                 let readme_upload_store = build_s3(default, options);
 
@@ -115,9 +170,12 @@ impl Storage {
                 Self {
                     store: Box::new(store),
                     crate_upload_store: Box::new(crate_upload_store),
+                    zstd_crate_upload_store: Box::new(zstd_crate_upload_store),
                     readme_upload_store: Box::new(readme_upload_store),
                     index_store: Box::new(index_store),
                     index_upload_store: Box::new(index_upload_store),
+                    replicas: replicas.iter().map(Replica::from_config).collect(),
+                    circuit_breaker: Arc::new(CircuitBreaker::new("storage")),
                 }
             }
 
@@ -144,9 +202,12 @@ impl Storage {
                 Self {
                     store: Box::new(store.clone()),
                     crate_upload_store: Box::new(store.clone()),
+                    zstd_crate_upload_store: Box::new(store.clone()),
                     readme_upload_store: Box::new(store),
                     index_store: Box::new(index_store.clone()),
                     index_upload_store: Box::new(index_store),
+                    replicas: vec![],
+                    circuit_breaker: Arc::new(CircuitBreaker::new("storage")),
                 }
             }
 
@@ -157,9 +218,12 @@ impl Storage {
                 Self {
                     store: Box::new(store.clone()),
                     crate_upload_store: Box::new(store.clone()),
+                    zstd_crate_upload_store: Box::new(store.clone()),
                     readme_upload_store: Box::new(store.clone()),
                     index_store: Box::new(PrefixStore::new(store.clone(), "index")),
                     index_upload_store: Box::new(PrefixStore::new(store, "index")),
+                    replicas: vec![],
+                    circuit_breaker: Arc::new(CircuitBreaker::new("storage")),
                 }
             }
         }
@@ -168,7 +232,15 @@ impl Storage {
     #[instrument(skip(self))]
     pub async fn delete_all_crate_files(&self, name: &str) -> Result<()> {
         let prefix = format!("{PREFIX_CRATES}/{name}").into();
-        self.delete_all_with_prefix(&prefix).await
+        self.delete_all_with_prefix(&prefix).await?;
+
+        for replica in &self.replicas {
+            if let Err(error) = replica.delete_all_with_prefix(&prefix).await {
+                warn!(region = %replica.region, %prefix, ?error, "Failed to delete replicated files");
+            }
+        }
+
+        Ok(())
     }
 
     #[instrument(skip(self))]
@@ -180,6 +252,20 @@ impl Storage {
     #[instrument(skip(self))]
     pub async fn delete_crate_file(&self, name: &str, version: &str) -> Result<()> {
         let path = crate_file_path(name, version);
+        self.delete_replicated(&path).await
+    }
+
+    /// Deletes a crate file from the content-addressed `objects/` layout, see
+    /// [`Self::upload_crate_object`].
+    #[instrument(skip(self))]
+    pub async fn delete_crate_object(&self, hash: &str) -> Result<()> {
+        let path = crate_object_path(hash);
+        self.delete_replicated(&path).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_staged_crate_file(&self, name: &str, version: &str) -> Result<()> {
+        let path = staged_crate_file_path(name, version);
         self.store.delete(&path).await
     }
 
@@ -189,19 +275,118 @@ impl Storage {
         self.store.delete(&path).await
     }
 
-    #[instrument(skip(self))]
+    #[instrument(skip(self, bytes))]
     pub async fn upload_crate_file(&self, name: &str, version: &str, bytes: Bytes) -> Result<()> {
+        self.guarded(async {
+            if version.contains('+') {
+                let version = version.replace('+', " ");
+                let path = crate_file_path(name, &version);
+                self.put_replicated(&path, bytes.clone(), false).await?
+            }
+
+            let path = crate_file_path(name, version);
+            self.put_replicated(&path, bytes, false).await
+        })
+        .await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn download_crate_file(&self, name: &str, version: &str) -> Result<Bytes> {
+        let path = crate_file_path(name, version);
+        self.store.get(&path).await?.bytes().await
+    }
+
+    /// Uploads a crate file to the content-addressed `objects/` layout, where
+    /// it is stored under the hex-encoded SHA-256 hash of its content.
+    ///
+    /// Returns `false` without uploading anything if an object with the same
+    /// hash already exists, since it must have the same content.
+    #[instrument(skip(self, bytes))]
+    pub async fn upload_crate_object(&self, hash: &str, bytes: Bytes) -> Result<bool> {
+        let path = crate_object_path(hash);
+        self.guarded(async {
+            match self.store.head(&path).await {
+                Ok(_) => return Ok(false),
+                Err(object_store::Error::NotFound { .. }) => {}
+                Err(error) => return Err(error),
+            }
+
+            self.put_replicated(&path, bytes, false).await?;
+            Ok(true)
+        })
+        .await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn download_crate_object(&self, hash: &str) -> Result<Bytes> {
+        let path = crate_object_path(hash);
+        self.store.get(&path).await?.bytes().await
+    }
+
+    /// Downloads a crate file by its path, e.g. `crates/foo/foo-1.0.0.crate`,
+    /// which is used to serve the signed download URLs of private registries.
+    #[instrument(skip(self))]
+    pub async fn download_file(&self, path: &str) -> Result<Bytes> {
+        let path = Path::from(path);
+        self.store.get(&path).await?.bytes().await
+    }
+
+    /// Uploads the zstd-compressed copy of a crate file, which contains the
+    /// same tarball as the gzip-compressed crate file.
+    #[instrument(skip(self, bytes))]
+    pub async fn upload_zstd_crate_file(
+        &self,
+        name: &str,
+        version: &str,
+        bytes: Bytes,
+    ) -> Result<()> {
         if version.contains('+') {
             let version = version.replace('+', " ");
-            let path = crate_file_path(name, &version);
-            self.crate_upload_store.put(&path, bytes.clone()).await?
+            let path = zstd_crate_file_path(name, &version);
+            self.put_replicated(&path, bytes.clone(), true).await?
         }
 
-        let path = crate_file_path(name, version);
-        self.crate_upload_store.put(&path, bytes).await
+        let path = zstd_crate_file_path(name, version);
+        self.put_replicated(&path, bytes, true).await
+    }
+
+    /// Uploads the crate file of a staged version. Staged crate files are not
+    /// served publicly, until they are moved by [`Self::promote_staged_crate_file`].
+    #[instrument(skip(self, bytes))]
+    pub async fn upload_staged_crate_file(
+        &self,
+        name: &str,
+        version: &str,
+        bytes: Bytes,
+    ) -> Result<()> {
+        let path = staged_crate_file_path(name, version);
+        self.guarded(self.store.put(&path, bytes)).await
     }
 
     #[instrument(skip(self))]
+    pub async fn download_staged_crate_file(&self, name: &str, version: &str) -> Result<Bytes> {
+        let path = staged_crate_file_path(name, version);
+        self.store.get(&path).await?.bytes().await
+    }
+
+    /// Moves the crate file of a staged version to its public location.
+    ///
+    /// If the staged crate file does not exist anymore, it is assumed that it has
+    /// already been moved.
+    #[instrument(skip(self))]
+    pub async fn promote_staged_crate_file(&self, name: &str, version: &str) -> Result<()> {
+        let path = staged_crate_file_path(name, version);
+        let bytes = match self.store.get(&path).await {
+            Ok(result) => result.bytes().await?,
+            Err(object_store::Error::NotFound { .. }) => return Ok(()),
+            Err(error) => return Err(error),
+        };
+
+        self.upload_crate_file(name, version, bytes).await?;
+        self.store.delete(&path).await
+    }
+
+    #[instrument(skip(self, bytes))]
     pub async fn upload_readme(&self, name: &str, version: &str, bytes: Bytes) -> Result<()> {
         if version.contains('+') {
             let version = version.replace('+', " ");
@@ -213,7 +398,34 @@ impl Storage {
         self.readme_upload_store.put(&path, bytes).await
     }
 
+    /// Uploads the personal data archive of a user. These archives are stored
+    /// in the default store, which does not set public cache headers, and are
+    /// only served through the authenticated export endpoint.
+    #[instrument(skip(self, bytes))]
+    pub async fn upload_user_export(
+        &self,
+        user_id: i32,
+        export_id: i32,
+        bytes: Bytes,
+    ) -> Result<()> {
+        let path = user_export_path(user_id, export_id);
+        self.store.put(&path, bytes).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn download_user_export(&self, user_id: i32, export_id: i32) -> Result<Bytes> {
+        let path = user_export_path(user_id, export_id);
+        self.guarded(async { self.store.get(&path).await?.bytes().await })
+            .await
+    }
+
     #[instrument(skip(self))]
+    pub async fn delete_all_user_exports(&self, user_id: i32) -> Result<()> {
+        let prefix = format!("{PREFIX_USER_EXPORTS}/{user_id}").into();
+        self.delete_all_with_prefix(&prefix).await
+    }
+
+    #[instrument(skip(self, content))]
     pub async fn sync_index(&self, name: &str, content: Option<String>) -> Result<()> {
         let path = crates_io_index::Repository::relative_index_file_for_url(name).into();
         if let Some(content) = content {
@@ -223,11 +435,302 @@ impl Storage {
         }
     }
 
+    /// Downloads the sparse index file of a crate. Usually the index is served
+    /// by the CDN, but private registries serve it through the application.
+    #[instrument(skip(self))]
+    pub async fn download_index_file(&self, name: &str) -> Result<Bytes> {
+        let path = crates_io_index::Repository::relative_index_file_for_url(name).into();
+        self.index_store.get(&path).await?.bytes().await
+    }
+
+    /// Lists the paths of all files of the sparse index.
+    #[instrument(skip(self))]
+    pub async fn list_index_files(&self) -> Result<Vec<Path>> {
+        let stream = self.index_store.list(None).await?;
+        let list = stream.try_collect::<Vec<_>>().await?;
+        Ok(list.into_iter().map(|meta| meta.location).collect())
+    }
+
+    /// Uploads a snapshot of the sparse index. Snapshots are immutable, since
+    /// a new snapshot gets a new sequence number.
+    #[instrument(skip(self, bytes))]
+    pub async fn upload_index_snapshot(&self, sequence: i64, bytes: Bytes) -> Result<()> {
+        let path = index_snapshot_path(sequence);
+        self.store.put(&path, bytes).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn download_index_snapshot(&self, sequence: i64) -> Result<Bytes> {
+        let path = index_snapshot_path(sequence);
+        self.guarded(async { self.store.get(&path).await?.bytes().await })
+            .await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_index_snapshot(&self, sequence: i64) -> Result<()> {
+        let path = index_snapshot_path(sequence);
+        self.store.delete(&path).await
+    }
+
+    /// Moves the crate files of a version under legal hold out of their
+    /// public locations, so that they can't be downloaded from the CDN
+    /// anymore. The files are kept under the `legal-holds/` prefix until the
+    /// hold is lifted, see [`Self::restore_crate_files`], and the copies in
+    /// the replicas are deleted.
+    ///
+    /// Returns the public paths of the files, which have to be invalidated
+    /// on the CDNs.
+    #[instrument(skip(self))]
+    pub async fn withhold_crate_files(
+        &self,
+        name: &str,
+        version: &str,
+        object_hash: Option<&str>,
+    ) -> Result<Vec<Path>> {
+        let paths = public_crate_file_paths(name, version, object_hash);
+        for path in &paths {
+            self.move_file(path, &legal_hold_path(path)).await?;
+
+            for replica in &self.replicas {
+                if let Err(error) = replica.store.delete(path).await {
+                    warn!(region = %replica.region, %path, ?error, "Failed to delete replicated file");
+                }
+            }
+        }
+
+        Ok(paths)
+    }
+
+    /// Moves the crate files of a version back to their public locations,
+    /// once its legal hold has been lifted. The replicas are restored by the
+    /// next [`Self::reconcile_replicas`] run.
+    #[instrument(skip(self))]
+    pub async fn restore_crate_files(
+        &self,
+        name: &str,
+        version: &str,
+        object_hash: Option<&str>,
+    ) -> Result<Vec<Path>> {
+        let paths = public_crate_file_paths(name, version, object_hash);
+        for path in &paths {
+            self.move_file(&legal_hold_path(path), path).await?;
+        }
+
+        Ok(paths)
+    }
+
+    /// Caches a crate file of the upstream registry. These files are kept
+    /// apart from the local crate files, since they have no database record.
+    #[instrument(skip(self, bytes))]
+    pub async fn upload_upstream_crate_file(
+        &self,
+        name: &str,
+        version: &str,
+        bytes: Bytes,
+    ) -> Result<()> {
+        let path = upstream_crate_file_path(name, version);
+        self.store.put(&path, bytes).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn download_upstream_crate_file(&self, name: &str, version: &str) -> Result<Bytes> {
+        let path = upstream_crate_file_path(name, version);
+        self.store.get(&path).await?.bytes().await
+    }
+
+    /// Caches the sparse index file of a crate of the upstream registry, so
+    /// that it can still be served while the upstream registry is unreachable.
+    #[instrument(skip(self, content))]
+    pub async fn upload_upstream_index_file(&self, name: &str, content: String) -> Result<()> {
+        let path = upstream_index_file_path(name);
+        self.store.put(&path, content.into()).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn download_upstream_index_file(&self, name: &str) -> Result<Bytes> {
+        let path = upstream_index_file_path(name);
+        self.store.get(&path).await?.bytes().await
+    }
+
+    /// Lists all files in the default store, including their modification
+    /// times. This does not include the files of the index.
+    #[instrument(skip(self))]
+    pub async fn list_files(&self) -> Result<Vec<ObjectMeta>> {
+        self.store.list(None).await?.try_collect().await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_file(&self, path: &Path) -> Result<()> {
+        self.store.delete(path).await
+    }
+
+    /// Copies the crate files that are missing in a replica from the default
+    /// store, e.g. after a replicated write failed during a publish, and
+    /// deletes the crate files that only exist in a replica anymore.
+    #[instrument(skip(self))]
+    pub async fn reconcile_replicas(&self) -> Result<ReplicaReconciliation> {
+        let mut reconciliation = ReplicaReconciliation::default();
+
+        for prefix in [PREFIX_CRATES, PREFIX_OBJECTS] {
+            let prefix = prefix.into();
+            let primary: HashSet<Path> = list_paths(self.store.as_ref(), &prefix).await?;
+
+            for replica in &self.replicas {
+                let existing = list_paths(replica.store.as_ref(), &prefix).await?;
+
+                for path in primary.difference(&existing) {
+                    let bytes = self.store.get(path).await?.bytes().await?;
+                    let zstd = path.as_ref().ends_with(".tar.zst");
+                    replica.upload_store(zstd).put(path, bytes).await?;
+                    reconciliation.copied += 1;
+                }
+
+                for path in existing.difference(&primary) {
+                    replica.store.delete(path).await?;
+                    reconciliation.deleted += 1;
+                }
+            }
+        }
+
+        Ok(reconciliation)
+    }
+
+    /// Checks whether the file storage is reachable by requesting the metadata
+    /// of a file that usually does not exist. A "not found" response is treated
+    /// as success, since it means that the storage backend could be contacted.
+    #[instrument(skip(self))]
+    pub async fn check_availability(&self) -> Result<()> {
+        let path = HEALTH_CHECK_PATH.into();
+        match self.store.head(&path).await {
+            Ok(_) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// The circuit breaker that guards the uploads and downloads that happen
+    /// while handling requests, e.g. while publishing a crate.
+    pub fn circuit_breaker(&self) -> Arc<CircuitBreaker> {
+        self.circuit_breaker.clone()
+    }
+
     /// This should only be used for assertions in the test suite!
     pub fn as_inner(&self) -> &dyn ObjectStore {
         &self.store
     }
 
+    /// Runs the storage operation, unless the circuit breaker is open.
+    ///
+    /// "Not found" errors are not counted as failures, since they mean that
+    /// the storage backend could be contacted.
+    async fn guarded<T>(&self, operation: impl Future<Output = Result<T>>) -> Result<T> {
+        self.circuit_breaker
+            .try_acquire()
+            .map_err(|error| object_store::Error::Generic {
+                store: "circuit_breaker",
+                source: Box::new(error),
+            })?;
+
+        let result = operation.await;
+        match &result {
+            Ok(_) | Err(object_store::Error::NotFound { .. }) => {
+                self.circuit_breaker.record_success()
+            }
+            Err(_) => self.circuit_breaker.record_failure(),
+        }
+
+        result
+    }
+
+    /// Deletes the file from the default store and all replicas. Failed
+    /// deletions in replicas are only logged, since they are repaired by
+    /// [`Self::reconcile_replicas`].
+    async fn delete_replicated(&self, path: &Path) -> Result<()> {
+        self.store.delete(path).await?;
+
+        for replica in &self.replicas {
+            if let Err(error) = replica.store.delete(path).await {
+                warn!(region = %replica.region, %path, ?error, "Failed to delete replicated file");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes a crate file to the default store and to all replicas in
+    /// parallel. Failed writes to a replica are only logged, since they are
+    /// repaired by the next [`Self::reconcile_replicas`] run.
+    async fn put_replicated(&self, path: &Path, bytes: Bytes, zstd: bool) -> Result<()> {
+        let store = match zstd {
+            true => &self.zstd_crate_upload_store,
+            false => &self.crate_upload_store,
+        };
+
+        let replicas = join_all(self.replicas.iter().map(|replica| {
+            let bytes = bytes.clone();
+            async move {
+                if let Err(error) = replica.upload_store(zstd).put(path, bytes).await {
+                    warn!(region = %replica.region, %path, ?error, "Failed to replicate file");
+                }
+            }
+        }));
+
+        let (result, _) = join(store.put(path, bytes), replicas).await;
+        result
+    }
+
+    /// Moves a file within the default store. Missing files are skipped,
+    /// since they might have been moved by a previous attempt already.
+    async fn move_file(&self, from: &Path, to: &Path) -> Result<()> {
+        match self.store.rename(from, to).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(error) => Err(error),
+        }
+    }
+
+    async fn delete_all_with_prefix(&self, prefix: &Path) -> Result<()> {
+        let objects = self.store.list(Some(prefix)).await?;
+        let locations = objects.map(|meta| meta.map(|m| m.location)).boxed();
+
+        self.store
+            .delete_stream(locations)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// A copy of the crate files in another region.
+struct Replica {
+    region: String,
+    store: Box<dyn ObjectStore>,
+    crate_upload_store: Box<dyn ObjectStore>,
+    zstd_crate_upload_store: Box<dyn ObjectStore>,
+}
+
+impl Replica {
+    fn from_config(config: &S3Config) -> Self {
+        let options = client_options(CONTENT_TYPE_CRATE, CACHE_CONTROL_IMMUTABLE);
+        let crate_upload_store = build_s3(config, options);
+
+        let options = client_options(CONTENT_TYPE_CRATE_ZSTD, CACHE_CONTROL_IMMUTABLE);
+        let zstd_crate_upload_store = build_s3(config, options);
+
+        Self {
+            region: config.region.clone().unwrap_or_default(),
+            store: Box::new(build_s3(config, ClientOptions::default())),
+            crate_upload_store: Box::new(crate_upload_store),
+            zstd_crate_upload_store: Box::new(zstd_crate_upload_store),
+        }
+    }
+
+    fn upload_store(&self, zstd: bool) -> &dyn ObjectStore {
+        match zstd {
+            true => self.zstd_crate_upload_store.as_ref(),
+            false => self.crate_upload_store.as_ref(),
+        }
+    }
+
     async fn delete_all_with_prefix(&self, prefix: &Path) -> Result<()> {
         let objects = self.store.list(Some(prefix)).await?;
         let locations = objects.map(|meta| meta.map(|m| m.location)).boxed();
@@ -241,6 +744,112 @@ impl Storage {
     }
 }
 
+/// The result of [`Storage::reconcile_replicas`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ReplicaReconciliation {
+    /// Number of files that were missing in a replica
+    pub copied: usize,
+    /// Number of files that were deleted from a replica
+    pub deleted: usize,
+}
+
+/// Parses a comma-separated list of `region=bucket` pairs, as used by the
+/// `S3_REPLICAS` and `S3_REPLICA_CDNS` environment variables.
+pub fn parse_replicas(value: &str) -> Vec<(String, String)> {
+    value
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(region, bucket)| (region.trim().to_string(), bucket.trim().to_string()))
+        .filter(|(region, bucket)| !region.is_empty() && !bucket.is_empty())
+        .collect()
+}
+
+async fn list_paths(store: &dyn ObjectStore, prefix: &Path) -> Result<HashSet<Path>> {
+    store
+        .list(Some(prefix))
+        .await?
+        .map_ok(|meta| meta.location)
+        .try_collect()
+        .await
+}
+
+/// A file in the default store, as identified by its path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoredFile {
+    /// A crate file, or its zstd-compressed copy
+    CrateFile {
+        name: String,
+        version: String,
+    },
+    /// A crate file in the content-addressed layout
+    CrateObject {
+        hash: String,
+    },
+    Readme {
+        name: String,
+        version: String,
+    },
+    StagedCrateFile {
+        name: String,
+        version: String,
+    },
+    UserExport {
+        user_id: i32,
+        export_id: i32,
+    },
+}
+
+impl StoredFile {
+    /// Parses the path of a file in the default store.
+    ///
+    /// Returns `None` for files that do not belong to a known prefix, e.g.
+    /// the database dumps, or that do not match the expected naming scheme.
+    /// The `+` in versions with build metadata is restored for the copies of
+    /// files that are stored with a space instead.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let parts = path.parts().collect::<Vec<_>>();
+        let parts = parts.iter().map(AsRef::as_ref).collect::<Vec<&str>>();
+
+        // The files of crates and readmes are named `{name}/{name}-{version}{suffix}`
+        let version = |name: &str, file: &str, suffixes: &[&str]| {
+            let version = file.strip_prefix(name)?.strip_prefix('-')?;
+            let version = suffixes
+                .iter()
+                .find_map(|suffix| version.strip_suffix(suffix))?;
+            Some(version.replace(' ', "+"))
+        };
+
+        let stored_file = match parts.as_slice() {
+            [PREFIX_CRATES, name, file] => StoredFile::CrateFile {
+                name: name.to_string(),
+                version: version(name, file, &[".crate", ".tar.zst"])?,
+            },
+            [PREFIX_OBJECTS, first, second, hash]
+                if hash.get(..2) == Some(*first) && hash.get(2..4) == Some(*second) =>
+            {
+                StoredFile::CrateObject {
+                    hash: hash.to_string(),
+                }
+            }
+            [PREFIX_READMES, name, file] => StoredFile::Readme {
+                name: name.to_string(),
+                version: version(name, file, &[".html"])?,
+            },
+            [PREFIX_STAGED_CRATES, name, file] => StoredFile::StagedCrateFile {
+                name: name.to_string(),
+                version: version(name, file, &[".crate"])?,
+            },
+            [PREFIX_USER_EXPORTS, user_id, file] => StoredFile::UserExport {
+                user_id: user_id.parse().ok()?,
+                export_id: file.strip_suffix(".tar.gz")?.parse().ok()?,
+            },
+            _ => return None,
+        };
+
+        Some(stored_file)
+    }
+}
+
 fn client_options(content_type: &str, cache_control: &'static str) -> ClientOptions {
     let mut headers = HeaderMap::new();
     headers.insert(CACHE_CONTROL, HeaderValue::from_static(cache_control));
@@ -251,12 +860,23 @@ fn client_options(content_type: &str, cache_control: &'static str) -> ClientOpti
 }
 
 fn build_s3(config: &S3Config, client_options: ClientOptions) -> AmazonS3 {
+    let client_options = client_options
+        .with_connect_timeout(S3_CONNECT_TIMEOUT)
+        .with_timeout(S3_REQUEST_TIMEOUT);
+
+    let retry_config = RetryConfig {
+        max_retries: S3_MAX_RETRIES,
+        retry_timeout: S3_RETRY_TIMEOUT,
+        ..Default::default()
+    };
+
     AmazonS3Builder::new()
         .with_region(config.region.as_deref().unwrap_or(DEFAULT_REGION))
         .with_bucket_name(&config.bucket)
         .with_access_key_id(&config.access_key)
         .with_secret_access_key(config.secret_key.expose_secret())
         .with_client_options(client_options)
+        .with_retry(retry_config)
         .build()
         .context("Failed to initialize S3 code")
         .unwrap()
@@ -266,6 +886,61 @@ fn crate_file_path(name: &str, version: &str) -> Path {
     format!("{PREFIX_CRATES}/{name}/{name}-{version}.crate").into()
 }
 
+fn crate_object_path(hash: &str) -> Path {
+    let first = hash.get(..2).unwrap_or_default();
+    let second = hash.get(2..4).unwrap_or_default();
+    format!("{PREFIX_OBJECTS}/{first}/{second}/{hash}").into()
+}
+
+fn zstd_crate_file_path(name: &str, version: &str) -> Path {
+    format!("{PREFIX_CRATES}/{name}/{name}-{version}.tar.zst").into()
+}
+
+/// All paths that the crate files of a version are publicly available at.
+fn public_crate_file_paths(name: &str, version: &str, object_hash: Option<&str>) -> Vec<Path> {
+    let mut paths = vec![
+        crate_file_path(name, version),
+        zstd_crate_file_path(name, version),
+    ];
+
+    if version.contains('+') {
+        let version = version.replace('+', " ");
+        paths.push(crate_file_path(name, &version));
+        paths.push(zstd_crate_file_path(name, &version));
+    }
+
+    if let Some(hash) = object_hash {
+        paths.push(crate_object_path(hash));
+    }
+
+    paths
+}
+
+fn legal_hold_path(path: &Path) -> Path {
+    format!("{PREFIX_LEGAL_HOLDS}/{path}").into()
+}
+
+fn index_snapshot_path(sequence: i64) -> Path {
+    format!("{PREFIX_INDEX_SNAPSHOTS}/{sequence}.tar.gz").into()
+}
+
+fn staged_crate_file_path(name: &str, version: &str) -> Path {
+    format!("{PREFIX_STAGED_CRATES}/{name}/{name}-{version}.crate").into()
+}
+
+fn upstream_crate_file_path(name: &str, version: &str) -> Path {
+    format!("{PREFIX_UPSTREAM}/{PREFIX_CRATES}/{name}/{name}-{version}.crate").into()
+}
+
+fn upstream_index_file_path(name: &str) -> Path {
+    let path = crates_io_index::Repository::relative_index_file_for_url(name);
+    format!("{PREFIX_UPSTREAM}/index/{path}").into()
+}
+
+fn user_export_path(user_id: i32, export_id: i32) -> Path {
+    format!("{PREFIX_USER_EXPORTS}/{user_id}/{export_id}.tar.gz").into()
+}
+
 fn readme_path(name: &str, version: &str) -> Path {
     format!("{PREFIX_READMES}/{name}/{name}-{version}.html").into()
 }
@@ -348,6 +1023,48 @@ mod tests {
         assert_eq!(stored_files(&storage.store).await, expected_files);
     }
 
+    #[tokio::test]
+    async fn withhold_and_restore_crate_files() {
+        let storage = prepare().await;
+
+        let paths = storage
+            .withhold_crate_files("foo", "1.2.3", None)
+            .await
+            .unwrap();
+        assert_eq!(paths[0].as_ref(), "crates/foo/foo-1.2.3.crate");
+
+        let expected_files = vec![
+            "crates/bar/bar-2.0.0.crate",
+            "crates/foo/foo-1.0.0.crate",
+            "legal-holds/crates/foo/foo-1.2.3.crate",
+            "readmes/bar/bar-2.0.0.html",
+            "readmes/foo/foo-1.0.0.html",
+            "readmes/foo/foo-1.2.3.html",
+        ];
+        assert_eq!(stored_files(&storage.store).await, expected_files);
+
+        // Files that were moved already are skipped
+        storage
+            .withhold_crate_files("foo", "1.2.3", None)
+            .await
+            .unwrap();
+
+        storage
+            .restore_crate_files("foo", "1.2.3", None)
+            .await
+            .unwrap();
+
+        let expected_files = vec![
+            "crates/bar/bar-2.0.0.crate",
+            "crates/foo/foo-1.0.0.crate",
+            "crates/foo/foo-1.2.3.crate",
+            "readmes/bar/bar-2.0.0.html",
+            "readmes/foo/foo-1.0.0.html",
+            "readmes/foo/foo-1.2.3.html",
+        ];
+        assert_eq!(stored_files(&storage.store).await, expected_files);
+    }
+
     #[tokio::test]
     async fn delete_readme() {
         let storage = prepare().await;
@@ -387,6 +1104,144 @@ mod tests {
         assert_eq!(stored_files(&s.store).await, expected_files);
     }
 
+    #[tokio::test]
+    async fn upload_crate_object() {
+        let s = Storage::from_config(&StorageConfig::InMemory);
+
+        let hash = "ab12cd34".repeat(8);
+        let bytes = Bytes::from_static(b"hello world");
+        assert!(s.upload_crate_object(&hash, bytes.clone()).await.unwrap());
+
+        let expected_files = vec![format!("objects/ab/12/{hash}")];
+        assert_eq!(stored_files(&s.store).await, expected_files);
+
+        // Objects with the same hash are only stored once
+        assert!(!s.upload_crate_object(&hash, Bytes::new()).await.unwrap());
+        assert_eq!(s.download_crate_object(&hash).await.unwrap(), bytes);
+    }
+
+    fn in_memory_replica(region: &str) -> Replica {
+        let store = ArcStore::new(InMemory::new());
+
+        Replica {
+            region: region.into(),
+            store: Box::new(store.clone()),
+            crate_upload_store: Box::new(store.clone()),
+            zstd_crate_upload_store: Box::new(store),
+        }
+    }
+
+    #[tokio::test]
+    async fn upload_crate_file_to_replicas() {
+        let mut s = Storage::from_config(&StorageConfig::InMemory);
+        s.replicas.push(in_memory_replica("eu-central-1"));
+
+        s.upload_crate_file("foo", "1.2.3", Bytes::new())
+            .await
+            .unwrap();
+        s.upload_zstd_crate_file("foo", "1.2.3", Bytes::new())
+            .await
+            .unwrap();
+
+        let expected_files = vec!["crates/foo/foo-1.2.3.crate", "crates/foo/foo-1.2.3.tar.zst"];
+        assert_eq!(stored_files(&s.replicas[0].store).await, expected_files);
+
+        s.delete_crate_file("foo", "1.2.3").await.unwrap();
+
+        let expected_files = vec!["crates/foo/foo-1.2.3.tar.zst"];
+        assert_eq!(stored_files(&s.replicas[0].store).await, expected_files);
+    }
+
+    #[tokio::test]
+    async fn reconcile_replicas() {
+        let mut s = prepare().await;
+        s.replicas.push(in_memory_replica("eu-central-1"));
+
+        let replica = &s.replicas[0].store;
+        let path = "crates/foo/foo-1.0.0.crate".into();
+        replica.put(&path, Bytes::new()).await.unwrap();
+        let path = "crates/old/old-0.1.0.crate".into();
+        replica.put(&path, Bytes::new()).await.unwrap();
+
+        let reconciliation = s.reconcile_replicas().await.unwrap();
+        assert_eq!(reconciliation.copied, 2);
+        assert_eq!(reconciliation.deleted, 1);
+
+        // Readmes are not replicated
+        let expected_files = vec![
+            "crates/bar/bar-2.0.0.crate",
+            "crates/foo/foo-1.0.0.crate",
+            "crates/foo/foo-1.2.3.crate",
+        ];
+        assert_eq!(stored_files(&s.replicas[0].store).await, expected_files);
+
+        let reconciliation = s.reconcile_replicas().await.unwrap();
+        assert_eq!(reconciliation, ReplicaReconciliation::default());
+    }
+
+    #[test]
+    fn replicas_from_environment_value() {
+        assert_eq!(
+            parse_replicas("eu-central-1=crates-eu, ap-southeast-1=crates-ap,invalid,=empty"),
+            vec![
+                ("eu-central-1".to_string(), "crates-eu".to_string()),
+                ("ap-southeast-1".to_string(), "crates-ap".to_string()),
+            ]
+        );
+        assert_eq!(parse_replicas(""), vec![]);
+    }
+
+    #[test]
+    fn stored_file_from_path() {
+        let parse = |path: &str| StoredFile::from_path(&path.into());
+        let crate_file = |name: &str, version: &str| StoredFile::CrateFile {
+            name: name.into(),
+            version: version.into(),
+        };
+
+        assert_eq!(
+            parse("crates/foo/foo-1.2.3.crate"),
+            Some(crate_file("foo", "1.2.3"))
+        );
+        assert_eq!(
+            parse("crates/foo-bar/foo-bar-1.0.0 baz.tar.zst"),
+            Some(crate_file("foo-bar", "1.0.0+baz"))
+        );
+        assert_eq!(parse("crates/foo/bar-1.2.3.crate"), None);
+        assert_eq!(parse("crates/foo/foo-1.2.3.txt"), None);
+        assert_eq!(
+            parse("readmes/foo/foo-1.2.3.html"),
+            Some(StoredFile::Readme {
+                name: "foo".into(),
+                version: "1.2.3".into()
+            })
+        );
+        assert_eq!(
+            parse("staged-crates/foo/foo-1.2.3.crate"),
+            Some(StoredFile::StagedCrateFile {
+                name: "foo".into(),
+                version: "1.2.3".into()
+            })
+        );
+        assert_eq!(
+            parse("objects/ab/cd/abcdef"),
+            Some(StoredFile::CrateObject {
+                hash: "abcdef".into()
+            })
+        );
+        assert_eq!(parse("objects/ab/ef/abcdef"), None);
+        assert_eq!(
+            parse("user-exports/1/2.tar.gz"),
+            Some(StoredFile::UserExport {
+                user_id: 1,
+                export_id: 2
+            })
+        );
+        assert_eq!(parse("user-exports/1/foo.tar.gz"), None);
+        assert_eq!(parse("db-dump.tar.gz"), None);
+        assert_eq!(parse("healthcheck"), None);
+    }
+
     #[tokio::test]
     async fn upload_readme() {
         let s = Storage::from_config(&StorageConfig::InMemory);
@@ -409,6 +1264,48 @@ mod tests {
         assert_eq!(stored_files(&s.store).await, expected_files);
     }
 
+    #[tokio::test]
+    async fn promote_staged_crate_file() {
+        let s = Storage::from_config(&StorageConfig::InMemory);
+
+        let bytes = Bytes::from_static(b"hello world");
+        s.upload_staged_crate_file("foo", "1.2.3", bytes.clone())
+            .await
+            .unwrap();
+
+        let expected_files = vec!["staged-crates/foo/foo-1.2.3.crate"];
+        assert_eq!(stored_files(&s.store).await, expected_files);
+
+        let downloaded = s.download_staged_crate_file("foo", "1.2.3").await.unwrap();
+        assert_eq!(downloaded, bytes);
+
+        s.promote_staged_crate_file("foo", "1.2.3").await.unwrap();
+
+        let expected_files = vec!["crates/foo/foo-1.2.3.crate"];
+        assert_eq!(stored_files(&s.store).await, expected_files);
+
+        // Promoting the same file again is a no-op
+        s.promote_staged_crate_file("foo", "1.2.3").await.unwrap();
+        assert_eq!(stored_files(&s.store).await, expected_files);
+    }
+
+    #[tokio::test]
+    async fn delete_crate_object_and_staged_crate_file() {
+        let s = Storage::from_config(&StorageConfig::InMemory);
+
+        let bytes = Bytes::from_static(b"hello world");
+        let hash = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+        assert!(s.upload_crate_object(hash, bytes.clone()).await.unwrap());
+        s.upload_staged_crate_file("foo", "1.2.3", bytes)
+            .await
+            .unwrap();
+
+        s.delete_crate_object(hash).await.unwrap();
+        s.delete_staged_crate_file("foo", "1.2.3").await.unwrap();
+
+        assert!(stored_files(&s.store).await.is_empty());
+    }
+
     #[tokio::test]
     async fn sync_index() {
         let s = Storage::from_config(&StorageConfig::InMemory);
@@ -425,4 +1322,43 @@ mod tests {
 
         assert!(stored_files(&s.store).await.is_empty());
     }
+
+    #[tokio::test]
+    async fn index_snapshots() {
+        let s = Storage::from_config(&StorageConfig::InMemory);
+
+        s.sync_index("foo", Some("foo".to_string())).await.unwrap();
+        s.sync_index("serde", Some("serde".to_string()))
+            .await
+            .unwrap();
+
+        let index_files = s.list_index_files().await.unwrap();
+        let index_files = index_files.iter().map(Path::as_ref).collect::<Vec<_>>();
+        assert_eq!(index_files, vec!["3/f/foo", "se/rd/serde"]);
+
+        let bytes = Bytes::from_static(b"snapshot");
+        s.upload_index_snapshot(42, bytes.clone()).await.unwrap();
+
+        let expected_files = vec![
+            "index-snapshots/42.tar.gz",
+            "index/3/f/foo",
+            "index/se/rd/serde",
+        ];
+        assert_eq!(stored_files(&s.store).await, expected_files);
+        assert_eq!(s.download_index_snapshot(42).await.unwrap(), bytes);
+
+        s.delete_index_snapshot(42).await.unwrap();
+
+        let expected_files = vec!["index/3/f/foo", "index/se/rd/serde"];
+        assert_eq!(stored_files(&s.store).await, expected_files);
+    }
+
+    #[tokio::test]
+    async fn check_availability() {
+        let s = Storage::from_config(&StorageConfig::InMemory);
+
+        // The health check file does not exist, but the storage is reachable
+        s.check_availability().await.unwrap();
+        assert!(stored_files(&s.store).await.is_empty());
+    }
 }
@@ -14,6 +14,7 @@ use super::errors::*;
 use super::storage;
 use crate::background_jobs::{Environment, Job, PerformState};
 use crate::db::{DieselPool, DieselPooledConn};
+use crate::util::request_id::RequestId;
 use event::Event;
 
 mod event;
@@ -24,6 +25,7 @@ pub struct Runner {
     thread_pool: ThreadPool,
     environment: Arc<Option<Environment>>,
     job_start_timeout: Duration,
+    paused_job_types: Arc<Vec<String>>,
 }
 
 impl Runner {
@@ -41,6 +43,7 @@ impl Runner {
             thread_pool: ThreadPool::new(5),
             environment,
             job_start_timeout: Duration::from_secs(job_start_timeout),
+            paused_job_types: Default::default(),
         }
     }
 
@@ -54,6 +57,7 @@ impl Runner {
             thread_pool: ThreadPool::new(2),
             environment: Arc::new(environment),
             job_start_timeout: Duration::from_secs(10),
+            paused_job_types: Default::default(),
         }
     }
 
@@ -63,9 +67,16 @@ impl Runner {
             thread_pool: ThreadPool::new(1),
             environment: Arc::new(Some(environment)),
             job_start_timeout: Duration::from_secs(5),
+            paused_job_types: Default::default(),
         }
     }
 
+    /// Jobs of the given types are left in the queue instead of being run.
+    pub fn pause_job_types(mut self, job_types: &[&str]) -> Self {
+        self.paused_job_types = Arc::new(job_types.iter().map(|s| s.to_string()).collect());
+        self
+    }
+
     /// Runs all pending jobs in the queue
     ///
     /// This function will return once all jobs in the queue have begun running,
@@ -126,6 +137,7 @@ impl Runner {
 
         // The connection may not be `Send` so we need to clone the pool instead
         let pool = self.connection_pool.clone();
+        let paused_job_types = self.paused_job_types.clone();
         self.thread_pool.execute(move || {
             let conn = &mut *match pool.get() {
                 Ok(conn) => conn,
@@ -137,7 +149,8 @@ impl Runner {
             };
 
             let job_run_result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
-                let job = match storage::find_next_unlocked_job(conn).optional() {
+                let job = match storage::find_next_unlocked_job(conn, &paused_job_types).optional()
+                {
                     Ok(Some(j)) => {
                         let _ = sender.send(Event::Working);
                         j
@@ -161,6 +174,20 @@ impl Runner {
                 let tx_ctx = sentry::TransactionContext::new(&job.job_type, "swirl.perform");
                 let tx = sentry::start_transaction(tx_ctx);
 
+                // All log lines of the job include the ID of the request that enqueued it, and
+                // jobs enqueued by this job inherit the ID
+                let request_id = job
+                    .request_id
+                    .as_deref()
+                    .map(|id| RequestId::from_header(Some(id)));
+                let span = info_span!(
+                    "swirl.perform",
+                    job.id = job_id,
+                    job.r#type = %job.job_type,
+                    request_id = request_id.as_ref().map(RequestId::as_str).unwrap_or_default(),
+                );
+                let _span = span.enter();
+
                 let result = sentry::with_scope(
                     |scope| scope.set_span(Some(tx.clone().into())),
                     || {
@@ -170,7 +197,7 @@ impl Runner {
                             catch_unwind(|| {
                                 // Ensure the whole `AssertUnwindSafe(_)` is moved
                                 let state = state;
-                                f(job, state.0)
+                                RequestId::in_scope(request_id, || f(job, state.0))
                             })
                             .map_err(|e| try_to_extract_panic_info(&e))
                         })
@@ -338,6 +365,25 @@ mod tests {
         assert_eq!(Ok(0), remaining_jobs);
     }
 
+    #[test]
+    fn paused_job_types_are_not_run() {
+        let _guard = TestGuard::lock();
+
+        let runner = runner().pause_job_types(&["Foo"]);
+        create_dummy_job(&runner);
+
+        let (sender, receiver) = sync_channel(1);
+        runner.get_single_job(sender, |_, _| panic!("paused jobs must not be run"));
+        runner.wait_for_jobs().unwrap();
+
+        assert!(matches!(receiver.recv(), Ok(Event::NoJobAvailable)));
+
+        let remaining_jobs = background_jobs
+            .count()
+            .get_result(&mut *runner.connection().unwrap());
+        assert_eq!(Ok(1), remaining_jobs);
+    }
+
     #[test]
     fn failed_jobs_do_not_release_lock_before_updating_retry_time() {
         let _guard = TestGuard::lock();
@@ -434,7 +480,7 @@ mod tests {
     fn create_dummy_job(runner: &Runner) -> storage::BackgroundJob {
         diesel::insert_into(background_jobs)
             .values((job_type.eq("Foo"), data.eq(serde_json::json!(null))))
-            .returning((id, job_type, data))
+            .returning((id, job_type, data, request_id))
             .get_result(&mut *runner.connection().unwrap())
             .unwrap()
     }
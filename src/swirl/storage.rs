@@ -11,6 +11,8 @@ pub(super) struct BackgroundJob {
     pub(super) id: i64,
     pub(super) job_type: String,
     pub(super) data: serde_json::Value,
+    /// ID of the request or job that enqueued this job
+    pub(super) request_id: Option<String>,
 }
 
 fn retriable() -> Box<dyn BoxableExpression<background_jobs::table, Pg, SqlType = Bool>> {
@@ -24,12 +26,18 @@ fn retriable() -> Box<dyn BoxableExpression<background_jobs::table, Pg, SqlType
 
 /// Finds the next job that is unlocked, and ready to be retried. If a row is
 /// found, it will be locked.
-pub(super) fn find_next_unlocked_job(conn: &mut PgConnection) -> QueryResult<BackgroundJob> {
+///
+/// Jobs with one of the `excluded_job_types` are skipped.
+pub(super) fn find_next_unlocked_job(
+    conn: &mut PgConnection,
+    excluded_job_types: &[String],
+) -> QueryResult<BackgroundJob> {
     use schema::background_jobs::dsl::*;
 
     background_jobs
-        .select((id, job_type, data))
+        .select((id, job_type, data, request_id))
         .filter(retriable())
+        .filter(job_type.ne_all(excluded_job_types))
         .order((priority.desc(), id))
         .for_update()
         .skip_locked()
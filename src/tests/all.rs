@@ -33,11 +33,14 @@ mod categories;
 mod dump_db;
 mod github_secret_scanning;
 mod krate;
+mod local_auth;
+mod maintenance_mode;
 mod middleware;
 mod models;
 mod not_found_error;
 mod owners;
 mod pagination;
+mod private_registry;
 mod read_only_mode;
 mod record;
 mod routes;
@@ -47,6 +50,7 @@ mod server_binary;
 mod team;
 mod token;
 mod unhealthy_database;
+mod upstream_proxy;
 mod user;
 mod util;
 mod version;
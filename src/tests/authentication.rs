@@ -2,7 +2,9 @@ use crate::util::{MockRequestExt, RequestHelper, Response};
 use crate::TestApp;
 
 use crate::util::encode_session_header;
+use crates_io::models::AuditLogEntry;
 use http::{header, Method, StatusCode};
+use serde_json::Value;
 
 static URL: &str = "/api/v1/me/updates";
 static MUST_LOGIN: &[u8] = br#"{"errors":[{"detail":"must be logged in to perform that action"}]}"#;
@@ -35,7 +37,7 @@ fn cookie_auth_cannot_find_user() {
     let (app, anon) = TestApp::init().empty();
 
     let session_key = app.as_inner().session_key();
-    let cookie = encode_session_header(session_key, -1);
+    let cookie = encode_session_header(session_key, -1, "cio-unknown-session");
 
     let mut request = anon.request_builder(Method::GET, URL);
     request.header(header::COOKIE, &cookie);
@@ -43,3 +45,42 @@ fn cookie_auth_cannot_find_user() {
     let error = anon.run::<()>(request);
     assert_eq!(error.status(), StatusCode::INTERNAL_SERVER_ERROR);
 }
+
+#[test]
+fn token_auth_rejects_ip_outside_of_allowed_cidrs() {
+    let (app, anon, user) = TestApp::init().with_user();
+
+    let create_token = |name: &str, cidr: &str| {
+        let body = json!({ "api_token": { "name": name, "allowed_cidrs": [cidr] } });
+        let json = user
+            .put::<Value>("/api/v1/me/tokens", &serde_json::to_vec(&body).unwrap())
+            .good();
+        let id = json["api_token"]["id"].as_i64().unwrap() as i32;
+        (id, json["api_token"]["token"].as_str().unwrap().to_string())
+    };
+
+    let request_with_token = |token: &str| {
+        let mut request = anon.request_builder(Method::GET, "/api/v1/crates?following=1");
+        request.header(header::AUTHORIZATION, token);
+        anon.run::<()>(request)
+    };
+
+    // Test requests originate from `127.0.0.1`
+    let (_, token) = create_token("local", "127.0.0.0/8");
+    assert_eq!(request_with_token(&token).status(), StatusCode::OK);
+
+    let (id, token) = create_token("ci", "192.0.2.0/24");
+    let response = request_with_token(&token);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(response.into_json().to_string().as_bytes(), MUST_LOGIN);
+
+    let entries = app.db(|conn| AuditLogEntry::for_user(conn, user.as_model().id).unwrap());
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].action, "token_ip_rejected");
+    assert_eq!(entries[0].api_token_id, Some(id));
+    assert_eq!(entries[0].ip_address.as_deref(), Some("127.0.0.1"));
+    assert_eq!(
+        entries[0].details,
+        json!({ "allowed_cidrs": ["192.0.2.0/24"] })
+    );
+}
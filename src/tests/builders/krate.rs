@@ -1,5 +1,5 @@
 use crates_io::{
-    models::{Category, Crate, Keyword, NewCrate},
+    models::{Category, Crate, Keyword, NewCrate, NewUploadLimit},
     schema::{crates, version_downloads},
     util::errors::AppResult,
 };
@@ -17,6 +17,7 @@ pub struct CrateBuilder<'a> {
     downloads: Option<i32>,
     keywords: Vec<&'a str>,
     krate: NewCrate<'a>,
+    max_upload_size: Option<i64>,
     owner_id: i32,
     recent_downloads: Option<i32>,
     updated_at: Option<NaiveDateTime>,
@@ -35,6 +36,7 @@ impl<'a> CrateBuilder<'a> {
                 name,
                 ..NewCrate::default()
             },
+            max_upload_size: None,
             owner_id,
             recent_downloads: None,
             updated_at: None,
@@ -67,8 +69,8 @@ impl<'a> CrateBuilder<'a> {
     }
 
     /// Sets the crate's `max_upload_size` override value.
-    pub fn max_upload_size(mut self, max_upload_size: i32) -> Self {
-        self.krate.max_upload_size = Some(max_upload_size);
+    pub fn max_upload_size(mut self, max_upload_size: i64) -> Self {
+        self.max_upload_size = Some(max_upload_size);
         self
     }
 
@@ -128,6 +130,16 @@ impl<'a> CrateBuilder<'a> {
                 .get_result(connection)?;
         }
 
+        if let Some(max_upload_size) = self.max_upload_size {
+            NewUploadLimit {
+                crate_id: krate.id,
+                max_upload_size,
+                max_unpack_size: None,
+                note: None,
+            }
+            .upsert(connection)?;
+        }
+
         if self.versions.is_empty() {
             self.versions.push(VersionBuilder::new("0.99.0"));
         }
@@ -7,5 +7,5 @@ mod version;
 
 pub use dependency::DependencyBuilder;
 pub use krate::CrateBuilder;
-pub use publish::PublishBuilder;
+pub use publish::{uncompressed_tarball, PublishBuilder};
 pub use version::VersionBuilder;
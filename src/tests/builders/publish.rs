@@ -20,6 +20,25 @@ fn generate_empty_tarball() -> Vec<u8> {
     empty_tarball
 }
 
+/// Builds an uncompressed tarball with a single file of the given size, so
+/// that the size of the crate file can be controlled, e.g. to test the upload
+/// limits.
+pub fn uncompressed_tarball(name: &str, version: &str, data_size: usize) -> Vec<u8> {
+    let mut tarball = Vec::new();
+    {
+        let data = vec![b'a'; data_size];
+
+        let mut ar = tar::Builder::new(GzEncoder::new(&mut tarball, Compression::none()));
+        let mut header = tar::Header::new_gnu();
+        assert_ok!(header.set_path(format!("{name}-{version}/Cargo.toml")));
+        header.set_size(data.len() as u64);
+        header.set_cksum();
+        assert_ok!(ar.append(&header, &*data));
+        assert_ok!(ar.finish());
+    }
+    tarball
+}
+
 /// A builder for constructing a crate for the purposes of testing publishing. If you only need
 /// a crate to exist and don't need to test behavior caused by the publish request, inserting
 /// a crate into the database directly by using CrateBuilder will be faster.
@@ -33,6 +52,7 @@ pub struct PublishBuilder {
     license: Option<String>,
     license_file: Option<String>,
     readme: Option<String>,
+    repository: Option<String>,
     tarball: Vec<u8>,
     version: semver::Version,
     features: BTreeMap<u::EncodableFeatureName, Vec<u::EncodableFeature>>,
@@ -52,6 +72,7 @@ impl PublishBuilder {
             license: Some("MIT".to_string()),
             license_file: None,
             readme: None,
+            repository: None,
             tarball: EMPTY_TARBALL_BYTES.to_vec(),
             version: semver::Version::parse("1.0.0").unwrap(),
             features: BTreeMap::new(),
@@ -135,6 +156,12 @@ impl PublishBuilder {
         self
     }
 
+    /// Set the repository URL of this crate
+    pub fn repository(mut self, repository: &str) -> Self {
+        self.repository = Some(repository.to_string());
+        self
+    }
+
     /// Add a keyword to this crate.
     pub fn keyword(mut self, keyword: &str) -> Self {
         self.keywords.push(keyword.into());
@@ -193,7 +220,7 @@ impl PublishBuilder {
             ),
             license: self.license,
             license_file: self.license_file,
-            repository: None,
+            repository: self.repository,
             links: None,
         };
 
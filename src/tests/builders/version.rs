@@ -21,6 +21,7 @@ pub struct VersionBuilder<'a> {
     checksum: String,
     links: Option<String>,
     rust_version: Option<String>,
+    staged_until: Option<NaiveDateTime>,
 }
 
 impl<'a> VersionBuilder<'a> {
@@ -47,6 +48,7 @@ impl<'a> VersionBuilder<'a> {
             checksum: String::new(),
             links: None,
             rust_version: None,
+            staged_until: None,
         }
     }
 
@@ -91,6 +93,12 @@ impl<'a> VersionBuilder<'a> {
         self
     }
 
+    /// Stages the version until the given time.
+    pub fn staged_until(mut self, staged_until: NaiveDateTime) -> Self {
+        self.staged_until = Some(staged_until);
+        self
+    }
+
     pub fn build(
         self,
         crate_id: i32,
@@ -112,6 +120,7 @@ impl<'a> VersionBuilder<'a> {
             self.checksum,
             self.links,
             self.rust_version,
+            self.staged_until,
         )?
         .save(connection, "someone@example.com")?;
 
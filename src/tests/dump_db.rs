@@ -1,4 +1,6 @@
 use crate::util::FreshSchema;
+use crate::TestApp;
+use crates_io::background_jobs::Job;
 use crates_io::worker::dump_db;
 
 #[test]
@@ -19,3 +21,18 @@ fn dump_db_and_reimport_dump() {
 
     // TODO: Consistency checks on the re-imported data?
 }
+
+#[test]
+fn dump_db_job_uploads_tarball() {
+    let (app, _) = TestApp::full().empty();
+
+    let database_url = crate::env("TEST_DATABASE_URL");
+    let target_name = "db-dump.tar.gz".to_string();
+    app.db(|conn| assert_ok!(Job::dump_db(database_url, target_name).enqueue(conn)));
+    app.run_pending_background_jobs();
+
+    let uploads = app.uploaded_files();
+    let tarball = assert_some!(uploads.get("db-dump.tar.gz"));
+    // The tarball is gzip compressed
+    assert_eq!(tarball[..2], [0x1f, 0x8b]);
+}
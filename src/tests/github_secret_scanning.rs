@@ -74,6 +74,9 @@ fn github_secret_alert_revokes_token() {
         assert_eq!(tokens.len(), 1);
     });
 
+    // Ensure that the web sessions of the user were revoked too
+    user.get::<()>("/api/v1/me").assert_forbidden();
+
     // Ensure exactly one email was sent
     assert_eq!(1, app.as_inner().emails.mails_in_memory().unwrap().len());
 }
@@ -1,4 +1,6 @@
 mod following;
 mod publish;
+mod publish_alerts;
+mod publish_idempotency;
 mod versions;
 mod yanking;
@@ -1,4 +1,6 @@
-use crate::builders::{CrateBuilder, DependencyBuilder, PublishBuilder};
+use crate::builders::{
+    uncompressed_tarball, CrateBuilder, DependencyBuilder, PublishBuilder, VersionBuilder,
+};
 use crate::new_category;
 use crate::util::insta::assert_yaml_snapshot;
 use crate::util::{RequestHelper, TestApp};
@@ -6,6 +8,7 @@ use crates_io::controllers::krate::publish::{
     missing_metadata_error_message, MISSING_RIGHTS_ERROR_MESSAGE, WILDCARD_ERROR_MESSAGE,
 };
 use crates_io::models::krate::MAX_NAME_LENGTH;
+use crates_io::models::AuditLogEntry;
 use crates_io::schema::{api_tokens, emails, versions_published_by};
 use crates_io::views::GoodCrate;
 use diesel::{delete, update, ExpressionMethods, QueryDsl, RunQueryDsl};
@@ -830,10 +833,28 @@ fn bad_keywords() {
     assert_eq!(response.status(), StatusCode::OK);
     assert_eq!(
         response.into_json(),
-        json!({ "errors": [{ "detail": "invalid upload request: invalid value: string \"áccênts\", expected a valid keyword specifier at line 1 column 183" }] })
+        json!({ "errors": [{ "detail": "invalid upload request: invalid value: string \"áccênts\", expected a valid keyword specifier" }] })
     );
 }
 
+#[test]
+fn non_ascii_keywords() {
+    let (_, anon, _, token) = TestApp::full()
+        .with_config(|config| config.allow_non_ascii_keywords = true)
+        .with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo_unicode_key")
+        .keyword("Наивный")
+        .keyword("\u{FB01}le");
+    token.publish_crate(crate_to_publish).good();
+
+    // Keywords are normalized to NFKC and lowercased
+    let json = anon.show_crate("foo_unicode_key");
+    let mut keywords = json.krate.keywords.unwrap();
+    keywords.sort();
+    assert_eq!(keywords, ["file", "наивный"]);
+}
+
 #[test]
 fn good_categories() {
     let (app, _, _, token) = TestApp::full().with_token();
@@ -853,15 +874,74 @@ fn good_categories() {
 }
 
 #[test]
-fn ignored_categories() {
-    let (_, _, _, token) = TestApp::full().with_token();
+fn unknown_categories() {
+    let (app, _, _, token) = TestApp::full().with_token();
 
-    let crate_to_publish = PublishBuilder::new("foo_ignored_cat").category("bar");
-    let json = token.publish_crate(crate_to_publish).good();
+    app.db(|conn| {
+        new_category("Development tools", "development-tools", "")
+            .create_or_update(conn)
+            .unwrap();
+    });
 
-    assert_eq!(json.krate.name, "foo_ignored_cat");
-    assert_eq!(json.krate.max_version, "1.0.0");
-    assert_eq!(json.warnings.invalid_categories, vec!["bar"]);
+    let crate_to_publish = PublishBuilder::new("foo_unknown_cat")
+        .category("development-tool")
+        .category("xyz");
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{
+            "detail": "unknown categories: `development-tool` (did you mean `development-tools`?), `xyz`. See https://crates.io/category_slugs for a list of valid categories.",
+            "unknown_categories": [
+                { "category": "development-tool", "suggestions": ["development-tools"] },
+                { "category": "xyz", "suggestions": [] },
+            ],
+        }] })
+    );
+
+    assert!(app.stored_files().is_empty());
+}
+
+#[test]
+fn namespace_claims() {
+    let (app, _, _, token) = TestApp::full().with_token();
+    let owner = app.db_new_user("user-org-owner");
+    let member = app.db_new_user("user-one-team");
+    let member_token = member.db_new_token("publish");
+
+    app.db(|conn| {
+        CrateBuilder::new("acme-existing", member.as_model().id).expect_build(conn);
+    });
+
+    let body = json!({
+        "prefix": "acme",
+        "github_org": "test-org",
+        "verification_method": "github",
+    });
+    owner
+        .post::<serde_json::Value>("/api/v1/namespace_claims", body.to_string().as_bytes())
+        .good();
+
+    let response = token.publish_crate(PublishBuilder::new("acme_new").version("1.0.0"));
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "the crate name `acme_new` is within the `acme-*` namespace, which is reserved for members of the `test-org` GitHub organization" }] })
+    );
+
+    // crates that existed before the claim can still be updated by their owners
+    member_token
+        .publish_crate(PublishBuilder::new("acme-existing").version("2.0.0"))
+        .good();
+
+    // names outside of the namespace are not affected
+    token
+        .publish_crate(PublishBuilder::new("acmefoo").version("1.0.0"))
+        .good();
+
+    member_token
+        .publish_crate(PublishBuilder::new("acme-new").version("1.0.0"))
+        .good();
 }
 
 #[test]
@@ -1001,18 +1081,84 @@ fn tarball_bigger_than_max_upload_size() {
         assert_ok!(ar.finish());
     }
 
+    let upload_size = tarball.len();
     let crate_to_publish = PublishBuilder::new("foo").version("1.1.0").tarball(tarball);
 
     let response = token.publish_crate(crate_to_publish);
-    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let json = response.into_json();
+    assert_eq!(json["errors"][0]["max_upload_size"], max_upload_size);
+    assert_eq!(json["errors"][0]["upload_size"], upload_size);
+    assert_eq!(json["errors"][0]["limit_source"], "default");
     assert_eq!(
-        response.into_json(),
-        json!({ "errors": [{ "detail": format!("max upload size is: {max_upload_size}") }] })
+        json["errors"][0]["detail"],
+        format!(
+            "the uploaded crate file is {upload_size} bytes, but the max upload size of this crate \
+             is {max_upload_size} bytes. Please email help@crates.io if you need a larger limit."
+        )
     );
 
     assert!(app.stored_files().is_empty());
 }
 
+#[test]
+fn crates_with_large_crate_files_get_larger_limit() {
+    let (app, _, user, token) = TestApp::full().with_token();
+
+    // The test config has a `max_upload_size` of 3000 and a `large_crate_max_upload_size` of 6000
+    app.db(|conn| {
+        CrateBuilder::new("foo_large", user.as_model().id)
+            .version(VersionBuilder::new("1.0.0").size(2500))
+            .version(VersionBuilder::new("1.0.1").size(2800))
+            .expect_build(conn);
+    });
+
+    let tarball = uncompressed_tarball("foo_large", "1.1.0", 3500);
+    let crate_to_publish = PublishBuilder::new("foo_large")
+        .version("1.1.0")
+        .tarball(tarball);
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(response.into_json()["errors"][0]["limit_source"], "default");
+
+    // Yanked versions do not count
+    app.db(|conn| {
+        CrateBuilder::new("foo_large", user.as_model().id)
+            .version(VersionBuilder::new("1.0.2").size(2900).yanked(true))
+            .expect_build(conn);
+    });
+
+    let tarball = uncompressed_tarball("foo_large", "1.1.0", 3500);
+    let crate_to_publish = PublishBuilder::new("foo_large")
+        .version("1.1.0")
+        .tarball(tarball);
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_large", user.as_model().id)
+            .version(VersionBuilder::new("1.0.3").size(3000))
+            .expect_build(conn);
+    });
+
+    let tarball = uncompressed_tarball("foo_large", "1.1.0", 3500);
+    let crate_to_publish = PublishBuilder::new("foo_large")
+        .version("1.1.0")
+        .tarball(tarball);
+    let json = token.publish_crate(crate_to_publish).good();
+    assert_eq!(json.krate.max_version, "1.1.0");
+
+    let tarball = uncompressed_tarball("foo_large", "1.2.0", 7000);
+    let crate_to_publish = PublishBuilder::new("foo_large")
+        .version("1.2.0")
+        .tarball(tarball);
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let json = response.into_json();
+    assert_eq!(json["errors"][0]["max_upload_size"], 6000);
+    assert_eq!(json["errors"][0]["limit_source"], "large_crate");
+}
+
 #[test]
 fn publish_new_crate_rate_limited() {
     let (app, anon, _, token) = TestApp::full()
@@ -1185,3 +1331,178 @@ fn version_with_build_metadata_3() {
         );
     });
 }
+
+#[test]
+fn dry_run() {
+    let (app, anon, _, token) = TestApp::full().with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo_dry_run").version("1.0.0");
+    let response =
+        token.put::<GoodCrate>("/api/v1/crates/new?dry_run=true", &crate_to_publish.body());
+    let json = response.good();
+    assert_eq!(json.krate.name, "foo_dry_run");
+    assert_eq!(json.krate.max_version, "1.0.0");
+
+    // Nothing has been persisted or uploaded
+    assert_eq!(
+        anon.get::<()>("/api/v1/crates/foo_dry_run").status(),
+        StatusCode::NOT_FOUND
+    );
+    assert!(app.stored_files().is_empty());
+}
+
+#[test]
+fn dry_run_reports_all_errors() {
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo_dry_run").version("1.0.0");
+    token.publish_crate(crate_to_publish).good();
+
+    let crate_to_publish = PublishBuilder::new("foo_dry_run")
+        .version("1.0.0")
+        .unset_description()
+        .dependency(DependencyBuilder::new("unknown_dry_run"));
+
+    let response = token.put::<()>("/api/v1/crates/new?dry_run=true", &crate_to_publish.body());
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_json(),
+        json!({
+            "errors": [
+                { "detail": missing_metadata_error_message(&["description"]) },
+                { "detail": "crate version `1.0.0` is already uploaded" },
+                { "detail": "no known crate named `unknown_dry_run`" },
+            ]
+        })
+    );
+
+    let expected_files = vec![
+        "crates/foo_dry_run/foo_dry_run-1.0.0.crate",
+        "index/fo/o_/foo_dry_run",
+    ];
+    assert_eq!(app.stored_files(), expected_files);
+}
+
+#[test]
+fn publish_batch() {
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    let dependency = DependencyBuilder::new("foo_batch_a").version_req("1.0.0");
+
+    let mut body = PublishBuilder::new("foo_batch_a").version("1.0.0").body();
+    body.extend(
+        PublishBuilder::new("foo_batch_b")
+            .version("2.0.0")
+            .dependency(dependency)
+            .body(),
+    );
+
+    let response = token.put::<()>("/api/v1/crates/new/batch", &body);
+    assert_eq!(response.status(), StatusCode::OK);
+    app.run_pending_background_jobs();
+
+    let json = response.into_json();
+    assert_eq!(json["crates"][0]["crate"]["name"], "foo_batch_a");
+    assert_eq!(json["crates"][1]["crate"]["name"], "foo_batch_b");
+
+    let crates = app.crates_from_index_head("foo_batch_b");
+    assert_eq!(crates.len(), 1);
+    assert_eq!(crates[0].deps.len(), 1);
+    assert_eq!(crates[0].deps[0].name, "foo_batch_a");
+
+    let expected_files = vec![
+        "crates/foo_batch_a/foo_batch_a-1.0.0.crate",
+        "crates/foo_batch_b/foo_batch_b-2.0.0.crate",
+        "index/fo/o_/foo_batch_a",
+        "index/fo/o_/foo_batch_b",
+    ];
+    assert_eq!(app.stored_files(), expected_files);
+}
+
+#[test]
+fn publish_batch_is_atomic() {
+    let (app, anon, _, token) = TestApp::full().with_token();
+
+    let mut body = PublishBuilder::new("foo_batch_a").version("1.0.0").body();
+    body.extend(
+        PublishBuilder::new("foo_batch_b")
+            .version("2.0.0")
+            .dependency(DependencyBuilder::new("unknown_batch"))
+            .body(),
+    );
+
+    let response = token.put::<()>("/api/v1/crates/new/batch", &body);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "no known crate named `unknown_batch`" }] })
+    );
+
+    let response = anon.get::<()>("/api/v1/crates/foo_batch_a");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(app.stored_files().is_empty());
+}
+
+#[test]
+fn publish_batch_without_auth() {
+    let (app, anon) = TestApp::full().empty();
+
+    let body = PublishBuilder::new("foo_batch_anon")
+        .version("1.0.0")
+        .body();
+
+    let response = anon.put::<()>("/api/v1/crates/new/batch", &body);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "must be logged in to perform that action" }] })
+    );
+    assert!(app.stored_files().is_empty());
+}
+
+#[test]
+fn metadata_changes_are_recorded_in_the_audit_log() {
+    let (app, _, user, token) = TestApp::full().with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo_metadata")
+        .version("1.0.0")
+        .repository("https://github.com/foo/foo_metadata")
+        .keyword("foo");
+    token.publish_crate(crate_to_publish).good();
+
+    // Publishing the same metadata again is not recorded
+    let crate_to_publish = PublishBuilder::new("foo_metadata")
+        .version("1.0.1")
+        .repository("https://github.com/foo/foo_metadata")
+        .keyword("foo");
+    token.publish_crate(crate_to_publish).good();
+
+    let crate_to_publish = PublishBuilder::new("foo_metadata")
+        .version("1.1.0")
+        .repository("https://github.com/evil/foo_metadata")
+        .keyword("foo")
+        .keyword("bar");
+    token.publish_crate(crate_to_publish).good();
+
+    let entries = app.db(|conn| AuditLogEntry::for_user(conn, user.as_model().id).unwrap());
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].action, "crate_metadata_changed");
+    assert_eq!(entries[0].api_token_id, Some(token.as_model().id));
+    assert_eq!(
+        entries[0].details,
+        json!({
+            "crate": "foo_metadata",
+            "version": "1.1.0",
+            "changes": {
+                "repository": {
+                    "before": "https://github.com/foo/foo_metadata",
+                    "after": "https://github.com/evil/foo_metadata",
+                },
+                "keywords": {
+                    "before": ["foo"],
+                    "after": ["bar", "foo"],
+                },
+            },
+        })
+    );
+}
@@ -0,0 +1,187 @@
+use crate::builders::PublishBuilder;
+use crate::util::{MockRequestExt, MockTokenUser, RequestHelper, TestApp};
+use crates_io::background_jobs::Job;
+use crates_io::models::PublishAlert;
+use crates_io::schema::{publish_alerts, versions};
+use diesel::prelude::*;
+use http::{Method, StatusCode};
+use serde_json::Value;
+
+fn alert_emails(app: &TestApp) -> Vec<String> {
+    app.as_inner()
+        .emails
+        .mails_in_memory()
+        .unwrap()
+        .into_iter()
+        .filter(|email| email.subject.starts_with("Unusual publish"))
+        .map(|email| email.body)
+        .collect()
+}
+
+fn yank_token(body: &str) -> String {
+    let (_, token) = body.split_once("/alert-yank/").unwrap();
+    token.split_whitespace().next().unwrap().to_string()
+}
+
+/// Follows the yank link of the email like the frontend does: the
+/// `/crates/:crate_id/:version_num/alert-yank/:token` page sends a request
+/// to the API route with the same parameters.
+fn follow_yank_link(body: &str) -> String {
+    let link = body
+        .split_whitespace()
+        .find(|word| word.contains("/alert-yank/"))
+        .unwrap();
+
+    let path = &link[link.find("/crates/").unwrap()..];
+    let segments = path.split('/').collect::<Vec<_>>();
+    let ["", "crates", crate_name, version, "alert-yank", token] = segments[..] else {
+        panic!("unexpected yank link: {link}");
+    };
+
+    format!("/api/v1/crates/{crate_name}/{version}/alert_yank/{token}")
+}
+
+fn publish_from_country(token: &MockTokenUser, builder: PublishBuilder, country: &str) {
+    let mut request = token.request_builder(Method::PUT, "/api/v1/crates/new");
+    request.header("cloudfront-viewer-country", country);
+    request.with_body(&builder.body());
+    token.run::<Value>(request).good();
+    token.app().run_pending_background_jobs();
+}
+
+#[test]
+fn no_alert_for_usual_publishes() {
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    token.publish_crate(PublishBuilder::new("foo_usual")).good();
+    token
+        .publish_crate(PublishBuilder::new("foo_usual").version("1.0.1"))
+        .good();
+
+    assert!(alert_emails(&app).is_empty());
+
+    let alerts: i64 = app.db(|conn| publish_alerts::table.count().get_result(conn).unwrap());
+    assert_eq!(alerts, 0);
+}
+
+#[test]
+fn alert_for_new_token() {
+    let (app, _, user, token) = TestApp::full().with_token();
+
+    token.publish_crate(PublishBuilder::new("foo_token")).good();
+
+    let other_token = user.db_new_token("other");
+    other_token
+        .publish_crate(PublishBuilder::new("foo_token").version("1.0.1"))
+        .good();
+
+    let emails = alert_emails(&app);
+    assert_eq!(emails.len(), 1);
+    assert!(emails[0].contains("Version 1.0.1 of the crate foo_token"));
+    assert!(emails[0].contains("first publish of the crate with this API token"));
+}
+
+#[test]
+fn alert_for_new_country() {
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    publish_from_country(&token, PublishBuilder::new("foo_country"), "DE");
+    let builder = PublishBuilder::new("foo_country").version("1.0.1");
+    publish_from_country(&token, builder, "DE");
+    assert!(alert_emails(&app).is_empty());
+
+    let builder = PublishBuilder::new("foo_country").version("1.0.2");
+    publish_from_country(&token, builder, "XY");
+
+    let emails = alert_emails(&app);
+    assert_eq!(emails.len(), 1);
+    assert!(emails[0].contains("first publish of the crate from this country (XY)"));
+}
+
+#[test]
+fn alert_for_large_binary_files() {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| config.max_unpack_size = 2 * 1024 * 1024)
+        .with_token();
+
+    token.publish_crate(PublishBuilder::new("foo_blob")).good();
+
+    let blob = vec![0; 1024 * 1024];
+    let builder = PublishBuilder::new("foo_blob")
+        .version("1.0.1")
+        .files(&[("foo_blob-1.0.1/payload.bin", &blob)]);
+    token.publish_crate(builder).good();
+
+    let emails = alert_emails(&app);
+    assert_eq!(emails.len(), 1);
+    assert!(emails[0].contains("Large binary files were added to the crate: payload.bin"));
+}
+
+#[test]
+fn alert_for_added_build_script_and_yank_link() {
+    let (app, anon, _, token) = TestApp::full().with_token();
+
+    token.publish_crate(PublishBuilder::new("foo_build")).good();
+
+    let builder = PublishBuilder::new("foo_build")
+        .version("1.0.1")
+        .files(&[("foo_build-1.0.1/build.rs", b"fn main() {}")]);
+    token.publish_crate(builder).good();
+
+    let emails = alert_emails(&app);
+    assert_eq!(emails.len(), 1);
+    assert!(emails[0].contains("A build script was added to the crate."));
+
+    let alerts: Vec<PublishAlert> = app.db(|conn| {
+        publish_alerts::table
+            .select(PublishAlert::as_select())
+            .load(conn)
+            .unwrap()
+    });
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(
+        alerts[0].reasons,
+        ["A build script was added to the crate."]
+    );
+
+    // Owners are only alerted once per version
+    app.db(|conn| {
+        Job::check_publish(alerts[0].version_id)
+            .enqueue(conn)
+            .unwrap()
+    });
+    app.run_pending_background_jobs();
+    assert_eq!(alert_emails(&app).len(), 1);
+
+    let url = "/api/v1/crates/foo_build/1.0.1/alert_yank";
+    let response = anon.put::<()>(&format!("{url}/invalid-token"), b"");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // The link is only valid for the flagged version
+    let token = yank_token(&emails[0]);
+    let response = anon.put::<()>(
+        &format!("/api/v1/crates/foo_build/1.0.0/alert_yank/{token}"),
+        b"",
+    );
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    anon.put::<Value>(&follow_yank_link(&emails[0]), b"").good();
+
+    let yanked: Vec<(String, bool)> = app.db(|conn| {
+        versions::table
+            .select((versions::num, versions::yanked))
+            .order(versions::num)
+            .load(conn)
+            .unwrap()
+    });
+    assert_eq!(
+        yanked,
+        [("1.0.0".to_string(), false), ("1.0.1".to_string(), true)]
+    );
+
+    let version = anon.show_version("foo_build", "1.0.1");
+    assert!(version.version.yanked);
+
+    // Using the link again has no effect
+    anon.put::<Value>(&format!("{url}/{token}"), b"").good();
+}
@@ -0,0 +1,145 @@
+use crate::builders::{CrateBuilder, PublishBuilder};
+use crate::util::{MockRequestExt, MockTokenUser, RequestHelper, Response, TestApp};
+use chrono::{Duration, Utc};
+use crates_io::background_jobs::Job;
+use crates_io::schema::publish_idempotency_keys;
+use crates_io::views::GoodCrate;
+use diesel::prelude::*;
+use http::{Method, StatusCode};
+
+fn publish_with_key(token: &MockTokenUser, body: &[u8], key: &str) -> Response<GoodCrate> {
+    let mut request = token.request_builder(Method::PUT, "/api/v1/crates/new");
+    request.header("idempotency-key", key);
+    request.with_body(body);
+    let response = token.run(request);
+    token.app().run_pending_background_jobs();
+    response
+}
+
+fn saved_keys(app: &TestApp) -> i64 {
+    app.db(|conn| {
+        publish_idempotency_keys::table
+            .count()
+            .get_result(conn)
+            .unwrap()
+    })
+}
+
+#[test]
+fn retry_with_same_key_replays_response() {
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    let body = PublishBuilder::new("foo_retry").version("1.0.0").body();
+
+    let first = publish_with_key(&token, &body, "retry-1").good();
+    assert_eq!(first.krate.name, "foo_retry");
+    assert_eq!(first.krate.max_version, "1.0.0");
+
+    // Without the key this would fail with "crate version `1.0.0` is already uploaded"
+    let second = publish_with_key(&token, &body, "retry-1").good();
+    assert_eq!(second.krate.name, "foo_retry");
+    assert_eq!(second.krate.max_version, "1.0.0");
+
+    let crates = app.crates_from_index_head("foo_retry");
+    assert_eq!(crates.len(), 1);
+    assert_eq!(saved_keys(&app), 1);
+}
+
+#[test]
+fn reusing_key_for_different_publish_fails() {
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    let body = PublishBuilder::new("foo_reuse").version("1.0.0").body();
+    publish_with_key(&token, &body, "reused").good();
+
+    let body = PublishBuilder::new("foo_reuse").version("1.0.1").body();
+    let response = publish_with_key(&token, &body, "reused");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "this idempotency key was already used for a different publish request" }] })
+    );
+
+    let crates = app.crates_from_index_head("foo_reuse");
+    assert_eq!(crates.len(), 1);
+}
+
+#[test]
+fn invalid_key_is_rejected() {
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    let body = PublishBuilder::new("foo_invalid_key").body();
+    let response = publish_with_key(&token, &body, "not a valid key");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "invalid `idempotency-key` header, expected at most 255 visible ASCII characters" }] })
+    );
+
+    assert!(app.stored_files().is_empty());
+    assert_eq!(saved_keys(&app), 0);
+}
+
+#[test]
+fn failed_publish_releases_key() {
+    let (app, _, user, token) = TestApp::full().with_token();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_failed", user.as_model().id)
+            .version("1.0.0")
+            .expect_build(conn);
+    });
+
+    let body = PublishBuilder::new("foo_failed").version("1.0.0").body();
+    let response = publish_with_key(&token, &body, "failed");
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "crate version `1.0.0` is already uploaded" }] })
+    );
+
+    assert_eq!(saved_keys(&app), 0);
+}
+
+#[test]
+fn dry_runs_do_not_save_key() {
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    let body = PublishBuilder::new("foo_dry_key").body();
+    let mut request = token.request_builder(Method::PUT, "/api/v1/crates/new?dry_run=true");
+    request.header("idempotency-key", "dry");
+    request.with_body(&body);
+    token.run::<GoodCrate>(request).good();
+
+    assert_eq!(saved_keys(&app), 0);
+}
+
+#[test]
+fn cleanup_job_deletes_expired_keys() {
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    let body = PublishBuilder::new("foo_expired").body();
+    publish_with_key(&token, &body, "old").good();
+    let body = PublishBuilder::new("foo_recent").body();
+    publish_with_key(&token, &body, "new").good();
+
+    app.db(|conn| {
+        let two_days_ago = Utc::now().naive_utc() - Duration::days(2);
+        diesel::update(publish_idempotency_keys::table)
+            .filter(publish_idempotency_keys::key.eq("old"))
+            .set(publish_idempotency_keys::created_at.eq(two_days_ago))
+            .execute(conn)
+            .unwrap();
+
+        Job::cleanup_idempotency_keys().enqueue(conn).unwrap();
+    });
+
+    app.run_pending_background_jobs();
+
+    let keys: Vec<String> = app.db(|conn| {
+        publish_idempotency_keys::table
+            .select(publish_idempotency_keys::key)
+            .load(conn)
+            .unwrap()
+    });
+    assert_eq!(keys, vec!["new"]);
+}
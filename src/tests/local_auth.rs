@@ -0,0 +1,235 @@
+use crate::util::{
+    MockAnonymousUser, MockCookieUser, MockRequestExt, RequestHelper, Response, TestApp,
+};
+use crate::OkBool;
+use crates_io::config::LocalAuthConfig;
+use crates_io::schema::emails;
+use diesel::prelude::*;
+use http::{Method, StatusCode};
+use serde_json::Value;
+use url::Url;
+
+const PASSWORD: &str = "correct horse battery staple";
+
+fn local_auth_config() -> LocalAuthConfig {
+    LocalAuthConfig {
+        webauthn_rp_id: "crates.io".to_string(),
+        webauthn_origin: Url::parse("https://crates.io").unwrap(),
+    }
+}
+
+fn init() -> (TestApp, MockAnonymousUser) {
+    TestApp::init()
+        .with_config(|config| config.local_auth = Some(local_auth_config()))
+        .empty()
+}
+
+fn register(anon: &MockAnonymousUser, username: &str) -> Response<Value> {
+    let body = json!({ "username": username, "email": "jdoe@example.com", "password": PASSWORD });
+    anon.post("/api/private/users", body.to_string().as_bytes())
+}
+
+fn login(anon: &MockAnonymousUser, username: &str, password: &str) -> Response<Value> {
+    let body = json!({ "username": username, "password": password });
+    anon.post("/api/private/session/password", body.to_string().as_bytes())
+}
+
+fn login_from(anon: &MockAnonymousUser, ip_address: &str, username: &str) -> Response<Value> {
+    let body = json!({ "username": username, "password": "wrong password!" });
+    let mut request = anon.request_builder(Method::POST, "/api/private/session/password");
+    request.header("x-real-ip", ip_address);
+    request.with_body(body.to_string().as_bytes());
+    anon.run(request)
+}
+
+fn reset_token(app: &TestApp) -> String {
+    let emails = app.as_inner().emails.mails_in_memory().unwrap();
+    let email = emails.last().unwrap();
+    assert_eq!(email.subject, "Reset your crates.io password");
+
+    let (_, token) = email.body.split_once("/reset-password/").unwrap();
+    token.split_whitespace().next().unwrap().to_string()
+}
+
+#[test]
+fn disabled_by_default() {
+    let (_, anon, user) = TestApp::init().with_user();
+
+    assert_eq!(register(&anon, "jdoe").status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        login(&anon, "jdoe", PASSWORD).status(),
+        StatusCode::NOT_FOUND
+    );
+    anon.post::<()>(
+        "/api/private/password_resets",
+        br#"{"email":"foo@example.com"}"#,
+    )
+    .assert_not_found();
+    user.put::<()>("/api/v1/me/password", br#"{"new_password":"foo"}"#)
+        .assert_not_found();
+}
+
+#[test]
+fn register_and_login() {
+    let (app, anon) = init();
+
+    let json = register(&anon, "JDoe").good();
+    assert_eq!(json["user"]["login"], "JDoe@local");
+    assert_eq!(json["user"]["email"], "jdoe@example.com");
+    assert_eq!(json["user"]["email_verified"], false);
+    assert_eq!(json["user"]["url"], Value::Null);
+
+    // The email address has to be confirmed like for other users
+    let emails = app.as_inner().emails.mails_in_memory().unwrap();
+    assert_eq!(emails.len(), 1);
+
+    // Usernames are case-insensitive
+    let response = register(&anon, "jdoe");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "the username `jdoe` is already taken" }] })
+    );
+
+    let json = login(&anon, "jdoe", PASSWORD).good();
+    assert_eq!(json["user"]["login"], "JDoe@local");
+
+    let response = login(&anon, "jdoe", "wrong password!");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = login(&anon, "unknown", PASSWORD);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn login_rate_limit() {
+    let (_, anon) = TestApp::init()
+        .with_config(|config| {
+            config.local_auth = Some(local_auth_config());
+            config.login_rate_limit.burst = 2;
+        })
+        .empty();
+
+    register(&anon, "jdoe").good();
+
+    for _ in 0..2 {
+        let response = login_from(&anon, "192.0.2.1", "jdoe");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    // Per IP address
+    let response = login_from(&anon, "192.0.2.1", "unknown");
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Per account
+    let response = login_from(&anon, "192.0.2.2", "jdoe");
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let response = login(&anon, "jdoe", PASSWORD);
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let response = login_from(&anon, "192.0.2.2", "unknown");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn register_validation() {
+    let (_, anon) = init();
+
+    let response = register(&anon, "-invalid name");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = json!({ "username": "jdoe", "email": "jdoe@example.com", "password": "short" });
+    let response = anon.post::<()>("/api/private/users", body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "passwords must be at least 12 characters long" }] })
+    );
+}
+
+#[test]
+fn add_and_change_password() {
+    let (app, anon) = init();
+    let user = app.db_new_user("foo");
+    let other_session = MockCookieUser::new(&app, user.as_model().clone());
+
+    // Adding a password requires choosing a username for the local account
+    let body = json!({ "new_password": PASSWORD });
+    let response = user.put::<()>("/api/v1/me/password", body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = json!({ "new_password": PASSWORD, "username": "foo" });
+    assert!(
+        user.put::<OkBool>("/api/v1/me/password", body.to_string().as_bytes())
+            .good()
+            .ok
+    );
+
+    // The GitHub login of the user does not change
+    let json = login(&anon, "foo", PASSWORD).good();
+    assert_eq!(json["user"]["login"], "foo");
+
+    // Changing the password requires the current password
+    let new_password = "another correct horse";
+    let body = json!({ "new_password": new_password, "current_password": "wrong password" });
+    let response = user.put::<()>("/api/v1/me/password", body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = json!({ "new_password": new_password, "current_password": PASSWORD });
+    user.put::<OkBool>("/api/v1/me/password", body.to_string().as_bytes())
+        .good();
+
+    login(&anon, "foo", new_password).good();
+    let response = login(&anon, "foo", PASSWORD);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Other sessions are logged out, the current one is kept
+    user.get::<Value>("/api/v1/me").good();
+    other_session.get::<()>("/api/v1/me").assert_forbidden();
+}
+
+#[test]
+fn reset_password() {
+    let (app, anon) = init();
+    let user_id = register(&anon, "jdoe").good()["user"]["id"]
+        .as_i64()
+        .unwrap() as i32;
+
+    // Only verified email addresses can be used for recovery
+    let body = br#"{"email":"jdoe@example.com"}"#;
+    assert!(
+        anon.post::<OkBool>("/api/private/password_resets", body)
+            .good()
+            .ok
+    );
+    let emails = app.as_inner().emails.mails_in_memory().unwrap();
+    assert_eq!(emails.len(), 1);
+
+    app.db(|conn| {
+        diesel::update(emails::table.filter(emails::user_id.eq(user_id)))
+            .set(emails::verified.eq(true))
+            .execute(conn)
+            .unwrap();
+    });
+
+    anon.post::<OkBool>("/api/private/password_resets", body)
+        .good();
+    let token = reset_token(&app);
+
+    let new_password = json!({ "password": "a brand new password" }).to_string();
+    let response = anon.put::<()>(
+        "/api/private/password_resets/invalid",
+        new_password.as_bytes(),
+    );
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let url = format!("/api/private/password_resets/{token}");
+    assert!(anon.put::<OkBool>(&url, new_password.as_bytes()).good().ok);
+
+    login(&anon, "jdoe", "a brand new password").good();
+    let response = login(&anon, "jdoe", PASSWORD);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Reset links can only be used once
+    let response = anon.put::<()>(&url, new_password.as_bytes());
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,99 @@
+use crate::builders::CrateBuilder;
+use crate::{RequestHelper, TestApp};
+use crates_io::models::MaintenanceModeSetting;
+
+use http::{header, StatusCode};
+use std::time::Duration;
+
+#[test]
+fn can_hit_read_only_endpoints_in_maintenance_mode() {
+    let (app, anon, user) = TestApp::init()
+        .with_config(|config| config.maintenance_mode = true)
+        .with_user();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_maintenance", user.as_model().id)
+            .version("1.0.0")
+            .expect_build(conn);
+    });
+
+    let response = anon.get::<()>("/api/v1/crates/foo_maintenance");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = anon.get::<()>("/api/v1/crates/foo_maintenance/1.0.0/download");
+    assert_eq!(response.status(), StatusCode::FOUND);
+
+    let body = json!({ "names": ["foo_maintenance"] });
+    let response = anon.post::<()>("/api/v1/crates_batch", body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let json = anon.get::<()>("/api/v1/site_metadata").into_json();
+    assert_eq!(json["read_only"], json!(true));
+}
+
+#[test]
+fn cannot_hit_write_endpoints_in_maintenance_mode() {
+    let (app, _, user, token) = TestApp::init()
+        .with_config(|config| {
+            config.maintenance_mode = true;
+            config.maintenance_retry_after = Duration::from_secs(120);
+        })
+        .with_token();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_yank_maintenance", user.as_model().id)
+            .version("1.0.0")
+            .expect_build(conn);
+    });
+
+    let response = token.delete::<()>("/api/v1/crates/foo_yank_maintenance/1.0.0/yank");
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.headers()[header::RETRY_AFTER], "120");
+    assert_eq!(
+        response.into_json(),
+        json!({
+            "errors": [{ "detail": "crates.io is currently in read-only mode for maintenance. Please try again later." }],
+            "retry_after": 120,
+        })
+    );
+
+    let json = token.show_version("foo_yank_maintenance", "1.0.0");
+    assert!(!json.version.yanked);
+}
+
+#[test]
+fn maintenance_mode_is_shared_between_instances() {
+    let (app, _, user, token) = TestApp::full().with_token();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_yank_maintenance", user.as_model().id)
+            .version("1.0.0")
+            .expect_build(conn);
+    });
+
+    // Another instance enabled the maintenance mode through the admin API
+    app.db(|conn| MaintenanceModeSetting::save(conn, true, Duration::from_secs(60)).unwrap());
+    app.as_inner().refresh_maintenance_mode().unwrap();
+
+    let response = token.delete::<()>("/api/v1/crates/foo_yank_maintenance/1.0.0/yank");
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.headers()[header::RETRY_AFTER], "60");
+
+    app.db(|conn| MaintenanceModeSetting::save(conn, false, Duration::from_secs(60)).unwrap());
+    app.as_inner().refresh_maintenance_mode().unwrap();
+
+    let response = token.delete::<()>("/api/v1/crates/foo_yank_maintenance/1.0.0/yank");
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[test]
+fn maintenance_mode_from_config_cannot_be_disabled() {
+    let (app, _) = TestApp::init()
+        .with_config(|config| config.maintenance_mode = true)
+        .empty();
+
+    app.db(|conn| MaintenanceModeSetting::save(conn, false, Duration::from_secs(60)).unwrap());
+    app.as_inner().refresh_maintenance_mode().unwrap();
+
+    assert!(app.as_inner().maintenance_mode.is_enabled());
+}
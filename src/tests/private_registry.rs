@@ -0,0 +1,194 @@
+use crate::builders::{CrateBuilder, PublishBuilder};
+use crate::{RequestHelper, TestApp};
+use crates_io::config::{SignedDownloadsConfig, SigningKey};
+use crates_io::models::token::{CrateScope, EndpointScope};
+
+use chrono::Utc;
+use http::{header, StatusCode};
+use std::time::Duration;
+
+#[test]
+fn read_requests_require_authentication() {
+    let (app, anon, user) = TestApp::init()
+        .with_config(|config| config.private_registry = true)
+        .with_user();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_private", user.as_model().id)
+            .version("1.0.0")
+            .expect_build(conn);
+    });
+
+    anon.get::<()>("/api/v1/crates/foo_private")
+        .assert_forbidden();
+    anon.get::<()>("/api/v1/crates?q=foo").assert_forbidden();
+    anon.get::<()>("/index/config.json").assert_forbidden();
+
+    let body = json!({ "names": ["foo_private"] });
+    let response = anon.post::<()>("/api/v1/crates_batch", body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // The login flow and the site metadata are still available
+    let response = anon.get::<()>("/api/v1/site_metadata");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = user.get::<()>("/api/v1/crates/foo_private");
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[test]
+fn read_requests_require_read_scope() {
+    let (app, _, user, token) = TestApp::init()
+        .with_config(|config| config.private_registry = true)
+        .with_scoped_token(None, Some(vec![EndpointScope::Read]));
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_private", user.as_model().id)
+            .version("1.0.0")
+            .expect_build(conn);
+    });
+
+    let response = token.get::<()>("/api/v1/crates/foo_private");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let publish_token = user.db_new_scoped_token(
+        "publish",
+        None,
+        Some(vec![EndpointScope::PublishUpdate]),
+        None,
+    );
+    publish_token
+        .get::<()>("/api/v1/crates/foo_private")
+        .assert_forbidden();
+}
+
+#[test]
+fn read_tokens_respect_crate_scopes() {
+    let (app, _, user, token) = TestApp::init()
+        .with_config(|config| config.private_registry = true)
+        .with_scoped_token(
+            Some(vec![CrateScope::try_from("foo_*").unwrap()]),
+            Some(vec![EndpointScope::Read]),
+        );
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_private", user.as_model().id).expect_build(conn);
+        CrateBuilder::new("bar_private", user.as_model().id).expect_build(conn);
+    });
+
+    let response = token.get::<()>("/api/v1/crates/foo_private");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    token
+        .get::<()>("/api/v1/crates/bar_private")
+        .assert_forbidden();
+
+    // Endpoints that deal with multiple crates could expose `bar_private`
+    token
+        .get::<()>("/api/v1/crates?q=private")
+        .assert_forbidden();
+
+    let body = json!({ "names": ["foo_private", "bar_private"] });
+    token
+        .post::<()>("/api/v1/crates_batch", body.to_string().as_bytes())
+        .assert_forbidden();
+
+    let response = token.get::<()>("/index/config.json");
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[test]
+fn downloads_and_index_are_served_directly() {
+    let (app, anon, _, token) = TestApp::full()
+        .with_config(|config| config.private_registry = true)
+        .with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo_private", "1.0.0");
+    token.publish_crate(crate_to_publish).good();
+    app.run_pending_background_jobs();
+
+    let response = token.get::<()>("/api/v1/crates/foo_private/1.0.0/download");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(!response.into_bytes().is_empty());
+
+    anon.get::<()>("/api/v1/crates/foo_private/1.0.0/download")
+        .assert_forbidden();
+
+    let json = token.get::<()>("/index/config.json").into_json();
+    assert_eq!(json["dl"], "https://crates.io/api/v1/crates");
+    assert_eq!(json["auth-required"], true);
+
+    let response = token.get::<()>("/index/fo/o_/foo_private");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.into_text().contains(r#""vers":"1.0.0""#));
+
+    token.get::<()>("/index/3/f/foo").assert_not_found();
+    token.get::<()>("/index/foo_private").assert_not_found();
+    anon.get::<()>("/index/fo/o_/foo_private")
+        .assert_forbidden();
+}
+
+fn signed_downloads_config(keys: &[(&str, &str)]) -> SignedDownloadsConfig {
+    SignedDownloadsConfig {
+        keys: keys
+            .iter()
+            .map(|(id, secret)| SigningKey::new(id, secret.as_bytes()))
+            .collect(),
+        ttl: Duration::from_secs(60),
+    }
+}
+
+#[test]
+fn downloads_redirect_to_signed_urls() {
+    let (app, anon, _, token) = TestApp::full()
+        .with_config(|config| {
+            config.private_registry = true;
+            config.signed_downloads = Some(signed_downloads_config(&[
+                ("new", "new-secret"),
+                ("old", "old-secret"),
+            ]));
+        })
+        .with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo_private", "1.0.0");
+    token.publish_crate(crate_to_publish).good();
+    app.run_pending_background_jobs();
+
+    anon.get::<()>("/api/v1/crates/foo_private/1.0.0/download")
+        .assert_forbidden();
+
+    let response = token.get::<()>("/api/v1/crates/foo_private/1.0.0/download");
+    assert_eq!(response.status(), StatusCode::FOUND);
+    let location = response.headers()[header::LOCATION].to_str().unwrap();
+    let (path, query) = location.split_once('?').unwrap();
+    assert_eq!(
+        path,
+        "/api/private/signed_downloads/crates/foo_private/foo_private-1.0.0.crate"
+    );
+    assert!(query.contains("&key=new&"));
+
+    // Signed URLs don't need any further authentication
+    let response = anon.get_with_query::<()>(path, query);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(!response.into_bytes().is_empty());
+
+    // but their path and expiration date can't be changed
+    let tampered_query = query.replacen("expires=", "expires=1", 1);
+    anon.get_with_query::<()>(path, &tampered_query)
+        .assert_forbidden();
+    let other_path = "/api/private/signed_downloads/crates/foo_private/foo_private-2.0.0.crate";
+    anon.get_with_query::<()>(other_path, query)
+        .assert_forbidden();
+    anon.get::<()>(path).assert_forbidden();
+
+    // URLs signed with a rotated key stay valid until they expire
+    let crate_path = "crates/foo_private/foo_private-1.0.0.crate";
+    let old_config = signed_downloads_config(&[("old", "old-secret")]);
+    let old_query = old_config.signed_query(crate_path, old_config.expires_at());
+    let response = anon.get_with_query::<()>(path, &old_query);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let expired_query = old_config.signed_query(crate_path, Utc::now().timestamp() - 1);
+    anon.get_with_query::<()>(path, &expired_query)
+        .assert_forbidden();
+}
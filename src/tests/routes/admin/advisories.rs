@@ -0,0 +1,74 @@
+use super::{admin_request, ADMIN_TOKEN};
+use crate::builders::CrateBuilder;
+use crate::util::{RequestHelper, TestApp};
+use http::{Method, StatusCode};
+
+const URL: &str = "/api/private/admin/advisories";
+
+#[test]
+fn advisories() {
+    let (app, anon, user) = TestApp::init()
+        .with_config(|config| config.admin_authorization_token = Some(ADMIN_TOKEN.into()))
+        .with_user();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_advisory", user.as_model().id)
+            .version("1.0.0")
+            .expect_build(conn);
+    });
+
+    let url = format!("{URL}/foo_advisory/RUSTSEC-2023-0001");
+    let body = br#"{ "affected": "<1.1.0" }"#;
+
+    let response = admin_request(&anon, Method::PUT, &url, None, body);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = admin_request(&anon, Method::PUT, &url, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    assert_eq!(json["advisory"]["crate"], "foo_advisory");
+    assert_eq!(json["advisory"]["id"], "RUSTSEC-2023-0001");
+    assert_eq!(json["advisory"]["affected"], "<1.1.0");
+
+    let json = admin_request(&anon, Method::GET, URL, Some(ADMIN_TOKEN), b"").into_json();
+    assert_eq!(json["advisories"].as_array().unwrap().len(), 1);
+
+    let body = json!({ "dependencies": [{ "name": "foo_advisory", "req": "*" }] });
+    let json = anon
+        .post::<()>("/api/v1/resolve", body.to_string().as_bytes())
+        .into_json();
+    assert_eq!(
+        json["dependencies"][0]["advisories"],
+        json!(["RUSTSEC-2023-0001"])
+    );
+
+    let response = admin_request(&anon, Method::DELETE, &url, Some(ADMIN_TOKEN), b"");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let json = admin_request(&anon, Method::GET, URL, Some(ADMIN_TOKEN), b"").into_json();
+    assert_eq!(json["advisories"], json!([]));
+
+    let response = admin_request(&anon, Method::DELETE, &url, Some(ADMIN_TOKEN), b"");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn invalid_advisories_are_rejected() {
+    let (app, anon, user) = TestApp::init()
+        .with_config(|config| config.admin_authorization_token = Some(ADMIN_TOKEN.into()))
+        .with_user();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_invalid", user.as_model().id).expect_build(conn);
+    });
+
+    let url = format!("{URL}/foo_invalid/RUSTSEC-2023-0001");
+    let body = br#"{ "affected": "not a requirement" }"#;
+    let response = admin_request(&anon, Method::PUT, &url, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let url = format!("{URL}/foo_unknown/RUSTSEC-2023-0001");
+    let body = br#"{ "affected": "*" }"#;
+    let response = admin_request(&anon, Method::PUT, &url, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
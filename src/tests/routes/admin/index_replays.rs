@@ -0,0 +1,48 @@
+use super::{admin_request, ADMIN_TOKEN};
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::schema::index_changes;
+use diesel::prelude::*;
+use http::{Method, StatusCode};
+
+#[test]
+fn replay_index_sync() {
+    let (app, anon, _, token) = TestApp::full()
+        .with_config(|config| config.admin_authorization_token = Some(ADMIN_TOKEN.into()))
+        .with_token();
+
+    token
+        .publish_crate(PublishBuilder::new("foo_replay"))
+        .good();
+
+    let url = "/api/private/admin/index_replays/FOO_REPLAY";
+    let response = admin_request(&anon, Method::PUT, url, None, b"");
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = admin_request(&anon, Method::PUT, url, Some(ADMIN_TOKEN), b"");
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    assert_eq!(json["replay"]["crate"], "foo_replay");
+    assert_eq!(json["replay"]["sequence"], 2);
+    let sync_states = json["replay"]["sync_states"].as_array().unwrap();
+    assert_eq!(sync_states.len(), 2);
+    assert_eq!(sync_states[0]["target"], "git");
+    assert_eq!(sync_states[0]["synced_sequence"], 1);
+    assert_eq!(sync_states[1]["target"], "sparse");
+    assert_eq!(sync_states[1]["synced_sequence"], 1);
+
+    app.run_pending_background_jobs();
+
+    let changes: i64 = app.db(|conn| {
+        index_changes::table
+            .filter(index_changes::crate_name.eq("foo_replay"))
+            .count()
+            .get_result(conn)
+            .unwrap()
+    });
+    assert_eq!(changes, 2);
+
+    let url = "/api/private/admin/index_replays/unknown";
+    let response = admin_request(&anon, Method::PUT, url, Some(ADMIN_TOKEN), b"");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
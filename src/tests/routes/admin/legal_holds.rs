@@ -0,0 +1,257 @@
+use super::{admin_request, ADMIN_TOKEN};
+use crate::builders::{CrateBuilder, PublishBuilder, VersionBuilder};
+use crate::util::{RequestHelper, TestApp};
+use http::{header, Method, StatusCode};
+
+const URL: &str = "/api/private/admin/legal_holds";
+
+fn app_with_crate() -> (TestApp, crate::util::MockAnonymousUser) {
+    let (app, anon, user) = TestApp::full()
+        .with_config(|config| config.admin_authorization_token = Some(ADMIN_TOKEN.into()))
+        .with_user();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_held", user.as_model().id)
+            .version(VersionBuilder::new("1.0.0"))
+            .version(VersionBuilder::new("1.1.0"))
+            .expect_build(conn);
+    });
+
+    (app, anon)
+}
+
+#[test]
+fn crate_hold_blocks_downloads_and_search() {
+    let (app, anon) = app_with_crate();
+
+    let body = br#"{
+        "crate": "foo_held",
+        "reason": "DMCA takedown notice",
+        "notice_url": "https://lumendatabase.org/notices/1234",
+        "note": "see ticket 42"
+    }"#;
+    let response = admin_request(&anon, Method::POST, URL, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::OK);
+    app.run_pending_background_jobs();
+    let json = response.into_json();
+    let hold = &json["legal_hold"];
+    assert_eq!(hold["crate"], "foo_held");
+    assert_eq!(hold["version"], json!(null));
+    assert_eq!(hold["lifted_at"], json!(null));
+    assert_eq!(hold["actions"][0]["action"], "place");
+    assert_eq!(hold["actions"][0]["note"], "see ticket 42");
+    let id = hold["id"].as_i64().unwrap();
+
+    let response = anon.get::<()>("/api/v1/crates/foo_held/1.0.0/download");
+    assert_eq!(response.status(), StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS);
+    assert_eq!(
+        response.headers()[header::LINK],
+        "<https://lumendatabase.org/notices/1234>; rel=\"blocked-by\""
+    );
+    let json = response.into_json();
+    assert_eq!(json["legal_hold"]["scope"], "crate");
+    assert_eq!(json["legal_hold"]["reason"], "DMCA takedown notice");
+    assert_eq!(
+        json["errors"][0]["detail"],
+        "foo_held@1.0.0 is unavailable for legal reasons"
+    );
+
+    // The crate is hidden from search, but its data is preserved
+    assert_eq!(anon.search("q=foo_held").crates.len(), 0);
+    assert_eq!(anon.search("").crates.len(), 0);
+    assert_eq!(anon.show_crate("foo_held").krate.name, "foo_held");
+
+    let url = format!("{URL}/{id}/lift");
+    let body = br#"{ "note": "counter notice accepted" }"#;
+    let response = admin_request(&anon, Method::PUT, &url, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::OK);
+    app.run_pending_background_jobs();
+    let json = response.into_json();
+    assert!(json["legal_hold"]["lifted_at"].is_string());
+    assert_eq!(json["legal_hold"]["actions"][1]["action"], "lift");
+    assert_eq!(
+        json["legal_hold"]["actions"][1]["note"],
+        "counter notice accepted"
+    );
+
+    anon.get::<()>("/api/v1/crates/foo_held/1.0.0/download")
+        .assert_redirect_ends_with("/crates/foo_held/foo_held-1.0.0.crate");
+    assert_eq!(anon.search("q=foo_held").crates.len(), 1);
+
+    // Holds can only be lifted once
+    let response = admin_request(&anon, Method::PUT, &url, Some(ADMIN_TOKEN), b"");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn version_hold_only_blocks_the_version() {
+    let (app, anon) = app_with_crate();
+
+    let body = br#"{ "crate": "foo_held", "version": "1.0.0", "reason": "Leaked secrets" }"#;
+    let response = admin_request(&anon, Method::POST, URL, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::OK);
+    app.run_pending_background_jobs();
+    assert_eq!(response.into_json()["legal_hold"]["version"], "1.0.0");
+
+    let response = anon.get::<()>("/api/v1/crates/foo_held/1.0.0/download");
+    assert_eq!(response.status(), StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS);
+    assert!(response.headers().get(header::LINK).is_none());
+    assert_eq!(response.into_json()["legal_hold"]["scope"], "version");
+
+    anon.get::<()>("/api/v1/crates/foo_held/1.1.0/download")
+        .assert_redirect_ends_with("/crates/foo_held/foo_held-1.1.0.crate");
+    assert_eq!(anon.search("q=foo_held").crates.len(), 1);
+
+    let json = admin_request(&anon, Method::GET, URL, Some(ADMIN_TOKEN), b"").into_json();
+    assert_eq!(json["legal_holds"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn hold_overrides_cached_downloads() {
+    let (app, anon) = app_with_crate();
+
+    // Populates the version ID cache
+    anon.get::<()>("/api/v1/crates/foo_held/1.0.0/download")
+        .assert_redirect_ends_with("/crates/foo_held/foo_held-1.0.0.crate");
+
+    let body = br#"{ "crate": "foo_held", "reason": "Court order" }"#;
+    let response = admin_request(&anon, Method::POST, URL, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::OK);
+    app.run_pending_background_jobs();
+
+    let response = anon.get::<()>("/api/v1/crates/foo_held/1.0.0/download");
+    assert_eq!(response.status(), StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS);
+}
+
+#[test]
+fn takedown_request_is_resolved_by_hold() {
+    let (app, anon) = app_with_crate();
+
+    let body = br#"{
+        "crate": "foo_held",
+        "version": "1.1.0",
+        "name": "Jane Doe",
+        "email": "legal@example.com",
+        "description": "This version contains our copyrighted code."
+    }"#;
+    let json = anon
+        .post::<()>("/api/v1/takedown_requests", body)
+        .into_json();
+    let request_id = json["takedown_request"]["id"].as_i64().unwrap();
+
+    let url = "/api/private/admin/takedown_requests";
+    let json = admin_request(&anon, Method::GET, url, Some(ADMIN_TOKEN), b"").into_json();
+    assert_eq!(json["takedown_requests"][0]["id"], request_id);
+    assert_eq!(json["takedown_requests"][0]["complainant_name"], "Jane Doe");
+
+    let body = format!(
+        r#"{{ "crate": "foo_held", "version": "1.1.0", "reason": "DMCA", "takedown_request_id": {request_id} }}"#
+    );
+    let response = admin_request(&anon, Method::POST, URL, Some(ADMIN_TOKEN), body.as_bytes());
+    assert_eq!(response.status(), StatusCode::OK);
+    app.run_pending_background_jobs();
+    assert_eq!(
+        response.into_json()["legal_hold"]["takedown_request_id"],
+        request_id
+    );
+
+    let json = admin_request(&anon, Method::GET, url, Some(ADMIN_TOKEN), b"").into_json();
+    assert_eq!(json["takedown_requests"], json!([]));
+}
+
+#[test]
+fn crate_files_and_index_entries_are_withheld() {
+    let (app, anon, _, token) = TestApp::full()
+        .with_config(|config| config.admin_authorization_token = Some(ADMIN_TOKEN.into()))
+        .with_token();
+
+    token
+        .publish_crate(PublishBuilder::new("foo_files").version("1.0.0"))
+        .good();
+    token
+        .publish_crate(PublishBuilder::new("foo_files").version("1.1.0"))
+        .good();
+
+    let body = br#"{ "crate": "foo_files", "version": "1.0.0", "reason": "Leaked secrets" }"#;
+    let response = admin_request(&anon, Method::POST, URL, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::OK);
+    let id = response.into_json()["legal_hold"]["id"].as_i64().unwrap();
+    app.run_pending_background_jobs();
+
+    let expected_files = vec![
+        "crates/foo_files/foo_files-1.1.0.crate",
+        "index/fo/o_/foo_files",
+        "legal-holds/crates/foo_files/foo_files-1.0.0.crate",
+    ];
+    assert_eq!(app.stored_files(), expected_files);
+
+    let crates = app.crates_from_index_head("foo_files");
+    assert_eq!(crates.len(), 1);
+    assert_eq!(crates[0].vers, "1.1.0");
+
+    let url = format!("{URL}/{id}/lift");
+    let response = admin_request(&anon, Method::PUT, &url, Some(ADMIN_TOKEN), b"");
+    assert_eq!(response.status(), StatusCode::OK);
+    app.run_pending_background_jobs();
+
+    let expected_files = vec![
+        "crates/foo_files/foo_files-1.0.0.crate",
+        "crates/foo_files/foo_files-1.1.0.crate",
+        "index/fo/o_/foo_files",
+    ];
+    assert_eq!(app.stored_files(), expected_files);
+    assert_eq!(app.crates_from_index_head("foo_files").len(), 2);
+}
+
+#[test]
+fn invalid_holds() {
+    let (_app, anon) = app_with_crate();
+
+    let body = br#"{ "crate": "unknown", "reason": "DMCA" }"#;
+    let response = admin_request(&anon, Method::POST, URL, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = br#"{ "crate": "foo_held", "version": "9.9.9", "reason": "DMCA" }"#;
+    let response = admin_request(&anon, Method::POST, URL, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = br#"{ "crate": "foo_held", "reason": " " }"#;
+    let response = admin_request(&anon, Method::POST, URL, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = br#"{ "crate": "foo_held", "reason": "DMCA", "notice_url": "javascript:alert(1)" }"#;
+    let response = admin_request(&anon, Method::POST, URL, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = br#"{ "crate": "foo_held", "reason": "DMCA", "takedown_request_id": 1234 }"#;
+    let response = admin_request(&anon, Method::POST, URL, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = admin_request(
+        &anon,
+        Method::PUT,
+        &format!("{URL}/1234/lift"),
+        Some(ADMIN_TOKEN),
+        b"",
+    );
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn wrong_auth() {
+    let (_app, anon) = app_with_crate();
+
+    let body = br#"{ "crate": "foo_held", "reason": "DMCA" }"#;
+    let response = admin_request(&anon, Method::POST, URL, Some("foobar"), body);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = admin_request(&anon, Method::POST, URL, None, body);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let url = "/api/private/admin/takedown_requests";
+    let response = admin_request(&anon, Method::GET, url, None, b"");
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    anon.get::<()>("/api/v1/crates/foo_held/1.0.0/download")
+        .assert_redirect_ends_with("/crates/foo_held/foo_held-1.0.0.crate");
+}
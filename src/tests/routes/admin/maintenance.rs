@@ -0,0 +1,84 @@
+use super::{admin_request, ADMIN_TOKEN};
+use crate::util::TestApp;
+use crates_io::models::MaintenanceModeSetting;
+use http::{Method, StatusCode};
+
+const URL: &str = "/api/private/admin/maintenance";
+
+#[test]
+fn show_and_update_maintenance_mode() {
+    let (app, anon) = TestApp::init()
+        .with_config(|config| config.admin_authorization_token = Some(ADMIN_TOKEN.into()))
+        .empty();
+
+    let response = admin_request(&anon, Method::GET, URL, Some(ADMIN_TOKEN), b"");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_json(),
+        json!({ "maintenance": { "enabled": false, "retry_after": 300 } })
+    );
+
+    let body = br#"{ "enabled": true, "retry_after": 600 }"#;
+    let response = admin_request(&anon, Method::PUT, URL, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_json(),
+        json!({ "maintenance": { "enabled": true, "retry_after": 600 } })
+    );
+    assert!(app.as_inner().maintenance_mode.is_enabled());
+
+    // The maintenance mode is persisted for the other instances and the background worker
+    let setting = app.db(|conn| MaintenanceModeSetting::load(conn).unwrap().unwrap());
+    assert!(setting.enabled);
+    assert_eq!(setting.retry_after, 600);
+
+    // The admin endpoint still works while the maintenance mode is enabled
+    let body = br#"{ "enabled": false }"#;
+    let response = admin_request(&anon, Method::PUT, URL, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_json(),
+        json!({ "maintenance": { "enabled": false, "retry_after": 300 } })
+    );
+    assert!(!app.as_inner().maintenance_mode.is_enabled());
+}
+
+#[test]
+fn invalid_update() {
+    let (_, anon) = TestApp::init()
+        .with_config(|config| config.admin_authorization_token = Some(ADMIN_TOKEN.into()))
+        .empty();
+
+    let body = br#"{ "retry_after": 600 }"#;
+    let response = admin_request(&anon, Method::PUT, URL, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn wrong_auth() {
+    let (app, anon) = TestApp::init()
+        .with_config(|config| config.admin_authorization_token = Some(ADMIN_TOKEN.into()))
+        .empty();
+
+    let body = br#"{ "enabled": true }"#;
+
+    let response = admin_request(&anon, Method::PUT, URL, Some("foobar"), body);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = admin_request(&anon, Method::PUT, URL, None, body);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = admin_request(&anon, Method::GET, URL, None, b"");
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    assert!(!app.as_inner().maintenance_mode.is_enabled());
+}
+
+#[test]
+fn disabled_without_admin_token() {
+    let (_, anon) = TestApp::init().empty();
+
+    let body = br#"{ "enabled": true }"#;
+    let response = admin_request(&anon, Method::PUT, URL, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
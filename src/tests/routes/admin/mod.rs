@@ -0,0 +1,29 @@
+use crate::util::{MockAnonymousUser, MockRequestExt, Response};
+use crate::RequestHelper;
+use http::{header, Method};
+
+pub mod advisories;
+pub mod index_replays;
+pub mod legal_holds;
+pub mod maintenance;
+pub mod quarantines;
+pub mod upload_limits;
+
+pub const ADMIN_TOKEN: &str = "admin-secret";
+
+/// Issues a request to the admin API, optionally authenticated with the given token
+#[track_caller]
+pub fn admin_request(
+    anon: &MockAnonymousUser,
+    method: Method,
+    path: &str,
+    token: Option<&str>,
+    body: &[u8],
+) -> Response<()> {
+    let mut req = anon.request_builder(method, path);
+    if let Some(token) = token {
+        req.header(header::AUTHORIZATION, &format!("Bearer {token}"));
+    }
+    req.with_body(body);
+    anon.run(req)
+}
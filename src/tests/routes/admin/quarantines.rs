@@ -0,0 +1,202 @@
+use super::{admin_request, ADMIN_TOKEN};
+use crate::builders::PublishBuilder;
+use crate::util::{MockAnonymousUser, MockTokenUser, RequestHelper, TestApp};
+use crates_io::schema::{publish_details, users, versions};
+use diesel::prelude::*;
+use http::{Method, StatusCode};
+use serde_json::Value;
+use std::time::Duration;
+
+const URL: &str = "/api/private/admin/quarantines";
+
+fn app_with_quarantine() -> (TestApp, MockAnonymousUser, MockTokenUser) {
+    let (app, anon, _, token) = TestApp::full()
+        .with_config(|config| {
+            config.admin_authorization_token = Some(ADMIN_TOKEN.into());
+            config.quarantine_account_age = Some(Duration::from_secs(7 * 24 * 60 * 60));
+        })
+        .with_token();
+
+    (app, anon, token)
+}
+
+#[test]
+fn crates_of_new_accounts_are_quarantined_until_approved() {
+    let (_app, anon, token) = app_with_quarantine();
+
+    token.publish_crate(PublishBuilder::new("foo_new")).good();
+
+    // Quarantined crates are flagged, but can still be downloaded
+    let json = anon.get::<()>("/api/v1/crates/foo_new").into_json();
+    assert_eq!(json["crate"]["quarantine_status"], "pending");
+    let json = anon.get::<()>("/api/v1/crates?q=foo_new").into_json();
+    assert_eq!(json["crates"][0]["quarantine_status"], "pending");
+    anon.get::<()>("/api/v1/crates/foo_new/1.0.0/download")
+        .assert_redirect_ends_with("/crates/foo_new/foo_new-1.0.0.crate");
+
+    let response = admin_request(&anon, Method::GET, URL, Some(ADMIN_TOKEN), b"");
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    assert_eq!(json["quarantines"][0]["crate"], "foo_new");
+    assert_eq!(json["quarantines"][0]["user"], "foo");
+    assert_eq!(json["quarantines"][0]["status"], "pending");
+
+    let url = format!("{URL}/foo_new/approve");
+    let body = br#"{ "note": "looks fine" }"#;
+    let response = admin_request(&anon, Method::PUT, &url, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    assert_eq!(json["quarantine"]["status"], "approved");
+    assert_eq!(json["quarantine"]["note"], "looks fine");
+    assert!(json["quarantine"]["reviewed_at"].is_string());
+
+    let json = anon.get::<()>("/api/v1/crates/foo_new").into_json();
+    assert_eq!(json["crate"].get("quarantine_status"), None);
+
+    let json = admin_request(&anon, Method::GET, URL, Some(ADMIN_TOKEN), b"").into_json();
+    assert_eq!(json["quarantines"], json!([]));
+
+    // Approved crates are not quarantined again
+    token
+        .publish_crate(PublishBuilder::new("foo_new").version("1.0.1"))
+        .good();
+    let json = anon.get::<()>("/api/v1/crates/foo_new").into_json();
+    assert_eq!(json["crate"].get("quarantine_status"), None);
+
+    // Reviews can not be changed
+    let response = admin_request(&anon, Method::PUT, &url, Some(ADMIN_TOKEN), b"");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json()["errors"][0]["detail"],
+        "crate `foo_new` was already approved"
+    );
+}
+
+#[test]
+fn rejected_crates_are_yanked() {
+    let (app, anon, token) = app_with_quarantine();
+
+    token.publish_crate(PublishBuilder::new("foo_bad")).good();
+
+    let url = format!("{URL}/foo_bad/reject");
+    let response = admin_request(&anon, Method::PUT, &url, Some(ADMIN_TOKEN), b"");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.into_json()["quarantine"]["status"], "rejected");
+    app.run_pending_background_jobs();
+
+    let yanked: Vec<bool> =
+        app.db(|conn| versions::table.select(versions::yanked).load(conn).unwrap());
+    assert_eq!(yanked, vec![true]);
+
+    let json = anon.get::<()>("/api/v1/crates/foo_bad").into_json();
+    assert_eq!(json["crate"]["quarantine_status"], "rejected");
+
+    let response = token.publish_crate(PublishBuilder::new("foo_bad").version("1.0.1"));
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_json()["errors"][0]["detail"],
+        "crate `foo_bad` was rejected during review and can not be published anymore"
+    );
+}
+
+#[test]
+fn crates_of_established_accounts_are_not_quarantined() {
+    let (app, anon, token) = app_with_quarantine();
+
+    // Accounts created before the creation time was recorded count as established
+    app.db(|conn| {
+        diesel::update(users::table)
+            .set(users::created_at.eq(None::<chrono::NaiveDateTime>))
+            .execute(conn)
+            .unwrap();
+    });
+
+    token.publish_crate(PublishBuilder::new("foo_old")).good();
+
+    let json = anon.get::<()>("/api/v1/crates/foo_old").into_json();
+    assert_eq!(json["crate"].get("quarantine_status"), None);
+
+    let url = format!("{URL}/foo_old/approve");
+    let response = admin_request(&anon, Method::PUT, &url, Some(ADMIN_TOKEN), b"");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json()["errors"][0]["detail"],
+        "crate `foo_old` is not quarantined"
+    );
+}
+
+fn publish_lib(token: &MockTokenUser, name: &str, version: &str) -> Value {
+    let path = format!("{name}-{version}/src/lib.rs");
+    let builder = PublishBuilder::new(name)
+        .version(version)
+        .files(&[(&path, b"pub fn copied() {}")]);
+
+    token.publish_crate(builder).into_json()
+}
+
+#[test]
+fn crates_with_duplicate_content_are_quarantined() {
+    let (app, anon, _, token) = TestApp::full()
+        .with_config(|config| {
+            config.admin_authorization_token = Some(ADMIN_TOKEN.into());
+        })
+        .with_token();
+
+    let json = publish_lib(&token, "foo_original", "1.0.0");
+    assert_eq!(json["warnings"]["other"], json!([]));
+
+    // New versions of the same crate are not duplicates
+    let json = publish_lib(&token, "foo_original", "1.0.1");
+    assert_eq!(json["warnings"]["other"], json!([]));
+
+    let json = publish_lib(&token, "foo_copy", "0.1.0");
+    assert_eq!(
+        json["warnings"]["other"],
+        json!([
+            "the content of this crate is identical to `foo_original` 1.0.0, \
+             so it has been flagged for review"
+        ])
+    );
+
+    let json = anon.get::<()>("/api/v1/crates/foo_copy").into_json();
+    assert_eq!(json["crate"]["quarantine_status"], "pending");
+    let json = anon.get::<()>("/api/v1/crates/foo_original").into_json();
+    assert_eq!(json["crate"].get("quarantine_status"), None);
+
+    let json = admin_request(&anon, Method::GET, URL, Some(ADMIN_TOKEN), b"").into_json();
+    assert_eq!(json["quarantines"][0]["crate"], "foo_copy");
+    assert_eq!(
+        json["quarantines"][0]["duplicate_of"],
+        json!({ "crate": "foo_original", "num": "1.0.0" })
+    );
+
+    let content_hashes: Vec<Option<String>> = app.db(|conn| {
+        publish_details::table
+            .select(publish_details::content_hash)
+            .load(conn)
+            .unwrap()
+    });
+    assert_eq!(content_hashes.len(), 3);
+    assert!(content_hashes.iter().all(|hash| *hash == content_hashes[0]));
+}
+
+#[test]
+fn crates_with_duplicate_content_can_be_rejected() {
+    let (_app, anon, _, token) = TestApp::full()
+        .with_config(|config| {
+            config.reject_duplicate_content = true;
+        })
+        .with_token();
+
+    publish_lib(&token, "foo_original", "1.0.0");
+
+    let json = publish_lib(&token, "foo_copy", "0.1.0");
+    assert_eq!(
+        json["errors"][0]["detail"],
+        "the content of this crate is identical to `foo_original` 1.0.0, \
+         republishing existing crates under a new name is not allowed"
+    );
+
+    anon.get::<()>("/api/v1/crates/foo_copy/0.1.0")
+        .assert_not_found();
+}
@@ -0,0 +1,78 @@
+use super::{admin_request, ADMIN_TOKEN};
+use crate::builders::{uncompressed_tarball, CrateBuilder, PublishBuilder};
+use crate::util::{RequestHelper, TestApp};
+use http::{Method, StatusCode};
+
+const URL: &str = "/api/private/admin/upload_limits";
+
+#[test]
+fn upload_limit_overrides() {
+    let (app, anon, user, token) = TestApp::full()
+        .with_config(|config| config.admin_authorization_token = Some(ADMIN_TOKEN.into()))
+        .with_token();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_override", user.as_model().id).expect_build(conn);
+    });
+
+    let url = format!("{URL}/foo_override");
+    let body = br#"{ "max_upload_size": 10000, "note": "ships test fixtures" }"#;
+
+    let response = admin_request(&anon, Method::PUT, &url, None, body);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = admin_request(&anon, Method::PUT, &url, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    assert_eq!(json["upload_limit"]["crate"], "foo_override");
+    assert_eq!(json["upload_limit"]["max_upload_size"], 10000);
+    assert_eq!(json["upload_limit"]["max_unpack_size"], json!(null));
+    assert_eq!(json["upload_limit"]["note"], "ships test fixtures");
+
+    let json = admin_request(&anon, Method::GET, URL, Some(ADMIN_TOKEN), b"").into_json();
+    assert_eq!(json["upload_limits"].as_array().unwrap().len(), 1);
+    assert_eq!(json["upload_limits"][0]["crate"], "foo_override");
+
+    // The test config has a `max_upload_size` of 3000
+    let crate_to_publish = PublishBuilder::new("foo_override")
+        .version("1.0.0")
+        .tarball(uncompressed_tarball("foo_override", "1.0.0", 3500));
+    token.publish_crate(crate_to_publish).good();
+
+    let response = admin_request(&anon, Method::DELETE, &url, Some(ADMIN_TOKEN), b"");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let json = admin_request(&anon, Method::GET, URL, Some(ADMIN_TOKEN), b"").into_json();
+    assert_eq!(json["upload_limits"], json!([]));
+
+    let crate_to_publish = PublishBuilder::new("foo_override")
+        .version("1.0.1")
+        .tarball(uncompressed_tarball("foo_override", "1.0.1", 3500));
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(response.into_json()["errors"][0]["limit_source"], "default");
+
+    let response = admin_request(&anon, Method::DELETE, &url, Some(ADMIN_TOKEN), b"");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn invalid_upload_limits_are_rejected() {
+    let (app, anon, user) = TestApp::init()
+        .with_config(|config| config.admin_authorization_token = Some(ADMIN_TOKEN.into()))
+        .with_user();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_invalid", user.as_model().id).expect_build(conn);
+    });
+
+    let url = format!("{URL}/foo_invalid");
+    let body = br#"{ "max_upload_size": 0 }"#;
+    let response = admin_request(&anon, Method::PUT, &url, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let url = format!("{URL}/foo_unknown");
+    let body = br#"{ "max_upload_size": 10000 }"#;
+    let response = admin_request(&anon, Method::PUT, &url, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -1,2 +1,4 @@
 pub mod get;
 pub mod list;
+mod suggest;
+mod tree;
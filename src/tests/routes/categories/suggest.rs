@@ -0,0 +1,52 @@
+use crate::new_category;
+use crate::util::{RequestHelper, TestApp};
+use http::StatusCode;
+
+#[test]
+fn suggest() {
+    let (app, anon) = TestApp::init().empty();
+
+    app.db(|conn| {
+        let categories = [
+            ("Command line utilities", "command-line-utilities"),
+            ("Command-line interface", "command-line-interface"),
+            ("Development tools", "development-tools"),
+            ("Development tools::Testing", "development-tools::testing"),
+        ];
+        for (category, slug) in categories {
+            assert_ok!(new_category(category, slug, "").create_or_update(conn));
+        }
+    });
+
+    let slugs = |q: &str| {
+        let json = anon
+            .get::<()>(&format!("/api/v1/categories/suggest?q={q}"))
+            .into_json();
+
+        json["categories"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["slug"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        slugs("comm"),
+        vec!["command-line-interface", "command-line-utilities"]
+    );
+    assert_eq!(
+        slugs("Development"),
+        vec!["development-tools", "development-tools::testing"]
+    );
+    assert_eq!(slugs("testting"), vec!["development-tools::testing"]);
+    assert_eq!(slugs("xyz"), Vec::<String>::new());
+}
+
+#[test]
+fn suggest_without_query() {
+    let (_, anon) = TestApp::init().empty();
+
+    let response = anon.get::<()>("/api/v1/categories/suggest");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,71 @@
+use crate::builders::CrateBuilder;
+use crate::new_category;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::models::Category;
+
+#[test]
+fn tree() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        let categories = [
+            ("Cat 1", "cat1"),
+            ("Cat 1::Sub 1", "cat1::sub1"),
+            ("Cat 1::Sub 1::Sub Sub", "cat1::sub1::subsub"),
+            ("Cat 1::Sub 2", "cat1::sub2"),
+            ("Cat 2", "cat2"),
+        ];
+        for (category, slug) in categories {
+            assert_ok!(new_category(category, slug, "").create_or_update(conn));
+        }
+
+        let krate = CrateBuilder::new("foo_tree", user.id).expect_build(conn);
+        Category::update_crate(conn, &krate, &["cat1", "cat1::sub1::subsub"]).unwrap();
+        let krate = CrateBuilder::new("bar_tree", user.id).expect_build(conn);
+        Category::update_crate(conn, &krate, &["cat1::sub2"]).unwrap();
+    });
+
+    let json = anon.get::<()>("/api/v1/categories/tree").into_json();
+    let categories = json["categories"].as_array().unwrap();
+    assert_eq!(categories.len(), 2);
+
+    let cat1 = &categories[0];
+    assert_eq!(cat1["slug"], "cat1");
+    assert_eq!(cat1["category"], "Cat 1");
+    assert_eq!(cat1["crates_cnt"], 3);
+    assert_eq!(cat1["direct_crates_cnt"], 1);
+
+    let children = cat1["children"].as_array().unwrap();
+    assert_eq!(children.len(), 2);
+    assert_eq!(children[0]["slug"], "cat1::sub1");
+    assert_eq!(children[0]["category"], "Sub 1");
+    assert_eq!(children[0]["crates_cnt"], 1);
+    assert_eq!(children[0]["direct_crates_cnt"], 0);
+    assert_eq!(children[0]["children"][0]["slug"], "cat1::sub1::subsub");
+    assert_eq!(children[1]["slug"], "cat1::sub2");
+    assert_eq!(children[1]["children"], json!([]));
+
+    assert_eq!(categories[1]["slug"], "cat2");
+    assert_eq!(categories[1]["crates_cnt"], 0);
+
+    let crate_names = |query: &str| {
+        let url = format!("/api/v1/categories/cat1/crates{query}");
+        let json = anon.get::<()>(&url).into_json();
+        json["crates"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|krate| krate["name"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(crate_names(""), vec!["foo_tree"]);
+    assert_eq!(
+        crate_names("?include_children=true"),
+        vec!["bar_tree", "foo_tree"]
+    );
+
+    anon.get::<()>("/api/v1/categories/unknown/crates")
+        .assert_not_found();
+}
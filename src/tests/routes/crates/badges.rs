@@ -0,0 +1,93 @@
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{RequestHelper, TestApp};
+use http::{header, StatusCode};
+
+fn badge(anon: &impl RequestHelper, url: &str) -> String {
+    let response = anon.get::<()>(url);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "image/svg+xml;charset=utf-8"
+    );
+    assert_eq!(
+        response.headers()[header::CACHE_CONTROL],
+        "public,max-age=3600"
+    );
+    response.into_text()
+}
+
+#[test]
+fn badges() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_badges", user.id)
+            .version(
+                VersionBuilder::new("1.2.0")
+                    .license(Some("MIT OR Apache-2.0"))
+                    .rust_version("1.60"),
+            )
+            .version(VersionBuilder::new("1.3.0-beta.1"))
+            .version(VersionBuilder::new("2.0.0").yanked(true))
+            .downloads(12_345)
+            .expect_build(conn);
+    });
+
+    let svg = badge(&anon, "/api/v1/crates/foo_badges/badges/version.svg");
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.contains("<title>crates.io: v1.2.0</title>"));
+    assert!(svg.contains("#007ec6"));
+
+    let svg = badge(&anon, "/api/v1/crates/foo_badges/badges/downloads.svg");
+    assert!(svg.contains("<title>downloads: 12k</title>"));
+
+    let svg = badge(&anon, "/api/v1/crates/foo_badges/badges/msrv.svg");
+    assert!(svg.contains("<title>msrv: 1.60</title>"));
+
+    let svg = badge(&anon, "/api/v1/crates/foo_badges/badges/license.svg");
+    assert!(svg.contains("<title>license: MIT OR Apache-2.0</title>"));
+
+    anon.get::<()>("/api/v1/crates/foo_badges/badges/unknown.svg")
+        .assert_not_found();
+}
+
+#[test]
+fn prerelease_and_unknown_values() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_pre", user.id)
+            .version(VersionBuilder::new("0.1.0-alpha.1").license(None))
+            .expect_build(conn);
+
+        CrateBuilder::new("foo_yanked", user.id)
+            .version(VersionBuilder::new("1.0.0").yanked(true))
+            .expect_build(conn);
+    });
+
+    let svg = badge(&anon, "/api/v1/crates/foo_pre/badges/version.svg");
+    assert!(svg.contains("<title>crates.io: v0.1.0-alpha.1</title>"));
+    assert!(svg.contains("#fe7d37"));
+
+    let svg = badge(&anon, "/api/v1/crates/foo_pre/badges/msrv.svg");
+    assert!(svg.contains("<title>msrv: unknown</title>"));
+
+    let svg = badge(&anon, "/api/v1/crates/foo_pre/badges/license.svg");
+    assert!(svg.contains("<title>license: unknown</title>"));
+
+    let svg = badge(&anon, "/api/v1/crates/foo_yanked/badges/version.svg");
+    assert!(svg.contains("<title>crates.io: yanked</title>"));
+}
+
+#[test]
+fn unknown_crate() {
+    let (_, anon) = TestApp::init().empty();
+
+    let response = anon.get::<()>("/api/v1/crates/unknown/badges/version.svg");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(response
+        .into_text()
+        .contains("<title>crates.io: not found</title>"));
+}
@@ -0,0 +1,105 @@
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{RequestHelper, TestApp};
+use chrono::{Duration, Utc};
+use http::StatusCode;
+use serde_json::Value;
+
+const URL: &str = "/api/v1/crates_batch";
+
+#[test]
+fn batch() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_batch", user.id)
+            .description("description")
+            .version(VersionBuilder::new("1.0.0"))
+            .version(VersionBuilder::new("1.1.0-beta.1"))
+            .version(VersionBuilder::new("1.2.0").yanked(true))
+            .downloads(20)
+            .recent_downloads(10)
+            .expect_build(conn);
+
+        CrateBuilder::new("bar_batch", user.id)
+            .version(VersionBuilder::new("0.1.0").yanked(true))
+            .version(VersionBuilder::new("0.2.0").yanked(true))
+            .expect_build(conn);
+    });
+
+    let body = json!({ "names": ["foo_batch", "bar_batch", "unknown", "foo_batch"] });
+    let response = anon.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let json = response.into_json();
+    assert_eq!(json["missing"], json!(["unknown"]));
+
+    let crates = json["crates"].as_array().unwrap();
+    assert_eq!(crates.len(), 2);
+
+    assert_eq!(crates[0]["name"], "bar_batch");
+    assert_eq!(crates[0]["max_version"], "0.2.0");
+    assert_eq!(crates[0]["yanked"], true);
+
+    assert_eq!(crates[1]["name"], "foo_batch");
+    assert_eq!(crates[1]["description"], "description");
+    assert_eq!(crates[1]["downloads"], 20);
+    assert_eq!(crates[1]["recent_downloads"], 10);
+    assert_eq!(crates[1]["max_version"], "1.1.0-beta.1");
+    assert_eq!(crates[1]["max_stable_version"], "1.0.0");
+    assert_eq!(crates[1]["yanked"], false);
+}
+
+#[test]
+fn batch_with_too_many_names() {
+    let (_, anon) = TestApp::init().empty();
+
+    let names = (0..101).map(|i| format!("crate_{i}")).collect::<Vec<_>>();
+    let body = json!({ "names": names });
+    let response = anon.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "too many crates requested, the maximum is 100" }] })
+    );
+}
+
+#[test]
+fn batch_with_invalid_body() {
+    let (_, anon) = TestApp::init().empty();
+
+    let response = anon.post::<()>(URL, b"{}");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn crate_named_batch_is_not_shadowed() {
+    let (app, anon, user) = TestApp::init().with_user();
+
+    app.db(|conn| {
+        CrateBuilder::new("batch", user.as_model().id).expect_build(conn);
+    });
+
+    let json = anon.get::<Value>("/api/v1/crates/batch").good();
+    assert_eq!(json["crate"]["name"], "batch");
+}
+
+#[test]
+fn batch_hides_staged_versions() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    let staged_until = (Utc::now() + Duration::days(1)).naive_utc();
+    app.db(|conn| {
+        CrateBuilder::new("foo_batch_staged", user.id)
+            .version(VersionBuilder::new("1.0.0"))
+            .version(VersionBuilder::new("1.1.0").staged_until(staged_until))
+            .expect_build(conn);
+    });
+
+    let body = json!({ "names": ["foo_batch_staged"] });
+    let json = anon
+        .post::<()>(URL, body.to_string().as_bytes())
+        .into_json();
+    assert_eq!(json["crates"][0]["max_version"], "1.0.0");
+}
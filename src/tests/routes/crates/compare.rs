@@ -0,0 +1,93 @@
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use serde_json::Value;
+
+const URL: &str = "/api/v1/crate_comparisons";
+
+#[test]
+fn compare() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        let dep = CrateBuilder::new("dep_compare", user.id).expect_build(conn);
+
+        CrateBuilder::new("foo_compare", user.id)
+            .version(VersionBuilder::new("1.0.0").license(Some("MIT")))
+            .version(
+                VersionBuilder::new("1.1.0")
+                    .license(Some("MIT OR Apache-2.0"))
+                    .rust_version("1.65")
+                    .dependency(&dep, None),
+            )
+            .version(VersionBuilder::new("2.0.0-beta.1"))
+            .version(VersionBuilder::new("1.2.0").yanked(true))
+            .downloads(20)
+            .recent_downloads(10)
+            .expect_build(conn);
+
+        CrateBuilder::new("bar_compare", user.id)
+            .version(VersionBuilder::new("0.1.0").yanked(true))
+            .expect_build(conn);
+    });
+
+    let query = "names=foo_compare,unknown,bar_compare,foo_compare";
+    let json = anon.get_with_query::<()>(URL, query).into_json();
+    assert_eq!(json["missing"], json!(["unknown"]));
+
+    let crates = json["crates"].as_array().unwrap();
+    assert_eq!(crates.len(), 2);
+
+    let foo = &crates[0];
+    assert_eq!(foo["name"], "foo_compare");
+    assert_eq!(foo["latest_version"], "1.1.0");
+    assert_eq!(foo["license"], "MIT OR Apache-2.0");
+    assert_eq!(foo["rust_version"], "1.65");
+    assert_eq!(foo["dependencies"], 1);
+    assert!(foo["last_release_at"].is_string());
+    assert_eq!(foo["downloads"], 20);
+    assert_eq!(foo["recent_downloads"], 10);
+
+    let trend = foo["downloads_trend"].as_array().unwrap();
+    let dates = json["meta"]["downloads_trend"]["dates"].as_array().unwrap();
+    assert_eq!(trend.len(), 12);
+    assert_eq!(trend.len(), dates.len());
+    assert_eq!(trend.last().unwrap(), 10);
+
+    let bar = &crates[1];
+    assert_eq!(bar["name"], "bar_compare");
+    assert_eq!(bar["latest_version"], "0.1.0");
+    assert_eq!(bar["dependencies"], 0);
+}
+
+#[test]
+fn compare_with_invalid_names() {
+    let (_, anon) = TestApp::init().empty();
+
+    let response = anon.get::<()>(URL);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let names = (0..11)
+        .map(|i| format!("crate_{i}"))
+        .collect::<Vec<_>>()
+        .join(",");
+    let response = anon.get_with_query::<()>(URL, &format!("names={names}"));
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "too many crates requested, the maximum is 10" }] })
+    );
+}
+
+#[test]
+fn crate_named_compare_is_not_shadowed() {
+    let (app, anon, user) = TestApp::init().with_user();
+
+    app.db(|conn| {
+        CrateBuilder::new("compare", user.as_model().id).expect_build(conn);
+    });
+
+    let json = anon.get::<Value>("/api/v1/crates/compare").good();
+    assert_eq!(json["crate"]["name"], "compare");
+}
@@ -0,0 +1,54 @@
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{RequestHelper, TestApp};
+use crates_io::background_jobs::Job;
+
+#[test]
+fn dependents_stats() {
+    let url = "/api/v1/crates/c1/dependents_stats";
+    let (app, anon, user) = TestApp::full().with_user();
+    let user = user.as_model();
+    anon.get::<()>(url).assert_not_found();
+
+    app.db(|conn| {
+        let c1 = CrateBuilder::new("c1", user.id)
+            .downloads(1000)
+            .expect_build(conn);
+        CrateBuilder::new("c2", user.id)
+            .downloads(600)
+            .version(VersionBuilder::new("1.0.0").dependency(&c1, None))
+            .expect_build(conn);
+        CrateBuilder::new("c3", user.id)
+            .downloads(200)
+            .version(VersionBuilder::new("1.0.0").dependency(&c1, None))
+            .expect_build(conn);
+        // Only the latest version of a dependent is considered
+        CrateBuilder::new("c4", user.id)
+            .downloads(800)
+            .version(VersionBuilder::new("1.0.0").dependency(&c1, None))
+            .version("2.0.0")
+            .expect_build(conn);
+    });
+
+    let json = anon.get::<()>(url).into_json();
+    assert_eq!(json["top_dependents"], json!([]));
+    assert_eq!(json["updated_at"], json!(null));
+
+    app.db(|conn| Job::update_dependent_stats().enqueue(conn).unwrap());
+    app.run_pending_background_jobs();
+
+    let mut json = anon.get::<()>(url).into_json();
+    assert!(json["updated_at"].is_string());
+    json.as_object_mut().unwrap().remove("updated_at");
+    assert_eq!(
+        json,
+        json!({
+            "crate": "c1",
+            "downloads": 1000,
+            "dependents_share": 0.8,
+            "top_dependents": [
+                { "crate": "c2", "downloads": 600, "share": 0.6 },
+                { "crate": "c3", "downloads": 200, "share": 0.2 },
+            ],
+        })
+    );
+}
@@ -1,7 +1,9 @@
 use crate::builders::{CrateBuilder, VersionBuilder};
 use crate::util::{MockAnonymousUser, RequestHelper, TestApp};
-use chrono::{Duration, Utc};
+use chrono::{Duration, NaiveDate, Utc};
+use crates_io::schema::{version_downloads, versions};
 use crates_io::views::EncodableVersionDownload;
+use diesel::prelude::*;
 use http::StatusCode;
 
 #[derive(Deserialize)]
@@ -81,3 +83,83 @@ fn download() {
     assert_dl_count(&anon, "FOO_DOWNLOAD/1.0.0", Some(&query), 2);
     assert_dl_count(&anon, "FOO_DOWNLOAD", Some(&query), 2);
 }
+
+#[test]
+fn download_series() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        let krate = CrateBuilder::new("foo_series", user.id)
+            .version(VersionBuilder::new("1.0.0"))
+            .version(VersionBuilder::new("1.1.0"))
+            .expect_build(conn);
+
+        let version_ids: Vec<(String, i32)> = versions::table
+            .filter(versions::crate_id.eq(krate.id))
+            .select((versions::num, versions::id))
+            .load(conn)
+            .unwrap();
+
+        let rows = version_ids
+            .iter()
+            .flat_map(|(num, version_id)| {
+                let downloads = if num == "1.0.0" { 1 } else { 10 };
+                // 2023-08-28 is a Monday
+                ["2023-08-28", "2023-08-30", "2023-09-04"].map(|date| {
+                    let date = NaiveDate::parse_from_str(date, "%F").unwrap();
+                    (
+                        version_downloads::version_id.eq(*version_id),
+                        version_downloads::downloads.eq(downloads),
+                        version_downloads::date.eq(date),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(version_downloads::table)
+            .values(&rows)
+            .execute(conn)
+            .unwrap();
+    });
+
+    let url = "/api/v1/crates/foo_series/1.0.0/downloads";
+    let json = anon
+        .get_with_query::<()>(url, "from=2023-08-29&to=2023-09-04")
+        .into_json();
+    assert_eq!(json["resolution"], "day");
+    assert_eq!(json["dates"].as_array().unwrap().len(), 7);
+    assert_eq!(json["dates"][0], "2023-08-29");
+    assert_eq!(json["downloads"], json!([0, 1, 0, 0, 0, 0, 1]));
+
+    let json = anon
+        .get_with_query::<()>(url, "from=2023-08-30&to=2023-09-10&resolution=week")
+        .into_json();
+    assert_eq!(json["from"], "2023-08-28");
+    assert_eq!(json["dates"], json!(["2023-08-28", "2023-09-04"]));
+    assert_eq!(json["downloads"], json!([2, 1]));
+
+    // The bulk variant returns the series of all versions, newest first
+    let url = "/api/v1/crates/foo_series/downloads";
+    let json = anon
+        .get_with_query::<()>(url, "from=2023-08-28&to=2023-09-10&resolution=week")
+        .into_json();
+    assert_eq!(json["dates"], json!(["2023-08-28", "2023-09-04"]));
+    let versions = json["versions"].as_array().unwrap();
+    assert_eq!(versions.len(), 2);
+    assert_eq!(versions[0]["num"], "1.1.0");
+    assert_eq!(versions[0]["downloads"], json!([20, 10]));
+    assert_eq!(versions[1]["num"], "1.0.0");
+    assert_eq!(versions[1]["downloads"], json!([2, 1]));
+
+    let url = "/api/v1/crates/foo_series/1.0.0/downloads";
+    for query in [
+        "resolution=month",
+        "from=yesterday",
+        "from=2023-09-04&to=2023-08-28",
+        "from=2020-01-01&to=2023-01-01",
+    ] {
+        let response = anon.get_with_query::<()>(url, query);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{query}");
+    }
+}
@@ -0,0 +1,80 @@
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{RequestHelper, TestApp};
+use chrono::{Duration, Utc};
+use crates_io::background_jobs::Job;
+
+#[test]
+fn health() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+    anon.get::<()>("/api/v1/crates/foo_health/health")
+        .assert_not_found();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_health", user.id)
+            .version("1.0.0")
+            .version(VersionBuilder::new("1.1.0").yanked(true))
+            .expect_build(conn);
+    });
+
+    let json = anon
+        .get::<()>("/api/v1/crates/foo_health/health")
+        .into_json();
+    assert_eq!(
+        json,
+        json!({
+            "crate": "foo_health",
+            "score": 75,
+            "checks": [{
+                "name": "release_recency",
+                "score": 50,
+                "max_score": 50,
+                "description": "Time since the latest release that has not been yanked",
+            }, {
+                "name": "yanked_ratio",
+                "score": 15,
+                "max_score": 30,
+                "description": "Share of versions that have not been yanked",
+            }, {
+                "name": "release_frequency",
+                "score": 10,
+                "max_score": 20,
+                "description": "Number of releases within the last year",
+            }],
+        })
+    );
+}
+
+#[test]
+fn update_health_scores() {
+    let (app, anon, user) = TestApp::full().with_user();
+    let user = user.as_model();
+
+    let long_ago = (Utc::now() - Duration::days(3 * 365)).naive_utc();
+    app.db(|conn| {
+        CrateBuilder::new("old_health", user.id)
+            .version(VersionBuilder::new("1.0.0").created_at(long_ago))
+            .expect_build(conn);
+
+        CrateBuilder::new("new_health", user.id)
+            .version("1.0.0")
+            .expect_build(conn);
+
+        Job::update_health_scores().enqueue(conn).unwrap();
+    });
+
+    let json = anon.get::<()>("/api/v1/crates/old_health").into_json();
+    assert_eq!(json["crate"].get("health_score"), None);
+
+    app.run_pending_background_jobs();
+
+    let json = anon.get::<()>("/api/v1/crates/old_health").into_json();
+    assert_eq!(json["crate"]["health_score"], 30);
+
+    let json = anon.get::<()>("/api/v1/crates/new_health").into_json();
+    assert_eq!(json["crate"]["health_score"], 85);
+
+    let json = anon.search("sort=health");
+    let names = json.crates.iter().map(|c| &*c.name).collect::<Vec<_>>();
+    assert_eq!(names, ["new_health", "old_health"]);
+}
@@ -133,6 +133,32 @@ fn search_includes_crates_where_name_is_stopword() {
     assert_eq!(json.meta.total, 1);
 }
 
+#[test]
+fn search_normalizes_unicode() {
+    let (app, anon, user) = TestApp::init()
+        .with_config(|config| config.search_transliteration = true)
+        .with_user();
+    let user = user.as_model();
+    app.db(|conn| {
+        CrateBuilder::new("naive_search", user.id)
+            .description("A naive implementation")
+            .expect_build(conn);
+        CrateBuilder::new("cafe_search", user.id)
+            .description("Un café pour tous")
+            .expect_build(conn);
+    });
+
+    // `naïve` is also matched as `naive`
+    let json = anon.search("q=na%C3%AFve");
+    assert_eq!(json.meta.total, 1);
+    assert_eq!(json.crates[0].name, "naive_search");
+
+    // `e` followed by a combining acute accent matches the composed `é`
+    let json = anon.search("q=cafe%CC%81");
+    assert_eq!(json.meta.total, 1);
+    assert_eq!(json.crates[0].name, "cafe_search");
+}
+
 #[test]
 fn exact_match_first_on_queries() {
     let (app, anon, user) = TestApp::init().with_user();
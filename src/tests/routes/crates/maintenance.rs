@@ -0,0 +1,75 @@
+use crate::builders::CrateBuilder;
+use crate::util::{RequestHelper, TestApp};
+use chrono::Utc;
+use crates_io::schema::crate_maintenance_status;
+use diesel::prelude::*;
+use http::StatusCode;
+
+const URL: &str = "/api/v1/crates/foo_maintenance/maintenance";
+
+#[test]
+fn show_without_status() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user_id = user.as_model().id;
+    app.db(|conn| {
+        CrateBuilder::new("foo_maintenance", user_id).expect_build(conn);
+    });
+
+    let json = anon.get::<()>(URL).into_json();
+    assert_eq!(
+        json,
+        json!({ "maintenance": { "stale": false, "flagged_at": null, "confirmed_at": null } })
+    );
+
+    anon.get::<()>("/api/v1/crates/unknown/maintenance")
+        .assert_not_found();
+}
+
+#[test]
+fn owners_can_confirm_maintenance() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user_id = user.as_model().id;
+    app.db(|conn| {
+        let krate = CrateBuilder::new("foo_maintenance", user_id).expect_build(conn);
+        diesel::insert_into(crate_maintenance_status::table)
+            .values((
+                crate_maintenance_status::crate_id.eq(krate.id),
+                crate_maintenance_status::flagged_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)
+            .unwrap();
+    });
+
+    let json = anon.get::<()>(URL).into_json();
+    assert_eq!(json["maintenance"]["stale"], true);
+
+    let json = user.put::<()>(URL, b"").into_json();
+    assert_eq!(json["maintenance"]["stale"], false);
+    assert!(json["maintenance"]["confirmed_at"].is_string());
+
+    let confirmed_by = app.db(|conn| {
+        crate_maintenance_status::table
+            .select(crate_maintenance_status::confirmed_by)
+            .first::<Option<i32>>(conn)
+            .unwrap()
+    });
+    assert_eq!(confirmed_by, Some(user_id));
+
+    let json = anon.get::<()>("/api/v1/crates/foo_maintenance").into_json();
+    assert!(json["crate"].get("stale_since").is_none());
+}
+
+#[test]
+fn non_owners_cannot_confirm_maintenance() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user_id = user.as_model().id;
+    app.db(|conn| {
+        CrateBuilder::new("foo_maintenance", user_id).expect_build(conn);
+    });
+
+    let other = app.db_new_user("other");
+    other.put::<()>(URL, b"").assert_forbidden();
+
+    let response = anon.put::<()>(URL, b"");
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
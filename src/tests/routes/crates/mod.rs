@@ -1,8 +1,16 @@
+mod badges;
+mod batch;
+mod compare;
+mod dependents_stats;
 pub mod downloads;
 mod following;
+mod health;
 mod list;
+mod maintenance;
 mod new;
 pub mod owners;
 mod read;
 mod reverse_dependencies;
+mod subscription;
+mod suggest;
 pub mod versions;
@@ -0,0 +1,148 @@
+use crate::builders::CrateBuilder;
+use crate::util::{MockCookieUser, RequestHelper, TestApp};
+use crate::{add_team_to_crate, new_team};
+use crates_io::schema::crate_owner_invitations;
+use diesel::prelude::*;
+use serde_json::Value;
+
+const URL: &str = "/api/v1/crates/guacamole/owners/bulk";
+
+/// Creates a crate owned by the user and the `github:test_org:core` team, and
+/// another user called `cilantro`.
+fn setup() -> (TestApp, MockCookieUser) {
+    let (app, _, owner) = TestApp::init().with_user();
+    app.db_new_user("cilantro");
+
+    app.db(|conn| {
+        let owner = owner.as_model();
+        let krate = CrateBuilder::new("guacamole", owner.id).expect_build(conn);
+        let team = new_team("github:test_org:core")
+            .create_or_update(conn)
+            .unwrap();
+        add_team_to_crate(&team, &krate, owner, conn).unwrap();
+    });
+
+    (app, owner)
+}
+
+fn bulk_update(user: &MockCookieUser, body: Value) -> Value {
+    user.put::<()>(URL, body.to_string().as_bytes()).into_json()
+}
+
+fn owner_logins(user: &MockCookieUser) -> Vec<String> {
+    let json = user
+        .get::<()>("/api/v1/crates/guacamole/owners")
+        .into_json();
+    let mut logins = json["users"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|owner| owner["login"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    logins.sort();
+    logins
+}
+
+fn invitation_count(app: &TestApp) -> i64 {
+    app.db(|conn| {
+        crate_owner_invitations::table
+            .count()
+            .get_result(conn)
+            .unwrap()
+    })
+}
+
+#[test]
+fn dry_run_returns_planned_changes() {
+    let (app, owner) = setup();
+
+    let json = bulk_update(
+        &owner,
+        json!({ "owners": ["foo", "cilantro", "unknown_user"], "dry_run": true }),
+    );
+    assert_eq!(
+        json,
+        json!({
+            "ok": true,
+            "dry_run": true,
+            "added": ["cilantro", "unknown_user"],
+            "removed": ["github:test_org:core"],
+            "errors": [{
+                "owner": "unknown_user",
+                "detail": "could not find user with login `unknown_user`",
+            }],
+            "msg": "owners successfully removed",
+        })
+    );
+
+    assert_eq!(owner_logins(&owner), vec!["foo", "github:test_org:core"]);
+    assert_eq!(invitation_count(&app), 0);
+}
+
+#[test]
+fn applies_changes() {
+    let (app, owner) = setup();
+
+    let json = bulk_update(&owner, json!({ "owners": ["foo", "Cilantro"] }));
+    assert_eq!(json["dry_run"], false);
+    assert_eq!(json["added"], json!(["Cilantro"]));
+    assert_eq!(json["removed"], json!(["github:test_org:core"]));
+    assert_eq!(json["errors"], json!([]));
+    assert_eq!(
+        json["msg"],
+        "user cilantro has been invited to be an owner of crate guacamole,\
+         owners successfully removed"
+    );
+
+    assert_eq!(owner_logins(&owner), vec!["foo"]);
+    assert_eq!(invitation_count(&app), 1);
+
+    // Nothing changes if the owners are already up to date
+    let json = bulk_update(&owner, json!({ "owners": ["foo"] }));
+    assert_eq!(json["added"], json!([]));
+    assert_eq!(json["removed"], json!([]));
+}
+
+#[test]
+fn errors_abort_the_whole_update() {
+    let (_app, owner) = setup();
+
+    let json = bulk_update(&owner, json!({ "owners": ["foo", "unknown_user"] }));
+    assert_eq!(
+        json,
+        json!({ "errors": [{ "detail": "could not find user with login `unknown_user`" }] })
+    );
+
+    assert_eq!(owner_logins(&owner), vec!["foo", "github:test_org:core"]);
+}
+
+#[test]
+fn at_least_one_user_owner_is_required() {
+    let (app, owner) = setup();
+
+    let json = bulk_update(
+        &owner,
+        json!({ "owners": ["cilantro", "github:test_org:core"] }),
+    );
+    assert_eq!(
+        json["errors"][0]["detail"],
+        "cannot remove all individual owners of a crate. \
+         Team member don't have permission to modify owners, so \
+         at least one individual owner is required."
+    );
+
+    assert_eq!(owner_logins(&owner), vec!["foo", "github:test_org:core"]);
+    assert_eq!(invitation_count(&app), 0);
+}
+
+#[test]
+fn only_owners_can_update_owners() {
+    let (app, _) = setup();
+    let other = app.db_new_user("other");
+
+    let json = bulk_update(&other, json!({ "owners": ["other"] }));
+    assert_eq!(
+        json["errors"][0]["detail"],
+        "only owners have permission to modify owners"
+    );
+}
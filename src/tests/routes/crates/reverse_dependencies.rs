@@ -1,6 +1,7 @@
 use crate::builders::{CrateBuilder, VersionBuilder};
 use crate::util::{RequestHelper, TestApp};
 use crate::CrateMeta;
+use chrono::{Duration, Utc};
 use crates_io::views::{EncodableDependency, EncodableVersion};
 
 #[derive(Deserialize)]
@@ -215,3 +216,28 @@ fn reverse_dependencies_query_supports_u64_version_number_parts() {
     assert_eq!(deps.versions[0].krate, "c2");
     assert_eq!(deps.versions[0].num, large_but_valid_version_number);
 }
+
+#[test]
+fn staged_versions_not_included_in_reverse_dependencies() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    let staged_until = (Utc::now() + Duration::days(1)).naive_utc();
+    app.db(|conn| {
+        let c1 = CrateBuilder::new("c1", user.id)
+            .version("1.0.0")
+            .expect_build(conn);
+        CrateBuilder::new("c2", user.id)
+            .version("1.0.0")
+            .version(
+                VersionBuilder::new("2.0.0")
+                    .dependency(&c1, None)
+                    .staged_until(staged_until),
+            )
+            .expect_build(conn);
+    });
+
+    let deps = anon.reverse_dependencies("c1");
+    assert_eq!(deps.dependencies.len(), 0);
+    assert_eq!(deps.meta.total, 0);
+}
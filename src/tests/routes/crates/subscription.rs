@@ -0,0 +1,165 @@
+use crate::builders::{CrateBuilder, PublishBuilder};
+use crate::routes::admin::{admin_request, ADMIN_TOKEN};
+use crate::routes::crates::versions::yank_unyank::YankRequestHelper;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::background_jobs::Job;
+use http::{Method, StatusCode};
+
+#[test]
+fn subscribe_and_unsubscribe() {
+    let (app, _, user) = TestApp::init().with_user();
+    let user_model = user.as_model();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_subscription", user_model.id).expect_build(conn);
+    });
+
+    let url = "/api/v1/crates/foo_subscription/subscription";
+    let json = user.get::<()>(url).into_json();
+    assert_eq!(json["subscription"], json!(null));
+
+    let json = user.put::<()>(url, &[]).into_json();
+    assert_eq!(json["subscription"]["new_versions"], true);
+    assert_eq!(json["subscription"]["yanks"], true);
+    assert_eq!(json["subscription"]["advisories"], true);
+
+    let json = user.put::<()>(url, br#"{ "yanks": false }"#).into_json();
+    assert_eq!(json["subscription"]["new_versions"], true);
+    assert_eq!(json["subscription"]["yanks"], false);
+
+    let json = user
+        .put::<()>(url, br#"{ "new_versions": false }"#)
+        .into_json();
+    assert_eq!(json["subscription"]["new_versions"], false);
+    assert_eq!(json["subscription"]["yanks"], false);
+    assert_eq!(json["subscription"]["advisories"], true);
+
+    let json = user.get::<()>(url).into_json();
+    assert_eq!(json["subscription"]["new_versions"], false);
+
+    assert_eq!(user.delete::<()>(url).status(), StatusCode::OK);
+
+    let json = user.get::<()>(url).into_json();
+    assert_eq!(json["subscription"], json!(null));
+}
+
+#[test]
+fn subscribe_to_unknown_crate() {
+    let (_, _, user) = TestApp::init().with_user();
+
+    user.put::<()>("/api/v1/crates/foo_unknown/subscription", &[])
+        .assert_not_found();
+}
+
+#[test]
+fn subscribe_requires_authentication() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_anon_subscription", user.id).expect_build(conn);
+    });
+
+    anon.put::<()>("/api/v1/crates/foo_anon_subscription/subscription", &[])
+        .assert_forbidden();
+}
+
+#[test]
+fn notification_digest() {
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo_digest").version("1.0.0");
+    token.publish_crate(crate_to_publish).good();
+
+    let subscriber = app.db_new_user("subscriber");
+    let url = "/api/v1/crates/foo_digest/subscription";
+    let response = subscriber.put::<()>(url, br#"{ "yanks": false }"#);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let crate_to_publish = PublishBuilder::new("foo_digest").version("1.1.0");
+    token.publish_crate(crate_to_publish).good();
+    token.yank("foo_digest", "1.0.0").good();
+
+    let mails_before = app.as_inner().emails.mails_in_memory().unwrap().len();
+
+    app.db(|conn| {
+        Job::send_crate_notification_digests()
+            .enqueue(conn)
+            .unwrap();
+    });
+    app.run_pending_background_jobs();
+
+    let mails = app.as_inner().emails.mails_in_memory().unwrap();
+    assert_eq!(mails.len(), mails_before + 1);
+
+    let digest = mails.last().unwrap();
+    assert_eq!(
+        digest.subject,
+        "Updates to the crates you are subscribed to"
+    );
+    assert!(digest.body.contains("- foo_digest 1.1.0 was published"));
+    assert!(!digest.body.contains("was yanked"));
+
+    // Notifications are only sent once
+    app.db(|conn| {
+        Job::send_crate_notification_digests()
+            .enqueue(conn)
+            .unwrap();
+    });
+    app.run_pending_background_jobs();
+
+    let mails = app.as_inner().emails.mails_in_memory().unwrap();
+    assert_eq!(mails.len(), mails_before + 1);
+}
+
+#[test]
+fn advisory_notification_digest() {
+    let (app, anon, _, token) = TestApp::full()
+        .with_config(|config| config.admin_authorization_token = Some(ADMIN_TOKEN.into()))
+        .with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo_advisory_digest").version("1.0.0");
+    token.publish_crate(crate_to_publish).good();
+
+    let subscriber = app.db_new_user("subscriber");
+    let url = "/api/v1/crates/foo_advisory_digest/subscription";
+    let response = subscriber.put::<()>(url, &[]);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let opted_out = app.db_new_user("opted_out");
+    let response = opted_out.put::<()>(url, br#"{ "advisories": false }"#);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let url = "/api/private/admin/advisories/foo_advisory_digest/RUSTSEC-2023-0001";
+    let body = br#"{ "affected": "<1.1.0" }"#;
+    let response = admin_request(&anon, Method::PUT, url, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Recording the same advisory again does not notify the subscribers twice
+    let response = admin_request(&anon, Method::PUT, url, Some(ADMIN_TOKEN), body);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mails_before = app.as_inner().emails.mails_in_memory().unwrap().len();
+
+    app.db(|conn| {
+        Job::send_crate_notification_digests()
+            .enqueue(conn)
+            .unwrap();
+    });
+    app.run_pending_background_jobs();
+
+    // Only the subscriber that did not opt out of advisories is notified
+    let mails = app.as_inner().emails.mails_in_memory().unwrap();
+    assert_eq!(mails.len(), mails_before + 1);
+
+    let digest = mails.last().unwrap();
+    assert_eq!(
+        digest.body.matches("RUSTSEC-2023-0001").count(),
+        1,
+        "{}",
+        digest.body
+    );
+    assert!(digest.body.contains(
+        "- foo_advisory_digest has the advisory RUSTSEC-2023-0001 for the versions `<1.1.0`"
+    ));
+}
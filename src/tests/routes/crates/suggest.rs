@@ -0,0 +1,82 @@
+use crate::builders::CrateBuilder;
+use crate::util::{RequestHelper, TestApp};
+use serde_json::Value;
+
+fn suggestions(anon: &impl RequestHelper, q: &str) -> Vec<String> {
+    let url = format!("/api/v1/crate_suggestions?q={q}");
+    let json = anon.get::<()>(&url).into_json();
+    json["crates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|krate| krate["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn suggest() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        CrateBuilder::new("serde", user.id)
+            .downloads(10)
+            .expect_build(conn);
+        CrateBuilder::new("serde_json", user.id)
+            .downloads(100)
+            .expect_build(conn);
+        CrateBuilder::new("serde-yaml", user.id)
+            .downloads(50)
+            .expect_build(conn);
+        CrateBuilder::new("serial", user.id)
+            .downloads(1000)
+            .expect_build(conn);
+        CrateBuilder::new("myserde", user.id)
+            .downloads(10000)
+            .expect_build(conn);
+    });
+
+    // Only crates that start with the query are returned, sorted by downloads
+    assert_eq!(
+        suggestions(&anon, "ser"),
+        ["serial", "serde_json", "serde-yaml", "serde"]
+    );
+
+    // An exact match is always ranked first
+    assert_eq!(
+        suggestions(&anon, "serde"),
+        ["serde", "serde_json", "serde-yaml"]
+    );
+
+    // `-` and `_` are equivalent, but other wildcards are matched literally
+    assert_eq!(suggestions(&anon, "SERDE-"), ["serde_json", "serde-yaml"]);
+    assert_eq!(suggestions(&anon, "se%25"), Vec::<String>::new());
+
+    assert_eq!(suggestions(&anon, ""), Vec::<String>::new());
+}
+
+#[test]
+fn suggest_is_limited() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        for i in 0..15 {
+            CrateBuilder::new(&format!("foo{i}"), user.id).expect_build(conn);
+        }
+    });
+
+    assert_eq!(suggestions(&anon, "foo").len(), 10);
+}
+
+#[test]
+fn crate_named_suggest_is_not_shadowed() {
+    let (app, anon, user) = TestApp::init().with_user();
+
+    app.db(|conn| {
+        CrateBuilder::new("suggest", user.as_model().id).expect_build(conn);
+    });
+
+    let json = anon.get::<Value>("/api/v1/crates/suggest").good();
+    assert_eq!(json["crate"]["name"], "suggest");
+}
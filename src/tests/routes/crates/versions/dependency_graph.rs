@@ -0,0 +1,138 @@
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{MockAnonymousUser, RequestHelper, TestApp};
+use http::{header, StatusCode};
+
+const URL: &str = "/api/v1/crates/foo_graph/1.0.0/dependency_graph";
+
+fn setup() -> (TestApp, MockAnonymousUser) {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        let baz = CrateBuilder::new("baz_graph", user.id).expect_build(conn);
+        let bar = CrateBuilder::new("bar_graph", user.id)
+            .version(VersionBuilder::new("1.0.0").dependency(&baz, None))
+            .version(VersionBuilder::new("2.0.0").yanked(true))
+            .expect_build(conn);
+        CrateBuilder::new("foo_graph", user.id)
+            .version(VersionBuilder::new("1.0.0").dependency(&bar, None))
+            .expect_build(conn);
+    });
+
+    (app, anon)
+}
+
+fn node_names(json: &serde_json::Value) -> Vec<String> {
+    json["dependency_graph"]["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|node| {
+            let crate_name = node["crate"].as_str().unwrap();
+            let version = node["version"].as_str().unwrap();
+            format!("{crate_name} {version}")
+        })
+        .collect()
+}
+
+#[test]
+fn resolves_transitive_dependencies() {
+    let (_app, anon) = setup();
+
+    let json = anon.get::<()>(URL).into_json();
+    assert_eq!(
+        node_names(&json),
+        vec!["foo_graph 1.0.0", "bar_graph 1.0.0", "baz_graph 0.99.0"]
+    );
+    assert_eq!(
+        json["dependency_graph"]["edges"].as_array().unwrap().len(),
+        2
+    );
+    assert_eq!(json["dependency_graph"]["edges"][0]["req"], ">= 0");
+    assert_eq!(json["dependency_graph"]["edges"][0]["kind"], "normal");
+    assert_eq!(json["dependency_graph"]["truncated"], false);
+    assert_eq!(json["meta"]["depth"], 3);
+}
+
+#[test]
+fn depth_limits_the_graph() {
+    let (_app, anon) = setup();
+
+    let json = anon.get_with_query::<()>(URL, "depth=1").into_json();
+    assert_eq!(
+        node_names(&json),
+        vec!["foo_graph 1.0.0", "bar_graph 1.0.0"]
+    );
+    assert_eq!(json["meta"]["depth"], 1);
+}
+
+#[test]
+fn invalid_depth() {
+    let (_app, anon) = setup();
+
+    for query in ["depth=0", "depth=11", "depth=foo"] {
+        let response = anon.get_with_query::<()>(URL, query);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.into_json(),
+            json!({ "errors": [{ "detail": "depth must be a number between 1 and 10" }] })
+        );
+    }
+}
+
+#[test]
+fn cycles() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        let a = CrateBuilder::new("cycle_a", user.id).expect_build(conn);
+        let b = CrateBuilder::new("cycle_b", user.id)
+            .version(VersionBuilder::new("1.0.0").dependency(&a, None))
+            .expect_build(conn);
+        VersionBuilder::new("1.0.0")
+            .dependency(&b, None)
+            .expect_build(a.id, user.id, conn);
+    });
+
+    let json = anon
+        .get::<()>("/api/v1/crates/cycle_a/1.0.0/dependency_graph")
+        .into_json();
+    assert_eq!(node_names(&json), vec!["cycle_a 1.0.0", "cycle_b 1.0.0"]);
+
+    let graph = &json["dependency_graph"];
+    let root = &graph["nodes"][0]["id"];
+    let edges = graph["edges"].as_array().unwrap();
+    assert_eq!(edges.len(), 2);
+    assert_eq!(&edges[1]["to"], root);
+}
+
+#[test]
+fn dot_format() {
+    let (_app, anon) = setup();
+
+    let response = anon.get_with_query::<()>(URL, "format=dot");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "text/vnd.graphviz; charset=utf-8"
+    );
+
+    let dot = response.into_text();
+    assert!(dot.starts_with("digraph dependencies {\n"));
+    assert!(dot.contains("[label=\"bar_graph 1.0.0\"];"));
+
+    let response = anon.get_with_query::<()>(URL, "format=svg");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn unknown_version() {
+    let (_app, anon) = setup();
+
+    let response = anon.get::<()>("/api/v1/crates/foo_graph/1.0.2/dependency_graph");
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "crate `foo_graph` does not have a version `1.0.2`" }] })
+    );
+}
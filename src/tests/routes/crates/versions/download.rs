@@ -1,5 +1,7 @@
-use crate::builders::{CrateBuilder, VersionBuilder};
-use crate::util::{RequestHelper, TestApp};
+use crate::builders::{CrateBuilder, PublishBuilder, VersionBuilder};
+use crate::util::{MockRequestExt, RequestHelper, TestApp};
+use crates_io::views::GoodCrate;
+use http::{header, StatusCode};
 
 #[test]
 fn download_nonexistent_version_of_existing_crate_404s() {
@@ -115,3 +117,151 @@ fn download_with_build_metadata() {
     anon.get::<()>("/api/v1/crates/foo/1.0.0+bar/readme")
         .assert_redirect_ends_with("/readmes/foo/foo-1.0.0%2Bbar.html");
 }
+
+#[test]
+fn download_zstd_recompressed_crate_file() {
+    let (app, anon, _, token) = TestApp::full()
+        .with_config(|config| {
+            config.zstd_recompression = true;
+        })
+        .with_token();
+
+    token
+        .publish_crate(PublishBuilder::new("foo").version("1.0.0+bar"))
+        .good();
+
+    let expected_files = vec![
+        "crates/foo/foo-1.0.0 bar.crate",
+        "crates/foo/foo-1.0.0 bar.tar.zst",
+        "crates/foo/foo-1.0.0+bar.crate",
+        "crates/foo/foo-1.0.0+bar.tar.zst",
+        "index/3/f/foo",
+    ];
+    assert_eq!(app.stored_files(), expected_files);
+
+    let version = anon.show_version("foo", "1.0.0+bar").version;
+    let checksum = version.checksum_zstd.unwrap();
+    assert_eq!(checksum.len(), 64);
+    assert_ne!(checksum, version.checksum);
+
+    anon.get::<()>("/api/v1/crates/foo/1.0.0+bar/download?format=zstd")
+        .assert_redirect_ends_with("/crates/foo/foo-1.0.0%2Bbar.tar.zst");
+
+    let mut request = anon.get_request("/api/v1/crates/foo/1.0.0+bar/download");
+    request.header(header::ACCEPT_ENCODING, "gzip, zstd");
+    anon.run::<()>(request)
+        .assert_redirect_ends_with("/crates/foo/foo-1.0.0%2Bbar.tar.zst");
+
+    // An explicit format takes precedence over the `Accept-Encoding` header
+    let mut request = anon.get_request("/api/v1/crates/foo/1.0.0+bar/download?format=gzip");
+    request.header(header::ACCEPT_ENCODING, "zstd");
+    anon.run::<()>(request)
+        .assert_redirect_ends_with("/crates/foo/foo-1.0.0%2Bbar.crate");
+
+    let mut request = anon.get_request("/api/v1/crates/foo/1.0.0+bar/download");
+    request.header(header::ACCEPT_ENCODING, "gzip, zstd;q=0");
+    anon.run::<()>(request)
+        .assert_redirect_ends_with("/crates/foo/foo-1.0.0%2Bbar.crate");
+}
+
+#[test]
+fn download_content_addressed_crate_file() {
+    let (app, anon, _, token) = TestApp::full()
+        .with_config(|config| {
+            config.content_addressed_storage = true;
+        })
+        .with_token();
+
+    token
+        .publish_crate(PublishBuilder::new("foo").version("1.0.0"))
+        .good();
+
+    let checksum = anon.show_version("foo", "1.0.0").version.checksum;
+    let object_path = format!("objects/{}/{}/{checksum}", &checksum[..2], &checksum[2..4]);
+
+    let expected_files = vec!["index/3/f/foo".to_string(), object_path.clone()];
+    assert_eq!(app.stored_files(), expected_files);
+
+    anon.get::<()>("/api/v1/crates/foo/1.0.0/download")
+        .assert_redirect_ends_with(&format!("/{object_path}"));
+}
+
+#[test]
+fn download_zstd_falls_back_to_gzip() {
+    let (app, anon, user) = TestApp::init().with_user();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_download", user.as_model().id)
+            .version(VersionBuilder::new("1.0.0"))
+            .expect_build(conn);
+    });
+
+    // No zstd-compressed copy of the crate file has been created yet
+    anon.get::<()>("/api/v1/crates/foo_download/1.0.0/download?format=zstd")
+        .assert_redirect_ends_with("/crates/foo_download/foo_download-1.0.0.crate");
+
+    let version = anon.show_version("foo_download", "1.0.0").version;
+    assert_none!(version.checksum_zstd);
+}
+
+#[test]
+fn download_staged_version_with_range_requests() {
+    let (_, _, _, token) = TestApp::full().with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo_staged").version("1.0.0");
+    token
+        .put::<GoodCrate>("/api/v1/crates/new?staged=true", &crate_to_publish.body())
+        .good();
+
+    let url = "/api/v1/crates/foo_staged/1.0.0/download";
+
+    let response = token.get::<()>(url);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::ACCEPT_RANGES], "bytes");
+    let etag = response.headers()[header::ETAG].clone();
+    assert!(!etag.to_str().unwrap().starts_with("W/"));
+    let content_length = response.headers()[header::CONTENT_LENGTH].clone();
+    let full = response.into_bytes();
+    assert_eq!(content_length, full.len().to_string());
+
+    let mut request = token.get_request(url);
+    request.header(header::RANGE, "bytes=10-19");
+    let response = token.run::<()>(request);
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        response.headers()[header::CONTENT_RANGE],
+        format!("bytes 10-19/{}", full.len())
+    );
+    assert_eq!(response.headers()[header::CONTENT_LENGTH], "10");
+    assert_eq!(response.into_bytes(), full.slice(10..20));
+
+    // Resuming an interrupted download with a matching validator
+    let mut request = token.get_request(url);
+    request.header(header::RANGE, "bytes=20-");
+    request.header(header::IF_RANGE, etag.to_str().unwrap());
+    let response = token.run::<()>(request);
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.into_bytes(), full.slice(20..));
+
+    // The full file is returned if it changed in the meantime
+    let mut request = token.get_request(url);
+    request.header(header::RANGE, "bytes=20-");
+    request.header(header::IF_RANGE, "\"outdated\"");
+    let response = token.run::<()>(request);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.into_bytes(), full);
+
+    let mut request = token.get_request(url);
+    request.header(header::RANGE, &format!("bytes={}-", full.len()));
+    let response = token.run::<()>(request);
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(
+        response.headers()[header::CONTENT_RANGE],
+        format!("bytes */{}", full.len())
+    );
+
+    let mut request = token.get_request(url);
+    request.header(header::IF_NONE_MATCH, etag.to_str().unwrap());
+    let response = token.run::<()>(request);
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+}
@@ -1,5 +1,7 @@
 mod authors;
 pub mod dependencies;
+mod dependency_graph;
 pub mod download;
+mod promote;
 mod read;
 pub mod yank_unyank;
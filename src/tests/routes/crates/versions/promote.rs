@@ -0,0 +1,109 @@
+use crate::builders::{CrateBuilder, PublishBuilder};
+use crate::util::{RequestHelper, TestApp};
+use crates_io::background_jobs::Job;
+use crates_io::schema::versions;
+use crates_io::views::GoodCrate;
+use diesel::prelude::*;
+use http::StatusCode;
+
+const STAGED_PUBLISH_URL: &str = "/api/v1/crates/new?staged=true";
+
+#[test]
+fn staged_version_is_only_available_to_owners() {
+    let (app, anon, _, token) = TestApp::full().with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo_staged").version("1.0.0");
+    token
+        .put::<GoodCrate>(STAGED_PUBLISH_URL, &crate_to_publish.body())
+        .good();
+    app.run_pending_background_jobs();
+
+    let expected_files = vec!["staged-crates/foo_staged/foo_staged-1.0.0.crate"];
+    assert_eq!(app.stored_files(), expected_files);
+
+    let json = anon
+        .get::<()>("/api/v1/crates/foo_staged/versions")
+        .into_json();
+    assert_eq!(json["versions"], json!([]));
+
+    let url = "/api/v1/crates/foo_staged/1.0.0/download";
+    anon.get::<()>(url).assert_forbidden();
+
+    let response = token.get::<()>(url);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/gzip");
+
+    // Simulate the end of the soak period
+    app.db(|conn| {
+        diesel::update(versions::table)
+            .set(versions::staged_until.eq(diesel::dsl::now))
+            .execute(conn)
+            .unwrap();
+
+        Job::promote_staged_versions().enqueue(conn).unwrap();
+    });
+    app.run_pending_background_jobs();
+
+    let expected_files = vec![
+        "crates/foo_staged/foo_staged-1.0.0.crate",
+        "index/fo/o_/foo_staged",
+    ];
+    assert_eq!(app.stored_files(), expected_files);
+
+    let crates = app.crates_from_index_head("foo_staged");
+    assert_eq!(crates.len(), 1);
+    assert_eq!(crates[0].vers, "1.0.0");
+
+    assert_eq!(anon.get::<()>(url).status(), StatusCode::FOUND);
+}
+
+#[test]
+fn promote() {
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo_staged").version("1.0.0");
+    token.publish_crate(crate_to_publish).good();
+
+    let crate_to_publish = PublishBuilder::new("foo_staged").version("1.1.0");
+    token
+        .put::<GoodCrate>(STAGED_PUBLISH_URL, &crate_to_publish.body())
+        .good();
+    app.run_pending_background_jobs();
+
+    let crates = app.crates_from_index_head("foo_staged");
+    assert_eq!(crates.len(), 1);
+
+    let response = token.put::<()>("/api/v1/crates/foo_staged/1.1.0/promote", &[]);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.into_json(), json!({ "ok": true }));
+    app.run_pending_background_jobs();
+
+    let crates = app.crates_from_index_head("foo_staged");
+    assert_eq!(crates.len(), 2);
+    assert_eq!(crates[1].vers, "1.1.0");
+
+    // The version is not staged anymore
+    let response = token.put::<()>("/api/v1/crates/foo_staged/1.1.0/promote", &[]);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "crate `foo_staged` does not have a staged version `1.1.0`" }] })
+    );
+}
+
+#[test]
+fn promote_by_a_non_owner_fails() {
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    let another_user = app.db_new_user("bar");
+    app.db(|conn| {
+        CrateBuilder::new("foo_not", another_user.as_model().id)
+            .version("1.0.0")
+            .expect_build(conn);
+    });
+
+    let response = token.put::<()>("/api/v1/crates/foo_not/1.0.0/promote", &[]);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "must already be an owner to promote a staged version" }] })
+    );
+}
@@ -0,0 +1,100 @@
+use crate::util::{RequestHelper, TestApp};
+use chrono::{Duration, Utc};
+use crates_io::schema::background_jobs;
+use diesel::prelude::*;
+use http::StatusCode;
+
+#[test]
+fn liveness() {
+    let (_, anon) = TestApp::init().empty();
+
+    let response = anon.get::<()>("/healthz");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.into_json(), json!({ "ok": true }));
+}
+
+#[test]
+fn readiness() {
+    let (_, anon) = TestApp::init().empty();
+
+    let response = anon.get::<()>("/readyz");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_json(),
+        json!({
+            "ok": true,
+            "checks": {
+                "database": { "ok": true },
+                "storage": { "ok": true },
+                "background_jobs": { "ok": true },
+            },
+        })
+    );
+}
+
+#[test]
+fn readiness_with_stalled_background_jobs() {
+    let (app, anon) = TestApp::init().empty();
+
+    app.db(|conn| {
+        diesel::insert_into(background_jobs::table)
+            .values((
+                background_jobs::job_type.eq("update_downloads"),
+                background_jobs::data.eq(serde_json::Value::Null),
+                background_jobs::created_at.eq((Utc::now() - Duration::hours(1)).naive_utc()),
+            ))
+            .execute(conn)
+            .unwrap();
+    });
+
+    let response = anon.get::<()>("/readyz");
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let json = response.into_json();
+    assert_eq!(json["ok"], json!(false));
+    assert_eq!(json["checks"]["database"]["ok"], json!(true));
+    assert_eq!(json["checks"]["background_jobs"], json!({ "ok": false }));
+
+    // Low priority jobs are not taken into account
+    app.db(|conn| {
+        diesel::update(background_jobs::table)
+            .set(background_jobs::priority.eq(-1))
+            .execute(conn)
+            .unwrap();
+    });
+
+    let response = anon.get::<()>("/readyz");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    app.db(|conn| {
+        diesel::delete(background_jobs::table)
+            .execute(conn)
+            .unwrap();
+    });
+}
+
+#[test]
+fn circuit_breakers() {
+    let (app, anon) = TestApp::init().empty();
+
+    let response = anon.get::<()>("/api/private/circuit_breakers");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_json(),
+        json!({
+            "circuit_breakers": [
+                { "name": "github", "state": "closed", "consecutive_failures": 0 },
+                { "name": "storage", "state": "closed", "consecutive_failures": 0 },
+                { "name": "email", "state": "closed", "consecutive_failures": 0 },
+            ],
+        })
+    );
+
+    for _ in 0..5 {
+        app.as_inner().circuit_breakers.github.record_failure();
+    }
+
+    let json = anon.get::<()>("/api/private/circuit_breakers").into_json();
+    assert_eq!(json["circuit_breakers"][0]["state"], json!("open"));
+    assert_eq!(json["circuit_breakers"][1]["state"], json!("closed"));
+}
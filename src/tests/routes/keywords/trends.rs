@@ -0,0 +1,63 @@
+use crate::builders::CrateBuilder;
+use crate::util::{RequestHelper, TestApp};
+use chrono::{Duration, Utc};
+use crates_io::background_jobs::Job;
+use crates_io::models::Keyword;
+use crates_io::schema::keyword_stats;
+use diesel::prelude::*;
+
+#[test]
+fn trends() {
+    let url = "/api/v1/keywords/wasm/trends";
+    let (app, anon, user) = TestApp::full().with_user();
+    let user = user.as_model();
+    anon.get::<()>(url).assert_not_found();
+
+    let today = Utc::now().date_naive();
+    app.db(|conn| {
+        CrateBuilder::new("foo_trends", user.id)
+            .keyword("wasm")
+            .version("1.0.0")
+            .recent_downloads(10)
+            .expect_build(conn);
+
+        let keyword = Keyword::find_by_keyword(conn, "wasm").unwrap();
+        diesel::insert_into(keyword_stats::table)
+            .values((
+                keyword_stats::keyword_id.eq(keyword.id),
+                keyword_stats::date.eq(today - Duration::days(2)),
+                keyword_stats::crates_cnt.eq(0),
+                keyword_stats::downloads.eq(3),
+            ))
+            .execute(conn)
+            .unwrap();
+
+        Job::update_keyword_stats().enqueue(conn).unwrap();
+    });
+    app.run_pending_background_jobs();
+
+    let json = anon.get::<()>(url).into_json();
+    assert_eq!(
+        json,
+        json!({ "trends": [
+            {
+                "date": today - Duration::days(2),
+                "crates_cnt": 0,
+                "crates_added": null,
+                "downloads": 3,
+            },
+            {
+                "date": today - Duration::days(1),
+                "crates_cnt": 1,
+                "crates_added": 1,
+                "downloads": 0,
+            },
+            {
+                "date": today,
+                "crates_cnt": 1,
+                "crates_added": 0,
+                "downloads": 10,
+            },
+        ] })
+    );
+}
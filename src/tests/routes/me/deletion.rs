@@ -0,0 +1,191 @@
+use crate::builders::{CrateBuilder, PublishBuilder};
+use crate::util::{MockCookieUser, RequestHelper, TestApp};
+use crate::OkBool;
+use chrono::NaiveDateTime;
+use crates_io::background_jobs::Job;
+use crates_io::models::ApiToken;
+use crates_io::schema::{
+    account_deletions, api_tokens, crate_owners, emails, follows, users, versions_published_by,
+};
+use diesel::prelude::*;
+use http::StatusCode;
+use serde_json::Value;
+
+const URL: &str = "/api/v1/me/deletion";
+
+fn confirmation_token(app: &TestApp) -> String {
+    let emails = app.as_inner().emails.mails_in_memory().unwrap();
+    let email = emails.last().unwrap();
+    assert_eq!(email.subject, "Please confirm the deletion of your account");
+
+    let (_, token) = email.body.split_once("/me/delete/").unwrap();
+    token.split_whitespace().next().unwrap().to_string()
+}
+
+fn confirm(user: &MockCookieUser, token: &str) -> Value {
+    user.put::<Value>(&format!("{URL}/{token}"), b"").good()
+}
+
+#[test]
+fn anonymous_user_unauthorized() {
+    let (_, anon) = TestApp::init().empty();
+    anon.delete::<()>("/api/v1/me").assert_forbidden();
+    anon.get::<()>(URL).assert_forbidden();
+    anon.delete::<()>(URL).assert_forbidden();
+}
+
+#[test]
+fn verified_email_required() {
+    let (app, _, user) = TestApp::init().with_user();
+    app.db(|conn| {
+        diesel::update(emails::table.filter(emails::user_id.eq(user.as_model().id)))
+            .set(emails::verified.eq(false))
+            .execute(conn)
+            .unwrap();
+    });
+
+    let response = user.delete::<()>("/api/v1/me");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "A verified email address is required to delete the account." }] })
+    );
+    assert_eq!(app.as_inner().emails.mails_in_memory().unwrap().len(), 0);
+}
+
+#[test]
+fn request_confirm_and_cancel() {
+    let (app, _, user) = TestApp::full().with_user();
+
+    let json = user.get::<Value>(URL).good();
+    assert_eq!(json, json!({ "deletion": null }));
+
+    let json = user.delete::<Value>("/api/v1/me").good();
+    assert_eq!(json["deletion"]["status"], "unconfirmed");
+    assert_eq!(json["deletion"]["scheduled_for"], Value::Null);
+    assert_eq!(json["deletion"].get("confirmation_token"), None);
+
+    let token = confirmation_token(&app);
+
+    let response = user.put::<()>(&format!("{URL}/invalid-token"), b"");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let json = confirm(&user, &token);
+    assert_eq!(json["deletion"]["status"], "scheduled");
+    assert!(json["deletion"]["confirmed_at"].is_string());
+    assert!(json["deletion"]["scheduled_for"].is_string());
+
+    // Scheduled deletions cannot be requested or confirmed again
+    let response = user.delete::<()>("/api/v1/me");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = user.put::<()>(&format!("{URL}/{token}"), b"");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // The grace period has not passed yet, so the account is not deleted
+    app.db(|conn| Job::purge_deleted_accounts().enqueue(conn).unwrap());
+    app.run_pending_background_jobs();
+    user.get::<Value>("/api/v1/me").good();
+
+    assert!(user.delete::<OkBool>(URL).good().ok);
+    let json = user.get::<Value>(URL).good();
+    assert_eq!(json, json!({ "deletion": null }));
+    user.delete::<()>(URL).assert_not_found();
+}
+
+#[test]
+fn deleted_account_is_anonymized() {
+    let (app, anon, user, token) = TestApp::full().with_token();
+    let user_id = user.as_model().id;
+    let other = app.db_new_user("other");
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_deleted", user_id).expect_build(conn);
+    });
+    user.put::<Value>("/api/v1/crates/foo_deleted/follow", b"")
+        .good();
+    token
+        .publish_crate(PublishBuilder::new("foo_published"))
+        .good();
+
+    user.delete::<Value>("/api/v1/me").good();
+    confirm(&user, &confirmation_token(&app));
+
+    app.db(|conn| {
+        diesel::update(account_deletions::table.find(user_id))
+            .set(account_deletions::scheduled_for.eq(diesel::dsl::now))
+            .execute(conn)
+            .unwrap();
+
+        Job::purge_deleted_accounts().enqueue(conn).unwrap();
+    });
+    app.run_pending_background_jobs();
+
+    // The sessions and API tokens of the user can no longer be used
+    user.get::<()>("/api/v1/me").assert_forbidden();
+    user.get::<()>(URL).assert_forbidden();
+    token.get::<()>("/api/v1/me/updates").assert_forbidden();
+
+    app.db(|conn| {
+        let completed_at: Option<NaiveDateTime> = account_deletions::table
+            .find(user_id)
+            .select(account_deletions::completed_at)
+            .first(conn)
+            .unwrap();
+        assert!(completed_at.is_some());
+
+        let (login, gh_id, name, avatar): (String, i32, Option<String>, Option<String>) =
+            users::table
+                .find(user_id)
+                .select((users::gh_login, users::gh_id, users::name, users::gh_avatar))
+                .first(conn)
+                .unwrap();
+        assert_eq!(login, format!("deleted-user-{user_id}"));
+        assert_eq!(gh_id, -1);
+        assert_eq!(name, None);
+        assert_eq!(avatar, None);
+
+        let emails: i64 = emails::table
+            .filter(emails::user_id.eq(user_id))
+            .count()
+            .get_result(conn)
+            .unwrap();
+        assert_eq!(emails, 0);
+
+        let publish_emails: i64 = versions_published_by::table
+            .count()
+            .get_result(conn)
+            .unwrap();
+        assert_eq!(publish_emails, 0);
+
+        let follows: i64 = follows::table
+            .filter(follows::user_id.eq(user_id))
+            .count()
+            .get_result(conn)
+            .unwrap();
+        assert_eq!(follows, 0);
+
+        let tokens: Vec<ApiToken> = api_tokens::table
+            .filter(api_tokens::user_id.eq(user_id))
+            .select(ApiToken::as_select())
+            .load(conn)
+            .unwrap();
+        assert!(tokens.iter().all(|token| token.revoked));
+
+        let owned: i64 = crate_owners::table
+            .filter(crate_owners::owner_id.eq(user_id))
+            .filter(crate_owners::deleted.eq(false))
+            .count()
+            .get_result(conn)
+            .unwrap();
+        assert_eq!(owned, 2);
+    });
+
+    // The crate ownership history is kept in anonymized form
+    let json = anon
+        .get::<Value>("/api/v1/crates/foo_deleted/owners")
+        .good();
+    assert_eq!(json["users"][0]["login"], format!("deleted-user-{user_id}"));
+
+    // Other users are not affected
+    other.get::<Value>("/api/v1/me").good();
+}
@@ -0,0 +1,153 @@
+use crate::builders::{CrateBuilder, PublishBuilder};
+use crate::util::{RequestHelper, TestApp};
+use flate2::read::GzDecoder;
+use http::{header, StatusCode};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Read;
+
+const URL: &str = "/api/v1/me/export";
+
+fn read_archive(bytes: &[u8]) -> HashMap<String, Value> {
+    let mut archive = tar::Archive::new(GzDecoder::new(bytes));
+    archive
+        .entries()
+        .unwrap()
+        .map(|entry| {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().to_string_lossy().into_owned();
+            let mut content = String::new();
+            entry.read_to_string(&mut content).unwrap();
+            (path, serde_json::from_str(&content).unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn anonymous_user_unauthorized() {
+    let (_, anon) = TestApp::init().empty();
+    anon.get::<()>(URL).assert_forbidden();
+    anon.get::<()>("/api/v1/me/export/download")
+        .assert_forbidden();
+}
+
+#[test]
+fn export_user_data() {
+    let (app, _, user, token) = TestApp::full().with_token();
+    let user_model = user.as_model();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_followed", user_model.id).expect_build(conn);
+    });
+    token
+        .publish_crate(PublishBuilder::new("foo_export"))
+        .good();
+    user.put::<Value>("/api/v1/crates/foo_followed/follow", b"")
+        .good();
+
+    // Nothing can be downloaded until the export is ready
+    user.get::<()>("/api/v1/me/export/download")
+        .assert_not_found();
+
+    let response = user.get::<()>(URL);
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let json = response.into_json();
+    let export_id = json["export"]["id"].clone();
+    assert_eq!(json["export"]["status"], "pending");
+    assert_eq!(json["export"]["download_path"], Value::Null);
+
+    // Polling returns the pending export instead of requesting a new one
+    let json = user.get::<Value>(URL).into_json();
+    assert_eq!(json["export"]["id"], export_id);
+
+    app.run_pending_background_jobs();
+
+    let response = user.get::<()>(URL);
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    assert_eq!(json["export"]["id"], export_id);
+    assert_eq!(json["export"]["status"], "ready");
+    assert!(json["export"]["completed_at"].is_string());
+    assert_eq!(
+        json["export"]["download_path"],
+        "/api/v1/me/export/download"
+    );
+
+    let expected_path = format!("user-exports/{}/{export_id}.tar.gz", user_model.id);
+    assert!(app.stored_files().contains(&expected_path));
+
+    let response = user.get::<()>("/api/v1/me/export/download");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/gzip");
+    let content_disposition = response.headers()[header::CONTENT_DISPOSITION]
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(content_disposition.starts_with("attachment; filename=\"crates-io-export-"));
+
+    let files = read_archive(&response.into_bytes());
+    let mut paths = files.keys().cloned().collect::<Vec<_>>();
+    paths.sort();
+    assert_eq!(
+        paths,
+        [
+            "crates-io-export/api_tokens.json",
+            "crates-io-export/audit_events.json",
+            "crates-io-export/audit_log.json",
+            "crates-io-export/crate_ownerships.json",
+            "crates-io-export/emails.json",
+            "crates-io-export/follows.json",
+            "crates-io-export/identities.json",
+            "crates-io-export/profile.json",
+            "crates-io-export/publish_emails.json",
+        ]
+    );
+
+    let profile = &files["crates-io-export/profile.json"];
+    assert_eq!(profile["login"], "foo");
+    assert_eq!(profile.get("gh_access_token"), None);
+
+    let emails = &files["crates-io-export/emails.json"];
+    assert_eq!(
+        emails,
+        &json!([{ "email": "something@example.com", "verified": true }])
+    );
+
+    let identities = &files["crates-io-export/identities.json"];
+    assert_eq!(identities[0]["provider"], "github");
+    assert_eq!(identities[0]["login"], "foo");
+
+    let tokens = files["crates-io-export/api_tokens.json"]
+        .as_array()
+        .unwrap();
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0]["name"], "bar");
+    assert_eq!(tokens[0]["revoked"], false);
+    assert_eq!(tokens[0].get("token"), None);
+
+    let events = files["crates-io-export/audit_events.json"]
+        .as_array()
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["crate"], "foo_export");
+    assert_eq!(events[0]["version"], "1.0.0");
+    assert_eq!(events[0]["action"], "publish");
+
+    let publishes = &files["crates-io-export/publish_emails.json"];
+    assert_eq!(
+        publishes,
+        &json!([{ "crate": "foo_export", "version": "1.0.0", "email": "something@example.com" }])
+    );
+
+    let ownerships = files["crates-io-export/crate_ownerships.json"]
+        .as_array()
+        .unwrap();
+    let owned = ownerships
+        .iter()
+        .map(|ownership| ownership["crate"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(owned, ["foo_export", "foo_followed"]);
+
+    let follows = &files["crates-io-export/follows.json"];
+    assert_eq!(follows, &json!(["foo_followed"]));
+}
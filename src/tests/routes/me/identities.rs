@@ -0,0 +1,103 @@
+use crate::builders::CrateBuilder;
+use crate::util::{MockCookieUser, RequestHelper, TestApp};
+use crate::OkBool;
+use crates_io::models::OAuthIdentity;
+use crates_io::schema::users;
+use diesel::prelude::*;
+use http::StatusCode;
+use serde_json::Value;
+
+const URL: &str = "/api/v1/me/identities";
+
+fn link(app: &TestApp, user: &MockCookieUser, provider: &str, id: &str, login: &str) {
+    let user_id = user.as_model().id;
+    app.db(|conn| {
+        OAuthIdentity::link(conn, user_id, provider, id, login).unwrap();
+    });
+}
+
+#[test]
+fn anonymous_user_unauthorized() {
+    let (_, anon) = TestApp::init().empty();
+    anon.get::<()>(URL).assert_forbidden();
+    anon.delete::<()>(&format!("{URL}/github"))
+        .assert_forbidden();
+}
+
+#[test]
+fn list_identities() {
+    let (app, _, user) = TestApp::init().with_user();
+    link(&app, &user, "gitlab", "1234", "jdoe");
+
+    let json = user.get::<Value>(URL).good();
+    assert_eq!(json["providers"], json!(["github"]));
+
+    let identities = json["identities"].as_array().unwrap();
+    assert_eq!(identities.len(), 2);
+    assert_eq!(identities[0]["provider"], "github");
+    assert_eq!(identities[0]["login"], "foo");
+    assert_eq!(identities[1]["provider"], "gitlab");
+    assert_eq!(identities[1]["login"], "jdoe");
+    assert!(identities[1]["created_at"].is_string());
+    assert_eq!(identities[1].get("provider_user_id"), None);
+}
+
+#[test]
+fn last_identity_can_not_be_unlinked() {
+    let (_, _, user) = TestApp::init().with_user();
+
+    user.delete::<()>(&format!("{URL}/gitlab"))
+        .assert_not_found();
+
+    let response = user.delete::<()>(&format!("{URL}/github"));
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let json = user.get::<Value>(URL).good();
+    assert_eq!(json["identities"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn unlink_github() {
+    let (app, _, user) = TestApp::init().with_user();
+    let other_browser = MockCookieUser::new(&app, user.as_model().clone());
+    link(&app, &user, "gitlab", "1234", "jdoe");
+
+    assert!(user.delete::<OkBool>(&format!("{URL}/github")).good().ok);
+
+    // The other sessions of the user are revoked
+    other_browser.get::<()>("/api/v1/me").assert_forbidden();
+
+    let json = user.get::<Value>(URL).good();
+    let identities = json["identities"].as_array().unwrap();
+    assert_eq!(identities.len(), 1);
+    assert_eq!(identities[0]["provider"], "gitlab");
+
+    // The user keeps their account, but without a GitHub login and profile
+    let json = user.get::<Value>("/api/v1/me").good();
+    assert_eq!(json["user"]["login"], "jdoe@gitlab");
+    assert_eq!(json["user"]["url"], Value::Null);
+
+    let user_id = user.as_model().id;
+    app.db(|conn| {
+        let (gh_id, gh_access_token): (i32, String) = users::table
+            .find(user_id)
+            .select((users::gh_id, users::gh_access_token))
+            .first(conn)
+            .unwrap();
+        assert_eq!(gh_id, -1);
+        assert_eq!(gh_access_token, "");
+    });
+
+    // Users without GitHub account can still be added as crate owners
+    let owner = app.db_new_user("bar");
+    app.db(|conn| {
+        CrateBuilder::new("foo_owned", owner.as_model().id).expect_build(conn);
+    });
+    let owner_token = owner.db_new_token("owner");
+    assert!(
+        owner_token
+            .add_named_owner("foo_owned", "jdoe@gitlab")
+            .good()
+            .ok
+    );
+}
@@ -1,4 +1,12 @@
+mod deletion;
 mod email_notifications;
+mod export;
 pub mod get;
+mod identities;
+mod notifications;
+mod passkeys;
+mod publisher_verifications;
+mod sessions;
+mod stats;
 pub mod tokens;
 mod updates;
@@ -0,0 +1,179 @@
+use crate::builders::CrateBuilder;
+use crate::util::{MockCookieUser, MockRequestExt, MockTokenUser, RequestHelper, TestApp};
+use crates_io::background_jobs::Job;
+use http::Method;
+use serde_json::Value;
+
+const URL: &str = "/api/v1/me/notifications";
+
+fn list(user: &MockCookieUser) -> Vec<Value> {
+    let json = user.get::<Value>(URL).good();
+    json["notifications"].as_array().unwrap().clone()
+}
+
+fn security_emails(app: &TestApp) -> Vec<(String, String, String)> {
+    app.as_inner()
+        .emails
+        .mails_in_memory()
+        .unwrap()
+        .into_iter()
+        .filter(|email| {
+            email.subject.starts_with("Security notification")
+                || email.subject.starts_with("Recent activity")
+        })
+        .map(|email| (email.to, email.subject, email.body))
+        .collect()
+}
+
+fn use_token_from(token: &MockTokenUser, ip_address: &str, country: &str) {
+    let mut request = token.request_builder(Method::PUT, "/api/v1/me/email_notifications");
+    request.header("x-real-ip", ip_address);
+    request.header("cloudfront-viewer-country", country);
+    request.with_body(b"[]");
+    token.run::<Value>(request).good();
+}
+
+#[test]
+fn anonymous_user_unauthorized() {
+    let (_, anon) = TestApp::init().empty();
+    anon.get::<()>(URL).assert_forbidden();
+}
+
+#[test]
+fn token_auth_is_not_allowed() {
+    let (_, _, _, token) = TestApp::init().with_token();
+    token.get::<()>(URL).assert_forbidden();
+}
+
+#[test]
+fn token_creation_is_emailed_immediately() {
+    let (app, _, user) = TestApp::init().with_user();
+
+    let body = br#"{ "api_token": { "name": "ci" } }"#;
+    user.put::<Value>("/api/v1/me/tokens", body).good();
+
+    let notifications = list(&user);
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0]["kind"], "token_created");
+    assert_eq!(notifications[0]["severity"], "high");
+    assert_eq!(
+        notifications[0]["message"],
+        "The API token \"ci\" was created"
+    );
+    assert!(notifications[0]["emailed_at"].is_string());
+
+    let emails = security_emails(&app);
+    assert_eq!(emails.len(), 1);
+    assert!(emails[0].2.contains("The API token \"ci\" was created"));
+
+    // Notifications of other users are not listed
+    let other_user = app.db_new_user("other");
+    assert!(list(&other_user).is_empty());
+}
+
+#[test]
+fn token_use_from_new_ip_addresses() {
+    let (app, _, user, token) = TestApp::init().with_job_runner().with_token();
+
+    // The first use of a token is not notified about
+    use_token_from(&token, "192.0.2.1", "DE");
+    assert!(list(&user).is_empty());
+
+    // A new IP address in a known country is included in the digest
+    use_token_from(&token, "192.0.2.2", "DE");
+    use_token_from(&token, "192.0.2.2", "DE");
+
+    // A new country is emailed immediately
+    use_token_from(&token, "198.51.100.1", "XY");
+
+    let notifications = list(&user);
+    assert_eq!(notifications.len(), 2);
+    assert_eq!(notifications[0]["kind"], "token_used_from_new_ip");
+    assert_eq!(notifications[0]["severity"], "high");
+    assert_eq!(notifications[0]["details"]["ip_address"], "198.51.100.1");
+    assert_eq!(notifications[0]["details"]["country"], "XY");
+    assert_eq!(notifications[1]["kind"], "token_used_from_new_ip");
+    assert_eq!(notifications[1]["severity"], "low");
+    assert_eq!(notifications[1]["details"]["ip_address"], "192.0.2.2");
+    assert!(notifications[1]["emailed_at"].is_null());
+
+    let emails = security_emails(&app);
+    assert_eq!(emails.len(), 1);
+    assert!(emails[0].2.contains("198.51.100.1 (XY)"));
+
+    app.db(|conn| {
+        Job::send_security_notification_digests()
+            .enqueue(conn)
+            .unwrap();
+    });
+    app.run_pending_background_jobs();
+
+    let emails = security_emails(&app);
+    assert_eq!(emails.len(), 2);
+    assert!(emails[1].1.starts_with("Recent activity"));
+    assert!(emails[1].2.contains("192.0.2.2 (DE)"));
+    assert!(!emails[1].2.contains("198.51.100.1"));
+
+    // Notifications are only included in a single digest
+    app.db(|conn| {
+        Job::send_security_notification_digests()
+            .enqueue(conn)
+            .unwrap();
+    });
+    app.run_pending_background_jobs();
+    assert_eq!(security_emails(&app).len(), 2);
+}
+
+#[test]
+fn new_owners_are_notified_to_existing_owners() {
+    let (app, _, user, token) = TestApp::init().with_token();
+
+    let krate =
+        app.db(|conn| CrateBuilder::new("foo_notified", user.as_model().id).expect_build(conn));
+
+    let new_owner = app.db_new_user("bar");
+    token.add_named_owner("foo_notified", "bar").good();
+
+    let body = json!({
+        "crate_owner_invite": {
+            "invited_by_username": "",
+            "crate_name": "foo_notified",
+            "crate_id": krate.id,
+            "created_at": "",
+            "accepted": true
+        }
+    });
+    let url = format!("/api/v1/me/crate_owner_invitations/{}", krate.id);
+    new_owner
+        .put::<Value>(&url, body.to_string().as_bytes())
+        .good();
+
+    let notifications = list(&user);
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0]["kind"], "owner_added");
+    assert_eq!(notifications[0]["severity"], "low");
+    assert_eq!(
+        notifications[0]["message"],
+        "bar was added as an owner of the crate foo_notified"
+    );
+
+    // The new owner is not notified about their own addition
+    assert!(list(&new_owner).is_empty());
+}
+
+#[test]
+fn email_changes_are_sent_to_the_previous_address() {
+    let (app, _, user) = TestApp::init().with_user();
+
+    user.update_email("attacker@example.com");
+
+    let notifications = list(&user);
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0]["kind"], "email_changed");
+    assert_eq!(notifications[0]["severity"], "high");
+
+    let emails = security_emails(&app);
+    assert_eq!(emails.len(), 1);
+    assert_eq!(emails[0].0, "something@example.com");
+    assert!(emails[0].2.contains("changed to attacker@example.com"));
+}
@@ -0,0 +1,181 @@
+use crate::util::{
+    MockAnonymousUser, MockCookieUser, MockRequestExt, RequestHelper, Response, TestApp,
+};
+use crates_io::config::LocalAuthConfig;
+use crates_io::schema::webauthn_ceremonies;
+use diesel::prelude::*;
+use http::{header, Method, StatusCode};
+use serde_json::Value;
+use url::Url;
+
+const URL: &str = "/api/v1/me/passkeys";
+
+fn init() -> (TestApp, MockAnonymousUser, MockCookieUser) {
+    TestApp::init()
+        .with_config(|config| {
+            config.local_auth = Some(LocalAuthConfig {
+                webauthn_rp_id: "crates.io".to_string(),
+                webauthn_origin: Url::parse("https://crates.io").unwrap(),
+            })
+        })
+        .with_user()
+}
+
+#[test]
+fn disabled_by_default() {
+    let (_, anon, user) = TestApp::init().with_user();
+    user.get::<()>(URL).assert_not_found();
+    user.post::<()>(&format!("{URL}/challenge"), b"")
+        .assert_not_found();
+    anon.post::<()>(
+        "/api/private/session/passkey/begin",
+        br#"{"username":"foo"}"#,
+    )
+    .assert_not_found();
+}
+
+#[test]
+fn anonymous_user_unauthorized() {
+    let (_, anon, _) = init();
+    anon.get::<()>(URL).assert_forbidden();
+    anon.post::<()>(&format!("{URL}/challenge"), b"")
+        .assert_forbidden();
+    anon.delete::<()>(&format!("{URL}/1")).assert_forbidden();
+}
+
+#[test]
+fn registration_challenge() {
+    let (_, _, user) = init();
+
+    let json = user.get::<Value>(URL).good();
+    assert_eq!(json, json!({ "passkeys": [] }));
+
+    let json = user.post::<Value>(&format!("{URL}/challenge"), b"").good();
+    let options = &json["publicKey"];
+    assert_eq!(options["rp"]["id"], "crates.io");
+    assert_eq!(options["user"]["name"], "foo");
+    assert!(options["challenge"].is_string());
+}
+
+#[test]
+fn registration_requires_challenge() {
+    let (_, _, user) = init();
+
+    let body = json!({
+        "name": "laptop",
+        "credential": {
+            "id": "AAAA",
+            "rawId": "AAAA",
+            "type": "public-key",
+            "response": { "attestationObject": "AAAA", "clientDataJSON": "AAAA" },
+        },
+    });
+    let response = user.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "no passkey registration in progress" }] })
+    );
+
+    user.delete::<()>(&format!("{URL}/1")).assert_not_found();
+}
+
+const BEGIN_LOGIN_URL: &str = "/api/private/session/passkey/begin";
+const FINISH_LOGIN_URL: &str = "/api/private/session/passkey/finish";
+
+/// Returns the session cookie that was set by the response, so that it can be
+/// sent with the next request.
+fn session_cookie<T>(response: &Response<T>) -> String {
+    let set_cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+    set_cookie.split(';').next().unwrap().to_string()
+}
+
+#[test]
+fn ceremony_state_is_not_stored_in_the_cookie() {
+    let (app, _, user) = init();
+
+    let response = user.post::<Value>(&format!("{URL}/challenge"), b"");
+    let cookie = session_cookie(&response);
+    let json = response.good();
+    let challenge = json["publicKey"]["challenge"].as_str().unwrap();
+    assert!(!cookie.contains(challenge));
+
+    app.db(|conn| {
+        let kinds: Vec<String> = webauthn_ceremonies::table
+            .select(webauthn_ceremonies::kind)
+            .load(conn)
+            .unwrap();
+        assert_eq!(kinds, vec!["registration"]);
+    });
+}
+
+#[test]
+fn login_without_passkeys() {
+    let (app, anon, _) = init();
+
+    // Users without passkeys get the same kind of response as unknown users
+    let begin_login = |username: &str| {
+        let body = json!({ "username": username });
+        let response = anon.post::<Value>(BEGIN_LOGIN_URL, body.to_string().as_bytes());
+        let cookie = session_cookie(&response);
+        (response.good(), cookie)
+    };
+
+    let (known, cookie) = begin_login("foo");
+    let (unknown, _) = begin_login("unknown");
+    let (known_again, _) = begin_login("FOO");
+
+    let options = &known["publicKey"];
+    assert_eq!(options["rpId"], "crates.io");
+    assert!(options["challenge"].is_string());
+    assert_eq!(options["allowCredentials"][0]["type"], "public-key");
+    assert_ne!(options["challenge"], known_again["publicKey"]["challenge"]);
+    assert_eq!(
+        options["allowCredentials"],
+        known_again["publicKey"]["allowCredentials"]
+    );
+    assert_ne!(
+        options["allowCredentials"],
+        unknown["publicKey"]["allowCredentials"]
+    );
+
+    // Signing in with these options always fails
+    let body = json!({
+        "credential": {
+            "id": "AAAA",
+            "rawId": "AAAA",
+            "type": "public-key",
+            "response": {
+                "authenticatorData": "AAAA",
+                "clientDataJSON": "AAAA",
+                "signature": "AAAA",
+                "userHandle": null,
+            },
+            "extensions": {},
+        },
+    });
+    let mut request = anon.request_builder(Method::POST, FINISH_LOGIN_URL);
+    request.header(header::COOKIE, &cookie);
+    request.with_body(body.to_string().as_bytes());
+    let response = anon.run::<()>(request);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "invalid passkey" }] })
+    );
+
+    // Each ceremony can only be finished once
+    let mut request = anon.request_builder(Method::POST, FINISH_LOGIN_URL);
+    request.header(header::COOKIE, &cookie);
+    request.with_body(body.to_string().as_bytes());
+    let response = anon.run::<()>(request);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "no passkey sign in in progress" }] })
+    );
+
+    app.db(|conn| {
+        let count: i64 = webauthn_ceremonies::table.count().get_result(conn).unwrap();
+        assert_eq!(count, 2);
+    });
+}
@@ -0,0 +1,190 @@
+use crate::builders::CrateBuilder;
+use crate::util::{MockCookieUser, RequestHelper, TestApp};
+use crate::OkBool;
+use crates_io::schema::publisher_verifications;
+use diesel::prelude::*;
+use http::StatusCode;
+use serde_json::Value;
+
+const URL: &str = "/api/v1/me/publisher_verifications";
+
+fn create(user: &MockCookieUser, body: Value) -> Value {
+    user.post::<Value>(URL, body.to_string().as_bytes()).good()
+}
+
+fn set_verification_token(app: &TestApp, id: &Value) {
+    let id = id.as_i64().unwrap() as i32;
+    app.db(|conn| {
+        diesel::update(publisher_verifications::table.find(id))
+            .set(publisher_verifications::verification_token.eq("mock-verification-token"))
+            .execute(conn)
+            .unwrap();
+    });
+}
+
+#[test]
+fn github_org() {
+    let (app, anon) = TestApp::init().empty();
+    let owner = app.db_new_user("user-org-owner");
+    let member = app.db_new_user("user-one-team");
+
+    let json = create(
+        &owner,
+        json!({ "method": "github", "github_org": "test-org" }),
+    );
+    let verification = &json["publisher_verification"];
+    assert_eq!(verification["method"], "github");
+    assert_eq!(verification["github_org"], "test-org");
+    assert!(verification["verified_at"].is_string());
+    assert_eq!(verification["instructions"], json!(null));
+
+    let body = json!({ "method": "github", "github_org": "test-org" });
+    let response = owner.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "a verification for this domain or GitHub organization already exists" }] })
+    );
+
+    let response = member.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "only admins of the GitHub organization can verify it" }] })
+    );
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_verified", owner.as_model().id).expect_build(conn);
+        CrateBuilder::new("foo_unverified", member.as_model().id).expect_build(conn);
+    });
+
+    let json = anon.get::<()>("/api/v1/crates/foo_verified").into_json();
+    assert_eq!(
+        json["crate"]["verified_publisher"],
+        json!({ "domains": [], "github_orgs": ["test-org"] })
+    );
+
+    let json = anon.get::<()>("/api/v1/crates/foo_unverified").into_json();
+    assert_eq!(json["crate"].get("verified_publisher"), None);
+
+    let json = anon.get::<()>("/api/v1/crates?q=foo").into_json();
+    let crates = json["crates"].as_array().unwrap();
+    assert_eq!(crates[0]["name"], "foo_unverified");
+    assert_eq!(crates[0].get("verified_publisher"), None);
+    assert_eq!(crates[1]["name"], "foo_verified");
+    assert_eq!(
+        crates[1]["verified_publisher"]["github_orgs"],
+        json!(["test-org"])
+    );
+}
+
+#[test]
+fn dns() {
+    let (app, anon) = TestApp::init().empty();
+    let user = app.db_new_user("foo");
+
+    let json = create(&user, json!({ "method": "dns", "domain": "ACME.example" }));
+    let verification = &json["publisher_verification"];
+    assert_eq!(verification["domain"], "acme.example");
+    assert_eq!(verification["verified_at"], json!(null));
+    assert_eq!(verification["instructions"]["type"], "TXT");
+    assert_eq!(
+        verification["instructions"]["name"],
+        "_crates-io-verification.acme.example"
+    );
+
+    let url = format!("{URL}/{}/verify", verification["id"]);
+    let response = user.put::<()>(&url, b"");
+    assert_eq!(response.status(), StatusCode::OK);
+    let detail = response.into_json()["errors"][0]["detail"].clone();
+    assert!(detail
+        .as_str()
+        .unwrap()
+        .starts_with("could not find the verification value"));
+
+    set_verification_token(&app, &verification["id"]);
+    let json = user.put::<Value>(&url, b"").good();
+    assert!(json["publisher_verification"]["verified_at"].is_string());
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_dns", user.as_model().id).expect_build(conn);
+    });
+
+    let json = anon.get::<()>("/api/v1/crates/foo_dns").into_json();
+    assert_eq!(
+        json["crate"]["verified_publisher"],
+        json!({ "domains": ["acme.example"], "github_orgs": [] })
+    );
+}
+
+#[test]
+fn well_known() {
+    let (app, _) = TestApp::init().empty();
+    let user = app.db_new_user("foo");
+
+    let json = create(
+        &user,
+        json!({ "method": "well_known", "domain": "acme-web.example" }),
+    );
+    let verification = &json["publisher_verification"];
+    assert_eq!(
+        verification["instructions"]["url"],
+        "https://acme-web.example/.well-known/crates-io-verification.txt"
+    );
+
+    set_verification_token(&app, &verification["id"]);
+    let url = format!("{URL}/{}/verify", verification["id"]);
+    let json = user.put::<Value>(&url, b"").good();
+    assert!(json["publisher_verification"]["verified_at"].is_string());
+}
+
+#[test]
+fn list_and_delete() {
+    let (app, _, user) = TestApp::init().with_user();
+    let other = app.db_new_user("other");
+
+    let json = create(&user, json!({ "method": "dns", "domain": "acme.example" }));
+    let id = &json["publisher_verification"]["id"];
+
+    let json = user.get::<()>(URL).into_json();
+    assert_eq!(json["publisher_verifications"].as_array().unwrap().len(), 1);
+
+    let json = other.get::<()>(URL).into_json();
+    assert_eq!(json, json!({ "publisher_verifications": [] }));
+
+    let url = format!("{URL}/{id}");
+    other.delete::<()>(&url).assert_not_found();
+    other
+        .put::<()>(&format!("{url}/verify"), b"")
+        .assert_not_found();
+
+    assert!(user.delete::<OkBool>(&url).good().ok);
+
+    let json = user.get::<()>(URL).into_json();
+    assert_eq!(json, json!({ "publisher_verifications": [] }));
+}
+
+#[test]
+fn invalid_requests() {
+    let (_, anon, user) = TestApp::init().with_user();
+
+    let body = json!({ "method": "dns", "domain": "localhost" });
+    let response = user.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "invalid domain `localhost`" }] })
+    );
+
+    let body = json!({ "method": "github" });
+    let response = user.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "a GitHub organization is required" }] })
+    );
+
+    let body = json!({ "method": "email", "domain": "acme.example" });
+    let response = user.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = json!({ "method": "dns", "domain": "acme.example" });
+    let response = anon.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
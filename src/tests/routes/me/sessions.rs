@@ -0,0 +1,123 @@
+use crate::util::{encode_session_header, MockCookieUser, MockRequestExt, RequestHelper, TestApp};
+use crate::OkBool;
+use crates_io::models::UserSession;
+use http::{header, Method, StatusCode};
+use serde_json::Value;
+
+const URL: &str = "/api/v1/me/sessions";
+
+fn list(user: &MockCookieUser) -> Vec<Value> {
+    let json = user.get::<Value>(URL).good();
+    json["sessions"].as_array().unwrap().clone()
+}
+
+#[test]
+fn anonymous_user_unauthorized() {
+    let (_, anon) = TestApp::init().empty();
+    anon.get::<()>(URL).assert_forbidden();
+    anon.delete::<()>(URL).assert_forbidden();
+    anon.delete::<()>(&format!("{URL}/1")).assert_forbidden();
+}
+
+#[test]
+fn token_auth_is_not_allowed() {
+    let (_, _, _, token) = TestApp::init().with_token();
+    token.get::<()>(URL).assert_forbidden();
+    token.delete::<()>(URL).assert_forbidden();
+}
+
+#[test]
+fn list_sessions() {
+    let (app, _, user) = TestApp::init().with_user();
+
+    app.db(|conn| {
+        let user_id = user.as_model().id;
+        UserSession::create(conn, user_id, "Mozilla/5.0", Some("127.0.0.1")).unwrap();
+    });
+
+    let sessions = list(&user);
+    assert_eq!(sessions.len(), 2);
+
+    let current = sessions.iter().find(|s| s["current"] == true).unwrap();
+    let other = sessions.iter().find(|s| s["current"] == false).unwrap();
+    assert_ne!(current["id"], other["id"]);
+    assert_eq!(other["user_agent"], "Mozilla/5.0");
+    assert_eq!(other["ip_address"], "127.0.0.1");
+    assert!(other["created_at"].is_string());
+    assert!(other["last_seen_at"].is_string());
+    assert_eq!(other.get("hashed_token"), None);
+
+    // Sessions of other users are not listed
+    let other_user = app.db_new_user("other");
+    assert_eq!(list(&other_user).len(), 1);
+}
+
+#[test]
+fn revoke_session() {
+    let (app, _, user) = TestApp::init().with_user();
+    let other_browser = MockCookieUser::new(&app, user.as_model().clone());
+    let other_user = app.db_new_user("other");
+
+    let sessions = list(&user);
+    let other_id = sessions.iter().find(|s| s["current"] == false).unwrap()["id"]
+        .as_i64()
+        .unwrap();
+
+    // Sessions of other users cannot be revoked
+    other_user
+        .delete::<()>(&format!("{URL}/{other_id}"))
+        .assert_not_found();
+    other_browser.get::<Value>("/api/v1/me").good();
+
+    assert!(
+        user.delete::<OkBool>(&format!("{URL}/{other_id}"))
+            .good()
+            .ok
+    );
+    other_browser.get::<()>("/api/v1/me").assert_forbidden();
+    user.get::<Value>("/api/v1/me").good();
+
+    user.delete::<()>(&format!("{URL}/{other_id}"))
+        .assert_not_found();
+    assert_eq!(list(&user).len(), 1);
+}
+
+#[test]
+fn revoke_other_sessions() {
+    let (app, _, user) = TestApp::init().with_user();
+    let first = MockCookieUser::new(&app, user.as_model().clone());
+    let second = MockCookieUser::new(&app, user.as_model().clone());
+    let other_user = app.db_new_user("other");
+
+    let json = user.delete::<Value>(URL).good();
+    assert_eq!(json, json!({ "revoked": 2 }));
+
+    first.get::<()>("/api/v1/me").assert_forbidden();
+    second.get::<()>("/api/v1/me").assert_forbidden();
+    user.get::<Value>("/api/v1/me").good();
+    other_user.get::<Value>("/api/v1/me").good();
+
+    let sessions = list(&user);
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0]["current"], true);
+}
+
+#[test]
+fn logout_revokes_session() {
+    let (_, _, user) = TestApp::init().with_user();
+
+    user.delete::<Value>("/api/private/session").good();
+    user.get::<()>("/api/v1/me").assert_forbidden();
+}
+
+#[test]
+fn cookie_without_session_is_rejected() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let session_key = app.as_inner().session_key();
+
+    let cookie = encode_session_header(session_key, user.as_model().id, "cio-unknown-session");
+    let mut request = anon.request_builder(Method::GET, "/api/v1/me");
+    request.header(header::COOKIE, &cookie);
+    let response = anon.run::<()>(request);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
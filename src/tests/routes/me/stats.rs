@@ -0,0 +1,87 @@
+use crate::builders::{CrateBuilder, PublishBuilder, VersionBuilder};
+use crate::util::{RequestHelper, TestApp};
+use chrono::{Duration, Utc};
+use crates_io::models::Crate;
+use crates_io::schema::{crate_owner_invitations, version_downloads, versions};
+use diesel::prelude::*;
+
+#[test]
+fn api_token_cannot_get_user_stats() {
+    let (_, _, _, token) = TestApp::init().with_token();
+    token.get::<()>("/api/v1/me/stats").assert_forbidden();
+}
+
+#[test]
+fn stats() {
+    let (app, _, user, token) = TestApp::full().with_token();
+    let user_model = user.as_model();
+
+    token
+        .publish_crate(PublishBuilder::new("foo_stats").version("1.0.0"))
+        .good();
+
+    let other_user = app.db_new_user("other");
+    let other_user = other_user.as_model();
+
+    app.db(|conn| {
+        let krate: Crate = Crate::by_name("foo_stats").first(conn).unwrap();
+        let version_id: i32 = versions::table
+            .filter(versions::crate_id.eq(krate.id))
+            .select(versions::id)
+            .first(conn)
+            .unwrap();
+
+        let today = Utc::now().date_naive();
+        diesel::insert_into(version_downloads::table)
+            .values(&vec![
+                (
+                    version_downloads::version_id.eq(version_id),
+                    version_downloads::downloads.eq(10),
+                    version_downloads::date.eq(today),
+                ),
+                (
+                    version_downloads::version_id.eq(version_id),
+                    version_downloads::downloads.eq(5),
+                    version_downloads::date.eq(today - Duration::days(20)),
+                ),
+            ])
+            .execute(conn)
+            .unwrap();
+
+        CrateBuilder::new("bar_stats", other_user.id)
+            .version(VersionBuilder::new("1.0.0").dependency(&krate, None))
+            .expect_build(conn);
+
+        let other_crate = CrateBuilder::new("baz_stats", other_user.id).expect_build(conn);
+        diesel::insert_into(crate_owner_invitations::table)
+            .values((
+                crate_owner_invitations::invited_user_id.eq(user_model.id),
+                crate_owner_invitations::invited_by_user_id.eq(other_user.id),
+                crate_owner_invitations::crate_id.eq(other_crate.id),
+            ))
+            .execute(conn)
+            .unwrap();
+    });
+
+    let json = user.get::<()>("/api/v1/me/stats").into_json();
+
+    let crates = json["crates"].as_array().unwrap();
+    assert_eq!(crates.len(), 1);
+    assert_eq!(crates[0]["name"], "foo_stats");
+    assert_eq!(crates[0]["recent_downloads"]["7_days"], 10);
+    assert_eq!(crates[0]["recent_downloads"]["30_days"], 15);
+    assert_eq!(crates[0]["recent_downloads"]["90_days"], 15);
+    assert_eq!(crates[0]["dependents"], 1);
+
+    let invitations = json["crate_owner_invitations"].as_array().unwrap();
+    assert_eq!(invitations.len(), 1);
+    assert_eq!(invitations[0]["crate_name"], "baz_stats");
+    assert_eq!(invitations[0]["invited_by_username"], "other");
+
+    let events = json["events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["crate"], "foo_stats");
+    assert_eq!(events[0]["version"], "1.0.0");
+    assert_eq!(events[0]["action"], "publish");
+    assert_eq!(events[0]["user"], user_model.gh_login);
+}
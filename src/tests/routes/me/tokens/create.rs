@@ -255,3 +255,55 @@ fn create_token_with_expiry_date() {
         ".api_token.token" => insta::api_token_redaction(),
     });
 }
+
+#[test]
+fn create_token_with_allowed_cidrs() {
+    let (app, _, user) = TestApp::init().with_user();
+
+    let json = json!({
+        "api_token": {
+            "name": "bar",
+            "allowed_cidrs": ["192.0.2.1/24", "2001:db8::/32"],
+        }
+    });
+
+    let response = user.put::<Value>("/api/v1/me/tokens", &serde_json::to_vec(&json).unwrap());
+    let json = response.good();
+    assert_eq!(
+        json["api_token"]["allowed_cidrs"],
+        json!(["192.0.2.0/24", "2001:db8::/32"])
+    );
+
+    let tokens: Vec<ApiToken> = app.db(|conn| {
+        assert_ok!(ApiToken::belonging_to(user.as_model())
+            .select(ApiToken::as_select())
+            .load(conn))
+    });
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(
+        tokens[0].allowed_cidrs,
+        Some(vec![
+            "192.0.2.0/24".parse().unwrap(),
+            "2001:db8::/32".parse().unwrap()
+        ])
+    );
+}
+
+#[test]
+fn create_token_with_invalid_cidr() {
+    let (_, _, user) = TestApp::init().with_user();
+
+    let json = json!({
+        "api_token": {
+            "name": "bar",
+            "allowed_cidrs": ["192.0.2.0/24", "not-a-network"],
+        }
+    });
+
+    let response = user.put::<()>("/api/v1/me/tokens", &serde_json::to_vec(&json).unwrap());
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "invalid CIDR block: not-a-network" }] })
+    );
+}
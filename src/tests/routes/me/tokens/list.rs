@@ -43,7 +43,8 @@ fn list_tokens() {
                     CrateScope::try_from("serde-*").unwrap()
                 ]),
                 Some(vec![EndpointScope::PublishUpdate]),
-                None
+                None,
+                None,
             )),
             assert_ok!(ApiToken::insert_with_scopes(
                 conn,
@@ -52,6 +53,7 @@ fn list_tokens() {
                 None,
                 None,
                 Some((Utc::now() - Duration::days(1)).naive_utc()),
+                None,
             )),
         ]
     });
@@ -87,6 +89,7 @@ fn list_recently_expired_tokens() {
                 ]),
                 Some(vec![EndpointScope::PublishUpdate]),
                 Some((Utc::now() - Duration::days(31)).naive_utc()),
+                None,
             )),
             assert_ok!(ApiToken::insert_with_scopes(
                 conn,
@@ -95,6 +98,7 @@ fn list_recently_expired_tokens() {
                 None,
                 None,
                 Some((Utc::now() - Duration::days(1)).naive_utc()),
+                None,
             )),
         ]
     });
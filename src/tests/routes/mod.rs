@@ -11,13 +11,19 @@
 //! - testing output serialization of a route
 //! - testing query parameter combinations of a route
 
+pub mod admin;
 pub mod categories;
 pub mod category_slugs;
 pub mod crates;
+pub mod health;
 pub mod keywords;
 pub mod me;
 pub mod metrics;
+pub mod namespace_claims;
+pub mod render_readme;
+pub mod resolve;
 pub mod session;
 pub mod summary;
+pub mod takedown_requests;
 pub mod users;
 pub mod versions;
@@ -0,0 +1,214 @@
+use crate::builders::CrateBuilder;
+use crate::util::{RequestHelper, TestApp};
+use http::StatusCode;
+
+const URL: &str = "/api/v1/namespace_claims";
+
+#[test]
+fn github_claim_is_verified_immediately() {
+    let (app, _) = TestApp::init().empty();
+    let owner = app.db_new_user("user-org-owner");
+
+    let body = json!({
+        "prefix": "acme-*",
+        "github_org": "test-org",
+        "verification_method": "github",
+    });
+    let response = owner.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let json = response.into_json();
+    assert_eq!(json["namespace_claim"]["pattern"], "acme-*");
+    assert_eq!(json["namespace_claim"]["github_org"], "test-org");
+    assert_eq!(json["namespace_claim"]["verification_method"], "github");
+    assert!(json["namespace_claim"]["verified_at"].is_string());
+    assert_eq!(json["dns_record"], json!(null));
+
+    // a verified prefix can't be claimed again, even with a different spelling
+    let body = json!({
+        "prefix": "ACME_",
+        "github_org": "test-org",
+        "verification_method": "github",
+    });
+    let response = owner.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "the namespace `acme-*` has already been claimed by the `test-org` GitHub organization" }] })
+    );
+}
+
+#[test]
+fn overlapping_claims_are_rejected() {
+    let (app, _) = TestApp::init().empty();
+    let owner = app.db_new_user("user-org-owner");
+
+    let claim = |prefix: &str| {
+        let body = json!({
+            "prefix": prefix,
+            "github_org": "test-org",
+            "verification_method": "github",
+        });
+        owner.post::<()>(URL, body.to_string().as_bytes())
+    };
+
+    assert_eq!(claim("acme-tools").status(), StatusCode::OK);
+
+    // a claim covering an existing claim
+    assert_eq!(
+        claim("acme").into_json(),
+        json!({ "errors": [{ "detail": "the namespace `acme-tools-*` has already been claimed by the `test-org` GitHub organization" }] })
+    );
+
+    // a claim within an existing claim
+    assert_eq!(
+        claim("acme_tools-extra").into_json(),
+        json!({ "errors": [{ "detail": "the namespace `acme-tools-*` has already been claimed by the `test-org` GitHub organization" }] })
+    );
+
+    // prefixes that only share some characters don't overlap
+    assert_eq!(claim("acme-toolsmith").status(), StatusCode::OK);
+}
+
+#[test]
+fn claims_on_crates_of_other_owners_are_rejected() {
+    let (app, _) = TestApp::init().empty();
+    let owner = app.db_new_user("user-org-owner");
+    let member = app.db_new_user("user-one-team");
+    let outsider = app.db_new_user("outsider");
+
+    app.db(|conn| {
+        CrateBuilder::new("acme-member", member.as_model().id).expect_build(conn);
+        CrateBuilder::new("acme-outsider", outsider.as_model().id).expect_build(conn);
+        CrateBuilder::new("acmeoutsider", outsider.as_model().id).expect_build(conn);
+        CrateBuilder::new("big-corp-member", member.as_model().id).expect_build(conn);
+    });
+
+    let body = json!({
+        "prefix": "acme",
+        "github_org": "test-org",
+        "verification_method": "github",
+    });
+    let response = owner.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "the namespace `acme-*` can not be claimed, because the existing crate `acme-outsider` is not owned by members or teams of the `test-org` GitHub organization" }] })
+    );
+
+    // crates of members of the organization can be covered by a claim
+    let body = json!({
+        "prefix": "big-corp",
+        "github_org": "test-org",
+        "verification_method": "github",
+    });
+    let response = owner.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[test]
+fn dns_claim_is_pending() {
+    let (app, _) = TestApp::init().empty();
+    let owner = app.db_new_user("user-org-owner");
+
+    let body = json!({
+        "prefix": "acme",
+        "github_org": "test-org",
+        "verification_method": "dns",
+        "domain": "ACME.example",
+    });
+    let response = owner.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let json = response.into_json();
+    assert_eq!(json["namespace_claim"]["domain"], "acme.example");
+    assert_eq!(json["namespace_claim"]["verified_at"], json!(null));
+    assert_eq!(json["dns_record"]["type"], "TXT");
+    assert_eq!(
+        json["dns_record"]["name"],
+        "_crates-io-verification.acme.example"
+    );
+    let value = json["dns_record"]["value"].as_str().unwrap();
+    assert!(value.starts_with("crates-io-verification="));
+
+    // pending claims are not listed
+    let json = owner.get::<()>(URL).into_json();
+    assert_eq!(json, json!({ "namespace_claims": [] }));
+}
+
+#[test]
+fn dns_claim_requires_domain() {
+    let (app, _) = TestApp::init().empty();
+    let owner = app.db_new_user("user-org-owner");
+
+    let body = json!({
+        "prefix": "acme",
+        "github_org": "test-org",
+        "verification_method": "dns",
+    });
+    let response = owner.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "a domain is required for DNS verification" }] })
+    );
+}
+
+#[test]
+fn only_org_admins_can_claim() {
+    let (app, _) = TestApp::init().empty();
+    let member = app.db_new_user("user-one-team");
+
+    let body = json!({
+        "prefix": "acme",
+        "github_org": "test-org",
+        "verification_method": "github",
+    });
+    let response = member.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "only admins of the GitHub organization can claim a namespace for it" }] })
+    );
+
+    let body = json!({
+        "prefix": "acme",
+        "github_org": "unknown-org",
+        "verification_method": "github",
+    });
+    let response = member.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "could not find the GitHub organization `unknown-org`" }] })
+    );
+}
+
+#[test]
+fn invalid_prefix() {
+    let (app, _) = TestApp::init().empty();
+    let owner = app.db_new_user("user-org-owner");
+
+    let body = json!({
+        "prefix": "a*",
+        "github_org": "test-org",
+        "verification_method": "github",
+    });
+    let response = owner.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "namespace prefixes must be at least 3 characters long" }] })
+    );
+}
+
+#[test]
+fn requires_cookie_auth() {
+    let (_, anon, _, token) = TestApp::init().with_token();
+
+    let body = json!({
+        "prefix": "acme",
+        "github_org": "test-org",
+        "verification_method": "github",
+    });
+    let response = anon.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = token.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
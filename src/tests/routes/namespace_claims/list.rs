@@ -0,0 +1,28 @@
+use crate::util::{RequestHelper, TestApp};
+use serde_json::Value;
+
+#[test]
+fn list() {
+    let (app, anon) = TestApp::init().empty();
+    let owner = app.db_new_user("user-org-owner");
+
+    for prefix in ["zeta", "acme"] {
+        let body = json!({
+            "prefix": prefix,
+            "github_org": "test-org",
+            "verification_method": "github",
+        });
+        owner
+            .post::<Value>("/api/v1/namespace_claims", body.to_string().as_bytes())
+            .good();
+    }
+
+    let json = anon.get::<()>("/api/v1/namespace_claims").into_json();
+    let patterns = json["namespace_claims"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|claim| claim["pattern"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(patterns, ["acme-*", "zeta-*"]);
+}
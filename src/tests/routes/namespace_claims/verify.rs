@@ -0,0 +1,85 @@
+use crate::util::{MockCookieUser, RequestHelper, TestApp};
+use crates_io::schema::namespace_claims;
+use diesel::prelude::*;
+use http::StatusCode;
+use serde_json::Value;
+
+fn create_dns_claim(user: &MockCookieUser, domain: &str) -> i32 {
+    let body = json!({
+        "prefix": "acme",
+        "github_org": "test-org",
+        "verification_method": "dns",
+        "domain": domain,
+    });
+    let json = user
+        .post::<Value>("/api/v1/namespace_claims", body.to_string().as_bytes())
+        .good();
+    json["namespace_claim"]["id"].as_i64().unwrap() as i32
+}
+
+fn set_verification_token(app: &TestApp, id: i32) {
+    app.db(|conn| {
+        diesel::update(namespace_claims::table.find(id))
+            .set(namespace_claims::verification_token.eq("mock-verification-token"))
+            .execute(conn)
+            .unwrap();
+    });
+}
+
+#[test]
+fn verify() {
+    let (app, _) = TestApp::init().empty();
+    let owner = app.db_new_user("user-org-owner");
+
+    let id = create_dns_claim(&owner, "acme.example");
+    let url = format!("/api/v1/namespace_claims/{id}/verify");
+
+    // the TXT record does not contain the random token yet
+    let response = owner.put::<()>(&url, b"");
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    let detail = json["errors"][0]["detail"].as_str().unwrap();
+    assert!(
+        detail.starts_with("could not find a TXT record on `_crates-io-verification.acme.example`")
+    );
+
+    set_verification_token(&app, id);
+
+    let json = owner.put::<Value>(&url, b"").good();
+    assert!(json["namespace_claim"]["verified_at"].is_string());
+    assert_eq!(json["dns_record"], json!(null));
+
+    let json = owner.get::<()>("/api/v1/namespace_claims").into_json();
+    assert_eq!(json["namespace_claims"][0]["pattern"], "acme-*");
+    assert_eq!(json["namespace_claims"][0]["domain"], "acme.example");
+}
+
+#[test]
+fn verify_unknown_domain() {
+    let (app, _) = TestApp::init().empty();
+    let owner = app.db_new_user("user-org-owner");
+
+    let id = create_dns_claim(&owner, "other.example");
+    set_verification_token(&app, id);
+
+    let response = owner.put::<()>(&format!("/api/v1/namespace_claims/{id}/verify"), b"");
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "could not find a TXT record on `_crates-io-verification.other.example` with the value `crates-io-verification=mock-verification-token`" }] })
+    );
+}
+
+#[test]
+fn verify_by_other_user() {
+    let (app, _, other) = TestApp::init().with_user();
+    let owner = app.db_new_user("user-org-owner");
+
+    let id = create_dns_claim(&owner, "acme.example");
+    set_verification_token(&app, id);
+
+    let response = other.put::<()>(&format!("/api/v1/namespace_claims/{id}/verify"), b"");
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = other.put::<()>("/api/v1/namespace_claims/0/verify", b"");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,181 @@
+use crate::builders::CrateBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::image_proxy::ImageProxy;
+use crates_io::schema::crates;
+use diesel::prelude::*;
+use http::StatusCode;
+use std::time::Duration;
+
+const URL: &str = "/api/v1/render_readme";
+
+#[test]
+fn render_readme() {
+    let (_, _, user) = TestApp::init().with_user();
+
+    let body = json!({
+        "text": "# Hello\n\n[docs](docs/index.md) <script>alert(1)</script>",
+        "repository": "https://github.com/rust-lang/foo",
+        "path_in_vcs": "sub",
+    });
+    let json = user
+        .post::<()>(URL, body.to_string().as_bytes())
+        .into_json();
+    let html = json["html"].as_str().unwrap();
+    assert!(html.contains("<h1><a href=\"#hello\""));
+    assert!(html.contains("https://github.com/rust-lang/foo/blob/HEAD/sub/docs/index.md"));
+    assert!(!html.contains("<script>"));
+
+    // Files without a Markdown extension are rendered as plain text
+    let body = json!({ "text": "*plain*", "readme_file": "README.txt" });
+    let json = user
+        .post::<()>(URL, body.to_string().as_bytes())
+        .into_json();
+    assert_eq!(json["html"], "*plain*");
+}
+
+#[test]
+fn render_readme_with_crate_repository() {
+    let (app, _, user) = TestApp::init().with_user();
+
+    app.db(|conn| {
+        let krate = CrateBuilder::new("foo_readme", user.as_model().id).expect_build(conn);
+        diesel::update(crates::table.find(krate.id))
+            .set(crates::repository.eq("https://gitlab.com/rust-lang/foo"))
+            .execute(conn)
+            .unwrap();
+    });
+
+    let body = json!({ "text": "[docs](docs.md)", "crate": "foo_readme" });
+    let json = user
+        .post::<()>(URL, body.to_string().as_bytes())
+        .into_json();
+    let html = json["html"].as_str().unwrap();
+    assert!(html.contains("https://gitlab.com/rust-lang/foo/blob/HEAD/docs.md"));
+
+    let body = json!({ "text": "[docs](docs.md)", "crate": "unknown" });
+    user.post::<()>(URL, body.to_string().as_bytes())
+        .assert_not_found();
+}
+
+#[test]
+fn render_readme_requires_authentication() {
+    let (_, anon) = TestApp::init().empty();
+
+    let body = json!({ "text": "# Hello" });
+    anon.post::<()>(URL, body.to_string().as_bytes())
+        .assert_forbidden();
+}
+
+#[test]
+fn render_readme_is_size_limited() {
+    let (_, _, user) = TestApp::init().with_user();
+
+    let body = json!({ "text": "a".repeat(600 * 1024) });
+    let response = user.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn render_readme_is_rate_limited() {
+    let (_, _, user) = TestApp::init()
+        .with_config(|config| {
+            config.readme_preview_rate_limit.rate = Duration::from_secs(60);
+            config.readme_preview_rate_limit.burst = 2;
+        })
+        .with_user();
+
+    let body = json!({ "text": "# Hello" }).to_string();
+    for _ in 0..2 {
+        let response = user.post::<()>(URL, body.as_bytes());
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = user.post::<()>(URL, body.as_bytes());
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[test]
+fn render_readme_with_sanitization_policy() {
+    let (_, _, user) = TestApp::init()
+        .with_config(|config| {
+            config.readme_sanitization.denied_tags = vec!["img".into()];
+            config.readme_sanitization.raw_html = false;
+        })
+        .with_user();
+
+    let body = json!({ "text": "![logo](https://example.com/logo.png) <kbd>Ctrl</kbd>" });
+    let json = user
+        .post::<()>(URL, body.to_string().as_bytes())
+        .into_json();
+    let html = json["html"].as_str().unwrap();
+    assert!(!html.contains("<img"));
+    assert!(!html.contains("<kbd>"));
+}
+
+#[test]
+fn render_readme_proxies_external_images() {
+    let (_, anon, user) = TestApp::init()
+        .with_config(|config| {
+            config.readme_sanitization.image_proxy_key = Some("secret".to_string().into());
+        })
+        .with_user();
+
+    let body = json!({ "text": "![logo](https://example.com/logo.png)" });
+    let json = user
+        .post::<()>(URL, body.to_string().as_bytes())
+        .into_json();
+    let html = json["html"].as_str().unwrap();
+    assert!(html.contains("https://crates.io/api/v1/image_proxy/"));
+    assert!(!html.contains("https://example.com/logo.png"));
+
+    // Only URLs signed by crates.io are fetched
+    let url = format!(
+        "/api/v1/image_proxy/invalid/{}",
+        hex::encode("https://example.com/logo.png")
+    );
+    anon.get::<()>(&url).assert_forbidden();
+}
+
+#[test]
+fn image_proxy_rejects_internal_hosts() {
+    let (_, anon, user) = TestApp::init()
+        .with_config(|config| {
+            config.readme_sanitization.image_proxy_key = Some("secret".to_string().into());
+        })
+        .with_user();
+
+    let text = "![](http://169.254.169.254/latest/meta-data) ![](http://localhost/a.png)";
+    let body = json!({ "text": text });
+    let json = user
+        .post::<()>(URL, body.to_string().as_bytes())
+        .into_json();
+    let html = json["html"].as_str().unwrap();
+    assert!(!html.contains("/api/v1/image_proxy/"));
+
+    // Even URLs with a valid signature are not fetched from internal hosts
+    let proxy = ImageProxy::new("crates.io", b"secret");
+    for image_url in [
+        "http://169.254.169.254/latest/meta-data",
+        "http://127.0.0.1:8888/logo.png",
+        "http://10.0.0.1/logo.png",
+        "http://192.168.1.1/logo.png",
+        "http://[::1]/logo.png",
+        "http://[fd00::1]/logo.png",
+        "http://localhost/logo.png",
+    ] {
+        let url = proxy.proxy_url(image_url);
+        let path = url.strip_prefix("https://crates.io").unwrap();
+        anon.get::<()>(path).assert_forbidden();
+    }
+}
+
+#[test]
+fn image_proxy_is_disabled_by_default() {
+    let (_, anon) = TestApp::init().empty();
+
+    let url = format!(
+        "/api/v1/image_proxy/invalid/{}",
+        hex::encode("https://example.com/logo.png")
+    );
+    anon.get::<()>(&url).assert_not_found();
+}
@@ -0,0 +1,134 @@
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{RequestHelper, TestApp};
+use chrono::{Duration, Utc};
+use crates_io::models::NewAdvisory;
+use http::StatusCode;
+
+const URL: &str = "/api/v1/resolve";
+
+#[test]
+fn resolve() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        CrateBuilder::new("foo_resolve", user.id)
+            .version(VersionBuilder::new("1.0.0"))
+            .version(VersionBuilder::new("1.1.0"))
+            .version(VersionBuilder::new("1.2.0").yanked(true))
+            .version(VersionBuilder::new("2.0.0-beta.1"))
+            .expect_build(conn);
+
+        CrateBuilder::new("bar_resolve", user.id)
+            .version(VersionBuilder::new("0.1.0").yanked(true))
+            .expect_build(conn);
+    });
+
+    let body = json!({
+        "dependencies": [
+            { "name": "foo_resolve", "req": "^1" },
+            { "name": "foo_resolve", "req": "=1.2.0" },
+            { "name": "foo_resolve", "req": "^2.0.0-beta" },
+            { "name": "foo_resolve", "req": "^3" },
+            { "name": "bar_resolve", "req": "*" },
+            { "name": "unknown", "req": "*" },
+        ],
+    });
+    let response = anon.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_json(),
+        json!({
+            "dependencies": [
+                { "name": "foo_resolve", "req": "^1", "version": "1.1.0", "yanked": false, "advisories": [] },
+                { "name": "foo_resolve", "req": "=1.2.0", "version": "1.2.0", "yanked": true, "advisories": [] },
+                { "name": "foo_resolve", "req": "^2.0.0-beta", "version": "2.0.0-beta.1", "yanked": false, "advisories": [] },
+                { "name": "foo_resolve", "req": "^3", "version": null, "yanked": false, "advisories": [] },
+                { "name": "bar_resolve", "req": "*", "version": "0.1.0", "yanked": true, "advisories": [] },
+                { "name": "unknown", "req": "*", "version": null, "yanked": false, "advisories": [] },
+            ],
+        })
+    );
+}
+
+#[test]
+fn resolve_with_advisories() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        let krate = CrateBuilder::new("foo_advisory", user.id)
+            .version(VersionBuilder::new("1.0.0"))
+            .version(VersionBuilder::new("1.1.0"))
+            .expect_build(conn);
+
+        NewAdvisory {
+            crate_id: krate.id,
+            identifier: "RUSTSEC-2023-0001",
+            affected: "<1.1.0",
+        }
+        .upsert(conn)
+        .unwrap();
+    });
+
+    let body = json!({
+        "dependencies": [
+            { "name": "foo_advisory", "req": "=1.0.0" },
+            { "name": "foo_advisory", "req": "^1" },
+        ],
+    });
+    let json = anon
+        .post::<()>(URL, body.to_string().as_bytes())
+        .into_json();
+    assert_eq!(
+        json["dependencies"][0]["advisories"],
+        json!(["RUSTSEC-2023-0001"])
+    );
+    assert_eq!(json["dependencies"][1]["version"], "1.1.0");
+    assert_eq!(json["dependencies"][1]["advisories"], json!([]));
+}
+
+#[test]
+fn resolve_with_invalid_requirement() {
+    let (_, anon) = TestApp::init().empty();
+
+    let body = json!({ "dependencies": [{ "name": "foo", "req": "not a requirement" }] });
+    let response = anon.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn resolve_with_too_many_dependencies() {
+    let (_, anon) = TestApp::init().empty();
+
+    let dependencies = (0..101)
+        .map(|i| json!({ "name": format!("crate_{i}"), "req": "*" }))
+        .collect::<Vec<_>>();
+    let body = json!({ "dependencies": dependencies });
+    let response = anon.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "too many dependencies requested, the maximum is 100" }] })
+    );
+}
+
+#[test]
+fn resolve_ignores_staged_versions() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    let staged_until = (Utc::now() + Duration::days(1)).naive_utc();
+    app.db(|conn| {
+        CrateBuilder::new("foo_resolve_staged", user.id)
+            .version(VersionBuilder::new("1.0.0"))
+            .version(VersionBuilder::new("1.1.0").staged_until(staged_until))
+            .expect_build(conn);
+    });
+
+    let body = json!({ "dependencies": [{ "name": "foo_resolve_staged", "req": "^1" }] });
+    let json = anon
+        .post::<()>(URL, body.to_string().as_bytes())
+        .into_json();
+    assert_eq!(json["dependencies"][0]["version"], "1.0.0");
+}
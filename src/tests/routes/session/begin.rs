@@ -1,4 +1,7 @@
 use crate::util::{RequestHelper, TestApp};
+use crates_io::config::GitLabConfig;
+use http::StatusCode;
+use oauth2::{ClientId, ClientSecret};
 
 #[derive(Deserialize)]
 struct AuthResponse {
@@ -12,3 +15,52 @@ fn auth_gives_a_token() {
     let json: AuthResponse = anon.get("/api/private/session/begin").good();
     assert!(json.url.contains(&json.state));
 }
+
+fn gitlab_config() -> GitLabConfig {
+    GitLabConfig {
+        base_url: "https://gitlab.example.com".to_string(),
+        client_id: ClientId::new("gitlab-client-id".to_string()),
+        client_secret: ClientSecret::new("gitlab-client-secret".to_string()),
+    }
+}
+
+#[test]
+fn begin_with_configured_provider() {
+    let (_, anon) = TestApp::init()
+        .with_config(|config| config.oauth_providers.gitlab = Some(gitlab_config()))
+        .empty();
+
+    let json: AuthResponse = anon
+        .get("/api/private/session/begin?provider=gitlab")
+        .good();
+    assert!(json
+        .url
+        .starts_with("https://gitlab.example.com/oauth/authorize?"));
+    assert!(json.url.contains("client_id=gitlab-client-id"));
+    assert!(json.url.contains("scope=read_user"));
+    assert!(json.url.contains("oauth-redirect.html"));
+    assert!(json.url.contains(&json.state));
+}
+
+#[test]
+fn begin_with_unknown_provider() {
+    let (_, anon) = TestApp::init().empty();
+
+    let response = anon.get::<()>("/api/private/session/begin?provider=google");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "unknown OAuth provider `google`" }] })
+    );
+}
+
+#[test]
+fn linking_requires_login() {
+    let (_, anon, user) = TestApp::init().with_user();
+
+    anon.get::<()>("/api/private/session/begin?link=true")
+        .assert_forbidden();
+
+    let json: AuthResponse = user.get("/api/private/session/begin?link=true").good();
+    assert!(json.url.contains(&json.state));
+}
@@ -0,0 +1,97 @@
+use crate::util::{RequestHelper, TestApp};
+use http::StatusCode;
+
+const URL: &str = "/api/v1/takedown_requests";
+
+#[test]
+fn create_anonymously() {
+    let (_, anon) = TestApp::init().empty();
+
+    let body = br#"{
+        "crate": "foo",
+        "name": "Jane Doe",
+        "email": "legal@example.com",
+        "description": "This crate contains our copyrighted code."
+    }"#;
+    let response = anon.post::<()>(URL, body);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let json = response.into_json();
+    let request = &json["takedown_request"];
+    assert_eq!(request["crate"], "foo");
+    assert_eq!(request["version"], json!(null));
+    assert_eq!(request["complainant_email"], "legal@example.com");
+    assert_eq!(request["resolved_at"], json!(null));
+}
+
+#[test]
+fn missing_fields() {
+    let (_, anon) = TestApp::init().empty();
+
+    let body = br#"{ "crate": "foo", "name": "Jane Doe", "email": "legal@example.com" }"#;
+    let response = anon.post::<()>(URL, body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body =
+        br#"{ "crate": "foo", "name": " ", "email": "legal@example.com", "description": "x" }"#;
+    let response = anon.post::<()>(URL, body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "the `name` field must not be empty" }] })
+    );
+
+    let body = br#"{ "crate": "foo", "name": "Jane", "email": "nope", "description": "x" }"#;
+    let response = anon.post::<()>(URL, body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn invalid_fields() {
+    let (_, anon) = TestApp::init().empty();
+
+    let invalid_bodies = [
+        r#"{ "crate": "foo bar", "name": "Jane", "email": "legal@example.com", "description": "x" }"#,
+        r#"{ "crate": "foo", "version": "one", "name": "Jane", "email": "legal@example.com", "description": "x" }"#,
+        r#"{ "crate": "foo", "name": "Jane", "email": "legal@", "description": "x" }"#,
+        r#"{ "crate": "foo", "name": "Jane", "email": "@example.com", "description": "x" }"#,
+    ];
+    for body in invalid_bodies {
+        let response = anon.post::<()>(URL, body.as_bytes());
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{body}");
+    }
+
+    let description = "x".repeat(10_001);
+    let body = json!({
+        "crate": "foo",
+        "name": "Jane Doe",
+        "email": "legal@example.com",
+        "description": description,
+    });
+    let response = anon.post::<()>(URL, body.to_string().as_bytes());
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let detail = "the `description` field must not be longer than 10000 characters";
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": detail }] })
+    );
+}
+
+#[test]
+fn rate_limited_per_ip_address() {
+    let (_, anon) = TestApp::init()
+        .with_config(|config| config.takedown_request_rate_limit.burst = 1)
+        .empty();
+
+    let body = br#"{
+        "crate": "foo",
+        "name": "Jane Doe",
+        "email": "legal@example.com",
+        "description": "This crate contains our copyrighted code."
+    }"#;
+    let response = anon.post::<()>(URL, body);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = anon.post::<()>(URL, body);
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+}
@@ -1,6 +1,7 @@
 use crate::builders::{CrateBuilder, VersionBuilder};
 use crate::util::insta::{self, assert_yaml_snapshot};
 use crate::util::{RequestHelper, TestApp};
+use chrono::{Duration, Utc};
 use crates_io::schema::versions;
 use diesel::{QueryDsl, RunQueryDsl};
 use serde_json::Value;
@@ -34,3 +35,21 @@ fn index() {
         ".versions[].published_by.id" => insta::id_redaction(user.id),
     });
 }
+
+#[test]
+fn index_hides_staged_versions() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    let staged_until = (Utc::now() + Duration::days(1)).naive_utc();
+    let staged = app.db(|conn| {
+        let krate = CrateBuilder::new("foo_vers_staged", user.id).expect_build(conn);
+        VersionBuilder::new("1.0.0")
+            .staged_until(staged_until)
+            .expect_build(krate.id, user.id, conn)
+    });
+
+    let query = format!("ids[]={}", staged.id);
+    let json: Value = anon.get_with_query("/api/v1/versions", &query).good();
+    assert_eq!(json["versions"], json!([]));
+}
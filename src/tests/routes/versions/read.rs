@@ -1,6 +1,7 @@
 use crate::builders::{CrateBuilder, VersionBuilder};
 use crate::util::insta::{self, assert_yaml_snapshot};
 use crate::util::{RequestHelper, TestApp};
+use chrono::{Duration, Utc};
 use serde_json::Value;
 
 #[test]
@@ -24,3 +25,20 @@ fn show_by_id() {
         ".version.published_by.id" => insta::id_redaction(user.id),
     });
 }
+
+#[test]
+fn show_by_id_hides_staged_versions() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    let staged_until = (Utc::now() + Duration::days(1)).naive_utc();
+    let v = app.db(|conn| {
+        let krate = CrateBuilder::new("foo_vers_show_staged", user.id).expect_build(conn);
+        VersionBuilder::new("1.0.0")
+            .staged_until(staged_until)
+            .expect_build(krate.id, user.id, conn)
+    });
+
+    let url = format!("/api/v1/versions/{}", v.id);
+    anon.get::<()>(&url).assert_not_found();
+}
@@ -76,3 +76,45 @@ fn block_traffic_via_arbitrary_header_and_value() {
     let resp = anon.run::<()>(req);
     assert_eq!(resp.status(), StatusCode::FOUND);
 }
+
+#[test]
+fn request_id_is_returned() {
+    let (_app, anon) = TestApp::init().empty();
+
+    let mut req = anon.request_builder(Method::GET, "/api/v1/summary");
+    req.header("x-request-id", "abcd-1234");
+    let resp = anon.run::<()>(req);
+    assert_eq!(resp.headers()["x-request-id"], "abcd-1234");
+
+    // A request ID is generated if the router did not provide one
+    let resp = anon.get::<()>("/api/v1/summary");
+    let request_id = resp.headers()["x-request-id"].to_str().unwrap();
+    assert_eq!(request_id.len(), 32);
+}
+
+#[test]
+fn request_id_is_stored_on_enqueued_jobs() {
+    use crates_io::schema::background_jobs;
+    use diesel::prelude::*;
+
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    let mut req = token.request_builder(Method::PUT, "/api/v1/crates/new");
+    req.header("x-request-id", "publish-1234");
+    req.with_body(&PublishBuilder::new("foo_request_id").body());
+    let resp = token.run::<()>(req);
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let request_ids: Vec<Option<String>> = app.db(|conn| {
+        background_jobs::table
+            .select(background_jobs::request_id)
+            .load(conn)
+            .unwrap()
+    });
+    assert!(!request_ids.is_empty());
+    assert!(request_ids
+        .iter()
+        .all(|id| id.as_deref() == Some("publish-1234")));
+
+    app.run_pending_background_jobs();
+}
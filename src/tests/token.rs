@@ -42,3 +42,24 @@ fn old_tokens_give_specific_error_message() {
         json!({ "errors": [{ "detail": TOKEN_FORMAT_ERROR }] })
     );
 }
+
+#[test]
+fn seeded_token_rng_generates_deterministic_tokens() {
+    use crates_io::util::token::seed_token_rng;
+    use secrecy::ExposeSecret;
+
+    let create_token = || {
+        let _guard = seed_token_rng(1234);
+        let (_, _, user) = TestApp::init().with_user();
+        let token = user.db_new_token("bar");
+        token.plaintext().expose_secret().clone()
+    };
+
+    let token = create_token();
+    assert_eq!(token, create_token());
+
+    // Without a seed the tokens are random again
+    let (_, _, user) = TestApp::init().with_user();
+    let token_user = user.db_new_token("bar");
+    assert_ne!(token_user.plaintext().expose_secret(), &token);
+}
@@ -0,0 +1,102 @@
+use crate::builders::CrateBuilder;
+use crate::{RequestHelper, TestApp};
+use crates_io::config::UpstreamConfig;
+
+use http::StatusCode;
+
+fn upstream_config() -> Option<UpstreamConfig> {
+    Some(UpstreamConfig {
+        index_url: "https://index.crates.io".into(),
+        dl_url: "https://static.crates.io/crates".into(),
+    })
+}
+
+#[test]
+fn index_files_are_fetched_from_upstream() {
+    let (app, anon) = TestApp::init()
+        .with_config(|config| config.upstream = upstream_config())
+        .empty();
+
+    let json = anon.get::<()>("/index/config.json").into_json();
+    assert_eq!(json["auth-required"], false);
+
+    let response = anon.get::<()>("/index/up/st/upstream_only");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.into_text().contains(r#""vers":"1.0.0""#));
+
+    anon.get::<()>("/index/mi/ss/missing").assert_not_found();
+
+    let stored_files = app.stored_files();
+    assert!(stored_files.contains(&"upstream/index/up/st/upstream_only".to_string()));
+}
+
+#[test]
+fn crate_files_are_fetched_from_upstream() {
+    let (app, anon) = TestApp::init()
+        .with_config(|config| config.upstream = upstream_config())
+        .empty();
+
+    let response = anon.get::<()>("/api/v1/crates/upstream_only/1.0.0/download");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(&response.into_bytes()[..], b"upstream crate file");
+
+    anon.get::<()>("/api/v1/crates/upstream_only/2.0.0/download")
+        .assert_not_found();
+
+    let stored_files = app.stored_files();
+    assert!(stored_files
+        .contains(&"upstream/crates/upstream_only/upstream_only-1.0.0.crate".to_string()));
+}
+
+#[test]
+fn crate_files_with_invalid_checksums_are_not_cached() {
+    let (app, anon) = TestApp::init()
+        .with_config(|config| config.upstream = upstream_config())
+        .empty();
+
+    let response = anon.get::<()>("/api/v1/crates/upstream_tampered/1.0.0/download");
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let stored_files = app.stored_files();
+    assert!(!stored_files
+        .iter()
+        .any(|path| path.starts_with("upstream/crates/upstream_tampered")));
+}
+
+#[test]
+fn invalid_crate_files_are_not_fetched_from_upstream() {
+    let (_, anon) = TestApp::init()
+        .with_config(|config| config.upstream = upstream_config())
+        .empty();
+
+    anon.get::<()>("/api/v1/crates/upstream_only/1.0/download")
+        .assert_not_found();
+    anon.get::<()>("/api/v1/crates/upstream_only/..%2F1.0.0/download")
+        .assert_not_found();
+}
+
+#[test]
+fn local_crates_take_precedence() {
+    let (app, anon, user) = TestApp::init()
+        .with_config(|config| config.upstream = upstream_config())
+        .with_user();
+
+    app.db(|conn| {
+        CrateBuilder::new("upstream_only", user.as_model().id)
+            .version("2.0.0")
+            .expect_build(conn);
+    });
+
+    anon.get::<()>("/api/v1/crates/upstream_only/1.0.0/download")
+        .assert_not_found();
+}
+
+#[test]
+fn upstream_is_not_used_by_default() {
+    let (_, anon) = TestApp::init().empty();
+
+    anon.get::<()>("/api/v1/crates/upstream_only/1.0.0/download")
+        .assert_not_found();
+    anon.get::<()>("/index/up/st/upstream_only")
+        .assert_not_found();
+}
@@ -56,11 +56,10 @@ fn github_without_email_does_not_overwrite_email() {
     // Don't use app.db_new_user because it adds a verified email.
     let user_without_github_email = app.db(|conn| {
         let u = new_user("arbitrary_username");
-        let u = u
-            .create_or_update(None, &app.as_inner().emails, conn)
-            .unwrap();
-        MockCookieUser::new(&app, u)
+        u.create_or_update(None, &app.as_inner().emails, conn)
+            .unwrap()
     });
+    let user_without_github_email = MockCookieUser::new(&app, user_without_github_email);
     let user_without_github_email_model = user_without_github_email.as_model();
 
     let json = user_without_github_email.show_me();
@@ -78,11 +77,11 @@ fn github_without_email_does_not_overwrite_email() {
             // new_user uses a None email; the rest of the fields are arbitrary
             ..new_user("arbitrary_username")
         };
-        let u = u
-            .create_or_update(None, &app.as_inner().emails, conn)
-            .unwrap();
-        MockCookieUser::new(&app, u)
+        u.create_or_update(None, &app.as_inner().emails, conn)
+            .unwrap()
     });
+    let again_user_without_github_email =
+        MockCookieUser::new(&app, again_user_without_github_email);
 
     let json = again_user_without_github_email.show_me();
     assert_eq!(json.user.email.unwrap(), "apricot@apricots.apricot");
@@ -113,11 +112,11 @@ fn github_with_email_does_not_overwrite_email() {
             // the rest of the fields are arbitrary
             ..new_user("arbitrary_username")
         };
-        let u = u
-            .create_or_update(Some(new_github_email), &app.as_inner().emails, conn)
-            .unwrap();
-        MockCookieUser::new(&app, u)
+        u.create_or_update(Some(new_github_email), &app.as_inner().emails, conn)
+            .unwrap()
     });
+    let user_with_different_email_in_github =
+        MockCookieUser::new(&app, user_with_different_email_in_github);
 
     let json = user_with_different_email_in_github.show_me();
     assert_eq!(json.user.email, Some(original_email));
@@ -160,11 +159,10 @@ fn test_confirm_user_email() {
         let u = NewUser {
             ..new_user("arbitrary_username")
         };
-        let u = u
-            .create_or_update(Some(email), &app.as_inner().emails, conn)
-            .unwrap();
-        MockCookieUser::new(&app, u)
+        u.create_or_update(Some(email), &app.as_inner().emails, conn)
+            .unwrap()
     });
+    let user = MockCookieUser::new(&app, user);
     let user_model = user.as_model();
 
     let email_token: String = app.db(|conn| {
@@ -209,8 +207,9 @@ fn test_existing_user_email() {
             .set(emails::token_generated_at.eq(None::<NaiveDateTime>))
             .execute(conn)
             .unwrap();
-        MockCookieUser::new(&app, u)
+        u
     });
+    let user = MockCookieUser::new(&app, user);
 
     let json = user.show_me();
     assert_eq!(json.user.email.unwrap(), "potahto@example.com");
@@ -24,7 +24,7 @@ use crate::{
     GoodCrate, OkBool, OwnersResponse, VersionResponse,
 };
 use crates_io::middleware::session;
-use crates_io::models::{ApiToken, CreatedApiToken, User};
+use crates_io::models::{ApiToken, CreatedApiToken, User, UserSession};
 
 use http::{Method, Request};
 
@@ -39,12 +39,14 @@ use std::collections::HashMap;
 use tower_service::Service;
 
 mod chaosproxy;
+mod domains;
 mod fresh_schema;
 mod github;
 pub mod insta;
 mod mock_request;
 mod response;
 mod test_app;
+mod upstream;
 
 pub(crate) use chaosproxy::ChaosProxy;
 pub(crate) use fresh_schema::FreshSchema;
@@ -57,18 +59,23 @@ pub use test_app::{TestApp, TestDatabase};
 /// include cookie-based authentication.
 ///
 /// ```
-/// let cookie = encode_session_header(session_key, user_id);
+/// let cookie = encode_session_header(session_key, user_id, session_token);
 /// request.header(header::COOKIE, &cookie);
 /// ```
 ///
 /// The implementation matches roughly what is happening inside of our
 /// session middleware.
-pub fn encode_session_header(session_key: &cookie::Key, user_id: i32) -> String {
+pub fn encode_session_header(
+    session_key: &cookie::Key,
+    user_id: i32,
+    session_token: &str,
+) -> String {
     let cookie_name = "cargo_session";
 
     // build session data map
     let mut map = HashMap::new();
     map.insert("user_id".into(), user_id.to_string());
+    map.insert("session_token".into(), session_token.to_string());
 
     // encode the map into a cookie value string
     let encoded = session::encode(&map);
@@ -136,6 +143,14 @@ pub trait RequestHelper {
         self.run(request)
     }
 
+    /// Issue a POST request
+    #[track_caller]
+    fn post<T>(&self, path: &str, body: &[u8]) -> Response<T> {
+        let mut request = self.post_request(path);
+        request.with_body(body);
+        self.run(request)
+    }
+
     /// Issue a PUT request
     #[track_caller]
     fn put<T>(&self, path: &str, body: &[u8]) -> Response<T> {
@@ -243,12 +258,13 @@ impl RequestHelper for MockAnonymousUser {
 pub struct MockCookieUser {
     app: TestApp,
     user: User,
+    session_token: String,
 }
 
 impl RequestHelper for MockCookieUser {
     fn request_builder(&self, method: Method, path: &str) -> MockRequest {
         let session_key = &self.app.as_inner().session_key();
-        let cookie = encode_session_header(session_key, self.user.id);
+        let cookie = encode_session_header(session_key, self.user.id, &self.session_token);
 
         let mut request = req(method, path);
         request.header(header::COOKIE, &cookie);
@@ -262,10 +278,16 @@ impl RequestHelper for MockCookieUser {
 
 impl MockCookieUser {
     /// Creates an instance from a database `User` instance
+    ///
+    /// This method creates a new web session in the database
     pub fn new(app: &TestApp, user: User) -> Self {
+        let session = app.db(|conn| UserSession::create(conn, user.id, "", None).unwrap());
+        let session_token = session.plaintext.expose_secret().clone();
+
         Self {
             app: app.clone(),
             user,
+            session_token,
         }
     }
 
@@ -299,6 +321,7 @@ impl MockCookieUser {
                 crate_scopes,
                 endpoint_scopes,
                 expired_at,
+                None,
             )
             .unwrap()
         });
@@ -0,0 +1,55 @@
+use crates_io::domains::DomainClient;
+use crates_io::util::errors::AppResult;
+
+pub(crate) const MOCK_DOMAIN_DATA: MockDomainData = MockDomainData {
+    txt_records: &[
+        (
+            "_crates-io-verification.acme.example",
+            "crates-io-verification=mock-verification-token",
+        ),
+        ("_crates-io-verification.acme.example", "v=spf1 -all"),
+    ],
+    well_known_files: &[(
+        "acme-web.example",
+        "crates-io-verification.txt",
+        "crates-io-verification=mock-verification-token\n",
+    )],
+};
+
+pub(crate) struct MockDomainClient {
+    data: &'static MockDomainData,
+}
+
+impl MockDomainClient {
+    pub(crate) fn new(data: &'static MockDomainData) -> Self {
+        Self { data }
+    }
+}
+
+impl DomainClient for MockDomainClient {
+    fn txt_records(&self, name: &str) -> AppResult<Vec<String>> {
+        Ok(self
+            .data
+            .txt_records
+            .iter()
+            .filter(|(record_name, _)| *record_name == name)
+            .map(|(_, value)| value.to_string())
+            .collect())
+    }
+
+    fn well_known_file(&self, domain: &str, file: &str) -> AppResult<Option<String>> {
+        Ok(self
+            .data
+            .well_known_files
+            .iter()
+            .find(|(d, f, _)| *d == domain && *f == file)
+            .map(|(_, _, content)| content.to_string()))
+    }
+}
+
+pub(crate) struct MockDomainData {
+    /// `(name, value)` pairs of `TXT` records
+    txt_records: &'static [(&'static str, &'static str)],
+    /// `(domain, file, content)` triples of `.well-known` files
+    well_known_files: &'static [(&'static str, &'static str, &'static str)],
+}
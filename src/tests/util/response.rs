@@ -45,6 +45,11 @@ impl<T> Response<T> {
         assert_ok!(self.response.text())
     }
 
+    #[track_caller]
+    pub fn into_bytes(self) -> axum::body::Bytes {
+        assert_ok!(self.response.bytes())
+    }
+
     #[track_caller]
     pub fn assert_redirect_ends_with(&self, target: &str) -> &Self {
         assert!(self
@@ -8,7 +8,9 @@ use crates_io_index::testing::UpstreamIndex;
 use crates_io_index::{Credentials, Repository as WorkerRepository, RepositoryConfig};
 use std::{rc::Rc, sync::Arc, time::Duration};
 
+use crate::util::domains::{MockDomainClient, MOCK_DOMAIN_DATA};
 use crate::util::github::{MockGitHubClient, MOCK_GITHUB_DATA};
+use crate::util::upstream::{MockUpstreamClient, MOCK_UPSTREAM_DATA};
 use anyhow::Context;
 use crates_io::models::token::{CrateScope, EndpointScope};
 use crates_io::swirl::Runner;
@@ -17,7 +19,7 @@ use futures_util::TryStreamExt;
 use oauth2::{ClientId, ClientSecret};
 use reqwest::{blocking::Client, Proxy};
 use secrecy::ExposeSecret;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 struct TestAppInner {
     app: Arc<App>,
@@ -68,7 +70,7 @@ impl Drop for TestAppInner {
 pub struct TestApp(Rc<TestAppInner>);
 
 impl TestApp {
-    /// Initialize an application with an `Uploader` that panics
+    /// Initialize an application with in-memory file storage and uploads
     pub fn init() -> TestAppBuilder {
         crates_io::util::tracing::init_for_test();
 
@@ -107,6 +109,7 @@ impl TestApp {
     ///
     /// This method updates the database directly
     pub fn db_new_user(&self, username: &str) -> MockCookieUser {
+        use crates_io::models::OAuthIdentity;
         use crates_io::schema::emails;
         use diesel::prelude::*;
 
@@ -124,12 +127,11 @@ impl TestApp {
                 ))
                 .execute(conn)
                 .unwrap();
+            OAuthIdentity::link(conn, user.id, "github", &user.gh_id.to_string(), username)
+                .unwrap();
             user
         });
-        MockCookieUser {
-            app: self.clone(),
-            user,
-        }
+        MockCookieUser::new(self, user)
     }
 
     /// Obtain a reference to the upstream repository ("the index")
@@ -144,6 +146,12 @@ impl TestApp {
             .unwrap()
     }
 
+    /// Returns the files that were uploaded through the `Uploader`, keyed by
+    /// their path, e.g. the database dumps.
+    pub fn uploaded_files(&self) -> BTreeMap<String, Vec<u8>> {
+        assert_some!(self.as_inner().config.uploader().uploads_in_memory())
+    }
+
     pub fn stored_files(&self) -> Vec<String> {
         let store = self.as_inner().storage.as_inner();
 
@@ -264,6 +272,11 @@ impl TestAppBuilder {
                 None,
                 None,
                 app.storage.clone(),
+                app.emails.clone(),
+                app.link_signer(),
+                app.config
+                    .readme_sanitization
+                    .policy(&app.config.domain_name),
             );
 
             Some(Runner::test_runner(
@@ -356,27 +369,9 @@ impl TestAppBuilder {
 }
 
 fn simple_config() -> config::Server {
-    let uploader = Uploader::S3 {
-        bucket: Box::new(s3::Bucket::new(
-            dotenvy::var("TEST_S3_BUCKET").unwrap_or_else(|_err| "crates-test".into()),
-            parse_test_region(dotenvy::var("TEST_S3_REGION").ok()),
-            dotenvy::var("TEST_AWS_ACCESS_KEY").unwrap_or_default(),
-            dotenvy::var("TEST_AWS_SECRET_KEY").unwrap_or_default(),
-            // When testing we route all API traffic over HTTP so we can
-            // sniff/record it, but everywhere else we use https
-            "http",
-        )),
-        index_bucket: Some(Box::new(s3::Bucket::new(
-            dotenvy::var("TEST_S3_INDEX_BUCKET").unwrap_or_else(|_err| "crates-index-test".into()),
-            parse_test_region(dotenvy::var("TEST_S3_INDEX_REGION").ok()),
-            dotenvy::var("TEST_AWS_ACCESS_KEY").unwrap_or_default(),
-            dotenvy::var("TEST_AWS_SECRET_KEY").unwrap_or_default(),
-            // When testing we route all API traffic over HTTP so we can
-            // sniff/record it, but everywhere else we use https
-            "http",
-        ))),
-        cdn: None,
-    };
+    // Keep all uploaded files in memory, so that the tests don't touch the
+    // filesystem and can assert on the uploads, see `TestApp::uploaded_files()`.
+    let uploader = Uploader::new_in_memory();
 
     let base = Base {
         env: Env::Test,
@@ -417,10 +412,16 @@ fn simple_config() -> config::Server {
         session_key: cookie::Key::derive_from("test this has to be over 32 bytes long".as_bytes()),
         gh_client_id: ClientId::new(dotenvy::var("GH_CLIENT_ID").unwrap_or_default()),
         gh_client_secret: ClientSecret::new(dotenvy::var("GH_CLIENT_SECRET").unwrap_or_default()),
+        oauth_providers: Default::default(),
+        local_auth: None,
         max_upload_size: 3000,
         max_unpack_size: 2000,
+        large_crate_max_upload_size: 6000,
         publish_rate_limit: Default::default(),
         new_version_rate_limit: Some(10),
+        readme_preview_rate_limit: Default::default(),
+        takedown_request_rate_limit: Default::default(),
+        login_rate_limit: Default::default(),
         blocked_traffic: Default::default(),
         max_allowed_page_offset: 200,
         page_offset_ua_blocklist: vec![],
@@ -431,6 +432,7 @@ fn simple_config() -> config::Server {
         downloads_persist_interval_ms: 1000,
         ownership_invitations_expiration_days: 30,
         metrics_authorization_token: None,
+        admin_authorization_token: None,
         use_test_database_pool: true,
         instance_metrics_log_every_seconds: None,
         force_unconditional_redirects: false,
@@ -439,6 +441,24 @@ fn simple_config() -> config::Server {
         version_id_cache_ttl: Duration::from_secs(5 * 60),
         cdn_user_agent: "Amazon CloudFront".to_string(),
         balance_capacity,
+        readiness_max_job_lag: Duration::from_secs(15 * 60),
+        maintenance_mode: false,
+        maintenance_retry_after: Duration::from_secs(5 * 60),
+        staged_release_soak_period: Duration::from_secs(24 * 60 * 60),
+        account_deletion_grace_period: Duration::from_secs(14 * 24 * 60 * 60),
+        quarantine_account_age: None,
+        zstd_recompression: false,
+        content_addressed_storage: false,
+        reject_duplicate_content: false,
+        category_tree_cache_ttl: Duration::from_secs(5 * 60),
+        dependency_graph_cache_ttl: Duration::from_secs(60 * 60),
+        search_transliteration: false,
+        allow_non_ascii_keywords: false,
+        private_registry: false,
+        signed_downloads: None,
+        upstream: None,
+        region_hint_header: None,
+        readme_sanitization: Default::default(),
 
         // The frontend code is not needed for the backend tests.
         serve_dist: false,
@@ -447,21 +467,6 @@ fn simple_config() -> config::Server {
     }
 }
 
-static DEFAULT_TEST_REGION: &str = "127.0.0.1:19000";
-
-fn parse_test_region(maybe_region: Option<String>) -> s3::Region {
-    match maybe_region {
-        Some(region) if region.contains("://") => {
-            let (_proto, host) = region.split_once("://").unwrap();
-            s3::Region::Host(host.to_string())
-        }
-        Some(region) if !region.is_empty() => s3::Region::Region(region),
-        // An empty or missing region will use the default. This needs to match the region
-        // configuration that was used to generate the cassettes in `src/tests/http-data`.
-        _ => s3::Region::Host(DEFAULT_TEST_REGION.to_string()),
-    }
-}
-
 fn build_app(config: config::Server, proxy: Option<String>) -> (Arc<App>, axum::Router) {
     let client = if let Some(proxy) = proxy {
         let mut builder = Client::builder();
@@ -476,29 +481,23 @@ fn build_app(config: config::Server, proxy: Option<String>) -> (Arc<App>, axum::
 
     // Use the in-memory email backend for all tests, allowing tests to analyze the emails sent by
     // the application. This will also prevent cluttering the filesystem.
-    app.emails = Emails::new_in_memory();
+    app.emails = Arc::new(Emails::new_in_memory());
 
     // Use a custom mock for the GitHub client, allowing to define the GitHub users and
     // organizations without actually having to create GitHub accounts.
     app.github = Box::new(MockGitHubClient::new(&MOCK_GITHUB_DATA));
 
+    // Use a mock for DNS and `.well-known` lookups, allowing to verify domain ownership without
+    // actually having to own a domain.
+    app.domains = Box::new(MockDomainClient::new(&MOCK_DOMAIN_DATA));
+
+    // Use a mock for the upstream registry in the upstream proxy mode, allowing to fetch crates
+    // without actually contacting crates.io.
+    if app.upstream.is_some() {
+        app.upstream = Some(Box::new(MockUpstreamClient::new(&MOCK_UPSTREAM_DATA)));
+    }
+
     let app = Arc::new(app);
     let router = crates_io::build_handler(Arc::clone(&app));
     (app, router)
 }
-
-#[test]
-fn test_parse_region() {
-    for (input, expected) in [
-        (None, s3::Region::Host(DEFAULT_TEST_REGION.into())),
-        (Some(""), s3::Region::Host(DEFAULT_TEST_REGION.into())),
-        (Some("us-west-2"), s3::Region::Region("us-west-2".into())),
-        (Some("http://foo.bar"), s3::Region::Host("foo.bar".into())),
-        (
-            Some("https://127.0.0.1:9000"),
-            s3::Region::Host("127.0.0.1:9000".into()),
-        ),
-    ] {
-        assert_eq!(parse_test_region(input.map(String::from)), expected);
-    }
-}
@@ -0,0 +1,57 @@
+use crates_io::upstream::UpstreamClient;
+use crates_io::util::errors::AppResult;
+use hyper::body::Bytes;
+
+pub(crate) const MOCK_UPSTREAM_DATA: MockUpstreamData = MockUpstreamData {
+    index_files: &[
+        (
+            "upstream_only",
+            "{\"name\":\"upstream_only\",\"vers\":\"1.0.0\",\"deps\":[],\"cksum\":\"2d4621b84b3fc23f29cda64fa66286d5d5a96201ebe1dfc176d3cf1029d211c2\",\"features\":{},\"yanked\":false}\n",
+        ),
+        (
+            "upstream_tampered",
+            "{\"name\":\"upstream_tampered\",\"vers\":\"1.0.0\",\"deps\":[],\"cksum\":\"0000000000000000000000000000000000000000000000000000000000000000\",\"features\":{},\"yanked\":false}\n",
+        ),
+    ],
+    crate_files: &[
+        ("upstream_only", "1.0.0", b"upstream crate file"),
+        ("upstream_tampered", "1.0.0", b"tampered crate file"),
+    ],
+};
+
+pub(crate) struct MockUpstreamClient {
+    data: &'static MockUpstreamData,
+}
+
+impl MockUpstreamClient {
+    pub(crate) fn new(data: &'static MockUpstreamData) -> Self {
+        Self { data }
+    }
+}
+
+impl UpstreamClient for MockUpstreamClient {
+    fn index_file(&self, name: &str) -> AppResult<Option<String>> {
+        Ok(self
+            .data
+            .index_files
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, content)| content.to_string()))
+    }
+
+    fn crate_file(&self, name: &str, version: &str) -> AppResult<Option<Bytes>> {
+        Ok(self
+            .data
+            .crate_files
+            .iter()
+            .find(|(n, v, _)| *n == name && *v == version)
+            .map(|(_, _, content)| Bytes::from_static(content)))
+    }
+}
+
+pub(crate) struct MockUpstreamData {
+    /// `(name, content)` pairs of sparse index files
+    index_files: &'static [(&'static str, &'static str)],
+    /// `(name, version, content)` triples of crate files
+    crate_files: &'static [(&'static str, &'static str, &'static [u8])],
+}
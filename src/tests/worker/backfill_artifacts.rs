@@ -0,0 +1,88 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::background_jobs::Job;
+use crates_io::models::BackfillProgress;
+use crates_io::schema::{readme_renderings, versions};
+use diesel::prelude::*;
+
+#[test]
+fn backfill_artifacts() {
+    let (app, anon, _, token) = TestApp::full().with_token();
+
+    let files: [(&str, &[u8]); 2] = [
+        ("foo-1.0.0/Cargo.toml", b"[package]\n"),
+        ("foo-1.0.0/README.md", b"hello world"),
+    ];
+    token
+        .publish_crate(PublishBuilder::new("foo").files(&files))
+        .good();
+    token.publish_crate(PublishBuilder::new("bar")).good();
+
+    let checksum = anon.show_version("foo", "1.0.0").version.checksum;
+    assert!(!app
+        .stored_files()
+        .contains(&"readmes/foo/foo-1.0.0.html".to_string()));
+
+    app.db(|conn| {
+        diesel::update(versions::table)
+            .set(versions::checksum.eq(""))
+            .execute(conn)
+            .unwrap();
+
+        // A batch size of one makes sure that the backfill continues with
+        // the next batch in a new job
+        Job::backfill_artifacts(1, 0).enqueue(conn).unwrap();
+    });
+    app.run_pending_background_jobs();
+
+    assert_eq!(anon.show_version("foo", "1.0.0").version.checksum, checksum);
+    assert!(app
+        .stored_files()
+        .contains(&"readmes/foo/foo-1.0.0.html".to_string()));
+
+    app.db(|conn| {
+        let rendered: i64 = readme_renderings::table.count().get_result(conn).unwrap();
+        assert_eq!(rendered, 1);
+
+        // `bar` has no readme, but its checksum was still computed
+        let blank: i64 = versions::table
+            .filter(versions::checksum.eq(""))
+            .count()
+            .get_result(conn)
+            .unwrap();
+        assert_eq!(blank, 0);
+
+        let progress = BackfillProgress::find(conn, "artifacts").unwrap().unwrap();
+        assert_eq!(progress.processed_count, 1);
+        assert_eq!(progress.skipped_count, 1);
+    });
+}
+
+#[test]
+fn backfill_artifacts_paused() {
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    token.publish_crate(PublishBuilder::new("foo")).good();
+
+    app.db(|conn| {
+        BackfillProgress::set_paused(conn, "artifacts", true).unwrap();
+        Job::backfill_artifacts(100, 0).enqueue(conn).unwrap();
+    });
+    app.run_pending_background_jobs();
+
+    app.db(|conn| {
+        let progress = BackfillProgress::find(conn, "artifacts").unwrap().unwrap();
+        assert_eq!(progress.last_version_id, 0);
+
+        // Resuming continues where the backfill was paused
+        BackfillProgress::set_paused(conn, "artifacts", false).unwrap();
+        Job::backfill_artifacts(100, 0).enqueue(conn).unwrap();
+    });
+    app.run_pending_background_jobs();
+
+    app.db(|conn| {
+        let progress = BackfillProgress::find(conn, "artifacts").unwrap().unwrap();
+        assert_ne!(progress.last_version_id, 0);
+        assert_eq!(progress.skipped_count, 1);
+    });
+}
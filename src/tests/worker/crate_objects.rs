@@ -0,0 +1,45 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::background_jobs::Job;
+use crates_io::schema::{crates, version_objects, versions};
+use diesel::prelude::*;
+
+#[test]
+fn backfill_crate_objects() {
+    let (app, anon, _, token) = TestApp::full().with_token();
+
+    token.publish_crate(PublishBuilder::new("foo")).good();
+    token.publish_crate(PublishBuilder::new("bar")).good();
+
+    let checksum = anon.show_version("foo", "1.0.0").version.checksum;
+
+    app.db(|conn| {
+        // Crate files that do not match their checksum are skipped
+        let bar_id = crates::table
+            .filter(crates::name.eq("bar"))
+            .select(crates::id);
+        diesel::update(versions::table.filter(versions::crate_id.eq_any(bar_id)))
+            .set(versions::checksum.eq("0".repeat(64)))
+            .execute(conn)
+            .unwrap();
+
+        Job::backfill_crate_objects().enqueue(conn).unwrap();
+    });
+    app.run_pending_background_jobs();
+
+    let object_path = format!("objects/{}/{}/{checksum}", &checksum[..2], &checksum[2..4]);
+    assert!(app.stored_files().contains(&object_path));
+
+    // The crate files in the legacy layout are preserved
+    assert!(app
+        .stored_files()
+        .contains(&"crates/foo/foo-1.0.0.crate".to_string()));
+
+    let hashes: Vec<String> = app.db(|conn| {
+        version_objects::table
+            .select(version_objects::object_hash)
+            .load(conn)
+            .unwrap()
+    });
+    assert_eq!(hashes, vec![checksum]);
+}
@@ -66,3 +66,60 @@ fn index_smoke_test() {
     );
     assert_ok_eq!(upstream.crate_exists("serde"), false);
 }
+
+#[test]
+fn duplicate_and_superseded_index_syncs_are_skipped() {
+    use crates_io::schema::{index_changes, index_sync_states};
+
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    token.publish_crate(PublishBuilder::new("serde")).good();
+
+    // Every sparse index sync records a change for the mirrors
+    let count_changes =
+        || -> i64 { app.db(|conn| assert_ok!(index_changes::table.count().get_result(conn))) };
+    let synced_sequences = || -> Vec<(String, i64)> {
+        app.db(|conn| {
+            assert_ok!(index_sync_states::table
+                .filter(index_sync_states::crate_name.eq("serde"))
+                .select((
+                    index_sync_states::target,
+                    index_sync_states::synced_sequence
+                ))
+                .order(index_sync_states::target)
+                .load(conn))
+        })
+    };
+
+    assert_eq!(count_changes(), 1);
+    assert_eq!(
+        synced_sequences(),
+        vec![("git".to_string(), 1), ("sparse".to_string(), 1)]
+    );
+
+    // A duplicate of a job that already ran is skipped
+    app.db(|conn| assert_ok!(Job::sync_to_sparse_index("serde", Some(1)).enqueue(conn)));
+    app.run_pending_background_jobs();
+    assert_eq!(count_changes(), 1);
+
+    // Only the most recent of multiple pending jobs is performed
+    app.db(|conn| {
+        assert_ok!(Job::enqueue_sync_to_index("serde", conn));
+        assert_ok!(Job::enqueue_sync_to_index("serde", conn));
+    });
+    app.run_pending_background_jobs();
+    assert_eq!(count_changes(), 2);
+    assert_eq!(
+        synced_sequences(),
+        vec![("git".to_string(), 3), ("sparse".to_string(), 3)]
+    );
+
+    // Jobs without a sequence number are always performed
+    app.db(|conn| assert_ok!(Job::sync_to_sparse_index("serde", None).enqueue(conn)));
+    app.run_pending_background_jobs();
+    assert_eq!(count_changes(), 3);
+    assert_eq!(
+        synced_sequences(),
+        vec![("git".to_string(), 3), ("sparse".to_string(), 3)]
+    );
+}
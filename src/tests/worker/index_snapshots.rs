@@ -0,0 +1,127 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use chrono::{Duration, Utc};
+use crates_io::background_jobs::Job;
+use crates_io::schema::index_changes;
+use diesel::prelude::*;
+use flate2::read::GzDecoder;
+use http::StatusCode;
+use std::io::Read;
+
+/// Moves the recorded changes out of the settle margin, so that they are
+/// included in snapshots and in the change feed.
+fn settle_index_changes(app: &TestApp) {
+    app.db(|conn| {
+        let created_at = Utc::now().naive_utc() - Duration::minutes(5);
+        diesel::update(index_changes::table)
+            .set(index_changes::created_at.eq(created_at))
+            .execute(conn)
+            .unwrap();
+    });
+}
+
+fn create_index_snapshot(app: &TestApp) {
+    app.db(|conn| Job::create_index_snapshot().enqueue(conn).unwrap());
+    app.run_pending_background_jobs();
+}
+
+fn max_sequence(app: &TestApp) -> i64 {
+    app.db(|conn| {
+        index_changes::table
+            .select(diesel::dsl::max(index_changes::id))
+            .get_result::<Option<i64>>(conn)
+            .unwrap()
+            .unwrap()
+    })
+}
+
+#[test]
+fn snapshot_and_change_feed() {
+    let (app, anon, _, token) = TestApp::full().with_token();
+
+    anon.get::<()>("/api/v1/index/snapshot").assert_not_found();
+
+    token.publish_crate(PublishBuilder::new("foo")).good();
+    token.publish_crate(PublishBuilder::new("serde")).good();
+    settle_index_changes(&app);
+    create_index_snapshot(&app);
+
+    let sequence = max_sequence(&app);
+
+    let json = anon.get::<()>("/api/v1/index/snapshot").into_json();
+    let snapshot = &json["snapshot"];
+    assert_eq!(snapshot["sequence"], sequence);
+    assert_eq!(snapshot["crates"], 2);
+
+    let snapshot_path = format!("index-snapshots/{sequence}.tar.gz");
+    assert!(app.stored_files().contains(&snapshot_path));
+
+    let download_path = snapshot["download_path"].as_str().unwrap();
+    let bytes = anon.get::<()>(download_path).into_bytes();
+    assert_eq!(snapshot["size"], bytes.len());
+
+    let mut archive = tar::Archive::new(GzDecoder::new(&*bytes));
+    let mut paths = vec![];
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let path = entry.path().unwrap().to_string_lossy().to_string();
+        if path == "manifest.json" {
+            let mut content = String::new();
+            entry.read_to_string(&mut content).unwrap();
+            let manifest: serde_json::Value = serde_json::from_str(&content).unwrap();
+            assert_eq!(manifest["sequence"], sequence);
+        }
+        paths.push(path);
+    }
+    assert_eq!(paths, vec!["manifest.json", "3/f/foo", "se/rd/serde"]);
+
+    // Recent changes are only handed out once they are settled
+    token.publish_crate(PublishBuilder::new("bar")).good();
+    let url = format!("/api/v1/index/changes?since={sequence}");
+    let json = anon.get::<()>(&url).into_json();
+    assert_eq!(json["changes"].as_array().unwrap().len(), 0);
+    assert_eq!(json["meta"]["next_since"], sequence);
+
+    settle_index_changes(&app);
+    let json = anon.get::<()>(&url).into_json();
+    let changes = json["changes"].as_array().unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0]["crate"], "bar");
+    assert_eq!(json["meta"]["next_since"], max_sequence(&app));
+
+    let response = anon.get::<()>("/api/v1/index/changes?since=foo");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn outdated_snapshots_are_pruned() {
+    let (app, anon, _, token) = TestApp::full().with_token();
+
+    token.publish_crate(PublishBuilder::new("foo")).good();
+    settle_index_changes(&app);
+    create_index_snapshot(&app);
+    let first = max_sequence(&app);
+
+    // Without new changes, no new snapshot is created
+    create_index_snapshot(&app);
+
+    for name in ["bar", "baz"] {
+        token.publish_crate(PublishBuilder::new(name)).good();
+        settle_index_changes(&app);
+        create_index_snapshot(&app);
+    }
+
+    let snapshots = app
+        .stored_files()
+        .into_iter()
+        .filter(|path| path.starts_with("index-snapshots/"))
+        .count();
+    assert_eq!(snapshots, 2);
+
+    let url = format!("/api/v1/index/snapshots/{first}/download");
+    anon.get::<()>(&url).assert_not_found();
+
+    let url = format!("/api/v1/index/changes?since={first}");
+    let response = anon.get::<()>(&url);
+    assert_eq!(response.status(), StatusCode::GONE);
+}
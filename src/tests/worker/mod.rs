@@ -1 +1,7 @@
+mod backfill_artifacts;
+mod crate_objects;
 mod git;
+mod index_snapshots;
+mod orphaned_files;
+mod retention;
+mod stale_crates;
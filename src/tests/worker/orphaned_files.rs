@@ -0,0 +1,93 @@
+use crate::builders::PublishBuilder;
+use crate::routes::admin::{admin_request, ADMIN_TOKEN};
+use crate::util::{RequestHelper, TestApp};
+use crates_io::background_jobs::Job;
+use http::{Method, StatusCode};
+use hyper::body::Bytes;
+use object_store::path::Path;
+
+const ORPHANED_FILES: [&str; 3] = [
+    "crates/bar/bar-1.0.0.crate",
+    "readmes/foo/foo-2.0.0.html",
+    "user-exports/1/1.tar.gz",
+];
+
+fn put_files(app: &TestApp, paths: &[&str]) {
+    let store = app.as_inner().storage.as_inner();
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    for path in paths {
+        let path = Path::from(*path);
+        rt.block_on(store.put(&path, Bytes::new())).unwrap();
+    }
+}
+
+fn run_cleanup(app: &TestApp, dry_run: bool, grace_period_hours: u32) {
+    app.db(|conn| {
+        Job::cleanup_orphaned_files(dry_run, grace_period_hours)
+            .enqueue(conn)
+            .unwrap();
+    });
+    app.run_pending_background_jobs();
+}
+
+#[test]
+fn cleanup_orphaned_files() {
+    let (app, anon, _, token) = TestApp::full()
+        .with_config(|config| config.admin_authorization_token = Some(ADMIN_TOKEN.into()))
+        .with_token();
+
+    token.publish_crate(PublishBuilder::new("foo")).good();
+    put_files(&app, &ORPHANED_FILES);
+    put_files(&app, &["db-dump.tar.gz"]);
+
+    let expected_files = vec![
+        "crates/bar/bar-1.0.0.crate",
+        "crates/foo/foo-1.0.0.crate",
+        "db-dump.tar.gz",
+        "index/3/f/foo",
+        "readmes/foo/foo-2.0.0.html",
+        "user-exports/1/1.tar.gz",
+    ];
+    assert_eq!(app.stored_files(), expected_files);
+
+    // Recently modified files are skipped
+    run_cleanup(&app, false, 24);
+    assert_eq!(app.stored_files(), expected_files);
+
+    run_cleanup(&app, true, 0);
+    assert_eq!(app.stored_files(), expected_files);
+
+    run_cleanup(&app, false, 0);
+    let expected_files = vec![
+        "crates/foo/foo-1.0.0.crate",
+        "db-dump.tar.gz",
+        "index/3/f/foo",
+    ];
+    assert_eq!(app.stored_files(), expected_files);
+
+    let url = "/api/private/admin/orphaned_files";
+    let response = admin_request(&anon, Method::GET, url, Some(ADMIN_TOKEN), b"");
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    let reports = json["reports"].as_array().unwrap();
+    assert_eq!(reports.len(), 3);
+
+    assert_eq!(reports[0]["dry_run"], false);
+    assert_eq!(reports[0]["orphaned_count"], 3);
+    assert_eq!(reports[0]["deleted_count"], 3);
+    assert_eq!(reports[0]["orphaned_files"], json!(ORPHANED_FILES));
+
+    assert_eq!(reports[1]["dry_run"], true);
+    assert_eq!(reports[1]["orphaned_count"], 3);
+    assert_eq!(reports[1]["deleted_count"], 0);
+
+    assert_eq!(reports[2]["orphaned_count"], 0);
+
+    let response = admin_request(&anon, Method::GET, url, None, b"");
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
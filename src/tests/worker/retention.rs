@@ -0,0 +1,143 @@
+use crate::builders::{CrateBuilder, PublishBuilder};
+use crate::util::{RequestHelper, TestApp};
+use chrono::{Duration, NaiveDateTime, Utc};
+use crates_io::background_jobs::Job;
+use crates_io::config::RetentionConfig;
+use crates_io::models::{ApiToken, RetentionStats, UserSession};
+use crates_io::schema::{api_tokens, crate_owner_invitations, emails, user_sessions};
+use diesel::prelude::*;
+
+fn days_ago(days: i64) -> NaiveDateTime {
+    Utc::now().naive_utc() - Duration::days(days)
+}
+
+fn purge_expired_data(app: &TestApp) {
+    app.db(|conn| {
+        Job::purge_expired_data(RetentionConfig::default())
+            .enqueue(conn)
+            .unwrap();
+    });
+    app.run_pending_background_jobs();
+}
+
+#[test]
+fn purge_expired_data_respects_retention_periods() {
+    let (app, _, user, token) = TestApp::full().with_token();
+    let user_id = user.as_model().id;
+    let other_user_id = app.db_new_user("bar").as_model().id;
+
+    // The token that published a version is referenced by the audit trail
+    token.publish_crate(PublishBuilder::new("foo")).good();
+    let publish_token_id = token.as_model().id;
+
+    app.db(|conn| {
+        for (name, created_at) in [("old_invite", days_ago(61)), ("new_invite", days_ago(1))] {
+            let krate = CrateBuilder::new(name, user_id).expect_build(conn);
+            diesel::insert_into(crate_owner_invitations::table)
+                .values((
+                    crate_owner_invitations::invited_user_id.eq(other_user_id),
+                    crate_owner_invitations::invited_by_user_id.eq(user_id),
+                    crate_owner_invitations::crate_id.eq(krate.id),
+                    crate_owner_invitations::created_at.eq(created_at),
+                ))
+                .execute(conn)
+                .unwrap();
+        }
+
+        let old_revoked = ApiToken::insert(conn, user_id, "old revoked").unwrap();
+        let new_revoked = ApiToken::insert(conn, user_id, "new revoked").unwrap();
+        diesel::update(api_tokens::table)
+            .filter(api_tokens::id.eq_any([old_revoked.model.id, new_revoked.model.id]))
+            .set(api_tokens::revoked.eq(true))
+            .execute(conn)
+            .unwrap();
+        diesel::update(api_tokens::table.find(old_revoked.model.id))
+            .set(api_tokens::revoked_at.eq(days_ago(91)))
+            .execute(conn)
+            .unwrap();
+
+        let expired = ApiToken::insert(conn, user_id, "expired").unwrap();
+        diesel::update(api_tokens::table)
+            .filter(api_tokens::id.eq_any([expired.model.id, publish_token_id]))
+            .set(api_tokens::expired_at.eq(days_ago(100)))
+            .execute(conn)
+            .unwrap();
+
+        let stale = UserSession::create(conn, user_id, "stale", None).unwrap();
+        diesel::update(user_sessions::table.find(stale.model.id))
+            .set(user_sessions::last_seen_at.eq(days_ago(91)))
+            .execute(conn)
+            .unwrap();
+
+        let revoked = UserSession::create(conn, user_id, "revoked", None).unwrap();
+        diesel::update(user_sessions::table.find(revoked.model.id))
+            .set(user_sessions::revoked_at.eq(days_ago(91)))
+            .execute(conn)
+            .unwrap();
+
+        diesel::update(emails::table.filter(emails::user_id.eq(user_id)))
+            .set(emails::token_generated_at.eq(days_ago(8)))
+            .execute(conn)
+            .unwrap();
+    });
+
+    purge_expired_data(&app);
+
+    app.db(|conn| {
+        let invitations: i64 = crate_owner_invitations::table
+            .count()
+            .get_result(conn)
+            .unwrap();
+        assert_eq!(invitations, 1);
+
+        let tokens: Vec<String> = api_tokens::table
+            .order(api_tokens::id)
+            .select(api_tokens::name)
+            .load(conn)
+            .unwrap();
+        assert_eq!(tokens, vec!["bar", "new revoked"]);
+
+        let sessions: Vec<String> = user_sessions::table
+            .select(user_sessions::user_agent)
+            .load(conn)
+            .unwrap();
+        assert!(!sessions.iter().any(|agent| agent == "stale"));
+        assert!(!sessions.iter().any(|agent| agent == "revoked"));
+
+        let cleared: Vec<bool> = emails::table
+            .order(emails::user_id)
+            .select(emails::token.eq(""))
+            .load(conn)
+            .unwrap();
+        assert_eq!(cleared, vec![true, false]);
+
+        let stats = RetentionStats::all(conn).unwrap();
+        let purged = stats
+            .iter()
+            .map(|stats| (stats.kind.as_str(), stats.last_purged))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            purged,
+            vec![
+                ("consumed_email_tokens", 1),
+                ("ip_limit_buckets", 0),
+                ("ownership_invitations", 1),
+                ("revoked_tokens", 2),
+                ("stale_sessions", 2),
+                ("webauthn_ceremonies", 0),
+            ]
+        );
+    });
+
+    // The totals add up over multiple runs
+    purge_expired_data(&app);
+
+    app.db(|conn| {
+        let stats = RetentionStats::all(conn).unwrap();
+        let totals = stats
+            .iter()
+            .map(|stats| (stats.last_purged, stats.purged_total))
+            .collect::<Vec<_>>();
+        assert_eq!(totals, vec![(0, 1), (0, 0), (0, 1), (0, 2), (0, 2), (0, 0)]);
+    });
+}
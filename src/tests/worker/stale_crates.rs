@@ -0,0 +1,107 @@
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{RequestHelper, TestApp};
+use chrono::{Duration, NaiveDateTime, Utc};
+use crates_io::background_jobs::Job;
+use crates_io::schema::{crates, user_sessions, users};
+use diesel::prelude::*;
+use serde_json::Value;
+
+fn days_ago(days: i64) -> NaiveDateTime {
+    Utc::now().naive_utc() - Duration::days(days)
+}
+
+fn flag_stale_crates(app: &TestApp) {
+    app.db(|conn| Job::flag_stale_crates(365).enqueue(conn).unwrap());
+    app.run_pending_background_jobs();
+}
+
+fn maintenance_emails(app: &TestApp) -> Vec<String> {
+    app.as_inner()
+        .emails
+        .mails_in_memory()
+        .unwrap()
+        .into_iter()
+        .filter(|email| email.subject.ends_with("still maintained?"))
+        .map(|email| email.subject)
+        .collect()
+}
+
+/// Creates a crate whose owner has last been active `days` days ago.
+fn setup(app: &TestApp, user_id: i32, name: &str, days: i64) {
+    app.db(|conn| {
+        let krate = CrateBuilder::new(name, user_id)
+            .version(VersionBuilder::new("1.0.0").created_at(days_ago(days)))
+            .expect_build(conn);
+
+        diesel::update(crates::table.find(krate.id))
+            .set(crates::created_at.eq(days_ago(days)))
+            .execute(conn)
+            .unwrap();
+        diesel::update(users::table.find(user_id))
+            .set(users::created_at.eq(days_ago(days)))
+            .execute(conn)
+            .unwrap();
+        diesel::update(user_sessions::table.filter(user_sessions::user_id.eq(user_id)))
+            .set(user_sessions::last_seen_at.eq(days_ago(days)))
+            .execute(conn)
+            .unwrap();
+    });
+}
+
+fn stale_since(anon: &impl RequestHelper, name: &str) -> Value {
+    let json = anon
+        .get::<()>(&format!("/api/v1/crates/{name}"))
+        .into_json();
+    json["crate"]["stale_since"].clone()
+}
+
+#[test]
+fn flags_crates_of_inactive_owners() {
+    let (app, anon, user) = TestApp::full().with_user();
+    let user_id = user.as_model().id;
+    setup(&app, user_id, "foo_inactive", 400);
+
+    flag_stale_crates(&app);
+    assert!(stale_since(&anon, "foo_inactive").is_string());
+    assert_eq!(
+        maintenance_emails(&app),
+        vec!["Is foo_inactive still maintained?"]
+    );
+
+    // Owners are only notified when the crate is flagged
+    flag_stale_crates(&app);
+    assert_eq!(maintenance_emails(&app).len(), 1);
+}
+
+#[test]
+fn does_not_flag_crates_of_active_owners() {
+    let (app, anon, user) = TestApp::full().with_user();
+    let user_id = user.as_model().id;
+    setup(&app, user_id, "foo_active", 100);
+
+    flag_stale_crates(&app);
+    assert!(stale_since(&anon, "foo_active").is_null());
+    assert!(maintenance_emails(&app).is_empty());
+}
+
+#[test]
+fn unflags_crates_when_owners_are_active_again() {
+    let (app, anon, user) = TestApp::full().with_user();
+    let user_id = user.as_model().id;
+    setup(&app, user_id, "foo_returning", 400);
+
+    flag_stale_crates(&app);
+    assert!(stale_since(&anon, "foo_returning").is_string());
+
+    app.db(|conn| {
+        let krate_id: i32 = crates::table
+            .filter(crates::name.eq("foo_returning"))
+            .select(crates::id)
+            .first(conn)
+            .unwrap();
+        VersionBuilder::new("1.1.0").expect_build(krate_id, user_id, conn);
+    });
+
+    flag_stale_crates(&app);
+    assert!(stale_since(&anon, "foo_returning").is_null());
+}
@@ -0,0 +1,137 @@
+//! The policy that decides how large the crate files of a publish may be.
+//!
+//! By default the global `max_upload_size` and `max_unpack_size` of the server
+//! config apply. Crates that have regularly published crate files close to the
+//! global limit automatically get the larger `large_crate_max_upload_size`,
+//! and the crates.io team can set explicit per-crate overrides through the
+//! admin API, which take precedence over both.
+
+use crate::config;
+use crate::models::UploadLimit;
+use crate::schema::versions;
+use crate::util::errors::{upload_too_large, AppResult};
+use diesel::prelude::*;
+use std::cmp;
+
+/// The number of non-yanked versions with large crate files that a crate
+/// needs before it gets the larger limit automatically.
+const LARGE_CRATE_MIN_VERSIONS: i64 = 3;
+
+/// Crate files are considered large if they are at least this percentage of
+/// the global `max_upload_size`.
+const LARGE_CRATE_FILE_PERCENT: u64 = 75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadLimitSource {
+    /// The global limits of the server config
+    Default,
+    /// The crate has historically published large crate files
+    LargeCrate,
+    /// The crates.io team has set an override for the crate
+    Override,
+}
+
+impl UploadLimitSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::LargeCrate => "large_crate",
+            Self::Override => "override",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadLimits {
+    pub max_upload_size: u64,
+    pub max_unpack_size: u64,
+    pub source: UploadLimitSource,
+}
+
+impl UploadLimits {
+    fn new(max_upload_size: u64, max_unpack_size: u64, source: UploadLimitSource) -> Self {
+        // A crate file that is allowed to be uploaded must also be allowed to be unpacked
+        let max_unpack_size = cmp::max(max_unpack_size, max_upload_size);
+
+        Self {
+            max_upload_size,
+            max_unpack_size,
+            source,
+        }
+    }
+
+    /// Returns the limits that apply to crates that have not been published yet.
+    pub fn for_new_crate(config: &config::Server) -> Self {
+        Self::new(
+            config.max_upload_size,
+            config.max_unpack_size,
+            UploadLimitSource::Default,
+        )
+    }
+
+    /// Returns the limits that apply to new versions of the given crate.
+    pub fn for_crate(
+        conn: &mut PgConnection,
+        config: &config::Server,
+        crate_id: i32,
+    ) -> QueryResult<Self> {
+        if let Some(limit) = UploadLimit::find(conn, crate_id)? {
+            let max_unpack_size = limit
+                .max_unpack_size
+                .map_or(config.max_unpack_size, |size| size as u64);
+
+            return Ok(Self::new(
+                limit.max_upload_size as u64,
+                max_unpack_size,
+                UploadLimitSource::Override,
+            ));
+        }
+
+        if is_large_crate(conn, config, crate_id)? {
+            return Ok(Self::new(
+                config.large_crate_max_upload_size,
+                config.max_unpack_size,
+                UploadLimitSource::LargeCrate,
+            ));
+        }
+
+        Ok(Self::for_new_crate(config))
+    }
+
+    /// Returns a `413 Payload Too Large` error if the crate file is larger
+    /// than the `max_upload_size`.
+    pub fn check_upload_size(&self, upload_size: u64) -> AppResult<()> {
+        if upload_size > self.max_upload_size {
+            return Err(upload_too_large(
+                upload_size,
+                self.max_upload_size,
+                self.source.as_str(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns `true` if the crate has historically published large crate files.
+fn is_large_crate(
+    conn: &mut PgConnection,
+    config: &config::Server,
+    crate_id: i32,
+) -> QueryResult<bool> {
+    if config.large_crate_max_upload_size <= config.max_upload_size {
+        return Ok(false);
+    }
+
+    let threshold = config.max_upload_size * LARGE_CRATE_FILE_PERCENT / 100;
+    let threshold = i32::try_from(threshold).unwrap_or(i32::MAX);
+
+    let large_versions: i64 = versions::table
+        .filter(versions::crate_id.eq(crate_id))
+        .filter(versions::yanked.eq(false))
+        .filter(versions::crate_size.ge(threshold))
+        .count()
+        .get_result(conn)?;
+
+    Ok(large_versions >= LARGE_CRATE_MIN_VERSIONS)
+}
@@ -2,9 +2,13 @@ use anyhow::Result;
 use reqwest::{blocking::Client, header};
 
 use reqwest::blocking::Body;
+use std::collections::BTreeMap;
 use std::env;
 use std::fs::{self, File};
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::config::SignedDownloadsConfig;
 
 #[derive(Clone, Debug)]
 pub enum Uploader {
@@ -14,11 +18,29 @@ pub enum Uploader {
         bucket: Box<s3::Bucket>,
         index_bucket: Option<Box<s3::Bucket>>,
         cdn: Option<String>,
+        /// Copies of the crate files in other regions, see `S3_REPLICAS`
+        replicas: Vec<Replica>,
     },
 
     /// For development usage only: "uploads" crate files to `dist` and serves them
     /// from there as well to enable local publishing and download
     Local,
+
+    /// For test usage: keeps the uploaded files in memory, keyed by their
+    /// path, so that the test suite can assert on them without touching the
+    /// filesystem or the network.
+    InMemory {
+        uploads: Arc<Mutex<BTreeMap<String, Vec<u8>>>>,
+    },
+}
+
+/// A copy of the crate files in another region, which is used for the
+/// downloads of clients with a matching region hint.
+#[derive(Clone, Debug)]
+pub struct Replica {
+    pub region: String,
+    pub bucket: Box<s3::Bucket>,
+    pub cdn: Option<String>,
 }
 
 pub enum UploadBucket {
@@ -27,25 +49,125 @@ pub enum UploadBucket {
 }
 
 impl Uploader {
+    pub fn new_in_memory() -> Self {
+        Uploader::InMemory {
+            uploads: Default::default(),
+        }
+    }
+
+    /// Returns a copy of the files uploaded so far, keyed by their path. This
+    /// is only available for the "in memory" uploader. It's not cfg'd away
+    /// because our integration tests need to access this.
+    pub fn uploads_in_memory(&self) -> Option<BTreeMap<String, Vec<u8>>> {
+        match self {
+            Uploader::InMemory { uploads } => Some(uploads.lock().unwrap().clone()),
+            _ => None,
+        }
+    }
+
     /// Returns the URL of an uploaded crate's version archive.
     ///
+    /// If a replica exists in the `region` that the client was routed to, e.g.
+    /// by GeoDNS or the CDN, the URL of the replicated file is returned.
+    ///
     /// The function doesn't check for the existence of the file.
-    pub fn crate_location(&self, crate_name: &str, version: &str) -> String {
+    pub fn crate_location(&self, crate_name: &str, version: &str, region: Option<&str>) -> String {
         let version = version.replace('+', "%2B");
 
         match *self {
             Uploader::S3 {
                 ref bucket,
                 ref cdn,
+                ref replicas,
                 ..
             } => {
                 let path = Uploader::crate_path(crate_name, &version);
-                match *cdn {
-                    Some(ref host) => format!("https://{host}/{path}"),
-                    None => bucket.url(&path).unwrap(),
-                }
+                regional_s3_location(bucket, cdn, replicas, &path, region)
+            }
+            Uploader::Local | Uploader::InMemory { .. } => {
+                format!("/{}", Uploader::crate_path(crate_name, &version))
+            }
+        }
+    }
+
+    /// Returns the URL of a crate file in the content-addressed `objects/`
+    /// layout, given the SHA-256 hash of its content. Just like
+    /// [`Self::crate_location`], this prefers the replica in the `region`.
+    ///
+    /// The function doesn't check for the existence of the file.
+    pub fn crate_location_by_hash(&self, hash: &str, region: Option<&str>) -> String {
+        let path = Uploader::crate_object_path(hash);
+
+        match *self {
+            Uploader::S3 {
+                ref bucket,
+                ref cdn,
+                ref replicas,
+                ..
+            } => regional_s3_location(bucket, cdn, replicas, &path, region),
+            Uploader::Local | Uploader::InMemory { .. } => format!("/{path}"),
+        }
+    }
+
+    /// Returns the URL of the zstd-compressed copy of a crate's version archive.
+    /// Just like [`Self::crate_location`], this prefers the replica in the `region`.
+    ///
+    /// The function doesn't check for the existence of the file.
+    pub fn zstd_crate_location(
+        &self,
+        crate_name: &str,
+        version: &str,
+        region: Option<&str>,
+    ) -> String {
+        let version = version.replace('+', "%2B");
+
+        match *self {
+            Uploader::S3 {
+                ref bucket,
+                ref cdn,
+                ref replicas,
+                ..
+            } => {
+                let path = Uploader::zstd_crate_path(crate_name, &version);
+                regional_s3_location(bucket, cdn, replicas, &path, region)
+            }
+            Uploader::Local | Uploader::InMemory { .. } => {
+                format!("/{}", Uploader::zstd_crate_path(crate_name, &version))
+            }
+        }
+    }
+
+    /// Returns a short-lived URL of the file at the internal `path`, which can
+    /// be downloaded without further authentication until it expires. This is
+    /// used for the crate files of private registries.
+    ///
+    /// S3 URLs are presigned with the credentials of the bucket, preferring
+    /// the replica in the `region`, and bypass the CDN. Local URLs point to
+    /// the `signed_downloads` endpoint, which checks the signature itself.
+    pub fn signed_location(
+        &self,
+        path: &str,
+        signing: &SignedDownloadsConfig,
+        region: Option<&str>,
+    ) -> String {
+        let expires = signing.expires_at();
+        let url_path = path.replace('+', "%2B");
+
+        match *self {
+            Uploader::S3 {
+                ref bucket,
+                ref replicas,
+                ..
+            } => {
+                let replica =
+                    region.and_then(|region| replicas.iter().find(|r| r.region == region));
+                let bucket = replica.map_or(&**bucket, |replica| &*replica.bucket);
+                bucket.presigned_url(&url_path, expires).unwrap()
+            }
+            Uploader::Local | Uploader::InMemory { .. } => {
+                let query = signing.signed_query(path, expires);
+                format!("/api/private/signed_downloads/{url_path}?{query}")
             }
-            Uploader::Local => format!("/{}", Uploader::crate_path(crate_name, &version)),
         }
     }
 
@@ -67,7 +189,9 @@ impl Uploader {
                     None => bucket.url(&path).unwrap(),
                 }
             }
-            Uploader::Local => format!("/{}", Uploader::readme_path(crate_name, &version)),
+            Uploader::Local | Uploader::InMemory { .. } => {
+                format!("/{}", Uploader::readme_path(crate_name, &version))
+            }
         }
     }
 
@@ -76,6 +200,19 @@ impl Uploader {
         format!("crates/{name}/{name}-{version}.crate")
     }
 
+    /// Returns the internal path of a crate file in the content-addressed
+    /// layout, e.g. `objects/ab/cd/abcd…` for the hash `abcd…`.
+    pub fn crate_object_path(hash: &str) -> String {
+        let first = hash.get(..2).unwrap_or_default();
+        let second = hash.get(2..4).unwrap_or_default();
+        format!("objects/{first}/{second}/{hash}")
+    }
+
+    /// Returns the internal path of the zstd-compressed copy of a crate's version archive.
+    pub fn zstd_crate_path(name: &str, version: &str) -> String {
+        format!("crates/{name}/{name}-{version}.tar.zst")
+    }
+
     /// Returns the internal path of an uploaded crate's version readme.
     pub fn readme_path(name: &str, version: &str) -> String {
         format!("readmes/{name}/{name}-{version}.html")
@@ -90,14 +227,14 @@ impl Uploader {
         env::current_dir().unwrap().join("local_uploads").join(path)
     }
 
-    /// Uploads a file using the configured uploader (either `S3`, `Local`).
+    /// Uploads a file using the configured uploader (either `S3`, `Local` or `InMemory`).
     ///
     /// It returns the path of the uploaded file.
     ///
     /// # Panics
     ///
     /// This function can panic on an `Self::Local` during development.
-    /// Production and tests use `Self::S3` which should not panic.
+    /// Production uses `Self::S3` and tests use `Self::InMemory`, which should not panic.
     #[instrument(skip_all, fields(%path))]
     pub fn upload<R: Into<Body>>(
         &self,
@@ -135,6 +272,39 @@ impl Uploader {
                 std::io::copy(&mut buffer, &mut file)?;
                 Ok(filename.to_str().map(String::from))
             }
+            Uploader::InMemory { ref uploads } => {
+                let path = match upload_bucket {
+                    UploadBucket::Index => format!("index/{path}"),
+                    UploadBucket::Default => path.to_string(),
+                };
+                let mut body = content.into();
+                let mut buffer = body.buffer()?;
+                let mut bytes = Vec::new();
+                std::io::copy(&mut buffer, &mut bytes)?;
+                uploads.lock().unwrap().insert(path.clone(), bytes);
+                Ok(Some(path))
+            }
         }
     }
 }
+
+/// Returns the S3 URL of a replicated file, using the CDN or bucket of the
+/// replica in the `region`, or the default ones if there is no such replica.
+fn regional_s3_location(
+    bucket: &s3::Bucket,
+    cdn: &Option<String>,
+    replicas: &[Replica],
+    path: &str,
+    region: Option<&str>,
+) -> String {
+    let replica = region.and_then(|region| replicas.iter().find(|r| r.region == region));
+    let (bucket, cdn) = match replica {
+        Some(replica) => (&*replica.bucket, &replica.cdn),
+        None => (bucket, cdn),
+    };
+
+    match cdn {
+        Some(host) => format!("https://{host}/{path}"),
+        None => bucket.url(path).unwrap(),
+    }
+}
@@ -0,0 +1,144 @@
+//! This module implements the lookups of crates in an upstream registry like
+//! crates.io, which are used by the upstream proxy mode to serve crates that
+//! do not exist locally.
+
+use hyper::body::Bytes;
+use reqwest::blocking::Client;
+use reqwest::{header, StatusCode};
+
+use crate::config::UpstreamConfig;
+use crate::models::Crate;
+use crate::util::errors::{internal, AppResult};
+
+pub trait UpstreamClient: Send + Sync {
+    /// Returns the sparse index file of the given crate, or `None` if the
+    /// crate does not exist upstream.
+    fn index_file(&self, name: &str) -> AppResult<Option<String>>;
+
+    /// Returns the crate file of the given version, or `None` if the version
+    /// does not exist upstream.
+    fn crate_file(&self, name: &str, version: &str) -> AppResult<Option<Bytes>>;
+}
+
+/// Returns whether the crate name and version are valid, so that they can be
+/// used in the URLs of the upstream registry and the paths of the cache.
+pub fn is_valid_crate_file(name: &str, version: &str) -> bool {
+    Crate::valid_name(name) && semver::Version::parse(version).is_ok()
+}
+
+/// Returns the checksum of the given version from a sparse index file, or
+/// `None` if the version is not listed.
+pub fn index_checksum(index_file: &str, version: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct IndexEntry {
+        vers: String,
+        cksum: String,
+    }
+
+    index_file
+        .lines()
+        .filter_map(|line| serde_json::from_str::<IndexEntry>(line).ok())
+        .find(|entry| entry.vers == version)
+        .map(|entry| entry.cksum)
+}
+
+/// An [`UpstreamClient`] that fetches crates via HTTP.
+#[derive(Debug)]
+pub struct RealUpstreamClient {
+    client: Option<Client>,
+    index_url: String,
+    dl_url: String,
+}
+
+impl RealUpstreamClient {
+    pub fn new(client: Option<Client>, config: &UpstreamConfig) -> Self {
+        Self {
+            client,
+            index_url: config.index_url.clone(),
+            dl_url: config.dl_url.clone(),
+        }
+    }
+
+    fn client(&self) -> &Client {
+        self.client
+            .as_ref()
+            .expect("No HTTP client is configured.  In tests, use `TestApp::with_proxy()`.")
+    }
+
+    fn get(&self, url: &str) -> AppResult<Option<reqwest::blocking::Response>> {
+        info!("Upstream lookup: {url}");
+
+        let response = self
+            .client()
+            .get(url)
+            .header(header::USER_AGENT, "crates.io (https://crates.io)")
+            .send()?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let response = response
+            .error_for_status()
+            .map_err(|e| internal(format!("upstream lookup failed: {e}")))?;
+
+        Ok(Some(response))
+    }
+}
+
+impl UpstreamClient for RealUpstreamClient {
+    fn index_file(&self, name: &str) -> AppResult<Option<String>> {
+        let path = crates_io_index::Repository::relative_index_file_for_url(name);
+        let url = format!("{}/{path}", self.index_url);
+
+        match self.get(&url)? {
+            Some(response) => Ok(Some(response.text()?)),
+            None => Ok(None),
+        }
+    }
+
+    fn crate_file(&self, name: &str, version: &str) -> AppResult<Option<Bytes>> {
+        if !is_valid_crate_file(name, version) {
+            return Ok(None);
+        }
+
+        let version = version.replace('+', "%2B");
+        let url = format!("{}/{name}/{name}-{version}.crate", self.dl_url);
+
+        match self.get(&url)? {
+            Some(response) => Ok(Some(response.bytes()?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crate_file_validation() {
+        assert!(is_valid_crate_file("serde", "1.0.0"));
+        assert!(is_valid_crate_file("serde", "1.0.0+build.1"));
+        assert!(!is_valid_crate_file("../serde", "1.0.0"));
+        assert!(!is_valid_crate_file("serde", "1.0"));
+        assert!(!is_valid_crate_file("serde", "../../1.0.0"));
+    }
+
+    #[test]
+    fn index_checksums() {
+        let index_file = concat!(
+            r#"{"name":"foo","vers":"1.0.0","deps":[],"cksum":"aaaa","features":{},"yanked":false}"#,
+            "\n",
+            r#"{"name":"foo","vers":"1.1.0","deps":[],"cksum":"bbbb","features":{},"yanked":false}"#,
+            "\n",
+        );
+
+        assert_eq!(
+            index_checksum(index_file, "1.1.0"),
+            Some("bbbb".to_string())
+        );
+        assert_eq!(index_checksum(index_file, "2.0.0"), None);
+        assert_eq!(index_checksum("not json", "1.0.0"), None);
+    }
+}
@@ -0,0 +1,226 @@
+//! Circuit breakers for outbound calls to external dependencies like the
+//! GitHub API, the file storage or the SMTP server.
+//!
+//! After a number of consecutive failures the breaker "opens" and rejects all
+//! calls immediately, instead of letting every request wait for a timeout.
+//! Once the reset timeout has passed a single trial call is let through, and
+//! depending on its outcome the breaker either closes again or stays open.
+
+use serde::Serialize;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Number of consecutive failures after which a breaker opens.
+const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+
+/// How long an open breaker rejects calls before a trial call is let through.
+const DEFAULT_RESET_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    name: &'static str,
+    failure_threshold: u32,
+    reset_timeout: Duration,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Closed { failures: u32 },
+    Open { since: Instant },
+    HalfOpen { since: Instant },
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str) -> Self {
+        Self::with_settings(name, DEFAULT_FAILURE_THRESHOLD, DEFAULT_RESET_TIMEOUT)
+    }
+
+    pub fn with_settings(
+        name: &'static str,
+        failure_threshold: u32,
+        reset_timeout: Duration,
+    ) -> Self {
+        Self {
+            name,
+            failure_threshold,
+            reset_timeout,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Checks whether a call to the dependency may be made right now.
+    ///
+    /// Callers have to report the outcome of the call via
+    /// [`Self::record_success`] or [`Self::record_failure`].
+    pub fn try_acquire(&self) -> Result<(), CircuitOpen> {
+        let mut state = self.state.lock().unwrap();
+        match *state {
+            State::Closed { .. } => Ok(()),
+            // Only a single trial call is allowed per reset timeout, so that a
+            // recovering dependency isn't flooded with requests.
+            State::Open { since } | State::HalfOpen { since }
+                if since.elapsed() >= self.reset_timeout =>
+            {
+                *state = State::HalfOpen {
+                    since: Instant::now(),
+                };
+                Ok(())
+            }
+            State::Open { since } | State::HalfOpen { since } => Err(CircuitOpen {
+                name: self.name,
+                retry_after: self.reset_timeout.saturating_sub(since.elapsed()),
+            }),
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        if !matches!(*state, State::Closed { .. }) {
+            info!(circuit_breaker = self.name, "Circuit breaker closed");
+        }
+        *state = State::Closed { failures: 0 };
+    }
+
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        match *state {
+            State::Closed { failures } if failures + 1 < self.failure_threshold => {
+                *state = State::Closed {
+                    failures: failures + 1,
+                };
+            }
+            State::Closed { .. } | State::HalfOpen { .. } => {
+                warn!(circuit_breaker = self.name, "Circuit breaker opened");
+                *state = State::Open {
+                    since: Instant::now(),
+                };
+            }
+            State::Open { .. } => {}
+        }
+    }
+
+    pub fn status(&self) -> CircuitBreakerStatus {
+        let state = *self.state.lock().unwrap();
+        let (state, consecutive_failures, retry_after) = match state {
+            State::Closed { failures } => ("closed", failures, None),
+            State::Open { since } => {
+                let retry_after = self.reset_timeout.saturating_sub(since.elapsed());
+                ("open", self.failure_threshold, Some(retry_after.as_secs()))
+            }
+            State::HalfOpen { .. } => ("half_open", self.failure_threshold, None),
+        };
+
+        CircuitBreakerStatus {
+            name: self.name,
+            state,
+            consecutive_failures,
+            retry_after,
+        }
+    }
+}
+
+/// The circuit breakers of the external dependencies that are used while
+/// handling requests.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakers {
+    pub github: Arc<CircuitBreaker>,
+    pub storage: Arc<CircuitBreaker>,
+    pub email: Arc<CircuitBreaker>,
+}
+
+impl CircuitBreakers {
+    pub fn statuses(&self) -> Vec<CircuitBreakerStatus> {
+        [&self.github, &self.storage, &self.email]
+            .into_iter()
+            .map(|breaker| breaker.status())
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CircuitBreakerStatus {
+    pub name: &'static str,
+    pub state: &'static str,
+    pub consecutive_failures: u32,
+    /// Seconds until an open breaker lets a trial call through.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
+}
+
+/// The error returned for calls that were rejected by an open breaker.
+#[derive(Debug)]
+pub struct CircuitOpen {
+    name: &'static str,
+    retry_after: Duration,
+}
+
+impl fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is currently unavailable, please try again in {} seconds",
+            self.name,
+            self.retry_after.as_secs().max(1)
+        )
+    }
+}
+
+impl std::error::Error for CircuitOpen {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_consecutive_failures() {
+        let breaker = CircuitBreaker::with_settings("test", 3, Duration::from_secs(60));
+
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_ok!(breaker.try_acquire());
+        assert_eq!(breaker.status().state, "closed");
+
+        breaker.record_failure();
+        assert_err!(breaker.try_acquire());
+        assert_eq!(breaker.status().state, "open");
+    }
+
+    #[test]
+    fn half_open_allows_single_trial_call() {
+        let breaker = CircuitBreaker::with_settings("test", 1, Duration::ZERO);
+
+        breaker.record_failure();
+        assert_eq!(breaker.status().state, "open");
+
+        assert_ok!(breaker.try_acquire());
+        assert_eq!(breaker.status().state, "half_open");
+
+        breaker.record_failure();
+        assert_eq!(breaker.status().state, "open");
+
+        assert_ok!(breaker.try_acquire());
+        breaker.record_success();
+        assert_eq!(breaker.status().state, "closed");
+        assert_eq!(breaker.status().consecutive_failures, 0);
+    }
+
+    #[test]
+    fn half_open_rejects_concurrent_calls() {
+        let breaker = CircuitBreaker::with_settings("test", 1, Duration::from_secs(60));
+        *breaker.state.lock().unwrap() = State::Open {
+            since: Instant::now() - Duration::from_secs(61),
+        };
+
+        assert_ok!(breaker.try_acquire());
+        assert_err!(breaker.try_acquire());
+    }
+}
@@ -31,8 +31,8 @@ mod json;
 
 pub use json::TOKEN_FORMAT_ERROR;
 pub(crate) use json::{
-    InsecurelyGeneratedTokenRevoked, MetricsDisabled, NotFound, OwnershipInvitationExpired,
-    ReadOnlyMode, RouteBlocked, TooManyRequests,
+    IndexChangesExpired, InsecurelyGeneratedTokenRevoked, MaintenanceMode, MetricsDisabled,
+    NotFound, OwnershipInvitationExpired, ReadOnlyMode, RouteBlocked, TooManyRequests,
 };
 
 pub type BoxedAppError = Box<dyn AppError>;
@@ -46,6 +46,34 @@ pub fn cargo_err<S: ToString + ?Sized>(error: &S) -> BoxedAppError {
     Box::new(json::Ok(error.to_string()))
 }
 
+/// Returns an error with status 200 listing the unknown categories together
+/// with the closest valid matches, both as a description and as structured JSON
+pub fn unknown_categories(categories: Vec<(String, Vec<String>)>) -> BoxedAppError {
+    Box::new(json::UnknownCategories { categories })
+}
+
+/// Returns an error with status 413 that tells the publisher the size of the
+/// upload, the applicable limit and where the limit comes from, as JSON
+pub fn upload_too_large(
+    upload_size: u64,
+    max_upload_size: u64,
+    limit_source: &'static str,
+) -> BoxedAppError {
+    Box::new(json::UploadTooLarge {
+        upload_size,
+        max_upload_size,
+        limit_source,
+    })
+}
+
+/// Returns `true` if the error was created via [`cargo_err`], [`unknown_categories`] or
+/// [`upload_too_large`]
+pub fn is_cargo_err(error: &BoxedAppError) -> bool {
+    error.is::<json::Ok>()
+        || error.is::<json::UnknownCategories>()
+        || error.is::<json::UploadTooLarge>()
+}
+
 // The following are intended to be used for errors being sent back to the Ember
 // frontend, not to cargo as cargo does not handle non-200 response codes well
 // (see <https://github.com/rust-lang/cargo/issues/3995>), but Ember requires
@@ -1,8 +1,10 @@
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use std::fmt;
+use std::time::Duration;
 
 use super::{AppError, BoxedAppError, InternalAppErrorStatic};
+use crate::publish_rate_limit::LimitedAction;
 
 use chrono::NaiveDateTime;
 use http::{header, StatusCode};
@@ -74,6 +76,7 @@ pub(super) struct ServerError(pub(super) String);
 pub(crate) struct ServiceUnavailable(pub(super) String);
 #[derive(Debug)]
 pub(crate) struct TooManyRequests {
+    pub action: LimitedAction,
     pub retry_after: NaiveDateTime,
 }
 
@@ -131,9 +134,9 @@ impl AppError for TooManyRequests {
         let retry_after = self.retry_after.format(HTTP_DATE_FORMAT);
 
         let detail = format!(
-            "You have published too many crates in a \
-             short period of time. Please try again after {retry_after} or email \
-             help@crates.io to have your limit increased."
+            "{} Please try again after {retry_after} or email \
+             help@crates.io to have your limit increased.",
+            self.action.error_message()
         );
         let mut response = json_error(&detail, StatusCode::TOO_MANY_REQUESTS);
         response.headers_mut().insert(
@@ -223,6 +226,28 @@ impl fmt::Display for AccountLocked {
     }
 }
 
+#[derive(Debug)]
+pub(crate) struct IndexChangesExpired {
+    pub(crate) since: i64,
+}
+
+impl AppError for IndexChangesExpired {
+    fn response(&self) -> Response {
+        json_error(&self.to_string(), StatusCode::GONE)
+    }
+}
+
+impl fmt::Display for IndexChangesExpired {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The index changes after sequence number {} are no longer available. \
+             Please start over from the latest index snapshot.",
+            self.since
+        )
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct OwnershipInvitationExpired {
     pub(crate) crate_name: String,
@@ -245,6 +270,91 @@ impl fmt::Display for OwnershipInvitationExpired {
     }
 }
 
+#[derive(Debug)]
+pub(crate) struct UnknownCategories {
+    /// The unknown category slugs, together with the closest valid slugs
+    pub(crate) categories: Vec<(String, Vec<String>)>,
+}
+
+impl AppError for UnknownCategories {
+    fn response(&self) -> Response {
+        let unknown_categories = self
+            .categories
+            .iter()
+            .map(|(category, suggestions)| {
+                json!({ "category": category, "suggestions": suggestions })
+            })
+            .collect::<Vec<_>>();
+
+        // Returned with status 200 for backwards compatibility with cargo
+        let json = json!({
+            "errors": [{
+                "detail": self.to_string(),
+                "unknown_categories": unknown_categories,
+            }],
+        });
+        (StatusCode::OK, Json(json)).into_response()
+    }
+}
+
+impl fmt::Display for UnknownCategories {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unknown categories: ")?;
+        for (i, (category, suggestions)) in self.categories.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "`{category}`")?;
+            if !suggestions.is_empty() {
+                let suggestions = suggestions
+                    .iter()
+                    .map(|s| format!("`{s}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, " (did you mean {suggestions}?)")?;
+            }
+        }
+        write!(
+            f,
+            ". See https://{}/category_slugs for a list of valid categories.",
+            crate::config::domain_name()
+        )
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct UploadTooLarge {
+    pub(crate) upload_size: u64,
+    pub(crate) max_upload_size: u64,
+    /// Why this limit applies to the crate, see `UploadLimitSource`
+    pub(crate) limit_source: &'static str,
+}
+
+impl AppError for UploadTooLarge {
+    fn response(&self) -> Response {
+        let json = json!({
+            "errors": [{
+                "detail": self.to_string(),
+                "upload_size": self.upload_size,
+                "max_upload_size": self.max_upload_size,
+                "limit_source": self.limit_source,
+            }],
+        });
+        (StatusCode::PAYLOAD_TOO_LARGE, Json(json)).into_response()
+    }
+}
+
+impl fmt::Display for UploadTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the uploaded crate file is {} bytes, but the max upload size of this crate is {} \
+             bytes. Please email help@crates.io if you need a larger limit.",
+            self.upload_size, self.max_upload_size
+        )
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct MetricsDisabled;
 
@@ -260,6 +370,36 @@ impl fmt::Display for MetricsDisabled {
     }
 }
 
+#[derive(Debug)]
+pub(crate) struct MaintenanceMode {
+    pub(crate) retry_after: Duration,
+}
+
+impl AppError for MaintenanceMode {
+    fn response(&self) -> Response {
+        let retry_after = self.retry_after.as_secs();
+        let json = json!({
+            "errors": [{ "detail": self.to_string() }],
+            "retry_after": retry_after,
+        });
+
+        let mut response = (StatusCode::SERVICE_UNAVAILABLE, Json(json)).into_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, retry_after.into());
+        response
+    }
+}
+
+impl fmt::Display for MaintenanceMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(
+            "crates.io is currently in read-only mode for maintenance. \
+             Please try again later.",
+        )
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct RouteBlocked;
 
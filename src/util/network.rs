@@ -0,0 +1,186 @@
+//! Fetching of user supplied URLs, e.g. README images or the well-known files
+//! of verified domains.
+//!
+//! These requests must not reach hosts inside of our network, so the target
+//! of every request (including redirects) is resolved up front, rejected if
+//! it is an internal address, and the connection is pinned to the resolved
+//! address. The client neither uses the configured proxy nor follows
+//! redirects on its own.
+
+use crate::util::errors::{cargo_err, internal, AppResult};
+use reqwest::blocking::{Client, Response};
+use reqwest::{header, redirect, Url};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::time::Duration;
+
+const USER_AGENT: &str = "crates.io (https://crates.io)";
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Returns `true` if the address is routable on the public internet.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                // Shared address space (RFC 6598)
+                || (a == 100 && (64..128).contains(&b))
+                // "This network" and reserved ranges
+                || a == 0
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(ip));
+            }
+
+            let first = ip.segments()[0];
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                // Unique local addresses (fc00::/7)
+                || (first & 0xfe00) == 0xfc00
+                // Link-local addresses (fe80::/10)
+                || (first & 0xffc0) == 0xfe80
+                // Documentation addresses (2001:db8::/32)
+                || (first == 0x2001 && ip.segments()[1] == 0x0db8))
+        }
+    }
+}
+
+/// Returns `true` if the host of the URL is a domain name, as opposed to an
+/// IP address literal or `localhost`. This does not resolve the host, see
+/// [`resolve_public_url`] for that.
+pub fn has_public_host(url: &Url) -> bool {
+    let Some(url::Host::Domain(domain)) = url.host() else {
+        return false;
+    };
+
+    let domain = domain.trim_end_matches('.').to_lowercase();
+    domain.contains('.') && domain != "localhost" && !domain.ends_with(".localhost")
+}
+
+/// Resolves the host of the URL, and returns the address to connect to, if
+/// the URL is allowed to be fetched.
+pub fn resolve_public_url(url: &Url) -> AppResult<SocketAddr> {
+    if !matches!(url.scheme(), "http" | "https") || !has_public_host(url) {
+        return Err(cargo_err(&format_args!("URL `{url}` is not allowed")));
+    }
+
+    let host = url.host_str().unwrap_or_default();
+    let port = url.port_or_known_default().unwrap_or(443);
+    let addrs = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| internal(format!("failed to resolve `{host}`: {e}")))?
+        .collect::<Vec<_>>();
+
+    // All addresses have to be public, since the client might pick any of them
+    if addrs.is_empty() || !addrs.iter().all(|addr| is_public_ip(addr.ip())) {
+        return Err(cargo_err(&format_args!("URL `{url}` is not allowed")));
+    }
+
+    Ok(addrs[0])
+}
+
+/// Sends a `GET` request to the URL, following up to `max_redirects`
+/// redirects, each of which is checked by [`resolve_public_url`].
+pub fn get_public_url(url: &str, max_redirects: usize) -> AppResult<Response> {
+    let mut url = Url::parse(url).map_err(|_| cargo_err(&format_args!("invalid URL `{url}`")))?;
+
+    for _ in 0..=max_redirects {
+        let addr = resolve_public_url(&url)?;
+        let host = url.host_str().unwrap_or_default();
+
+        let client = Client::builder()
+            .no_proxy()
+            .redirect(redirect::Policy::none())
+            .resolve(host, addr)
+            .timeout(TIMEOUT)
+            .build()?;
+
+        let response = client
+            .get(url.clone())
+            .header(header::USER_AGENT, USER_AGENT)
+            .send()?;
+
+        if !response.status().is_redirection() {
+            return Ok(response);
+        }
+
+        let location = response
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| internal(format!("redirect from `{url}` without location")))?;
+
+        url = url
+            .join(location)
+            .map_err(|_| internal(format!("invalid redirect from `{url}`")))?;
+    }
+
+    Err(internal(format!("too many redirects fetching `{url}`")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_ips() {
+        for ip in ["93.184.216.34", "2606:2800:220:1:248:1893:25c8:1946"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+
+        for ip in [
+            "127.0.0.1",
+            "0.0.0.0",
+            "10.0.0.1",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fc00::1",
+            "fd12:3456::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn public_hosts() {
+        let is_public = |url: &str| has_public_host(&Url::parse(url).unwrap());
+
+        assert!(is_public("https://example.com/logo.png"));
+        assert!(!is_public("http://169.254.169.254/latest/meta-data"));
+        assert!(!is_public("http://10.0.0.1/"));
+        assert!(!is_public("http://[::1]/"));
+        assert!(!is_public("http://localhost:8888/"));
+        assert!(!is_public("http://foo.localhost/"));
+        assert!(!is_public("http://intranet/"));
+        assert!(!is_public("http://2130706433/"));
+    }
+
+    #[test]
+    fn internal_urls_are_not_resolved() {
+        for url in [
+            "http://127.0.0.1/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://localhost/",
+            "file:///etc/passwd",
+        ] {
+            assert_err!(resolve_public_url(&Url::parse(url).unwrap()));
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Serves in-memory files with support for conditional and range requests.
+//!
+//! This is used whenever crate files are served by the backend itself instead
+//! of redirecting to the CDN, so that clients on flaky networks can resume
+//! interrupted downloads.
+
+use axum::body::Bytes;
+use axum::response::{IntoResponse, Response};
+use hex::ToHex;
+use http::{header, HeaderMap, HeaderValue, StatusCode};
+use sha2::{Digest, Sha256};
+use std::ops::RangeInclusive;
+
+/// Returns a response for the given file contents, honoring the `Range`,
+/// `If-Range` and `If-None-Match` headers of the request.
+///
+/// The strong `ETag` of the response is derived from the SHA256 checksum of
+/// the file contents.
+pub fn serve_bytes(
+    request_headers: &HeaderMap,
+    content_type: HeaderValue,
+    bytes: Bytes,
+) -> Response {
+    let etag = strong_etag(&bytes);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::ETAG, etag.clone());
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+
+    if let Some(if_none_match) = request_headers.get(header::IF_NONE_MATCH) {
+        if matches_etag(if_none_match, &etag) {
+            return (StatusCode::NOT_MODIFIED, headers).into_response();
+        }
+    }
+
+    headers.insert(header::CONTENT_TYPE, content_type);
+
+    let len = bytes.len() as u64;
+    let range = request_headers
+        .get(header::RANGE)
+        .filter(|_| if_range_matches(request_headers, &etag))
+        .and_then(|range| range.to_str().ok())
+        .and_then(parse_range);
+
+    match range.map(|range| satisfiable_range(range, len)) {
+        Some(Some(range)) => {
+            let content_range = format!("bytes {}-{}/{len}", range.start(), range.end());
+            headers.insert(header::CONTENT_RANGE, header_value(content_range));
+
+            let body = bytes.slice(*range.start() as usize..=*range.end() as usize);
+            headers.insert(header::CONTENT_LENGTH, body.len().into());
+            (StatusCode::PARTIAL_CONTENT, headers, body).into_response()
+        }
+        Some(None) => {
+            headers.insert(
+                header::CONTENT_RANGE,
+                header_value(format!("bytes */{len}")),
+            );
+            headers.insert(header::CONTENT_LENGTH, 0.into());
+            (StatusCode::RANGE_NOT_SATISFIABLE, headers).into_response()
+        }
+        None => {
+            headers.insert(header::CONTENT_LENGTH, len.into());
+            (StatusCode::OK, headers, bytes).into_response()
+        }
+    }
+}
+
+fn strong_etag(bytes: &[u8]) -> HeaderValue {
+    let checksum: String = Sha256::digest(bytes).encode_hex();
+    header_value(format!("\"{checksum}\""))
+}
+
+fn header_value(value: String) -> HeaderValue {
+    HeaderValue::try_from(value).expect("generated header values are always valid")
+}
+
+/// Checks whether an `If-None-Match` header value matches the given ETag,
+/// using the weak comparison mandated by RFC 9110.
+fn matches_etag(if_none_match: &HeaderValue, etag: &HeaderValue) -> bool {
+    let Ok(if_none_match) = if_none_match.to_str() else {
+        return false;
+    };
+
+    let etag = etag.to_str().unwrap_or_default();
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+/// Ranges are only applied if the `If-Range` header is missing, or if it
+/// contains the current ETag. Dates are not supported as validators, since
+/// the files are served from memory without a reliable modification date.
+fn if_range_matches(request_headers: &HeaderMap, etag: &HeaderValue) -> bool {
+    match request_headers.get(header::IF_RANGE) {
+        None => true,
+        Some(if_range) => if_range == etag,
+    }
+}
+
+/// A single byte range, as requested in a `Range` header.
+#[derive(Debug, PartialEq, Eq)]
+enum ByteRange {
+    /// `bytes=start-` or `bytes=start-end`
+    FromTo(u64, Option<u64>),
+    /// `bytes=-length`
+    Suffix(u64),
+}
+
+/// Parses a `Range` header value.
+///
+/// Only single ranges are supported. Requests for multiple ranges, or with
+/// invalid syntax, are answered with the full file, as allowed by RFC 9110.
+fn parse_range(value: &str) -> Option<ByteRange> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+
+    let (start, end) = spec.trim().split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+    if start.is_empty() {
+        return end.parse().ok().map(ByteRange::Suffix);
+    }
+
+    let start = start.parse().ok()?;
+    if end.is_empty() {
+        return Some(ByteRange::FromTo(start, None));
+    }
+
+    let end = end.parse().ok()?;
+    (start <= end).then_some(ByteRange::FromTo(start, Some(end)))
+}
+
+/// Resolves a requested range against the file length, returning `None` if
+/// the range is not satisfiable.
+fn satisfiable_range(range: ByteRange, len: u64) -> Option<RangeInclusive<u64>> {
+    if len == 0 {
+        return None;
+    }
+
+    match range {
+        ByteRange::FromTo(start, _) if start >= len => None,
+        ByteRange::FromTo(start, end) => {
+            let end = end.map_or(len - 1, |end| end.min(len - 1));
+            Some(start..=end)
+        }
+        ByteRange::Suffix(0) => None,
+        ByteRange::Suffix(suffix) => Some(len.saturating_sub(suffix)..=len - 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ranges() {
+        assert_eq!(
+            parse_range("bytes=0-99"),
+            Some(ByteRange::FromTo(0, Some(99)))
+        );
+        assert_eq!(
+            parse_range("bytes=100-"),
+            Some(ByteRange::FromTo(100, None))
+        );
+        assert_eq!(parse_range("bytes=-50"), Some(ByteRange::Suffix(50)));
+        assert_eq!(parse_range("bytes=5-1"), None);
+        assert_eq!(parse_range("bytes=0-1,5-9"), None);
+        assert_eq!(parse_range("items=0-1"), None);
+        assert_eq!(parse_range("bytes=a-b"), None);
+    }
+
+    #[test]
+    fn satisfiable_ranges() {
+        assert_eq!(
+            satisfiable_range(ByteRange::FromTo(0, Some(99)), 10),
+            Some(0..=9)
+        );
+        assert_eq!(
+            satisfiable_range(ByteRange::FromTo(3, None), 10),
+            Some(3..=9)
+        );
+        assert_eq!(satisfiable_range(ByteRange::FromTo(10, None), 10), None);
+        assert_eq!(satisfiable_range(ByteRange::Suffix(4), 10), Some(6..=9));
+        assert_eq!(satisfiable_range(ByteRange::Suffix(40), 10), Some(0..=9));
+        assert_eq!(satisfiable_range(ByteRange::Suffix(0), 10), None);
+        assert_eq!(satisfiable_range(ByteRange::FromTo(0, None), 0), None);
+    }
+
+    #[test]
+    fn etag_matching() {
+        let etag = HeaderValue::from_static("\"abc\"");
+        assert!(matches_etag(&HeaderValue::from_static("\"abc\""), &etag));
+        assert!(matches_etag(&HeaderValue::from_static("W/\"abc\""), &etag));
+        assert!(matches_etag(
+            &HeaderValue::from_static("\"x\", \"abc\""),
+            &etag
+        ));
+        assert!(matches_etag(&HeaderValue::from_static("*"), &etag));
+        assert!(!matches_etag(&HeaderValue::from_static("\"abcd\""), &etag));
+    }
+}
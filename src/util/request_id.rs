@@ -0,0 +1,125 @@
+//! Per-request IDs, which are used to correlate the log lines of an HTTP
+//! request with the log lines of the background jobs that it enqueued.
+//!
+//! The ID of the current request is available through [`RequestId::current()`]
+//! both in async code running inside of [`RequestId::scope()`], and in
+//! blocking code running inside of [`RequestId::in_scope()`].
+
+use rand::distributions::{Alphanumeric, DistString};
+use std::cell::RefCell;
+use std::fmt;
+use std::future::Future;
+
+/// The maximum length of request IDs that are accepted from the
+/// `X-Request-Id` header. Longer IDs are replaced by a generated one.
+const MAX_REQUEST_ID_LENGTH: usize = 200;
+
+tokio::task_local! {
+    static TASK_REQUEST_ID: RequestId;
+}
+
+thread_local! {
+    static THREAD_REQUEST_ID: RefCell<Option<RequestId>> = RefCell::new(None);
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestId(String);
+
+impl RequestId {
+    /// Uses the ID from the `X-Request-Id` header that is set by the router in
+    /// front of the application, or generates a random ID if it is missing or
+    /// invalid.
+    pub fn from_header(header: Option<&str>) -> Self {
+        header
+            .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LENGTH)
+            .filter(|id| id.bytes().all(|b| b.is_ascii_graphic()))
+            .map(|id| Self(id.to_string()))
+            .unwrap_or_else(Self::generate)
+    }
+
+    pub fn generate() -> Self {
+        Self(Alphanumeric.sample_string(&mut rand::thread_rng(), 32))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the ID of the request or background job that is currently
+    /// being handled, if any.
+    pub fn current() -> Option<Self> {
+        THREAD_REQUEST_ID
+            .with(|current| current.borrow().clone())
+            .or_else(|| TASK_REQUEST_ID.try_with(Clone::clone).ok())
+    }
+
+    /// Runs the future with this as the current request ID.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        TASK_REQUEST_ID.scope(self, future).await
+    }
+
+    /// Runs the blocking function with the given ID as the current request ID
+    /// of this thread.
+    pub fn in_scope<R>(request_id: Option<Self>, f: impl FnOnce() -> R) -> R {
+        struct ResetGuard(Option<RequestId>);
+
+        impl Drop for ResetGuard {
+            fn drop(&mut self) {
+                let previous = self.0.take();
+                THREAD_REQUEST_ID.with(|current| *current.borrow_mut() = previous);
+            }
+        }
+
+        let previous = THREAD_REQUEST_ID.with(|current| current.replace(request_id));
+        let _guard = ResetGuard(previous);
+        f()
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_header() {
+        let id = RequestId::from_header(Some("a1b2-c3"));
+        assert_eq!(id.as_str(), "a1b2-c3");
+
+        assert_eq!(RequestId::from_header(None).as_str().len(), 32);
+        assert_eq!(RequestId::from_header(Some("")).as_str().len(), 32);
+        assert_eq!(RequestId::from_header(Some("a b")).as_str().len(), 32);
+
+        let too_long = "a".repeat(MAX_REQUEST_ID_LENGTH + 1);
+        assert_eq!(RequestId::from_header(Some(&too_long)).as_str().len(), 32);
+    }
+
+    #[test]
+    fn in_scope() {
+        assert_eq!(RequestId::current(), None);
+
+        let outer = RequestId::from_header(Some("outer"));
+        let inner = RequestId::from_header(Some("inner"));
+        RequestId::in_scope(Some(outer.clone()), || {
+            assert_eq!(RequestId::current(), Some(outer.clone()));
+            RequestId::in_scope(Some(inner.clone()), || {
+                assert_eq!(RequestId::current(), Some(inner));
+            });
+            assert_eq!(RequestId::current(), Some(outer));
+        });
+
+        assert_eq!(RequestId::current(), None);
+    }
+
+    #[tokio::test]
+    async fn scope() {
+        let id = RequestId::from_header(Some("async"));
+        let current = id.clone().scope(async { RequestId::current() }).await;
+        assert_eq!(current, Some(id));
+    }
+}
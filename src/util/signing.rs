@@ -0,0 +1,53 @@
+//! HMAC signatures for links that have to work without being logged in, e.g.
+//! the links in notification emails.
+
+use ring::hmac;
+
+#[derive(Clone)]
+pub struct Signer {
+    key: hmac::Key,
+}
+
+impl Signer {
+    pub fn new(secret: &[u8]) -> Self {
+        Self {
+            key: hmac::Key::new(hmac::HMAC_SHA256, secret),
+        }
+    }
+
+    /// Returns the hex encoded signature of the message.
+    pub fn sign(&self, message: &str) -> String {
+        hex::encode(hmac::sign(&self.key, message.as_bytes()))
+    }
+
+    /// Checks the hex encoded signature of the message in constant time.
+    pub fn verify(&self, message: &str, signature: &str) -> bool {
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+
+        hmac::verify(&self.key, message.as_bytes(), &signature).is_ok()
+    }
+}
+
+impl std::fmt::Debug for Signer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Signer").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Signer;
+
+    #[test]
+    fn sign_and_verify() {
+        let signer = Signer::new(b"secret");
+        let signature = signer.sign("message");
+
+        assert!(signer.verify("message", &signature));
+        assert!(!signer.verify("other message", &signature));
+        assert!(!signer.verify("message", "not hex"));
+        assert!(!Signer::new(b"other secret").verify("message", &signature));
+    }
+}
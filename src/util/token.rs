@@ -1,7 +1,9 @@
 use diesel::{deserialize::FromSql, pg::Pg, serialize::ToSql, sql_types::Bytea};
-use rand::{distributions::Uniform, rngs::OsRng, Rng};
+use rand::rngs::{OsRng, StdRng};
+use rand::{distributions::Uniform, Rng};
 use secrecy::{ExposeSecret, SecretString, SecretVec};
 use sha2::{Digest, Sha256};
+use std::cell::RefCell;
 
 const TOKEN_LENGTH: usize = 32;
 
@@ -9,6 +11,38 @@ const TOKEN_LENGTH: usize = 32;
 /// revoke all the tokens, disrupting production users.
 const TOKEN_PREFIX: &str = "cio";
 
+thread_local! {
+    /// Replaces `OsRng` for the tokens generated on the current thread, see
+    /// [`seed_token_rng()`].
+    static SEEDED_RNG: RefCell<Option<StdRng>> = RefCell::new(None);
+}
+
+/// Makes the tokens generated on the current thread deterministic, until the
+/// returned guard is dropped.
+///
+/// This is only available in the test suite, see the `testing` feature. Since
+/// the RNG is thread-local, only tokens that are created directly by the test,
+/// e.g. via the test builders, are affected, but not the tokens created by
+/// request handlers.
+#[cfg(any(test, feature = "testing"))]
+pub fn seed_token_rng(seed: u64) -> SeededTokenRngGuard {
+    use rand::SeedableRng;
+
+    SEEDED_RNG.with(|rng| *rng.borrow_mut() = Some(StdRng::seed_from_u64(seed)));
+    SeededTokenRngGuard(())
+}
+
+#[cfg(any(test, feature = "testing"))]
+#[must_use = "the seeded RNG is reset when the guard is dropped"]
+pub struct SeededTokenRngGuard(());
+
+#[cfg(any(test, feature = "testing"))]
+impl Drop for SeededTokenRngGuard {
+    fn drop(&mut self) {
+        SEEDED_RNG.with(|rng| *rng.borrow_mut() = None);
+    }
+}
+
 #[derive(FromSqlRow, AsExpression)]
 #[diesel(sql_type = Bytea)]
 pub struct HashedToken(SecretVec<u8>);
@@ -78,11 +112,17 @@ impl ExposeSecret<String> for PlainToken {
 fn generate_secure_alphanumeric_string(len: usize) -> String {
     const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
 
-    OsRng
-        .sample_iter(Uniform::from(0..CHARS.len()))
-        .map(|idx| CHARS[idx] as char)
-        .take(len)
-        .collect()
+    fn sample(rng: impl Rng, len: usize) -> String {
+        rng.sample_iter(Uniform::from(0..CHARS.len()))
+            .map(|idx| CHARS[idx] as char)
+            .take(len)
+            .collect()
+    }
+
+    SEEDED_RNG.with(|rng| match rng.borrow_mut().as_mut() {
+        Some(rng) => sample(rng, len),
+        None => sample(OsRng, len),
+    })
 }
 
 #[cfg(test)]
@@ -103,6 +143,29 @@ mod tests {
         assert_eq!(parsed.0.expose_secret(), token.hashed().0.expose_secret());
     }
 
+    #[test]
+    fn test_seeded_rng() {
+        let first = {
+            let _guard = seed_token_rng(42);
+            [PlainToken::generate(), PlainToken::generate()]
+        };
+
+        let second = {
+            let _guard = seed_token_rng(42);
+            [PlainToken::generate(), PlainToken::generate()]
+        };
+
+        assert_eq!(first[0].expose_secret(), second[0].expose_secret());
+        assert_eq!(first[1].expose_secret(), second[1].expose_secret());
+        assert_ne!(first[0].expose_secret(), first[1].expose_secret());
+
+        // Without a seed the tokens are random again
+        assert_ne!(
+            PlainToken::generate().expose_secret(),
+            first[0].expose_secret()
+        );
+    }
+
     #[test]
     fn test_parse_no_kind() {
         assert!(HashedToken::parse("nokind").is_none());
@@ -1,40 +1,96 @@
+use crate::config::OpenTelemetryConfig;
+use opentelemetry::sdk::{trace, Resource};
+use opentelemetry::trace::TraceError;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
 use sentry::integrations::tracing::EventFilter;
 use tracing::Level;
 use tracing::Metadata;
 use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::{prelude::*, EnvFilter};
 
+mod json;
+
+pub use json::JsonLayer;
+
 /// Initializes the `tracing` logging framework.
 ///
 /// Regular CLI output is influenced by the
 /// [`RUST_LOG`](tracing_subscriber::filter::EnvFilter) environment variable.
+/// If the `LOG_FORMAT` environment variable is set to `json`, every log line is
+/// written as a JSON object instead, see [`JsonLayer`].
 ///
 /// This function also sets up the Sentry error reporting integration for the
 /// `tracing` framework, which is hardcoded to include all `INFO` level events.
+///
+/// If an OTLP endpoint is configured (see [`OpenTelemetryConfig`]), all `INFO`
+/// level spans are additionally exported via OpenTelemetry.
 pub fn init() {
     init_with_default_level(LevelFilter::ERROR)
 }
 
 pub fn init_with_default_level(level: LevelFilter) {
-    let env_filter = EnvFilter::builder()
-        .with_default_directive(level.into())
-        .from_env_lossy();
+    let env_filter = || {
+        EnvFilter::builder()
+            .with_default_directive(level.into())
+            .from_env_lossy()
+    };
 
-    let log_layer = tracing_subscriber::fmt::layer()
-        .compact()
-        .without_time()
-        .with_filter(env_filter);
+    let json_logs = dotenvy::var("LOG_FORMAT").map_or(false, |format| format == "json");
+
+    let log_layer = (!json_logs).then(|| {
+        tracing_subscriber::fmt::layer()
+            .compact()
+            .without_time()
+            .with_filter(env_filter())
+    });
+
+    let json_log_layer =
+        json_logs.then(|| JsonLayer::new(std::io::stdout).with_filter(env_filter()));
 
     let sentry_layer = sentry::integrations::tracing::layer()
         .event_filter(event_filter)
         .with_filter(LevelFilter::INFO);
 
+    let otel_config = OpenTelemetryConfig::from_environment();
+    let otel_layer = otel_config.endpoint.map(|endpoint| {
+        let tracer = otlp_tracer(endpoint, otel_config.service_name)
+            .expect("Failed to initialize OpenTelemetry exporter");
+
+        tracing_opentelemetry::layer()
+            .with_tracer(tracer)
+            .with_filter(LevelFilter::INFO)
+    });
+
     tracing_subscriber::registry()
         .with(log_layer)
+        .with(json_log_layer)
         .with(sentry_layer)
+        .with(otel_layer)
         .init();
 }
 
+/// Flushes all pending spans to the OpenTelemetry exporter, if configured.
+///
+/// This should be called before the process exits.
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+fn otlp_tracer(endpoint: String, service_name: String) -> Result<trace::Tracer, TraceError> {
+    let exporter = opentelemetry_otlp::new_exporter()
+        .http()
+        .with_endpoint(endpoint);
+
+    let resource = Resource::new([KeyValue::new("service.name", service_name)]);
+
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(exporter)
+        .with_trace_config(trace::config().with_resource(resource))
+        .install_batch(opentelemetry::runtime::TokioCurrentThread)
+}
+
 pub fn event_filter(metadata: &Metadata<'_>) -> EventFilter {
     match metadata.level() {
         &Level::ERROR if metadata.target() == "http" => EventFilter::Breadcrumb,
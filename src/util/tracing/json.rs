@@ -0,0 +1,190 @@
+//! A `tracing` layer that writes every event as a single line of JSON.
+//!
+//! Besides the fields of the event itself, every line contains the fields of
+//! all spans the event happened in, e.g. the `request_id` of the
+//! `http.request` span or the `job.id` of the `swirl.perform` span. This makes
+//! it possible to find all log lines of a request, including the log lines of
+//! the background jobs that it enqueued.
+
+use chrono::{SecondsFormat, Utc};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::io::Write;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+pub struct JsonLayer<W> {
+    make_writer: W,
+}
+
+impl<W> JsonLayer<W>
+where
+    W: for<'w> MakeWriter<'w> + 'static,
+{
+    pub fn new(make_writer: W) -> Self {
+        Self { make_writer }
+    }
+}
+
+/// The fields of a span, which are stored in its extensions.
+#[derive(Default)]
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().into(), format!("{value:?}").into());
+    }
+}
+
+impl<S, W> Layer<S> for JsonLayer<W>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + 'static,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+
+        let mut fields = JsonFields::default();
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+
+        let mut extensions = span.extensions_mut();
+        if let Some(fields) = extensions.get_mut::<JsonFields>() {
+            values.record(fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+
+        let mut line = Map::new();
+        let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        line.insert("timestamp".into(), timestamp.into());
+        line.insert("level".into(), metadata.level().as_str().into());
+        line.insert("target".into(), metadata.target().into());
+
+        // Fields of inner spans take precedence over the fields of outer spans
+        let mut span_names = Vec::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                span_names.push(Value::from(span.name()));
+                if let Some(fields) = span.extensions().get::<JsonFields>() {
+                    line.extend(fields.0.clone());
+                }
+            }
+        }
+        if !span_names.is_empty() {
+            line.insert("spans".into(), span_names.into());
+        }
+
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+        line.extend(fields.0);
+
+        let Ok(mut buffer) = serde_json::to_vec(&line) else {
+            return;
+        };
+        buffer.push(b'\n');
+
+        // There is nowhere to report errors of the logger itself
+        let _ = self.make_writer.make_writer().write_all(&buffer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::prelude::*;
+
+    #[derive(Clone, Default)]
+    struct TestWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for TestWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn log_lines(writer: &TestWriter) -> Vec<Value> {
+        let buffer = writer.0.lock().unwrap();
+        std::str::from_utf8(&buffer)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn events_include_span_fields() {
+        let writer = TestWriter::default();
+        let layer = JsonLayer::new({
+            let writer = writer.clone();
+            move || writer.clone()
+        });
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = info_span!(
+                "http.request",
+                request_id = "abc",
+                status = tracing::field::Empty
+            );
+            let _enter = span.enter();
+            span.record("status", 200);
+
+            let span = info_span!("swirl.perform", job.id = 42);
+            let _enter = span.enter();
+            info!(krate = "foo", retries = 3, "Publishing {}", "foo@1.0.0");
+        });
+
+        let lines = log_lines(&writer);
+        assert_eq!(lines.len(), 1);
+
+        let line = &lines[0];
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["message"], "Publishing foo@1.0.0");
+        assert_eq!(line["krate"], "foo");
+        assert_eq!(line["retries"], 3);
+        assert_eq!(line["request_id"], "abc");
+        assert_eq!(line["status"], 200);
+        assert_eq!(line["job.id"], 42);
+        assert_eq!(line["spans"], json!(["http.request", "swirl.perform"]));
+        assert!(line["timestamp"].is_string());
+    }
+}
@@ -0,0 +1,42 @@
+//! Unicode normalization of search queries and keywords.
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Applies the Unicode NFKC normalization and lowercases the text, so that
+/// compatibility characters like `ﬁ` or fullwidth `ｓｅｒｄｅ` and
+/// differently composed accents match their canonical form.
+pub fn fold(text: &str) -> String {
+    text.nfkc().collect::<String>().to_lowercase()
+}
+
+/// Returns the ASCII transliteration of the text, e.g. `naive` for `naïve`
+/// and `naivnyi` for `наивный`, or `None` if the text is ASCII already.
+pub fn transliterate(text: &str) -> Option<String> {
+    if text.is_ascii() {
+        return None;
+    }
+
+    Some(deunicode::deunicode(text).to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold() {
+        assert_eq!(fold("Serde"), "serde");
+        assert_eq!(fold("ｓｅｒｄｅ"), "serde");
+        assert_eq!(fold("ﬁle"), "file");
+        // `e` followed by a combining acute accent
+        assert_eq!(fold("cafe\u{301}"), "café");
+        assert_eq!(fold("НАИВНЫЙ"), "наивный");
+    }
+
+    #[test]
+    fn test_transliterate() {
+        assert_eq!(transliterate("serde"), None);
+        assert_eq!(transliterate("naïve").as_deref(), Some("naive"));
+        assert_eq!(transliterate("наивный").as_deref(), Some("naivnyi"));
+    }
+}
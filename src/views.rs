@@ -1,12 +1,16 @@
 use chrono::NaiveDateTime;
 use secrecy::ExposeSecret;
+use std::collections::HashMap;
 use url::Url;
 
 use crate::github;
 use crate::models::{
-    ApiToken, Category, Crate, CrateOwnerInvitation, CreatedApiToken, Dependency, DependencyKind,
-    Keyword, Owner, ReverseDependency, Team, TopVersions, User, Version, VersionDownload,
-    VersionOwnerAction,
+    AccountDeletion, Advisory, ApiToken, Category, CategoryTreeRow, Crate, CrateMaintenanceStatus,
+    CrateOwnerInvitation, CrateQuarantine, CreatedApiToken, Dependency, DependencyKind,
+    IndexChange, IndexSnapshot, Keyword, LegalHold, LegalHoldAction, NamespaceClaim, OAuthIdentity,
+    OrphanedFileReport, Owner, PublisherVerification, ReverseDependency, TakedownRequest, Team,
+    TopVersions, UploadLimit, User, UserDataExport, UserNotification, UserPasskey, UserSession,
+    Version, VersionDownload, VersionOwnerAction,
 };
 use crate::util::rfc3339;
 
@@ -49,6 +53,60 @@ impl From<Category> for EncodableCategory {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EncodableCategoryTreeNode {
+    pub id: String,
+    pub category: String,
+    pub slug: String,
+    pub description: String,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+    /// The number of crates in this category and all its subcategories
+    pub crates_cnt: i32,
+    /// The number of crates directly in this category
+    pub direct_crates_cnt: i32,
+    pub children: Vec<EncodableCategoryTreeNode>,
+}
+
+impl EncodableCategoryTreeNode {
+    /// Builds the nested category tree from a flat list of categories
+    pub fn from_rows(rows: Vec<CategoryTreeRow>) -> Vec<Self> {
+        let mut children_by_parent: HashMap<Option<i32>, Vec<CategoryTreeRow>> = HashMap::new();
+        for row in rows {
+            children_by_parent
+                .entry(row.parent_id)
+                .or_default()
+                .push(row);
+        }
+
+        fn build(
+            parent_id: Option<i32>,
+            children_by_parent: &mut HashMap<Option<i32>, Vec<CategoryTreeRow>>,
+        ) -> Vec<EncodableCategoryTreeNode> {
+            let rows = children_by_parent.remove(&parent_id).unwrap_or_default();
+            rows.into_iter()
+                .map(|row| {
+                    let children = build(Some(row.category.id), children_by_parent);
+                    let direct_crates_cnt = row.category.crates_cnt;
+                    let category = EncodableCategory::from(row.category);
+                    EncodableCategoryTreeNode {
+                        id: category.id,
+                        category: category.category,
+                        slug: category.slug,
+                        description: category.description,
+                        created_at: category.created_at,
+                        crates_cnt: row.total_crates_cnt,
+                        direct_crates_cnt,
+                        children,
+                    }
+                })
+                .collect()
+        }
+
+        build(None, &mut children_by_parent)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct EncodableCategoryWithSubcategories {
     pub id: String,
@@ -222,6 +280,24 @@ pub struct EncodableCrate {
     pub repository: Option<String>,
     pub links: EncodableCrateLinks,
     pub exact_match: bool,
+    /// The maintenance score computed by the `update_health_scores` job
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health_score: Option<i32>,
+    /// The verified domains and GitHub organizations of the crate owners
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified_publisher: Option<EncodableVerifiedPublisher>,
+    /// `pending` or `rejected` if the crate was published by a new account
+    /// and has not been approved yet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quarantine_status: Option<String>,
+    /// When the crate was flagged because all of its owners have been
+    /// inactive for a long time
+    #[serde(
+        default,
+        with = "rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub stale_since: Option<NaiveDateTime>,
 }
 
 impl EncodableCrate {
@@ -245,6 +321,7 @@ impl EncodableCrate {
             homepage,
             documentation,
             repository,
+            health_score,
             ..
         } = krate;
         let versions_link = match versions {
@@ -300,6 +377,10 @@ impl EncodableCrate {
             exact_match,
             description,
             repository,
+            health_score,
+            verified_publisher: None,
+            quarantine_status: None,
+            stale_since: None,
             links: EncodableCrateLinks {
                 version_downloads: format!("/api/v1/crates/{name}/downloads"),
                 versions: versions_link,
@@ -374,6 +455,41 @@ fn domain_is_subdomain(potential_subdomain: &str, root: &str) -> bool {
     potential_subdomain.ends_with(&root_with_prefix)
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct EncodableVerifiedPublisher {
+    pub domains: Vec<String>,
+    pub github_orgs: Vec<String>,
+}
+
+impl EncodableVerifiedPublisher {
+    /// Combines the verifications of all crate owners, returning `None` if
+    /// there are none.
+    pub fn from_verifications(
+        verifications: impl IntoIterator<Item = PublisherVerification>,
+    ) -> Option<Self> {
+        let mut domains = Vec::new();
+        let mut github_orgs = Vec::new();
+        for verification in verifications {
+            domains.extend(verification.domain);
+            github_orgs.extend(verification.github_org_login);
+        }
+
+        if domains.is_empty() && github_orgs.is_empty() {
+            return None;
+        }
+
+        domains.sort();
+        domains.dedup();
+        github_orgs.sort();
+        github_orgs.dedup();
+
+        Some(EncodableVerifiedPublisher {
+            domains,
+            github_orgs,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct EncodableCrateLinks {
     pub version_downloads: String,
@@ -404,12 +520,12 @@ impl From<Owner> for EncodableOwner {
                 gh_avatar,
                 ..
             }) => {
-                let url = format!("https://github.com/{gh_login}");
+                let url = User::github_url(&gh_login);
                 Self {
                     id,
                     login: gh_login,
                     avatar: gh_avatar,
-                    url: Some(url),
+                    url,
                     name,
                     kind: String::from("user"),
                 }
@@ -465,6 +581,365 @@ impl From<Team> for EncodableTeam {
     }
 }
 
+#[derive(Serialize, Debug)]
+pub struct EncodableLegalHold {
+    pub id: i32,
+    #[serde(rename = "crate")]
+    pub krate: String,
+    /// `None` if the whole crate is under legal hold
+    pub version: Option<String>,
+    pub takedown_request_id: Option<i32>,
+    pub reason: String,
+    pub notice_url: Option<String>,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "rfc3339::option")]
+    pub lifted_at: Option<NaiveDateTime>,
+    pub actions: Vec<EncodableLegalHoldAction>,
+}
+
+impl EncodableLegalHold {
+    pub fn from(
+        hold: LegalHold,
+        crate_name: String,
+        version: Option<String>,
+        actions: Vec<LegalHoldAction>,
+    ) -> Self {
+        let LegalHold {
+            id,
+            takedown_request_id,
+            reason,
+            notice_url,
+            created_at,
+            lifted_at,
+            ..
+        } = hold;
+
+        Self {
+            id,
+            krate: crate_name,
+            version,
+            takedown_request_id,
+            reason,
+            notice_url,
+            created_at,
+            lifted_at,
+            actions: actions.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncodableLegalHoldAction {
+    pub action: String,
+    pub note: Option<String>,
+    #[serde(with = "rfc3339")]
+    pub time: NaiveDateTime,
+}
+
+impl From<LegalHoldAction> for EncodableLegalHoldAction {
+    fn from(action: LegalHoldAction) -> Self {
+        Self {
+            action: action.action,
+            note: action.note,
+            time: action.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncodableTakedownRequest {
+    pub id: i32,
+    #[serde(rename = "crate")]
+    pub krate: String,
+    pub version: Option<String>,
+    pub complainant_name: String,
+    pub complainant_email: String,
+    pub description: String,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "rfc3339::option")]
+    pub resolved_at: Option<NaiveDateTime>,
+}
+
+impl From<TakedownRequest> for EncodableTakedownRequest {
+    fn from(request: TakedownRequest) -> Self {
+        let TakedownRequest {
+            id,
+            crate_name,
+            version,
+            complainant_name,
+            complainant_email,
+            description,
+            created_at,
+            resolved_at,
+        } = request;
+
+        Self {
+            id,
+            krate: crate_name,
+            version,
+            complainant_name,
+            complainant_email,
+            description,
+            created_at,
+            resolved_at,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncodableOrphanedFileReport {
+    pub id: i32,
+    pub dry_run: bool,
+    pub orphaned_count: i32,
+    pub deleted_count: i32,
+    /// Only the first 1000 paths are included
+    pub orphaned_files: Vec<String>,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+}
+
+impl From<OrphanedFileReport> for EncodableOrphanedFileReport {
+    fn from(report: OrphanedFileReport) -> Self {
+        let OrphanedFileReport {
+            id,
+            dry_run,
+            orphaned_count,
+            deleted_count,
+            orphaned_files,
+            created_at,
+        } = report;
+
+        Self {
+            id,
+            dry_run,
+            orphaned_count,
+            deleted_count,
+            orphaned_files,
+            created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncodableCrateQuarantine {
+    #[serde(rename = "crate")]
+    pub krate: String,
+    /// Login of the new account that published the crate, `None` if it has
+    /// been deleted
+    pub user: Option<String>,
+    pub status: String,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "rfc3339::option")]
+    pub reviewed_at: Option<NaiveDateTime>,
+    pub note: Option<String>,
+    /// A version of another crate with identical content, which suggests
+    /// that the crate is squatting or republishing the other crate
+    pub duplicate_of: Option<EncodableQuarantineDuplicate>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncodableQuarantineDuplicate {
+    #[serde(rename = "crate")]
+    pub krate: String,
+    pub num: String,
+}
+
+impl EncodableCrateQuarantine {
+    pub fn from(
+        quarantine: CrateQuarantine,
+        crate_name: String,
+        user_login: Option<String>,
+        duplicate_of: Option<(String, String)>,
+    ) -> Self {
+        let CrateQuarantine {
+            status,
+            created_at,
+            reviewed_at,
+            note,
+            ..
+        } = quarantine;
+
+        Self {
+            krate: crate_name,
+            user: user_login,
+            status,
+            created_at,
+            reviewed_at,
+            note,
+            duplicate_of: duplicate_of
+                .map(|(krate, num)| EncodableQuarantineDuplicate { krate, num }),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncodableMaintenanceStatus {
+    /// `true` if all owners of the crate have been inactive for a long time
+    pub stale: bool,
+    #[serde(with = "rfc3339::option")]
+    pub flagged_at: Option<NaiveDateTime>,
+    /// When an owner last confirmed that the crate is still maintained
+    #[serde(with = "rfc3339::option")]
+    pub confirmed_at: Option<NaiveDateTime>,
+}
+
+impl From<Option<CrateMaintenanceStatus>> for EncodableMaintenanceStatus {
+    fn from(status: Option<CrateMaintenanceStatus>) -> Self {
+        let flagged_at = status.as_ref().and_then(|status| status.flagged_at);
+        let confirmed_at = status.as_ref().and_then(|status| status.confirmed_at);
+
+        Self {
+            stale: flagged_at.is_some(),
+            flagged_at,
+            confirmed_at,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncodableUploadLimit {
+    #[serde(rename = "crate")]
+    pub krate: String,
+    pub max_upload_size: i64,
+    pub max_unpack_size: Option<i64>,
+    pub note: Option<String>,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "rfc3339")]
+    pub updated_at: NaiveDateTime,
+}
+
+impl EncodableUploadLimit {
+    pub fn from(limit: UploadLimit, crate_name: String) -> Self {
+        let UploadLimit {
+            max_upload_size,
+            max_unpack_size,
+            note,
+            created_at,
+            updated_at,
+            ..
+        } = limit;
+
+        Self {
+            krate: crate_name,
+            max_upload_size,
+            max_unpack_size,
+            note,
+            created_at,
+            updated_at,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncodableAdvisory {
+    #[serde(rename = "crate")]
+    pub krate: String,
+    pub id: String,
+    pub affected: String,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "rfc3339")]
+    pub updated_at: NaiveDateTime,
+}
+
+impl EncodableAdvisory {
+    pub fn from(advisory: Advisory, crate_name: String) -> Self {
+        let Advisory {
+            identifier,
+            affected,
+            created_at,
+            updated_at,
+            ..
+        } = advisory;
+
+        Self {
+            krate: crate_name,
+            id: identifier,
+            affected,
+            created_at,
+            updated_at,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncodableNamespaceClaim {
+    pub id: i32,
+    /// The glob pattern of the covered crate names, e.g. `acme-*`
+    pub pattern: String,
+    pub github_org: String,
+    pub verification_method: String,
+    pub domain: Option<String>,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "rfc3339::option")]
+    pub verified_at: Option<NaiveDateTime>,
+}
+
+impl From<NamespaceClaim> for EncodableNamespaceClaim {
+    fn from(claim: NamespaceClaim) -> Self {
+        let pattern = claim.pattern();
+        let NamespaceClaim {
+            id,
+            github_org_login,
+            verification_method,
+            domain,
+            created_at,
+            verified_at,
+            ..
+        } = claim;
+
+        EncodableNamespaceClaim {
+            id,
+            pattern,
+            github_org: github_org_login,
+            verification_method,
+            domain,
+            created_at,
+            verified_at,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncodablePublisherVerification {
+    pub id: i32,
+    pub method: String,
+    pub domain: Option<String>,
+    pub github_org: Option<String>,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "rfc3339::option")]
+    pub verified_at: Option<NaiveDateTime>,
+}
+
+impl From<PublisherVerification> for EncodablePublisherVerification {
+    fn from(verification: PublisherVerification) -> Self {
+        let PublisherVerification {
+            id,
+            method,
+            domain,
+            github_org_login,
+            created_at,
+            verified_at,
+            ..
+        } = verification;
+
+        EncodablePublisherVerification {
+            id,
+            method,
+            domain,
+            github_org: github_org_login,
+            created_at,
+            verified_at,
+        }
+    }
+}
+
 /// The serialization format for the `ApiToken` model with its token value.
 /// This should only be used when initially creating a new token to minimize
 /// the chance of token leaks.
@@ -485,6 +960,214 @@ impl From<CreatedApiToken> for EncodableApiTokenWithToken {
     }
 }
 
+#[derive(Serialize, Debug)]
+pub struct EncodableUserDataExport {
+    pub id: i32,
+    /// Either `pending` or `ready`
+    pub status: &'static str,
+    #[serde(with = "rfc3339")]
+    pub requested_at: NaiveDateTime,
+    #[serde(with = "rfc3339::option")]
+    pub completed_at: Option<NaiveDateTime>,
+    /// The path of the archive, once the export is ready
+    pub download_path: Option<&'static str>,
+}
+
+impl From<UserDataExport> for EncodableUserDataExport {
+    fn from(export: UserDataExport) -> Self {
+        let completed = export.is_completed();
+        Self {
+            id: export.id,
+            status: if completed { "ready" } else { "pending" },
+            requested_at: export.requested_at,
+            completed_at: export.completed_at,
+            download_path: completed.then_some("/api/v1/me/export/download"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncodableIndexSnapshot {
+    /// The sequence number of the last change that is included
+    pub sequence: i64,
+    pub crates: i32,
+    pub size: i64,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+    pub download_path: String,
+}
+
+impl From<IndexSnapshot> for EncodableIndexSnapshot {
+    fn from(snapshot: IndexSnapshot) -> Self {
+        Self {
+            sequence: snapshot.sequence,
+            crates: snapshot.crates,
+            size: snapshot.size,
+            created_at: snapshot.created_at,
+            download_path: format!("/api/v1/index/snapshots/{}/download", snapshot.sequence),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncodableIndexChange {
+    pub sequence: i64,
+    #[serde(rename = "crate")]
+    pub krate: String,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+}
+
+impl From<IndexChange> for EncodableIndexChange {
+    fn from(change: IndexChange) -> Self {
+        Self {
+            sequence: change.id,
+            krate: change.crate_name,
+            created_at: change.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncodableIndexSyncState {
+    /// Either `git` or `sparse`
+    pub target: String,
+    pub synced_sequence: i64,
+    #[serde(with = "rfc3339")]
+    pub synced_at: NaiveDateTime,
+}
+
+impl From<IndexSyncState> for EncodableIndexSyncState {
+    fn from(state: IndexSyncState) -> Self {
+        Self {
+            target: state.target,
+            synced_sequence: state.synced_sequence,
+            synced_at: state.synced_at,
+        }
+    }
+}
+
+/// The serialization format for the `AccountDeletion` model. The confirmation
+/// token is only ever sent by email.
+#[derive(Serialize, Debug)]
+pub struct EncodableAccountDeletion {
+    /// Either `unconfirmed`, `scheduled` or `completed`
+    pub status: &'static str,
+    #[serde(with = "rfc3339")]
+    pub requested_at: NaiveDateTime,
+    #[serde(with = "rfc3339::option")]
+    pub confirmed_at: Option<NaiveDateTime>,
+    #[serde(with = "rfc3339::option")]
+    pub scheduled_for: Option<NaiveDateTime>,
+}
+
+impl From<AccountDeletion> for EncodableAccountDeletion {
+    fn from(deletion: AccountDeletion) -> Self {
+        Self {
+            status: deletion.status(),
+            requested_at: deletion.requested_at,
+            confirmed_at: deletion.confirmed_at,
+            scheduled_for: deletion.scheduled_for,
+        }
+    }
+}
+
+/// The serialization format for the `UserSession` model.
+#[derive(Serialize, Debug)]
+pub struct EncodableUserSession {
+    pub id: i32,
+    pub user_agent: String,
+    pub ip_address: Option<String>,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "rfc3339")]
+    pub last_seen_at: NaiveDateTime,
+    /// Whether this is the session that made the request
+    pub current: bool,
+}
+
+impl EncodableUserSession {
+    pub fn from(session: UserSession, current_session_id: Option<i32>) -> Self {
+        Self {
+            id: session.id,
+            user_agent: session.user_agent,
+            ip_address: session.ip_address,
+            created_at: session.created_at,
+            last_seen_at: session.last_seen_at,
+            current: current_session_id == Some(session.id),
+        }
+    }
+}
+
+/// The serialization format for the `UserNotification` model.
+#[derive(Serialize, Debug)]
+pub struct EncodableUserNotification {
+    pub id: i32,
+    pub kind: String,
+    pub severity: String,
+    pub message: String,
+    pub details: serde_json::Value,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "rfc3339::option")]
+    pub emailed_at: Option<NaiveDateTime>,
+}
+
+impl From<UserNotification> for EncodableUserNotification {
+    fn from(notification: UserNotification) -> Self {
+        Self {
+            id: notification.id,
+            message: notification.message(),
+            kind: notification.kind,
+            severity: notification.severity,
+            details: notification.details,
+            created_at: notification.created_at,
+            emailed_at: notification.emailed_at,
+        }
+    }
+}
+
+/// The serialization format for the `OAuthIdentity` model.
+#[derive(Serialize, Debug)]
+pub struct EncodableOAuthIdentity {
+    pub provider: String,
+    pub login: String,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+}
+
+impl From<OAuthIdentity> for EncodableOAuthIdentity {
+    fn from(identity: OAuthIdentity) -> Self {
+        Self {
+            provider: identity.provider,
+            login: identity.login,
+            created_at: identity.created_at,
+        }
+    }
+}
+
+/// The serialization format for the `UserPasskey` model.
+#[derive(Serialize, Debug)]
+pub struct EncodableUserPasskey {
+    pub id: i32,
+    pub name: String,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "rfc3339::option")]
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+impl From<UserPasskey> for EncodableUserPasskey {
+    fn from(passkey: UserPasskey) -> Self {
+        Self {
+            id: passkey.id,
+            name: passkey.name,
+            created_at: passkey.created_at,
+            last_used_at: passkey.last_used_at,
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct OwnedCrate {
     pub id: i32,
@@ -528,7 +1211,7 @@ impl EncodablePrivateUser {
             gh_avatar,
             ..
         } = user;
-        let url = format!("https://github.com/{gh_login}");
+        let url = User::github_url(&gh_login);
 
         EncodablePrivateUser {
             id,
@@ -538,7 +1221,7 @@ impl EncodablePrivateUser {
             avatar: gh_avatar,
             login: gh_login,
             name,
-            url: Some(url),
+            url,
         }
     }
 }
@@ -564,13 +1247,13 @@ impl From<User> for EncodablePublicUser {
             gh_avatar,
             ..
         } = user;
-        let url = format!("https://github.com/{gh_login}");
+        let url = User::github_url(&gh_login);
         EncodablePublicUser {
             id,
             avatar: gh_avatar,
             login: gh_login,
             name,
-            url: Some(url),
+            url,
         }
     }
 }
@@ -606,6 +1289,9 @@ pub struct EncodableVersion {
     pub published_by: Option<EncodablePublicUser>,
     pub audit_actions: Vec<EncodableAuditAction>,
     pub checksum: String,
+    /// The checksum of the zstd-compressed crate file, if one has been created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum_zstd: Option<String>,
     pub rust_version: Option<String>,
 }
 
@@ -628,6 +1314,7 @@ impl EncodableVersion {
             crate_size,
             checksum,
             rust_version,
+            checksum_zstd,
             ..
         } = version;
 
@@ -652,6 +1339,7 @@ impl EncodableVersion {
             links,
             crate_size,
             checksum,
+            checksum_zstd,
             rust_version,
             published_by: published_by.map(User::into),
             audit_actions: audit_actions
@@ -776,6 +1464,7 @@ mod tests {
             },
             crate_size: Some(1234),
             checksum: String::new(),
+            checksum_zstd: None,
             rust_version: None,
             published_by: None,
             audit_actions: vec![EncodableAuditAction {
@@ -838,6 +1527,10 @@ mod tests {
                 reverse_dependencies: "".to_string(),
             },
             exact_match: false,
+            health_score: None,
+            verified_publisher: None,
+            quarantine_status: None,
+            stale_since: None,
         };
         let json = serde_json::to_string(&crt).unwrap();
         assert_some!(json
@@ -112,7 +112,7 @@ impl<'de> Deserialize<'de> for EncodableDependencyName {
 impl<'de> Deserialize<'de> for EncodableKeyword {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<EncodableKeyword, D::Error> {
         let s = String::deserialize(d)?;
-        if !CrateKeyword::valid_name(&s) {
+        if !CrateKeyword::valid_unicode_name(&s) {
             let value = de::Unexpected::Str(&s);
             let expected = "a valid keyword specifier";
             Err(de::Error::invalid_value(value, &expected))
@@ -0,0 +1,148 @@
+//! Backfill of artifacts that are missing for historical versions, like
+//! rendered readmes or checksums.
+
+use crate::admin::render_readmes::render_pkg_readme;
+use crate::background_jobs::{BackfillArtifactsJob, Environment, Job};
+use crate::models::{BackfillProgress, Version};
+use crate::schema::{crates, readme_renderings, versions};
+use crate::swirl::PerformError;
+use anyhow::Context;
+use diesel::prelude::*;
+use flate2::read::GzDecoder;
+use hex::ToHex;
+use sha2::{Digest, Sha256};
+use std::thread;
+use std::time::Duration;
+use tar::Archive;
+
+/// The name of the backfill in the `backfill_progress` table.
+pub const BACKFILL_ARTIFACTS: &str = "artifacts";
+
+/// Processes a single batch of versions that have no rendered readme or no
+/// checksum, and enqueues another job for the next batch.
+///
+/// The crate file of each version is downloaded from the storage, a missing
+/// checksum is computed from it, and a missing readme is rendered from the
+/// file that is referenced by the manifest and uploaded to the storage.
+///
+/// Since every batch runs in its own job, the progress is committed after
+/// each batch and is recorded in the `backfill_progress` table, so that the
+/// backfill can be paused and resumed later on. Versions without a readme
+/// file or without a crate file in the storage are skipped.
+#[instrument(skip_all, fields(batch_size = job.batch_size))]
+pub fn perform_backfill_artifacts(
+    conn: &mut PgConnection,
+    env: &Environment,
+    job: &BackfillArtifactsJob,
+) -> Result<(), PerformError> {
+    // Rate limit the backfill before anything is locked in the database
+    thread::sleep(Duration::from_millis(job.batch_delay_ms));
+
+    let progress = BackfillProgress::find_or_create(conn, BACKFILL_ARTIFACTS)?;
+    if progress.paused {
+        info!("Backfill is paused");
+        return Ok(());
+    }
+
+    let batch: Vec<(i32, String, String, String, bool)> = versions::table
+        .inner_join(crates::table)
+        .left_join(readme_renderings::table)
+        .filter(
+            readme_renderings::version_id
+                .is_null()
+                .or(versions::checksum.eq("")),
+        )
+        .filter(versions::staged_until.is_null())
+        .filter(versions::id.gt(progress.last_version_id))
+        .order(versions::id)
+        .select((
+            versions::id,
+            crates::name,
+            versions::num,
+            versions::checksum,
+            readme_renderings::version_id.nullable().is_not_null(),
+        ))
+        .limit(job.batch_size.into())
+        .load(conn)?;
+
+    let Some((last_version_id, ..)) = batch.last() else {
+        info!(
+            processed = progress.processed_count,
+            skipped = progress.skipped_count,
+            "Backfill of artifacts is complete"
+        );
+        return Ok(());
+    };
+    let last_version_id = *last_version_id;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to initialize tokio runtime")?;
+
+    let mut processed = 0;
+    let mut skipped = 0;
+
+    for (version_id, krate, num, checksum, has_readme) in batch {
+        let future = env.storage.download_crate_file(&krate, &num);
+        let bytes = match rt.block_on(future) {
+            Ok(bytes) => bytes,
+            Err(object_store::Error::NotFound { .. }) => {
+                warn!(%krate, %num, "Crate file is missing");
+                skipped += 1;
+                continue;
+            }
+            Err(error) => {
+                return Err(error).context("Failed to download crate file")?;
+            }
+        };
+
+        if checksum.trim().is_empty() {
+            let hash: String = Sha256::digest(&bytes).encode_hex();
+            diesel::update(versions::table.find(version_id))
+                .set(versions::checksum.eq(&hash))
+                .execute(conn)?;
+        }
+
+        if !has_readme {
+            let pkg_name = format!("{krate}-{num}");
+            let archive = Archive::new(GzDecoder::new(&*bytes));
+            let readme = match render_pkg_readme(archive, &pkg_name, &env.readme_policy) {
+                Ok(readme) => readme,
+                Err(error) => {
+                    warn!(%krate, %num, "Failed to render readme: {error:#}");
+                    skipped += 1;
+                    continue;
+                }
+            };
+
+            if !readme.is_empty() {
+                let future = env.storage.upload_readme(&krate, &num, readme.into());
+                rt.block_on(future).context("Failed to upload readme")?;
+            }
+
+            Version::record_readme_rendering(version_id, conn)?;
+        }
+
+        processed += 1;
+    }
+
+    let progress = BackfillProgress::record_batch(
+        conn,
+        BACKFILL_ARTIFACTS,
+        last_version_id,
+        processed,
+        skipped,
+    )?;
+
+    info!(
+        last_version_id,
+        processed = progress.processed_count,
+        skipped = progress.skipped_count,
+        "Backfilled artifacts batch"
+    );
+
+    Job::backfill_artifacts(job.batch_size, job.batch_delay_ms).enqueue(conn)?;
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+//! Migration of crate files to the content-addressed storage layout.
+
+use crate::background_jobs::Environment;
+use crate::models::VersionObject;
+use crate::schema::{crates, version_objects, versions};
+use crate::swirl::PerformError;
+use anyhow::Context;
+use diesel::prelude::*;
+use hex::ToHex;
+use sha2::{Digest, Sha256};
+
+/// The number of versions that are loaded from the database at once.
+const BATCH_SIZE: i64 = 100;
+
+/// Copies the crate files of all versions that are only stored in the legacy
+/// layout to the content-addressed layout, and records their object hashes.
+///
+/// Every crate file is checked against the checksum in the `versions` table,
+/// so the backfill doubles as an integrity audit of the file storage. Crate
+/// files that are missing or do not match their checksum are logged and
+/// skipped. The crate files in the legacy layout are not deleted.
+#[instrument(skip_all)]
+pub fn perform_backfill_crate_objects(
+    conn: &mut PgConnection,
+    env: &Environment,
+) -> Result<(), PerformError> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to initialize tokio runtime")?;
+
+    let mut last_version_id = 0;
+    let mut uploaded = 0;
+    let mut deduplicated = 0;
+    let mut skipped = 0;
+
+    loop {
+        let batch: Vec<(i32, String, String, String)> = versions::table
+            .inner_join(crates::table)
+            .left_join(version_objects::table)
+            .filter(version_objects::version_id.is_null())
+            .filter(versions::staged_until.is_null())
+            .filter(versions::id.gt(last_version_id))
+            .order(versions::id)
+            .select((
+                versions::id,
+                crates::name,
+                versions::num,
+                versions::checksum,
+            ))
+            .limit(BATCH_SIZE)
+            .load(conn)?;
+
+        let Some((version_id, ..)) = batch.last() else {
+            break;
+        };
+        last_version_id = *version_id;
+
+        for (version_id, krate, num, checksum) in batch {
+            let future = env.storage.download_crate_file(&krate, &num);
+            let bytes = match rt.block_on(future) {
+                Ok(bytes) => bytes,
+                Err(object_store::Error::NotFound { .. }) => {
+                    warn!(%krate, %num, "Crate file is missing");
+                    skipped += 1;
+                    continue;
+                }
+                Err(error) => {
+                    return Err(error).context("Failed to download crate file")?;
+                }
+            };
+
+            let hash: String = Sha256::digest(&bytes).encode_hex();
+            if hash != checksum.trim() {
+                warn!(%krate, %num, %checksum, %hash, "Crate file does not match its checksum");
+                skipped += 1;
+                continue;
+            }
+
+            let future = env.storage.upload_crate_object(&hash, bytes);
+            let is_new_object = rt
+                .block_on(future)
+                .context("Failed to upload crate object")?;
+
+            if is_new_object {
+                uploaded += 1;
+            } else {
+                deduplicated += 1;
+            }
+
+            VersionObject::insert(conn, version_id, &hash)?;
+        }
+    }
+
+    info!(uploaded, deduplicated, skipped, "Backfilled crate objects");
+
+    Ok(())
+}
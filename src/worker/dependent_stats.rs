@@ -0,0 +1,26 @@
+use crate::schema::crate_dependent_stats;
+use crate::swirl::PerformError;
+use diesel::prelude::*;
+use diesel::sql_types::Integer;
+
+/// The number of dependents that are recorded for each crate.
+const TOP_DEPENDENTS: i32 = 10;
+
+/// Recomputes the most downloaded dependents of all crates, and their
+/// estimated share of the crate's downloads, in the `crate_dependent_stats`
+/// table, which backs the dependents stats endpoint.
+#[instrument(skip_all)]
+pub fn perform_update_dependent_stats(conn: &mut PgConnection) -> Result<(), PerformError> {
+    info!("Updating dependent stats");
+
+    let rows = conn.transaction(|conn| {
+        diesel::delete(crate_dependent_stats::table).execute(conn)?;
+
+        diesel::sql_query(include_str!("update_dependent_stats.sql"))
+            .bind::<Integer, _>(TOP_DEPENDENTS)
+            .execute(conn)
+    })?;
+
+    info!(rows, "Finished updating dependent stats");
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use crate::background_jobs::{Environment, NormalizeIndexJob};
-use crate::models;
+use crate::models::{self, IndexSyncState, IndexTarget};
 use crate::swirl::PerformError;
 use anyhow::Context;
 use chrono::Utc;
@@ -11,12 +11,17 @@ use std::io::{BufRead, BufReader, ErrorKind, Write};
 use std::process::Command;
 
 /// Regenerates or removes an index file for a single crate
-#[instrument(skip_all, fields(krate.name = ?krate))]
+#[instrument(skip_all, fields(krate.name = ?krate, sequence = ?sequence))]
 pub fn sync_to_git_index(
     env: &Environment,
     conn: &mut PgConnection,
     krate: &str,
+    sequence: Option<i64>,
 ) -> Result<(), PerformError> {
+    let Some(state) = claim_index_sync(conn, krate, IndexTarget::Git, sequence)? else {
+        return Ok(());
+    };
+
     info!("Syncing to git index");
 
     let new = get_index_data(krate, conn).context("Failed to get index data")?;
@@ -50,16 +55,25 @@ pub fn sync_to_git_index(
         _ => debug!("Skipping sync because index is up-to-date"),
     }
 
+    if let Some(sequence) = sequence {
+        state.mark_synced(conn, sequence)?;
+    }
+
     Ok(())
 }
 
 /// Regenerates or removes an index file for a single crate
-#[instrument(skip_all, fields(krate.name = ?krate))]
+#[instrument(skip_all, fields(krate.name = ?krate, sequence = ?sequence))]
 pub fn sync_to_sparse_index(
     env: &Environment,
     conn: &mut PgConnection,
     krate: &str,
+    sequence: Option<i64>,
 ) -> Result<(), PerformError> {
+    let Some(state) = claim_index_sync(conn, krate, IndexTarget::Sparse, sequence)? else {
+        return Ok(());
+    };
+
     info!("Syncing to sparse index");
 
     let content = get_index_data(krate, conn).context("Failed to get index data")?;
@@ -73,6 +87,9 @@ pub fn sync_to_sparse_index(
     let future = env.storage.sync_index(krate, content);
     rt.block_on(future).context("Failed to sync index data")?;
 
+    // Mirrors follow the change feed to update their copy of the index
+    models::IndexChange::record(conn, krate).context("Failed to record index change")?;
+
     if let Some(cloudfront) = env.cloudfront() {
         let path = Repository::relative_index_file_for_url(krate);
 
@@ -82,9 +99,49 @@ pub fn sync_to_sparse_index(
             .context("Failed to invalidate CloudFront")?;
     }
 
+    if let Some(sequence) = sequence {
+        state.mark_synced(conn, sequence)?;
+    }
+
     Ok(())
 }
 
+/// Locks the sync state of the crate, so that the index sync jobs of a crate
+/// are performed one after another, and checks whether the job still needs
+/// to be performed.
+///
+/// Since the index file is always regenerated from the current database state,
+/// a job can be skipped if a job with a higher sequence number already synced
+/// the index file, or is waiting to do so.
+fn claim_index_sync(
+    conn: &mut PgConnection,
+    krate: &str,
+    target: IndexTarget,
+    sequence: Option<i64>,
+) -> QueryResult<Option<IndexSyncState>> {
+    let state = IndexSyncState::lock(conn, krate, target)?;
+
+    let Some(sequence) = sequence else {
+        return Ok(Some(state));
+    };
+
+    if sequence <= state.synced_sequence {
+        info!(
+            synced_sequence = state.synced_sequence,
+            "Skipping duplicate or out-of-order index sync"
+        );
+        return Ok(None);
+    }
+
+    let latest_sequence = models::latest_index_sequence(conn, krate)?;
+    if sequence < latest_sequence {
+        info!(%latest_sequence, "Skipping index sync that was superseded by a newer one");
+        return Ok(None);
+    }
+
+    Ok(Some(state))
+}
+
 #[instrument(skip_all, fields(krate.name = ?name))]
 pub fn get_index_data(name: &str, conn: &mut PgConnection) -> anyhow::Result<Option<String>> {
     debug!("Looking up crate by name");
@@ -94,6 +151,13 @@ pub fn get_index_data(name: &str, conn: &mut PgConnection) -> anyhow::Result<Opt
         return Ok(None);
     };
 
+    // Crates under legal hold are removed from the index until the hold is
+    // lifted, see `perform_sync_legal_hold()`
+    if models::LegalHold::crate_held(conn, krate.id)? {
+        info!("Crate is under legal hold");
+        return Ok(None);
+    }
+
     debug!("Gathering remaining index data");
     let crates = krate
         .index_metadata(conn)
@@ -0,0 +1,25 @@
+use crate::models::{health_score, CrateReleaseStats};
+use crate::swirl::PerformError;
+use diesel::prelude::*;
+use diesel::sql_types::{Array, Integer};
+
+/// Recomputes the health score of all crates and stores it in the
+/// `crates.health_score` column.
+#[instrument(skip_all)]
+pub fn perform_update_health_scores(conn: &mut PgConnection) -> Result<(), PerformError> {
+    info!("Updating crate health scores");
+
+    let now = chrono::Utc::now().naive_utc();
+    let (crate_ids, scores): (Vec<i32>, Vec<i32>) = CrateReleaseStats::load(conn, None)?
+        .iter()
+        .map(|stats| (stats.crate_id, health_score(stats, now)))
+        .unzip();
+
+    let rows = diesel::sql_query(include_str!("update_health_scores.sql"))
+        .bind::<Array<Integer>, _>(crate_ids)
+        .bind::<Array<Integer>, _>(scores)
+        .execute(conn)?;
+
+    info!(rows, "Finished updating crate health scores");
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use crate::models::PublishIdempotencyKey;
+use crate::swirl::PerformError;
+use diesel::prelude::*;
+
+/// Deletes the idempotency keys of publish requests that are older than the
+/// retention period, after which the requests can not be retried anymore.
+#[instrument(skip_all)]
+pub fn perform_cleanup_idempotency_keys(conn: &mut PgConnection) -> Result<(), PerformError> {
+    let deleted = PublishIdempotencyKey::delete_expired(conn)?;
+    info!(deleted, "Deleted expired idempotency keys");
+
+    Ok(())
+}
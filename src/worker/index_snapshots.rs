@@ -0,0 +1,170 @@
+//! Snapshots of the sparse index, which new mirrors download instead of
+//! crawling the index file of every crate.
+
+use crate::background_jobs::Environment;
+use crate::models::{IndexChange, IndexSnapshot};
+use crate::swirl::PerformError;
+use crate::util::rfc3339;
+use anyhow::Context;
+use chrono::{NaiveDateTime, Utc};
+use crates_io_index::Repository;
+use diesel::prelude::*;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+/// The number of snapshots that are kept in the file storage. The change feed
+/// is retained back to the sequence number of the oldest one.
+const SNAPSHOTS_TO_KEEP: usize = 2;
+
+/// Bundles all files of the sparse index into a `.tar.gz` archive, together
+/// with a `manifest.json` file that contains the sequence number of the last
+/// change that is included.
+///
+/// Mirrors download the latest snapshot and then follow the change feed from
+/// its sequence number. Any change that was recorded while the snapshot was
+/// built is replayed via the change feed, which is harmless since the index
+/// files are always fetched in full.
+#[instrument(skip_all)]
+pub fn perform_create_index_snapshot(
+    env: &Environment,
+    conn: &mut PgConnection,
+) -> Result<(), PerformError> {
+    let sequence = IndexChange::settled_sequence(conn)?;
+
+    let latest = IndexSnapshot::latest(conn)?;
+    if latest.map_or(false, |snapshot| snapshot.sequence >= sequence) {
+        debug!(%sequence, "Index snapshot is up to date");
+        return Ok(());
+    }
+
+    info!(%sequence, "Creating index snapshot");
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to initialize tokio runtime")?;
+
+    let paths = rt
+        .block_on(env.storage.list_index_files())
+        .context("Failed to list index files")?;
+
+    // Only the files of crates are included, e.g. not the `config.json` file
+    let mut files = Vec::with_capacity(paths.len());
+    for path in paths {
+        let name = path.filename().unwrap_or_default().to_string();
+        if Repository::relative_index_file_for_url(&name) != path.as_ref() {
+            continue;
+        }
+
+        let content = rt
+            .block_on(env.storage.download_index_file(&name))
+            .with_context(|| format!("Failed to download index file of {name}"))?;
+
+        files.push((path.to_string(), content));
+    }
+
+    let created_at = Utc::now().naive_utc();
+    let archive = build_archive(sequence, created_at, &files)
+        .context("Failed to build index snapshot archive")?;
+
+    let snapshot = IndexSnapshot {
+        sequence,
+        crates: files.len() as i32,
+        size: archive.len() as i64,
+        created_at,
+    };
+
+    let future = env.storage.upload_index_snapshot(sequence, archive.into());
+    rt.block_on(future)
+        .context("Failed to upload index snapshot")?;
+
+    snapshot.insert(conn)?;
+
+    info!(%sequence, crates = snapshot.crates, "Created index snapshot");
+
+    let snapshots = IndexSnapshot::all(conn)?;
+    for outdated in snapshots.iter().skip(SNAPSHOTS_TO_KEEP) {
+        let future = env.storage.delete_index_snapshot(outdated.sequence);
+        rt.block_on(future)
+            .context("Failed to delete outdated index snapshot")?;
+
+        outdated.delete(conn)?;
+    }
+
+    // Mirrors that are older than the oldest snapshot need to start over
+    if let Some(oldest) = snapshots.iter().take(SNAPSHOTS_TO_KEEP).last() {
+        let pruned = IndexChange::prune(conn, oldest.sequence)?;
+        debug!(%pruned, "Pruned index changes");
+    }
+
+    Ok(())
+}
+
+fn build_archive(
+    sequence: i64,
+    created_at: NaiveDateTime,
+    files: &[(String, hyper::body::Bytes)],
+) -> anyhow::Result<Vec<u8>> {
+    let encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let mut archive = tar::Builder::new(encoder);
+
+    let mtime = created_at.timestamp() as u64;
+    let mut append = |path: &str, content: &[u8]| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        header.set_cksum();
+
+        archive.append_data(&mut header, path, content)
+    };
+
+    // The manifest comes first, so that it can be read without unpacking the
+    // whole archive
+    let manifest = serde_json::to_vec_pretty(&json!({
+        "sequence": sequence,
+        "crates": files.len(),
+        "created_at": rfc3339::serialize(&created_at, serde_json::value::Serializer)?,
+    }))?;
+    append("manifest.json", &manifest)?;
+
+    for (path, content) in files {
+        append(path, content)?;
+    }
+
+    Ok(archive.into_inner()?.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn archive_starts_with_manifest() {
+        let created_at = Utc::now().naive_utc();
+        let files = vec![
+            ("3/f/foo".to_string(), "foo".into()),
+            ("se/rd/serde".to_string(), "serde".into()),
+        ];
+
+        let archive = build_archive(42, created_at, &files).unwrap();
+
+        let mut archive = tar::Archive::new(GzDecoder::new(archive.as_slice()));
+        let mut entries = archive.entries().unwrap();
+
+        let mut manifest = entries.next().unwrap().unwrap();
+        assert_eq!(manifest.path().unwrap().to_str(), Some("manifest.json"));
+        let mut content = String::new();
+        manifest.read_to_string(&mut content).unwrap();
+        let manifest: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(manifest["sequence"], 42);
+        assert_eq!(manifest["crates"], 2);
+
+        let paths = entries
+            .map(|entry| entry.unwrap().path().unwrap().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["3/f/foo", "se/rd/serde"]);
+    }
+}
@@ -0,0 +1,12 @@
+use crate::swirl::PerformError;
+use diesel::{sql_query, PgConnection, RunQueryDsl};
+
+/// Updates the daily number of crates and downloads per keyword in the
+/// `keyword_stats` table, which backs the keyword trends endpoint.
+#[instrument(skip_all)]
+pub fn perform_update_keyword_stats(conn: &mut PgConnection) -> Result<(), PerformError> {
+    info!("Updating keyword stats");
+    let rows = sql_query(include_str!("update_keyword_stats.sql")).execute(conn)?;
+    info!(rows, "Finished updating keyword stats");
+    Ok(())
+}
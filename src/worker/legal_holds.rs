@@ -0,0 +1,109 @@
+//! Keeps the crate files and the index in sync with the legal holds, see
+//! [`LegalHold`].
+
+use crate::background_jobs::{Environment, Job};
+use crate::models::LegalHold;
+use crate::schema::{crates, legal_holds, version_objects, versions};
+use crate::swirl::PerformError;
+use anyhow::Context;
+use diesel::dsl::exists;
+use diesel::prelude::*;
+
+/// Moves the crate files of the versions affected by a legal hold out of
+/// their public locations, or back once the hold has been lifted, purges
+/// them from the CDNs, and updates the index of the crate.
+///
+/// Versions that are still under another active legal hold are not restored
+/// when the hold is lifted.
+#[instrument(skip_all, fields(hold.id = hold_id))]
+pub fn perform_sync_legal_hold(
+    env: &Environment,
+    conn: &mut PgConnection,
+    hold_id: i32,
+) -> Result<(), PerformError> {
+    let hold: LegalHold = legal_holds::table.find(hold_id).first(conn)?;
+    let crate_name: String = crates::table
+        .find(hold.crate_id)
+        .select(crates::name)
+        .first(conn)?;
+
+    // Staged versions are not publicly available anyway
+    let mut query = versions::table
+        .filter(versions::crate_id.eq(hold.crate_id))
+        .filter(versions::staged_until.is_null())
+        .select((versions::id, versions::num))
+        .into_boxed();
+    if let Some(version_id) = hold.version_id {
+        query = query.filter(versions::id.eq(version_id));
+    }
+    let versions: Vec<(i32, String)> = query.load(conn)?;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to initialize tokio runtime")?;
+
+    let mut paths = Vec::new();
+    for (version_id, version) in versions {
+        let held = LegalHold::active_for_version(conn, hold.crate_id, version_id)?.is_some();
+        let object_hash = unshared_object_hash(conn, version_id)?;
+        let object_hash = object_hash.as_deref();
+
+        info!(%crate_name, %version, held, "Syncing crate files with legal hold");
+        let moved = if held {
+            rt.block_on(
+                env.storage
+                    .withhold_crate_files(&crate_name, &version, object_hash),
+            )
+        } else {
+            rt.block_on(
+                env.storage
+                    .restore_crate_files(&crate_name, &version, object_hash),
+            )
+        };
+        paths.extend(moved.context("Failed to move crate files")?);
+    }
+
+    for path in paths {
+        if let Some(cloudfront) = env.cloudfront() {
+            cloudfront
+                .invalidate(env.http_client(), path.as_ref())
+                .context("Failed to invalidate CloudFront")?;
+        }
+
+        if let Some(fastly) = env.fastly() {
+            fastly
+                .invalidate(env.http_client(), path.as_ref())
+                .context("Failed to invalidate Fastly")?;
+        }
+    }
+
+    // Versions under legal hold are omitted from the index
+    Job::enqueue_sync_to_index(&crate_name, conn)?;
+
+    Ok(())
+}
+
+/// Returns the hash of the content-addressed crate file of the version,
+/// unless other versions have the same content, which then has to stay
+/// available for them.
+fn unshared_object_hash(conn: &mut PgConnection, version_id: i32) -> QueryResult<Option<String>> {
+    let hash: Option<String> = version_objects::table
+        .filter(version_objects::version_id.eq(version_id))
+        .select(version_objects::object_hash)
+        .first(conn)
+        .optional()?;
+
+    let Some(hash) = hash else {
+        return Ok(None);
+    };
+
+    let shared = diesel::select(exists(
+        version_objects::table
+            .filter(version_objects::object_hash.eq(&hash))
+            .filter(version_objects::version_id.ne(version_id)),
+    ))
+    .get_result::<bool>(conn)?;
+
+    Ok((!shared).then_some(hash))
+}
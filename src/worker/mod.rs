@@ -3,18 +3,54 @@
 //! the daily database maintenance, but also operations like rendering READMEs
 //! and uploading them to S3.
 
+mod backfill_artifacts;
 pub mod cloudfront;
+mod crate_objects;
 mod daily_db_maintenance;
+mod dependent_stats;
 pub mod dump_db;
 pub mod fastly;
 mod git;
+mod health_scores;
+mod idempotency_keys;
+mod index_snapshots;
+mod keyword_stats;
+mod legal_holds;
+mod orphaned_files;
+mod publish_alerts;
 mod readmes;
+mod recompress;
+mod retention;
+mod security_notifications;
+mod staged_versions;
+mod stale_crates;
+mod storage_replicas;
+mod subscriptions;
 mod update_downloads;
+mod user_data;
 
+pub(crate) use backfill_artifacts::{perform_backfill_artifacts, BACKFILL_ARTIFACTS};
+pub(crate) use crate_objects::perform_backfill_crate_objects;
 pub(crate) use daily_db_maintenance::perform_daily_db_maintenance;
+pub(crate) use dependent_stats::perform_update_dependent_stats;
 pub(crate) use dump_db::perform_dump_db;
 pub(crate) use git::{
     perform_index_squash, perform_normalize_index, sync_to_git_index, sync_to_sparse_index,
 };
+pub(crate) use health_scores::perform_update_health_scores;
+pub(crate) use idempotency_keys::perform_cleanup_idempotency_keys;
+pub(crate) use index_snapshots::perform_create_index_snapshot;
+pub(crate) use keyword_stats::perform_update_keyword_stats;
+pub(crate) use legal_holds::perform_sync_legal_hold;
+pub(crate) use orphaned_files::perform_cleanup_orphaned_files;
+pub(crate) use publish_alerts::perform_check_publish;
 pub(crate) use readmes::perform_render_and_upload_readme;
+pub(crate) use recompress::perform_recompress_crate_file;
+pub(crate) use retention::perform_purge_expired_data;
+pub(crate) use security_notifications::perform_send_security_notification_digests;
+pub(crate) use staged_versions::perform_promote_staged_versions;
+pub(crate) use stale_crates::perform_flag_stale_crates;
+pub(crate) use storage_replicas::perform_reconcile_storage_replicas;
+pub(crate) use subscriptions::perform_send_crate_notification_digests;
 pub(crate) use update_downloads::perform_update_downloads;
+pub(crate) use user_data::{perform_export_user_data, perform_purge_deleted_accounts};
@@ -0,0 +1,123 @@
+//! Cleanup of files in the storage bucket without a corresponding database
+//! record, e.g. crate files that were uploaded by a publish that failed
+//! afterwards.
+
+use crate::background_jobs::Environment;
+use crate::models::NewOrphanedFileReport;
+use crate::schema::{crates, user_data_exports, version_objects, versions};
+use crate::storage::StoredFile;
+use crate::swirl::PerformError;
+use anyhow::Context;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use std::collections::HashSet;
+
+/// The maximum number of orphaned file paths that are saved in a report.
+const MAX_REPORTED_FILES: usize = 1000;
+
+/// Lists all files in the storage bucket and reports the crate files,
+/// readmes, staged crate files and user data exports that do not belong to
+/// any version or export in the database. Unless `dry_run` is set, the
+/// orphaned files are deleted too.
+///
+/// Files that were modified within the grace period are skipped, since the
+/// crate files of a publish are uploaded before its database transaction is
+/// committed. Files outside of the known prefixes are never touched.
+///
+/// This includes the database dumps, which are not reported either: they have
+/// no database records that they could be orphaned from, since the `dump_db`
+/// job replaces the file at its target name in place. An old dump is still
+/// the latest dump for its target name, and is publicly linked to, so it is
+/// never stale in the sense of this cleanup.
+#[instrument(skip(conn, env))]
+pub fn perform_cleanup_orphaned_files(
+    conn: &mut PgConnection,
+    env: &Environment,
+    dry_run: bool,
+    grace_period_hours: u32,
+) -> Result<(), PerformError> {
+    let cutoff = Utc::now() - Duration::hours(grace_period_hours.into());
+
+    let versions: HashSet<(String, String)> = versions::table
+        .inner_join(crates::table)
+        .select((crates::name, versions::num))
+        .load::<(String, String)>(conn)?
+        .into_iter()
+        .collect();
+
+    let staged_versions: HashSet<(String, String)> = versions::table
+        .inner_join(crates::table)
+        .filter(versions::staged_until.is_not_null())
+        .select((crates::name, versions::num))
+        .load::<(String, String)>(conn)?
+        .into_iter()
+        .collect();
+
+    let object_hashes: HashSet<String> = version_objects::table
+        .select(version_objects::object_hash)
+        .load::<String>(conn)?
+        .into_iter()
+        .collect();
+
+    let exports: HashSet<(i32, i32)> = user_data_exports::table
+        .select((user_data_exports::user_id, user_data_exports::id))
+        .load::<(i32, i32)>(conn)?
+        .into_iter()
+        .collect();
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to initialize tokio runtime")?;
+
+    let files = rt
+        .block_on(env.storage.list_files())
+        .context("Failed to list files")?;
+
+    let orphaned_files = files
+        .into_iter()
+        .filter(|file| file.last_modified < cutoff)
+        .map(|file| file.location)
+        .filter(|path| match StoredFile::from_path(path) {
+            Some(StoredFile::CrateFile { name, version })
+            | Some(StoredFile::Readme { name, version }) => !versions.contains(&(name, version)),
+            Some(StoredFile::StagedCrateFile { name, version }) => {
+                !staged_versions.contains(&(name, version))
+            }
+            Some(StoredFile::CrateObject { hash }) => !object_hashes.contains(&hash),
+            Some(StoredFile::UserExport { user_id, export_id }) => {
+                !exports.contains(&(user_id, export_id))
+            }
+            None => false,
+        })
+        .collect::<Vec<_>>();
+
+    info!(count = orphaned_files.len(), "Found orphaned files");
+
+    let mut deleted_count = 0;
+    if !dry_run {
+        for path in &orphaned_files {
+            // Failing to delete a single file should not prevent deleting the others
+            match rt.block_on(env.storage.delete_file(path)) {
+                Ok(()) => deleted_count += 1,
+                Err(error) => warn!(%path, ?error, "Failed to delete orphaned file"),
+            }
+        }
+    }
+
+    let reported_files = orphaned_files
+        .iter()
+        .take(MAX_REPORTED_FILES)
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    NewOrphanedFileReport {
+        dry_run,
+        orphaned_count: orphaned_files.len() as i32,
+        deleted_count,
+        orphaned_files: &reported_files,
+    }
+    .insert(conn)?;
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+//! Detection of suspicious publishes, e.g. with a stolen API token.
+
+use crate::background_jobs::Environment;
+use crate::models::{Crate, Owner, PublishAlert, PublishDetails, Version, VersionAction};
+use crate::schema::{crates, publish_details, version_owner_actions, versions};
+use crate::swirl::PerformError;
+use chrono::Utc;
+use diesel::dsl::exists;
+use diesel::prelude::*;
+
+/// Compares the details of a new version with the previous publishes of the
+/// crate, and alerts all owners by email if the publish looks suspicious.
+#[instrument(skip_all, fields(version.id = version_id))]
+pub fn perform_check_publish(
+    conn: &mut PgConnection,
+    env: &Environment,
+    version_id: i32,
+) -> Result<(), PerformError> {
+    let Some(details) = PublishDetails::find(conn, version_id)? else {
+        debug!("No publish details found for the version");
+        return Ok(());
+    };
+
+    let version: Version = versions::table.find(version_id).first(conn)?;
+    let krate: Crate = Crate::all()
+        .filter(crates::id.eq(version.crate_id))
+        .first(conn)?;
+
+    let reasons = detect_anomalies(conn, &version, &details)?;
+    if reasons.is_empty() {
+        debug!("Publish does not look suspicious");
+        return Ok(());
+    }
+
+    let Some(alert) = PublishAlert::create(conn, version_id, &reasons)? else {
+        debug!("Owners were already alerted about the publish");
+        return Ok(());
+    };
+
+    info!(reasons = ?alert.reasons, "Alerting owners about suspicious publish");
+
+    let now = Utc::now().naive_utc();
+    for owner in krate.owners(conn)? {
+        // The members of teams are only known to GitHub
+        let Owner::User(user) = owner else {
+            continue;
+        };
+
+        let Some(email) = user.verified_email(conn)? else {
+            continue;
+        };
+
+        let token = PublishAlert::yank_token(&env.link_signer, version_id, user.id, now);
+
+        // Failing to alert a single owner should not prevent alerting the others
+        if let Err(error) = env.emails.send_publish_alert(
+            &email,
+            &user.gh_login,
+            &krate.name,
+            &version.num,
+            &alert.reasons,
+            &token,
+        ) {
+            warn!(user.id, ?error, "Failed to send publish alert");
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the reasons why the publish looks suspicious, if any.
+fn detect_anomalies(
+    conn: &mut PgConnection,
+    version: &Version,
+    details: &PublishDetails,
+) -> QueryResult<Vec<String>> {
+    let previous_version_ids: Vec<i32> = versions::table
+        .filter(versions::crate_id.eq(version.crate_id))
+        .filter(versions::id.lt(version.id))
+        .select(versions::id)
+        .load(conn)?;
+
+    // There is nothing to compare the first publish of a crate with
+    if previous_version_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Versions that were published before the details were recorded are
+    // missing here, so these checks are skipped for them
+    let previous_details: Vec<PublishDetails> = publish_details::table
+        .filter(publish_details::version_id.eq_any(&previous_version_ids))
+        .order(publish_details::version_id.desc())
+        .select(PublishDetails::as_select())
+        .load(conn)?;
+
+    let mut reasons = Vec::new();
+
+    if let Some(api_token_id) = details.api_token_id {
+        let previous_publishes = version_owner_actions::table
+            .filter(version_owner_actions::version_id.eq_any(&previous_version_ids))
+            .filter(version_owner_actions::action.eq(VersionAction::Publish))
+            .filter(version_owner_actions::api_token_id.eq(api_token_id));
+
+        let used_before: bool = diesel::select(exists(previous_publishes)).get_result(conn)?;
+        if !used_before {
+            reasons.push("It is the first publish of the crate with this API token.".to_string());
+        }
+    }
+
+    if let Some(country) = &details.country {
+        let known_countries = previous_details
+            .iter()
+            .filter_map(|previous| previous.country.as_deref())
+            .collect::<Vec<_>>();
+
+        if !known_countries.is_empty() && !known_countries.contains(&country.as_str()) {
+            reasons.push(format!(
+                "It is the first publish of the crate from this country ({country})."
+            ));
+        }
+    }
+
+    if let Some(previous) = previous_details.first() {
+        if details.has_build_script && !previous.has_build_script {
+            reasons.push("A build script was added to the crate.".to_string());
+        }
+
+        let added_binary_files = details
+            .large_binary_files
+            .iter()
+            .filter(|path| !previous.large_binary_files.contains(path))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+
+        if !added_binary_files.is_empty() {
+            reasons.push(format!(
+                "Large binary files were added to the crate: {}",
+                added_binary_files.join(", ")
+            ));
+        }
+    }
+
+    Ok(reasons)
+}
@@ -2,7 +2,7 @@
 
 use crate::swirl::PerformError;
 use anyhow::Context;
-use crates_io_markdown::text_to_html;
+use crates_io_markdown::text_to_html_with_policy;
 use diesel::PgConnection;
 
 use crate::background_jobs::Environment;
@@ -23,7 +23,13 @@ pub fn perform_render_and_upload_readme(
 
     info!(?version_id, "Rendering README");
 
-    let rendered = text_to_html(text, readme_path, base_url, pkg_path_in_vcs);
+    let rendered = text_to_html_with_policy(
+        text,
+        readme_path,
+        base_url,
+        pkg_path_in_vcs,
+        &env.readme_policy,
+    );
     if rendered.is_empty() {
         return Ok(());
     }
@@ -0,0 +1,109 @@
+//! Recompress crate files with zstd.
+
+use crate::background_jobs::Environment;
+use crate::schema::{crates, version_objects, versions};
+use crate::swirl::PerformError;
+use anyhow::Context;
+use diesel::prelude::*;
+use flate2::read::GzDecoder;
+use hex::ToHex;
+use sha2::{Digest, Sha256};
+
+/// The zstd compression level used for the recompressed crate files.
+///
+/// The files are only compressed once, so a high compression level is used
+/// in exchange for smaller downloads.
+const ZSTD_LEVEL: i32 = 19;
+
+/// Stores a zstd-compressed copy of the crate file of the given version and
+/// saves its checksum in the `versions.checksum_zstd` column.
+#[instrument(skip_all, fields(krate.name))]
+pub fn perform_recompress_crate_file(
+    conn: &mut PgConnection,
+    env: &Environment,
+    version_id: i32,
+) -> Result<(), PerformError> {
+    info!(?version_id, "Recompressing crate file");
+
+    let version = versions::table
+        .find(version_id)
+        .inner_join(crates::table)
+        .filter(versions::staged_until.is_null())
+        .select((crates::name, versions::num))
+        .first::<(String, String)>(conn)
+        .optional()?;
+
+    let Some((crate_name, vers)) = version else {
+        warn!(?version_id, "Version not found or still staged, skipping recompression");
+        return Ok(());
+    };
+
+    tracing::Span::current().record("krate.name", tracing::field::display(&crate_name));
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to initialize tokio runtime")?;
+
+    let object_hash = version_objects::table
+        .find(version_id)
+        .select(version_objects::object_hash)
+        .first::<String>(conn)
+        .optional()?;
+
+    let future = async {
+        match &object_hash {
+            Some(hash) => env.storage.download_crate_object(hash).await,
+            None => env.storage.download_crate_file(&crate_name, &vers).await,
+        }
+    };
+    let gzip_bytes = rt
+        .block_on(future)
+        .context("Failed to download crate file")?;
+
+    let zstd_bytes = recompress(&gzip_bytes)?;
+    let checksum: String = Sha256::digest(&zstd_bytes).encode_hex();
+
+    let future = env
+        .storage
+        .upload_zstd_crate_file(&crate_name, &vers, zstd_bytes.into());
+    rt.block_on(future)
+        .context("Failed to upload zstd crate file")?;
+
+    diesel::update(versions::table.find(version_id))
+        .set(versions::checksum_zstd.eq(checksum))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Decompresses a gzip-compressed tarball and compresses it again with zstd.
+fn recompress(gzip_bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let decoder = GzDecoder::new(gzip_bytes);
+    zstd::encode_all(decoder, ZSTD_LEVEL).context("Failed to recompress crate file")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn recompress_roundtrip() {
+        let tarball = b"not really a tarball, but good enough".repeat(100);
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&tarball).unwrap();
+        let gzip_bytes = encoder.finish().unwrap();
+
+        let zstd_bytes = recompress(&gzip_bytes).unwrap();
+        assert_eq!(zstd::decode_all(&*zstd_bytes).unwrap(), tarball);
+    }
+
+    #[test]
+    fn recompress_invalid_gzip() {
+        assert!(recompress(b"definitely not gzip").is_err());
+    }
+}
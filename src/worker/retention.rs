@@ -0,0 +1,60 @@
+//! Purges data that is expired or no longer used, according to the
+//! retention periods of the [`RetentionConfig`].
+
+use crate::config::RetentionConfig;
+use crate::models::{
+    ApiToken, CrateOwnerInvitation, Email, RetentionStats, UserSession, WebauthnCeremony,
+};
+use crate::publish_rate_limit::purge_ip_buckets_before;
+use crate::swirl::PerformError;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+
+#[instrument(skip_all)]
+pub fn perform_purge_expired_data(
+    conn: &mut PgConnection,
+    retention: &RetentionConfig,
+) -> Result<(), PerformError> {
+    let cutoff = |days: u32| Utc::now().naive_utc() - Duration::days(days.into());
+
+    purge(conn, "ownership_invitations", |conn| {
+        let cutoff = cutoff(retention.ownership_invitations_days);
+        CrateOwnerInvitation::purge_created_before(conn, cutoff)
+    })?;
+
+    purge(conn, "revoked_tokens", |conn| {
+        ApiToken::purge_revoked_before(conn, cutoff(retention.revoked_tokens_days))
+    })?;
+
+    purge(conn, "stale_sessions", |conn| {
+        UserSession::purge_stale_before(conn, cutoff(retention.stale_sessions_days))
+    })?;
+
+    purge(conn, "consumed_email_tokens", |conn| {
+        let cutoff = cutoff(retention.consumed_email_tokens_days);
+        Email::clear_consumed_tokens_before(conn, cutoff)
+    })?;
+
+    // Unused rate limit buckets have been refilled completely by now
+    purge(conn, "ip_limit_buckets", |conn| {
+        purge_ip_buckets_before(conn, cutoff(1))
+    })?;
+
+    // Passkey ceremonies expire after a few minutes anyway
+    purge(conn, "webauthn_ceremonies", |conn| {
+        WebauthnCeremony::purge_created_before(conn, cutoff(1))
+    })?;
+
+    Ok(())
+}
+
+fn purge(
+    conn: &mut PgConnection,
+    kind: &str,
+    f: impl FnOnce(&mut PgConnection) -> QueryResult<usize>,
+) -> QueryResult<()> {
+    let purged = f(conn)?;
+    info!(kind, purged, "Purged expired data");
+
+    RetentionStats::record(conn, kind, purged)
+}
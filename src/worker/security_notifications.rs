@@ -0,0 +1,72 @@
+//! Emails users about the security relevant events concerning their accounts,
+//! see [`UserNotification`].
+
+use crate::background_jobs::Environment;
+use crate::models::UserNotification;
+use crate::schema::{emails, user_notifications};
+use crate::swirl::PerformError;
+use diesel::prelude::*;
+use std::collections::BTreeMap;
+
+/// Sends a single email to each user with pending notifications, summarizing
+/// them. These are low severity notifications, and high severity ones that
+/// could not be emailed immediately.
+#[instrument(skip_all)]
+pub fn perform_send_security_notification_digests(
+    env: &Environment,
+    conn: &mut PgConnection,
+) -> Result<(), PerformError> {
+    let notifications: Vec<(UserNotification, Option<String>)> = user_notifications::table
+        .left_join(
+            emails::table.on(emails::user_id
+                .eq(user_notifications::user_id)
+                .and(emails::verified)),
+        )
+        .filter(user_notifications::emailed_at.is_null())
+        .select((UserNotification::as_select(), emails::email.nullable()))
+        .order(user_notifications::id)
+        .load(conn)?;
+
+    if notifications.is_empty() {
+        debug!("No security notifications to send");
+        return Ok(());
+    }
+
+    let ids = notifications
+        .iter()
+        .map(|(notification, _)| notification.id)
+        .collect::<Vec<_>>();
+
+    let mut digests: BTreeMap<(i32, String), Vec<String>> = BTreeMap::new();
+    for (notification, email) in notifications {
+        let previous_email = notification.details["previous_email"].as_str();
+        let Some(email) = previous_email.map(String::from).or(email) else {
+            continue;
+        };
+
+        digests
+            .entry((notification.user_id, email))
+            .or_default()
+            .push(notification.message());
+    }
+
+    info!(
+        users = digests.len(),
+        "Sending security notification digests"
+    );
+
+    for ((user_id, email), messages) in digests {
+        // Failing to notify a single user should not cause the digest to be
+        // sent again to everyone else
+        if let Err(error) = env
+            .emails
+            .send_security_notification_digest(&email, &messages)
+        {
+            warn!(%user_id, ?error, "Failed to send security notification digest");
+        }
+    }
+
+    UserNotification::mark_emailed(conn, &ids)?;
+
+    Ok(())
+}
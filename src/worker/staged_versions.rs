@@ -0,0 +1,49 @@
+use crate::background_jobs::{Environment, Job};
+use crate::models::{insert_crate_notification, VersionAction};
+use crate::schema::{crates, versions};
+use crate::swirl::PerformError;
+use anyhow::Context;
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::prelude::*;
+
+/// Publishes all staged versions whose soak period is over, by moving their
+/// crate files to the public location and adding them to the index.
+#[instrument(skip_all)]
+pub fn perform_promote_staged_versions(
+    env: &Environment,
+    conn: &mut PgConnection,
+) -> Result<(), PerformError> {
+    let due_versions: Vec<(i32, String, String)> = versions::table
+        .inner_join(crates::table)
+        .filter(versions::staged_until.le(now))
+        .select((versions::id, crates::name, versions::num))
+        .load(conn)?;
+
+    if due_versions.is_empty() {
+        debug!("No staged versions to promote");
+        return Ok(());
+    }
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to initialize tokio runtime")?;
+
+    for (version_id, krate, num) in due_versions {
+        info!(%krate, %num, "Promoting staged version");
+
+        let future = env.storage.promote_staged_crate_file(&krate, &num);
+        rt.block_on(future)
+            .context("Failed to promote staged crate file")?;
+
+        diesel::update(versions::table.find(version_id))
+            .set(versions::staged_until.eq(None::<NaiveDateTime>))
+            .execute(conn)?;
+
+        insert_crate_notification(conn, version_id, VersionAction::Publish)?;
+        Job::enqueue_sync_to_index(&krate, conn)?;
+    }
+
+    Ok(())
+}
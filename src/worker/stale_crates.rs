@@ -0,0 +1,63 @@
+//! Flags crates whose owners have all been inactive for a long time, and asks
+//! the owners to confirm that the crates are still maintained.
+
+use crate::background_jobs::Environment;
+use crate::models::{Crate, CrateMaintenanceStatus, Owner};
+use crate::schema::crates;
+use crate::swirl::PerformError;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+
+#[instrument(skip(conn, env))]
+pub fn perform_flag_stale_crates(
+    conn: &mut PgConnection,
+    env: &Environment,
+    inactivity_days: u32,
+) -> Result<(), PerformError> {
+    let now = Utc::now().naive_utc();
+    let cutoff = now - Duration::days(inactivity_days.into());
+
+    let stale_crate_ids = CrateMaintenanceStatus::find_stale_crates(conn, cutoff)?;
+
+    let unflagged = CrateMaintenanceStatus::unflag_all_except(conn, &stale_crate_ids)?;
+    let newly_flagged = CrateMaintenanceStatus::flag(conn, &stale_crate_ids, now)?;
+
+    info!(
+        stale = stale_crate_ids.len(),
+        newly_flagged = newly_flagged.len(),
+        unflagged,
+        "Finished flagging stale crates"
+    );
+
+    for crate_id in newly_flagged {
+        let krate: Crate = Crate::all().filter(crates::id.eq(crate_id)).first(conn)?;
+
+        for owner in krate.owners(conn)? {
+            let Owner::User(user) = owner else {
+                continue;
+            };
+
+            let Some(email) = user.verified_email(conn)? else {
+                continue;
+            };
+
+            // Failing to notify a single owner should not prevent notifying the others
+            if let Err(error) = env.emails.send_maintenance_confirmation_request(
+                &email,
+                &user.gh_login,
+                &krate.name,
+                inactivity_days,
+            ) {
+                warn!(
+                    user.id,
+                    ?error,
+                    "Failed to send maintenance confirmation request"
+                );
+            }
+        }
+
+        CrateMaintenanceStatus::mark_notified(conn, crate_id, now)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+//! Reconciliation of the crate files in the replica buckets of other regions.
+
+use crate::background_jobs::Environment;
+use crate::swirl::PerformError;
+use anyhow::Context;
+
+/// Copies the crate files that are missing in the replica buckets, e.g.
+/// because a replicated write failed while publishing, and deletes the crate
+/// files that were removed from the default bucket in the meantime.
+#[instrument(skip_all)]
+pub fn perform_reconcile_storage_replicas(env: &Environment) -> Result<(), PerformError> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to initialize tokio runtime")?;
+
+    let reconciliation = rt
+        .block_on(env.storage.reconcile_replicas())
+        .context("Failed to reconcile storage replicas")?;
+
+    info!(
+        copied = reconciliation.copied,
+        deleted = reconciliation.deleted,
+        "Reconciled storage replicas"
+    );
+
+    Ok(())
+}
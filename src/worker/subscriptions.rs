@@ -0,0 +1,117 @@
+use crate::background_jobs::Environment;
+use crate::models::VersionAction;
+use crate::schema::{
+    advisories, advisory_notifications, crate_notifications, crate_subscriptions, crates, emails,
+    versions,
+};
+use crate::swirl::PerformError;
+use diesel::dsl::max;
+use diesel::prelude::*;
+use std::collections::BTreeMap;
+
+/// Sends a single email to each subscribed user, summarizing the new versions,
+/// yanks and advisories of their subscribed crates since the last digest.
+#[instrument(skip_all)]
+pub fn perform_send_crate_notification_digests(
+    env: &Environment,
+    conn: &mut PgConnection,
+) -> Result<(), PerformError> {
+    let last_id: Option<i32> = crate_notifications::table
+        .select(max(crate_notifications::id))
+        .get_result(conn)?;
+
+    let last_advisory_id: Option<i32> = advisory_notifications::table
+        .select(max(advisory_notifications::id))
+        .get_result(conn)?;
+
+    if last_id.is_none() && last_advisory_id.is_none() {
+        debug!("No crate notifications to send");
+        return Ok(());
+    }
+
+    let last_id = last_id.unwrap_or_default();
+    let last_advisory_id = last_advisory_id.unwrap_or_default();
+
+    let wants_notification = crate_notifications::action
+        .eq(VersionAction::Publish)
+        .and(crate_subscriptions::new_versions)
+        .or(crate_notifications::action
+            .eq(VersionAction::Yank)
+            .and(crate_subscriptions::yanks));
+
+    let notifications: Vec<(i32, String, String, String, VersionAction)> =
+        crate_notifications::table
+            .inner_join(versions::table.inner_join(crates::table))
+            .inner_join(
+                crate_subscriptions::table.on(crate_subscriptions::crate_id.eq(versions::crate_id)),
+            )
+            .inner_join(emails::table.on(emails::user_id.eq(crate_subscriptions::user_id)))
+            .filter(crate_notifications::id.le(last_id))
+            .filter(crate_subscriptions::created_at.le(crate_notifications::created_at))
+            .filter(emails::verified)
+            .filter(wants_notification)
+            .select((
+                crate_subscriptions::user_id,
+                emails::email,
+                crates::name,
+                versions::num,
+                crate_notifications::action,
+            ))
+            .order(crate_notifications::id)
+            .load(conn)?;
+
+    let advisory_updates: Vec<(i32, String, String, String, String)> =
+        advisory_notifications::table
+            .inner_join(advisories::table.inner_join(crates::table))
+            .inner_join(
+                crate_subscriptions::table
+                    .on(crate_subscriptions::crate_id.eq(advisories::crate_id)),
+            )
+            .inner_join(emails::table.on(emails::user_id.eq(crate_subscriptions::user_id)))
+            .filter(advisory_notifications::id.le(last_advisory_id))
+            .filter(crate_subscriptions::created_at.le(advisory_notifications::created_at))
+            .filter(emails::verified)
+            .filter(crate_subscriptions::advisories)
+            .select((
+                crate_subscriptions::user_id,
+                emails::email,
+                crates::name,
+                advisories::identifier,
+                advisories::affected,
+            ))
+            .order(advisory_notifications::id)
+            .load(conn)?;
+
+    let mut digests: BTreeMap<(i32, String), Vec<String>> = BTreeMap::new();
+    for (user_id, email, krate, num, action) in notifications {
+        let update = match action {
+            VersionAction::Yank => format!("{krate} {num} was yanked"),
+            _ => format!("{krate} {num} was published"),
+        };
+        digests.entry((user_id, email)).or_default().push(update);
+    }
+
+    for (user_id, email, krate, identifier, affected) in advisory_updates {
+        let update = format!("{krate} has the advisory {identifier} for the versions `{affected}`");
+        digests.entry((user_id, email)).or_default().push(update);
+    }
+
+    info!(users = digests.len(), "Sending crate notification digests");
+
+    for ((user_id, email), updates) in digests {
+        // Failing to notify a single user should not cause the digest to be
+        // sent again to everyone else
+        if let Err(error) = env.emails.send_crate_notification_digest(&email, &updates) {
+            warn!(%user_id, ?error, "Failed to send crate notification digest");
+        }
+    }
+
+    diesel::delete(crate_notifications::table.filter(crate_notifications::id.le(last_id)))
+        .execute(conn)?;
+    diesel::delete(
+        advisory_notifications::table.filter(advisory_notifications::id.le(last_advisory_id)),
+    )
+    .execute(conn)?;
+
+    Ok(())
+}
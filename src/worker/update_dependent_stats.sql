@@ -0,0 +1,34 @@
+-- Only the latest version of a dependent crate is considered, like for the
+-- reverse dependencies endpoint. Dev-dependencies are ignored, since they
+-- are not downloaded by the users of the dependent crate.
+WITH latest_versions AS (
+    SELECT DISTINCT ON (crate_id) id, crate_id
+    FROM versions
+    WHERE NOT yanked
+    ORDER BY crate_id, to_semver_no_prerelease(num) DESC NULLS LAST
+), dependents AS (
+    SELECT DISTINCT dependencies.crate_id, latest_versions.crate_id AS dependent_id
+    FROM dependencies
+    INNER JOIN latest_versions
+      ON latest_versions.id = dependencies.version_id
+    WHERE dependencies.kind <> 2
+      AND dependencies.crate_id <> latest_versions.crate_id
+), ranked AS (
+    SELECT dependents.crate_id, dependents.dependent_id, crates.downloads,
+      row_number() OVER (
+          PARTITION BY dependents.crate_id
+          ORDER BY crates.downloads DESC, crates.name
+      ) AS rank
+    FROM dependents
+    INNER JOIN crates
+      ON crates.id = dependents.dependent_id
+)
+INSERT INTO crate_dependent_stats (crate_id, dependent_id, downloads, share)
+-- Every download of a dependent causes a download of the crate, so the
+-- share is estimated from the downloads of both crates
+SELECT ranked.crate_id, ranked.dependent_id, ranked.downloads,
+  LEAST(ranked.downloads::DOUBLE PRECISION / GREATEST(crates.downloads, 1), 1)
+FROM ranked
+INNER JOIN crates
+  ON crates.id = ranked.crate_id
+WHERE ranked.rank <= $1
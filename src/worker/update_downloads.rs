@@ -111,6 +111,7 @@ mod test {
             "0000000000000000000000000000000000000000000000000000000000000000".to_string(),
             None,
             None,
+            None,
         )
         .unwrap();
         let version = version.save(conn, "someone@example.com").unwrap();
@@ -0,0 +1,5 @@
+UPDATE crates
+SET health_score = data.score
+FROM unnest($1::int[], $2::int[]) AS data(id, score)
+WHERE crates.id = data.id
+  AND crates.health_score IS DISTINCT FROM data.score
@@ -0,0 +1,27 @@
+-- Downloads of today and yesterday are recomputed on every run, since
+-- yesterday's counts may still have changed after the previous run
+WITH downloads AS (
+    SELECT crates_keywords.keyword_id, version_downloads.date,
+      SUM(version_downloads.downloads) AS downloads
+    FROM version_downloads
+    INNER JOIN versions
+      ON versions.id = version_downloads.version_id
+    INNER JOIN crates_keywords
+      ON crates_keywords.crate_id = versions.crate_id
+    WHERE version_downloads.date >= CURRENT_DATE - 1
+    GROUP BY crates_keywords.keyword_id, version_downloads.date
+)
+INSERT INTO keyword_stats (keyword_id, date, crates_cnt, downloads)
+SELECT keywords.id, dates.date, keywords.crates_cnt, COALESCE(downloads.downloads, 0)
+FROM keywords
+CROSS JOIN (VALUES (CURRENT_DATE - 1), (CURRENT_DATE)) AS dates (date)
+LEFT JOIN downloads
+  ON downloads.keyword_id = keywords.id
+ AND downloads.date = dates.date
+ON CONFLICT (keyword_id, date) DO UPDATE
+SET downloads = EXCLUDED.downloads,
+    -- The number of crates is a snapshot, so it is only updated for today
+    crates_cnt = CASE
+        WHEN EXCLUDED.date = CURRENT_DATE THEN EXCLUDED.crates_cnt
+        ELSE keyword_stats.crates_cnt
+    END
@@ -0,0 +1,348 @@
+//! Export and deletion of the personal data of users.
+
+use crate::background_jobs::Environment;
+use crate::models::{
+    AccountDeletion, ApiToken, AuditLogEntry, OwnerKind, User, UserDataExport, UserSession,
+    VersionAction,
+};
+use crate::schema::*;
+use crate::swirl::PerformError;
+use crate::util::rfc3339;
+use anyhow::Context;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde_json::Value;
+
+/// The lock reason of anonymized accounts. Locking the account invalidates all
+/// existing sessions of the user.
+const DELETED_ACCOUNT_LOCK_REASON: &str = "This account has been deleted.";
+
+/// Bundles the personal data of the user into a `.tar.gz` archive of JSON
+/// files, and uploads it to the file storage.
+#[instrument(skip_all, fields(user.id))]
+pub fn perform_export_user_data(
+    conn: &mut PgConnection,
+    env: &Environment,
+    export_id: i32,
+) -> Result<(), PerformError> {
+    let export: UserDataExport = user_data_exports::table.find(export_id).first(conn)?;
+    tracing::Span::current().record("user.id", export.user_id);
+
+    if export.is_completed() {
+        debug!(?export_id, "User data export was already completed");
+        return Ok(());
+    }
+
+    info!(?export_id, "Exporting user data");
+
+    let files = collect_user_data(conn, export.user_id)?;
+    let archive = build_archive(&files).context("Failed to build user data archive")?;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to initialize tokio runtime")?;
+
+    let future = env
+        .storage
+        .upload_user_export(export.user_id, export.id, archive.into());
+    rt.block_on(future)
+        .context("Failed to upload user data archive")?;
+
+    export.mark_completed(conn)?;
+
+    Ok(())
+}
+
+/// Collects the personal data of the user as a list of JSON files.
+fn collect_user_data(
+    conn: &mut PgConnection,
+    user_id: i32,
+) -> QueryResult<Vec<(&'static str, Value)>> {
+    let user: User = users::table.find(user_id).first(conn)?;
+    let profile = json!({
+        "id": user.id,
+        "login": user.gh_login,
+        "name": user.name,
+        "avatar": user.gh_avatar,
+        "github_id": user.gh_id,
+    });
+
+    let emails: Vec<(String, bool)> = emails::table
+        .filter(emails::user_id.eq(user_id))
+        .select((emails::email, emails::verified))
+        .load(conn)?;
+    let emails = emails
+        .into_iter()
+        .map(|(email, verified)| json!({ "email": email, "verified": verified }))
+        .collect::<Vec<_>>();
+
+    let identities: Vec<(String, String, NaiveDateTime)> = oauth_identities::table
+        .filter(oauth_identities::user_id.eq(user_id))
+        .select((
+            oauth_identities::provider,
+            oauth_identities::login,
+            oauth_identities::created_at,
+        ))
+        .order(oauth_identities::provider)
+        .load(conn)?;
+    let identities = identities
+        .into_iter()
+        .map(|(provider, login, created_at)| {
+            json!({ "provider": provider, "login": login, "created_at": timestamp(created_at) })
+        })
+        .collect::<Vec<_>>();
+
+    // Only the token metadata is exported, the token hashes are of no use to the user
+    let tokens: Vec<ApiToken> = api_tokens::table
+        .filter(api_tokens::user_id.eq(user_id))
+        .select(ApiToken::as_select())
+        .order(api_tokens::id)
+        .load(conn)?;
+    let tokens = tokens
+        .into_iter()
+        .map(|token| {
+            let revoked = token.revoked;
+            let mut value = serde_json::to_value(token).unwrap_or_default();
+            value["revoked"] = revoked.into();
+            value
+        })
+        .collect::<Vec<_>>();
+
+    let actions: Vec<(String, String, VersionAction, Option<i32>, NaiveDateTime)> =
+        version_owner_actions::table
+            .inner_join(versions::table.inner_join(crates::table))
+            .filter(version_owner_actions::user_id.eq(user_id))
+            .select((
+                crates::name,
+                versions::num,
+                version_owner_actions::action,
+                version_owner_actions::api_token_id,
+                version_owner_actions::time,
+            ))
+            .order(version_owner_actions::id)
+            .load(conn)?;
+    let actions = actions
+        .into_iter()
+        .map(|(krate, version, action, api_token_id, time)| {
+            let action: &'static str = action.into();
+            json!({
+                "crate": krate,
+                "version": version,
+                "action": action,
+                "api_token_id": api_token_id,
+                "time": timestamp(time),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // The email address that was verified at the time of each publish
+    let publishes: Vec<(String, String, String)> = versions_published_by::table
+        .inner_join(versions::table.inner_join(crates::table))
+        .filter(versions::published_by.eq(user_id))
+        .select((crates::name, versions::num, versions_published_by::email))
+        .order(versions::id)
+        .load(conn)?;
+    let publishes = publishes
+        .into_iter()
+        .map(
+            |(krate, version, email)| json!({ "crate": krate, "version": version, "email": email }),
+        )
+        .collect::<Vec<_>>();
+
+    let audit_log = AuditLogEntry::for_user(conn, user_id)?
+        .into_iter()
+        .map(|entry| {
+            json!({
+                "action": entry.action,
+                "api_token_id": entry.api_token_id,
+                "ip_address": entry.ip_address,
+                "details": entry.details,
+                "time": timestamp(entry.created_at),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let ownerships: Vec<(String, NaiveDateTime, bool)> = crate_owners::table
+        .inner_join(crates::table)
+        .filter(crate_owners::owner_id.eq(user_id))
+        .filter(crate_owners::owner_kind.eq(OwnerKind::User as i32))
+        .filter(crate_owners::deleted.eq(false))
+        .select((
+            crates::name,
+            crate_owners::created_at,
+            crate_owners::email_notifications,
+        ))
+        .order(crates::name)
+        .load(conn)?;
+    let ownerships = ownerships
+        .into_iter()
+        .map(|(krate, created_at, email_notifications)| {
+            json!({
+                "crate": krate,
+                "created_at": timestamp(created_at),
+                "email_notifications": email_notifications,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let follows: Vec<String> = follows::table
+        .inner_join(crates::table)
+        .filter(follows::user_id.eq(user_id))
+        .select(crates::name)
+        .order(crates::name)
+        .load(conn)?;
+
+    Ok(vec![
+        ("profile.json", profile),
+        ("emails.json", emails.into()),
+        ("identities.json", identities.into()),
+        ("api_tokens.json", tokens.into()),
+        ("audit_events.json", actions.into()),
+        ("publish_emails.json", publishes.into()),
+        ("audit_log.json", audit_log.into()),
+        ("crate_ownerships.json", ownerships.into()),
+        ("follows.json", follows.into()),
+    ])
+}
+
+fn timestamp(time: NaiveDateTime) -> Value {
+    rfc3339::serialize(&time, serde_json::value::Serializer).unwrap_or_default()
+}
+
+fn build_archive(files: &[(&str, Value)]) -> anyhow::Result<Vec<u8>> {
+    let encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let mut archive = tar::Builder::new(encoder);
+
+    let mtime = chrono::Utc::now().timestamp() as u64;
+    for (name, value) in files {
+        let content = serde_json::to_vec_pretty(value)?;
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        header.set_cksum();
+
+        let path = format!("crates-io-export/{name}");
+        archive.append_data(&mut header, path, content.as_slice())?;
+    }
+
+    Ok(archive.into_inner()?.finish()?)
+}
+
+/// Anonymizes all accounts whose confirmed deletion is due.
+///
+/// The user rows are preserved, so that the crate ownership and publishing
+/// history stays intact, but all personal data is removed from them.
+#[instrument(skip_all)]
+pub fn perform_purge_deleted_accounts(
+    conn: &mut PgConnection,
+    env: &Environment,
+) -> Result<(), PerformError> {
+    let deletions = AccountDeletion::due(conn)?;
+    if deletions.is_empty() {
+        debug!("No account deletions are due");
+        return Ok(());
+    }
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to initialize tokio runtime")?;
+
+    for deletion in deletions {
+        let user_id = deletion.user_id;
+        info!(%user_id, "Anonymizing deleted account");
+
+        conn.transaction(|conn| {
+            anonymize_user(conn, user_id)?;
+            deletion.mark_completed(conn)
+        })?;
+
+        let future = env.storage.delete_all_user_exports(user_id);
+        if let Err(error) = rt.block_on(future) {
+            warn!(%user_id, ?error, "Failed to delete user data exports");
+        }
+    }
+
+    Ok(())
+}
+
+fn anonymize_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<()> {
+    // A `gh_id` of `-1` is excluded from the unique index, so that the GitHub
+    // account can be used to sign up again, which creates a new account.
+    diesel::update(users::table.find(user_id))
+        .set((
+            users::gh_login.eq(format!("deleted-user-{user_id}")),
+            users::gh_id.eq(-1),
+            users::gh_access_token.eq(""),
+            users::name.eq(None::<String>),
+            users::gh_avatar.eq(None::<String>),
+            users::account_lock_reason.eq(DELETED_ACCOUNT_LOCK_REASON),
+            users::account_lock_until.eq(None::<NaiveDateTime>),
+        ))
+        .execute(conn)?;
+
+    diesel::delete(emails::table.filter(emails::user_id.eq(user_id))).execute(conn)?;
+    diesel::delete(oauth_identities::table.filter(oauth_identities::user_id.eq(user_id)))
+        .execute(conn)?;
+    diesel::delete(user_passwords::table.find(user_id)).execute(conn)?;
+    diesel::delete(user_passkeys::table.filter(user_passkeys::user_id.eq(user_id)))
+        .execute(conn)?;
+    diesel::delete(password_resets::table.filter(password_resets::user_id.eq(user_id)))
+        .execute(conn)?;
+    UserSession::revoke_all_for_user(conn, user_id, None)?;
+
+    // The versions keep their publisher, but not the email address of the
+    // publisher at the time of the publish
+    let published_versions = versions::table
+        .filter(versions::published_by.eq(user_id))
+        .select(versions::id);
+    diesel::delete(
+        versions_published_by::table
+            .filter(versions_published_by::version_id.eq_any(published_versions)),
+    )
+    .execute(conn)?;
+
+    // The audit log is kept, but without the IP addresses of the user
+    diesel::update(audit_log::table.filter(audit_log::user_id.eq(user_id)))
+        .set(audit_log::ip_address.eq(None::<String>))
+        .execute(conn)?;
+
+    ApiToken::revoke_all_for_user(conn, user_id)?;
+
+    let tokens = api_tokens::table
+        .filter(api_tokens::user_id.eq(user_id))
+        .select(api_tokens::id);
+    diesel::delete(api_token_origins::table.filter(api_token_origins::api_token_id.eq_any(tokens)))
+        .execute(conn)?;
+    diesel::delete(user_notifications::table.filter(user_notifications::user_id.eq(user_id)))
+        .execute(conn)?;
+
+    diesel::delete(follows::table.filter(follows::user_id.eq(user_id))).execute(conn)?;
+    diesel::delete(crate_subscriptions::table.filter(crate_subscriptions::user_id.eq(user_id)))
+        .execute(conn)?;
+    diesel::delete(
+        crate_owner_invitations::table.filter(crate_owner_invitations::invited_user_id.eq(user_id)),
+    )
+    .execute(conn)?;
+    diesel::delete(
+        publisher_verifications::table.filter(publisher_verifications::user_id.eq(user_id)),
+    )
+    .execute(conn)?;
+    diesel::delete(user_data_exports::table.filter(user_data_exports::user_id.eq(user_id)))
+        .execute(conn)?;
+
+    diesel::update(
+        crate_owners::table
+            .filter(crate_owners::owner_id.eq(user_id))
+            .filter(crate_owners::owner_kind.eq(OwnerKind::User as i32)),
+    )
+    .set(crate_owners::email_notifications.eq(false))
+    .execute(conn)?;
+
+    Ok(())
+}
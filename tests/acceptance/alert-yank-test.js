@@ -0,0 +1,38 @@
+import { click, currentURL } from '@ember/test-helpers';
+import { module, test } from 'qunit';
+
+import { setupApplicationTest } from 'cargo/tests/helpers';
+
+import { visit } from '../helpers/visit-ignoring-abort';
+
+module('Acceptance | /crates/:crate_id/:version_num/alert-yank/:token', function (hooks) {
+  setupApplicationTest(hooks);
+
+  test('yanks the version once confirmed', async function (assert) {
+    let crate = this.server.create('crate', { name: 'foo' });
+    let version = this.server.create('version', { crate, num: '1.0.1' });
+
+    // This is the link from the publish alert email
+    await visit('/crates/foo/1.0.1/alert-yank/secret123');
+    assert.strictEqual(currentURL(), '/crates/foo/1.0.1/alert-yank/secret123');
+    assert.dom('h1').hasText('Yank foo 1.0.1?');
+    assert.dom('[data-test-confirm-message]').exists();
+    assert.false(version.reload().yanked);
+
+    await click('[data-test-yank-button]');
+    assert.dom('h1').hasText('foo 1.0.1 has been yanked');
+    assert.dom('[data-test-success-message]').exists();
+    assert.true(version.reload().yanked);
+  });
+
+  test('shows error for invalid links', async function (assert) {
+    let errorMessage = 'The yank link is invalid or has expired.';
+    let payload = { errors: [{ detail: errorMessage }] };
+    this.server.put('/api/v1/crates/:name/:version/alert_yank/:token', payload, 400);
+
+    await visit('/crates/foo/1.0.1/alert-yank/expired');
+    await click('[data-test-yank-button]');
+    assert.dom('[data-test-error-message]').hasText(errorMessage);
+    assert.dom('[data-test-success-message]').doesNotExist();
+  });
+});